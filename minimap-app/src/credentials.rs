@@ -0,0 +1,147 @@
+//! Asking the user for the credentials git remotes need.
+//!
+//! When a [`GitRemote`](minimap_core::GitRemote) needs a passphrase, a
+//! password or a host key confirmation, the [`CredentialBroker`] emits a
+//! [`CREDENTIAL_REQUESTED`] event and blocks the operation until the
+//! frontend answers it with `credential_respond` (or gives up after a
+//! while). Commands that may end up asking must therefore not run on the
+//! main thread, or the answer could never come in.
+
+use crate::Result;
+use minimap_core::CredentialProvider;
+use std::{
+	collections::HashMap,
+	path::Path,
+	sync::{
+		atomic::{AtomicU64, Ordering},
+		mpsc, Arc, Mutex,
+	},
+	time::Duration,
+};
+use tauri::{AppHandle, Manager, State};
+
+/// Emitted when credentials are needed, with a [`CredentialRequest`]
+/// payload.
+pub(crate) const CREDENTIAL_REQUESTED: &str = "minimap://credential-requested";
+
+/// How long to wait for an answer before giving up.
+const TIMEOUT: Duration = Duration::from_secs(5 * 60);
+
+#[derive(Clone, serde::Serialize)]
+#[serde(tag = "kind", rename_all = "kebab-case")]
+enum Request {
+	Passphrase {
+		url: String,
+		key: String,
+	},
+	UserPass {
+		url: String,
+		username: Option<String>,
+	},
+	HostKey {
+		host: String,
+		fingerprint: String,
+	},
+}
+
+#[derive(Clone, serde::Serialize)]
+struct CredentialRequest {
+	/// Identifies the request, to respond to it.
+	id: u64,
+	#[serde(flatten)]
+	request: Request,
+}
+
+/// An answer to a [`CredentialRequest`], of the same kind.
+#[derive(serde::Deserialize)]
+#[serde(tag = "kind", rename_all = "kebab-case")]
+pub(crate) enum CredentialResponse {
+	Passphrase { passphrase: String },
+	UserPass { username: String, password: String },
+	HostKey { trust: bool },
+}
+
+/// Asks the frontend for credentials on behalf of git remotes.
+pub(crate) struct CredentialBroker {
+	app: AppHandle,
+	pending: Mutex<HashMap<u64, mpsc::Sender<Option<CredentialResponse>>>>,
+	next_id: AtomicU64,
+}
+
+impl CredentialBroker {
+	pub(crate) fn new(app: AppHandle) -> Self {
+		Self {
+			app,
+			pending: Mutex::default(),
+			next_id: AtomicU64::new(0),
+		}
+	}
+
+	/// Asks for credentials, returning `None` if the frontend declined or
+	/// didn't answer in time.
+	fn ask(&self, request: Request) -> Option<CredentialResponse> {
+		let id = self.next_id.fetch_add(1, Ordering::Relaxed);
+		let (sender, receiver) = mpsc::channel();
+		self.pending.lock().unwrap().insert(id, sender);
+
+		let response = self
+			.app
+			.emit_all(CREDENTIAL_REQUESTED, CredentialRequest { id, request })
+			.ok()
+			.and_then(|_| receiver.recv_timeout(TIMEOUT).ok())
+			.flatten();
+
+		self.pending.lock().unwrap().remove(&id);
+		response
+	}
+
+	/// Answers a request; `None` declines it. Returns `false` if the
+	/// request was already answered or given up on.
+	fn respond(&self, id: u64, response: Option<CredentialResponse>) -> bool {
+		match self.pending.lock().unwrap().remove(&id) {
+			Some(sender) => sender.send(response).is_ok(),
+			None => false,
+		}
+	}
+}
+
+impl CredentialProvider for CredentialBroker {
+	fn passphrase(&self, url: &str, key: &Path) -> Option<String> {
+		match self.ask(Request::Passphrase {
+			url: url.to_string(),
+			key: key.display().to_string(),
+		})? {
+			CredentialResponse::Passphrase { passphrase } => Some(passphrase),
+			_ => None,
+		}
+	}
+
+	fn user_pass(&self, url: &str, username: Option<&str>) -> Option<(String, String)> {
+		match self.ask(Request::UserPass {
+			url: url.to_string(),
+			username: username.map(str::to_string),
+		})? {
+			CredentialResponse::UserPass { username, password } => Some((username, password)),
+			_ => None,
+		}
+	}
+
+	fn confirm_host_key(&self, host: &str, fingerprint: &str) -> bool {
+		matches!(
+			self.ask(Request::HostKey {
+				host: host.to_string(),
+				fingerprint: fingerprint.to_string(),
+			}),
+			Some(CredentialResponse::HostKey { trust: true })
+		)
+	}
+}
+
+#[tauri::command]
+pub(crate) fn credential_respond(
+	broker: State<Arc<CredentialBroker>>,
+	id: u64,
+	response: Option<CredentialResponse>,
+) -> Result<bool> {
+	Ok(broker.respond(id, response))
+}
@@ -0,0 +1,101 @@
+//! Opening `minimap://` links to tickets.
+//!
+//! Links (see [`TicketLink`]) reach the app as a command line argument when
+//! the OS launches it to open one; on Linux, the desktop entry registers
+//! the scheme (see `minimap.desktop`). The frontend takes the link the app
+//! was launched with, if any, with `deep_link_take`, and opens links with
+//! `deep_link_open`, which opens the ticket's workspace (or reuses it if
+//! it's open already) and resolves to the ticket to navigate to.
+//!
+//! Links can come from anywhere, so the frontend checks their remote with
+//! `deep_link_parse` first, and asks before opening one it doesn't know.
+
+use crate::{
+	git_workspace_open, run_blocking, session::Session, CredentialBroker, Error,
+	GitWorkspaceRegistry, Result, WorkspaceKey,
+};
+use minimap_core::{TicketLink, LINK_SCHEME};
+use std::sync::{Arc, Mutex};
+use tauri::{AppHandle, State, Window};
+
+/// The link the app was launched with, until the frontend takes it.
+pub(crate) struct LaunchLink(Mutex<Option<String>>);
+
+impl LaunchLink {
+	pub(crate) fn from_args() -> Self {
+		let prefix = format!("{}://", LINK_SCHEME);
+		let link = std::env::args()
+			.skip(1)
+			.find(|arg| arg.starts_with(&prefix));
+		Self(Mutex::new(link))
+	}
+}
+
+/// A ticket opened from a link, as returned by `deep_link_open`.
+#[derive(serde::Serialize)]
+pub(crate) struct OpenedLink {
+	remote: String,
+	workspace: WorkspaceKey,
+	ticket: String,
+}
+
+/// A link's remote and ticket, as returned by `deep_link_parse`.
+#[derive(serde::Serialize)]
+pub(crate) struct ParsedLink {
+	remote: String,
+	ticket: String,
+}
+
+/// Takes the link the app was launched with, if any; it's only returned
+/// once.
+#[tauri::command]
+pub(crate) fn deep_link_take(link: State<LaunchLink>) -> Result<Option<String>> {
+	Ok(link.0.lock().unwrap().take())
+}
+
+/// Parses a link without opening anything.
+#[tauri::command]
+pub(crate) fn deep_link_parse(link: String) -> Result<ParsedLink> {
+	let TicketLink { remote, ticket } = link.parse()?;
+	Ok(ParsedLink { remote, ticket })
+}
+
+/// Opens the workspace of a linked ticket, and shows it in the window.
+#[tauri::command]
+pub(crate) async fn deep_link_open(
+	app: AppHandle,
+	window: Window,
+	workspace_registry: State<'_, GitWorkspaceRegistry<'static>>,
+	credential_broker: State<'_, Arc<CredentialBroker>>,
+	session: State<'_, Session>,
+	link: String,
+) -> Result<OpenedLink> {
+	let TicketLink { remote, ticket } = link.parse()?;
+	let workspace = git_workspace_open(
+		app,
+		window,
+		workspace_registry.clone(),
+		credential_broker,
+		session,
+		remote.clone(),
+	)
+	.await?;
+
+	let workspace_mutex = workspace_registry
+		.lock()
+		.unwrap()
+		.get(workspace)
+		.cloned()
+		.ok_or(Error::NoSuchWorkspace(workspace))?;
+	// Fails early for tickets that don't exist (anymore).
+	let ticket = run_blocking(workspace_mutex, move |workspace| {
+		Ok(workspace.ticket(&ticket)?.slug().to_string())
+	})
+	.await?;
+
+	Ok(OpenedLink {
+		remote,
+		workspace,
+		ticket,
+	})
+}
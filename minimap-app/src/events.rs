@@ -0,0 +1,108 @@
+//! Change events pushed to the frontend.
+//!
+//! Records committed to a git workspace, here or elsewhere, are turned into
+//! [`TICKET_UPDATED`] and [`PROJECT_UPDATED`] events (see [`crate::sync`]
+//! for how they're collected), so that views can refresh themselves rather
+//! than query again on every navigation.
+
+use crate::{GitWorkspace, WorkspaceKey};
+use std::collections::BTreeSet;
+use tauri::{AppHandle, Manager};
+
+/// Emitted when a ticket changes, with a [`TicketUpdated`] payload.
+pub(crate) const TICKET_UPDATED: &str = "minimap://ticket-updated";
+/// Emitted when a project changes, including when tickets are created in
+/// or deleted from it, with a [`ProjectUpdated`] payload.
+pub(crate) const PROJECT_UPDATED: &str = "minimap://project-updated";
+
+#[derive(Clone, serde::Serialize)]
+struct TicketUpdated {
+	workspace: WorkspaceKey,
+	ticket: String,
+}
+
+#[derive(Clone, serde::Serialize)]
+struct ProjectUpdated {
+	workspace: WorkspaceKey,
+	project: String,
+}
+
+/// A record written to a workspace, as its collection and message.
+pub(crate) type Change = (String, String);
+
+/// What changed.
+#[derive(PartialEq, Eq, PartialOrd, Ord)]
+enum Subject {
+	Project(String),
+	Ticket(String),
+}
+
+/// Emits an event per project and ticket changed, once each.
+pub(crate) fn emit(
+	app: &AppHandle,
+	key: WorkspaceKey,
+	workspace: &GitWorkspace,
+	changes: &[Change],
+) {
+	let subjects = changes
+		.iter()
+		.flat_map(|(collection, message)| subjects(workspace, collection, message))
+		.collect::<BTreeSet<_>>();
+
+	for subject in subjects {
+		// Emitting only fails if the app is shutting down.
+		let _ = match subject {
+			Subject::Project(project) => app.emit_all(
+				PROJECT_UPDATED,
+				ProjectUpdated {
+					workspace: key,
+					project,
+				},
+			),
+			Subject::Ticket(ticket) => app.emit_all(
+				TICKET_UPDATED,
+				TicketUpdated {
+					workspace: key,
+					ticket,
+				},
+			),
+		};
+	}
+}
+
+/// Finds what a change is about, from its collection (and its message,
+/// for the sets of projects and of a project's tickets).
+fn subjects(workspace: &GitWorkspace, collection: &str, message: &str) -> Vec<Subject> {
+	if collection == "meta/projects" {
+		return vec![Subject::Project(message.to_string())];
+	}
+
+	let path = collection.strip_prefix("meta/").unwrap_or(collection);
+	let Some(rest) = path.strip_prefix("project/") else {
+		return vec![];
+	};
+
+	let mut parts = rest.split('/');
+	let project = parts.next().unwrap_or_default();
+	let subject = Subject::Project(project.to_string());
+	match (parts.next(), parts.next()) {
+		(Some("tickets"), None) => ticket_subject(workspace, project, message)
+			.into_iter()
+			.chain([subject])
+			.collect(),
+		(Some("ticket"), Some(id)) => ticket_subject(workspace, project, id).into_iter().collect(),
+		_ => vec![subject],
+	}
+}
+
+fn ticket_subject(workspace: &GitWorkspace, project: &str, id: &str) -> Option<Subject> {
+	let id = id.parse::<u64>().ok()?;
+	let slug = workspace
+		.project(project)
+		.and_then(|project| project.ticket(id))
+		.map(|ticket| ticket.slug().to_string())
+		// Deleted tickets (and those of deleted projects) can't be looked up.
+		.unwrap_or_else(|_| format!("{}-{}", project, id));
+
+	Some(Subject::Ticket(slug))
+}
@@ -0,0 +1,223 @@
+//! Exporting workspaces as documents.
+//!
+//! `*_workspace_export` writes every ticket of a workspace, along with its
+//! comments, as JSON or markdown, laid out like the CLI's `minimap export`
+//! does. Taking a [`TicketView`] of each ticket is what takes time in large
+//! workspaces, so [`EXPORT_PROGRESS`] events report how many are done.
+
+use crate::{Result, WorkspaceKey};
+use minimap_core::{Record, Remote, TicketView, Workspace};
+use std::io::Write;
+use tauri::{AppHandle, Manager};
+
+/// Emitted while exporting, with an [`ExportProgress`] payload.
+pub(crate) const EXPORT_PROGRESS: &str = "minimap://export-progress";
+
+/// How many tickets are exported between progress events.
+const PROGRESS_STEP: usize = 25;
+
+/// The formats workspaces can be exported to.
+#[derive(Clone, Copy, PartialEq, Eq, serde::Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub(crate) enum ExportFormat {
+	Json,
+	Markdown,
+}
+
+#[derive(Clone, serde::Serialize)]
+struct ExportProgress {
+	workspace: WorkspaceKey,
+	/// The number of tickets exported so far, out of `total`.
+	done: usize,
+	total: usize,
+}
+
+#[derive(serde::Serialize)]
+struct ExportedProject {
+	slug: String,
+	name: Option<String>,
+	description: Option<String>,
+	tickets: Vec<TicketView>,
+}
+
+/// Exports a workspace into a file, replacing it if it exists. Returns the
+/// number of tickets exported.
+pub(crate) fn export<'a, R: Remote<'a>>(
+	app: &AppHandle,
+	key: WorkspaceKey,
+	workspace: &'a Workspace<'a, R>,
+	format: ExportFormat,
+	dest_path: &str,
+) -> Result<usize> {
+	let projects = workspace.projects_with_meta()?;
+	let mut tickets = vec![];
+	for meta in &projects {
+		tickets.push(meta.project.tickets()?);
+	}
+	let total = tickets.iter().map(Vec::len).sum();
+
+	let progress = |done| {
+		// Emitting only fails if the app is shutting down.
+		let _ = app.emit_all(
+			EXPORT_PROGRESS,
+			ExportProgress {
+				workspace: key,
+				done,
+				total,
+			},
+		);
+	};
+	progress(0);
+
+	let mut exported = vec![];
+	let mut done = 0;
+	for (meta, tickets) in projects.into_iter().zip(tickets) {
+		let mut views = vec![];
+		for ticket in tickets {
+			views.push(ticket.view()?);
+			done += 1;
+			if done % PROGRESS_STEP == 0 && done < total {
+				progress(done);
+			}
+		}
+		exported.push(ExportedProject {
+			slug: meta.project.slug().to_string(),
+			name: meta.name.map(|r| r.message()),
+			description: meta.description.map(|r| r.message()),
+			tickets: views,
+		});
+	}
+
+	let file = std::fs::File::create(dest_path).map_err(minimap_core::Error::Io)?;
+	let mut writer = std::io::BufWriter::new(file);
+	let name = workspace.name()?.map(|r| r.message());
+	let description = workspace.description()?.map(|r| r.message());
+	match format {
+		ExportFormat::Json => {
+			let document = serde_json::json!({
+				"name": name,
+				"description": description,
+				"projects": exported,
+			});
+			serde_json::to_writer_pretty(&mut writer, &document)?;
+			writeln!(writer).map_err(minimap_core::Error::Io)?;
+		}
+		ExportFormat::Markdown => {
+			write_markdown(&mut writer, name, description, &exported)
+				.map_err(minimap_core::Error::Io)?;
+		}
+	}
+	writer.flush().map_err(minimap_core::Error::Io)?;
+
+	progress(total);
+	Ok(total)
+}
+
+/// Writes projects as markdown, under a heading for the workspace.
+fn write_markdown(
+	writer: &mut dyn Write,
+	name: Option<String>,
+	description: Option<String>,
+	projects: &[ExportedProject],
+) -> std::io::Result<()> {
+	writeln!(writer, "# {}", name.as_deref().unwrap_or("Workspace"))?;
+	if let Some(description) = description {
+		writeln!(writer, "\n{}", description.trim_end())?;
+	}
+	writeln!(writer)?;
+
+	for project in projects {
+		match &project.name {
+			Some(name) => writeln!(writer, "## {}: {}\n", project.slug, name)?,
+			None => writeln!(writer, "## {}\n", project.slug)?,
+		}
+		if let Some(description) = &project.description {
+			writeln!(writer, "{}\n", description.trim_end())?;
+		}
+
+		for ticket in &project.tickets {
+			writeln!(
+				writer,
+				"### {}: {}\n",
+				ticket.slug,
+				ticket.title.as_deref().unwrap_or("(untitled)")
+			)?;
+
+			match &ticket.state_reason {
+				Some(reason) => writeln!(
+					writer,
+					"- **State:** {} ({})",
+					ticket.state.to_string(),
+					reason
+				)?,
+				None => writeln!(writer, "- **State:** {}", ticket.state.to_string())?,
+			}
+			if !ticket.labels.is_empty() {
+				writeln!(writer, "- **Labels:** {}", ticket.labels.join(", "))?;
+			}
+			if !ticket.assignees.is_empty() {
+				writeln!(writer, "- **Assignees:** {}", ticket.assignees.join(", "))?;
+			}
+			for dependency in &ticket.dependencies {
+				writeln!(
+					writer,
+					"- **Depends on:** {}@{} ({})",
+					dependency.origin,
+					dependency.endpoint,
+					dependency.kind.to_string()
+				)?;
+			}
+			if !ticket.attachments.is_empty() {
+				writeln!(
+					writer,
+					"- **Attachments:** {}",
+					ticket.attachments.join(", ")
+				)?;
+			}
+			writeln!(writer)?;
+
+			if let Some(description) = &ticket.description {
+				writeln!(writer, "{}\n", description.trim_end())?;
+			}
+
+			// Oldest first, as they would be read.
+			for comment in ticket.comments.iter().rev() {
+				writeln!(
+					writer,
+					"**{}** on {}:\n",
+					comment.record.author,
+					date(comment.record.timestamp)
+				)?;
+				for line in comment.record.message.trim_end().lines() {
+					match line {
+						"" => writeln!(writer, ">")?,
+						line => writeln!(writer, "> {}", line)?,
+					}
+				}
+				writeln!(writer)?;
+			}
+		}
+	}
+
+	Ok(())
+}
+
+/// Formats a Unix timestamp as a `YYYY-MM-DD` date, in UTC. See
+/// <http://howardhinnant.github.io/date_algorithms.html>.
+fn date(timestamp: i64) -> String {
+	let days = timestamp.div_euclid(86400) + 719468;
+	let era = days.div_euclid(146097);
+	let day_of_era = days - era * 146097;
+	let year_of_era =
+		(day_of_era - day_of_era / 1460 + day_of_era / 36524 - day_of_era / 146096) / 365;
+	let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+	let month_index = (5 * day_of_year + 2) / 153;
+	let day = day_of_year - (153 * month_index + 2) / 5 + 1;
+	let month = if month_index < 10 {
+		month_index + 3
+	} else {
+		month_index - 9
+	};
+	let year = year_of_era + era * 400 + i64::from(month <= 2);
+	format!("{:04}-{:02}-{:02}", year, month, day)
+}
@@ -1,14 +1,32 @@
 #![cfg_attr(not(debug_assertions), windows_subsystem = "windows")]
 
-use minimap_core::{GitRemote, MemoryRemote, Record, TicketState, Workspace};
+mod credentials;
+mod deep_link;
+mod events;
+mod export;
+mod origins;
+mod session;
+mod sync;
+
+use credentials::CredentialBroker;
+use deep_link::LaunchLink;
+use export::ExportFormat;
+use minimap_core::{
+	AttachmentInfo, Cursor, Dependency, DependencyKind, DependencyRegistry, DependencyStatus,
+	EffectiveState, GitRemote, LabelDefinition, MemoryRemote, ObservedRemote, Record, Remote,
+	Ticket, TicketQuery, TicketState, TicketView, Workspace,
+};
 use paste::paste;
 use serde::{de::Deserialize, ser::Serialize};
-use slotmap::{new_key_type, Key, KeyData, SlotMap};
+use session::{Session, WorkspaceKind};
+use slotmap::{new_key_type, Key, KeyData, SecondaryMap, SlotMap};
 use std::{
 	collections::HashMap,
 	sync::{Arc, Mutex},
+	time::Duration,
 };
-use tauri::State;
+use sync::Syncer;
+use tauri::{Manager, State, Window, WindowEvent};
 
 new_key_type! { pub struct WorkspaceKey; }
 
@@ -16,468 +34,1232 @@ macro_rules! remote_backend_impl {
 	($Registry:ty, $Record:ty, $prefix:ident) => {
 		paste! {
 			#[tauri::command]
-			fn [<$prefix _workspace_name>](
+			async fn [<$prefix _workspace_name>](
 				workspace: WorkspaceKey,
-				workspace_registry: State<$Registry>,
+				workspace_registry: State<'_, $Registry>,
 			) -> Result<Option<$Record>> {
-				let workspace_registry = workspace_registry.lock().unwrap();
 				let workspace_mutex = workspace_registry
+					.lock()
+					.unwrap()
 					.get(workspace)
 					.cloned()
 					.ok_or(Error::NoSuchWorkspace(workspace))?;
-				let workspace = workspace_mutex.lock().unwrap();
-				let name = workspace.name()?.map(Into::into);
-				drop(workspace);
-				drop(workspace_mutex);
-				drop(workspace_registry);
-				Ok(name)
+				run_blocking(workspace_mutex, move |workspace| {
+					let name = workspace.name()?.map(Into::into);
+					Ok(name)
+				})
+				.await
 			}
 
 			#[tauri::command]
-			fn [<$prefix _workspace_set_name>](
+			async fn [<$prefix _workspace_set_name>](
 				workspace: WorkspaceKey,
-				workspace_registry: State<$Registry>,
+				workspace_registry: State<'_, $Registry>,
 				name: String,
 			) -> Result<$Record> {
-				let workspace_registry = workspace_registry.lock().unwrap();
 				let workspace_mutex = workspace_registry
+					.lock()
+					.unwrap()
 					.get(workspace)
 					.cloned()
 					.ok_or(Error::NoSuchWorkspace(workspace))?;
-				let workspace = workspace_mutex.lock().unwrap();
-				let record = workspace.set_name(&name)?.into();
-				Ok(record)
+				run_blocking(workspace_mutex, move |workspace| {
+					let record = workspace.set_name(&name)?.into();
+					Ok(record)
+				})
+				.await
 			}
 
 			#[tauri::command]
-			fn [<$prefix _workspace_description>](
+			async fn [<$prefix _workspace_description>](
 				workspace: WorkspaceKey,
-				workspace_registry: State<$Registry>,
+				workspace_registry: State<'_, $Registry>,
 			) -> Result<Option<$Record>> {
-				let workspace_registry = workspace_registry.lock().unwrap();
 				let workspace_mutex = workspace_registry
+					.lock()
+					.unwrap()
 					.get(workspace)
 					.cloned()
 					.ok_or(Error::NoSuchWorkspace(workspace))?;
-				let workspace = workspace_mutex.lock().unwrap();
-				let record = workspace.description()?.map(Into::into);
-				Ok(record)
+				run_blocking(workspace_mutex, move |workspace| {
+					let record = workspace.description()?.map(Into::into);
+					Ok(record)
+				})
+				.await
 			}
 
 			#[tauri::command]
-			fn [<$prefix _workspace_set_description>](
+			async fn [<$prefix _workspace_set_description>](
 				workspace: WorkspaceKey,
-				workspace_registry: State<$Registry>,
+				workspace_registry: State<'_, $Registry>,
 				description: String,
 			) -> Result<$Record> {
-				let workspace_registry = workspace_registry.lock().unwrap();
 				let workspace_mutex = workspace_registry
+					.lock()
+					.unwrap()
 					.get(workspace)
 					.cloned()
 					.ok_or(Error::NoSuchWorkspace(workspace))?;
-				let workspace = workspace_mutex.lock().unwrap();
-				let record = workspace.set_description(&description)?.into();
-				Ok(record)
+				run_blocking(workspace_mutex, move |workspace| {
+					let record = workspace.set_description(&description)?.into();
+					Ok(record)
+				})
+				.await
 			}
 
 			#[tauri::command]
-			fn [<$prefix _workspace_create_project>](
+			async fn [<$prefix _workspace_create_project>](
 				workspace: WorkspaceKey,
-				workspace_registry: State<$Registry>,
+				workspace_registry: State<'_, $Registry>,
 				project: String,
 			) -> Result<std::result::Result<String, $Record>> {
-				let workspace_registry = workspace_registry.lock().unwrap();
 				let workspace_mutex = workspace_registry
+					.lock()
+					.unwrap()
 					.get(workspace)
 					.cloned()
 					.ok_or(Error::NoSuchWorkspace(workspace))?;
-				let workspace = workspace_mutex.lock().unwrap();
-				let record = workspace
-					.create_project(&project)?
-					.map(|_| project)
-					.map_err(Into::into);
-				Ok(record)
+				run_blocking(workspace_mutex, move |workspace| {
+					let record = workspace
+						.create_project(&project)?
+						.map(|_| project)
+						.map_err(Into::into);
+					Ok(record)
+				})
+				.await
 			}
 
 			#[tauri::command]
-			fn [<$prefix _workspace_projects>](
+			async fn [<$prefix _workspace_projects>](
 				workspace: WorkspaceKey,
-				workspace_registry: State<$Registry>,
+				workspace_registry: State<'_, $Registry>,
 			) -> Result<Vec<$Record>> {
-				let workspace_registry = workspace_registry.lock().unwrap();
 				let workspace_mutex = workspace_registry
+					.lock()
+					.unwrap()
+					.get(workspace)
+					.cloned()
+					.ok_or(Error::NoSuchWorkspace(workspace))?;
+				run_blocking(workspace_mutex, move |workspace| {
+					let record = Vec::from_iter(
+						workspace.projects()?.into_iter().map(Into::into),
+					);
+					Ok(record)
+				})
+				.await
+			}
+
+			#[tauri::command]
+			async fn [<$prefix _workspace_projects_with_meta>](
+				workspace: WorkspaceKey,
+				workspace_registry: State<'_, $Registry>,
+			) -> Result<Vec<(String, Option<$Record>, Option<$Record>)>> {
+				let workspace_mutex = workspace_registry
+					.lock()
+					.unwrap()
+					.get(workspace)
+					.cloned()
+					.ok_or(Error::NoSuchWorkspace(workspace))?;
+				run_blocking(workspace_mutex, move |workspace| {
+					let projects = workspace
+						.projects_with_meta()?
+						.into_iter()
+						.map(|meta| {
+							(
+								meta.project.slug().to_string(),
+								meta.name.map(Into::into),
+								meta.description.map(Into::into),
+							)
+						})
+						.collect();
+					Ok(projects)
+				})
+				.await
+			}
+
+			#[tauri::command]
+			async fn [<$prefix _workspace_projects_detailed>](
+				workspace: WorkspaceKey,
+				workspace_registry: State<'_, $Registry>,
+			) -> Result<Vec<ProjectDetails<$Record>>> {
+				let workspace_mutex = workspace_registry
+					.lock()
+					.unwrap()
+					.get(workspace)
+					.cloned()
+					.ok_or(Error::NoSuchWorkspace(workspace))?;
+				run_blocking(workspace_mutex, move |workspace| {
+					let mut projects = vec![];
+					for meta in workspace.projects_with_meta()? {
+						let parent = match meta.project.parent() {
+							Ok(parent) => parent.map(|parent| parent.slug().to_string()),
+							// A deleted parent leaves the project at the root.
+							Err(minimap_core::Error::NotFound(..)) => None,
+							Err(e) => return Err(e.into()),
+						};
+						let mut open_tickets = 0;
+						for ticket in meta.project.tickets()? {
+							if ticket.is_open()? {
+								open_tickets += 1;
+							}
+						}
+						projects.push(ProjectDetails {
+							slug: meta.project.slug().to_string(),
+							name: meta.name.map(Into::into),
+							description: meta.description.map(Into::into),
+							parent,
+							open_tickets,
+						});
+					}
+					Ok(projects)
+				})
+				.await
+			}
+
+			#[tauri::command]
+			async fn [<$prefix _workspace_export>](
+				app: tauri::AppHandle,
+				workspace: WorkspaceKey,
+				workspace_registry: State<'_, $Registry>,
+				format: ExportFormat,
+				dest_path: String,
+			) -> Result<usize> {
+				let key = workspace;
+				let workspace_mutex = workspace_registry
+					.lock()
+					.unwrap()
 					.get(workspace)
 					.cloned()
 					.ok_or(Error::NoSuchWorkspace(workspace))?;
-				let workspace = workspace_mutex.lock().unwrap();
-				let record = Vec::from_iter(
-					workspace.projects()?.into_iter().map(Into::into),
-				);
-				Ok(record)
+				run_blocking(workspace_mutex, move |workspace| {
+					export::export(&app, key, workspace, format, &dest_path)
+				})
+				.await
 			}
 
 			#[tauri::command]
-			fn [<$prefix _workspace_delete_project>](
+			async fn [<$prefix _workspace_delete_project>](
 				workspace: WorkspaceKey,
-				workspace_registry: State<$Registry>,
+				workspace_registry: State<'_, $Registry>,
 				project: String,
+				reason: Option<String>,
 			) -> Result<std::result::Result<$Record, Option<$Record>>> {
-				let workspace_registry = workspace_registry.lock().unwrap();
 				let workspace_mutex = workspace_registry
+					.lock()
+					.unwrap()
 					.get(workspace)
 					.cloned()
 					.ok_or(Error::NoSuchWorkspace(workspace))?;
-				let workspace = workspace_mutex.lock().unwrap();
-				let record = workspace
-					.delete_project(&project)?
-					.map(Into::into)
-					.map_err(|e| e.map(Into::into));
-				Ok(record)
+				run_blocking(workspace_mutex, move |workspace| {
+					let record = workspace
+						.delete_project_with_reason(&project, reason.as_deref())?
+						.map(Into::into)
+						.map_err(|e| e.map(Into::into));
+					Ok(record)
+				})
+				.await
 			}
 
 			#[tauri::command]
-			fn [<$prefix _project_set_name>](
+			async fn [<$prefix _project_set_name>](
 				workspace: WorkspaceKey,
-				workspace_registry: State<$Registry>,
+				workspace_registry: State<'_, $Registry>,
 				project: String,
 				name: String,
 			) -> Result<$Record> {
-				let workspace_registry = workspace_registry.lock().unwrap();
 				let workspace_mutex = workspace_registry
+					.lock()
+					.unwrap()
 					.get(workspace)
 					.cloned()
 					.ok_or(Error::NoSuchWorkspace(workspace))?;
-				let workspace = workspace_mutex.lock().unwrap();
-				let project = workspace.project(&project)?;
-				let record = project.set_name(&name)?.into();
-				Ok(record)
+				run_blocking(workspace_mutex, move |workspace| {
+					let project = workspace.project(&project)?;
+					let record = project.set_name(&name)?.into();
+					Ok(record)
+				})
+				.await
 			}
 
 			#[tauri::command]
-			fn [<$prefix _project_set_description>](
+			async fn [<$prefix _project_set_description>](
 				workspace: WorkspaceKey,
-				workspace_registry: State<$Registry>,
+				workspace_registry: State<'_, $Registry>,
 				project: String,
 				description: String,
 			) -> Result<$Record> {
-				let workspace_registry = workspace_registry.lock().unwrap();
 				let workspace_mutex = workspace_registry
+					.lock()
+					.unwrap()
 					.get(workspace)
 					.cloned()
 					.ok_or(Error::NoSuchWorkspace(workspace))?;
-				let workspace = workspace_mutex.lock().unwrap();
-				let project = workspace.project(&project)?;
-				let record = project.set_description(&description)?.into();
-				Ok(record)
+				run_blocking(workspace_mutex, move |workspace| {
+					let project = workspace.project(&project)?;
+					let record = project.set_description(&description)?.into();
+					Ok(record)
+				})
+				.await
 			}
 
 			#[tauri::command]
-			fn [<$prefix _project_name>](
+			async fn [<$prefix _project_name>](
 				workspace: WorkspaceKey,
-				workspace_registry: State<$Registry>,
+				workspace_registry: State<'_, $Registry>,
 				project: String,
 			) -> Result<Option<$Record>> {
-				let workspace_registry = workspace_registry.lock().unwrap();
 				let workspace_mutex = workspace_registry
+					.lock()
+					.unwrap()
 					.get(workspace)
 					.cloned()
 					.ok_or(Error::NoSuchWorkspace(workspace))?;
-				let workspace = workspace_mutex.lock().unwrap();
-				let project = workspace.project(&project)?;
-				let record = project.name()?.map(Into::into);
-				Ok(record)
+				run_blocking(workspace_mutex, move |workspace| {
+					let project = workspace.project(&project)?;
+					let record = project.name()?.map(Into::into);
+					Ok(record)
+				})
+				.await
 			}
 
 			#[tauri::command]
-			fn [<$prefix _project_description>](
+			async fn [<$prefix _project_description>](
 				workspace: WorkspaceKey,
-				workspace_registry: State<$Registry>,
+				workspace_registry: State<'_, $Registry>,
 				project: String,
 			) -> Result<Option<$Record>> {
-				let workspace_registry = workspace_registry.lock().unwrap();
 				let workspace_mutex = workspace_registry
+					.lock()
+					.unwrap()
+					.get(workspace)
+					.cloned()
+					.ok_or(Error::NoSuchWorkspace(workspace))?;
+				run_blocking(workspace_mutex, move |workspace| {
+					let project = workspace.project(&project)?;
+					let record = project.description()?.map(Into::into);
+					Ok(record)
+				})
+				.await
+			}
+
+			#[tauri::command]
+			async fn [<$prefix _project_create_subproject>](
+				workspace: WorkspaceKey,
+				workspace_registry: State<'_, $Registry>,
+				project: String,
+				subproject: String,
+			) -> Result<std::result::Result<String, $Record>> {
+				let workspace_mutex = workspace_registry
+					.lock()
+					.unwrap()
+					.get(workspace)
+					.cloned()
+					.ok_or(Error::NoSuchWorkspace(workspace))?;
+				run_blocking(workspace_mutex, move |workspace| {
+					let project = workspace.project(&project)?;
+					let record = project
+						.create_project(&subproject)?
+						.map(|_| subproject)
+						.map_err(Into::into);
+					Ok(record)
+				})
+				.await
+			}
+
+			#[tauri::command]
+			async fn [<$prefix _project_parent>](
+				workspace: WorkspaceKey,
+				workspace_registry: State<'_, $Registry>,
+				project: String,
+			) -> Result<Option<String>> {
+				let workspace_mutex = workspace_registry
+					.lock()
+					.unwrap()
+					.get(workspace)
+					.cloned()
+					.ok_or(Error::NoSuchWorkspace(workspace))?;
+				run_blocking(workspace_mutex, move |workspace| {
+					let project = workspace.project(&project)?;
+					match project.parent() {
+						Ok(parent) => Ok(parent.map(|parent| parent.slug().to_string())),
+						// A deleted parent leaves the project at the root.
+						Err(minimap_core::Error::NotFound(..)) => Ok(None),
+						Err(e) => Err(e.into()),
+					}
+				})
+				.await
+			}
+
+			#[tauri::command]
+			async fn [<$prefix _project_children>](
+				workspace: WorkspaceKey,
+				workspace_registry: State<'_, $Registry>,
+				project: String,
+			) -> Result<Vec<String>> {
+				let workspace_mutex = workspace_registry
+					.lock()
+					.unwrap()
 					.get(workspace)
 					.cloned()
 					.ok_or(Error::NoSuchWorkspace(workspace))?;
-				let workspace = workspace_mutex.lock().unwrap();
-				let project = workspace.project(&project)?;
-				let record = project.description()?.map(Into::into);
-				Ok(record)
+				run_blocking(workspace_mutex, move |workspace| {
+					let children = workspace
+						.project(&project)?
+						.children()?
+						.iter()
+						.map(|child| child.slug().to_string())
+						.collect();
+					Ok(children)
+				})
+				.await
 			}
 
 			#[tauri::command]
-			fn [<$prefix _project_create_ticket>](
+			async fn [<$prefix _project_labels>](
 				workspace: WorkspaceKey,
-				workspace_registry: State<$Registry>,
+				workspace_registry: State<'_, $Registry>,
+				project: String,
+			) -> Result<Vec<LabelDefinition>> {
+				let workspace_mutex = workspace_registry
+					.lock()
+					.unwrap()
+					.get(workspace)
+					.cloned()
+					.ok_or(Error::NoSuchWorkspace(workspace))?;
+				run_blocking(workspace_mutex, move |workspace| {
+					let labels = workspace.project(&project)?.labels()?;
+					Ok(labels)
+				})
+				.await
+			}
+
+			#[tauri::command]
+			async fn [<$prefix _project_icon_base64>](
+				workspace: WorkspaceKey,
+				workspace_registry: State<'_, $Registry>,
+				project: String,
+			) -> Result<Option<(String, String)>> {
+				use base64::{engine::general_purpose, Engine as _};
+				let workspace_mutex = workspace_registry
+					.lock()
+					.unwrap()
+					.get(workspace)
+					.cloned()
+					.ok_or(Error::NoSuchWorkspace(workspace))?;
+				run_blocking(workspace_mutex, move |workspace| {
+					let project = workspace.project(&project)?;
+					let icon = project.icon()?;
+					Ok(icon.map(|icon| {
+						(icon.mime, general_purpose::STANDARD_NO_PAD.encode(icon.data))
+					}))
+				})
+				.await
+			}
+
+			#[tauri::command]
+			async fn [<$prefix _project_set_icon>](
+				workspace: WorkspaceKey,
+				workspace_registry: State<'_, $Registry>,
+				project: String,
+				data: Vec<u8>,
+			) -> Result<$Record> {
+				let workspace_mutex = workspace_registry
+					.lock()
+					.unwrap()
+					.get(workspace)
+					.cloned()
+					.ok_or(Error::NoSuchWorkspace(workspace))?;
+				run_blocking(workspace_mutex, move |workspace| {
+					let project = workspace.project(&project)?;
+					let record = project.set_icon(&data)?.into();
+					Ok(record)
+				})
+				.await
+			}
+
+			#[tauri::command]
+			async fn [<$prefix _project_create_ticket>](
+				workspace: WorkspaceKey,
+				workspace_registry: State<'_, $Registry>,
 				project: String,
 			) -> Result<String> {
-				let workspace_registry = workspace_registry.lock().unwrap();
-				let workspace_mutex = workspace_registry.get(workspace).cloned().unwrap();
-				let workspace = workspace_mutex.lock().unwrap();
-				let project = workspace.project(&project)?;
-				Ok(project.create_ticket()?.slug().to_string())
+				let workspace_mutex = workspace_registry
+					.lock()
+					.unwrap()
+					.get(workspace)
+					.cloned()
+					.ok_or(Error::NoSuchWorkspace(workspace))?;
+				run_blocking(workspace_mutex, move |workspace| {
+					let project = workspace.project(&project)?;
+					Ok(project.create_ticket()?.slug().to_string())
+				})
+				.await
+			}
+
+			#[tauri::command]
+			async fn [<$prefix _project_tickets>](
+				workspace: WorkspaceKey,
+				workspace_registry: State<'_, $Registry>,
+				project: String,
+				filter: Option<TicketFilter>,
+			) -> Result<Vec<TicketSummary>> {
+				let query = filter.unwrap_or_default().into_query()?;
+				let workspace_mutex = workspace_registry
+					.lock()
+					.unwrap()
+					.get(workspace)
+					.cloned()
+					.ok_or(Error::NoSuchWorkspace(workspace))?;
+				run_blocking(workspace_mutex, move |workspace| {
+					let project = workspace.project(&project)?;
+					let mut tickets = vec![];
+					for ticket in project.query(&query)? {
+						tickets.push(TicketSummary::new(&ticket?)?);
+					}
+					Ok(tickets)
+				})
+				.await
+			}
+
+			/// Like `*_project_tickets`, with the filter serialized as JSON,
+			/// e.g. as saved in the configuration.
+			#[tauri::command]
+			async fn [<$prefix _project_query_tickets>](
+				workspace: WorkspaceKey,
+				workspace_registry: State<'_, $Registry>,
+				project: String,
+				filter_json: String,
+			) -> Result<Vec<TicketSummary>> {
+				let query = serde_json::from_str::<TicketFilter>(&filter_json)?.into_query()?;
+				let workspace_mutex = workspace_registry
+					.lock()
+					.unwrap()
+					.get(workspace)
+					.cloned()
+					.ok_or(Error::NoSuchWorkspace(workspace))?;
+				run_blocking(workspace_mutex, move |workspace| {
+					let project = workspace.project(&project)?;
+					let mut tickets = vec![];
+					for ticket in project.query(&query)? {
+						tickets.push(TicketSummary::new(&ticket?)?);
+					}
+					Ok(tickets)
+				})
+				.await
+			}
+
+			#[tauri::command]
+			async fn [<$prefix _project_delete_ticket>](
+				workspace: WorkspaceKey,
+				workspace_registry: State<'_, $Registry>,
+				project: String,
+				ticket: u64,
+				reason: Option<String>,
+			) -> Result<std::result::Result<$Record, Option<$Record>>> {
+				let workspace_mutex = workspace_registry
+					.lock()
+					.unwrap()
+					.get(workspace)
+					.cloned()
+					.ok_or(Error::NoSuchWorkspace(workspace))?;
+				run_blocking(workspace_mutex, move |workspace| {
+					let project = workspace.project(&project)?;
+					let record = project
+						.delete_ticket_with_reason(ticket, reason.as_deref())?
+						.map(Into::into)
+						.map_err(|e| e.map(Into::into));
+					Ok(record)
+				})
+				.await
+			}
+
+			#[tauri::command]
+			async fn [<$prefix _project_restore_ticket>](
+				workspace: WorkspaceKey,
+				workspace_registry: State<'_, $Registry>,
+				project: String,
+				ticket: u64,
+			) -> Result<std::result::Result<$Record, Option<$Record>>> {
+				let workspace_mutex = workspace_registry
+					.lock()
+					.unwrap()
+					.get(workspace)
+					.cloned()
+					.ok_or(Error::NoSuchWorkspace(workspace))?;
+				run_blocking(workspace_mutex, move |workspace| {
+					let project = workspace.project(&project)?;
+					let record = project
+						.restore_ticket(ticket)?
+						.map(Into::into)
+						.map_err(|e| e.map(Into::into));
+					Ok(record)
+				})
+				.await
 			}
 
 			#[tauri::command]
-			fn [<$prefix _ticket_title>](
+			async fn [<$prefix _ticket_title>](
 				workspace: WorkspaceKey,
-				workspace_registry: State<$Registry>,
+				workspace_registry: State<'_, $Registry>,
 				ticket: String,
 			) -> Result<Option<$Record>> {
-				let workspace_registry = workspace_registry.lock().unwrap();
-				let workspace_mutex = workspace_registry.get(workspace).cloned().unwrap();
-				let workspace = workspace_mutex.lock().unwrap();
-				let ticket = workspace.ticket(&ticket)?;
-				let record = ticket.title()?.map(Into::into);
-				Ok(record)
+				let workspace_mutex = workspace_registry
+					.lock()
+					.unwrap()
+					.get(workspace)
+					.cloned()
+					.ok_or(Error::NoSuchWorkspace(workspace))?;
+				run_blocking(workspace_mutex, move |workspace| {
+					let ticket = workspace.ticket(&ticket)?;
+					let record = ticket.title()?.map(Into::into);
+					Ok(record)
+				})
+				.await
 			}
 
 			#[tauri::command]
-			fn [<$prefix _ticket_set_title>](
+			async fn [<$prefix _ticket_set_title>](
 				workspace: WorkspaceKey,
-				workspace_registry: State<$Registry>,
+				workspace_registry: State<'_, $Registry>,
 				ticket: String,
 				title: String,
 			) -> Result<$Record> {
-				let workspace_registry = workspace_registry.lock().unwrap();
-				let workspace_mutex = workspace_registry.get(workspace).cloned().unwrap();
-				let workspace = workspace_mutex.lock().unwrap();
-				let ticket = workspace.ticket(&ticket)?;
-				let record = ticket.set_title(&title)?.into();
-				Ok(record)
+				let workspace_mutex = workspace_registry
+					.lock()
+					.unwrap()
+					.get(workspace)
+					.cloned()
+					.ok_or(Error::NoSuchWorkspace(workspace))?;
+				run_blocking(workspace_mutex, move |workspace| {
+					let ticket = workspace.ticket(&ticket)?;
+					let record = ticket.set_title(&title)?.into();
+					Ok(record)
+				})
+				.await
 			}
 
 			#[tauri::command]
-			fn [<$prefix _ticket_add_comment>](
+			async fn [<$prefix _ticket_description>](
 				workspace: WorkspaceKey,
-				workspace_registry: State<$Registry>,
+				workspace_registry: State<'_, $Registry>,
+				ticket: String,
+			) -> Result<Option<$Record>> {
+				let workspace_mutex = workspace_registry
+					.lock()
+					.unwrap()
+					.get(workspace)
+					.cloned()
+					.ok_or(Error::NoSuchWorkspace(workspace))?;
+				run_blocking(workspace_mutex, move |workspace| {
+					let ticket = workspace.ticket(&ticket)?;
+					let record = ticket.description()?.map(Into::into);
+					Ok(record)
+				})
+				.await
+			}
+
+			#[tauri::command]
+			async fn [<$prefix _ticket_set_description>](
+				workspace: WorkspaceKey,
+				workspace_registry: State<'_, $Registry>,
+				ticket: String,
+				description: String,
+			) -> Result<$Record> {
+				let workspace_mutex = workspace_registry
+					.lock()
+					.unwrap()
+					.get(workspace)
+					.cloned()
+					.ok_or(Error::NoSuchWorkspace(workspace))?;
+				run_blocking(workspace_mutex, move |workspace| {
+					let ticket = workspace.ticket(&ticket)?;
+					let record = ticket.set_description(&description)?.into();
+					Ok(record)
+				})
+				.await
+			}
+
+			#[tauri::command]
+			async fn [<$prefix _ticket_view>](
+				workspace: WorkspaceKey,
+				workspace_registry: State<'_, $Registry>,
+				ticket: String,
+			) -> Result<TicketView> {
+				let workspace_mutex = workspace_registry
+					.lock()
+					.unwrap()
+					.get(workspace)
+					.cloned()
+					.ok_or(Error::NoSuchWorkspace(workspace))?;
+				run_blocking(workspace_mutex, move |workspace| {
+					let ticket = workspace.ticket(&ticket)?;
+					let view = ticket.view()?;
+					Ok(view)
+				})
+				.await
+			}
+
+			#[tauri::command]
+			async fn [<$prefix _ticket_add_comment>](
+				workspace: WorkspaceKey,
+				workspace_registry: State<'_, $Registry>,
 				ticket: String,
 				comment: String,
 			) -> Result<$Record> {
-				let workspace_registry = workspace_registry.lock().unwrap();
-				let workspace_mutex = workspace_registry.get(workspace).cloned().unwrap();
-				let workspace = workspace_mutex.lock().unwrap();
-				let ticket = workspace.ticket(&ticket)?;
-				let record = ticket.add_comment(&comment)?.into();
-				Ok(record)
+				let workspace_mutex = workspace_registry
+					.lock()
+					.unwrap()
+					.get(workspace)
+					.cloned()
+					.ok_or(Error::NoSuchWorkspace(workspace))?;
+				run_blocking(workspace_mutex, move |workspace| {
+					let ticket = workspace.ticket(&ticket)?;
+					let record = ticket.add_comment(&comment)?.into();
+					Ok(record)
+				})
+				.await
 			}
 
 			#[tauri::command]
-			fn [<$prefix _ticket_comments>](
+			async fn [<$prefix _ticket_comments>](
 				workspace: WorkspaceKey,
-				workspace_registry: State<$Registry>,
+				workspace_registry: State<'_, $Registry>,
 				ticket: String,
 			) -> Result<Vec<$Record>> {
-				let workspace_registry = workspace_registry.lock().unwrap();
-				let workspace_mutex = workspace_registry.get(workspace).cloned().unwrap();
-				let workspace = workspace_mutex.lock().unwrap();
-				let ticket = workspace.ticket(&ticket)?;
-				let mut comments = Vec::new();
-				for comment_record in ticket.comments()? {
-					comments.push(comment_record?.into());
-				}
-				Ok(comments)
+				let workspace_mutex = workspace_registry
+					.lock()
+					.unwrap()
+					.get(workspace)
+					.cloned()
+					.ok_or(Error::NoSuchWorkspace(workspace))?;
+				run_blocking(workspace_mutex, move |workspace| {
+					let ticket = workspace.ticket(&ticket)?;
+					let mut comments = Vec::new();
+					for comment_record in ticket.comments()? {
+						comments.push(comment_record?.into());
+					}
+					Ok(comments)
+				})
+				.await
 			}
 
 			#[tauri::command]
-			fn [<$prefix _ticket_upsert_attachment>](
+			async fn [<$prefix _ticket_comments_page>](
 				workspace: WorkspaceKey,
-				workspace_registry: State<$Registry>,
+				workspace_registry: State<'_, $Registry>,
+				ticket: String,
+				after: Option<String>,
+				limit: usize,
+			) -> Result<CommentPage<$Record>> {
+				let after = after.map(|cursor| cursor.parse::<Cursor>()).transpose()?;
+				let workspace_mutex = workspace_registry
+					.lock()
+					.unwrap()
+					.get(workspace)
+					.cloned()
+					.ok_or(Error::NoSuchWorkspace(workspace))?;
+				run_blocking(workspace_mutex, move |workspace| {
+					let ticket = workspace.ticket(&ticket)?;
+					let page = ticket.comments_page(after.as_ref(), limit)?;
+					Ok(CommentPage {
+						comments: page.records.into_iter().map(Into::into).collect(),
+						next: page.next.map(|cursor| cursor.to_string()),
+					})
+				})
+				.await
+			}
+
+			#[tauri::command]
+			async fn [<$prefix _ticket_upsert_attachment>](
+				workspace: WorkspaceKey,
+				workspace_registry: State<'_, $Registry>,
 				ticket: String,
 				name: String,
 				data: Vec<u8>,
 			) -> Result<$Record> {
-				let workspace_registry = workspace_registry.lock().unwrap();
-				let workspace_mutex = workspace_registry.get(workspace).cloned().unwrap();
-				let workspace = workspace_mutex.lock().unwrap();
-				let ticket = workspace.ticket(&ticket)?;
-				let record = ticket.upsert_attachment(&name, &data)?.into();
-				Ok(record)
+				let workspace_mutex = workspace_registry
+					.lock()
+					.unwrap()
+					.get(workspace)
+					.cloned()
+					.ok_or(Error::NoSuchWorkspace(workspace))?;
+				run_blocking(workspace_mutex, move |workspace| {
+					let ticket = workspace.ticket(&ticket)?;
+					let record = ticket.upsert_attachment(&name, &data)?.into();
+					Ok(record)
+				})
+				.await
 			}
 
 			#[tauri::command]
-			fn [<$prefix _ticket_upsert_attachment_filepath>](
+			async fn [<$prefix _ticket_upsert_attachment_filepath>](
 				workspace: WorkspaceKey,
-				workspace_registry: State<$Registry>,
+				workspace_registry: State<'_, $Registry>,
 				ticket: String,
 				name: String,
 				filepath: String,
 			) -> Result<$Record> {
 				let data = std::fs::read(filepath).map_err(minimap_core::Error::Io)?;
-				let workspace_registry = workspace_registry.lock().unwrap();
-				let workspace_mutex = workspace_registry.get(workspace).cloned().unwrap();
-				let workspace = workspace_mutex.lock().unwrap();
-				let ticket = workspace.ticket(&ticket)?;
-				let record = ticket.upsert_attachment(&name, &data)?.into();
-				Ok(record)
+				let workspace_mutex = workspace_registry
+					.lock()
+					.unwrap()
+					.get(workspace)
+					.cloned()
+					.ok_or(Error::NoSuchWorkspace(workspace))?;
+				run_blocking(workspace_mutex, move |workspace| {
+					let ticket = workspace.ticket(&ticket)?;
+					let record = ticket.upsert_attachment(&name, &data)?.into();
+					Ok(record)
+				})
+				.await
 			}
 
 			#[tauri::command]
-			fn [<$prefix _ticket_remove_attachment>](
+			async fn [<$prefix _ticket_remove_attachment>](
 				workspace: WorkspaceKey,
-				workspace_registry: State<$Registry>,
+				workspace_registry: State<'_, $Registry>,
 				ticket: String,
 				name: String,
 			) -> Result<std::result::Result<$Record, Option<$Record>>> {
-				let workspace_registry = workspace_registry.lock().unwrap();
-				let workspace_mutex = workspace_registry.get(workspace).cloned().unwrap();
-				let workspace = workspace_mutex.lock().unwrap();
-				let ticket = workspace.ticket(&ticket)?;
-				let record = ticket
-					.remove_attachment(&name)?
-					.map(Into::into)
-					.map_err(|e| e.map(Into::into));
-				Ok(record)
+				let workspace_mutex = workspace_registry
+					.lock()
+					.unwrap()
+					.get(workspace)
+					.cloned()
+					.ok_or(Error::NoSuchWorkspace(workspace))?;
+				run_blocking(workspace_mutex, move |workspace| {
+					let ticket = workspace.ticket(&ticket)?;
+					let record = ticket
+						.remove_attachment(&name)?
+						.map(Into::into)
+						.map_err(|e| e.map(Into::into));
+					Ok(record)
+				})
+				.await
 			}
 
 			#[tauri::command]
-			fn [<$prefix _ticket_attachment>](
+			async fn [<$prefix _ticket_attachment>](
 				workspace: WorkspaceKey,
-				workspace_registry: State<$Registry>,
+				workspace_registry: State<'_, $Registry>,
 				ticket: String,
 				name: String,
 			) -> Result<Option<Vec<u8>>> {
-				let workspace_registry = workspace_registry.lock().unwrap();
-				let workspace_mutex = workspace_registry.get(workspace).cloned().unwrap();
-				let workspace = workspace_mutex.lock().unwrap();
-				let ticket = workspace.ticket(&ticket)?;
-				Ok(ticket.attachment(&name)?)
+				let workspace_mutex = workspace_registry
+					.lock()
+					.unwrap()
+					.get(workspace)
+					.cloned()
+					.ok_or(Error::NoSuchWorkspace(workspace))?;
+				run_blocking(workspace_mutex, move |workspace| {
+					let ticket = workspace.ticket(&ticket)?;
+					Ok(ticket.attachment(&name)?)
+				})
+				.await
 			}
 
 			#[tauri::command]
-			fn [<$prefix _ticket_attachment_base64>](
+			async fn [<$prefix _ticket_attachment_base64>](
 				workspace: WorkspaceKey,
-				workspace_registry: State<$Registry>,
+				workspace_registry: State<'_, $Registry>,
 				ticket: String,
 				name: String,
 			) -> Result<Option<String>> {
 				use base64::{engine::general_purpose, Engine as _};
-				let workspace_registry = workspace_registry.lock().unwrap();
-				let workspace_mutex = workspace_registry.get(workspace).cloned().unwrap();
-				let workspace = workspace_mutex.lock().unwrap();
-				let ticket = workspace.ticket(&ticket)?;
-				let data = ticket.attachment(&name)?;
-				Ok(data.map(|d| general_purpose::STANDARD_NO_PAD.encode(d)))
+				let workspace_mutex = workspace_registry
+					.lock()
+					.unwrap()
+					.get(workspace)
+					.cloned()
+					.ok_or(Error::NoSuchWorkspace(workspace))?;
+				run_blocking(workspace_mutex, move |workspace| {
+					let ticket = workspace.ticket(&ticket)?;
+					let data = ticket.attachment(&name)?;
+					Ok(data.map(|d| general_purpose::STANDARD_NO_PAD.encode(d)))
+				})
+				.await
 			}
 
 			#[tauri::command]
-			fn [<$prefix _ticket_state>](
+			async fn [<$prefix _ticket_attachment_save>](
 				workspace: WorkspaceKey,
-				workspace_registry: State<$Registry>,
+				workspace_registry: State<'_, $Registry>,
+				ticket: String,
+				name: String,
+				dest_path: String,
+				overwrite: Option<bool>,
+			) -> Result<bool> {
+				let workspace_mutex = workspace_registry
+					.lock()
+					.unwrap()
+					.get(workspace)
+					.cloned()
+					.ok_or(Error::NoSuchWorkspace(workspace))?;
+				let data = run_blocking(workspace_mutex, move |workspace| {
+					Ok(workspace.ticket(&ticket)?.attachment(&name)?)
+				})
+				.await?;
+				let Some(data) = data else {
+					return Ok(false);
+				};
+
+				// The workspace is unlocked by now, so that other commands
+				// needn't wait for the file to be written.
+				tauri::async_runtime::spawn_blocking(move || {
+					write_file(&dest_path, &data, overwrite.unwrap_or_default())
+				})
+				.await??;
+				Ok(true)
+			}
+
+			#[tauri::command]
+			async fn [<$prefix _ticket_attachment_info>](
+				workspace: WorkspaceKey,
+				workspace_registry: State<'_, $Registry>,
+				ticket: String,
+				name: String,
+			) -> Result<Option<AttachmentInfo>> {
+				let workspace_mutex = workspace_registry
+					.lock()
+					.unwrap()
+					.get(workspace)
+					.cloned()
+					.ok_or(Error::NoSuchWorkspace(workspace))?;
+				run_blocking(workspace_mutex, move |workspace| {
+					let ticket = workspace.ticket(&ticket)?;
+					Ok(ticket.attachment_info(&name)?)
+				})
+				.await
+			}
+
+			#[tauri::command]
+			async fn [<$prefix _ticket_state>](
+				workspace: WorkspaceKey,
+				workspace_registry: State<'_, $Registry>,
 				ticket: String,
 			) -> Result<(String, Option<$Record>)> {
-				let workspace_registry = workspace_registry.lock().unwrap();
-				let workspace_mutex = workspace_registry.get(workspace).cloned().unwrap();
-				let workspace = workspace_mutex.lock().unwrap();
-				let ticket = workspace.ticket(&ticket)?;
-				Ok(ticket
-					.state()
-					.map(|(s, r)| (s.to_string(), r.map(Into::into)))?)
+				let workspace_mutex = workspace_registry
+					.lock()
+					.unwrap()
+					.get(workspace)
+					.cloned()
+					.ok_or(Error::NoSuchWorkspace(workspace))?;
+				run_blocking(workspace_mutex, move |workspace| {
+					let ticket = workspace.ticket(&ticket)?;
+					Ok(ticket
+						.state()
+						.map(|(s, r)| (s.to_string(), r.map(Into::into)))?)
+				})
+				.await
 			}
 
 			#[tauri::command]
-			fn [<$prefix _ticket_set_state>](
+			async fn [<$prefix _ticket_set_state>](
 				workspace: WorkspaceKey,
-				workspace_registry: State<$Registry>,
+				workspace_registry: State<'_, $Registry>,
 				ticket: String,
 				state: String,
+				reason: Option<String>,
 			) -> Result<$Record> {
 				let state = TicketState::try_from(state)?;
-				let workspace_registry = workspace_registry.lock().unwrap();
-				let workspace_mutex = workspace_registry.get(workspace).cloned().unwrap();
-				let workspace = workspace_mutex.lock().unwrap();
-				let ticket = workspace.ticket(&ticket)?;
-				let record = ticket.set_state(state)?.into();
-				Ok(record)
+				let workspace_mutex = workspace_registry
+					.lock()
+					.unwrap()
+					.get(workspace)
+					.cloned()
+					.ok_or(Error::NoSuchWorkspace(workspace))?;
+				run_blocking(workspace_mutex, move |workspace| {
+					let ticket = workspace.ticket(&ticket)?;
+					let record = ticket
+						.set_state_with_reason(state, reason.as_deref().unwrap_or_default())?
+						.into();
+					Ok(record)
+				})
+				.await
 			}
 
 			#[tauri::command]
-			fn [<$prefix _ticket_is_open>](
+			async fn [<$prefix _ticket_effective_state>](
 				workspace: WorkspaceKey,
-				workspace_registry: State<$Registry>,
+				workspace_registry: State<'_, $Registry>,
 				ticket: String,
-			) -> Result<bool> {
-				let workspace_registry = workspace_registry.lock().unwrap();
-				let workspace_mutex = workspace_registry.get(workspace).cloned().unwrap();
-				let workspace = workspace_mutex.lock().unwrap();
-				let ticket = workspace.ticket(&ticket)?;
-				Ok(ticket.is_open()?)
+			) -> Result<EffectiveState> {
+				let workspace_mutex = workspace_registry
+					.lock()
+					.unwrap()
+					.get(workspace)
+					.cloned()
+					.ok_or(Error::NoSuchWorkspace(workspace))?;
+				run_blocking(workspace_mutex, move |workspace| {
+					let ticket = workspace.ticket(&ticket)?;
+					Ok(ticket.effective_state(&DependencyRegistry::new())?)
+				})
+				.await
 			}
 
 			#[tauri::command]
-			fn [<$prefix _ticket_is_closed>](
+			async fn [<$prefix _ticket_is_open>](
 				workspace: WorkspaceKey,
-				workspace_registry: State<$Registry>,
+				workspace_registry: State<'_, $Registry>,
 				ticket: String,
 			) -> Result<bool> {
-				let workspace_registry = workspace_registry.lock().unwrap();
-				let workspace_mutex = workspace_registry.get(workspace).cloned().unwrap();
-				let workspace = workspace_mutex.lock().unwrap();
-				let ticket = workspace.ticket(&ticket)?;
-				Ok(ticket.is_closed()?)
+				let workspace_mutex = workspace_registry
+					.lock()
+					.unwrap()
+					.get(workspace)
+					.cloned()
+					.ok_or(Error::NoSuchWorkspace(workspace))?;
+				run_blocking(workspace_mutex, move |workspace| {
+					let ticket = workspace.ticket(&ticket)?;
+					Ok(ticket.is_open()?)
+				})
+				.await
 			}
 
 			#[tauri::command]
-			fn [<$prefix _ticket_dependencies>](
+			async fn [<$prefix _ticket_is_closed>](
 				workspace: WorkspaceKey,
-				workspace_registry: State<$Registry>,
+				workspace_registry: State<'_, $Registry>,
 				ticket: String,
-			) -> Result<Vec<(String, String, $Record)>> {
-				let workspace_registry = workspace_registry.lock().unwrap();
-				let workspace_mutex = workspace_registry.get(workspace).cloned().unwrap();
-				let workspace = workspace_mutex.lock().unwrap();
+			) -> Result<bool> {
+				let workspace_mutex = workspace_registry
+					.lock()
+					.unwrap()
+					.get(workspace)
+					.cloned()
+					.ok_or(Error::NoSuchWorkspace(workspace))?;
+				run_blocking(workspace_mutex, move |workspace| {
+					let ticket = workspace.ticket(&ticket)?;
+					Ok(ticket.is_closed()?)
+				})
+				.await
+			}
 
-				let result = workspace
-					.ticket(&ticket)?
-					.dependencies()?
-					.into_iter()
-					.map(|(a, b, r)| (a, b, r.into()))
-					.collect();
+			#[tauri::command]
+			async fn [<$prefix _ticket_dependencies>](
+				workspace: WorkspaceKey,
+				workspace_registry: State<'_, $Registry>,
+				ticket: String,
+			) -> Result<Vec<Dependency>> {
+				let workspace_mutex = workspace_registry
+					.lock()
+					.unwrap()
+					.get(workspace)
+					.cloned()
+					.ok_or(Error::NoSuchWorkspace(workspace))?;
+				run_blocking(workspace_mutex, move |workspace| {
+					Ok(workspace.ticket(&ticket)?.dependencies()?)
+				})
+				.await
+			}
 
-				Ok(result)
+			#[tauri::command]
+			async fn [<$prefix _ticket_resolved_dependencies>](
+				workspace: WorkspaceKey,
+				workspace_registry: State<'_, $Registry>,
+				ticket: String,
+			) -> Result<Vec<(String, String, DependencyStatus)>> {
+				let workspace_mutex = workspace_registry
+					.lock()
+					.unwrap()
+					.get(workspace)
+					.cloned()
+					.ok_or(Error::NoSuchWorkspace(workspace))?;
+				run_blocking(workspace_mutex, move |workspace| {
+					let registry = origins::dependency_registry()?;
+					let ticket = workspace.ticket(&ticket)?;
+					let dependencies = ticket
+						.resolve_dependencies(&registry)?
+						.collect::<minimap_core::Result<_>>()?;
+					Ok(dependencies)
+				})
+				.await
 			}
 
 			#[tauri::command]
-			fn [<$prefix _ticket_add_dependency>](
+			async fn [<$prefix _ticket_add_dependency>](
 				workspace: WorkspaceKey,
-				workspace_registry: State<$Registry>,
+				workspace_registry: State<'_, $Registry>,
 				ticket: String,
 				origin: String,
 				endpoint: String,
+				note: Option<String>,
+				kind: Option<String>,
 			) -> Result<$Record> {
-				let workspace_registry = workspace_registry.lock().unwrap();
-				let workspace_mutex = workspace_registry.get(workspace).cloned().unwrap();
-				let workspace = workspace_mutex.lock().unwrap();
-				let record = workspace
-					.ticket(&ticket)?
-					.add_dependency(&origin, &endpoint)?
-					.into();
-				Ok(record)
+				let kind = kind.as_deref().map(DependencyKind::try_from).transpose()?;
+				let workspace_mutex = workspace_registry
+					.lock()
+					.unwrap()
+					.get(workspace)
+					.cloned()
+					.ok_or(Error::NoSuchWorkspace(workspace))?;
+				run_blocking(workspace_mutex, move |workspace| {
+					let ticket = workspace.ticket(&ticket)?;
+					if let Some(kind) = kind {
+						ticket.add_dependency_with_kind(&origin, &endpoint, kind)?;
+					}
+					let record = match note {
+						Some(note) => ticket.add_dependency_with_note(&origin, &endpoint, &note)?,
+						None => ticket.add_dependency(&origin, &endpoint)?,
+					};
+					Ok(record.into())
+				})
+				.await
 			}
 
 			#[tauri::command]
-			fn [<$prefix _ticket_remove_dependency>](
+			async fn [<$prefix _ticket_remove_dependency>](
 				workspace: WorkspaceKey,
-				workspace_registry: State<$Registry>,
+				workspace_registry: State<'_, $Registry>,
 				ticket: String,
 				origin: String,
 				endpoint: String,
 			) -> Result<Option<$Record>> {
-				let workspace_registry = workspace_registry.lock().unwrap();
-				let workspace_mutex = workspace_registry.get(workspace).cloned().unwrap();
-				let workspace = workspace_mutex.lock().unwrap();
-				let record = workspace
-					.ticket(&ticket)?
-					.remove_dependency(&origin, &endpoint)?
-					.map(Into::into);
-				Ok(record)
+				let workspace_mutex = workspace_registry
+					.lock()
+					.unwrap()
+					.get(workspace)
+					.cloned()
+					.ok_or(Error::NoSuchWorkspace(workspace))?;
+				run_blocking(workspace_mutex, move |workspace| {
+					let record = workspace
+						.ticket(&ticket)?
+						.remove_dependency(&origin, &endpoint)?
+						.map(Into::into);
+					Ok(record)
+				})
+				.await
+			}
+
+			#[tauri::command]
+			async fn [<$prefix _ticket_labels>](
+				workspace: WorkspaceKey,
+				workspace_registry: State<'_, $Registry>,
+				ticket: String,
+			) -> Result<Vec<String>> {
+				let workspace_mutex = workspace_registry
+					.lock()
+					.unwrap()
+					.get(workspace)
+					.cloned()
+					.ok_or(Error::NoSuchWorkspace(workspace))?;
+				run_blocking(workspace_mutex, move |workspace| {
+					let labels = workspace.ticket(&ticket)?.labels()?;
+					Ok(labels)
+				})
+				.await
+			}
+
+			#[tauri::command]
+			async fn [<$prefix _ticket_add_label>](
+				workspace: WorkspaceKey,
+				workspace_registry: State<'_, $Registry>,
+				ticket: String,
+				label: String,
+			) -> Result<$Record> {
+				let workspace_mutex = workspace_registry
+					.lock()
+					.unwrap()
+					.get(workspace)
+					.cloned()
+					.ok_or(Error::NoSuchWorkspace(workspace))?;
+				run_blocking(workspace_mutex, move |workspace| {
+					let record = workspace.ticket(&ticket)?.add_label(&label)?.into();
+					Ok(record)
+				})
+				.await
+			}
+
+			#[tauri::command]
+			async fn [<$prefix _ticket_remove_label>](
+				workspace: WorkspaceKey,
+				workspace_registry: State<'_, $Registry>,
+				ticket: String,
+				label: String,
+			) -> Result<Option<$Record>> {
+				let workspace_mutex = workspace_registry
+					.lock()
+					.unwrap()
+					.get(workspace)
+					.cloned()
+					.ok_or(Error::NoSuchWorkspace(workspace))?;
+				run_blocking(workspace_mutex, move |workspace| {
+					let record = workspace
+						.ticket(&ticket)?
+						.remove_label(&label)?
+						.map(Into::into);
+					Ok(record)
+				})
+				.await
+			}
+
+			#[tauri::command]
+			async fn [<$prefix _ticket_assignees>](
+				workspace: WorkspaceKey,
+				workspace_registry: State<'_, $Registry>,
+				ticket: String,
+			) -> Result<Vec<String>> {
+				let workspace_mutex = workspace_registry
+					.lock()
+					.unwrap()
+					.get(workspace)
+					.cloned()
+					.ok_or(Error::NoSuchWorkspace(workspace))?;
+				run_blocking(workspace_mutex, move |workspace| {
+					let assignees = workspace.ticket(&ticket)?.assignees()?;
+					Ok(assignees)
+				})
+				.await
+			}
+
+			#[tauri::command]
+			async fn [<$prefix _ticket_add_assignee>](
+				workspace: WorkspaceKey,
+				workspace_registry: State<'_, $Registry>,
+				ticket: String,
+				user: String,
+			) -> Result<$Record> {
+				let workspace_mutex = workspace_registry
+					.lock()
+					.unwrap()
+					.get(workspace)
+					.cloned()
+					.ok_or(Error::NoSuchWorkspace(workspace))?;
+				run_blocking(workspace_mutex, move |workspace| {
+					let record = workspace.ticket(&ticket)?.add_assignee(&user)?.into();
+					Ok(record)
+				})
+				.await
+			}
+
+			#[tauri::command]
+			async fn [<$prefix _ticket_remove_assignee>](
+				workspace: WorkspaceKey,
+				workspace_registry: State<'_, $Registry>,
+				ticket: String,
+				user: String,
+			) -> Result<Option<$Record>> {
+				let workspace_mutex = workspace_registry
+					.lock()
+					.unwrap()
+					.get(workspace)
+					.cloned()
+					.ok_or(Error::NoSuchWorkspace(workspace))?;
+				run_blocking(workspace_mutex, move |workspace| {
+					let record = workspace
+						.ticket(&ticket)?
+						.remove_assignee(&user)?
+						.map(Into::into);
+					Ok(record)
+				})
+				.await
 			}
 		}
 	};
 }
 
-remote_backend_impl!(WorkspaceRegistry, TauriRecord<impl Record>, mem);
-remote_backend_impl!(GitWorkspaceRegistry, ConcreteTauriRecord, git);
+remote_backend_impl!(WorkspaceRegistry<'static>, ConcreteTauriRecord, mem);
+remote_backend_impl!(GitWorkspaceRegistry<'static>, ConcreteTauriRecord, git);
 
 impl Serialize for WorkspaceKey {
 	fn serialize<S>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error>
@@ -502,16 +1284,30 @@ impl<'a> Deserialize<'a> for WorkspaceKey {
 pub(crate) enum Error {
 	#[error("tauri error: {0}")]
 	Tauri(#[from] tauri::Error),
+	/// Kept as its message, since core errors aren't all `Send`, which
+	/// commands running off the main thread need.
 	#[error("minimap error: {0}")]
-	Minimap(#[from] minimap_core::Error),
+	Minimap(String),
 	#[error("no such workspace: {0:?}")]
 	NoSuchWorkspace(WorkspaceKey),
+	#[error("json parsing error: {0}")]
+	Json(#[from] serde_json::Error),
 	#[error("toml parsing error: {0}")]
 	TomlDe(#[from] toml::de::Error),
 	#[error("toml serialization error: {0}")]
 	TomlSer(#[from] toml::ser::Error),
 	#[error("no configuration directory found")]
 	NoConfigDir,
+	#[error("{0} already exists")]
+	FileExists(String),
+	#[error("{0} changes haven't been pushed yet")]
+	PendingChanges(usize),
+}
+
+impl From<minimap_core::Error> for Error {
+	fn from(e: minimap_core::Error) -> Self {
+		Self::Minimap(e.to_string())
+	}
 }
 
 impl serde::ser::Serialize for Error {
@@ -525,50 +1321,78 @@ impl serde::ser::Serialize for Error {
 
 pub(crate) type Result<T> = std::result::Result<T, Error>;
 
+/// Runs an operation on a workspace on the blocking task pool, so that
+/// neither the main thread nor the async runtime wait for its lock, or
+/// for the network.
+async fn run_blocking<W, T>(
+	workspace: Arc<Mutex<W>>,
+	operation: impl FnOnce(&W) -> Result<T> + Send + 'static,
+) -> Result<T>
+where
+	W: Send + 'static,
+	T: Send + 'static,
+{
+	tauri::async_runtime::spawn_blocking(move || operation(&workspace.lock().unwrap())).await?
+}
+
 type WorkspaceRegistry<'a> = Mutex<SlotMap<WorkspaceKey, Arc<Mutex<Workspace<'a, MemoryRemote>>>>>;
 
+/// Git workspaces are observed, to push their changes to the frontend
+/// (see [`sync`]).
+type GitWorkspace<'a> = Workspace<'a, ObservedRemote<GitRemote>>;
+
 #[derive(Default)]
 struct GitWorkspaceRegistry<'a> {
-	inner: Mutex<SlotMap<WorkspaceKey, Arc<Mutex<Workspace<'a, GitRemote>>>>>,
+	inner: Mutex<SlotMap<WorkspaceKey, Arc<Mutex<GitWorkspace<'a>>>>>,
 	remotes: Mutex<HashMap<String, WorkspaceKey>>,
+	syncers: Mutex<SecondaryMap<WorkspaceKey, Arc<Syncer>>>,
 }
 
 impl<'a> GitWorkspaceRegistry<'a> {
 	fn lock(
 		&self,
 	) -> std::result::Result<
-		std::sync::MutexGuard<'_, SlotMap<WorkspaceKey, Arc<Mutex<Workspace<'a, GitRemote>>>>>,
+		std::sync::MutexGuard<'_, SlotMap<WorkspaceKey, Arc<Mutex<GitWorkspace<'a>>>>>,
 		std::sync::PoisonError<
-			std::sync::MutexGuard<'_, SlotMap<WorkspaceKey, Arc<Mutex<Workspace<'a, GitRemote>>>>>,
+			std::sync::MutexGuard<'_, SlotMap<WorkspaceKey, Arc<Mutex<GitWorkspace<'a>>>>>,
 		>,
 	> {
 		self.inner.lock()
 	}
-}
-
-#[derive(Debug)]
-struct TauriRecord<R: Record>(R);
 
-impl<R: Record> From<R> for TauriRecord<R> {
-	#[inline]
-	fn from(r: R) -> Self {
-		Self(r)
+	fn syncer(&self, workspace: WorkspaceKey) -> Result<Arc<Syncer>> {
+		self.syncers
+			.lock()
+			.unwrap()
+			.get(workspace)
+			.cloned()
+			.ok_or(Error::NoSuchWorkspace(workspace))
 	}
 }
 
-impl<R: Record> serde::ser::Serialize for TauriRecord<R> {
-	fn serialize<S>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error>
-	where
-		S: serde::Serializer,
-	{
-		use serde::ser::SerializeMap;
-		let mut map = serializer.serialize_map(Some(5))?;
-		map.serialize_entry("id", &self.0.id())?;
-		map.serialize_entry("author", &self.0.author())?;
-		map.serialize_entry("email", &self.0.email())?;
-		map.serialize_entry("timestamp", &self.0.timestamp())?;
-		map.serialize_entry("message", &self.0.message())?;
-		map.end()
+impl GitWorkspaceRegistry<'static> {
+	/// Registers a workspace opened on a remote, and starts syncing it.
+	/// If another window opened the remote in the meantime, its workspace
+	/// is kept instead.
+	fn insert(
+		&self,
+		app: tauri::AppHandle,
+		remote: String,
+		workspace: GitWorkspace<'static>,
+	) -> WorkspaceKey {
+		let mut remotes = self.remotes.lock().unwrap();
+		if let Some(&key) = remotes.get(&remote) {
+			return key;
+		}
+
+		let workspace = Arc::new(Mutex::new(workspace));
+		let key = self.inner.lock().unwrap().insert(workspace.clone());
+		remotes.insert(remote, key);
+		self.syncers
+			.lock()
+			.unwrap()
+			.insert(key, Syncer::start(app, key, &workspace));
+		key
 	}
 }
 
@@ -594,43 +1418,318 @@ impl<R: Record> From<R> for ConcreteTauriRecord {
 	}
 }
 
+/// A project along with what's needed to list it, as returned by the
+/// `*_workspace_projects_detailed` commands.
+#[derive(serde::Serialize)]
+struct ProjectDetails<R> {
+	slug: String,
+	name: Option<R>,
+	description: Option<R>,
+	parent: Option<String>,
+	open_tickets: usize,
+}
+
+/// Which tickets the `*_project_tickets` commands list. Archived tickets
+/// are left out unless `archived` is set, in which case only they are
+/// listed.
+#[derive(Default, serde::Deserialize)]
+struct TicketFilter {
+	state: Option<String>,
+	#[serde(default)]
+	labels: Vec<String>,
+	assignee: Option<String>,
+	text: Option<String>,
+	#[serde(default)]
+	archived: bool,
+}
+
+impl TicketFilter {
+	fn into_query(self) -> Result<TicketQuery> {
+		let mut query = TicketQuery::new().archived(self.archived);
+		if let Some(state) = self.state {
+			query = query.state(TicketState::try_from(state)?);
+		}
+		for label in &self.labels {
+			query = query.label(label);
+		}
+		if let Some(assignee) = &self.assignee {
+			query = query.assignee(assignee);
+		}
+		if let Some(text) = &self.text {
+			query = query.text(text);
+		}
+		Ok(query)
+	}
+}
+
+/// A ticket, as listed by the `*_project_tickets` and
+/// `*_project_query_tickets` commands.
+#[derive(serde::Serialize)]
+struct TicketSummary {
+	slug: String,
+	title: Option<String>,
+	state: TicketState,
+	labels: Vec<String>,
+	assignees: Vec<String>,
+}
+
+impl TicketSummary {
+	fn new<'a, R: Remote<'a>>(ticket: &Ticket<'a, R>) -> Result<Self> {
+		Ok(Self {
+			slug: ticket.slug().to_string(),
+			title: ticket.title()?.map(|r| r.message()),
+			state: ticket.state()?.0,
+			labels: ticket.labels()?,
+			assignees: ticket.assignees()?,
+		})
+	}
+}
+
+/// A page of comments, as returned by the `*_ticket_comments_page`
+/// commands. `next` is the cursor to pass back in for the following page,
+/// or `None` after the last one.
+#[derive(serde::Serialize)]
+struct CommentPage<R> {
+	comments: Vec<R>,
+	next: Option<String>,
+}
+
+/// Opens a new, empty workspace, and shows it in the window.
 #[tauri::command]
 fn mem_workspace_open(
+	window: Window,
 	workspace_registry: State<WorkspaceRegistry>,
-	workspace_key: State<Mutex<Option<WorkspaceKey>>>,
+	session: State<Session>,
+	author: String,
+	email: String,
 ) -> Result<WorkspaceKey> {
-	if let Some(ref k) = workspace_key.lock().unwrap().clone() {
-		return Ok(*k);
-	}
-
-	let workspace = Workspace::open(MemoryRemote::new("Max Mustermann", "max@example.com"));
+	let workspace = Workspace::open(MemoryRemote::new(&author, &email))?;
 	let key = workspace_registry
 		.lock()
 		.unwrap()
 		.insert(Arc::new(Mutex::new(workspace)));
-	workspace_key.lock().unwrap().replace(key);
+	session.attach(&window, WorkspaceKind::Mem, key);
 	Ok(key)
 }
 
 #[tauri::command]
-fn git_workspace_open(
-	workspace_registry: State<GitWorkspaceRegistry>,
+fn mem_workspace_close(
+	workspace: WorkspaceKey,
+	workspace_registry: State<WorkspaceRegistry>,
+	session: State<Session>,
+) -> Result<()> {
+	workspace_registry
+		.lock()
+		.unwrap()
+		.remove(workspace)
+		.ok_or(Error::NoSuchWorkspace(workspace))?;
+	session.forget_workspace(WorkspaceKind::Mem, workspace);
+	Ok(())
+}
+
+/// Opens the workspace on a remote, and shows it in the window. Windows
+/// opening the same remote share its workspace.
+#[tauri::command]
+async fn git_workspace_open(
+	app: tauri::AppHandle,
+	window: Window,
+	workspace_registry: State<'_, GitWorkspaceRegistry<'static>>,
+	credential_broker: State<'_, Arc<CredentialBroker>>,
+	session: State<'_, Session>,
+	remote: String,
+) -> Result<WorkspaceKey> {
+	let open = workspace_registry
+		.remotes
+		.lock()
+		.unwrap()
+		.get(&remote)
+		.copied();
+	if let Some(key) = open {
+		session.attach(&window, WorkspaceKind::Git, key);
+		return Ok(key);
+	}
+
+	// Opening may clone the remote, asking for credentials to do so.
+	let (url, credential_broker) = (remote.clone(), credential_broker.inner().clone());
+	let workspace = tauri::async_runtime::spawn_blocking(move || -> Result<_> {
+		let git_remote =
+			GitRemote::open_with_credentials(&url, credential_broker)?.with_offline_queue()?;
+		Ok(Workspace::open(ObservedRemote::new(git_remote))?)
+	})
+	.await??;
+	let key = workspace_registry.insert(app, remote, workspace);
+	session.attach(&window, WorkspaceKind::Git, key);
+	Ok(key)
+}
+
+/// Initializes a new workspace on an empty remote (see
+/// [`Workspace::init`]), and shows it in the window.
+#[tauri::command]
+async fn git_workspace_init(
+	app: tauri::AppHandle,
+	window: Window,
+	workspace_registry: State<'_, GitWorkspaceRegistry<'static>>,
+	credential_broker: State<'_, Arc<CredentialBroker>>,
+	session: State<'_, Session>,
 	remote: String,
 ) -> Result<WorkspaceKey> {
-	let workspace = Workspace::open(GitRemote::open(&remote)?);
-	let key = {
-		workspace_registry
-			.inner
+	let (url, credential_broker) = (remote.clone(), credential_broker.inner().clone());
+	let workspace = tauri::async_runtime::spawn_blocking(move || -> Result<_> {
+		let git_remote =
+			GitRemote::open_with_credentials(&url, credential_broker)?.with_offline_queue()?;
+		Ok(Workspace::init(ObservedRemote::new(git_remote))?)
+	})
+	.await??;
+	let key = workspace_registry.insert(app, remote, workspace);
+	session.attach(&window, WorkspaceKind::Git, key);
+	Ok(key)
+}
+
+/// Closes a workspace, unless changes made while its remote couldn't be
+/// reached are still waiting to be pushed (see `git_workspace_pending`)
+/// and closing isn't forced. Forcing it keeps them queued in the local
+/// repository, to be pushed once the workspace is opened again.
+#[tauri::command]
+async fn git_workspace_close(
+	workspace: WorkspaceKey,
+	workspace_registry: State<'_, GitWorkspaceRegistry<'static>>,
+	session: State<'_, Session>,
+	force: Option<bool>,
+) -> Result<()> {
+	if !force.unwrap_or(false) {
+		let syncer = workspace_registry.syncer(workspace)?;
+		let workspace_mutex = workspace_registry
 			.lock()
 			.unwrap()
-			.insert(Arc::new(Mutex::new(workspace)))
-	};
+			.get(workspace)
+			.cloned()
+			.ok_or(Error::NoSuchWorkspace(workspace))?;
+		let pending = run_blocking(workspace_mutex, move |workspace| {
+			Ok(syncer.pending(workspace)?)
+		})
+		.await?;
+		if pending > 0 {
+			return Err(Error::PendingChanges(pending));
+		}
+	}
+
+	workspace_registry
+		.lock()
+		.unwrap()
+		.remove(workspace)
+		.ok_or(Error::NoSuchWorkspace(workspace))?;
 	workspace_registry
 		.remotes
 		.lock()
 		.unwrap()
-		.insert(remote, key);
-	Ok(key)
+		.retain(|_, key| *key != workspace);
+	// Its thread stops once the workspace is dropped too, i.e. once the
+	// commands still using it are done.
+	workspace_registry.syncers.lock().unwrap().remove(workspace);
+	session.forget_workspace(WorkspaceKind::Git, workspace);
+	Ok(())
+}
+
+#[tauri::command]
+async fn git_workspace_sync(
+	workspace: WorkspaceKey,
+	workspace_registry: State<'_, GitWorkspaceRegistry<'static>>,
+) -> Result<Vec<String>> {
+	let syncer = workspace_registry.syncer(workspace)?;
+	let workspace_mutex = workspace_registry
+		.lock()
+		.unwrap()
+		.get(workspace)
+		.cloned()
+		.ok_or(Error::NoSuchWorkspace(workspace))?;
+	run_blocking(
+		workspace_mutex,
+		move |workspace| Ok(syncer.sync(workspace)?),
+	)
+	.await
+}
+
+/// Counts the changes made while the remote couldn't be reached, which are
+/// waiting to be pushed.
+#[tauri::command]
+async fn git_workspace_pending(
+	workspace: WorkspaceKey,
+	workspace_registry: State<'_, GitWorkspaceRegistry<'static>>,
+) -> Result<usize> {
+	let syncer = workspace_registry.syncer(workspace)?;
+	let workspace_mutex = workspace_registry
+		.lock()
+		.unwrap()
+		.get(workspace)
+		.cloned()
+		.ok_or(Error::NoSuchWorkspace(workspace))?;
+	run_blocking(workspace_mutex, move |workspace| {
+		Ok(syncer.pending(workspace)?)
+	})
+	.await
+}
+
+/// Retries pushing the changes made while the remote couldn't be reached.
+/// Returns the number still pending.
+#[tauri::command]
+async fn git_workspace_flush_pending(
+	workspace: WorkspaceKey,
+	workspace_registry: State<'_, GitWorkspaceRegistry<'static>>,
+) -> Result<usize> {
+	let syncer = workspace_registry.syncer(workspace)?;
+	let workspace_mutex = workspace_registry
+		.lock()
+		.unwrap()
+		.get(workspace)
+		.cloned()
+		.ok_or(Error::NoSuchWorkspace(workspace))?;
+	run_blocking(workspace_mutex, move |workspace| {
+		Ok(syncer.flush_pending(workspace)?)
+	})
+	.await
+}
+
+#[tauri::command]
+fn git_workspace_last_sync(
+	workspace: WorkspaceKey,
+	workspace_registry: State<GitWorkspaceRegistry>,
+) -> Result<Option<i64>> {
+	Ok(workspace_registry.syncer(workspace)?.synced_at())
+}
+
+/// Sets how often the workspace is synced, in seconds; `None` (or zero)
+/// only syncs it when forced.
+#[tauri::command]
+fn git_workspace_set_sync_interval(
+	workspace: WorkspaceKey,
+	workspace_registry: State<GitWorkspaceRegistry>,
+	interval: Option<u64>,
+) -> Result<()> {
+	let interval = interval
+		.filter(|&seconds| seconds > 0)
+		.map(Duration::from_secs);
+	workspace_registry.syncer(workspace)?.set_interval(interval);
+	Ok(())
+}
+
+/// Writes data to a file, which must not exist yet unless `overwrite`.
+fn write_file(path: &str, data: &[u8], overwrite: bool) -> Result<()> {
+	use std::io::Write;
+	let file = std::fs::OpenOptions::new()
+		.write(true)
+		.create_new(!overwrite)
+		.create(overwrite)
+		.truncate(overwrite)
+		.open(path);
+	let mut file = match file {
+		Ok(file) => file,
+		Err(e) if e.kind() == std::io::ErrorKind::AlreadyExists => {
+			return Err(Error::FileExists(path.to_string()));
+		}
+		Err(e) => return Err(minimap_core::Error::Io(e).into()),
+	};
+	file.write_all(data).map_err(minimap_core::Error::Io)?;
+	Ok(())
 }
 
 fn get_config_path() -> Result<std::path::PathBuf> {
@@ -670,32 +1769,70 @@ fn main() {
 	tauri::Builder::default()
 		.manage(WorkspaceRegistry::default())
 		.manage(GitWorkspaceRegistry::default())
-		.manage::<Mutex<Option<WorkspaceKey>>>(Mutex::default())
+		.manage(Session::default())
+		.manage(LaunchLink::from_args())
+		.setup(|app| {
+			app.manage(Arc::new(CredentialBroker::new(app.handle())));
+			Ok(())
+		})
+		.on_window_event(|event| {
+			if let WindowEvent::Destroyed = event.event() {
+				let window = event.window();
+				window.state::<Session>().forget_window(window.label());
+			}
+		})
 		.invoke_handler(tauri::generate_handler![
 			config_store,
 			config_load,
+			credentials::credential_respond,
+			deep_link::deep_link_take,
+			deep_link::deep_link_parse,
+			deep_link::deep_link_open,
+			session::workspace_list,
+			session::window_workspace,
+			session::window_set_workspace,
 			mem_workspace_open,
+			mem_workspace_close,
 			mem_workspace_name,
 			mem_workspace_set_name,
 			mem_workspace_description,
 			mem_workspace_set_description,
 			mem_workspace_create_project,
 			mem_workspace_projects,
+			mem_workspace_projects_with_meta,
+			mem_workspace_projects_detailed,
+			mem_workspace_export,
 			mem_workspace_delete_project,
 			mem_project_set_name,
 			mem_project_set_description,
 			mem_project_name,
 			mem_project_description,
+			mem_project_create_subproject,
+			mem_project_parent,
+			mem_project_children,
+			mem_project_labels,
+			mem_project_icon_base64,
+			mem_project_set_icon,
 			mem_project_create_ticket,
+			mem_project_tickets,
+			mem_project_query_tickets,
+			mem_project_delete_ticket,
+			mem_project_restore_ticket,
 			mem_ticket_title,
 			mem_ticket_set_title,
+			mem_ticket_description,
+			mem_ticket_set_description,
+			mem_ticket_view,
 			mem_ticket_add_comment,
 			mem_ticket_comments,
+			mem_ticket_comments_page,
 			mem_ticket_upsert_attachment,
 			mem_ticket_upsert_attachment_filepath,
 			mem_ticket_remove_attachment,
 			mem_ticket_attachment,
 			mem_ticket_attachment_base64,
+			mem_ticket_attachment_save,
+			mem_ticket_attachment_info,
 			mem_ticket_state,
 			mem_ticket_set_state,
 			mem_workspace_create_project,
@@ -717,33 +1854,67 @@ fn main() {
 			mem_ticket_attachment_base64,
 			mem_ticket_state,
 			mem_ticket_set_state,
+			mem_ticket_effective_state,
 			mem_ticket_is_open,
 			mem_ticket_is_closed,
 			mem_ticket_dependencies,
+			mem_ticket_resolved_dependencies,
 			mem_ticket_add_dependency,
 			mem_ticket_remove_dependency,
+			mem_ticket_labels,
+			mem_ticket_add_label,
+			mem_ticket_remove_label,
+			mem_ticket_assignees,
+			mem_ticket_add_assignee,
+			mem_ticket_remove_assignee,
 			git_workspace_open,
+			git_workspace_init,
+			git_workspace_close,
+			git_workspace_sync,
+			git_workspace_pending,
+			git_workspace_flush_pending,
+			git_workspace_last_sync,
+			git_workspace_set_sync_interval,
 			git_workspace_name,
 			git_workspace_set_name,
 			git_workspace_description,
 			git_workspace_set_description,
 			git_workspace_create_project,
 			git_workspace_projects,
+			git_workspace_projects_with_meta,
+			git_workspace_projects_detailed,
+			git_workspace_export,
 			git_workspace_delete_project,
 			git_project_set_name,
 			git_project_set_description,
 			git_project_name,
 			git_project_description,
+			git_project_create_subproject,
+			git_project_parent,
+			git_project_children,
+			git_project_labels,
+			git_project_icon_base64,
+			git_project_set_icon,
 			git_project_create_ticket,
+			git_project_tickets,
+			git_project_query_tickets,
+			git_project_delete_ticket,
+			git_project_restore_ticket,
 			git_ticket_title,
 			git_ticket_set_title,
+			git_ticket_description,
+			git_ticket_set_description,
+			git_ticket_view,
 			git_ticket_add_comment,
 			git_ticket_comments,
+			git_ticket_comments_page,
 			git_ticket_upsert_attachment,
 			git_ticket_upsert_attachment_filepath,
 			git_ticket_remove_attachment,
 			git_ticket_attachment,
 			git_ticket_attachment_base64,
+			git_ticket_attachment_save,
+			git_ticket_attachment_info,
 			git_ticket_state,
 			git_ticket_set_state,
 			git_workspace_create_project,
@@ -765,11 +1936,19 @@ fn main() {
 			git_ticket_attachment_base64,
 			git_ticket_state,
 			git_ticket_set_state,
+			git_ticket_effective_state,
 			git_ticket_is_open,
 			git_ticket_is_closed,
 			git_ticket_dependencies,
+			git_ticket_resolved_dependencies,
 			git_ticket_add_dependency,
 			git_ticket_remove_dependency,
+			git_ticket_labels,
+			git_ticket_add_label,
+			git_ticket_remove_label,
+			git_ticket_assignees,
+			git_ticket_add_assignee,
+			git_ticket_remove_assignee,
 		])
 		.run(tauri::generate_context!())
 		.expect("error while running tauri application");
@@ -0,0 +1,66 @@
+//! Dependency origins, as configured in the app's configuration.
+//!
+//! Besides `minimap`, the `date` origin is always available. The
+//! `crates-io` one isn't, as crates.io is only served over HTTPS, which
+//! the app has no client for. Other origins are HTTP ones, configured by
+//! slug under `origins` (the same table the CLI keeps its tokens in):
+//!
+//! ```toml
+//! [origins.tracker]
+//! kind = "http"
+//! json-pointer = "/state"
+//! complete = ["closed", "resolved"]
+//! ```
+//!
+//! With neither `json-pointer` nor `regex`, an endpoint is complete once
+//! its URL responds successfully (see [`HttpStatusRule`]).
+
+use crate::{config_load, Result};
+use minimap_core::{
+	DateDependencyOrigin, DependencyRegistry, HttpDependencyOrigin, HttpStatusRule,
+};
+use std::collections::BTreeMap;
+
+#[derive(serde::Deserialize)]
+#[serde(rename_all = "kebab-case")]
+struct OriginSettings {
+	/// Only `http` origins are registered; the others are settings of the
+	/// built-in origins (e.g. the CLI's tokens).
+	kind: Option<String>,
+	json_pointer: Option<String>,
+	regex: Option<String>,
+	#[serde(default)]
+	complete: Vec<String>,
+}
+
+/// Builds a registry of the configured origins.
+pub(crate) fn dependency_registry() -> Result<DependencyRegistry> {
+	let mut registry = DependencyRegistry::new();
+	registry.register(Box::new(DateDependencyOrigin::new()))?;
+
+	let origins = match config_load()?.get("origins") {
+		Some(origins) => origins
+			.clone()
+			.try_into::<BTreeMap<String, OriginSettings>>()?,
+		None => BTreeMap::new(),
+	};
+	for (slug, origin) in origins {
+		if origin.kind.as_deref() != Some("http") {
+			continue;
+		}
+
+		let complete = origin
+			.complete
+			.iter()
+			.map(String::as_str)
+			.collect::<Vec<_>>();
+		let rule = match (origin.json_pointer, origin.regex) {
+			(Some(pointer), _) => HttpStatusRule::json_pointer(&pointer, &complete),
+			(None, Some(pattern)) => HttpStatusRule::regex(&pattern, &complete)?,
+			(None, None) => HttpStatusRule::success(),
+		};
+		registry.register(Box::new(HttpDependencyOrigin::new(&slug).with_rule(rule)))?;
+	}
+
+	Ok(registry)
+}
@@ -0,0 +1,172 @@
+//! The workspaces open in the app, and the windows showing them.
+//!
+//! Any number of memory and git workspaces can be open at once. Each
+//! window shows at most one of them: the one it last opened, or switched
+//! to with `window_set_workspace`. A reloaded window finds its workspace
+//! again with `window_workspace`, and a workspace switcher lists them all
+//! with `workspace_list`.
+
+use crate::{Error, GitWorkspaceRegistry, Result, WorkspaceKey, WorkspaceRegistry};
+use minimap_core::Record;
+use std::{collections::HashMap, sync::Mutex};
+use tauri::{State, Window};
+
+/// The backend of a workspace, i.e. the prefix of its commands. Memory and
+/// git workspaces are registered separately, so their keys may coincide.
+#[derive(Clone, Copy, PartialEq, Eq, Debug, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub(crate) enum WorkspaceKind {
+	Mem,
+	Git,
+}
+
+/// An open workspace, as windows refer to it.
+#[derive(Clone, Copy, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub(crate) struct WindowWorkspace {
+	kind: WorkspaceKind,
+	workspace: WorkspaceKey,
+}
+
+/// The workspace each window shows, by window label.
+#[derive(Default)]
+pub(crate) struct Session {
+	windows: Mutex<HashMap<String, WindowWorkspace>>,
+}
+
+impl Session {
+	/// Shows a workspace in a window, instead of the one it showed.
+	pub(crate) fn attach(&self, window: &Window, kind: WorkspaceKind, workspace: WorkspaceKey) {
+		self.windows.lock().unwrap().insert(
+			window.label().to_string(),
+			WindowWorkspace { kind, workspace },
+		);
+	}
+
+	/// Forgets about a closed workspace; the windows showing it show none.
+	pub(crate) fn forget_workspace(&self, kind: WorkspaceKind, workspace: WorkspaceKey) {
+		let closed = WindowWorkspace { kind, workspace };
+		self.windows
+			.lock()
+			.unwrap()
+			.retain(|_, shown| *shown != closed);
+	}
+
+	/// Forgets about a closed window.
+	pub(crate) fn forget_window(&self, label: &str) {
+		self.windows.lock().unwrap().remove(label);
+	}
+}
+
+/// An open workspace, as listed by `workspace_list`.
+#[derive(serde::Serialize)]
+pub(crate) struct WorkspaceEntry {
+	kind: WorkspaceKind,
+	workspace: WorkspaceKey,
+	name: Option<String>,
+	/// The remote git workspaces were opened on.
+	remote: Option<String>,
+	/// The labels of the windows showing the workspace.
+	windows: Vec<String>,
+}
+
+/// Lists the open workspaces, memory ones first.
+#[tauri::command]
+pub(crate) async fn workspace_list(
+	mem_registry: State<'_, WorkspaceRegistry<'static>>,
+	git_registry: State<'_, GitWorkspaceRegistry<'static>>,
+	session: State<'_, Session>,
+) -> Result<Vec<WorkspaceEntry>> {
+	let mem = mem_registry
+		.lock()
+		.unwrap()
+		.iter()
+		.map(|(key, workspace)| (key, workspace.clone()))
+		.collect::<Vec<_>>();
+	let git = git_registry
+		.lock()
+		.unwrap()
+		.iter()
+		.map(|(key, workspace)| (key, workspace.clone()))
+		.collect::<Vec<_>>();
+	let remotes = git_registry
+		.remotes
+		.lock()
+		.unwrap()
+		.iter()
+		.map(|(remote, key)| (*key, remote.clone()))
+		.collect::<HashMap<_, _>>();
+	let windows = session.windows.lock().unwrap().clone();
+	let windows_of = move |kind, workspace| {
+		let shown = WindowWorkspace { kind, workspace };
+		let mut labels = windows
+			.iter()
+			.filter(|(_, workspace)| **workspace == shown)
+			.map(|(label, _)| label.clone())
+			.collect::<Vec<_>>();
+		labels.sort();
+		labels
+	};
+
+	// Names are read off the main thread, as for the other commands, since
+	// a workspace may be busy syncing.
+	tauri::async_runtime::spawn_blocking(move || {
+		let mut entries = vec![];
+		for (workspace, workspace_mutex) in mem {
+			let workspace_guard = workspace_mutex.lock().unwrap();
+			entries.push(WorkspaceEntry {
+				kind: WorkspaceKind::Mem,
+				workspace,
+				name: workspace_guard.name()?.map(|name| name.message()),
+				remote: None,
+				windows: windows_of(WorkspaceKind::Mem, workspace),
+			});
+		}
+		for (workspace, workspace_mutex) in git {
+			let workspace_guard = workspace_mutex.lock().unwrap();
+			entries.push(WorkspaceEntry {
+				kind: WorkspaceKind::Git,
+				workspace,
+				name: workspace_guard.name()?.map(|name| name.message()),
+				remote: remotes.get(&workspace).cloned(),
+				windows: windows_of(WorkspaceKind::Git, workspace),
+			});
+		}
+		Ok(entries)
+	})
+	.await?
+}
+
+/// Gets the workspace the window shows, if any.
+#[tauri::command]
+pub(crate) fn window_workspace(
+	window: Window,
+	session: State<Session>,
+) -> Result<Option<WindowWorkspace>> {
+	Ok(session.windows.lock().unwrap().get(window.label()).copied())
+}
+
+/// Switches the window to another open workspace, or to none.
+#[tauri::command]
+pub(crate) fn window_set_workspace(
+	window: Window,
+	session: State<Session>,
+	mem_registry: State<WorkspaceRegistry>,
+	git_registry: State<GitWorkspaceRegistry>,
+	workspace: Option<WindowWorkspace>,
+) -> Result<()> {
+	let Some(WindowWorkspace { kind, workspace }) = workspace else {
+		session.forget_window(window.label());
+		return Ok(());
+	};
+
+	let open = match kind {
+		WorkspaceKind::Mem => mem_registry.lock().unwrap().contains_key(workspace),
+		WorkspaceKind::Git => git_registry.lock().unwrap().contains_key(workspace),
+	};
+	if !open {
+		return Err(Error::NoSuchWorkspace(workspace));
+	}
+
+	session.attach(&window, kind, workspace);
+	Ok(())
+}
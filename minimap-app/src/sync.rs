@@ -0,0 +1,263 @@
+//! Background syncing of git workspaces.
+//!
+//! Git workspaces are opened on an [`ObservedRemote`], which queues up the
+//! records committed through it. A thread per workspace turns those into
+//! [change events](crate::events) once the command that made the changes
+//! has let go of the workspace, and fetches the workspace every so often
+//! (see [`Syncer::set_interval`]) to do the same for the records that came
+//! in from elsewhere. Each fetch is followed by a [`SYNCED`] event listing
+//! the collections it changed, so the frontend knows what to drop from its
+//! caches. Syncs can also be forced, e.g. when the app regains focus.
+//!
+//! Writes made while the remote can't be reached are queued (see
+//! [`GitRemote::with_offline_queue`]), and [`PENDING_CHANGED`] events
+//! report how many are. Each sync pushes them first, and they can be
+//! flushed on demand too.
+//!
+//! [`ObservedRemote`]: minimap_core::ObservedRemote
+//! [`GitRemote::with_offline_queue`]: minimap_core::GitRemote::with_offline_queue
+
+use crate::{
+	events::{self, Change},
+	GitWorkspace, WorkspaceKey,
+};
+use minimap_core::{Clock, FetchChange, Record, RecordEvent, Remote, SystemClock};
+use std::{
+	sync::{mpsc, Arc, Mutex, Weak},
+	time::{Duration, Instant},
+};
+use tauri::{AppHandle, Manager};
+
+/// Emitted after each fetch, with a [`Synced`] payload.
+pub(crate) const SYNCED: &str = "minimap://synced";
+
+/// Emitted when the number of changes waiting to be pushed changes, with a
+/// [`Pending`] payload.
+pub(crate) const PENDING_CHANGED: &str = "minimap://pending-changed";
+
+/// How often workspaces are fetched, until told otherwise.
+const DEFAULT_INTERVAL: Duration = Duration::from_secs(30);
+
+#[derive(Clone, serde::Serialize)]
+struct Synced {
+	workspace: WorkspaceKey,
+	/// When the fetch finished, in seconds since the Unix epoch.
+	timestamp: i64,
+	/// The collections the fetch created, updated or deleted.
+	collections: Vec<String>,
+}
+
+#[derive(Clone, serde::Serialize)]
+struct Pending {
+	workspace: WorkspaceKey,
+	/// The number of changes queued while offline.
+	pending: usize,
+}
+
+enum Message {
+	/// A record was committed to the workspace.
+	Committed(Change),
+	/// The interval changed, so the next sync is due at another time.
+	Reschedule,
+}
+
+struct Schedule {
+	/// How often to sync, or `None` to only sync when forced.
+	interval: Option<Duration>,
+	/// When the last sync was attempted.
+	attempted_at: Instant,
+	/// When the last sync succeeded, in seconds since the Unix epoch.
+	synced_at: Option<i64>,
+}
+
+/// Syncs a git workspace in the background. Dropping the syncer (along
+/// with its workspace) stops the thread.
+pub(crate) struct Syncer {
+	app: AppHandle,
+	key: WorkspaceKey,
+	schedule: Mutex<Schedule>,
+	/// The number of pending changes last reported.
+	pending: Mutex<usize>,
+	sender: mpsc::Sender<Message>,
+}
+
+impl Syncer {
+	/// Starts syncing a workspace, and emitting events for its changes.
+	pub(crate) fn start(
+		app: AppHandle,
+		key: WorkspaceKey,
+		workspace: &Arc<Mutex<GitWorkspace<'static>>>,
+	) -> Arc<Self> {
+		let (sender, receiver) = mpsc::channel();
+		let observer = sender.clone();
+		workspace
+			.lock()
+			.unwrap()
+			.remote()
+			.subscribe(Box::new(move |event: &RecordEvent| {
+				// The receiver is only gone once the workspace is.
+				let _ = observer.send(Message::Committed((
+					event.collection.clone(),
+					event.record.message.clone(),
+				)));
+			}));
+
+		let syncer = Arc::new(Self {
+			app,
+			key,
+			schedule: Mutex::new(Schedule {
+				interval: Some(DEFAULT_INTERVAL),
+				attempted_at: Instant::now(),
+				synced_at: None,
+			}),
+			pending: Mutex::new(0),
+			sender,
+		});
+		let (workspace, weak) = (Arc::downgrade(workspace), Arc::downgrade(&syncer));
+		std::thread::spawn(move || run(weak, workspace, receiver));
+
+		syncer
+	}
+
+	/// Gets when the workspace was last synced, in seconds since the Unix
+	/// epoch, or `None` if it hasn't been yet.
+	pub(crate) fn synced_at(&self) -> Option<i64> {
+		self.schedule.lock().unwrap().synced_at
+	}
+
+	/// Sets how often to sync the workspace, counting from the last sync;
+	/// `None` only syncs when forced.
+	pub(crate) fn set_interval(&self, interval: Option<Duration>) {
+		self.schedule.lock().unwrap().interval = interval;
+		// The thread is gone if the workspace is.
+		let _ = self.sender.send(Message::Reschedule);
+	}
+
+	/// Syncs the workspace now, emitting events for what changed. Returns
+	/// the collections that were created, updated or deleted.
+	pub(crate) fn sync(&self, workspace: &GitWorkspace) -> minimap_core::Result<Vec<String>> {
+		// Failed syncs (e.g. while offline) are retried after the interval,
+		// like successful ones.
+		self.schedule.lock().unwrap().attempted_at = Instant::now();
+
+		// Fetching would otherwise hide the remote's changes to collections
+		// with queued writes until they're pushed.
+		self.flush_pending(workspace)?;
+
+		let remote = workspace.remote();
+		let mut collections = vec![];
+		let mut changes = vec![];
+		for (collection, change) in remote.fetch()? {
+			match change {
+				// The records that came in are the latest ones of their collections.
+				FetchChange::Created(count) | FetchChange::Updated(count) => {
+					for record in remote.walk(&collection)?.take(count) {
+						changes.push((collection.clone(), record?.message()));
+					}
+				}
+				// Deleted collections are emitted with an empty message.
+				FetchChange::Deleted => changes.push((collection.clone(), String::new())),
+			}
+			collections.push(collection);
+		}
+
+		let timestamp = SystemClock.now();
+		self.schedule.lock().unwrap().synced_at = Some(timestamp);
+		events::emit(&self.app, self.key, workspace, &changes);
+		// Emitting only fails if the app is shutting down.
+		let _ = self.app.emit_all(
+			SYNCED,
+			Synced {
+				workspace: self.key,
+				timestamp,
+				collections: collections.clone(),
+			},
+		);
+
+		Ok(collections)
+	}
+
+	/// Pushes the changes queued while offline. Returns the number of
+	/// changes still pending, i.e. none unless it fails.
+	///
+	/// Queued changes that the remote rejects (because changes were made
+	/// elsewhere in the meantime) are replayed onto the remote's changes
+	/// and pushed again; they stay queued if that fails too (see
+	/// [`GitRemote::push_deferred`]).
+	///
+	/// [`GitRemote::push_deferred`]: minimap_core::GitRemote::push_deferred
+	pub(crate) fn flush_pending(&self, workspace: &GitWorkspace) -> minimap_core::Result<usize> {
+		let pushed = workspace.remote().inner().push_deferred();
+		let pending = self.pending(workspace)?;
+		pushed.map(|_| pending)
+	}
+
+	/// Counts the changes queued while offline, emitting a
+	/// [`PENDING_CHANGED`] event if that changed since last counted.
+	pub(crate) fn pending(&self, workspace: &GitWorkspace) -> minimap_core::Result<usize> {
+		let pending = workspace.remote().inner().deferred_changes()?;
+		let mut reported = self.pending.lock().unwrap();
+		if *reported != pending {
+			*reported = pending;
+			// Emitting only fails if the app is shutting down.
+			let _ = self.app.emit_all(
+				PENDING_CHANGED,
+				Pending {
+					workspace: self.key,
+					pending,
+				},
+			);
+		}
+		Ok(pending)
+	}
+
+	/// Gets how long until the next sync is due, if ever.
+	fn due_in(&self) -> Duration {
+		let schedule = self.schedule.lock().unwrap();
+		match schedule.interval {
+			Some(interval) => interval.saturating_sub(schedule.attempted_at.elapsed()),
+			None => Duration::MAX,
+		}
+	}
+}
+
+/// Emits events for the changes committed to a workspace as they come in,
+/// and syncs it when due, until the workspace or its syncer are dropped.
+fn run(
+	syncer: Weak<Syncer>,
+	workspace: Weak<Mutex<GitWorkspace<'static>>>,
+	messages: mpsc::Receiver<Message>,
+) {
+	loop {
+		let Some(due_in) = syncer.upgrade().map(|syncer| syncer.due_in()) else {
+			return;
+		};
+		let first = match messages.recv_timeout(due_in) {
+			Ok(message) => Some(message),
+			Err(mpsc::RecvTimeoutError::Timeout) => None,
+			Err(mpsc::RecvTimeoutError::Disconnected) => return,
+		};
+		let changes = first
+			.into_iter()
+			.chain(messages.try_iter())
+			.filter_map(|message| match message {
+				Message::Committed(change) => Some(change),
+				Message::Reschedule => None,
+			})
+			.collect::<Vec<_>>();
+
+		let (Some(syncer), Some(workspace)) = (syncer.upgrade(), workspace.upgrade()) else {
+			return;
+		};
+		let workspace = workspace.lock().unwrap();
+		events::emit(&syncer.app, syncer.key, &workspace, &changes);
+		if !changes.is_empty() {
+			// Any of them may have been queued.
+			let _ = syncer.pending(&workspace);
+		}
+		if syncer.due_in().is_zero() {
+			// Failed syncs are retried when next due.
+			let _ = syncer.sync(&workspace);
+		}
+	}
+}
@@ -1,3 +1,4 @@
+import { listen } from '@tauri-apps/api/event';
 import { invoke } from '@tauri-apps/api/tauri';
 
 class Ticket {
@@ -35,6 +36,12 @@ class Ticket {
 		return this._send('ticket_comments');
 	}
 
+	// Resolves to up to `limit` comments, latest first, and the cursor to
+	// pass back in as `after` for the next page (null after the last one).
+	/*async*/ getCommentsPage(limit, after = null) {
+		return this._send('ticket_comments_page', { after, limit });
+	}
+
 	/*async*/ getStatus() {
 		return this._send('ticket_status');
 	}
@@ -72,17 +79,57 @@ class Ticket {
 		return this._send('ticket_attachment_base64', { name });
 	}
 
+	// Saves an attachment to a file, which must not exist yet unless
+	// overwriting it. Resolves to false if there's no such attachment.
+	/*async*/ saveAttachment(name, destPath, overwrite = false) {
+		return this._send('ticket_attachment_save', { name, destPath, overwrite });
+	}
+
+	/*async*/ getEffectiveState() {
+		return this._send('ticket_effective_state');
+	}
+
 	/*async*/ getDependencies() {
 		return this._send('ticket_dependencies');
 	}
 
-	/*async*/ addDependency(origin, endpoint) {
-		return this._send('ticket_add_dependency', { origin, endpoint });
+	// Resolves to [origin, endpoint, status] triples, with statuses being
+	// 'pending' or 'complete' as reported by the configured origins.
+	/*async*/ getResolvedDependencies() {
+		return this._send('ticket_resolved_dependencies');
+	}
+
+	/*async*/ addDependency(origin, endpoint, note = null, kind = null) {
+		return this._send('ticket_add_dependency', { origin, endpoint, note, kind });
 	}
 
 	/*async*/ removeDependency(origin, endpoint) {
 		return this._send('ticket_remove_dependency', { origin, endpoint });
 	}
+
+	/*async*/ getLabels() {
+		return this._send('ticket_labels');
+	}
+
+	/*async*/ addLabel(label) {
+		return this._send('ticket_add_label', { label });
+	}
+
+	/*async*/ removeLabel(label) {
+		return this._send('ticket_remove_label', { label });
+	}
+
+	/*async*/ getAssignees() {
+		return this._send('ticket_assignees');
+	}
+
+	/*async*/ addAssignee(user) {
+		return this._send('ticket_add_assignee', { user });
+	}
+
+	/*async*/ removeAssignee(user) {
+		return this._send('ticket_remove_assignee', { user });
+	}
 }
 
 class Project {
@@ -116,10 +163,47 @@ class Project {
 		return this._send('project_set_description', { description });
 	}
 
+	// Resolves to the labels defined on the project, each with a name, and
+	// a color and description if set.
+	/*async*/ getLabels() {
+		return this._send('project_labels');
+	}
+
+	async createSubproject(subproject) {
+		await this._send('project_create_subproject', { subproject });
+		return new Project(this._workspaceId, subproject, this._prefix);
+	}
+
+	// Resolves to the parent project, or null for root projects.
+	async getParent() {
+		const parent = await this._send('project_parent');
+		return parent && new Project(this._workspaceId, parent, this._prefix);
+	}
+
+	async getChildren() {
+		const children = await this._send('project_children');
+		return children.map(
+			child => new Project(this._workspaceId, child, this._prefix)
+		);
+	}
+
 	async createTicket() {
 		const slug = await this._send('project_create_ticket');
 		return new Ticket(this._workspaceId, slug, this._prefix);
 	}
+
+	// Resolves to the slug, title, state, labels and assignees of the
+	// project's tickets. The filter may have a state, labels, an assignee,
+	// some text to search for, and whether to list archived tickets instead.
+	/*async*/ getTickets(filter = null) {
+		return this._send('project_tickets', { filter });
+	}
+
+	// Like getTickets, with the filter already serialized, e.g. as saved in
+	// the configuration.
+	/*async*/ queryTickets(filterJson) {
+		return this._send('project_query_tickets', { filterJson });
+	}
 }
 
 export class Workspace {
@@ -128,16 +212,53 @@ export class Workspace {
 		return new Workspace(id, 'git');
 	}
 
+	// Initializes a new workspace on an empty remote; use open_git for
+	// existing ones.
+	static async init_git(remote) {
+		const id = await invoke('git_workspace_init', { remote });
+		return new Workspace(id, 'git');
+	}
+
 	static async open_mem(author, email) {
 		const id = await invoke('mem_workspace_open', { author, email });
 		return new Workspace(id, 'mem');
 	}
 
+	// Resolves to the open workspaces, each as { kind, workspace, name,
+	// remote, windows }, where windows are the labels of those showing it.
+	static async list() {
+		const entries = await invoke('workspace_list');
+		return entries.map(entry => ({
+			...entry,
+			workspace: new Workspace(entry.workspace, entry.kind)
+		}));
+	}
+
+	// Resolves to the workspace this window shows, or null; e.g. to get it
+	// back after a reload.
+	static async forWindow() {
+		const shown = await invoke('window_workspace');
+		return shown && new Workspace(shown.workspace, shown.kind);
+	}
+
+	// Stops showing any workspace in this window.
+	static /*async*/ hide() {
+		return invoke('window_set_workspace', { workspace: null });
+	}
+
 	constructor(id, prefix) {
 		this._id = id;
 		this._prefix = prefix;
 	}
 
+	// Shows the workspace in this window instead of the one it showed.
+	// Opening a workspace shows it already.
+	/*async*/ show() {
+		return invoke('window_set_workspace', {
+			workspace: { kind: this._prefix, workspace: this._id }
+		});
+	}
+
 	/*async*/ _send(command, data = {}) {
 		return invoke(`${this._prefix}_${command}`, {
 			workspace: this._id,
@@ -145,6 +266,13 @@ export class Workspace {
 		});
 	}
 
+	// Closes the workspace, after which none of its methods can be called.
+	// Git workspaces with pending changes (see getPendingCount) aren't
+	// closed unless forced; they're then pushed once it's opened again.
+	/*async*/ close(force = false) {
+		return this._send('workspace_close', { force });
+	}
+
 	/*async*/ getName() {
 		return this._send('workspace_name');
 	}
@@ -174,11 +302,140 @@ export class Workspace {
 		return this._send('workspace_projects');
 	}
 
-	/*async*/ deleteProject(project) {
-		return this._send('workspace_delete_project', { project });
+	// Resolves to the slug, name and description records, parent slug and
+	// open ticket count of each project, in one go.
+	/*async*/ getProjectsDetailed() {
+		return this._send('workspace_projects_detailed');
+	}
+
+	// Soft-deletes a project; the reason, if given, goes in the audit log.
+	/*async*/ deleteProject(project, reason = null) {
+		return this._send('workspace_delete_project', { project, reason });
+	}
+
+	// Writes every ticket and its comments into a file, as 'json' or
+	// 'markdown', resolving to the number of tickets exported.
+	/*async*/ export(format, destPath) {
+		return this._send('workspace_export', { format, destPath });
+	}
+
+	// Calls back with the number of tickets exported so far, and the total,
+	// while exporting.
+	/*async*/ onExportProgress(callback) {
+		return this._listen('minimap://export-progress', ({ done, total }) =>
+			callback(done, total)
+		);
 	}
 
 	getTicket(ticket) {
-		return new Workspace(this._id, ticket, this._prefix);
+		return new Ticket(this._id, ticket, this._prefix);
+	}
+
+	// Fetches the workspace now, resolving to the collections that changed.
+	// Git workspaces only, like the other sync methods.
+	/*async*/ sync() {
+		return this._send('workspace_sync');
+	}
+
+	// Resolves to when the workspace was last synced, in seconds since the
+	// Unix epoch, or null if it hasn't been yet.
+	/*async*/ getLastSync() {
+		return this._send('workspace_last_sync');
+	}
+
+	// Sets how often the workspace is synced in the background, in seconds;
+	// null only syncs it when asked to.
+	/*async*/ setSyncInterval(interval) {
+		return this._send('workspace_set_sync_interval', { interval });
+	}
+
+	// Calls back with the timestamp and changed collections of each sync.
+	/*async*/ onSynced(callback) {
+		return this._listen('minimap://synced', ({ timestamp, collections }) =>
+			callback(timestamp, collections)
+		);
+	}
+
+	// Resolves to the number of changes made while the remote couldn't be
+	// reached, which are waiting to be pushed.
+	/*async*/ getPendingCount() {
+		return this._send('workspace_pending');
 	}
+
+	// Retries pushing the pending changes, resolving to the number still
+	// pending. Syncing pushes them too.
+	/*async*/ flushPending() {
+		return this._send('workspace_flush_pending');
+	}
+
+	// Calls back with the number of pending changes whenever it changes.
+	/*async*/ onPendingChanged(callback) {
+		return this._listen('minimap://pending-changed', ({ pending }) =>
+			callback(pending)
+		);
+	}
+
+	// Calls back with the slug of each ticket that changes, here or
+	// elsewhere; resolves to a function that stops listening.
+	/*async*/ onTicketUpdated(callback) {
+		return this._listen('minimap://ticket-updated', ({ ticket }) =>
+			callback(ticket)
+		);
+	}
+
+	// Calls back with the slug of each project that changes, including
+	// when tickets are created in or deleted from it.
+	/*async*/ onProjectUpdated(callback) {
+		return this._listen('minimap://project-updated', ({ project }) =>
+			callback(project)
+		);
+	}
+
+	/*async*/ _listen(event, callback) {
+		return listen(event, ({ payload }) => {
+			if (payload.workspace === this._id) {
+				callback(payload);
+			}
+		});
+	}
+}
+
+// Resolves to the minimap:// link the app was launched with, if any, only
+// once.
+export function takeDeepLink() {
+	return invoke('deep_link_take');
+}
+
+// Resolves to the remote and ticket slug of a minimap:// link, without
+// opening anything.
+export function parseDeepLink(link) {
+	return invoke('deep_link_parse', { link });
+}
+
+// Opens a minimap://workspace/<remote>/ticket/<slug> link, showing its
+// workspace in this window; resolves to its remote, the workspace and the
+// ticket.
+export async function openDeepLink(link) {
+	const opened = await invoke('deep_link_open', { link });
+	const workspace = new Workspace(opened.workspace, 'git');
+	return {
+		remote: opened.remote,
+		workspace,
+		ticket: workspace.getTicket(opened.ticket)
+	};
+}
+
+// Answers the credential requests of git remotes (passphrases, usernames
+// and passwords, and host keys to trust) with whatever the callback
+// resolves to: e.g. { kind: 'passphrase', passphrase }, or null to decline.
+// Resolves to a function that stops listening.
+export function onCredentialRequested(callback) {
+	return listen('minimap://credential-requested', async ({ payload }) => {
+		let response = null;
+		try {
+			response = await callback(payload);
+		} finally {
+			await invoke('credential_respond', { id: payload.id, response });
+		}
+	});
 }
@@ -10,7 +10,12 @@ import ErrorView from 'minimap/js/module/ErrorView.mjs';
 import WorkspaceSelect from 'minimap/js/module/WorkspaceSelect.mjs';
 import Loading from 'minimap/js/module/Loading.mjs';
 
-import { Workspace } from 'minimap/js/api.mjs';
+import {
+	Workspace,
+	openDeepLink,
+	parseDeepLink,
+	takeDeepLink
+} from 'minimap/js/api.mjs';
 
 import './reset.css';
 import './global.css';
@@ -112,6 +117,39 @@ S.root(() => {
 		}
 	});
 
+	// Open the ticket the app was launched with a link to, if any
+	takeDeepLink().then(async link => {
+		if (!link) return;
+		try {
+			// Links can come from anywhere; only open remotes the user
+			// doesn't know yet once they've agreed to.
+			const { remote: linkedRemote } = await parseDeepLink(link);
+			const isKnown = entry =>
+				(entry.type ?? entry.kind) === 'git' &&
+				entry.remote === linkedRemote;
+			const known =
+				Minimap.savedWorkspaces().some(isKnown) ||
+				(await Workspace.list()).some(isKnown);
+			if (
+				!known &&
+				!window.confirm(
+					I`Open the workspace at ${linkedRemote}? Only open links you trust.`
+				)
+			)
+				return;
+
+			const { remote, workspace, ticket } = await openDeepLink(link);
+			Minimap.currentWorkspace({
+				type: 'git',
+				remote,
+				workspace,
+				ticket: ticket.slug
+			});
+		} catch (err) {
+			console.error('cannot open link:', link, err);
+		}
+	});
+
 	// Attach!
 	document.body.prepend(<Root view={currentView} fadeTime={150} />);
 });
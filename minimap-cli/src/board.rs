@@ -0,0 +1,222 @@
+//! The interactive `minimap board`.
+//!
+//! Only built with the `tui` feature; without it (or outside of a
+//! terminal) the board is printed instead. Like the browser, the board
+//! only keeps the [`Board`] it shows, and reloads it after every move.
+
+use crate::{load_board, tui::with_terminal, Board, Result, BOARD_COLUMNS};
+use crossterm::event::{self, Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers};
+use minimap_core::{EffectiveState, GitRemote, TicketState, Workspace};
+use ratatui::{
+	prelude::*,
+	widgets::{Block, Borders, List, ListItem, ListState, Paragraph},
+};
+use std::rc::Rc;
+
+const HELP: &str = "q quit  h/l column  j/k ticket  L close  H reopen  r reload";
+
+pub(crate) fn run<'a>(
+	workspace: &'a Workspace<'a, Rc<GitRemote>>,
+	project: Option<&'a str>,
+) -> Result<i32> {
+	let mut app = App::new(workspace, project)?;
+	with_terminal(|terminal| app.run(terminal))?;
+
+	Ok(0)
+}
+
+struct App<'a> {
+	workspace: &'a Workspace<'a, Rc<GitRemote>>,
+	project: Option<&'a str>,
+	board: Board,
+	/// The selected ticket of each column.
+	lists: [ListState; 3],
+	column: usize,
+	status: String,
+	quit: bool,
+}
+
+impl<'a> App<'a> {
+	fn new(workspace: &'a Workspace<'a, Rc<GitRemote>>, project: Option<&'a str>) -> Result<Self> {
+		let mut app = Self {
+			workspace,
+			project,
+			board: Board::default(),
+			lists: Default::default(),
+			column: 0,
+			status: HELP.to_string(),
+			quit: false,
+		};
+		app.reload(None)?;
+		Ok(app)
+	}
+
+	fn run<B: Backend>(&mut self, terminal: &mut Terminal<B>) -> Result<()> {
+		while !self.quit {
+			terminal.draw(|frame| self.draw(frame))?;
+
+			if let Event::Key(key) = event::read()?
+				&& key.kind == KeyEventKind::Press
+			{
+				// Errors (e.g. a denied transition) are shown rather
+				// than ending the session.
+				if let Err(err) = self.handle_key(key) {
+					self.status = format!("error: {}", err);
+				}
+			}
+		}
+
+		Ok(())
+	}
+
+	/// Reloads the board from the workspace, then selects the given
+	/// ticket (wherever it ended up), or keeps the current selections.
+	fn reload(&mut self, select: Option<&str>) -> Result<()> {
+		let selected = BOARD_COLUMNS.map(|state| self.selected_card(state).map(str::to_string));
+		self.board = load_board(self.workspace, self.project)?;
+
+		for (index, state) in BOARD_COLUMNS.into_iter().enumerate() {
+			let cards = self.board.column(state);
+			let position = |slug: &str| cards.iter().position(|card| card.slug == slug);
+
+			if let Some(position) = select.and_then(position) {
+				self.column = index;
+				self.lists[index].select(Some(position));
+				continue;
+			}
+
+			// Stay near the previous selection if it's gone.
+			let previous = self.lists[index].selected().unwrap_or(0);
+			let position = selected[index]
+				.as_deref()
+				.and_then(position)
+				.or((!cards.is_empty()).then(|| previous.min(cards.len() - 1)));
+			self.lists[index].select(position);
+		}
+
+		Ok(())
+	}
+
+	fn selected_card(&self, state: EffectiveState) -> Option<&str> {
+		let index = BOARD_COLUMNS.iter().position(|&column| column == state)?;
+		self.lists[index]
+			.selected()
+			.and_then(|position| self.board.column(state).get(position))
+			.map(|card| card.slug.as_str())
+	}
+
+	/// Moves the selected ticket to the closed column, or out of it.
+	fn move_ticket(&mut self, state: TicketState) -> Result<()> {
+		let column = BOARD_COLUMNS[self.column];
+		let Some(slug) = self.selected_card(column).map(str::to_string) else {
+			return Ok(());
+		};
+
+		match (column, state) {
+			(EffectiveState::Closed, TicketState::Closed) => {
+				self.status = format!("{} is already closed", slug);
+				return Ok(());
+			}
+			(EffectiveState::Open | EffectiveState::Blocked, TicketState::Open) => {
+				self.status = format!(
+					"{} is already open; being blocked depends on its dependencies",
+					slug
+				);
+				return Ok(());
+			}
+			_ => {}
+		}
+
+		self.workspace.ticket(&slug)?.set_state(state)?;
+		self.reload(Some(&slug))?;
+		self.status = format!("{} is now {}", slug, BOARD_COLUMNS[self.column].to_string());
+		Ok(())
+	}
+
+	fn handle_key(&mut self, key: KeyEvent) -> Result<()> {
+		let shift = key.modifiers.contains(KeyModifiers::SHIFT);
+		if key.modifiers.contains(KeyModifiers::CONTROL) && key.code == KeyCode::Char('c') {
+			self.quit = true;
+			return Ok(());
+		}
+
+		match key.code {
+			KeyCode::Char('q') | KeyCode::Esc => self.quit = true,
+			KeyCode::Char('r') => {
+				self.reload(None)?;
+				self.status = "reloaded".to_string();
+			}
+			KeyCode::Char('L') => self.move_ticket(TicketState::Closed)?,
+			KeyCode::Right if shift => self.move_ticket(TicketState::Closed)?,
+			KeyCode::Char('H') => self.move_ticket(TicketState::Open)?,
+			KeyCode::Left if shift => self.move_ticket(TicketState::Open)?,
+			KeyCode::Right | KeyCode::Char('l') | KeyCode::Tab => {
+				self.column = (self.column + 1).min(BOARD_COLUMNS.len() - 1);
+			}
+			KeyCode::Left | KeyCode::Char('h') | KeyCode::BackTab => {
+				self.column = self.column.saturating_sub(1);
+			}
+			KeyCode::Down | KeyCode::Char('j') => self.move_selection(1),
+			KeyCode::Up | KeyCode::Char('k') => self.move_selection(-1),
+			_ => {}
+		}
+
+		Ok(())
+	}
+
+	fn move_selection(&mut self, by: isize) {
+		let len = self.board.column(BOARD_COLUMNS[self.column]).len();
+		if len == 0 {
+			return;
+		}
+
+		let list = &mut self.lists[self.column];
+		let index = list.selected().unwrap_or(0) as isize + by;
+		list.select(Some(index.clamp(0, len as isize - 1) as usize));
+	}
+
+	fn draw(&mut self, frame: &mut Frame) {
+		let [main, status] = *Layout::default()
+			.direction(Direction::Vertical)
+			.constraints([Constraint::Min(1), Constraint::Length(1)])
+			.split(frame.size())
+		else {
+			return;
+		};
+		frame.render_widget(Paragraph::new(self.status.clone()), status);
+
+		let areas = Layout::default()
+			.direction(Direction::Horizontal)
+			.constraints([Constraint::Ratio(1, 3); 3])
+			.split(main);
+
+		for (index, state) in BOARD_COLUMNS.into_iter().enumerate() {
+			let cards = self.board.column(state);
+			let width = cards.iter().map(|card| card.slug.len()).max().unwrap_or(0);
+			let items = cards
+				.iter()
+				.map(|card| {
+					ListItem::new(Line::from(vec![
+						Span::styled(format!("{:<width$}  ", card.slug), Style::new().bold()),
+						Span::raw(card.title.clone().unwrap_or_default()),
+					]))
+				})
+				.collect::<Vec<_>>();
+
+			let title = format!("{} ({})", state.to_string(), cards.len());
+			let title = match state {
+				EffectiveState::Open => Span::styled(title, Style::new().green()),
+				EffectiveState::Blocked => Span::styled(title, Style::new().yellow()),
+				EffectiveState::Closed => Span::styled(title, Style::new().dim()),
+			};
+			let highlight = match index == self.column {
+				true => Style::new().reversed(),
+				false => Style::new().underlined(),
+			};
+			let list = List::new(items)
+				.block(Block::default().borders(Borders::ALL).title(title))
+				.highlight_style(highlight);
+			frame.render_stateful_widget(list, areas[index], &mut self.lists[index]);
+		}
+	}
+}
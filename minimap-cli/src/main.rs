@@ -1,7 +1,78 @@
 #![feature(let_chains)]
 
-use minimap_core::{GitRemote, Record, Workspace};
-use std::{fs::Metadata, path::PathBuf};
+use clap::Parser;
+use minimap_core::{
+	git2, ticket_references, AuditEntry, DependencyKind, DependencyRegistry, DependencyResolver,
+	DependencyStatus, EffectiveState, FetchChange, GitRemote, HttpClient, HttpResponse, Project,
+	Record, RecordView, ReferenceKind, Remote, SetOperation, Ticket, TicketEvent, TicketQuery,
+	TicketSort, TicketState, TicketView, Workspace, FORMAT_VERSION,
+};
+use std::{
+	cell::RefCell,
+	collections::{BTreeMap, BTreeSet},
+	ffi::OsString,
+	fs::Metadata,
+	io::{IsTerminal, Write},
+	path::{Path, PathBuf},
+	process::{Child, Command, Stdio},
+	rc::Rc,
+	sync::{
+		atomic::{AtomicBool, Ordering},
+		Mutex, OnceLock,
+	},
+	time::Duration,
+};
+
+/// Set by `--json`; see [`json_output`].
+static JSON_OUTPUT: AtomicBool = AtomicBool::new(false);
+
+/// Set by `--no-pager`; see [`start_pager`].
+static NO_PAGER: AtomicBool = AtomicBool::new(false);
+
+/// The pager started by [`start_pager`], if any.
+static PAGER: Mutex<Option<Child>> = Mutex::new(None);
+
+/// Set by `--color`; see [`color_enabled`].
+static COLOR: Mutex<Option<ColorChoice>> = Mutex::new(None);
+
+/// Set by `--remote`; see [`open_remote`].
+static REMOTE: OnceLock<String> = OnceLock::new();
+
+/// The named workspace of the `.minimap` file to open, given by `--workspace`.
+static WORKSPACE: OnceLock<String> = OnceLock::new();
+
+/// Set by [`load_user_config`].
+static USER_CONFIG: OnceLock<UserConfig> = OnceLock::new();
+
+/// See [`settings`].
+static SETTINGS: Mutex<Settings> = Mutex::new(Settings {
+	color: None,
+	identity: None,
+	origins: BTreeMap::new(),
+});
+
+/// Whether writes are kept local and discarded, rather than pushed;
+/// set by `--dry-run`. See [`report_dry_run`].
+static DRY_RUN: AtomicBool = AtomicBool::new(false);
+
+thread_local! {
+	/// The remote shared by all commands run by this process, once
+	/// `minimap batch` (or `--dry-run`) opened it; see [`shared_remote`].
+	static SHARED_REMOTE: RefCell<Option<Rc<GitRemote>>> = RefCell::new(None);
+}
+
+/// Prints an error message to stderr, after a (colored) `error:`.
+macro_rules! print_error {
+	($($arg:tt)*) => {
+		eprintln!("{}: {}", $crate::paint_stderr($crate::Style::Red, "error"), format_args!($($arg)*))
+	};
+}
+
+#[cfg(feature = "tui")]
+mod board;
+mod serve;
+#[cfg(feature = "tui")]
+mod tui;
 
 #[derive(Debug, thiserror::Error)]
 enum Error {
@@ -11,8 +82,22 @@ enum Error {
 	Io(#[from] std::io::Error),
 	#[error("failed to parse .minimap file: {0}: {1}")]
 	Toml(toml::de::Error, PathBuf),
+	#[error("failed to parse config file: {0}: {1}")]
+	Config(toml::de::Error, PathBuf),
 	#[error("no .minimap file found (hit filesystem boundary)")]
 	NoDotMinimap,
+	#[error("no workspace named `{0}` in {1} (expected one of: {2})")]
+	UnknownWorkspace(String, PathBuf, String),
+	#[error("{0} has no default workspace; pick one with --workspace (one of: {1})")]
+	NoDefaultWorkspace(PathBuf, String),
+	#[error("{0} defines no remote")]
+	NoRemote(PathBuf),
+	#[error("`{0}` matches several users: {1}")]
+	AmbiguousUser(String, String),
+	#[error("request to {0} failed: {1}")]
+	Http(String, String),
+	#[error("editor `{0}` failed: {1}")]
+	Editor(String, std::process::ExitStatus),
 }
 
 type Result<T> = std::result::Result<T, Error>;
@@ -21,135 +106,444 @@ fn main() {
 	std::process::exit(pmain());
 }
 
-fn pmain() -> i32 {
-	let mut args = std::env::args().fuse();
-	let arg0 = args.next();
-
-	// Here, we expand out `-abcd` into `-a -b -c -d`,
-	// and `--foo=bar` into `--foo bar`.
-	let mut args = args.flat_map(|arg| {
-		let mut chars = arg.chars();
-		let mut expanded = vec![];
-
-		if chars.next() == Some('-') {
-			match chars.next() {
-				Some('-') => {
-					arg.split_once('=')
-						.map(|(key, value)| {
-							expanded.push(key.into());
-							expanded.push(value.into());
-						})
-						.unwrap_or_else(|| expanded.push(arg));
-				}
-				Some(c) => {
-					expanded.push(format!("-{}", c));
-					expanded.extend(chars.map(|c| format!("-{}", c)));
-				}
-				None => {
-					expanded.push(arg);
-				}
-			}
-		} else {
-			expanded.push(arg);
-		}
+/// Minimap: projects and tickets, stored in a Git repository.
+///
+/// The workspace is found through the nearest .minimap file, unless
+/// --remote (or $MINIMAP_REMOTE) gives the URL of its remote.
+///
+/// A .minimap file may also define named workspaces, as tables like
+/// `[workspaces.<name>]` with their own `remote` (and settings).
+/// --workspace (or $MINIMAP_WORKSPACE) picks one of them; otherwise the
+/// file's own `remote` is used, or else the named workspace given by
+/// its `workspace` key.
+///
+/// Long output (from e.g. list and show) is shown in a pager when
+/// printing to a terminal: $MINIMAP_PAGER, $PAGER or `less`, in that
+/// order. Use --no-pager (or set the pager to `cat`) to disable it.
+///
+/// Output is colored when printing to a terminal, unless $NO_COLOR is
+/// set.
+///
+/// Defaults are read from ~/.config/minimap/config.toml (or from
+/// $XDG_CONFIG_HOME/minimap/config.toml): `remote`, used when there's no
+/// .minimap file, `color`, `identity.name` and `identity.email`, used to
+/// author changes instead of Git's identity, and `origins.<slug>.token`,
+/// exported as $MINIMAP_<SLUG>_TOKEN for dependency origins. All but
+/// `remote` can also be set in .minimap files, which take precedence.
+///
+/// The audit log of deletions is keyed with $MINIMAP_AUDIT_KEY, if set;
+/// it must be the same for everyone deleting from the workspace.
+#[derive(clap::Parser)]
+#[command(
+	name = "minimap",
+	version,
+	subcommand_required = true,
+	arg_required_else_help = true
+)]
+struct Cli {
+	/// Runs as if minimap was started in the given directory; when given
+	/// more than once, each one is relative to the previous one
+	#[arg(short = 'C', value_name = "path")]
+	directories: Vec<PathBuf>,
+	/// The URL of the workspace's remote, instead of the .minimap file's
+	#[arg(long, value_name = "url", conflicts_with = "workspace")]
+	remote: Option<String>,
+	/// The named workspace of the .minimap file to open
+	#[arg(short, long, value_name = "name")]
+	workspace: Option<String>,
+	/// Prints records and entities as JSON, one value per line
+	#[arg(long)]
+	json: bool,
+	/// Never shows output in a pager
+	#[arg(short = 'P', long)]
+	no_pager: bool,
+	/// When to color output
+	#[arg(long, value_name = "when", value_enum)]
+	color: Option<ColorChoice>,
+	/// Pushes nothing to the workspace: changes are made locally, listed
+	/// once the command is done, then discarded
+	#[arg(long, global = true)]
+	dry_run: bool,
+	#[command(subcommand)]
+	command: Subcommand,
+}
 
-		expanded.into_iter()
-	});
+const TUI_KEYS: &str = concat!(
+	"Keys:\n",
+	"  j, k, arrows    Moves the selection (or scrolls a ticket)\n",
+	"  tab, h, l       Switches between projects and tickets\n",
+	"  enter           Opens the selected ticket\n",
+	"  esc             Goes back, or cancels the current input\n",
+	"  /               Filters tickets by slug or title\n",
+	"  s               Cycles between open, closed and all tickets\n",
+	"  c, o            Closes or reopens the selected ticket\n",
+	"  m               Comments on the selected ticket\n",
+	"  r               Reloads everything from the workspace\n",
+	"  q               Quits",
+);
 
-	let mut precommand_args = vec![];
+#[derive(clap::Subcommand)]
+enum Subcommand {
+	Init(InitArgs),
+	Batch(BatchArgs),
+	/// Fetches changes made to the workspace elsewhere.
+	///
+	/// Fetches the changes made to the workspace elsewhere, and summarizes
+	/// them by ticket and project. Changes made here are pushed as they're
+	/// made, so there's nothing to send.
+	Sync,
+	#[command(subcommand)]
+	Workspace(WorkspaceCommand),
+	#[command(subcommand)]
+	Project(ProjectCommand),
+	#[command(subcommand)]
+	Ticket(TicketCommand),
+	Show(ShowArgs),
+	List(ListArgs),
+	Search(SearchArgs),
+	Comment(CommentArgs),
+	Comments(CommentsArgs),
+	#[command(subcommand)]
+	Label(LabelCommand),
+	/// Assigns users to a ticket.
+	Assign(AssignArgs),
+	/// Unassigns users from a ticket.
+	Unassign(AssignArgs),
+	Move(MoveArgs),
+	/// Archives a project or a ticket.
+	///
+	/// Archived tickets, and the tickets of archived projects, are left
+	/// out of `list` (unless given --archived), but are otherwise kept as
+	/// they are.
+	Archive(ArchiveArgs),
+	/// Unarchives a project or a ticket.
+	Unarchive(ArchiveArgs),
+	Board(BoardArgs),
+	/// Browses projects and tickets interactively.
+	///
+	/// Only available if minimap was built with the `tui` feature.
+	#[command(after_long_help = TUI_KEYS)]
+	Tui,
+	Log(LogArgs),
+	Watch(WatchArgs),
+	Stats(StatsArgs),
+	Export(ExportArgs),
+	#[command(subcommand)]
+	Import(ImportSource),
+	#[command(subcommand)]
+	Hooks(HooksCommand),
+	Serve(serve::ServeArgs),
+}
 
-	let subcommand = {
-		let mut last = args.next();
-		while last.as_ref().map(|s| s.starts_with('-')).unwrap_or(false) {
-			let arg = last.unwrap();
-			let should_break = arg == "--";
-			precommand_args.push(arg);
-			last = args.next();
-			if should_break {
-				break;
-			}
+impl Subcommand {
+	/// The name the command was run with, e.g. in errors about it.
+	fn name(&self) -> &'static str {
+		match self {
+			Subcommand::Init(_) => "init",
+			Subcommand::Batch(_) => "batch",
+			Subcommand::Sync => "sync",
+			Subcommand::Workspace(_) => "workspace",
+			Subcommand::Project(_) => "project",
+			Subcommand::Ticket(_) => "ticket",
+			Subcommand::Show(_) => "show",
+			Subcommand::List(_) => "list",
+			Subcommand::Search(_) => "search",
+			Subcommand::Comment(_) => "comment",
+			Subcommand::Comments(_) => "comments",
+			Subcommand::Label(_) => "label",
+			Subcommand::Assign(_) => "assign",
+			Subcommand::Unassign(_) => "unassign",
+			Subcommand::Move(_) => "move",
+			Subcommand::Archive(_) => "archive",
+			Subcommand::Unarchive(_) => "unarchive",
+			Subcommand::Board(_) => "board",
+			Subcommand::Tui => "tui",
+			Subcommand::Log(_) => "log",
+			Subcommand::Watch(_) => "watch",
+			Subcommand::Stats(_) => "stats",
+			Subcommand::Export(_) => "export",
+			Subcommand::Import(_) => "import",
+			Subcommand::Hooks(_) => "hooks",
+			Subcommand::Serve(_) => "serve",
+		}
+	}
+}
+
+fn pmain() -> i32 {
+	let cli = match Cli::try_parse_from(strip_separator(std::env::args_os().collect())) {
+		Ok(cli) => cli,
+		Err(err) => {
+			// Also prints --help and --version.
+			err.print().ok();
+			return err.exit_code();
 		}
-		last
 	};
 
-	let args = args.collect::<Vec<_>>();
+	for dir in &cli.directories {
+		if std::env::set_current_dir(dir).is_err() {
+			print_error!("failed to change directory to `{}`", dir.display());
+			return 1;
+		}
+	}
 
-	let mut precommand_args = precommand_args.into_iter();
-	while let Some(arg) = precommand_args.next() {
-		match arg.as_str() {
-			"--help" => return show_usage(arg0),
-			"--version" => {
-				eprintln!("minimap {}", env!("CARGO_PKG_VERSION"));
-				return 2;
-			}
-			"-C" => {
-				if let Some(dir) = precommand_args.next() {
-					if !std::env::set_current_dir(&dir).is_ok() {
-						eprintln!("error: failed to change directory to `{}`", dir);
-						return 1;
-					}
-				} else {
-					eprintln!("error: missing argument to `-C`");
-					return 1;
-				}
-			}
-			unknown => {
-				eprintln!("error: unknown argument `{}`\n", unknown);
-				return show_usage(arg0);
-			}
-		};
+	JSON_OUTPUT.store(cli.json, Ordering::Relaxed);
+	NO_PAGER.store(cli.no_pager, Ordering::Relaxed);
+	DRY_RUN.store(cli.dry_run, Ordering::Relaxed);
+	*COLOR.lock().unwrap() = cli.color;
+	if let Some(remote) = cli.remote {
+		REMOTE.set(remote).ok();
+	}
+	if let Some(workspace) = cli.workspace {
+		WORKSPACE.set(workspace).ok();
 	}
 
-	let result = match subcommand.as_ref().map(|s| s.as_str()) {
-		Some("workspace") => cmd_workspace(arg0.as_ref().map(|s| s.as_str()), &args),
-		Some("project") => cmd_project(arg0.as_ref().map(|s| s.as_str()), &args),
-		Some(unknown) => {
-			eprintln!("error: unknown subcommand `{}`\n", unknown);
-			Ok(show_usage(arg0))
-		}
-		None => Ok(show_usage(arg0)),
+	if let Err(err) = load_user_config() {
+		print_error!("{}", err);
+		return 1;
+	}
+
+	// `init` creates the workspace's repository, which can't be undone,
+	// and `serve` never finishes to report what it would have done.
+	if dry_run() && matches!(cli.command, Subcommand::Init(_) | Subcommand::Serve(_)) {
+		print_error!("`{}` can't be run with --dry-run", cli.command.name());
+		return 2;
+	}
+
+	let result = match cli.command {
+		Subcommand::Batch(args) => cmd_batch(args),
+		command => run_subcommand(command),
 	};
+	// Failed commands may have written something too.
+	let result = match (result, dry_run()) {
+		(result, false) => result,
+		(Ok(code), true) => report_dry_run().map(|()| code),
+		(Err(err), true) => report_dry_run().and(Err(err)),
+	};
+
+	finish_pager();
 
 	match result {
 		Ok(code) => std::process::exit(code),
 		Err(err) => {
-			eprintln!("error: {}", err);
+			print_error!("{}", err);
 			std::process::exit(1);
 		}
 	}
 }
 
-fn show_usage(arg0: Option<String>) -> i32 {
-	let arg0 = arg0.unwrap_or_else(|| "minimap".to_string());
-	eprintln!(
-		concat!(
-			"minimap ",
-			env!("CARGO_PKG_VERSION"),
-			"\n",
-			"\n",
-			"usage: {arg0} [--version] [--help] <command> [<args>]\n",
-			"\n",
-			"Available commands:\n",
-			"\n",
-			"interacting with workspaces:\n",
-			"workspace name     Gets or sets the workspace name\n"
-		),
-		arg0 = arg0
-	);
-	2
+/// Drops the `--` that may end the options given before the command
+/// (as in `minimap -C dir -- list`), which clap would otherwise take
+/// as the end of every option, including the command's.
+fn strip_separator(mut args: Vec<OsString>) -> Vec<OsString> {
+	let mut index = 1;
+	while let Some(arg) = args.get(index).and_then(|arg| arg.to_str()) {
+		match arg {
+			"--" => {
+				args.remove(index);
+				break;
+			}
+			// Options that take a value, given separately.
+			"-C" | "--color" | "--remote" | "-w" | "--workspace" => index += 2,
+			arg if arg.starts_with('-') => index += 1,
+			_ => break,
+		}
+	}
+
+	args
 }
 
-#[derive(serde::Deserialize, serde::Serialize)]
+/// Runs a subcommand (other than `batch`).
+fn run_subcommand(command: Subcommand) -> Result<i32> {
+	match command {
+		Subcommand::Init(args) => cmd_init(args),
+		Subcommand::Batch(_) => unreachable!("batches are run by `pmain`"),
+		Subcommand::Sync => cmd_sync(),
+		Subcommand::Workspace(command) => cmd_workspace(command),
+		Subcommand::Project(command) => cmd_project(command),
+		Subcommand::Ticket(command) => cmd_ticket(command),
+		Subcommand::Show(args) => cmd_show(args),
+		Subcommand::List(args) => cmd_list(args),
+		Subcommand::Search(args) => cmd_search(args),
+		Subcommand::Comment(args) => cmd_comment(args),
+		Subcommand::Comments(args) => cmd_comments(args),
+		Subcommand::Label(command) => cmd_label(command),
+		Subcommand::Assign(args) => cmd_assign(args, true),
+		Subcommand::Unassign(args) => cmd_assign(args, false),
+		Subcommand::Archive(args) => cmd_archive(args, true),
+		Subcommand::Unarchive(args) => cmd_archive(args, false),
+		Subcommand::Move(args) => cmd_move(args),
+		Subcommand::Board(args) => cmd_board(args),
+		#[cfg(feature = "tui")]
+		Subcommand::Tui => tui::cmd_tui(),
+		#[cfg(not(feature = "tui"))]
+		Subcommand::Tui => {
+			print_error!("minimap was built without the `tui` feature");
+			Ok(1)
+		}
+		Subcommand::Log(args) => cmd_log(args),
+		Subcommand::Watch(args) => cmd_watch(args),
+		Subcommand::Stats(args) => cmd_stats(args),
+		Subcommand::Export(args) => cmd_export(args),
+		Subcommand::Import(source) => cmd_import(source),
+		Subcommand::Hooks(command) => cmd_hooks(command),
+		Subcommand::Serve(args) => serve::cmd_serve(args),
+	}
+}
+
+#[derive(Default, Clone, Copy, serde::Deserialize, serde::Serialize, clap::ValueEnum)]
 #[serde(rename_all = "lowercase")]
 enum DotMinimapRemoteType {
+	#[default]
 	Git,
 }
 
 #[derive(serde::Deserialize, serde::Serialize)]
 struct DotMinimap {
+	/// The remote of the workspace opened by default. May be left out
+	/// when named workspaces are defined.
+	#[serde(skip_serializing_if = "Option::is_none")]
+	remote: Option<String>,
+	#[serde(rename = "type", default)]
+	remote_type: DotMinimapRemoteType,
+	/// The named workspace opened by default, if there's no `remote`.
+	#[serde(rename = "workspace", skip_serializing_if = "Option::is_none")]
+	default_workspace: Option<String>,
+	/// Named workspaces, picked with `--workspace`.
+	#[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+	workspaces: BTreeMap<String, DotMinimapWorkspace>,
+	#[serde(flatten)]
+	settings: Settings,
+}
+
+/// A named workspace in a `.minimap` file. Its settings are
+/// merged on top of those of the file.
+#[derive(serde::Deserialize, serde::Serialize)]
+struct DotMinimapWorkspace {
 	remote: String,
-	#[serde(rename = "type")]
+	#[serde(rename = "type", default)]
 	remote_type: DotMinimapRemoteType,
+	#[serde(flatten)]
+	settings: Settings,
+}
+
+/// The user's configuration file; see [`load_user_config`].
+#[derive(Default, serde::Deserialize)]
+struct UserConfig {
+	/// The remote used when no `.minimap` file is found.
+	remote: Option<String>,
+	#[serde(flatten)]
+	settings: Settings,
+}
+
+/// Settings that can be given both in the user's configuration
+/// file and in `.minimap` files, the latter taking precedence.
+#[derive(Default, Clone, serde::Deserialize, serde::Serialize)]
+struct Settings {
+	/// When to color output, unless `--color` is given.
+	#[serde(skip_serializing_if = "Option::is_none")]
+	color: Option<ColorChoice>,
+	/// The identity that new records are authored with,
+	/// instead of the one configured in Git.
+	#[serde(skip_serializing_if = "Option::is_none")]
+	identity: Option<Identity>,
+	/// Dependency origin settings, by origin slug.
+	#[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+	origins: BTreeMap<String, OriginSettings>,
+}
+
+#[derive(Clone, serde::Deserialize, serde::Serialize)]
+struct Identity {
+	name: String,
+	email: String,
+}
+
+#[derive(Default, Clone, serde::Deserialize, serde::Serialize)]
+struct OriginSettings {
+	/// The token used to authenticate with the origin, exported
+	/// to plugins and hooks as `MINIMAP_<SLUG>_TOKEN`.
+	#[serde(skip_serializing_if = "Option::is_none")]
+	token: Option<String>,
+}
+
+impl Settings {
+	/// Applies `overrides` on top of these settings.
+	fn merge(mut self, overrides: Settings) -> Settings {
+		self.color = overrides.color.or(self.color);
+		self.identity = overrides.identity.or(self.identity);
+		for (slug, origin) in overrides.origins {
+			let merged = self.origins.entry(slug).or_default();
+			merged.token = origin.token.or(merged.token.take());
+		}
+		self
+	}
+}
+
+/// Gets the path of the user's configuration file:
+/// `$XDG_CONFIG_HOME/minimap/config.toml`, or
+/// `~/.config/minimap/config.toml` if that isn't set.
+fn user_config_path() -> Option<PathBuf> {
+	let config_dir = match std::env::var_os("XDG_CONFIG_HOME") {
+		Some(dir) if !dir.is_empty() => PathBuf::from(dir),
+		_ => PathBuf::from(std::env::var_os("HOME")?).join(".config"),
+	};
+	Some(config_dir.join("minimap").join("config.toml"))
+}
+
+/// Loads the user's configuration file (if there is one), making
+/// its settings the current ones until a `.minimap` file is read.
+fn load_user_config() -> Result<()> {
+	let config = match user_config_path() {
+		Some(path) if path.is_file() => {
+			let contents = std::fs::read_to_string(&path)?;
+			toml::from_str(&contents).map_err(|err| Error::Config(err, path))?
+		}
+		_ => UserConfig::default(),
+	};
+
+	*SETTINGS.lock().unwrap() = config.settings.clone();
+	USER_CONFIG.set(config).ok();
+	Ok(())
+}
+
+fn user_config() -> &'static UserConfig {
+	USER_CONFIG.get_or_init(UserConfig::default)
+}
+
+/// Gets the current settings: those from the user's configuration
+/// file, merged with those of the `.minimap` file once it's read.
+fn settings() -> std::sync::MutexGuard<'static, Settings> {
+	SETTINGS.lock().unwrap()
+}
+
+/// Makes the given settings the current ones, and applies
+/// them to the remote that they were read for.
+fn apply_settings(remote: GitRemote, settings: Settings) -> GitRemote {
+	for (slug, origin) in &settings.origins {
+		let Some(token) = &origin.token else {
+			continue;
+		};
+
+		let slug = slug
+			.chars()
+			.map(|c| match c.is_ascii_alphanumeric() {
+				true => c.to_ascii_uppercase(),
+				false => '_',
+			})
+			.collect::<String>();
+		let name = format!("MINIMAP_{}_TOKEN", slug);
+		// Tokens given in the environment take precedence.
+		if std::env::var_os(&name).is_none() {
+			std::env::set_var(name, token);
+		}
+	}
+
+	let remote = match &settings.identity {
+		Some(identity) => remote.with_identity(&identity.name, &identity.email),
+		None => remote,
+	};
+
+	*SETTINGS.lock().unwrap() = settings;
+	remote
 }
 
 #[cfg(unix)]
@@ -163,7 +557,53 @@ fn has_hit_filesystem_boundary(_last: &Metadata, _current: &Metadata) -> bool {
 	false
 }
 
-fn open_workspace<'a>() -> Result<Workspace<'a, GitRemote>> {
+/// Opens the workspace of [`shared_remote`], keying its audit log with
+/// `$MINIMAP_AUDIT_KEY`.
+fn open_workspace<'a>() -> Result<Workspace<'a, Rc<GitRemote>>> {
+	let workspace = Workspace::open(shared_remote()?)?;
+	Ok(match std::env::var_os("MINIMAP_AUDIT_KEY") {
+		Some(key) if !key.is_empty() => workspace.with_audit_key(key.as_encoded_bytes()),
+		_ => workspace,
+	})
+}
+
+/// Gets the remote shared by all commands, if `minimap batch` or an
+/// earlier command opened it, or else opens the one given by
+/// [`open_remote`]. In dry runs, pushes to it are deferred (see
+/// [`report_dry_run`]), and it's shared from then on.
+fn shared_remote() -> Result<Rc<GitRemote>> {
+	if let Some(remote) = SHARED_REMOTE.with(|remote| remote.borrow().clone()) {
+		return Ok(remote);
+	}
+
+	let remote = Rc::new(open_remote()?);
+	if dry_run() {
+		remote.defer_pushes();
+		SHARED_REMOTE.with(|shared| *shared.borrow_mut() = Some(remote.clone()));
+	}
+	Ok(remote)
+}
+
+/// Opens the remote given by `--remote` or `$MINIMAP_REMOTE`, or else
+/// the one in the nearest `.minimap` file (see [`DotMinimap::resolve`]),
+/// or else the user's default.
+fn open_remote() -> Result<GitRemote> {
+	let workspace = WORKSPACE.get().cloned().or_else(|| {
+		std::env::var("MINIMAP_WORKSPACE")
+			.ok()
+			.filter(|workspace| !workspace.is_empty())
+	});
+	// Naming a workspace takes precedence over $MINIMAP_REMOTE.
+	let remote = REMOTE.get().cloned().or_else(|| {
+		std::env::var("MINIMAP_REMOTE")
+			.ok()
+			.filter(|remote| !remote.is_empty() && WORKSPACE.get().is_none())
+	});
+	if let Some(remote) = remote {
+		let settings = user_config().settings.clone();
+		return Ok(apply_settings(GitRemote::open(&remote)?, settings));
+	}
+
 	let minimap_file = {
 		let mut current_dir = std::env::current_dir()?;
 		let mut last_stats = std::fs::metadata(&current_dir)?;
@@ -177,7 +617,7 @@ fn open_workspace<'a>() -> Result<Workspace<'a, GitRemote>> {
 				if next_dir == current_dir {
 					true
 				} else {
-					let stats = std::fs::metadata(&next_dir)?;
+					let stats = std::fs::metadata(next_dir)?;
 
 					if has_hit_filesystem_boundary(&last_stats, &stats) {
 						true
@@ -192,101 +632,259 @@ fn open_workspace<'a>() -> Result<Workspace<'a, GitRemote>> {
 			};
 
 			if give_up {
-				return Err(Error::NoDotMinimap);
+				// Named workspaces only exist in `.minimap` files.
+				let Some(remote) = user_config()
+					.remote
+					.as_ref()
+					.filter(|_| workspace.is_none())
+				else {
+					return Err(Error::NoDotMinimap);
+				};
+
+				let settings = user_config().settings.clone();
+				return Ok(apply_settings(GitRemote::open(remote)?, settings));
 			}
 		}
 	};
 
 	let minimap_file_contents = std::fs::read_to_string(&minimap_file)?;
-	let minimap_file: DotMinimap =
-		toml::from_str(&minimap_file_contents).map_err(|err| Error::Toml(err, minimap_file))?;
+	let dot_minimap: DotMinimap = toml::from_str(&minimap_file_contents)
+		.map_err(|err| Error::Toml(err, minimap_file.clone()))?;
 
-	let git_remote = GitRemote::open(&minimap_file.remote)?;
-	let workspace = Workspace::open(git_remote);
-	Ok(workspace)
+	let (remote, remote_type, settings) =
+		dot_minimap.resolve(workspace.as_deref(), minimap_file)?;
+	let remote = match remote_type {
+		DotMinimapRemoteType::Git => GitRemote::open(&remote)?,
+	};
+	Ok(apply_settings(
+		remote,
+		user_config().settings.clone().merge(settings),
+	))
 }
 
-fn cmd_workspace(arg0: Option<&str>, args: &[String]) -> Result<i32> {
-	let subcommand = args.iter().next();
+impl DotMinimap {
+	/// Picks the workspace to open: the named one if a name is given,
+	/// or else the one of the file's own `remote`, or else the named
+	/// one given by its `workspace` key. Returns its remote and type,
+	/// and the settings to open it with.
+	fn resolve(
+		self,
+		name: Option<&str>,
+		path: PathBuf,
+	) -> Result<(String, DotMinimapRemoteType, Settings)> {
+		let names = || {
+			self.workspaces
+				.keys()
+				.map(String::as_str)
+				.collect::<Vec<_>>()
+				.join(", ")
+		};
 
-	match subcommand.as_ref().map(|s| s.as_str()) {
-		Some("name") => cmd_workspace_name(arg0, &args[1..]),
-		Some("description") => cmd_workspace_description(arg0, &args[1..]),
-		Some("--help") | None => {
-			eprintln!(
-				concat!(
-					"usage: {arg0} workspace <command> [<args>]\n",
-					"\n",
-					"Minimap workspace metadata commands.\n",
-					"\n",
-					"Available commands:\n",
-					"    name           Gets or sets the workspace name\n",
-					"    description    Gets or sets the workspace description\n",
-					"    --help         Prints this help message",
-				),
-				arg0 = arg0.unwrap_or("minimap")
-			);
-			Ok(2)
-		}
-		Some(unknown) if unknown.starts_with('-') => {
-			eprintln!("error: unknown 'workspace' argument `{}`\n", unknown);
-			Ok(2)
-		}
-		Some(unknown) => {
-			eprintln!("error: unknown 'workspace' subcommand `{}`\n", unknown);
-			Ok(2)
+		let name = match (name, &self.remote) {
+			(Some(name), _) => name.to_string(),
+			(None, Some(remote)) => return Ok((remote.clone(), self.remote_type, self.settings)),
+			(None, None) => match &self.default_workspace {
+				Some(name) => name.clone(),
+				None if self.workspaces.is_empty() => return Err(Error::NoRemote(path)),
+				None => return Err(Error::NoDefaultWorkspace(path, names())),
+			},
+		};
+
+		let names = names();
+		let mut workspaces = self.workspaces;
+		match workspaces.remove(&name) {
+			Some(workspace) => Ok((
+				workspace.remote,
+				workspace.remote_type,
+				self.settings.merge(workspace.settings),
+			)),
+			None => Err(Error::UnknownWorkspace(name, path, names)),
 		}
 	}
 }
 
-fn cmd_workspace_name(arg0: Option<&str>, args: &[String]) -> Result<i32> {
-	let mut write_name = None;
-	let mut verbose = false;
-	let mut idempotent = true;
+fn prompt(question: &str, default: Option<&str>) -> Result<String> {
+	match default {
+		Some(default) => eprint!("{} [{}]: ", question, default),
+		None => eprint!("{}: ", question),
+	}
+	std::io::stderr().flush()?;
 
-	for arg in args {
-		match arg.as_str() {
-			"--help" => {
-				eprintln!(
-					concat!(
-						"usage: {arg0} workspace name [-vf] [<new_name>]\n",
-						"\n",
-						"Gets or sets the workspace name.\n",
-						"\n",
-						"Returns non-zero if the workspace name is not set and no\n",
-						"new name is provided.\n",
-						"\n",
-						"Options:\n",
-						"    -v, --verbose     Prints all record information along with the name\n",
-						"    -f, --force       Perform a commit even if the last committed name\n",
-						"                      is the same as the new name\n",
-						"    --help            Prints this help message",
-					),
-					arg0 = arg0.unwrap_or("minimap")
-				);
-				return Ok(2);
-			}
-			"--verbose" | "-v" => {
-				verbose = true;
-			}
-			"--force" | "-f" => {
-				idempotent = false;
-			}
-			arg if arg.starts_with('-') => {
-				eprintln!("error: unknown argument `{}`\n", arg);
-				return Ok(2);
-			}
-			name => {
-				if write_name.is_some() {
-					eprintln!("error: too many arguments\nusage: minimap workspace name --help");
+	let mut answer = String::new();
+	if std::io::stdin().read_line(&mut answer)? == 0 {
+		return Err(std::io::Error::from(std::io::ErrorKind::UnexpectedEof).into());
+	}
+
+	match answer.trim() {
+		"" => Ok(default.unwrap_or_default().to_string()),
+		answer => Ok(answer.to_string()),
+	}
+}
+
+/// Creates a .minimap file pointing to a workspace.
+///
+/// Creates a .minimap file in the current directory, pointing to the
+/// workspace at the given remote URL.
+///
+/// If no remote is given, prompts for the remote URL, its type and the
+/// workspace name.
+///
+/// Unless --no-connect is given, the remote is opened first, which
+/// initializes it if it isn't a workspace yet.
+#[derive(clap::Args)]
+struct InitArgs {
+	/// Overwrites an existing .minimap file
+	#[arg(short, long)]
+	force: bool,
+	/// Only writes the .minimap file
+	#[arg(long)]
+	no_connect: bool,
+	/// The remote type (default: git)
+	#[arg(short = 't', long = "type", value_name = "type", value_enum)]
+	remote_type: Option<DotMinimapRemoteType>,
+	/// Sets the workspace name
+	#[arg(short, long, value_name = "name", conflicts_with = "no_connect")]
+	name: Option<String>,
+	/// The remote to connect to, a URL or a path
+	#[arg(value_name = "remote")]
+	remote: Option<String>,
+}
+
+fn cmd_init(args: InitArgs) -> Result<i32> {
+	let InitArgs {
+		force,
+		no_connect,
+		remote_type,
+		name,
+		remote,
+	} = args;
+	let connect = !no_connect;
+
+	let minimap_file = std::env::current_dir()?.join(".minimap");
+	if !force && minimap_file.exists() {
+		print_error!(
+			"{} already exists (use --force to overwrite it)",
+			minimap_file.display()
+		);
+		return Ok(1);
+	}
+
+	let interactive = remote.is_none();
+	let remote = match remote {
+		Some(remote) => remote,
+		None => prompt("remote URL", None)?,
+	};
+	if remote.is_empty() {
+		print_error!("missing remote URL\nusage: minimap init --help");
+		return Ok(2);
+	}
+
+	let remote_type = match remote_type {
+		Some(remote_type) => remote_type,
+		None if interactive => {
+			let answer = prompt("remote type", Some("git"))?;
+			match clap::ValueEnum::from_str(&answer, false) {
+				Ok(remote_type) => remote_type,
+				Err(_) => {
+					print_error!(
+						"unknown remote type `{}` (expected `git`)\nusage: minimap init --help",
+						answer
+					);
 					return Ok(2);
 				}
+			}
+		}
+		None => DotMinimapRemoteType::default(),
+	};
+
+	if connect {
+		let workspace = Workspace::open(match remote_type {
+			DotMinimapRemoteType::Git => GitRemote::open(&remote)?,
+		})?;
 
-				write_name = Some(name);
+		let name = match name {
+			Some(name) => Some(name),
+			None if interactive => {
+				let current = workspace.name()?.map(|record| record.message());
+				Some(prompt("workspace name", current.as_deref())?)
 			}
+			None => None,
+		};
+		if let Some(name) = name
+			&& !name.is_empty()
+			&& workspace.name()?.map(|record| record.message()).as_deref() != Some(&name)
+		{
+			workspace.set_name(&name)?;
 		}
 	}
 
+	let contents = toml::to_string(&DotMinimap {
+		remote: Some(remote),
+		remote_type,
+		default_workspace: None,
+		workspaces: BTreeMap::new(),
+		settings: Settings::default(),
+	})
+	.expect("failed to serialize .minimap file");
+	std::fs::write(&minimap_file, contents)?;
+	if json_output() {
+		print_json(&serde_json::json!({ "created": minimap_file }));
+	} else {
+		println!("created {}", minimap_file.display());
+	}
+
+	Ok(0)
+}
+
+/// Gets or sets the workspace's metadata, or migrates it.
+#[derive(clap::Subcommand)]
+#[command(arg_required_else_help = true)]
+enum WorkspaceCommand {
+	Name(WorkspaceNameArgs),
+	Description(WorkspaceDescriptionArgs),
+	/// Upgrades the workspace to the current format.
+	///
+	/// Upgrades the workspace's on-disk format to the one used by this
+	/// version of Minimap. Workspaces written by an older version may have
+	/// to be migrated before they can be used.
+	Migrate,
+}
+
+fn cmd_workspace(command: WorkspaceCommand) -> Result<i32> {
+	match command {
+		WorkspaceCommand::Name(args) => cmd_workspace_name(args),
+		WorkspaceCommand::Description(args) => cmd_workspace_description(args),
+		WorkspaceCommand::Migrate => cmd_workspace_migrate(),
+	}
+}
+
+/// Gets or sets the workspace name.
+///
+/// Returns non-zero if the workspace name is not set and no new name is
+/// provided.
+#[derive(clap::Args)]
+struct WorkspaceNameArgs {
+	/// Prints all record information along with the name
+	#[arg(short, long)]
+	verbose: bool,
+	/// Performs a commit even if the last committed name is the same as
+	/// the new name
+	#[arg(short, long)]
+	force: bool,
+	/// The new name
+	#[arg(value_name = "name")]
+	new_name: Option<String>,
+}
+
+fn cmd_workspace_name(args: WorkspaceNameArgs) -> Result<i32> {
+	let WorkspaceNameArgs {
+		verbose,
+		force,
+		new_name: write_name,
+	} = args;
+	let write_name = write_name.as_deref();
+	let idempotent = !force;
+
 	let workspace = open_workspace()?;
 
 	if let Some(name) = write_name {
@@ -302,70 +900,65 @@ fn cmd_workspace_name(arg0: Option<&str>, args: &[String]) -> Result<i32> {
 			workspace.set_name(name)?
 		};
 
-		if verbose {
+		if verbose || json_output() {
 			print_record(&record, true);
 		}
 
+		Ok(0)
+	} else if let Some(record) = workspace.name()? {
+		print_record(&record, verbose);
 		Ok(0)
 	} else {
-		if let Some(record) = workspace.name()? {
-			print_record(&record, verbose);
-			Ok(0)
-		} else {
-			Ok(1)
-		}
+		Ok(1)
 	}
 }
 
-fn cmd_workspace_description(arg0: Option<&str>, args: &[String]) -> Result<i32> {
-	let mut write_description = None;
-	let mut verbose = false;
-	let mut idempotent = true;
+fn cmd_workspace_migrate() -> Result<i32> {
+	let remote = shared_remote()?;
+	let migrated = Workspace::migrate(&remote)?;
+	if json_output() {
+		print_json(&serde_json::json!({
+			"migrated": migrated,
+			"format_version": FORMAT_VERSION,
+		}));
+		return Ok(0);
+	}
+	if migrated.is_empty() {
+		println!("workspace is already at format version {}", FORMAT_VERSION);
+	}
+	for version in migrated {
+		println!("migrated workspace to format version {}", version);
+	}
 
-	for arg in args {
-		match arg.as_str() {
-			"--help" => {
-				eprintln!(
-					concat!(
-						"usage: {arg0} workspace description [-vf] [<new_description>]\n",
-						"\n",
-						"Gets or sets the workspace description.\n",
-						"\n",
-						"Returns non-zero if the workspace description is not set and no\n",
-						"new description is provided.\n",
-						"\n",
-						"Options:\n",
-						"    -v, --verbose     Prints all record information along with the description\n",
-						"    -f, --force       Perform a commit even if the last committed description\n",
-						"                      is the same as the new description\n",
-						"    --help            Prints this help message",
-					),
-					arg0 = arg0.unwrap_or("minimap")
-				);
-				return Ok(2);
-			}
-			"--verbose" | "-v" => {
-				verbose = true;
-			}
-			"--force" | "-f" => {
-				idempotent = false;
-			}
-			arg if arg.starts_with('-') => {
-				eprintln!("error: unknown argument `{}`\n", arg);
-				return Ok(2);
-			}
-			description => {
-				if write_description.is_some() {
-					eprintln!(
-						"error: too many arguments\nusage: minimap workspace description --help"
-					);
-					return Ok(2);
-				}
+	Ok(0)
+}
 
-				write_description = Some(description);
-			}
-		}
-	}
+/// Gets or sets the workspace description.
+///
+/// Returns non-zero if the workspace description is not set and no new
+/// description is provided.
+#[derive(clap::Args)]
+struct WorkspaceDescriptionArgs {
+	/// Prints all record information along with the description
+	#[arg(short, long)]
+	verbose: bool,
+	/// Performs a commit even if the last committed description is the
+	/// same as the new description
+	#[arg(short, long)]
+	force: bool,
+	/// The new description
+	#[arg(value_name = "description")]
+	new_description: Option<String>,
+}
+
+fn cmd_workspace_description(args: WorkspaceDescriptionArgs) -> Result<i32> {
+	let WorkspaceDescriptionArgs {
+		verbose,
+		force,
+		new_description: write_description,
+	} = args;
+	let write_description = write_description.as_deref();
+	let idempotent = !force;
 
 	let workspace = open_workspace()?;
 
@@ -382,162 +975,93 @@ fn cmd_workspace_description(arg0: Option<&str>, args: &[String]) -> Result<i32>
 			workspace.set_description(description)?
 		};
 
-		if verbose {
+		if verbose || json_output() {
 			print_record(&record, true);
 		}
 
+		Ok(0)
+	} else if let Some(record) = workspace.description()? {
+		print_record(&record, verbose);
 		Ok(0)
 	} else {
-		if let Some(record) = workspace.description()? {
-			print_record(&record, verbose);
-			Ok(0)
-		} else {
-			Ok(1)
-		}
+		Ok(1)
 	}
 }
 
-fn cmd_project(arg0: Option<&str>, args: &[String]) -> Result<i32> {
-	let subcommand = args.iter().next();
+/// Creates, deletes and edits projects.
+#[derive(clap::Subcommand)]
+#[command(arg_required_else_help = true)]
+enum ProjectCommand {
+	List(ProjectListArgs),
+	Create(ProjectCreateArgs),
+	Delete(ProjectDeleteArgs),
+	/// Gets or sets a project's name.
+	///
+	/// Returns non-zero if the project's name is not set and no new name
+	/// is provided.
+	Name(ProjectTextArgs),
+	/// Gets or sets a project's description.
+	///
+	/// Returns non-zero if the project's description is not set and no
+	/// new description is provided.
+	Description(ProjectTextArgs),
+	Parent(ProjectParentArgs),
+}
 
-	match subcommand.as_ref().map(|s| s.as_str()) {
-		Some("create") => cmd_project_create(arg0, &args[1..]),
-		Some("--help") | None => {
-			eprintln!(
-				concat!(
-					"usage: {arg0} project <command> [<args>]\n",
-					"\n",
-					"Minimap project commands.\n",
-					"\n",
-					"Available commands:\n",
-					"    create    Creates a new project\n",
-					"    --help    Prints this help message",
-				),
-				arg0 = arg0.unwrap_or("minimap")
-			);
-			Ok(2)
-		}
-		Some(unknown) if unknown.starts_with('-') => {
-			eprintln!("error: unknown 'project' argument `{}`\n", unknown);
-			Ok(2)
-		}
-		Some(unknown) => {
-			eprintln!("error: unknown 'project' subcommand `{}`\n", unknown);
-			Ok(2)
-		}
+fn cmd_project(command: ProjectCommand) -> Result<i32> {
+	match command {
+		ProjectCommand::List(args) => cmd_project_list(args),
+		ProjectCommand::Create(args) => cmd_project_create(args),
+		ProjectCommand::Delete(args) => cmd_project_delete(args),
+		ProjectCommand::Name(args) => cmd_project_text(args, "name"),
+		ProjectCommand::Description(args) => cmd_project_text(args, "description"),
+		ProjectCommand::Parent(args) => cmd_project_parent(args),
 	}
 }
 
-fn cmd_project_create(arg0: Option<&str>, args: &[String]) -> Result<i32> {
-	let mut args = args.into_iter();
-	let mut verbose = false; // -v or --verbose
-	let mut allow_existing = false; // -e or --allow-existing
-	let mut allow_lowercase = false; // -l or --allow-lowercase
-	let mut slug = None; // one and only positional
-	let mut description = None; // -d or --description <description>
-	let mut name = None; // -n or --name <name>
-
-	while let Some(arg) = args.next() {
-		match arg.as_str() {
-			"--help" => {
-				eprintln!(
-					concat!(
-						"usage: {arg0} project create [-vel] [-d <description>] [-n <name>] <slug>\n",
-						"\n",
-						"Creates a new project.\n",
-						"\n",
-						"Options:\n",
-						"    -v, --verbose         Prints all record information along with the project\n",
-						"    -e, --allow-existing  Allow the project to be created if it already exists\n",
-						"    -l, --allow-lowercase Allow the project slug to contain lowercase letters\n",
-						"    -d, --description     Sets the project description\n",
-						"    -n, --name            Sets the project name\n",
-						"    --help                Prints this help message",
-					),
-					arg0 = arg0.unwrap_or("minimap")
-				);
-				return Ok(2);
-			}
-			"--verbose" | "-v" => {
-				verbose = true;
-			}
-			"--allow-existing" | "-e" => {
-				allow_existing = true;
-			}
-			"--allow-lowercase" | "-l" => {
-				allow_lowercase = true;
-			}
-			"--description" | "-d" => {
-				if description.is_some() {
-					eprintln!(
-						"error: `--description` may only be specified once\nusage: minimap project create --help"
-					);
-					return Ok(2);
-				}
-
-				if let Some(arg) = args.next()
-					&& !arg.starts_with('-')
-				{
-					description = Some(arg.to_string());
-				} else {
-					eprintln!(
-						"error: missing argument to `--description`\nusage: minimap project create --help"
-					);
-					return Ok(2);
-				}
-			}
-			"--name" | "-n" => {
-				if name.is_some() {
-					eprintln!(
-						"error: `--name` may only be specified once\nusage: minimap project create --help"
-					);
-					return Ok(2);
-				}
-
-				if let Some(arg) = args.next()
-					&& !arg.starts_with('-')
-				{
-					name = Some(arg.to_string());
-				} else {
-					eprintln!(
-						"error: missing argument to `--name`\nusage: minimap project create --help"
-					);
-					return Ok(2);
-				}
-			}
-			arg if arg.starts_with('-') => {
-				eprintln!("error: unknown argument `{}`\n", arg);
-				return Ok(2);
-			}
-			arg => {
-				if slug.is_some() {
-					eprintln!("error: too many arguments\nusage: minimap project create --help");
-					return Ok(2);
-				}
-
-				slug = Some(arg.to_string());
-			}
-		}
-	}
+/// Creates a new project.
+#[derive(clap::Args)]
+struct ProjectCreateArgs {
+	/// Prints all record information along with the project
+	#[arg(short, long)]
+	verbose: bool,
+	/// Allows the project to be created if it already exists
+	#[arg(short = 'e', long)]
+	allow_existing: bool,
+	/// Allows the project slug to contain lowercase letters
+	#[arg(short = 'l', long)]
+	allow_lowercase: bool,
+	/// Sets the project description
+	#[arg(short, long, value_name = "description")]
+	description: Option<String>,
+	/// Sets the project name
+	#[arg(short, long, value_name = "name")]
+	name: Option<String>,
+	/// The project's slug, e.g. `ABC`
+	#[arg(value_name = "slug")]
+	slug: String,
+}
 
-	let slug = match slug {
-		Some(slug) => slug,
-		None => {
-			eprintln!("error: missing argument `slug`\nusage: minimap project create --help");
-			return Ok(2);
-		}
-	};
+fn cmd_project_create(args: ProjectCreateArgs) -> Result<i32> {
+	let ProjectCreateArgs {
+		verbose,
+		allow_existing,
+		allow_lowercase,
+		description,
+		name,
+		slug,
+	} = args;
 
 	if !allow_lowercase && slug.chars().any(|c| c.is_ascii_lowercase()) {
-		eprintln!(
-			"error: slug contains lowercase letters: {slug}\nusage: minimap project create --help"
+		print_error!(
+			"slug contains lowercase letters: {slug}\nusage: minimap project create --help"
 		);
 		return Ok(2);
 	}
 
 	if slug.chars().any(|c| !c.is_ascii_alphanumeric()) {
-		eprintln!(
-			"error: slug contains invalid characters: {slug}\nusage: minimap project create --help"
+		print_error!(
+			"slug contains invalid characters: {slug}\nusage: minimap project create --help"
 		);
 		return Ok(2);
 	}
@@ -547,21 +1071,28 @@ fn cmd_project_create(arg0: Option<&str>, args: &[String]) -> Result<i32> {
 
 	match project {
 		Ok(project) => {
-			if verbose {
+			if let Some(name) = name {
+				project.set_name(&name)?;
+			}
+			if let Some(description) = description {
+				project.set_description(&description)?;
+			}
+
+			if verbose || json_output() {
 				print_record(&project.record()?.unwrap(), true);
 			}
 
 			Ok(0)
 		}
 		Err(old_record) if allow_existing => {
-			if verbose {
+			if verbose || json_output() {
 				print_record(&old_record, true);
 			}
 
 			Ok(0)
 		}
 		Err(old_record) => {
-			eprintln!("error: project already exists: {slug}", slug = slug);
+			print_error!("project already exists: {slug}", slug = slug);
 
 			if verbose {
 				eprintln!("\n");
@@ -573,9 +1104,4262 @@ fn cmd_project_create(arg0: Option<&str>, args: &[String]) -> Result<i32> {
 	}
 }
 
+/// Lists the projects in the workspace.
+///
+/// Lists the projects in the workspace, along with their names.
+#[derive(clap::Args)]
+struct ProjectListArgs {
+	/// Prints all record information of each project, along with its name
+	/// and description
+	#[arg(short, long)]
+	verbose: bool,
+	/// Prints each project using the given format, e.g. "{slug}: {name}".
+	/// Available placeholders: {slug}, {name}, {description}, {id},
+	/// {author}, {email} and {date}
+	#[arg(long, value_name = "format", value_parser = |template: &str| parse_format(template, PROJECT_PLACEHOLDERS))]
+	format: Option<Format>,
+}
+
+fn cmd_project_list(args: ProjectListArgs) -> Result<i32> {
+	let ProjectListArgs { verbose, format } = args;
+
+	let workspace = open_workspace()?;
+
+	for (i, meta) in workspace.projects_with_meta()?.into_iter().enumerate() {
+		if let Some(format) = &format {
+			let name = meta.name.as_ref().map(Record::message);
+			let description = meta.description.as_ref().map(Record::message);
+			println!(
+				"{}",
+				render_format(format, |field| match field {
+					"slug" => Ok(meta.project.slug().to_string()),
+					"name" => Ok(name.clone().unwrap_or_default()),
+					"description" => Ok(description.clone().unwrap_or_default()),
+					field => Ok(record_placeholder(&meta.record, field)),
+				})?
+			);
+			continue;
+		}
+
+		if json_output() {
+			print_json(&serde_json::json!({
+				"slug": meta.project.slug(),
+				"name": meta.name.map(|record| record.message()),
+				"description": meta.description.map(|record| record.message()),
+				"record": RecordView::from(meta.record),
+			}));
+			continue;
+		}
+
+		let name = meta.name.map(|record| record.message()).unwrap_or_default();
+
+		if verbose {
+			if i > 0 {
+				println!();
+			}
+			print_record(&meta.record, true);
+			println!("name:   {}", name);
+			if let Some(description) = meta.description {
+				println!("\n{}", description.message());
+			}
+		} else {
+			println!("{:<24} {}", meta.project.slug(), name);
+		}
+	}
+
+	Ok(0)
+}
+
+/// Deletes a project.
+///
+/// Deletes a project. Its tickets are kept, and are restored if a
+/// project with the same slug is created again.
+///
+/// Returns non-zero if the project doesn't exist.
+#[derive(clap::Args)]
+struct ProjectDeleteArgs {
+	/// Prints all record information of the deletion
+	#[arg(short, long)]
+	verbose: bool,
+	/// Why the project is deleted, for the audit log
+	#[arg(short, long)]
+	reason: Option<String>,
+	/// The project to delete
+	#[arg(value_name = "slug")]
+	slug: String,
+}
+
+fn cmd_project_delete(args: ProjectDeleteArgs) -> Result<i32> {
+	let ProjectDeleteArgs {
+		verbose,
+		reason,
+		slug,
+	} = args;
+	let slug = slug.as_str();
+
+	let workspace = open_workspace()?;
+	let deleted = workspace.delete_project_with_reason(slug, reason.as_deref())?;
+
+	match deleted {
+		Ok(record) => {
+			if verbose || json_output() {
+				print_record(&record, true);
+			}
+
+			Ok(0)
+		}
+		Err(Some(old_record)) => {
+			print_error!("project already deleted: {}", slug);
+
+			if verbose {
+				eprintln!("\n");
+				print_record(&old_record, true);
+			}
+
+			Ok(1)
+		}
+		Err(None) => {
+			print_error!("project does not exist: {}", slug);
+			Ok(1)
+		}
+	}
+}
+
+/// The arguments of `project name` and `project description`.
+#[derive(clap::Args)]
+struct ProjectTextArgs {
+	/// Prints all record information along with the value
+	#[arg(short, long)]
+	verbose: bool,
+	/// Performs a commit even if the last committed value is the same as
+	/// the new value
+	#[arg(short, long)]
+	force: bool,
+	/// The project
+	#[arg(value_name = "slug")]
+	slug: String,
+	/// The new value
+	#[arg(value_name = "value")]
+	new_value: Option<String>,
+}
+
+/// Implements `project name` and `project description`, which
+/// only differ in the field they get or set.
+fn cmd_project_text(args: ProjectTextArgs, field: &str) -> Result<i32> {
+	let ProjectTextArgs {
+		verbose,
+		force,
+		slug,
+		new_value: write_value,
+	} = args;
+	let write_value = write_value.as_deref();
+	let idempotent = !force;
+
+	let workspace = open_workspace()?;
+	let project = workspace.project(&slug)?;
+
+	let get = || match field {
+		"name" => project.name(),
+		_ => project.description(),
+	};
+
+	if let Some(value) = write_value {
+		let set = || match field {
+			"name" => project.set_name(value),
+			_ => project.set_description(value),
+		};
+
+		let record = if idempotent {
+			if let Some(record) = get()?
+				&& record.message() == value
+			{
+				record
+			} else {
+				set()?
+			}
+		} else {
+			set()?
+		};
+
+		if verbose || json_output() {
+			print_record(&record, true);
+		}
+
+		Ok(0)
+	} else if let Some(record) = get()? {
+		print_record(&record, verbose);
+		Ok(0)
+	} else {
+		Ok(1)
+	}
+}
+
+/// Gets or sets a project's parent project.
+///
+/// Returns non-zero if the project has no parent and no new parent is
+/// provided.
+#[derive(clap::Args)]
+struct ProjectParentArgs {
+	/// Prints all record information of the change
+	#[arg(short, long)]
+	verbose: bool,
+	/// Moves the project to the root of the hierarchy
+	#[arg(short, long, conflicts_with = "parent")]
+	clear: bool,
+	/// The project
+	#[arg(value_name = "slug")]
+	slug: String,
+	/// The new parent project
+	#[arg(value_name = "parent")]
+	parent: Option<String>,
+}
+
+fn cmd_project_parent(args: ProjectParentArgs) -> Result<i32> {
+	let ProjectParentArgs {
+		verbose,
+		clear,
+		slug,
+		parent,
+	} = args;
+	let parent = parent.as_deref();
+
+	let workspace = open_workspace()?;
+	let project = workspace.project(&slug)?;
+
+	if clear || parent.is_some() {
+		let record = project.set_parent(parent)?;
+
+		if verbose || json_output() {
+			print_record(&record, true);
+		}
+
+		Ok(0)
+	} else if let Some(parent) = project.parent()? {
+		if json_output() {
+			print_json(&parent.slug());
+		} else {
+			println!("{}", parent.slug());
+		}
+		Ok(0)
+	} else {
+		Ok(1)
+	}
+}
+
+/// Creates, shows, lists and edits tickets.
+#[derive(clap::Subcommand)]
+#[command(arg_required_else_help = true)]
+enum TicketCommand {
+	New(TicketNewArgs),
+	Show(TicketShowArgs),
+	List(TicketListArgs),
+	/// Closes a ticket.
+	Close(TicketStateArgs),
+	/// Reopens a closed ticket.
+	Reopen(TicketStateArgs),
+	Title(TicketTitleArgs),
+}
+
+fn cmd_ticket(command: TicketCommand) -> Result<i32> {
+	match command {
+		TicketCommand::New(args) => cmd_ticket_new(args),
+		TicketCommand::Show(args) => cmd_ticket_show(args),
+		TicketCommand::List(args) => cmd_ticket_list(args),
+		TicketCommand::Close(args) => cmd_ticket_set_state(args, TicketState::Closed),
+		TicketCommand::Reopen(args) => cmd_ticket_set_state(args, TicketState::Open),
+		TicketCommand::Title(args) => cmd_ticket_title(args),
+	}
+}
+
+/// Shows a ticket's title, state and description.
+#[derive(clap::Args)]
+struct TicketShowArgs {
+	/// Prints the ticket using the given format, e.g. "{slug} {state}
+	/// {title}". Available placeholders: {slug}, {id}, {project}, {state},
+	/// {reason}, {title} and {description}
+	#[arg(long, value_name = "format", value_parser = |template: &str| parse_format(template, TICKET_PLACEHOLDERS))]
+	format: Option<Format>,
+	/// The ticket, e.g. `ABC-1`
+	#[arg(value_name = "slug")]
+	slug: String,
+}
+
+fn cmd_ticket_show(args: TicketShowArgs) -> Result<i32> {
+	let TicketShowArgs { format, slug } = args;
+
+	let workspace = open_workspace()?;
+	let ticket = workspace.ticket(&slug)?;
+
+	if let Some(format) = &format {
+		println!(
+			"{}",
+			render_format(format, |field| ticket_placeholder(&ticket, field))?
+		);
+		return Ok(0);
+	}
+
+	if json_output() {
+		print_json(&ticket.view()?);
+		return Ok(0);
+	}
+
+	println!("ticket: {}", ticket.slug());
+	println!(
+		"title:  {}",
+		ticket.title()?.map(|r| r.message()).unwrap_or_default()
+	);
+	let state = paint_state(&ticket.state()?.0.to_string());
+	match ticket.state_reason()? {
+		Some(reason) => println!("state:  {} ({})", state, reason),
+		None => println!("state:  {}", state),
+	}
+
+	if let Some(description) = ticket.description()? {
+		println!("\n{}", description.message());
+	}
+
+	Ok(0)
+}
+
+/// Shows everything about a ticket.
+///
+/// Shows everything about a ticket: its title, state, description,
+/// labels, assignees, dependencies (with their current status) and most
+/// recent comments.
+#[derive(clap::Args)]
+struct ShowArgs {
+	/// Shows the given number of recent comments
+	#[arg(short, long, value_name = "count", default_value_t = 3)]
+	comments: usize,
+	/// Renders the description and comments as markdown
+	#[arg(short, long)]
+	markdown: bool,
+	/// The ticket, e.g. `ABC-1`
+	#[arg(value_name = "slug")]
+	slug: String,
+}
+
+fn cmd_show(args: ShowArgs) -> Result<i32> {
+	let ShowArgs {
+		comments,
+		markdown,
+		slug,
+	} = args;
+
+	let registry = DependencyRegistry::new();
+	let workspace = open_workspace()?;
+	let ticket = workspace.ticket(&slug)?;
+	let view = ticket.view()?;
+
+	start_pager();
+
+	if json_output() {
+		print_json(&view);
+		return Ok(0);
+	}
+
+	let styled = color_enabled(false);
+	let body = |text: &str| match markdown {
+		true => render_markdown(text, styled),
+		false => text.trim_end().to_string(),
+	};
+
+	println!(
+		"{}: {}",
+		paint(Style::Yellow, &view.slug),
+		paint(Style::Bold, view.title.as_deref().unwrap_or("(untitled)"))
+	);
+	println!();
+
+	let state = match ticket.effective_state(&registry) {
+		Ok(EffectiveState::Blocked) => paint(Style::Yellow, "open (blocked)"),
+		_ => paint_state(&view.state.to_string()),
+	};
+	let mut details = vec![(
+		"State",
+		match &view.state_reason {
+			Some(reason) => format!("{} ({})", state, reason),
+			None => state,
+		},
+	)];
+	if ticket.is_archived()? {
+		details.push(("Archived", "yes".to_string()));
+	}
+	if !view.labels.is_empty() {
+		details.push(("Labels", view.labels.join(", ")));
+	}
+	if !view.assignees.is_empty() {
+		details.push(("Assignees", view.assignees.join(", ")));
+	}
+	if !view.votes.is_empty() {
+		details.push(("Votes", view.votes.len().to_string()));
+	}
+	if let Some(parent) = &view.parent {
+		details.push(("Parent", parent.clone()));
+	}
+	if !view.subtasks.is_empty() {
+		details.push(("Subtasks", view.subtasks.join(", ")));
+	}
+	if let Some(original) = &view.duplicate_of {
+		details.push(("Duplicate of", original.clone()));
+	}
+	if let Some(estimate) = view.estimate {
+		details.push(("Estimate", format_minutes(estimate)));
+	}
+	if view.time_spent > 0 {
+		details.push(("Time spent", format_minutes(view.time_spent)));
+	}
+	for (name, value) in &view.fields {
+		details.push((name, value.clone()));
+	}
+
+	let width = details
+		.iter()
+		.map(|(name, _)| name.len())
+		.max()
+		.unwrap_or(0);
+	for (name, value) in details {
+		println!(
+			"{:<width$}  {}",
+			format!("{}:", name),
+			value,
+			width = width + 1
+		);
+	}
+
+	if let Some(description) = &view.description {
+		println!();
+		for line in body(description).lines() {
+			println!("    {}", line);
+		}
+	}
+
+	if !view.dependencies.is_empty() {
+		println!();
+		println!("Dependencies:");
+		for dependency in &view.dependencies {
+			let status = match dependency_status(
+				&workspace,
+				&registry,
+				&dependency.origin,
+				&dependency.endpoint,
+			) {
+				Ok(DependencyStatus::Pending) => paint(Style::Yellow, "pending"),
+				Ok(DependencyStatus::Complete) => paint(Style::Green, "complete"),
+				Err(_) => paint(Style::Dim, "unknown"),
+			};
+
+			let mut line = format!(
+				"    [{}] {}@{}",
+				status, dependency.origin, dependency.endpoint
+			);
+			if dependency.kind != DependencyKind::Blocking {
+				line.push_str(&format!(" ({})", dependency.kind.to_string()));
+			}
+			if let Some(note) = &dependency.note {
+				line.push_str(&format!(": {}", note));
+			}
+			println!("{}", line);
+		}
+	}
+
+	if comments > 0 && !view.comments.is_empty() {
+		println!();
+		if view.comments.len() > comments {
+			println!("Comments ({} of {}):", comments, view.comments.len());
+		} else {
+			println!("Comments:");
+		}
+
+		// Oldest first, so that the latest comment ends up at the bottom.
+		for comment in view.comments.iter().take(comments).rev() {
+			println!();
+			println!(
+				"    {} <{}> on {}",
+				paint(Style::Bold, &comment.record.author),
+				comment.record.email,
+				paint(Style::Dim, timestamp_to_date(comment.record.timestamp))
+			);
+			for line in body(&comment.record.message).lines() {
+				println!("        {}", line);
+			}
+			if !comment.attachments.is_empty() {
+				println!("        (attached: {})", comment.attachments.join(", "));
+			}
+		}
+	}
+
+	Ok(0)
+}
+
+/// Resolves a dependency's status; `_` dependencies are other tickets of
+/// the workspace.
+fn dependency_status(
+	workspace: &Workspace<Rc<GitRemote>>,
+	registry: &DependencyRegistry,
+	origin: &str,
+	endpoint: &str,
+) -> minimap_core::Result<DependencyStatus> {
+	match origin {
+		"_" => workspace
+			.ticket(endpoint)
+			.and_then(|blocker| blocker.state())
+			.map(|(state, _)| state.into()),
+		origin => registry.status(origin, endpoint),
+	}
+}
+
+/// Formats a number of minutes as e.g. `1h 30m`.
+fn format_minutes(minutes: u64) -> String {
+	match (minutes / 60, minutes % 60) {
+		(0, minutes) => format!("{}m", minutes),
+		(hours, 0) => format!("{}h", hours),
+		(hours, minutes) => format!("{}h {}m", hours, minutes),
+	}
+}
+
+/// Renders markdown as plain text for the terminal. Emphasis, headings
+/// and code are shown with ANSI styles if `styled` is set, and dropped
+/// otherwise.
+fn render_markdown(text: &str, styled: bool) -> String {
+	use pulldown_cmark::{Event, HeadingLevel, Parser, Tag};
+
+	let style = |style: Style| if styled { style.code() } else { "" };
+	let bold = style(Style::Bold);
+	let italic = style(Style::Italic);
+	let underline = style(Style::Underline);
+	let dim = style(Style::Dim);
+	let reset = if styled { RESET } else { "" };
+
+	let mut out = String::new();
+	// The ordinal of the next item of each enclosing list, if it's numbered.
+	let mut lists = Vec::<Option<u64>>::new();
+	let mut in_code_block = false;
+	// Whether nothing was printed yet for the current code block.
+	let mut code_line_start = false;
+	let mut quote_depth = 0;
+
+	// Starts a new line, within any enclosing block quotes.
+	let new_line = |out: &mut String, quote_depth: usize| {
+		if !out.is_empty() && !out.ends_with('\n') {
+			out.push('\n');
+		}
+		out.push_str(&"> ".repeat(quote_depth));
+	};
+	// Starts a new block, separated from the previous one by a blank line.
+	let new_block = |out: &mut String, quote_depth: usize| {
+		if !out.is_empty() {
+			new_line(out, quote_depth);
+			out.truncate(out.trim_end_matches([' ', '>']).len());
+			out.push('\n');
+		}
+		out.push_str(&"> ".repeat(quote_depth));
+	};
+
+	for event in Parser::new(text) {
+		match event {
+			Event::Start(Tag::Paragraph) => {
+				if lists.is_empty() {
+					new_block(&mut out, quote_depth);
+				}
+			}
+			Event::Start(Tag::Heading(level, ..)) => {
+				new_block(&mut out, quote_depth);
+				if level == HeadingLevel::H1 {
+					out.push_str(underline);
+				}
+				out.push_str(bold);
+			}
+			Event::End(Tag::Heading(..)) => out.push_str(reset),
+			Event::Start(Tag::BlockQuote) => quote_depth += 1,
+			Event::End(Tag::BlockQuote) => quote_depth -= 1,
+			Event::Start(Tag::CodeBlock(_)) => {
+				new_block(&mut out, quote_depth);
+				in_code_block = true;
+				code_line_start = true;
+			}
+			Event::End(Tag::CodeBlock(_)) => in_code_block = false,
+			Event::Start(Tag::List(start)) => {
+				if lists.is_empty() {
+					new_block(&mut out, quote_depth);
+				}
+				lists.push(start);
+			}
+			Event::End(Tag::List(_)) => {
+				lists.pop();
+			}
+			Event::Start(Tag::Item) => {
+				new_line(&mut out, quote_depth);
+				out.push_str(&"  ".repeat(lists.len() - 1));
+				match lists.last_mut() {
+					Some(Some(ordinal)) => {
+						out.push_str(&format!("{}. ", ordinal));
+						*ordinal += 1;
+					}
+					_ => out.push_str("• "),
+				}
+			}
+			Event::Start(Tag::Emphasis) => out.push_str(italic),
+			Event::Start(Tag::Strong) => out.push_str(bold),
+			Event::End(Tag::Emphasis | Tag::Strong) => out.push_str(reset),
+			Event::Start(Tag::Link(..)) => out.push_str(underline),
+			Event::End(Tag::Link(_, url, _)) => {
+				out.push_str(reset);
+				out.push_str(&format!(" <{}>", url));
+			}
+			Event::Text(text) => {
+				if in_code_block {
+					for line in text.lines() {
+						if !code_line_start {
+							new_line(&mut out, quote_depth);
+						}
+						code_line_start = false;
+						out.push_str(&format!("    {}{}{}", dim, line, reset));
+					}
+				} else {
+					out.push_str(&text);
+				}
+			}
+			Event::Code(code) => {
+				out.push_str(dim);
+				out.push_str(&code);
+				out.push_str(reset);
+			}
+			Event::SoftBreak => out.push(' '),
+			Event::HardBreak => new_line(&mut out, quote_depth),
+			Event::Rule => {
+				new_block(&mut out, quote_depth);
+				out.push_str("----------");
+			}
+			Event::TaskListMarker(done) => out.push_str(if done { "[x] " } else { "[ ] " }),
+			_ => {}
+		}
+	}
+
+	out.trim_end().to_string()
+}
+
+/// Creates a new ticket.
+///
+/// Creates a new ticket in a project and prints its slug.
+#[derive(clap::Args)]
+struct TicketNewArgs {
+	/// Prints all record information of the title
+	#[arg(short, long)]
+	verbose: bool,
+	/// Sets the ticket title
+	#[arg(short, long, value_name = "title")]
+	title: Option<String>,
+	/// The project to create the ticket in
+	#[arg(value_name = "project")]
+	project: String,
+}
+
+fn cmd_ticket_new(args: TicketNewArgs) -> Result<i32> {
+	let TicketNewArgs {
+		verbose,
+		title,
+		project,
+	} = args;
+
+	let workspace = open_workspace()?;
+	let ticket = workspace.project(&project)?.create_ticket()?;
+	let title = title.map(|title| ticket.set_title(&title)).transpose()?;
+
+	if json_output() {
+		print_json(&serde_json::json!({
+			"slug": ticket.slug(),
+			"title": title.map(RecordView::from),
+		}));
+		return Ok(0);
+	}
+
+	println!("{}", ticket.slug());
+
+	if let Some(record) = title
+		&& verbose
+	{
+		println!();
+		print_record(&record, true);
+	}
+
+	Ok(0)
+}
+
+/// Lists tickets.
+///
+/// Lists the open tickets in the workspace, or in a single project if
+/// one is given, along with their titles.
+#[derive(clap::Args)]
+struct TicketListArgs {
+	/// Lists all tickets, open or closed
+	#[arg(short, long, conflicts_with = "closed")]
+	all: bool,
+	/// Lists closed tickets only
+	#[arg(short, long)]
+	closed: bool,
+	/// Prints each ticket using the given format; see `ticket show --help`
+	/// for the placeholders
+	#[arg(long, value_name = "format", value_parser = |template: &str| parse_format(template, TICKET_PLACEHOLDERS))]
+	format: Option<Format>,
+	/// Only list the tickets of this project
+	#[arg(value_name = "project")]
+	project: Option<String>,
+}
+
+fn cmd_ticket_list(args: TicketListArgs) -> Result<i32> {
+	let TicketListArgs {
+		all,
+		closed,
+		format,
+		project,
+	} = args;
+	let state = match (all, closed) {
+		(true, _) => None,
+		(false, true) => Some(TicketState::Closed),
+		(false, false) => Some(TicketState::Open),
+	};
+
+	let workspace = open_workspace()?;
+	let tickets = match project {
+		Some(project) => workspace.project(&project)?.tickets()?,
+		None => workspace.tickets()?,
+	};
+
+	for ticket in tickets {
+		let ticket_state = ticket.state()?.0;
+		if state.is_some_and(|state| state != ticket_state) {
+			continue;
+		}
+
+		if let Some(format) = &format {
+			println!(
+				"{}",
+				render_format(format, |field| ticket_placeholder(&ticket, field))?
+			);
+			continue;
+		}
+
+		let title = ticket.title()?.map(|r| r.message());
+		if json_output() {
+			print_json(&serde_json::json!({
+				"slug": ticket.slug(),
+				"state": ticket_state,
+				"title": title,
+			}));
+		} else {
+			println!(
+				"{:<16} {} {}",
+				ticket.slug(),
+				paint_state(&format!("{:<8}", ticket_state.to_string())),
+				title.unwrap_or_default()
+			);
+		}
+	}
+
+	Ok(0)
+}
+
+/// The ticket states `list --state` can filter on.
+#[derive(Clone, Copy, clap::ValueEnum)]
+enum StateFilter {
+	/// Open tickets (the default)
+	Open,
+	/// Closed tickets
+	Closed,
+	/// Open tickets that are waiting on a dependency
+	Blocked,
+	/// Tickets in any state
+	All,
+}
+
+/// Lists tickets matching filters, as a table.
+///
+/// Lists the tickets in the workspace (or in a single project, if one is
+/// given) that match all of the given filters.
+#[derive(clap::Args)]
+struct ListArgs {
+	/// Only lists tickets in the given state
+	#[arg(long, value_name = "state", value_enum, default_value_t = StateFilter::Open, hide_default_value = true)]
+	state: StateFilter,
+	/// Only lists tickets with the label; may be given multiple times
+	#[arg(short, long = "label", value_name = "label")]
+	labels: Vec<String>,
+	/// Only lists tickets assigned to the user, or to yourself if the user
+	/// is `me`
+	#[arg(short, long, value_name = "user")]
+	assignee: Option<String>,
+	/// Sorts by `id` (the default), `votes`, or the value of a custom
+	/// field; append `:desc` to a field name to sort from the greatest
+	/// value (e.g. `priority:desc`)
+	#[arg(short, long, value_name = "key", value_parser = parse_sort)]
+	sort: Option<TicketSort>,
+	/// Lists at most the given number of tickets
+	#[arg(short = 'n', long, value_name = "count")]
+	limit: Option<usize>,
+	/// Prints each ticket using the given format; see `ticket show --help`
+	/// for the placeholders
+	#[arg(long, value_name = "format", value_parser = |template: &str| parse_format(template, TICKET_PLACEHOLDERS))]
+	format: Option<Format>,
+	/// Only lists archived tickets, which are otherwise left out
+	#[arg(long)]
+	archived: bool,
+	/// Prints the tickets as CSV, with a header row
+	#[arg(long, conflicts_with = "format")]
+	csv: bool,
+	/// Only list the tickets of this project
+	#[arg(value_name = "project")]
+	project: Option<String>,
+}
+
+fn cmd_list(args: ListArgs) -> Result<i32> {
+	let ListArgs {
+		state,
+		labels,
+		assignee,
+		sort,
+		limit,
+		format,
+		archived,
+		csv,
+		project,
+	} = args;
+
+	let mut query = TicketQuery::new()
+		.archived(archived)
+		.sort(sort.unwrap_or_default());
+	query = match state {
+		StateFilter::Open => query.state(TicketState::Open),
+		StateFilter::Closed => query.state(TicketState::Closed),
+		StateFilter::Blocked => query.effective_state(EffectiveState::Blocked),
+		StateFilter::All => query,
+	};
+	for label in labels {
+		query = query.label(&label);
+	}
+
+	let registry = DependencyRegistry::new();
+	let workspace = open_workspace()?;
+
+	if let Some(assignee) = assignee.as_deref() {
+		query = match assignee {
+			"me" => query.assignee(&workspace.remote().identity()?.1),
+			user => query.assignee(user),
+		};
+	}
+
+	let tickets = match project {
+		Some(project) => workspace.project(&project)?.query_with(&query, &registry)?,
+		None => workspace.query_with(&query, &registry)?,
+	};
+
+	start_pager();
+
+	let mut rows = vec![];
+	for ticket in tickets.take(limit.unwrap_or(usize::MAX)) {
+		let ticket = ticket?;
+
+		if let Some(format) = &format {
+			println!(
+				"{}",
+				render_format(format, |field| ticket_placeholder(&ticket, field))?
+			);
+			continue;
+		}
+
+		let state = ticket.state()?.0;
+		let labels = ticket.labels()?;
+		let assignees = ticket.assignees()?;
+		let title = ticket.title()?.map(|r| r.message());
+
+		if json_output() && !csv {
+			print_json(&serde_json::json!({
+				"slug": ticket.slug(),
+				"state": state,
+				"labels": labels,
+				"assignees": assignees,
+				"title": title,
+			}));
+			continue;
+		}
+
+		rows.push([
+			ticket.slug().to_string(),
+			match csv {
+				true => state.to_string(),
+				false => paint_state(&state.to_string()),
+			},
+			labels.join(","),
+			assignees.join(","),
+			title.unwrap_or_default(),
+		]);
+	}
+
+	if csv {
+		print_csv(["slug", "state", "labels", "assignees", "title"], &rows);
+	} else if !rows.is_empty() {
+		print_table(["SLUG", "STATE", "LABELS", "ASSIGNEES", "TITLE"], &rows);
+	}
+
+	Ok(0)
+}
+
+/// Parses a `list --sort` key: `id`, `votes`, or the name of a
+/// custom field, optionally followed by `:asc` or `:desc`.
+fn parse_sort(key: &str) -> std::result::Result<TicketSort, String> {
+	Ok(match key {
+		"id" => TicketSort::Id,
+		"votes" => TicketSort::Votes,
+		field => match field.strip_suffix(":desc") {
+			Some(name) => TicketSort::Field {
+				name: name.to_string(),
+				descending: true,
+			},
+			None => TicketSort::Field {
+				name: field.strip_suffix(":asc").unwrap_or(field).to_string(),
+				descending: false,
+			},
+		},
+	})
+}
+
+/// Prints rows as columns aligned to their widest cell, ignoring
+/// colors. The last column isn't padded.
+fn print_table<const N: usize>(header: [&str; N], rows: &[[String; N]]) {
+	let mut widths = header.map(|cell| cell.chars().count());
+	for row in rows {
+		for (width, cell) in widths.iter_mut().zip(row) {
+			*width = (*width).max(visible_width(cell));
+		}
+	}
+
+	let print_row = |row: &[&str]| {
+		let mut line = String::new();
+		for (i, (cell, width)) in row.iter().zip(widths).enumerate() {
+			line.push_str(cell);
+			if i + 1 < N {
+				line.push_str(&" ".repeat(width - visible_width(cell) + 2));
+			}
+		}
+		println!("{}", line.trim_end());
+	};
+
+	let header = header.map(|cell| paint(Style::Bold, cell));
+	print_row(&header.iter().map(String::as_str).collect::<Vec<_>>());
+	for row in rows {
+		print_row(&row.iter().map(String::as_str).collect::<Vec<_>>());
+	}
+}
+
+/// Prints rows as CSV, after a header row.
+fn print_csv<const N: usize>(header: [&str; N], rows: &[[String; N]]) {
+	println!("{}", csv_row(&header));
+	for row in rows {
+		println!("{}", csv_row(row));
+	}
+}
+
+/// Searches tickets' titles, descriptions and comments.
+///
+/// Searches the titles, descriptions and comments of every ticket in the
+/// workspace, and lists the matching tickets from most to least relevant,
+/// along with a snippet of the matching text.
+#[derive(clap::Args)]
+struct SearchArgs {
+	/// Lists at most the given number of tickets
+	#[arg(short = 'n', long, value_name = "count")]
+	limit: Option<usize>,
+	/// The words to search for
+	#[arg(required = true, value_name = "query")]
+	query: Vec<String>,
+}
+
+fn cmd_search(args: SearchArgs) -> Result<i32> {
+	let SearchArgs { limit, query } = args;
+	let query = query.join(" ");
+
+	let workspace = open_workspace()?;
+	let highlight = !json_output() && color_enabled(false);
+
+	for (i, hit) in workspace
+		.search(&query)?
+		.into_iter()
+		.take(limit.unwrap_or(usize::MAX))
+		.enumerate()
+	{
+		let ticket = workspace.ticket(&hit.slug)?;
+		let title = ticket.title()?.map(|r| r.message());
+
+		let snippet = ticket_snippet(&ticket, &query, highlight)?;
+
+		if json_output() {
+			print_json(&serde_json::json!({
+				"slug": hit.slug,
+				"score": hit.score,
+				"title": title,
+				"snippet": snippet,
+			}));
+			continue;
+		}
+
+		if i > 0 {
+			println!();
+		}
+		println!(
+			"{}  {}",
+			paint(Style::Yellow, &hit.slug),
+			paint(Style::Bold, title.unwrap_or_default())
+		);
+		if let Some(snippet) = snippet {
+			println!("    {}", snippet);
+		}
+	}
+
+	Ok(0)
+}
+
+/// Finds a snippet of a search hit: from its description if it matches,
+/// or else from its first matching comment.
+fn ticket_snippet(
+	ticket: &Ticket<Rc<GitRemote>>,
+	query: &str,
+	highlight: bool,
+) -> Result<Option<String>> {
+	if let Some(description) = ticket.description()? {
+		if let Some(snippet) = search_snippet(&description.message(), query, highlight) {
+			return Ok(Some(snippet));
+		}
+	}
+	for comment in ticket.comments()? {
+		if let Some(snippet) = search_snippet(&comment?.message(), query, highlight) {
+			return Ok(Some(snippet));
+		}
+	}
+
+	Ok(None)
+}
+
+/// The number of characters of context shown around
+/// the first match in a search snippet.
+const SNIPPET_CONTEXT: usize = 40;
+
+/// Extracts the text around the first word of `text` that's one of the
+/// query's terms (compared the same way as the search index does), on a
+/// single line. If `highlight` is set, matching words are shown in bold.
+fn search_snippet(text: &str, query: &str, highlight: bool) -> Option<String> {
+	let is_term_char = |c: char| c.is_alphanumeric();
+	let terms = query
+		.split(|c: char| !is_term_char(c))
+		.filter(|term| !term.is_empty())
+		.map(str::to_lowercase)
+		.collect::<Vec<_>>();
+
+	let text = text.split_whitespace().collect::<Vec<_>>().join(" ");
+	let chars = text.chars().collect::<Vec<_>>();
+
+	// Every word of the text, as `(start, end, matches)` char indices.
+	let mut words = vec![];
+	let mut start = None;
+	for (i, c) in chars.iter().copied().chain([' ']).enumerate() {
+		match (is_term_char(c), start) {
+			(true, None) => start = Some(i),
+			(false, Some(s)) => {
+				let word = chars[s..i].iter().collect::<String>().to_lowercase();
+				words.push((s, i, terms.contains(&word)));
+				start = None;
+			}
+			_ => {}
+		}
+	}
+
+	let &(first, first_end, _) = words.iter().find(|(_, _, matches)| *matches)?;
+	let from = first.saturating_sub(SNIPPET_CONTEXT);
+	let to = (first_end + SNIPPET_CONTEXT).min(chars.len());
+
+	let mut snippet = String::new();
+	if from > 0 {
+		snippet.push('…');
+	}
+	let mut at = from;
+	for &(start, end, matches) in &words {
+		if !matches || !highlight || start < from || end > to {
+			continue;
+		}
+		snippet.extend(&chars[at..start]);
+		snippet.push_str(&paint_if(
+			true,
+			Style::Bold,
+			chars[start..end].iter().collect::<String>(),
+		));
+		at = end;
+	}
+	snippet.extend(&chars[at..to]);
+	if to < chars.len() {
+		snippet.push('…');
+	}
+
+	Some(snippet)
+}
+
+/// Implements `ticket close` and `ticket reopen`.
+/// The arguments of `ticket close` and `ticket reopen`.
+#[derive(clap::Args)]
+struct TicketStateArgs {
+	/// Prints all record information of the state change
+	#[arg(short, long)]
+	verbose: bool,
+	/// Records why the state was changed
+	#[arg(short, long, value_name = "reason")]
+	reason: Option<String>,
+	/// The ticket
+	#[arg(value_name = "slug")]
+	slug: String,
+}
+
+fn cmd_ticket_set_state(args: TicketStateArgs, state: TicketState) -> Result<i32> {
+	let TicketStateArgs {
+		verbose,
+		reason,
+		slug,
+	} = args;
+	let reason = reason.as_deref();
+
+	let workspace = open_workspace()?;
+	let ticket = workspace.ticket(&slug)?;
+	let record = ticket.set_state_with_reason(state, reason.unwrap_or_default())?;
+
+	if verbose || json_output() {
+		print_record(&record, true);
+	}
+
+	Ok(0)
+}
+
+/// Gets or sets a ticket's title.
+///
+/// Returns non-zero if the ticket's title is not set and no new title is
+/// provided.
+#[derive(clap::Args)]
+struct TicketTitleArgs {
+	/// Prints all record information along with the title
+	#[arg(short, long)]
+	verbose: bool,
+	/// Performs a commit even if the last committed title is the same as
+	/// the new title
+	#[arg(short, long)]
+	force: bool,
+	/// The ticket
+	#[arg(value_name = "slug")]
+	slug: String,
+	/// The new title
+	#[arg(value_name = "title")]
+	new_title: Option<String>,
+}
+
+fn cmd_ticket_title(args: TicketTitleArgs) -> Result<i32> {
+	let TicketTitleArgs {
+		verbose,
+		force,
+		slug,
+		new_title: write_title,
+	} = args;
+	let write_title = write_title.as_deref();
+	let idempotent = !force;
+
+	let workspace = open_workspace()?;
+	let ticket = workspace.ticket(&slug)?;
+
+	if let Some(title) = write_title {
+		let record = if idempotent {
+			if let Some(record) = ticket.title()?
+				&& record.message() == title
+			{
+				record
+			} else {
+				ticket.set_title(title)?
+			}
+		} else {
+			ticket.set_title(title)?
+		};
+
+		if verbose || json_output() {
+			print_record(&record, true);
+		}
+
+		Ok(0)
+	} else if let Some(record) = ticket.title()? {
+		print_record(&record, verbose);
+		Ok(0)
+	} else {
+		Ok(1)
+	}
+}
+
+/// Adds or removes a ticket's labels, or defines a project's.
+#[derive(clap::Subcommand)]
+#[command(arg_required_else_help = true)]
+enum LabelCommand {
+	/// Adds labels to a ticket.
+	///
+	/// Labels that are neither defined on the ticket's project nor used by
+	/// its tickets yet are noted, along with the known labels they could be
+	/// a typo of.
+	Add(LabelEditArgs),
+	/// Removes labels from a ticket.
+	Rm(LabelEditArgs),
+	List(LabelListArgs),
+	Define(LabelDefineArgs),
+	Undefine(LabelUndefineArgs),
+}
+
+fn cmd_label(command: LabelCommand) -> Result<i32> {
+	match command {
+		LabelCommand::Add(args) => cmd_label_edit(args, true),
+		LabelCommand::Rm(args) => cmd_label_edit(args, false),
+		LabelCommand::List(args) => cmd_label_list(args),
+		LabelCommand::Define(args) => cmd_label_define(args),
+		LabelCommand::Undefine(args) => cmd_label_undefine(args),
+	}
+}
+
+/// The arguments of `label add` and `label rm`.
+#[derive(clap::Args)]
+struct LabelEditArgs {
+	/// Prints all record information of the changes
+	#[arg(short, long)]
+	verbose: bool,
+	/// The ticket
+	#[arg(value_name = "slug")]
+	slug: String,
+	#[arg(required = true, value_name = "label")]
+	labels: Vec<String>,
+}
+
+fn cmd_label_edit(args: LabelEditArgs, add: bool) -> Result<i32> {
+	let LabelEditArgs {
+		verbose,
+		slug,
+		labels,
+	} = args;
+
+	let workspace = open_workspace()?;
+	let ticket = workspace.ticket(&slug)?;
+
+	let mut status = 0;
+	if add {
+		let project = ticket.project()?;
+		let known = known_labels(&project)?;
+		for label in labels {
+			if !known.iter().any(|known| known.name == label) {
+				let lowercase = label.to_lowercase();
+				let similar = known
+					.iter()
+					.map(|known| known.name.as_str())
+					.filter(|known| {
+						let known = known.to_lowercase();
+						known.starts_with(&lowercase) || lowercase.starts_with(&known)
+					})
+					.map(|known| format!("`{}`", known))
+					.collect::<Vec<_>>();
+				eprintln!(
+					"{}: `{}` is a new label for {}{}",
+					paint_stderr(Style::Yellow, "note"),
+					label,
+					project.slug(),
+					match similar.is_empty() {
+						true => String::new(),
+						false => format!(" (did you mean {}?)", similar.join(" or ")),
+					}
+				);
+			}
+
+			let record = ticket.add_label(&label)?;
+			if verbose || json_output() {
+				print_record(&record, true);
+			}
+		}
+	} else {
+		for label in labels {
+			match ticket.remove_label(&label)? {
+				Some(record) if verbose || json_output() => print_record(&record, true),
+				Some(_) => {}
+				None => {
+					print_error!("{} isn't labeled `{}`", slug, label);
+					status = 1;
+				}
+			}
+		}
+	}
+
+	Ok(status)
+}
+
+/// A label known to a project; see [`known_labels`].
+#[derive(serde::Serialize)]
+struct KnownLabel {
+	name: String,
+	color: Option<String>,
+	description: Option<String>,
+	/// Whether the label is defined on the project,
+	/// rather than only used by its tickets.
+	defined: bool,
+	/// The number of the project's tickets with the label.
+	tickets: usize,
+}
+
+/// Lists the labels known to a project: those defined on it,
+/// in order of definition, then those only used by its tickets.
+fn known_labels(project: &Project<Rc<GitRemote>>) -> Result<Vec<KnownLabel>> {
+	let mut known = project
+		.labels()?
+		.into_iter()
+		.map(|label| KnownLabel {
+			name: label.name,
+			color: label.color,
+			description: label.description,
+			defined: true,
+			tickets: 0,
+		})
+		.collect::<Vec<_>>();
+	for ticket in project.tickets()? {
+		for label in ticket.labels()? {
+			match known.iter_mut().find(|known| known.name == label) {
+				Some(known) => known.tickets += 1,
+				None => known.push(KnownLabel {
+					name: label,
+					color: None,
+					description: None,
+					defined: false,
+					tickets: 1,
+				}),
+			}
+		}
+	}
+
+	Ok(known)
+}
+
+/// Lists a ticket's labels, or a project's.
+///
+/// Lists the labels of a ticket, one per line.
+///
+/// With --project, lists the labels known to a project instead: those
+/// defined on it, with their color and description, then those only used
+/// by its tickets, with how many tickets use them.
+#[derive(clap::Args)]
+struct LabelListArgs {
+	/// Lists the labels of a project
+	#[arg(short, long)]
+	project: bool,
+	/// The ticket, or the project with `--project`
+	#[arg(value_name = "slug")]
+	slug: String,
+}
+
+fn cmd_label_list(args: LabelListArgs) -> Result<i32> {
+	let LabelListArgs { project, slug } = args;
+
+	let workspace = open_workspace()?;
+
+	if !project {
+		for label in workspace.ticket(&slug)?.labels()? {
+			match json_output() {
+				true => print_json(&label),
+				false => println!("{}", label),
+			}
+		}
+		return Ok(0);
+	}
+
+	let known = known_labels(&workspace.project(&slug)?)?;
+	if json_output() {
+		for label in &known {
+			print_json(label);
+		}
+		return Ok(0);
+	}
+
+	let rows = known
+		.into_iter()
+		.map(|label| {
+			[
+				label.name,
+				match label.defined {
+					true => label.color.unwrap_or_default(),
+					false => paint(Style::Dim, "(undefined)"),
+				},
+				label.tickets.to_string(),
+				label.description.unwrap_or_default(),
+			]
+		})
+		.collect::<Vec<_>>();
+	if !rows.is_empty() {
+		print_table(["LABEL", "COLOR", "TICKETS", "DESCRIPTION"], &rows);
+	}
+
+	Ok(0)
+}
+
+/// Defines a label on a project, with its color.
+///
+/// Defines a label on a project, with a color of the form #rrggbb,
+/// replacing the color and description of an existing definition.
+#[derive(clap::Args)]
+struct LabelDefineArgs {
+	/// Prints all record information of the definition
+	#[arg(short, long)]
+	verbose: bool,
+	/// Describes what the label means
+	#[arg(short, long, value_name = "description")]
+	description: Option<String>,
+	/// The project to define the label in
+	#[arg(value_name = "project")]
+	project: String,
+	/// The label's name
+	#[arg(value_name = "name")]
+	name: String,
+	/// The label's color, e.g. `#d73a4a`
+	#[arg(value_name = "color")]
+	color: String,
+}
+
+fn cmd_label_define(args: LabelDefineArgs) -> Result<i32> {
+	let LabelDefineArgs {
+		verbose,
+		description,
+		project,
+		name,
+		color,
+	} = args;
+
+	let workspace = open_workspace()?;
+	let record = workspace.project(&project)?.define_label(
+		&name,
+		&color,
+		description.as_deref().unwrap_or_default(),
+	)?;
+
+	if verbose || json_output() {
+		print_record(&record, true);
+	}
+
+	Ok(0)
+}
+
+/// Removes a label definition from a project. Tickets keep the label.
+#[derive(clap::Args)]
+struct LabelUndefineArgs {
+	/// Prints all record information of the removal
+	#[arg(short, long)]
+	verbose: bool,
+	/// The project the label is defined in
+	#[arg(value_name = "project")]
+	project: String,
+	/// The label's name
+	#[arg(value_name = "name")]
+	name: String,
+}
+
+fn cmd_label_undefine(args: LabelUndefineArgs) -> Result<i32> {
+	let LabelUndefineArgs {
+		verbose,
+		project,
+		name,
+	} = args;
+
+	let workspace = open_workspace()?;
+	let Some(record) = workspace.project(&project)?.remove_label(&name)? else {
+		print_error!("label `{}` isn't defined on {}", name, project);
+		return Ok(1);
+	};
+
+	if verbose || json_output() {
+		print_record(&record, true);
+	}
+
+	Ok(0)
+}
+
+/// The arguments of `assign` and `unassign`.
+#[derive(clap::Args)]
+struct AssignArgs {
+	/// Prints all record information of the changes
+	#[arg(short, long)]
+	verbose: bool,
+	/// The ticket
+	#[arg(value_name = "slug")]
+	slug: String,
+	/// The users, usually given by email address; `me` is the current
+	/// identity, and the start of a registered user's email address or
+	/// name (or of a current assignee's) is completed to the full address,
+	/// as long as only one user matches
+	#[arg(required = true, value_name = "user")]
+	users: Vec<String>,
+}
+
+fn cmd_assign(args: AssignArgs, assign: bool) -> Result<i32> {
+	let AssignArgs {
+		verbose,
+		slug,
+		users,
+	} = args;
+
+	let workspace = open_workspace()?;
+	let ticket = workspace.ticket(&slug)?;
+
+	let mut status = 0;
+	for user in users {
+		let user = resolve_user(&workspace, &ticket, &user)?;
+		let record = match assign {
+			true => Some(ticket.add_assignee(&user)?),
+			false => ticket.remove_assignee(&user)?,
+		};
+
+		match record {
+			Some(record) if verbose || json_output() => print_record(&record, true),
+			Some(_) => {}
+			None => {
+				print_error!("{} isn't assigned to {}", user, slug);
+				status = 1;
+			}
+		}
+	}
+
+	Ok(status)
+}
+
+/// Resolves a user given on the command line: `me` is the current
+/// identity's email address, and anything else that isn't a known user
+/// but starts exactly one known user's email address or name (ignoring
+/// case) is completed to that address. Known users are those registered
+/// in the workspace, and those assigned to the ticket.
+fn resolve_user<'a>(
+	workspace: &'a Workspace<'a, Rc<GitRemote>>,
+	ticket: &Ticket<'a, Rc<GitRemote>>,
+	user: &str,
+) -> Result<String> {
+	if user == "me" {
+		return Ok(workspace.remote().identity()?.1);
+	}
+
+	let mut known = workspace
+		.users()?
+		.into_iter()
+		.map(|user| (user.email, Some(user.name)))
+		.collect::<Vec<_>>();
+	for assignee in ticket.assignees()? {
+		if !known.iter().any(|(email, _)| *email == assignee) {
+			known.push((assignee, None));
+		}
+	}
+
+	if known.iter().any(|(email, _)| email == user) {
+		return Ok(user.to_string());
+	}
+
+	let lowercase = user.to_lowercase();
+	let matches = known
+		.into_iter()
+		.filter(|(email, name)| {
+			email.to_lowercase().starts_with(&lowercase)
+				|| name
+					.as_ref()
+					.is_some_and(|name| name.to_lowercase().starts_with(&lowercase))
+		})
+		.map(|(email, _)| email)
+		.collect::<Vec<_>>();
+
+	match &matches[..] {
+		[] => Ok(user.to_string()),
+		[email] => Ok(email.clone()),
+		_ => Err(Error::AmbiguousUser(user.to_string(), matches.join(", "))),
+	}
+}
+
+/// Moves a ticket to another project.
+///
+/// Moves a ticket to another project, printing its new slug.
+///
+/// The ticket keeps its history (comments, labels, state and so on), but
+/// gets a new slug in the target project. Its old slug keeps working: it
+/// redirects to the new one, as do dependencies on the ticket.
+#[derive(clap::Args)]
+struct MoveArgs {
+	/// The ticket to move
+	#[arg(value_name = "slug")]
+	slug: String,
+	/// The project to move it to
+	#[arg(value_name = "project")]
+	project: String,
+}
+
+fn cmd_move(args: MoveArgs) -> Result<i32> {
+	let MoveArgs { slug, project } = args;
+
+	let workspace = open_workspace()?;
+	let ticket = workspace.ticket(&slug)?;
+	let target = workspace.project(&project)?;
+	if ticket.project()?.slug() == target.slug() {
+		print_error!("{} already belongs to {}", ticket.slug(), target.slug());
+		return Ok(1);
+	}
+
+	let moved = ticket.move_to(target.slug())?.slug().to_string();
+
+	if json_output() {
+		print_json(&serde_json::json!({
+			"from": ticket.slug(),
+			"to": moved,
+			"dry_run": dry_run(),
+		}));
+	} else {
+		println!("{}", moved);
+	}
+	eprintln!(
+		"{} {} to {}{}",
+		match dry_run() {
+			true => paint_stderr(Style::Yellow, "would move"),
+			false => paint_stderr(Style::Green, "moved"),
+		},
+		ticket.slug(),
+		paint_stderr(Style::Bold, &moved),
+		match dry_run() {
+			true => String::new(),
+			false => format!("; {} now redirects to it", ticket.slug()),
+		}
+	);
+
+	Ok(0)
+}
+
+/// The arguments of `archive` and `unarchive`.
+#[derive(clap::Args)]
+struct ArchiveArgs {
+	/// Prints all record information of the change
+	#[arg(short, long)]
+	verbose: bool,
+	/// The project or ticket
+	#[arg(value_name = "project | ticket")]
+	slug: String,
+}
+
+fn cmd_archive(args: ArchiveArgs, archive: bool) -> Result<i32> {
+	let ArchiveArgs { verbose, slug } = args;
+
+	let workspace = open_workspace()?;
+
+	let record = match slug.contains('-') {
+		true => {
+			let ticket = workspace.ticket(&slug)?;
+			let record = match archive {
+				true => ticket.archive()?,
+				false => ticket.unarchive()?,
+			};
+
+			let project = ticket.project()?;
+			if !archive && project.is_archived()? {
+				match record {
+					Some(_) => eprintln!(
+						"{}: {} is still archived along with its project, {}",
+						paint_stderr(Style::Yellow, "note"),
+						slug,
+						project.slug()
+					),
+					None => {
+						print_error!(
+							"{} is archived along with its project; unarchive {} instead",
+							slug,
+							project.slug()
+						);
+						return Ok(1);
+					}
+				}
+			}
+			record
+		}
+		false => {
+			let project = workspace.project(&slug)?;
+			match archive {
+				true => project.archive()?,
+				false => project.unarchive()?,
+			}
+		}
+	};
+
+	match record {
+		Some(record) if verbose || json_output() => print_record(&record, true),
+		Some(_) => {}
+		None => {
+			match archive {
+				true => print_error!("{} is already archived", slug),
+				false => print_error!("{} isn't archived", slug),
+			}
+			return Ok(1);
+		}
+	}
+
+	Ok(0)
+}
+
+/// Adds a comment to a ticket.
+///
+/// Adds a comment to a ticket. If no message is given, opens $VISUAL or
+/// $EDITOR (or vi) to write one; lines starting with `#` are ignored, and
+/// an empty message aborts.
+#[derive(clap::Args)]
+struct CommentArgs {
+	/// Prints all record information of the comment
+	#[arg(short, long)]
+	verbose: bool,
+	/// Uses the given message instead of opening an editor
+	#[arg(short, long, value_name = "message")]
+	message: Option<String>,
+	/// The ticket to comment on
+	#[arg(value_name = "slug")]
+	slug: String,
+}
+
+fn cmd_comment(args: CommentArgs) -> Result<i32> {
+	let CommentArgs {
+		verbose,
+		message,
+		slug,
+	} = args;
+
+	let workspace = open_workspace()?;
+	let ticket = workspace.ticket(&slug)?;
+
+	let message = match message {
+		Some(message) => message,
+		None => {
+			let title = ticket.title()?.map(|r| r.message()).unwrap_or_default();
+			edit_message(&format!(
+				concat!(
+					"\n",
+					"# Write your comment on {slug} ({title}).\n",
+					"# Lines starting with '#' will be ignored, and an empty\n",
+					"# message aborts the comment.\n",
+				),
+				slug = ticket.slug(),
+				title = title
+			))?
+		}
+	};
+
+	if message.trim().is_empty() {
+		print_error!("aborting comment due to empty message");
+		return Ok(1);
+	}
+
+	let record = ticket.add_comment(&message)?;
+
+	if verbose || json_output() {
+		print_record(&record, true);
+	}
+
+	Ok(0)
+}
+
+/// Opens the user's editor on a temporary file holding `template`, and
+/// returns what was written, without lines starting with `#`.
+fn edit_message(template: &str) -> Result<String> {
+	let editor = std::env::var("VISUAL")
+		.or_else(|_| std::env::var("EDITOR"))
+		.unwrap_or_else(|_| "vi".to_string());
+
+	let path = std::env::temp_dir().join(format!("minimap-message-{}.md", std::process::id()));
+	std::fs::write(&path, template)?;
+
+	// Like git, allow the editor to carry arguments (e.g. `code --wait`).
+	let mut parts = editor.split_whitespace();
+	let status = std::process::Command::new(parts.next().unwrap_or("vi"))
+		.args(parts)
+		.arg(&path)
+		.status();
+	let contents = std::fs::read_to_string(&path);
+	std::fs::remove_file(&path).ok();
+
+	let status = status?;
+	if !status.success() {
+		return Err(Error::Editor(editor, status));
+	}
+
+	Ok(contents?
+		.lines()
+		.filter(|line| !line.starts_with('#'))
+		.collect::<Vec<_>>()
+		.join("\n")
+		.trim()
+		.to_string())
+}
+
+/// Lists a ticket's comments, from latest to oldest.
+#[derive(clap::Args)]
+struct CommentsArgs {
+	/// Prints each comment using the given format, e.g. "{author}:
+	/// {message}". Available placeholders: {id}, {author}, {email}, {date}
+	/// and {message}
+	#[arg(long, value_name = "format", value_parser = |template: &str| parse_format(template, RECORD_PLACEHOLDERS))]
+	format: Option<Format>,
+	/// The ticket
+	#[arg(value_name = "slug")]
+	slug: String,
+}
+
+fn cmd_comments(args: CommentsArgs) -> Result<i32> {
+	let CommentsArgs { format, slug } = args;
+
+	let workspace = open_workspace()?;
+	let ticket = workspace.ticket(&slug)?;
+
+	start_pager();
+
+	for (i, comment) in ticket.comments()?.enumerate() {
+		let comment = comment?;
+
+		if let Some(format) = &format {
+			println!(
+				"{}",
+				render_format(format, |field| Ok(record_placeholder(&comment, field)))?
+			);
+			continue;
+		}
+
+		if json_output() {
+			print_record(&comment, true);
+			continue;
+		}
+
+		if i > 0 {
+			println!();
+		}
+		println!(
+			"{}",
+			paint(Style::Yellow, format!("comment {}", comment.id()))
+		);
+		println!("Author: {} <{}>", comment.author(), comment.email());
+		println!("Date:   {}", timestamp_to_iso8601(comment.timestamp()));
+		println!();
+		for line in comment.message().lines() {
+			println!("    {}", line);
+		}
+	}
+
+	Ok(0)
+}
+
+/// An entry in `minimap log`'s timeline.
+enum LogEntry<T: Record> {
+	/// An event in the given ticket's activity.
+	Ticket(String, TicketEvent<T>),
+	/// An entry in the workspace's audit log.
+	Audit(AuditEntry<T>),
+}
+
+impl<T: Record> LogEntry<T> {
+	fn record(&self) -> &T {
+		match self {
+			Self::Ticket(_, event) => event.record(),
+			Self::Audit(entry) => &entry.record,
+		}
+	}
+
+	/// A short name for the kind of entry, as printed in JSON output.
+	fn kind(&self) -> &'static str {
+		match self {
+			Self::Ticket(_, event) => match event {
+				TicketEvent::Created(_) => "created",
+				TicketEvent::Deleted(_) => "deleted",
+				TicketEvent::TitleChanged { .. } => "title_changed",
+				TicketEvent::DescriptionChanged(_) => "description_changed",
+				TicketEvent::StateChanged { .. } => "state_changed",
+				TicketEvent::CommentAdded(_) => "comment_added",
+				TicketEvent::CommentDeleted { .. } => "comment_deleted",
+				TicketEvent::AttachmentAdded { .. } => "attachment_added",
+				TicketEvent::AttachmentRemoved { .. } => "attachment_removed",
+				TicketEvent::DependencyAdded { .. } => "dependency_added",
+				TicketEvent::DependencyRemoved { .. } => "dependency_removed",
+			},
+			Self::Audit(_) => "audit",
+		}
+	}
+
+	/// Describes the entry, in one or more lines.
+	fn summary(&self) -> String {
+		let event = match self {
+			Self::Ticket(_, event) => event,
+			Self::Audit(entry) => {
+				let action = format!("{} {}", entry.operation.to_string(), entry.target);
+				return match &entry.reason {
+					Some(reason) => format!("{}\n\n{}", action, reason),
+					None => action,
+				};
+			}
+		};
+
+		match event {
+			TicketEvent::Created(_) => "Created the ticket".to_string(),
+			TicketEvent::Deleted(_) => "Deleted the ticket".to_string(),
+			TicketEvent::TitleChanged { title, .. } => {
+				format!("Changed the title to \"{}\"", title)
+			}
+			TicketEvent::DescriptionChanged(_) => "Changed the description".to_string(),
+			TicketEvent::StateChanged { state, reason, .. } => {
+				let action = match state {
+					TicketState::Open => "Reopened the ticket",
+					TicketState::Closed => "Closed the ticket",
+				};
+				match reason {
+					Some(reason) => format!("{}\n\n{}", action, reason),
+					None => action.to_string(),
+				}
+			}
+			TicketEvent::CommentAdded(record) => format!("Commented\n\n{}", record.message()),
+			TicketEvent::CommentDeleted { comment_id, .. } => {
+				format!("Deleted comment {}", comment_id)
+			}
+			TicketEvent::AttachmentAdded { name, .. } => format!("Attached {}", name),
+			TicketEvent::AttachmentRemoved { name, .. } => format!("Removed attachment {}", name),
+			TicketEvent::DependencyAdded {
+				origin, endpoint, ..
+			} => format!("Added dependency {}@{}", origin, endpoint),
+			TicketEvent::DependencyRemoved {
+				origin, endpoint, ..
+			} => format!("Removed dependency {}@{}", origin, endpoint),
+		}
+	}
+}
+
+/// Shows the history of a ticket, project or workspace.
+///
+/// Shows the history of a ticket, of every ticket in a project, or (if
+/// neither is given) of the whole workspace, from latest to oldest. The
+/// workspace's history includes its audit log.
+#[derive(clap::Args)]
+struct LogArgs {
+	/// Only shows entries from the given date onwards, as YYYY-MM-DD or an
+	/// RFC 3339 timestamp
+	#[arg(long, value_name = "date", value_parser = |date: &str| parse_date(date).ok_or("expected YYYY-MM-DD or an RFC 3339 timestamp"))]
+	since: Option<i64>,
+	/// Only shows entries whose author's name or email contains the given
+	/// text (ignoring case)
+	#[arg(long, value_name = "author")]
+	author: Option<String>,
+	/// Shows at most the given number of entries
+	#[arg(short = 'n', long, value_name = "count")]
+	limit: Option<usize>,
+	/// A project or a ticket
+	#[arg(value_name = "project | ticket")]
+	entity: Option<String>,
+}
+
+fn cmd_log(args: LogArgs) -> Result<i32> {
+	let LogArgs {
+		since,
+		author,
+		limit,
+		entity,
+	} = args;
+	let entity = entity.as_deref();
+	let author = author.map(|author| author.to_lowercase());
+
+	let workspace = open_workspace()?;
+
+	// Ticket slugs are `<project>-<id>`, while project
+	// slugs can only contain letters and digits.
+	let tickets = match entity {
+		Some(slug) if slug.contains('-') => vec![workspace.ticket(slug)?],
+		Some(slug) => workspace.project(slug)?.tickets()?,
+		None => workspace.tickets()?,
+	};
+
+	let mut entries = vec![];
+	for ticket in tickets {
+		for event in ticket.activity()? {
+			entries.push(LogEntry::Ticket(ticket.slug().to_string(), event));
+		}
+	}
+
+	// Audit entries (e.g. deletions) are kept after their targets are
+	// gone, so they're matched by slug.
+	for entry in workspace.audit_log()?.into_iter().rev() {
+		let relevant = match entity {
+			Some(slug) if slug.contains('-') => entry.target == slug,
+			Some(slug) => entry.target == slug || entry.target.starts_with(&format!("{}-", slug)),
+			None => true,
+		};
+		if relevant {
+			entries.push(LogEntry::Audit(entry));
+		}
+	}
+
+	// Every source is ordered from oldest to latest;
+	// the sort keeps that order for equal timestamps.
+	entries.sort_by_key(|entry| entry.record().timestamp());
+	entries.reverse();
+
+	start_pager();
+
+	let entries = entries
+		.into_iter()
+		.filter(|entry| {
+			since.map_or(true, |since| entry.record().timestamp() >= since)
+				&& author.as_ref().map_or(true, |author| {
+					let record = entry.record();
+					record.author().to_lowercase().contains(author)
+						|| record.email().to_lowercase().contains(author)
+				})
+		})
+		.take(limit.unwrap_or(usize::MAX));
+
+	for (i, entry) in entries.enumerate() {
+		let record = entry.record();
+		let ticket = match &entry {
+			LogEntry::Ticket(slug, _) => Some(slug.as_str()),
+			LogEntry::Audit(_) => None,
+		};
+
+		if json_output() {
+			print_json(&serde_json::json!({
+				"event": entry.kind(),
+				"ticket": ticket,
+				"summary": entry.summary(),
+				"record": RecordView::from(record.clone()),
+			}));
+			continue;
+		}
+
+		if i > 0 {
+			println!();
+		}
+		println!(
+			"{}",
+			paint(Style::Yellow, format!("{} {}", entry.kind(), record.id()))
+		);
+		if let Some(ticket) = ticket {
+			println!("Ticket: {}", ticket);
+		}
+		println!("Author: {} <{}>", record.author(), record.email());
+		println!("Date:   {}", timestamp_to_iso8601(record.timestamp()));
+		println!();
+		for line in entry.summary().lines() {
+			println!("    {}", line);
+		}
+	}
+
+	Ok(0)
+}
+
+/// Parses a date given on the command line (`YYYY-MM-DD`, taken as
+/// midnight UTC, or an RFC 3339 timestamp) into a unix timestamp.
+fn parse_date(date: &str) -> Option<i64> {
+	if let Ok(date) = chrono::NaiveDate::parse_from_str(date, "%Y-%m-%d") {
+		return Some(date.and_hms_opt(0, 0, 0)?.and_utc().timestamp());
+	}
+
+	chrono::DateTime::parse_from_rfc3339(date)
+		.ok()
+		.map(|datetime| datetime.timestamp())
+}
+
+/// Follows a ticket, project or workspace as it changes.
+///
+/// Fetches the workspace every interval, and prints what happened since
+/// to a ticket, to the tickets of a project, or (if neither is given) to
+/// every ticket: new comments, state changes and the rest of their
+/// activity, as in `log`, as well as dependencies completing (or becoming
+/// pending again). Runs until interrupted.
+///
+/// With --json, each change is printed as {"event", "ticket", "summary"},
+/// with the "record" of recorded changes or the "dependency" ({"origin",
+/// "endpoint"}) of dependency changes.
+#[derive(clap::Args)]
+struct WatchArgs {
+	/// The number of seconds between fetches
+	#[arg(short, long, value_name = "seconds", default_value_t = 30, value_parser = clap::value_parser!(u64).range(1..))]
+	interval: u64,
+	/// A project or a ticket
+	#[arg(value_name = "project | ticket")]
+	entity: Option<String>,
+}
+
+fn cmd_watch(args: WatchArgs) -> Result<i32> {
+	let WatchArgs { interval, entity } = args;
+	let entity = entity.as_deref();
+
+	let registry = DependencyRegistry::new();
+	let workspace = open_workspace()?;
+
+	// Listed again after every fetch, to pick up new tickets.
+	let watched = || -> Result<Vec<Ticket<Rc<GitRemote>>>> {
+		Ok(match entity {
+			Some(slug) if slug.contains('-') => vec![workspace.ticket(slug)?],
+			Some(slug) => workspace.project(slug)?.tickets()?,
+			None => workspace.tickets()?,
+		})
+	};
+
+	let mut statuses = dependency_statuses(&workspace, &registry, &watched()?);
+	eprintln!(
+		"watching {}, fetching every {}s",
+		entity.unwrap_or("the workspace"),
+		interval
+	);
+
+	loop {
+		std::thread::sleep(Duration::from_secs(interval));
+
+		// The network may come back by the next fetch.
+		let changes = match workspace.remote().fetch() {
+			Ok(changes) => changes,
+			Err(err) => {
+				print_error!("failed to fetch: {}", err);
+				continue;
+			}
+		};
+
+		// The records that came in are the latest ones of their collections.
+		let mut fetched = BTreeMap::<String, BTreeSet<String>>::new();
+		for (collection, change) in &changes {
+			let (FetchChange::Created(count) | FetchChange::Updated(count)) = change else {
+				continue;
+			};
+			let ids = fetched
+				.entry(collection_owner(&workspace, collection))
+				.or_default();
+			for record in workspace.remote().walk(collection)?.take(*count) {
+				ids.insert(record?.id());
+			}
+		}
+
+		let tickets = watched()?;
+		let mut entries = vec![];
+		for ticket in &tickets {
+			let Some(ids) = fetched.get(ticket.slug()) else {
+				continue;
+			};
+			for event in ticket.activity()? {
+				if ids.contains(&event.record().id()) {
+					entries.push(LogEntry::Ticket(ticket.slug().to_string(), event));
+				}
+			}
+		}
+		entries.sort_by_key(|entry| entry.record().timestamp());
+
+		for entry in entries {
+			let LogEntry::Ticket(slug, _) = &entry else {
+				continue;
+			};
+			let record = entry.record();
+
+			if json_output() {
+				print_json(&serde_json::json!({
+					"event": entry.kind(),
+					"ticket": slug,
+					"summary": entry.summary(),
+					"record": RecordView::from(record.clone()),
+				}));
+			} else {
+				print_change(
+					record.timestamp(),
+					slug,
+					&entry.summary(),
+					Some(&record.author()),
+				);
+			}
+		}
+
+		let current = dependency_statuses(&workspace, &registry, &tickets);
+		for ((slug, origin, endpoint), &status) in &current {
+			// New dependencies are in the tickets' activity.
+			let Some(&previous) = statuses.get(&(slug.clone(), origin.clone(), endpoint.clone()))
+			else {
+				continue;
+			};
+			if previous == status {
+				continue;
+			}
+
+			let (event, summary) = match status {
+				DependencyStatus::Complete => (
+					"dependency_completed",
+					format!("Dependency {}@{} completed", origin, endpoint),
+				),
+				DependencyStatus::Pending => (
+					"dependency_pending",
+					format!("Dependency {}@{} is pending again", origin, endpoint),
+				),
+			};
+			if json_output() {
+				print_json(&serde_json::json!({
+					"event": event,
+					"ticket": slug,
+					"summary": summary,
+					"dependency": { "origin": origin, "endpoint": endpoint },
+				}));
+			} else {
+				print_change(chrono::Utc::now().timestamp(), slug, &summary, None);
+			}
+		}
+		statuses = current;
+	}
+}
+
+/// The statuses of the tickets' dependencies, by ticket slug, origin and
+/// endpoint. Those that can't be resolved are left out.
+fn dependency_statuses(
+	workspace: &Workspace<Rc<GitRemote>>,
+	registry: &DependencyRegistry,
+	tickets: &[Ticket<Rc<GitRemote>>],
+) -> BTreeMap<(String, String, String), DependencyStatus> {
+	let mut statuses = BTreeMap::new();
+	for ticket in tickets {
+		for dependency in ticket.dependencies().unwrap_or_default() {
+			if let Ok(status) = dependency_status(
+				workspace,
+				registry,
+				&dependency.origin,
+				&dependency.endpoint,
+			) {
+				let key = (
+					ticket.slug().to_string(),
+					dependency.origin,
+					dependency.endpoint,
+				);
+				statuses.insert(key, status);
+			}
+		}
+	}
+
+	statuses
+}
+
+/// Prints a change seen by `minimap watch`: the first line of its summary
+/// after the time and ticket, then the rest of it, indented.
+fn print_change(timestamp: i64, ticket: &str, summary: &str, author: Option<&str>) {
+	let mut lines = summary.lines();
+	let mut first = lines.next().unwrap_or_default().to_string();
+	if let Some(author) = author {
+		first.push_str(&format!(" ({})", author));
+	}
+
+	println!(
+		"{}  {}  {}",
+		paint(Style::Dim, timestamp_to_iso8601(timestamp)),
+		paint(Style::Yellow, ticket),
+		first
+	);
+	for line in lines.skip_while(|line| line.is_empty()) {
+		println!("    {}", line);
+	}
+}
+
+/// Shows ticket statistics for the workspace or a project.
+///
+/// Shows ticket statistics for the whole workspace, or for a single
+/// project if one is given.
+///
+/// With --csv, statistics are printed as rows of "category", "name" and
+/// "count": tickets (by state), comments, labels, assignees and activity
+/// (by day).
+#[derive(clap::Args)]
+struct StatsArgs {
+	/// Prints the statistics as CSV, with a header row
+	#[arg(long)]
+	csv: bool,
+	/// Only count the tickets of this project
+	#[arg(value_name = "project")]
+	project: Option<String>,
+}
+
+fn cmd_stats(args: StatsArgs) -> Result<i32> {
+	let StatsArgs { csv, project } = args;
+
+	let workspace = open_workspace()?;
+	let stats = match project {
+		Some(project) => workspace.project(&project)?.stats()?,
+		None => workspace.stats()?,
+	};
+
+	if csv {
+		let mut rows = vec![
+			["tickets", "open", &stats.open.to_string()].map(String::from),
+			["tickets", "closed", &stats.closed.to_string()].map(String::from),
+			["comments", "", &stats.comments.to_string()].map(String::from),
+		];
+		for (label, count) in &stats.labels {
+			rows.push(["label".to_string(), label.clone(), count.to_string()]);
+		}
+		for (assignee, count) in &stats.assignees {
+			rows.push(["assignee".to_string(), assignee.clone(), count.to_string()]);
+		}
+		for (bucket, count) in &stats.activity {
+			rows.push([
+				"activity".to_string(),
+				timestamp_to_date(*bucket),
+				count.to_string(),
+			]);
+		}
+
+		print_csv(["category", "name", "count"], &rows);
+		return Ok(0);
+	}
+
+	if json_output() {
+		print_json(&stats);
+		return Ok(0);
+	}
+
+	println!(
+		"tickets:  {} ({} open, {} closed)",
+		stats.tickets(),
+		stats.open,
+		stats.closed
+	);
+	println!("comments: {}", stats.comments);
+
+	if !stats.labels.is_empty() {
+		println!("\nlabels:");
+		for (label, count) in &stats.labels {
+			println!("    {:<24} {}", label, count);
+		}
+	}
+
+	if !stats.assignees.is_empty() {
+		println!("\nassignees:");
+		for (assignee, count) in &stats.assignees {
+			println!("    {:<24} {}", assignee, count);
+		}
+	}
+
+	if !stats.activity.is_empty() {
+		println!("\nactivity:");
+		for (bucket, count) in &stats.activity {
+			println!("    {:<24} {}", timestamp_to_date(*bucket), count);
+		}
+	}
+
+	Ok(0)
+}
+
+const BOARD_KEYS: &str = concat!(
+	"Keys:\n",
+	"  h, l, arrows    Selects a column\n",
+	"  j, k, arrows    Selects a ticket\n",
+	"  L, shift+right  Closes the selected ticket\n",
+	"  H, shift+left   Reopens the selected ticket\n",
+	"  r               Reloads the board from the workspace\n",
+	"  q, esc          Quits",
+);
+
+/// Shows tickets as a board, with a column per state.
+///
+/// Shows the tickets of the workspace, or of a single project if one is
+/// given, as a board with an open, a blocked and a closed column. Blocked
+/// tickets are open tickets with at least one pending blocking dependency.
+///
+/// In a terminal (and if built with the `tui` feature), the board is
+/// interactive, and tickets can be moved between columns (see the keys
+/// below). Since being blocked depends on dependencies, tickets are never
+/// moved into the blocked column directly; reopened tickets land in it if
+/// they're still blocked.
+#[derive(clap::Args)]
+#[command(after_long_help = BOARD_KEYS)]
+struct BoardArgs {
+	/// Prints the board instead, even in a terminal
+	#[arg(long)]
+	print: bool,
+	/// Only show the tickets of this project
+	#[arg(value_name = "project")]
+	project: Option<String>,
+}
+
+fn cmd_board(args: BoardArgs) -> Result<i32> {
+	let BoardArgs { print, project } = args;
+	let project = project.as_deref();
+
+	let workspace = open_workspace()?;
+
+	if !print && !json_output() && std::io::stdin().is_terminal() && std::io::stdout().is_terminal()
+	{
+		#[cfg(feature = "tui")]
+		return board::run(&workspace, project);
+	}
+
+	let board = load_board(&workspace, project)?;
+
+	if json_output() {
+		print_json(&board);
+		return Ok(0);
+	}
+
+	for (index, state) in BOARD_COLUMNS.into_iter().enumerate() {
+		let cards = board.column(state);
+		if index > 0 {
+			println!();
+		}
+		println!("{} ({})", paint_state(&state.to_string()), cards.len());
+
+		let width = cards.iter().map(|card| card.slug.len()).max().unwrap_or(0);
+		for card in cards {
+			println!(
+				"    {}  {}",
+				paint(Style::Yellow, format!("{:<width$}", card.slug)),
+				card.title.as_deref().unwrap_or("(untitled)")
+			);
+		}
+	}
+
+	Ok(0)
+}
+
+/// The columns of a board, in order.
+const BOARD_COLUMNS: [EffectiveState; 3] = [
+	EffectiveState::Open,
+	EffectiveState::Blocked,
+	EffectiveState::Closed,
+];
+
+/// Tickets grouped by effective state, as shown by `minimap board`.
+#[derive(Default, serde::Serialize)]
+struct Board {
+	open: Vec<BoardCard>,
+	blocked: Vec<BoardCard>,
+	closed: Vec<BoardCard>,
+}
+
+impl Board {
+	fn column(&self, state: EffectiveState) -> &[BoardCard] {
+		match state {
+			EffectiveState::Open => &self.open,
+			EffectiveState::Blocked => &self.blocked,
+			EffectiveState::Closed => &self.closed,
+		}
+	}
+
+	fn column_mut(&mut self, state: EffectiveState) -> &mut Vec<BoardCard> {
+		match state {
+			EffectiveState::Open => &mut self.open,
+			EffectiveState::Blocked => &mut self.blocked,
+			EffectiveState::Closed => &mut self.closed,
+		}
+	}
+}
+
+/// A ticket on a [`Board`].
+#[derive(serde::Serialize)]
+struct BoardCard {
+	slug: String,
+	title: Option<String>,
+}
+
+/// Loads the board of a project, or of the whole workspace.
+fn load_board<'a>(
+	workspace: &'a Workspace<'a, Rc<GitRemote>>,
+	project: Option<&str>,
+) -> Result<Board> {
+	let registry = DependencyRegistry::new();
+	let tickets = match project {
+		Some(project) => workspace.project(project)?.tickets()?,
+		None => workspace.tickets()?,
+	};
+
+	let mut board = Board::default();
+	for ticket in tickets {
+		let state = match ticket.effective_state(&registry) {
+			Ok(state) => state,
+			// Dependencies that can't be resolved don't keep a ticket off
+			// the board.
+			Err(_) if ticket.state()?.0 == TicketState::Open => EffectiveState::Open,
+			Err(err) => return Err(err.into()),
+		};
+
+		board.column_mut(state).push(BoardCard {
+			slug: ticket.slug().to_string(),
+			title: ticket.title()?.map(|title| title.message()),
+		});
+	}
+
+	Ok(board)
+}
+
+/// Runs commands read from stdin, pushing once at the end.
+///
+/// Runs the commands read from stdin against the workspace, which is only
+/// opened once, and pushes all of their changes at the end.
+///
+/// Commands are given one per line, as they would be after `minimap` on
+/// the command line (quoted with '...' or "..." where needed); empty lines
+/// and lines starting with # are ignored. Alternatively, stdin can be a
+/// JSON array of commands, each either a string or an array of arguments.
+/// `batch`, `board`, `init`, `serve`, `sync`, `tui` and `watch` can't be
+/// run.
+///
+/// Each command's result is printed to stderr after its output, or to
+/// stdout as JSON with --json: {"command", "args", "status"}, and "error"
+/// if it failed.
+#[derive(clap::Args)]
+struct BatchArgs {
+	/// Runs the remaining commands after one fails, pushing the changes of
+	/// those that succeeded; by default, the batch stops and nothing is
+	/// pushed
+	#[arg(short, long)]
+	keep_going: bool,
+}
+
+/// A command run by `minimap batch`, parsed like the arguments that
+/// follow `minimap` on the command line (but without its options).
+#[derive(clap::Parser)]
+#[command(name = "minimap", no_binary_name = true)]
+struct BatchCommand {
+	#[command(subcommand)]
+	command: Subcommand,
+}
+
+fn cmd_batch(args: BatchArgs) -> Result<i32> {
+	let BatchArgs { keep_going } = args;
+
+	let mut input = String::new();
+	std::io::Read::read_to_string(&mut std::io::stdin(), &mut input)?;
+	let commands = match parse_batch(&input) {
+		Ok(commands) => commands,
+		Err(err) => {
+			print_error!("{}", err);
+			return Ok(2);
+		}
+	};
+
+	let remote = shared_remote()?;
+	remote.defer_pushes();
+	SHARED_REMOTE.with(|shared| *shared.borrow_mut() = Some(remote.clone()));
+	// Output from one command shouldn't wait for the pager to exit.
+	NO_PAGER.store(true, Ordering::Relaxed);
+
+	let mut failed = 0;
+	for (index, (line, command)) in commands.iter().enumerate() {
+		let (status, error) = match BatchCommand::try_parse_from(command).map(|batch| batch.command)
+		{
+			Ok(
+				command @ (Subcommand::Batch(_)
+				| Subcommand::Board(_)
+				| Subcommand::Init(_)
+				| Subcommand::Serve(_)
+				| Subcommand::Sync
+				| Subcommand::Tui
+				| Subcommand::Watch(_)),
+			) => (
+				2,
+				Some(format!("`{}` can't be run in a batch", command.name())),
+			),
+			Ok(command) => match run_subcommand(command) {
+				Ok(status) => (status, None),
+				Err(err) => (1, Some(err.to_string())),
+			},
+			Err(_) if command.is_empty() => (2, Some("empty command".to_string())),
+			// e.g. `list --help`
+			Err(err) if !err.use_stderr() => {
+				err.print().ok();
+				(0, None)
+			}
+			Err(err) => (2, Some(clap_error_message(&err))),
+		};
+
+		if json_output() {
+			let mut result = serde_json::json!({
+				"command": index + 1,
+				"args": command,
+				"status": status,
+			});
+			if let Some(error) = &error {
+				result["error"] = error.as_str().into();
+			}
+			print_json(&result);
+		} else if status == 0 {
+			eprintln!("{} {}", paint_stderr(Style::Green, "ok"), line);
+		} else {
+			if let Some(error) = &error {
+				print_error!("{}", error);
+			}
+			eprintln!(
+				"{} {}",
+				paint_stderr(Style::Red, format!("failed ({})", status)),
+				line
+			);
+		}
+
+		if status != 0 {
+			failed += 1;
+			if !keep_going {
+				// Dry runs still list what the batch did until then.
+				if !dry_run() {
+					remote.discard_deferred()?;
+				}
+				print_error!(
+					"stopped after command {} of {}; nothing was pushed",
+					index + 1,
+					commands.len()
+				);
+				return Ok(1);
+			}
+		}
+	}
+
+	if dry_run() {
+		eprintln!(
+			"ran {} command{} ({} failed)",
+			commands.len(),
+			if commands.len() == 1 { "" } else { "s" },
+			failed
+		);
+		return Ok(if failed == 0 { 0 } else { 1 });
+	}
+
+	let pushed = remote.push_deferred()?;
+	eprintln!(
+		"ran {} command{} ({} failed), pushing {} changed collection{}",
+		commands.len(),
+		if commands.len() == 1 { "" } else { "s" },
+		failed,
+		pushed,
+		if pushed == 1 { "" } else { "s" }
+	);
+
+	Ok(if failed == 0 { 0 } else { 1 })
+}
+
+/// Parses the commands given to `minimap batch`: a JSON array of
+/// commands (as strings or arrays of arguments), or one per line.
+/// Returns each command as written, along with its arguments.
+#[allow(clippy::type_complexity)]
+fn parse_batch(input: &str) -> std::result::Result<Vec<(String, Vec<String>)>, String> {
+	if input.trim_start().starts_with('[') {
+		let commands = serde_json::from_str::<Vec<serde_json::Value>>(input)
+			.map_err(|err| format!("malformed JSON commands: {}", err))?;
+		return commands
+			.into_iter()
+			.map(|command| match command {
+				serde_json::Value::String(line) => Ok((line.clone(), split_command_line(&line)?)),
+				serde_json::Value::Array(args) => {
+					let line = serde_json::Value::Array(args.clone()).to_string();
+					let args = args
+						.into_iter()
+						.map(|arg| match arg {
+							serde_json::Value::String(arg) => Ok(arg),
+							arg => Err(format!("expected a string argument, not `{}`", arg)),
+						})
+						.collect::<std::result::Result<_, _>>()?;
+					Ok((line, args))
+				}
+				command => Err(format!(
+					"expected a string or array command, not `{}`",
+					command
+				)),
+			})
+			.collect();
+	}
+
+	input
+		.lines()
+		.map(str::trim)
+		.filter(|line| !line.is_empty() && !line.starts_with('#'))
+		.map(|line| Ok((line.to_string(), split_command_line(line)?)))
+		.collect()
+}
+
+/// Splits a line into arguments like a (very) simple shell would:
+/// on whitespace, except within '...' or "...", with \ escaping the
+/// next character outside of single quotes.
+fn split_command_line(line: &str) -> std::result::Result<Vec<String>, String> {
+	let mut args = vec![];
+	let mut current = None::<String>;
+	let mut quote = None;
+	let mut chars = line.chars();
+
+	while let Some(c) = chars.next() {
+		match (quote, c) {
+			(Some(q), c) if c == q => quote = None,
+			(Some('\''), c) => current.get_or_insert_with(String::new).push(c),
+			(_, '\\') => {
+				let escaped = chars
+					.next()
+					.ok_or_else(|| format!("trailing backslash in `{}`", line))?;
+				current.get_or_insert_with(String::new).push(escaped);
+			}
+			(Some(_), c) => current.get_or_insert_with(String::new).push(c),
+			(None, '\'' | '"') => {
+				quote = Some(c);
+				current.get_or_insert_with(String::new);
+			}
+			(None, c) if c.is_whitespace() => args.extend(current.take()),
+			(None, c) => current.get_or_insert_with(String::new).push(c),
+		}
+	}
+
+	if quote.is_some() {
+		return Err(format!("unterminated quote in `{}`", line));
+	}
+	args.extend(current);
+	Ok(args)
+}
+
+fn cmd_sync() -> Result<i32> {
+	let workspace = open_workspace()?;
+	let changes = workspace.remote().fetch()?;
+
+	if json_output() {
+		for (collection, change) in &changes {
+			let (change, records) = match change {
+				FetchChange::Created(records) => ("created", *records),
+				FetchChange::Updated(records) => ("updated", *records),
+				FetchChange::Deleted => ("deleted", 0),
+			};
+			print_json(&serde_json::json!({
+				"collection": collection,
+				"owner": collection_owner(&workspace, collection),
+				"change": change,
+				"records": records,
+			}));
+		}
+		return Ok(0);
+	}
+
+	if changes.is_empty() {
+		println!("already up to date");
+		return Ok(0);
+	}
+
+	// owner -> (new records, whether all of its collections were deleted)
+	let mut owners = BTreeMap::<String, (usize, bool)>::new();
+	for (collection, change) in &changes {
+		let (records, deleted) = owners
+			.entry(collection_owner(&workspace, collection))
+			.or_insert((0, true));
+		match change {
+			FetchChange::Created(count) | FetchChange::Updated(count) => {
+				*records += count;
+				*deleted = false;
+			}
+			FetchChange::Deleted => {}
+		}
+	}
+
+	let plural = |count: usize| if count == 1 { "" } else { "s" };
+	let total = owners.values().map(|(records, _)| records).sum::<usize>();
+	println!("fetched {} new record{}", total, plural(total));
+	let width = owners.keys().map(|owner| owner.len()).max().unwrap_or(0);
+	for (owner, (records, deleted)) in owners {
+		let summary = match deleted {
+			true => paint(Style::Red, "deleted"),
+			false => format!("{} new record{}", records, plural(records)),
+		};
+		println!(
+			"    {}{}  {}",
+			paint(Style::Bold, &owner),
+			" ".repeat(width - owner.len()),
+			summary
+		);
+	}
+
+	Ok(0)
+}
+
+/// Names what a collection belongs to: a ticket (by slug),
+/// a project, or else the workspace itself.
+fn collection_owner(workspace: &Workspace<Rc<GitRemote>>, collection: &str) -> String {
+	let path = collection.strip_prefix("meta/").unwrap_or(collection);
+	let Some(rest) = path.strip_prefix("project/") else {
+		return "workspace".to_string();
+	};
+
+	let mut parts = rest.split('/');
+	let project = parts.next().unwrap_or_default();
+	match (parts.next(), parts.next().map(str::parse::<u64>)) {
+		(Some("ticket"), Some(Ok(id))) => workspace
+			.project(project)
+			.and_then(|project| project.ticket(id))
+			.map(|ticket| ticket.slug().to_string())
+			// The ticket (or its project) may have been deleted.
+			.unwrap_or_else(|_| format!("{}-{}", project, id)),
+		_ => project.to_string(),
+	}
+}
+
+/// The formats `export` can write.
+#[derive(Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+enum ExportFormat {
+	/// The tickets and their comments, as JSON
+	Json,
+	/// One row per ticket, without comments
+	Csv,
+	/// The tickets and their comments, as a markdown document
+	Markdown,
+	/// A full copy of the workspace that can be imported into another
+	Bundle,
+}
+
+/// Exports tickets as JSON, CSV or markdown.
+///
+/// Exports every ticket of the workspace (or of a single project, if one
+/// is given), along with their comments.
+#[derive(clap::Args)]
+struct ExportArgs {
+	/// The format to export to
+	#[arg(short, long, value_name = "format", value_enum, default_value_t = ExportFormat::Json)]
+	format: ExportFormat,
+	/// Exports to CSV; the same as `--format csv`
+	#[arg(long, conflicts_with = "format")]
+	csv: bool,
+	/// Writes the export to the given file instead of printing it
+	#[arg(short, long, value_name = "path")]
+	output: Option<PathBuf>,
+	/// Also writes the tickets' attachments into the given directory, as
+	/// <ticket>/<name> (and comment attachments as
+	/// <ticket>/comments/<id>/<name>)
+	#[arg(long, value_name = "dir")]
+	attachments: Option<PathBuf>,
+	/// Only export this project
+	#[arg(value_name = "project")]
+	project: Option<String>,
+}
+
+fn cmd_export(args: ExportArgs) -> Result<i32> {
+	let ExportArgs {
+		format,
+		csv,
+		output,
+		attachments,
+		project,
+	} = args;
+	let project = project.as_deref();
+	let format = match csv {
+		true => ExportFormat::Csv,
+		false => format,
+	};
+
+	if format == ExportFormat::Bundle && (project.is_some() || attachments.is_some()) {
+		print_error!(
+			"bundles always contain the whole workspace, including attachments\nusage: minimap export --help"
+		);
+		return Ok(2);
+	}
+
+	let workspace = open_workspace()?;
+
+	let mut writer: Box<dyn Write> = match &output {
+		Some(path) => Box::new(std::io::BufWriter::new(std::fs::File::create(path)?)),
+		None => Box::new(std::io::stdout().lock()),
+	};
+
+	if format == ExportFormat::Bundle {
+		let records = workspace.export(&mut writer)?;
+		writer.flush()?;
+		eprintln!("exported {} records", records);
+		return Ok(0);
+	}
+
+	let projects = match project {
+		Some(slug) => vec![workspace.project(slug)?],
+		None => workspace
+			.projects_with_meta()?
+			.into_iter()
+			.map(|meta| meta.project)
+			.collect(),
+	};
+
+	let mut exported = vec![];
+	let mut tickets = 0;
+	for project in &projects {
+		let mut views = vec![];
+		for ticket in project.tickets()? {
+			if let Some(dir) = &attachments {
+				export_attachments(&ticket, &dir.join(file_name(ticket.slug())))?;
+			}
+			views.push(ticket.view()?);
+		}
+
+		tickets += views.len();
+		exported.push(ExportedProject {
+			slug: project.slug().to_string(),
+			name: project.name()?.map(|r| r.message()),
+			description: project.description()?.map(|r| r.message()),
+			tickets: views,
+		});
+	}
+
+	match format {
+		ExportFormat::Json => {
+			let json = match project {
+				Some(_) => serde_json::to_string_pretty(&exported[0]),
+				None => serde_json::to_string_pretty(&serde_json::json!({
+					"name": workspace.name()?.map(|r| r.message()),
+					"description": workspace.description()?.map(|r| r.message()),
+					"projects": exported,
+				})),
+			};
+			writeln!(writer, "{}", json.expect("failed to serialize export"))?;
+		}
+		ExportFormat::Csv => export_csv(&mut writer, &exported)?,
+		_ => {
+			if project.is_none() {
+				let name = workspace.name()?.map(|r| r.message());
+				writeln!(writer, "# {}", name.as_deref().unwrap_or("Workspace"))?;
+				if let Some(description) = workspace.description()? {
+					writeln!(writer, "\n{}", description.message().trim_end())?;
+				}
+				writeln!(writer)?;
+			}
+			export_markdown(&mut writer, &exported, project.is_some())?;
+		}
+	}
+	writer.flush()?;
+
+	if let Some(path) = &output {
+		eprintln!(
+			"exported {} tickets from {} projects to {}",
+			tickets,
+			exported.len(),
+			path.display()
+		);
+	}
+
+	Ok(0)
+}
+
+/// A project, as written by `minimap export`.
+#[derive(serde::Serialize)]
+struct ExportedProject {
+	slug: String,
+	name: Option<String>,
+	description: Option<String>,
+	tickets: Vec<TicketView>,
+}
+
+/// Writes a ticket's attachments (and those of its comments) into a directory.
+fn export_attachments(ticket: &Ticket<Rc<GitRemote>>, dir: &Path) -> Result<()> {
+	for name in ticket.view()?.attachments {
+		if let Some(data) = ticket.attachment(&name)? {
+			std::fs::create_dir_all(dir)?;
+			std::fs::write(dir.join(file_name(&name)), data)?;
+		}
+	}
+
+	for comment in ticket.comments()? {
+		let comment = comment?;
+		let comment_dir = dir.join("comments").join(comment.id());
+		for name in ticket.comment_attachments(&comment)? {
+			if let Some(data) = comment.attachment(&name)? {
+				std::fs::create_dir_all(&comment_dir)?;
+				std::fs::write(comment_dir.join(file_name(&name)), data)?;
+			}
+		}
+	}
+
+	Ok(())
+}
+
+/// Makes a name safe to use as a single path component.
+fn file_name(name: &str) -> String {
+	let name = name.replace(['/', '\\'], "_");
+	match name.as_str() {
+		"" | "." | ".." => format!("_{}", name),
+		_ => name,
+	}
+}
+
+/// The columns written by [`export_csv`].
+const CSV_COLUMNS: &[&str] = &[
+	"project",
+	"slug",
+	"title",
+	"state",
+	"state_reason",
+	"labels",
+	"assignees",
+	"votes",
+	"estimate",
+	"time_spent",
+	"parent",
+	"description",
+];
+
+fn export_csv(writer: &mut dyn Write, projects: &[ExportedProject]) -> Result<()> {
+	writeln!(writer, "{}", csv_row(CSV_COLUMNS))?;
+	for project in projects {
+		for ticket in &project.tickets {
+			let row = [
+				project.slug.clone(),
+				ticket.slug.clone(),
+				ticket.title.clone().unwrap_or_default(),
+				ticket.state.to_string(),
+				ticket.state_reason.clone().unwrap_or_default(),
+				ticket.labels.join(", "),
+				ticket.assignees.join(", "),
+				ticket.votes.len().to_string(),
+				ticket.estimate.map(|e| e.to_string()).unwrap_or_default(),
+				ticket.time_spent.to_string(),
+				ticket.parent.clone().unwrap_or_default(),
+				ticket.description.clone().unwrap_or_default(),
+			];
+			writeln!(writer, "{}", csv_row(&row))?;
+		}
+	}
+
+	Ok(())
+}
+
+/// Joins fields into a CSV row, quoting them as needed.
+fn csv_row(fields: &[impl AsRef<str>]) -> String {
+	let fields = fields
+		.iter()
+		.map(|field| csv_field(field.as_ref()))
+		.collect::<Vec<_>>();
+	fields.join(",")
+}
+
+/// Quotes a CSV field if needed (RFC 4180).
+fn csv_field(field: &str) -> String {
+	if field.contains([',', '"', '\n', '\r']) {
+		format!("\"{}\"", field.replace('"', "\"\""))
+	} else {
+		field.to_string()
+	}
+}
+
+/// Writes projects as markdown. Projects are level 2 headings,
+/// unless `single` is set (i.e. only one project is exported).
+fn export_markdown(
+	writer: &mut dyn Write,
+	projects: &[ExportedProject],
+	single: bool,
+) -> Result<()> {
+	let level = if single { "#" } else { "##" };
+
+	for project in projects {
+		match &project.name {
+			Some(name) => writeln!(writer, "{} {}: {}\n", level, project.slug, name)?,
+			None => writeln!(writer, "{} {}\n", level, project.slug)?,
+		}
+		if let Some(description) = &project.description {
+			writeln!(writer, "{}\n", description.trim_end())?;
+		}
+
+		for ticket in &project.tickets {
+			writeln!(
+				writer,
+				"{}# {}: {}\n",
+				level,
+				ticket.slug,
+				ticket.title.as_deref().unwrap_or("(untitled)")
+			)?;
+
+			match &ticket.state_reason {
+				Some(reason) => writeln!(
+					writer,
+					"- **State:** {} ({})",
+					ticket.state.to_string(),
+					reason
+				)?,
+				None => writeln!(writer, "- **State:** {}", ticket.state.to_string())?,
+			}
+			if !ticket.labels.is_empty() {
+				writeln!(writer, "- **Labels:** {}", ticket.labels.join(", "))?;
+			}
+			if !ticket.assignees.is_empty() {
+				writeln!(writer, "- **Assignees:** {}", ticket.assignees.join(", "))?;
+			}
+			for dependency in &ticket.dependencies {
+				writeln!(
+					writer,
+					"- **Depends on:** {}@{} ({})",
+					dependency.origin,
+					dependency.endpoint,
+					dependency.kind.to_string()
+				)?;
+			}
+			if !ticket.attachments.is_empty() {
+				writeln!(
+					writer,
+					"- **Attachments:** {}",
+					ticket.attachments.join(", ")
+				)?;
+			}
+			writeln!(writer)?;
+
+			if let Some(description) = &ticket.description {
+				writeln!(writer, "{}\n", description.trim_end())?;
+			}
+
+			// Oldest first, as they would be read.
+			for comment in ticket.comments.iter().rev() {
+				writeln!(
+					writer,
+					"**{}** on {}:\n",
+					comment.record.author,
+					timestamp_to_date(comment.record.timestamp)
+				)?;
+				for line in comment.record.message.trim_end().lines() {
+					match line {
+						"" => writeln!(writer, ">")?,
+						line => writeln!(writer, "> {}", line)?,
+					}
+				}
+				writeln!(writer)?;
+			}
+		}
+	}
+
+	Ok(())
+}
+
+/// Imports tickets from another tracker.
+#[derive(clap::Subcommand)]
+#[command(arg_required_else_help = true)]
+enum ImportSource {
+	Github(ImportGithubArgs),
+}
+
+fn cmd_import(source: ImportSource) -> Result<i32> {
+	match source {
+		ImportSource::Github(args) => cmd_import_github(args),
+	}
+}
+
+const GITHUB_API: &str = "https://api.github.com";
+const GITHUB_PAGE_SIZE: usize = 100;
+
+/// Imports the issues of a GitHub repository.
+///
+/// Imports the issues of a GitHub repository (but not its pull requests)
+/// into a project, oldest first, along with their comments. Labels and
+/// assignees are copied over, and closed issues are closed with GitHub's
+/// reason. The GitHub authors of issues and comments are noted in their
+/// text, since changes are always authored by the current user.
+///
+/// Issues that were already imported into the project are skipped, so the
+/// import can be run again to pick up new issues. Requests are made with
+/// `curl`.
+#[derive(clap::Args)]
+struct ImportGithubArgs {
+	/// The token used to authenticate with GitHub; defaults to
+	/// $MINIMAP_GITHUB_TOKEN (i.e. `origins.github.token` in the
+	/// configuration), then $GITHUB_TOKEN
+	#[arg(long, value_name = "token")]
+	token: Option<String>,
+	/// The URL of the GitHub API, for GitHub Enterprise
+	#[arg(long = "api-url", value_name = "url", default_value = GITHUB_API)]
+	api: String,
+	/// Refers to the given GitHub user by another name (e.g. an email
+	/// address) in the text and assignees of imported tickets; can be
+	/// given more than once
+	#[arg(long = "map-author", value_name = "login=name", value_parser = parse_author_mapping)]
+	authors: Vec<(String, String)>,
+	/// The repository, as <owner>/<repo>
+	#[arg(value_name = "owner/repo", value_parser = parse_repository)]
+	repository: String,
+	/// The project to import into
+	#[arg(value_name = "project")]
+	project: String,
+}
+
+/// Parses a `--map-author` mapping, given as `<login>=<name>`.
+fn parse_author_mapping(mapping: &str) -> std::result::Result<(String, String), String> {
+	match mapping.split_once('=') {
+		Some((login, name)) if !login.is_empty() && !name.is_empty() => {
+			Ok((login.to_string(), name.to_string()))
+		}
+		_ => Err("expected `<login>=<name>`".to_string()),
+	}
+}
+
+/// Checks that a repository is given as `<owner>/<repo>`.
+fn parse_repository(repository: &str) -> std::result::Result<String, String> {
+	match repository.split_once('/') {
+		Some((owner, repo)) if !owner.is_empty() && !repo.is_empty() && !repo.contains('/') => {
+			Ok(repository.to_string())
+		}
+		_ => Err("expected `<owner>/<repo>`".to_string()),
+	}
+}
+
+fn cmd_import_github(args: ImportGithubArgs) -> Result<i32> {
+	let ImportGithubArgs {
+		token,
+		api,
+		authors,
+		repository,
+		project,
+	} = args;
+	let api = api.trim_end_matches('/').to_string();
+	let authors = authors.into_iter().collect::<BTreeMap<_, _>>();
+
+	// Opening the workspace exports `origins.github.token`
+	// as $MINIMAP_GITHUB_TOKEN.
+	let workspace = open_workspace()?;
+	let project = workspace.project(&project)?;
+
+	let client = CurlClient {
+		token: token
+			.or_else(|| std::env::var("MINIMAP_GITHUB_TOKEN").ok())
+			.or_else(|| std::env::var("GITHUB_TOKEN").ok())
+			.filter(|token| !token.is_empty()),
+	};
+	let author = |user: Option<&GithubUser>| match user {
+		Some(GithubUser { login }) => authors
+			.get(login)
+			.cloned()
+			.unwrap_or_else(|| format!("@{}", login)),
+		None => "a deleted user".to_string(),
+	};
+
+	// Imported tickets link back to their issue in their description.
+	let mut imported = std::collections::HashSet::new();
+	for ticket in project.tickets()? {
+		if let Some(description) = ticket.description()? {
+			imported.extend(
+				description
+					.message()
+					.lines()
+					.filter_map(|line| line.strip_prefix(GITHUB_SOURCE_PREFIX))
+					.map(|url| url.trim_end_matches('.').to_string()),
+			);
+		}
+	}
+
+	let mut issues = vec![];
+	for page in 1.. {
+		eprintln!(
+			"{} issues of {} (page {})...",
+			paint_stderr(Style::Dim, "fetching"),
+			repository,
+			page
+		);
+		let batch = github_get::<Vec<GithubIssue>>(
+			&client,
+			&format!(
+				"{}/repos/{}/issues?state=all&sort=created&direction=asc&per_page={}&page={}",
+				api, repository, GITHUB_PAGE_SIZE, page
+			),
+		)?;
+		let last = batch.len() < GITHUB_PAGE_SIZE;
+		issues.extend(
+			batch
+				.into_iter()
+				.filter(|issue| issue.pull_request.is_none()),
+		);
+		if last {
+			break;
+		}
+	}
+
+	let total = issues.len();
+	let (mut created, mut skipped) = (0, 0);
+	for (index, issue) in issues.into_iter().enumerate() {
+		let progress = format!("[{}/{}]", index + 1, total);
+		if imported.contains(&issue.html_url) {
+			skipped += 1;
+			eprintln!(
+				"{} {} #{} (already imported)",
+				paint_stderr(Style::Dim, progress),
+				paint_stderr(Style::Dim, "skipped"),
+				issue.number
+			);
+			continue;
+		}
+
+		let comments = match issue.comments {
+			0 => vec![],
+			_ => {
+				let mut comments = vec![];
+				for page in 1.. {
+					let batch = github_get::<Vec<GithubComment>>(
+						&client,
+						&format!(
+							"{}/repos/{}/issues/{}/comments?per_page={}&page={}",
+							api, repository, issue.number, GITHUB_PAGE_SIZE, page
+						),
+					)?;
+					let last = batch.len() < GITHUB_PAGE_SIZE;
+					comments.extend(batch);
+					if last {
+						break;
+					}
+				}
+				comments
+			}
+		};
+
+		let closed = issue.state == "closed";
+		let labels = issue
+			.labels
+			.iter()
+			.map(|label| label.name.as_str())
+			.collect::<Vec<_>>();
+		let assignees = issue
+			.assignees
+			.iter()
+			.map(|assignee| author(Some(assignee)))
+			.collect::<Vec<_>>();
+
+		let ticket = project.create_ticket()?;
+		ticket.set_title(&issue.title)?;
+
+		let mut description = issue
+			.body
+			.clone()
+			.unwrap_or_default()
+			.trim_end()
+			.to_string();
+		if !description.is_empty() {
+			description.push_str("\n\n---\n\n");
+		}
+		description.push_str(&format!(
+			"Opened by {} on {}.\n{}{}\n",
+			author(issue.user.as_ref()),
+			github_date(&issue.created_at),
+			GITHUB_SOURCE_PREFIX,
+			issue.html_url
+		));
+		ticket.set_description(&description)?;
+
+		for label in &issue.labels {
+			// Defined so that they keep their color, and so
+			// that workspaces restricting labels accept them.
+			if project.label(&label.name)?.is_none() {
+				project.define_label(
+					&label.name,
+					&format!("#{}", label.color),
+					&label
+						.description
+						.as_deref()
+						.unwrap_or_default()
+						.replace(['\t', '\n'], " "),
+				)?;
+			}
+			ticket.add_label(&label.name)?;
+		}
+		for assignee in &assignees {
+			ticket.add_assignee(assignee)?;
+		}
+		for comment in &comments {
+			ticket.add_comment(&format!(
+				"{} commented on {}:\n\n{}",
+				author(comment.user.as_ref()),
+				github_date(&comment.created_at),
+				comment.body.as_deref().unwrap_or_default().trim_end()
+			))?;
+		}
+		if closed {
+			ticket.set_state_with_reason(
+				TicketState::Closed,
+				&issue.state_reason.clone().unwrap_or_default(),
+			)?;
+		}
+
+		let slug = ticket.slug().to_string();
+		created += 1;
+
+		if json_output() {
+			print_json(&serde_json::json!({
+				"issue": issue.number,
+				"ticket": slug,
+				"title": issue.title,
+				"state": issue.state,
+				"labels": labels,
+				"assignees": assignees,
+				"comments": comments.len(),
+			}));
+		}
+		eprintln!(
+			"{} {} #{} {} as {}{}",
+			paint_stderr(Style::Dim, progress),
+			match dry_run() {
+				true => paint_stderr(Style::Yellow, "would import"),
+				false => paint_stderr(Style::Green, "imported"),
+			},
+			issue.number,
+			issue.title,
+			paint_stderr(Style::Bold, &slug),
+			match (comments.len(), closed) {
+				(0, false) => String::new(),
+				(0, true) => " (closed)".to_string(),
+				(count, closed) => format!(
+					" ({} comments{})",
+					count,
+					if closed { ", closed" } else { "" }
+				),
+			}
+		);
+	}
+
+	eprintln!(
+		"{} {} issues from {} into {}, skipped {}",
+		if dry_run() {
+			"would import"
+		} else {
+			"imported"
+		},
+		created,
+		repository,
+		project.slug(),
+		skipped
+	);
+	Ok(0)
+}
+
+/// Starts the line of an imported ticket's description that
+/// links to its issue; see [`cmd_import_github`].
+const GITHUB_SOURCE_PREFIX: &str = "Imported from ";
+
+#[derive(serde::Deserialize)]
+struct GithubIssue {
+	number: u64,
+	title: String,
+	body: Option<String>,
+	state: String,
+	state_reason: Option<String>,
+	html_url: String,
+	/// `None` for deleted users.
+	user: Option<GithubUser>,
+	#[serde(default)]
+	labels: Vec<GithubLabel>,
+	#[serde(default)]
+	assignees: Vec<GithubUser>,
+	/// The number of comments.
+	comments: u64,
+	created_at: String,
+	/// Only present for pull requests, which GitHub lists as issues.
+	pull_request: Option<serde_json::Value>,
+}
+
+#[derive(serde::Deserialize)]
+struct GithubUser {
+	login: String,
+}
+
+#[derive(serde::Deserialize)]
+struct GithubLabel {
+	name: String,
+	/// `rrggbb`, without the leading `#`.
+	color: String,
+	description: Option<String>,
+}
+
+#[derive(serde::Deserialize)]
+struct GithubComment {
+	body: Option<String>,
+	user: Option<GithubUser>,
+	created_at: String,
+}
+
+/// Formats one of GitHub's timestamps (e.g. `2023-12-08T10:00:00Z`) as a date.
+fn github_date(timestamp: &str) -> &str {
+	timestamp
+		.split_once('T')
+		.map_or(timestamp, |(date, _)| date)
+}
+
+/// Gets and parses a response from the GitHub API.
+fn github_get<T: serde::de::DeserializeOwned>(client: &CurlClient, url: &str) -> Result<T> {
+	let response = client
+		.get(url)
+		.map_err(|err| Error::Http(url.to_string(), err.to_string()))?;
+	if !(200..300).contains(&response.status) {
+		// GitHub explains errors (e.g. rate limiting) in `message`.
+		let message = serde_json::from_str::<serde_json::Value>(&response.body)
+			.ok()
+			.and_then(|body| body["message"].as_str().map(str::to_string))
+			.unwrap_or_else(|| format!("HTTP {}", response.status));
+		return Err(Error::Http(url.to_string(), message));
+	}
+
+	serde_json::from_str(&response.body)
+		.map_err(|err| Error::Http(url.to_string(), format!("unexpected response: {}", err)))
+}
+
+/// An [`HttpClient`] that runs `curl`, since Minimap's own
+/// [`PlainHttpClient`](minimap_core::PlainHttpClient) doesn't support HTTPS.
+struct CurlClient {
+	/// Sent as a bearer token, if set.
+	token: Option<String>,
+}
+
+impl HttpClient for CurlClient {
+	fn get(&self, url: &str) -> std::result::Result<HttpResponse, Box<dyn std::error::Error>> {
+		// Headers are given through stdin, so that
+		// the token doesn't show up in process lists.
+		let mut curl = Command::new("curl")
+			.args(["--silent", "--show-error", "--location"])
+			.args(["--write-out", "\n%{http_code}", "--header", "@-", "--", url])
+			.stdin(Stdio::piped())
+			.stdout(Stdio::piped())
+			.stderr(Stdio::piped())
+			.spawn()
+			.map_err(|err| format!("failed to run curl: {}", err))?;
+
+		let mut headers =
+			String::from("Accept: application/vnd.github+json\nUser-Agent: minimap\n");
+		if let Some(token) = &self.token {
+			headers.push_str(&format!("Authorization: Bearer {}\n", token));
+		}
+		curl.stdin.take().unwrap().write_all(headers.as_bytes())?;
+
+		let output = curl.wait_with_output()?;
+		if !output.status.success() {
+			return Err(String::from_utf8_lossy(&output.stderr)
+				.trim()
+				.to_string()
+				.into());
+		}
+
+		let output = String::from_utf8_lossy(&output.stdout);
+		let (body, status) = output.rsplit_once('\n').unwrap_or(("", &output));
+		Ok(HttpResponse {
+			status: status.trim().parse()?,
+			body: body.to_string(),
+		})
+	}
+}
+
+/// Links commits in a source repository to tickets.
+///
+/// Installs Git hooks into a source repository that check the tickets its
+/// commit messages reference, with lines like `Refs: ABC-12, ABC-13` or
+/// `Closes: ABC-14` (also `Ref`, `References`, `Fixes` and `Resolves`).
+#[derive(clap::Subcommand)]
+#[command(arg_required_else_help = true)]
+enum HooksCommand {
+	Install(HooksInstallArgs),
+	Uninstall(HooksUninstallArgs),
+	#[command(hide = true)]
+	CommitMsg(HooksCommitMsgArgs),
+	#[command(hide = true)]
+	PostCommit(HooksPostCommitArgs),
+}
+
+fn cmd_hooks(command: HooksCommand) -> Result<i32> {
+	match command {
+		HooksCommand::Install(args) => cmd_hooks_install(args),
+		HooksCommand::Uninstall(args) => cmd_hooks_uninstall(args),
+		HooksCommand::CommitMsg(args) => cmd_hooks_commit_msg(args),
+		HooksCommand::PostCommit(args) => cmd_hooks_post_commit(args),
+	}
+}
+
+/// Marks the hooks written by `minimap hooks install`,
+/// which it replaces (and `uninstall` removes) without --force.
+const HOOK_MARKER: &str = "# Installed by `minimap hooks install`.";
+
+/// Installs hooks that check and act on ticket references.
+///
+/// Installs a commit-msg hook into a source repository, which rejects
+/// commits referencing tickets that don't exist in the workspace, and,
+/// with --comment or --close, a post-commit hook that updates the
+/// referenced tickets once a commit lands.
+///
+/// The hooks run this minimap executable, with the workspace found from
+/// the repository (as with any command), or the one given by --remote or
+/// --workspace when installing. Commits can skip the check with
+/// `git commit --no-verify`.
+#[derive(clap::Args)]
+struct HooksInstallArgs {
+	/// Also rejects commits that don't reference any ticket
+	#[arg(long)]
+	require: bool,
+	/// Comments on the referenced tickets, naming the commit
+	#[arg(long)]
+	comment: bool,
+	/// Closes the tickets referenced with `Closes:` (or `Fixes:` or
+	/// `Resolves:`)
+	#[arg(long)]
+	close: bool,
+	/// Replaces existing hooks that weren't installed by minimap
+	#[arg(short, long)]
+	force: bool,
+	/// The source repository (by default, the one around the current
+	/// directory)
+	#[arg(long, value_name = "path", default_value = ".")]
+	repo: PathBuf,
+}
+
+fn cmd_hooks_install(args: HooksInstallArgs) -> Result<i32> {
+	let HooksInstallArgs {
+		require,
+		comment,
+		close,
+		force,
+		repo,
+	} = args;
+
+	let hooks = hooks_dir(&repo)?;
+	let mut command = vec![std::env::current_exe()?.display().to_string()];
+	if let Some(remote) = REMOTE.get() {
+		command.extend(["--remote".to_string(), remote.clone()]);
+	}
+	if let Some(workspace) = WORKSPACE.get() {
+		command.extend(["--workspace".to_string(), workspace.clone()]);
+	}
+	command.push("hooks".to_string());
+	let command = command
+		.iter()
+		.map(|arg| shell_quote(arg))
+		.collect::<Vec<_>>()
+		.join(" ");
+
+	let mut commit_msg = format!("{} commit-msg", command);
+	if require {
+		commit_msg.push_str(" --require");
+	}
+	let mut post_commit = format!("{} post-commit", command);
+	if comment {
+		post_commit.push_str(" --comment");
+	}
+	if close {
+		post_commit.push_str(" --close");
+	}
+
+	let mut scripts = vec![("commit-msg", Some(format!("{} \"$1\"", commit_msg)))];
+	// The post-commit hook has nothing to do without either.
+	scripts.push(("post-commit", (comment || close).then_some(post_commit)));
+
+	// Check everything first, so that nothing is half-installed.
+	for (name, _) in &scripts {
+		let path = hooks.join(name);
+		if !force && path.exists() && !is_minimap_hook(&path)? {
+			print_error!(
+				"{} already exists; use --force to replace it",
+				path.display()
+			);
+			return Ok(1);
+		}
+	}
+
+	std::fs::create_dir_all(&hooks)?;
+	for (name, command) in scripts {
+		let path = hooks.join(name);
+		match command {
+			Some(command) => {
+				let script = format!("#!/bin/sh\n{}\nexec {}\n", HOOK_MARKER, command);
+				std::fs::write(&path, script)?;
+				#[cfg(unix)]
+				{
+					use std::os::unix::fs::PermissionsExt;
+					std::fs::set_permissions(&path, std::fs::Permissions::from_mode(0o755))?;
+				}
+				println!("installed {}", path.display());
+			}
+			// A previous install may have written one.
+			None if path.exists() && is_minimap_hook(&path)? => {
+				std::fs::remove_file(&path)?;
+				println!("removed {}", path.display());
+			}
+			None => {}
+		}
+	}
+
+	Ok(0)
+}
+
+/// Removes the hooks installed by `minimap hooks install`.
+#[derive(clap::Args)]
+struct HooksUninstallArgs {
+	/// The source repository (by default, the one around the current
+	/// directory)
+	#[arg(long, value_name = "path", default_value = ".")]
+	repo: PathBuf,
+}
+
+fn cmd_hooks_uninstall(args: HooksUninstallArgs) -> Result<i32> {
+	let HooksUninstallArgs { repo } = args;
+
+	let hooks = hooks_dir(&repo)?;
+	for name in ["commit-msg", "post-commit"] {
+		let path = hooks.join(name);
+		if path.exists() && is_minimap_hook(&path)? {
+			std::fs::remove_file(&path)?;
+			println!("removed {}", path.display());
+		}
+	}
+
+	Ok(0)
+}
+
+/// Finds the hooks directory of the repository around `path`,
+/// honoring `core.hooksPath`.
+fn hooks_dir(path: &Path) -> Result<PathBuf> {
+	let repo = git2::Repository::discover(path).map_err(minimap_core::Error::from)?;
+	let hooks_path = repo
+		.config()
+		.and_then(|config| config.get_path("core.hooksPath"))
+		.ok();
+
+	Ok(match hooks_path {
+		// Relative paths are relative to where hooks run.
+		Some(hooks) if hooks.is_relative() => {
+			repo.workdir().unwrap_or_else(|| repo.path()).join(hooks)
+		}
+		Some(hooks) => hooks,
+		// Linked worktrees share the main repository's hooks.
+		None => match std::fs::read_to_string(repo.path().join("commondir")) {
+			Ok(common) => repo.path().join(common.trim()).join("hooks"),
+			Err(_) => repo.path().join("hooks"),
+		},
+	})
+}
+
+fn is_minimap_hook(path: &Path) -> Result<bool> {
+	Ok(std::fs::read_to_string(path)
+		.map(|script| script.lines().any(|line| line == HOOK_MARKER))
+		// e.g. a binary hook
+		.or_else(|err| match err.kind() {
+			std::io::ErrorKind::InvalidData => Ok(false),
+			_ => Err(err),
+		})?)
+}
+
+/// Quotes an argument for `sh`, if needed.
+fn shell_quote(arg: &str) -> String {
+	let plain = !arg.is_empty()
+		&& arg
+			.chars()
+			.all(|c| c.is_ascii_alphanumeric() || "-_./=:@,+".contains(c));
+	match plain {
+		true => arg.to_string(),
+		false => format!("'{}'", arg.replace('\'', r"'\''")),
+	}
+}
+
+/// Checks the ticket references of a commit message (run by the
+/// commit-msg hook).
+#[derive(clap::Args)]
+struct HooksCommitMsgArgs {
+	/// Rejects messages that don't reference any ticket
+	#[arg(long)]
+	require: bool,
+	/// The file holding the commit message
+	#[arg(value_name = "path")]
+	file: PathBuf,
+}
+
+fn cmd_hooks_commit_msg(args: HooksCommitMsgArgs) -> Result<i32> {
+	let HooksCommitMsgArgs { require, file } = args;
+
+	let references = ticket_references(&std::fs::read_to_string(file)?);
+	if references.is_empty() {
+		if require {
+			print_error!("the commit message doesn't reference a ticket (e.g. `Refs: ABC-12`)");
+			print_error!("commit with --no-verify to skip this check");
+			return Ok(1);
+		}
+		return Ok(0);
+	}
+
+	let workspace = open_workspace()?;
+	let mut missing = 0;
+	for reference in &references {
+		match workspace.ticket(&reference.slug) {
+			Ok(_) => {}
+			Err(
+				minimap_core::Error::NotFound(..) | minimap_core::Error::MalformedTicketSlug(_),
+			) => {
+				print_error!(
+					"the commit message references `{}`, which doesn't exist",
+					reference.slug
+				);
+				missing += 1;
+			}
+			Err(err) => return Err(err.into()),
+		}
+	}
+
+	if missing > 0 {
+		print_error!("commit with --no-verify to skip this check");
+		return Ok(1);
+	}
+
+	Ok(0)
+}
+
+/// Updates the tickets referenced by the latest commit (run by the
+/// post-commit hook).
+#[derive(clap::Args)]
+struct HooksPostCommitArgs {
+	/// Comments on the referenced tickets
+	#[arg(long)]
+	comment: bool,
+	/// Closes the tickets referenced with `Closes:`
+	#[arg(long)]
+	close: bool,
+}
+
+fn cmd_hooks_post_commit(args: HooksPostCommitArgs) -> Result<i32> {
+	let HooksPostCommitArgs { comment, close } = args;
+
+	let repo = git2::Repository::discover(".").map_err(minimap_core::Error::from)?;
+	let commit = repo
+		.head()
+		.and_then(|head| head.peel_to_commit())
+		.map_err(minimap_core::Error::from)?;
+	let id = commit.id().to_string();
+	let summary = commit.summary().unwrap_or_default();
+
+	let references = ticket_references(commit.message().unwrap_or_default());
+	if references.is_empty() {
+		return Ok(0);
+	}
+
+	let workspace = open_workspace()?;
+	let mut failed = 0;
+	for reference in references {
+		let closes = close && reference.kind == ReferenceKind::Closes;
+		let result = (|| -> Result<()> {
+			let ticket = workspace.ticket(&reference.slug)?;
+
+			if comment {
+				ticket.add_comment(&format!("Referenced by commit {}: {}", id, summary))?;
+				eprintln!("minimap: commented on {}", reference.slug);
+			}
+			if closes && ticket.is_open()? {
+				let short = &id[..id.len().min(10)];
+				ticket.set_state_with_reason(
+					TicketState::Closed,
+					&format!("closed by commit {}", short),
+				)?;
+				eprintln!("minimap: closed {}", reference.slug);
+			}
+			Ok(())
+		})();
+
+		// The commit is already made; carry on with the other tickets.
+		if let Err(err) = result {
+			print_error!("failed to update {}: {}", reference.slug, err);
+			failed += 1;
+		}
+	}
+
+	Ok(if failed > 0 { 1 } else { 0 })
+}
+
+/// A parsed `--format` template. See [`parse_format`].
+#[derive(Clone)]
+struct Format(Vec<FormatSegment>);
+
+/// A piece of a `--format` template.
+#[derive(Clone)]
+enum FormatSegment {
+	Literal(String),
+	Placeholder(String),
+}
+
+const RECORD_PLACEHOLDERS: &[&str] = &["id", "author", "email", "date", "message"];
+const PROJECT_PLACEHOLDERS: &[&str] = &[
+	"slug",
+	"name",
+	"description",
+	"id",
+	"author",
+	"email",
+	"date",
+];
+const TICKET_PLACEHOLDERS: &[&str] = &[
+	"slug",
+	"id",
+	"project",
+	"state",
+	"reason",
+	"title",
+	"description",
+];
+
+/// Parses a `--format` template, where `{name}` is replaced by the
+/// value of the placeholder `name` and `{{`/`}}` are literal braces.
+/// Returns a description of the problem if the template is malformed
+/// or uses a placeholder not in `placeholders`.
+fn parse_format(template: &str, placeholders: &[&str]) -> std::result::Result<Format, String> {
+	let mut segments = vec![];
+	let mut literal = String::new();
+	let mut chars = template.chars().peekable();
+
+	while let Some(c) = chars.next() {
+		match c {
+			'{' if chars.peek() == Some(&'{') => {
+				chars.next();
+				literal.push('{');
+			}
+			'}' if chars.peek() == Some(&'}') => {
+				chars.next();
+				literal.push('}');
+			}
+			'{' => {
+				let mut name = String::new();
+				loop {
+					match chars.next() {
+						Some('}') => break,
+						Some(c) => name.push(c),
+						None => return Err(format!("unterminated placeholder `{{{}`", name)),
+					}
+				}
+
+				if !placeholders.contains(&name.as_str()) {
+					return Err(format!(
+						"unknown placeholder `{{{}}}` (expected one of {})",
+						name,
+						placeholders
+							.iter()
+							.map(|name| format!("{{{}}}", name))
+							.collect::<Vec<_>>()
+							.join(", ")
+					));
+				}
+
+				if !literal.is_empty() {
+					segments.push(FormatSegment::Literal(std::mem::take(&mut literal)));
+				}
+				segments.push(FormatSegment::Placeholder(name));
+			}
+			'}' => return Err("unmatched `}` (use `}}` for a literal brace)".to_string()),
+			c => literal.push(c),
+		}
+	}
+
+	if !literal.is_empty() {
+		segments.push(FormatSegment::Literal(literal));
+	}
+
+	Ok(Format(segments))
+}
+
+/// Renders a parsed `--format` template, looking up placeholders
+/// with `value`.
+fn render_format(format: &Format, mut value: impl FnMut(&str) -> Result<String>) -> Result<String> {
+	let mut rendered = String::new();
+	for segment in &format.0 {
+		match segment {
+			FormatSegment::Literal(literal) => rendered.push_str(literal),
+			FormatSegment::Placeholder(name) => rendered.push_str(&value(name)?),
+		}
+	}
+
+	Ok(rendered)
+}
+
+/// Looks up one of [`RECORD_PLACEHOLDERS`].
+fn record_placeholder<R: Record>(record: &R, name: &str) -> String {
+	match name {
+		"id" => record.id(),
+		"author" => record.author(),
+		"email" => record.email(),
+		"date" => timestamp_to_iso8601(record.timestamp()),
+		"message" => record.message(),
+		_ => unreachable!("unknown record placeholder `{}`", name),
+	}
+}
+
+/// Looks up one of [`TICKET_PLACEHOLDERS`].
+fn ticket_placeholder(ticket: &Ticket<Rc<GitRemote>>, name: &str) -> Result<String> {
+	Ok(match name {
+		"slug" => ticket.slug().to_string(),
+		"id" => ticket.id().to_string(),
+		"project" => ticket.project()?.slug().to_string(),
+		"state" => ticket.state()?.0.to_string(),
+		"reason" => ticket.state_reason()?.unwrap_or_default(),
+		"title" => ticket.title()?.map(|r| r.message()).unwrap_or_default(),
+		"description" => ticket
+			.description()?
+			.map(|r| r.message())
+			.unwrap_or_default(),
+		_ => unreachable!("unknown ticket placeholder `{}`", name),
+	})
+}
+
+/// Sends the rest of the command's output through the user's pager, like
+/// git does. Nothing happens if the output isn't a terminal, or with
+/// `--no-pager`. The pager is waited for by [`finish_pager`].
+#[cfg(unix)]
+fn start_pager() {
+	use std::os::unix::io::AsRawFd;
+
+	if NO_PAGER.load(Ordering::Relaxed) || !std::io::stdout().is_terminal() {
+		return;
+	}
+
+	let pager = std::env::var("MINIMAP_PAGER")
+		.or_else(|_| std::env::var("PAGER"))
+		.unwrap_or_else(|_| "less".to_string());
+	if pager.trim().is_empty() || pager.trim() == "cat" {
+		return;
+	}
+
+	let mut command = Command::new("sh");
+	command.arg("-c").arg(&pager).stdin(Stdio::piped());
+	if std::env::var_os("LESS").is_none() {
+		// Quit if the output fits on one screen, and keep colors.
+		command.env("LESS", "FRX");
+	}
+
+	let Ok(mut child) = command.spawn() else {
+		return;
+	};
+	let stdin = child.stdin.take().expect("pager stdin is piped");
+
+	std::io::stdout().flush().ok();
+	// SAFETY: both file descriptors are open; stdout is replaced
+	// (not closed) by the pager's stdin, which stays open via the dup.
+	if unsafe { libc::dup2(stdin.as_raw_fd(), libc::STDOUT_FILENO) } < 0 {
+		child.kill().ok();
+		return;
+	}
+
+	// If the pager is quit before all of the output was written, stop
+	// quietly (as git does) rather than failing to print.
+	unsafe { libc::signal(libc::SIGPIPE, libc::SIG_DFL) };
+
+	*PAGER.lock().unwrap() = Some(child);
+}
+
+#[cfg(not(unix))]
+fn start_pager() {}
+
+/// Closes the output and waits for the user to quit the pager,
+/// if one was started by [`start_pager`].
+fn finish_pager() {
+	let Some(mut pager) = PAGER.lock().unwrap().take() else {
+		return;
+	};
+
+	std::io::stdout().flush().ok();
+	#[cfg(unix)]
+	// SAFETY: stdout isn't used after this point.
+	unsafe {
+		libc::close(libc::STDOUT_FILENO)
+	};
+	pager.wait().ok();
+}
+
+/// Whether the output ends up on a terminal (possibly through the pager),
+/// i.e. whether it can be styled.
+fn terminal_output() -> bool {
+	PAGER.lock().unwrap().is_some() || std::io::stdout().is_terminal()
+}
+
+/// When to color output, as given to `--color` or in the settings.
+#[derive(
+	Debug, Clone, Copy, PartialEq, Eq, serde::Deserialize, serde::Serialize, clap::ValueEnum,
+)]
+#[serde(rename_all = "lowercase")]
+enum ColorChoice {
+	/// Only when printing to a terminal, and `NO_COLOR` isn't set.
+	Auto,
+	Always,
+	Never,
+}
+
+/// A text style; see [`paint`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Style {
+	Bold,
+	Dim,
+	Italic,
+	Underline,
+	Red,
+	Green,
+	Yellow,
+}
+
+impl Style {
+	/// The ANSI escape sequence that turns the style on.
+	fn code(self) -> &'static str {
+		match self {
+			Self::Bold => "\x1b[1m",
+			Self::Dim => "\x1b[2m",
+			Self::Italic => "\x1b[3m",
+			Self::Underline => "\x1b[4m",
+			Self::Red => "\x1b[31m",
+			Self::Green => "\x1b[32m",
+			Self::Yellow => "\x1b[33m",
+		}
+	}
+}
+
+/// The ANSI escape sequence that turns all styles off.
+const RESET: &str = "\x1b[0m";
+
+/// Whether output to stdout (or to stderr, if `stderr` is set)
+/// should be colored.
+fn color_enabled(stderr: bool) -> bool {
+	let choice = *COLOR.lock().unwrap();
+	match choice.or(settings().color).unwrap_or(ColorChoice::Auto) {
+		ColorChoice::Always => true,
+		ColorChoice::Never => false,
+		ColorChoice::Auto => {
+			std::env::var_os("NO_COLOR").map_or(true, |value| value.is_empty())
+				&& match stderr {
+					true => std::io::stderr().is_terminal(),
+					false => terminal_output(),
+				}
+		}
+	}
+}
+
+/// Styles text that's printed to stdout, if colors are enabled.
+fn paint(style: Style, text: impl std::fmt::Display) -> String {
+	paint_if(color_enabled(false), style, text)
+}
+
+/// Styles text that's printed to stderr, if colors are enabled.
+fn paint_stderr(style: Style, text: impl std::fmt::Display) -> String {
+	paint_if(color_enabled(true), style, text)
+}
+
+fn paint_if(enabled: bool, style: Style, text: impl std::fmt::Display) -> String {
+	match enabled {
+		true => format!("{}{}{}", style.code(), text, RESET),
+		false => text.to_string(),
+	}
+}
+
+/// Colors a ticket state (`open`, `closed` or `blocked`, possibly
+/// padded): open tickets are green, closed ones red and blocked
+/// ones yellow.
+fn paint_state(state: &str) -> String {
+	match state.trim() {
+		"open" => paint(Style::Green, state),
+		"closed" => paint(Style::Red, state),
+		"blocked" => paint(Style::Yellow, state),
+		_ => state.to_string(),
+	}
+}
+
+/// The number of characters in the text, not counting ANSI escape
+/// sequences (which take up no space on the terminal).
+fn visible_width(text: &str) -> usize {
+	let mut width = 0;
+	let mut chars = text.chars();
+	while let Some(c) = chars.next() {
+		if c == '\x1b' {
+			chars.by_ref().find(|c| c.is_ascii_alphabetic());
+		} else {
+			width += 1;
+		}
+	}
+	width
+}
+
+/// Whether writes are kept local and discarded (`--dry-run`).
+fn dry_run() -> bool {
+	DRY_RUN.load(Ordering::Relaxed)
+}
+
+/// Lists what a dry run wrote to the shared remote (see [`shared_remote`]),
+/// then discards it. Lists each collection as a JSON object with `--json`.
+fn report_dry_run() -> Result<()> {
+	let Some(remote) = SHARED_REMOTE.with(|remote| remote.borrow_mut().take()) else {
+		eprintln!(
+			"{} nothing would have been committed",
+			paint_stderr(Style::Yellow, "dry run:")
+		);
+		return Ok(());
+	};
+
+	let collections = remote.deferred_records()?;
+	let records = collections
+		.iter()
+		.filter_map(|(_, records)| records.as_ref().map(Vec::len))
+		.sum::<usize>();
+
+	if json_output() {
+		for (collection, records) in &collections {
+			let deleted = records.is_none();
+			let records = records
+				.iter()
+				.flatten()
+				.map(|(record, op)| {
+					let mut view =
+						serde_json::to_value(RecordView::from(record.clone())).unwrap_or_default();
+					if let Some(op) = op {
+						view["op"] = match op {
+							SetOperation::Add => "add",
+							SetOperation::Del => "del",
+						}
+						.into();
+					}
+					view
+				})
+				.collect::<Vec<_>>();
+			print_json(&serde_json::json!({
+				"collection": collection,
+				"deleted": deleted,
+				"records": records,
+			}));
+		}
+	} else {
+		eprintln!(
+			"{} nothing was pushed; would have committed {} record{} to {} collection{}{}",
+			paint_stderr(Style::Yellow, "dry run:"),
+			records,
+			if records == 1 { "" } else { "s" },
+			collections.len(),
+			if collections.len() == 1 { "" } else { "s" },
+			if collections.is_empty() { "" } else { ":" }
+		);
+		for (collection, records) in &collections {
+			let Some(records) = records else {
+				eprintln!(
+					"{} {}",
+					paint_stderr(Style::Bold, collection),
+					paint_stderr(Style::Red, "(deleted)")
+				);
+				continue;
+			};
+
+			eprintln!("{}", paint_stderr(Style::Bold, collection));
+			for (record, op) in records {
+				let message = record.message();
+				let mut lines = message.lines();
+				let first = lines.next().unwrap_or_default();
+				let more = match lines.next() {
+					Some(_) => paint_stderr(Style::Dim, " …"),
+					None => String::new(),
+				};
+				let op = match op {
+					Some(SetOperation::Add) => format!("{} ", paint_stderr(Style::Green, "+")),
+					Some(SetOperation::Del) => format!("{} ", paint_stderr(Style::Red, "-")),
+					None => String::new(),
+				};
+				eprintln!("    {}{}{}", op, first, more);
+			}
+		}
+	}
+
+	remote.discard_deferred()?;
+	Ok(())
+}
+
+/// Gets the message of a clap error, to be printed with [`print_error!`]:
+/// its first line, without its `error: ` prefix or usage hint.
+fn clap_error_message(err: &clap::Error) -> String {
+	let message = err.to_string();
+	let line = message.lines().next().unwrap_or_default();
+	line.strip_prefix("error: ").unwrap_or(line).to_string()
+}
+
+/// Whether commands should print JSON instead of text (`--json`).
+fn json_output() -> bool {
+	JSON_OUTPUT.load(Ordering::Relaxed)
+}
+
+fn print_json<T: serde::Serialize + ?Sized>(value: &T) {
+	println!(
+		"{}",
+		serde_json::to_string(value).expect("failed to serialize output")
+	);
+}
+
+/// Prints a record; `verbose` is implied when printing JSON.
 fn print_record<R: Record>(record: &R, verbose: bool) {
-	if verbose {
-		println!("id:     {}", record.id());
+	if json_output() {
+		print_json(&RecordView::from(record.clone()));
+	} else if verbose {
+		println!("id:     {}", paint(Style::Yellow, record.id()));
 		println!("author: {}", record.author());
 		println!("email:  {}", record.email());
 		println!("date:   {}", timestamp_to_iso8601(record.timestamp()));
@@ -585,6 +5369,13 @@ fn print_record<R: Record>(record: &R, verbose: bool) {
 	}
 }
 
+fn timestamp_to_date(timestamp: i64) -> String {
+	chrono::NaiveDateTime::from_timestamp_opt(timestamp, 0)
+		.unwrap()
+		.date()
+		.to_string()
+}
+
 fn timestamp_to_iso8601(timestamp: i64) -> String {
 	let naive_datetime = chrono::NaiveDateTime::from_timestamp_opt(timestamp, 0).unwrap();
 	let datetime: chrono::DateTime<chrono::Utc> =
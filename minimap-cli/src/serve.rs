@@ -0,0 +1,769 @@
+//! `minimap serve`: the workspace as a JSON API over HTTP.
+//!
+//! Lets web UIs and integrations share one machine's clone of a
+//! workspace, instead of each needing access to its repository. Requests
+//! go through the same core APIs the other commands use, and are handled
+//! one at a time, over plain HTTP/1.1 without keep-alive; TLS is left to
+//! a reverse proxy.
+
+use crate::{open_workspace, paint_stderr, parse_sort, ticket_snippet, Result, StateFilter, Style};
+use minimap_core::{
+	AttachmentInfo, DependencyRegistry, EffectiveState, GitRemote, Record, RecordView, Remote,
+	TicketQuery, TicketState, Workspace,
+};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use std::{
+	io::{BufRead, BufReader, Read, Write},
+	net::{TcpListener, TcpStream},
+	rc::Rc,
+	time::{Duration, Instant},
+};
+
+/// The largest request head (request line and headers) accepted.
+const MAX_HEAD: usize = 16 * 1024;
+/// The largest request body accepted, e.g. for attachment uploads.
+const MAX_BODY: usize = 64 * 1024 * 1024;
+/// How long a client may take to send its request or read the response.
+const CLIENT_TIMEOUT: Duration = Duration::from_secs(10);
+
+const ROUTES: &str = concat!(
+	"Routes:\n",
+	"  GET    /projects\n",
+	"  GET    /projects/<project>/tickets    Takes ?state, ?label, ?assignee, ?sort and\n",
+	"                                        ?limit, like `list`\n",
+	"  POST   /projects/<project>/tickets    Creates a ticket: {\"title\", \"description\"}\n",
+	"  GET    /tickets                       Lists the tickets of every project, as above\n",
+	"  GET    /tickets/<ticket>\n",
+	"  PATCH  /tickets/<ticket>              Sets any of {\"title\", \"description\",\n",
+	"                                        \"state\", \"reason\"}\n",
+	"  GET    /tickets/<ticket>/comments\n",
+	"  POST   /tickets/<ticket>/comments     Comments: {\"message\"}\n",
+	"  GET    /tickets/<ticket>/attachments\n",
+	"  GET    /tickets/<ticket>/attachments/<name>\n",
+	"  PUT    /tickets/<ticket>/attachments/<name>     Uploads the request body\n",
+	"  DELETE /tickets/<ticket>/attachments/<name>\n",
+	"  GET    /search?q=<query>              Takes ?limit\n",
+	"\n",
+	"Errors are answered with {\"error\": <message>}.",
+);
+
+/// Serves the workspace over HTTP, as a JSON API.
+///
+/// Serves the workspace's projects, tickets, comments and attachments
+/// over HTTP, as JSON, until interrupted. Changes are made (and pushed)
+/// as this machine's identity, like any other command's.
+///
+/// Without a token, anyone who can reach the server can read and write
+/// to the workspace; it only listens on localhost by default.
+#[derive(clap::Args)]
+#[command(after_long_help = ROUTES)]
+pub(crate) struct ServeArgs {
+	/// The address to listen on
+	#[arg(short, long, value_name = "address", default_value = "127.0.0.1:7878")]
+	listen: String,
+	/// Requires requests to send `Authorization: Bearer <token>`; can
+	/// also be given through $MINIMAP_SERVE_TOKEN
+	#[arg(long, value_name = "token")]
+	token: Option<String>,
+	/// Lets web pages from the given origin use the API (through CORS)
+	#[arg(long, value_name = "origin")]
+	allow_origin: Option<String>,
+	/// Fetches changes made elsewhere before handling a request, if the
+	/// last fetch is at least this old
+	#[arg(long, value_name = "seconds", default_value_t = 30)]
+	fetch_interval: u64,
+}
+
+pub(crate) fn cmd_serve(args: ServeArgs) -> Result<i32> {
+	let ServeArgs {
+		listen,
+		token,
+		allow_origin,
+		fetch_interval,
+	} = args;
+	let token = token
+		.or_else(|| std::env::var("MINIMAP_SERVE_TOKEN").ok())
+		.filter(|token| !token.is_empty());
+
+	let workspace = open_workspace()?;
+	let listener = TcpListener::bind(listen)?;
+	let address = listener.local_addr()?;
+
+	if token.is_none() && !address.ip().is_loopback() {
+		eprintln!(
+			"{}: serving on {} without a token; anyone who can reach it can change the workspace",
+			paint_stderr(Style::Yellow, "warning"),
+			address
+		);
+	}
+	eprintln!("listening on http://{}", address);
+
+	let mut server = Server {
+		workspace: &workspace,
+		token,
+		allow_origin,
+		fetch_interval: Duration::from_secs(fetch_interval),
+		last_fetch: Instant::now(),
+	};
+	for stream in listener.incoming() {
+		// A client going away mid-request doesn't concern the others.
+		match stream {
+			Ok(stream) => server.serve(stream),
+			Err(err) => print_error!("failed to accept a connection: {}", err),
+		}
+	}
+
+	Ok(0)
+}
+
+struct Server<'a> {
+	workspace: &'a Workspace<'a, Rc<GitRemote>>,
+	token: Option<String>,
+	allow_origin: Option<String>,
+	fetch_interval: Duration,
+	last_fetch: Instant,
+}
+
+impl<'a> Server<'a> {
+	fn serve(&mut self, mut stream: TcpStream) {
+		stream.set_read_timeout(Some(CLIENT_TIMEOUT)).ok();
+		stream.set_write_timeout(Some(CLIENT_TIMEOUT)).ok();
+
+		let (label, response) = match Request::read(&stream) {
+			Ok(request) => (
+				format!("{} {}", request.method, request.target),
+				self.respond(&request),
+			),
+			Err(err) => ("(malformed request)".to_string(), err.into()),
+		};
+		eprintln!("{} {}", label, response.status);
+
+		if let Err(err) = response.write(&mut stream, self.allow_origin.as_deref()) {
+			print_error!("failed to respond to {}: {}", label, err);
+		}
+	}
+
+	fn respond(&mut self, request: &Request) -> Response {
+		// Browsers send preflight requests without credentials.
+		if request.method == "OPTIONS" && self.allow_origin.is_some() {
+			return Response::preflight();
+		}
+		if !self.authorized(request) {
+			return ApiError::new(401, "missing or wrong bearer token").into();
+		}
+
+		if self.last_fetch.elapsed() >= self.fetch_interval {
+			// Stale data is better than none.
+			if let Err(err) = self.workspace.remote().fetch() {
+				print_error!("failed to fetch: {}", err);
+			}
+			self.last_fetch = Instant::now();
+		}
+
+		self.route(request).unwrap_or_else(Into::into)
+	}
+
+	fn authorized(&self, request: &Request) -> bool {
+		let Some(token) = &self.token else {
+			return true;
+		};
+		let given = request
+			.header("authorization")
+			.and_then(|value| value.strip_prefix("Bearer "))
+			.unwrap_or_default();
+
+		// Compared in constant time, so that timing doesn't leak the token.
+		given.len() == token.len()
+			&& given
+				.bytes()
+				.zip(token.bytes())
+				.fold(0, |diff, (a, b)| diff | (a ^ b))
+				== 0
+	}
+
+	fn route(&self, request: &Request) -> std::result::Result<Response, ApiError> {
+		let workspace = self.workspace;
+		let segments = request
+			.segments
+			.iter()
+			.map(String::as_str)
+			.collect::<Vec<_>>();
+
+		match (request.method.as_str(), segments.as_slice()) {
+			("GET", ["projects"]) => {
+				let projects = workspace
+					.projects_with_meta()?
+					.into_iter()
+					.map(|meta| {
+						serde_json::json!({
+							"slug": meta.project.slug(),
+							"name": meta.name.map(|record| record.message()),
+							"description": meta.description.map(|record| record.message()),
+							"record": RecordView::from(meta.record),
+						})
+					})
+					.collect::<Vec<_>>();
+				Ok(Response::json(200, &projects))
+			}
+			("GET", ["projects", project, "tickets"]) => self.list_tickets(request, Some(project)),
+			("POST", ["projects", project, "tickets"]) => {
+				let NewTicket { title, description } = request.json()?;
+				let ticket = workspace.project(project)?.create_ticket()?;
+				if let Some(title) = title {
+					ticket.set_title(&title)?;
+				}
+				if let Some(description) = description {
+					ticket.set_description(&description)?;
+				}
+				Ok(Response::json(201, &ticket.view()?))
+			}
+			("GET", ["tickets"]) => self.list_tickets(request, None),
+			("GET", ["tickets", slug]) => Ok(Response::json(200, &workspace.ticket(slug)?.view()?)),
+			("PATCH", ["tickets", slug]) => {
+				let TicketUpdate {
+					title,
+					description,
+					state,
+					reason,
+				} = request.json()?;
+				let ticket = workspace.ticket(slug)?;
+
+				// The state goes first, as the workflow may deny it.
+				match (state, reason) {
+					(Some(state), reason) => {
+						let state = TicketState::try_from(state.as_str())?;
+						ticket
+							.set_state_with_reason(state, reason.as_deref().unwrap_or_default())?;
+					}
+					(None, Some(_)) => {
+						return Err(ApiError::new(400, "a reason needs a state"));
+					}
+					(None, None) => {}
+				}
+				if let Some(title) = title {
+					ticket.set_title(&title)?;
+				}
+				if let Some(description) = description {
+					ticket.set_description(&description)?;
+				}
+				Ok(Response::json(200, &ticket.view()?))
+			}
+			("GET", ["tickets", slug, "comments"]) => Ok(Response::json(
+				200,
+				&workspace.ticket(slug)?.view()?.comments,
+			)),
+			("POST", ["tickets", slug, "comments"]) => {
+				let NewComment { message } = request.json()?;
+				if message.trim().is_empty() {
+					return Err(ApiError::new(400, "empty comment"));
+				}
+				let record = workspace.ticket(slug)?.add_comment(&message)?;
+				Ok(Response::json(201, &RecordView::from(record)))
+			}
+			("GET", ["tickets", slug, "attachments"]) => {
+				let ticket = workspace.ticket(slug)?;
+				let mut attachments = Vec::new();
+				for name in ticket.view()?.attachments {
+					if let Some(info) = ticket.attachment_info(&name)? {
+						attachments.push(AttachmentEntry { name, info });
+					}
+				}
+				Ok(Response::json(200, &attachments))
+			}
+			("GET", ["tickets", slug, "attachments", name]) => {
+				let ticket = workspace.ticket(slug)?;
+				let no_attachment =
+					|| ApiError::new(404, format!("no attachment named `{}`", name));
+				let info = ticket.attachment_info(name)?.ok_or_else(no_attachment)?;
+				let data = ticket.attachment(name)?.ok_or_else(no_attachment)?;
+
+				let mut response = Response::new(200, &info.mime, data);
+				// Attachments come from users; keep pages (e.g. SVG
+				// images) from running scripts on the API's origin.
+				response
+					.headers
+					.push(("Content-Security-Policy", "sandbox".to_string()));
+				Ok(response)
+			}
+			("PUT", ["tickets", slug, "attachments", name]) => {
+				if !is_attachment_name(name) {
+					return Err(ApiError::new(
+						400,
+						"attachment names may only contain letters, digits, `.`, `_` and `-`",
+					));
+				}
+				let ticket = workspace.ticket(slug)?;
+				ticket.upsert_attachment(name, &request.body)?;
+				let info = ticket
+					.attachment_info(name)?
+					.ok_or_else(|| ApiError::new(500, "the attachment wasn't recorded"))?;
+				let name = name.to_string();
+				Ok(Response::json(201, &AttachmentEntry { name, info }))
+			}
+			("DELETE", ["tickets", slug, "attachments", name]) => {
+				match workspace.ticket(slug)?.remove_attachment(name)? {
+					Ok(_) => Ok(Response::new(204, "text/plain", Vec::new())),
+					Err(_) => Err(ApiError::new(
+						404,
+						format!("no attachment named `{}`", name),
+					)),
+				}
+			}
+			("GET", ["search"]) => {
+				let query = request
+					.param("q")
+					.filter(|query| !query.trim().is_empty())
+					.ok_or_else(|| ApiError::new(400, "missing ?q"))?;
+				let limit = request.parse_param("limit", str::parse::<usize>)?;
+
+				let mut hits = Vec::new();
+				for hit in workspace
+					.search(query)?
+					.into_iter()
+					.take(limit.unwrap_or(usize::MAX))
+				{
+					let ticket = workspace.ticket(&hit.slug)?;
+					hits.push(serde_json::json!({
+						"slug": hit.slug,
+						"score": hit.score,
+						"title": ticket.title()?.map(|r| r.message()),
+						"snippet": ticket_snippet(&ticket, query, false)?,
+					}));
+				}
+				Ok(Response::json(200, &hits))
+			}
+			(method, segments) if is_route(segments) => Err(ApiError::new(
+				405,
+				format!("{} isn't supported on {}", method, request.path()),
+			)),
+			_ => Err(ApiError::new(
+				404,
+				format!("no such route: {}", request.path()),
+			)),
+		}
+	}
+
+	/// Lists tickets like `minimap list --json` does,
+	/// taking its filters as query parameters.
+	fn list_tickets(
+		&self,
+		request: &Request,
+		project: Option<&str>,
+	) -> std::result::Result<Response, ApiError> {
+		let state = request.parse_param("state", |state| {
+			clap::ValueEnum::from_str(state, false)
+				.map_err(|_| "expected open, closed, blocked or all")
+		})?;
+		let sort = request.parse_param("sort", parse_sort)?;
+		let limit = request.parse_param("limit", str::parse::<usize>)?;
+
+		let mut query = TicketQuery::new().sort(sort.unwrap_or_default());
+		query = match state.unwrap_or(StateFilter::Open) {
+			StateFilter::Open => query.state(TicketState::Open),
+			StateFilter::Closed => query.state(TicketState::Closed),
+			StateFilter::Blocked => query.effective_state(EffectiveState::Blocked),
+			StateFilter::All => query,
+		};
+		for label in request.params("label") {
+			query = query.label(label);
+		}
+		if let Some(assignee) = request.param("assignee") {
+			query = query.assignee(assignee);
+		}
+
+		let registry = DependencyRegistry::new();
+		let tickets = match project {
+			Some(project) => self
+				.workspace
+				.project(project)?
+				.query_with(&query, &registry)?,
+			None => self.workspace.query_with(&query, &registry)?,
+		};
+
+		let mut rows = Vec::new();
+		for ticket in tickets.take(limit.unwrap_or(usize::MAX)) {
+			let ticket = ticket?;
+			rows.push(serde_json::json!({
+				"slug": ticket.slug(),
+				"state": ticket.state()?.0,
+				"labels": ticket.labels()?,
+				"assignees": ticket.assignees()?,
+				"title": ticket.title()?.map(|r| r.message()),
+			}));
+		}
+		Ok(Response::json(200, &rows))
+	}
+}
+
+/// Whether some method is supported on the path, for telling
+/// unsupported methods (405) apart from unknown paths (404).
+fn is_route(segments: &[&str]) -> bool {
+	matches!(
+		segments,
+		["projects"]
+			| ["projects", _, "tickets"]
+			| ["tickets"]
+			| ["tickets", _]
+			| ["tickets", _, "comments" | "attachments"]
+			| ["tickets", _, "attachments", _]
+			| ["search"]
+	)
+}
+
+/// Whether an attachment can be uploaded with the name; names end up in
+/// Git ref names, which can't hold just anything.
+fn is_attachment_name(name: &str) -> bool {
+	!name.starts_with('.')
+		&& !name.contains("..")
+		&& !name.ends_with(".lock")
+		&& name
+			.chars()
+			.all(|c| c.is_ascii_alphanumeric() || matches!(c, '.' | '_' | '-'))
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct NewTicket {
+	title: Option<String>,
+	description: Option<String>,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct TicketUpdate {
+	title: Option<String>,
+	description: Option<String>,
+	state: Option<String>,
+	reason: Option<String>,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct NewComment {
+	message: String,
+}
+
+#[derive(Serialize)]
+struct AttachmentEntry {
+	name: String,
+	#[serde(flatten)]
+	info: AttachmentInfo,
+}
+
+struct Request {
+	method: String,
+	/// The request target, as sent.
+	target: String,
+	/// The percent-decoded segments of the target's path.
+	segments: Vec<String>,
+	/// The percent-decoded parameters of the target's query.
+	query: Vec<(String, String)>,
+	/// Headers, with lowercase names.
+	headers: Vec<(String, String)>,
+	body: Vec<u8>,
+}
+
+impl Request {
+	fn read(stream: &TcpStream) -> std::result::Result<Self, ApiError> {
+		let malformed = |what: &str| ApiError::new(400, format!("malformed request {}", what));
+		let mut reader = BufReader::new(stream);
+
+		let mut lines = Vec::new();
+		let mut remaining = MAX_HEAD;
+		loop {
+			let mut line = String::new();
+			let read = (&mut reader)
+				.take(remaining as u64)
+				.read_line(&mut line)
+				.map_err(|_| malformed("head"))?;
+			if !line.ends_with('\n') {
+				return Err(malformed("head"));
+			}
+			remaining -= read;
+
+			let line = line.trim_end_matches(['\r', '\n']);
+			if line.is_empty() {
+				break;
+			}
+			lines.push(line.to_string());
+		}
+
+		let mut lines = lines.into_iter();
+		let request_line = lines.next().ok_or_else(|| malformed("line"))?;
+		let [method, target, _version] = request_line
+			.split(' ')
+			.collect::<Vec<_>>()
+			.try_into()
+			.map_err(|_| malformed("line"))?;
+
+		let (path, query) = target.split_once('?').unwrap_or((target, ""));
+		if !path.starts_with('/') {
+			return Err(malformed("target"));
+		}
+		let segments = path
+			.split('/')
+			.filter(|segment| !segment.is_empty())
+			.map(|segment| percent_decode(segment, false))
+			.collect::<std::result::Result<Vec<_>, _>>()?;
+		let query = query
+			.split('&')
+			.filter(|param| !param.is_empty())
+			.map(|param| {
+				let (name, value) = param.split_once('=').unwrap_or((param, ""));
+				Ok((percent_decode(name, true)?, percent_decode(value, true)?))
+			})
+			.collect::<std::result::Result<Vec<_>, ApiError>>()?;
+
+		let headers = lines
+			.map(|line| {
+				let (name, value) = line.split_once(':').ok_or_else(|| malformed("header"))?;
+				Ok((name.trim().to_ascii_lowercase(), value.trim().to_string()))
+			})
+			.collect::<std::result::Result<Vec<_>, ApiError>>()?;
+
+		let mut request = Self {
+			method: method.to_string(),
+			target: target.to_string(),
+			segments,
+			query,
+			headers,
+			body: Vec::new(),
+		};
+
+		if request.header("transfer-encoding").is_some() {
+			return Err(ApiError::new(
+				411,
+				"chunked requests aren't supported; send a Content-Length",
+			));
+		}
+		let length = request
+			.header("content-length")
+			.map(|length| length.parse::<usize>().map_err(|_| malformed("length")))
+			.transpose()?
+			.unwrap_or(0);
+		if length > MAX_BODY {
+			return Err(ApiError::new(
+				413,
+				format!("request bodies are limited to {} bytes", MAX_BODY),
+			));
+		}
+		request.body.resize(length, 0);
+		reader
+			.read_exact(&mut request.body)
+			.map_err(|_| malformed("body"))?;
+
+		Ok(request)
+	}
+
+	/// The target's path, without its query.
+	fn path(&self) -> &str {
+		self.target.split('?').next().unwrap_or_default()
+	}
+
+	fn header(&self, name: &str) -> Option<&str> {
+		self.headers
+			.iter()
+			.find(|(header, _)| header == name)
+			.map(|(_, value)| value.as_str())
+	}
+
+	/// The first value of the query parameter.
+	fn param<'r>(&'r self, name: &'r str) -> Option<&'r str> {
+		self.params(name).next()
+	}
+
+	fn params<'r>(&'r self, name: &'r str) -> impl Iterator<Item = &'r str> {
+		self.query
+			.iter()
+			.filter(move |(param, _)| param == name)
+			.map(|(_, value)| value.as_str())
+	}
+
+	fn parse_param<T, E: std::fmt::Display>(
+		&self,
+		name: &str,
+		parse: impl FnOnce(&str) -> std::result::Result<T, E>,
+	) -> std::result::Result<Option<T>, ApiError> {
+		self.param(name)
+			.map(|value| {
+				parse(value).map_err(|err| {
+					ApiError::new(400, format!("invalid ?{} `{}`: {}", name, value, err))
+				})
+			})
+			.transpose()
+	}
+
+	/// Parses the body as JSON; an empty body is taken as `{}`.
+	fn json<T: DeserializeOwned>(&self) -> std::result::Result<T, ApiError> {
+		let body = match self.body.is_empty() {
+			true => b"{}".as_slice(),
+			false => &self.body,
+		};
+		serde_json::from_slice(body)
+			.map_err(|err| ApiError::new(400, format!("invalid request body: {}", err)))
+	}
+}
+
+/// Decodes `%XX` escapes (and `+`, in query strings).
+fn percent_decode(text: &str, plus_as_space: bool) -> std::result::Result<String, ApiError> {
+	let malformed = || ApiError::new(400, format!("malformed escape in `{}`", text));
+
+	let mut decoded = Vec::with_capacity(text.len());
+	let mut bytes = text.bytes();
+	while let Some(byte) = bytes.next() {
+		decoded.push(match byte {
+			b'%' => {
+				let hex = [bytes.next(), bytes.next()];
+				let [Some(high), Some(low)] =
+					hex.map(|digit| digit.map(char::from).and_then(|digit| digit.to_digit(16)))
+				else {
+					return Err(malformed());
+				};
+				(high * 16 + low) as u8
+			}
+			b'+' if plus_as_space => b' ',
+			byte => byte,
+		});
+	}
+
+	String::from_utf8(decoded).map_err(|_| malformed())
+}
+
+struct Response {
+	status: u16,
+	headers: Vec<(&'static str, String)>,
+	body: Vec<u8>,
+}
+
+impl Response {
+	fn new(status: u16, content_type: &str, body: Vec<u8>) -> Self {
+		Self {
+			status,
+			headers: vec![("Content-Type", content_type.to_string())],
+			body,
+		}
+	}
+
+	fn json<T: Serialize + ?Sized>(status: u16, value: &T) -> Self {
+		let body = serde_json::to_vec(value).expect("failed to serialize response");
+		Self::new(status, "application/json", body)
+	}
+
+	/// Answers a CORS preflight request.
+	fn preflight() -> Self {
+		let mut response = Self::new(204, "text/plain", Vec::new());
+		response.headers.extend([
+			(
+				"Access-Control-Allow-Methods",
+				"GET, POST, PUT, PATCH, DELETE".to_string(),
+			),
+			(
+				"Access-Control-Allow-Headers",
+				"Authorization, Content-Type".to_string(),
+			),
+			("Access-Control-Max-Age", "600".to_string()),
+		]);
+		response
+	}
+
+	fn write(&self, stream: &mut TcpStream, allow_origin: Option<&str>) -> std::io::Result<()> {
+		let mut head = format!("HTTP/1.1 {} {}\r\n", self.status, reason(self.status));
+		for (name, value) in &self.headers {
+			head.push_str(&format!("{}: {}\r\n", name, value));
+		}
+		if let Some(origin) = allow_origin {
+			head.push_str(&format!("Access-Control-Allow-Origin: {}\r\n", origin));
+		}
+		head.push_str(&format!(
+			"Content-Length: {}\r\nConnection: close\r\n\r\n",
+			self.body.len()
+		));
+
+		stream.write_all(head.as_bytes())?;
+		stream.write_all(&self.body)?;
+		stream.flush()
+	}
+}
+
+fn reason(status: u16) -> &'static str {
+	match status {
+		200 => "OK",
+		201 => "Created",
+		204 => "No Content",
+		400 => "Bad Request",
+		401 => "Unauthorized",
+		403 => "Forbidden",
+		404 => "Not Found",
+		405 => "Method Not Allowed",
+		409 => "Conflict",
+		411 => "Length Required",
+		413 => "Payload Too Large",
+		_ => "Internal Server Error",
+	}
+}
+
+/// An error, answered as `{"error": <message>}`.
+struct ApiError {
+	status: u16,
+	message: String,
+}
+
+impl ApiError {
+	fn new(status: u16, message: impl Into<String>) -> Self {
+		Self {
+			status,
+			message: message.into(),
+		}
+	}
+}
+
+impl From<minimap_core::Error> for ApiError {
+	fn from(err: minimap_core::Error) -> Self {
+		use minimap_core::Error;
+
+		let status = match &err {
+			Error::Malformed(_)
+			| Error::MalformedOrigin(_)
+			| Error::UnknownOrigin(_)
+			| Error::MalformedEndpoint(_)
+			| Error::MalformedProjectSlug(_)
+			| Error::MalformedTicketSlug(_)
+			| Error::MalformedCursor(_)
+			| Error::ZeroPageLimit => 400,
+			Error::PermissionDenied(..) => 403,
+			Error::NotFound(..) => 404,
+			Error::Exists(..)
+			| Error::TransitionDenied(..)
+			| Error::MissingRequiredFields(..)
+			| Error::ForceRequired(_)
+			| Error::ProjectCycle(_)
+			| Error::DependencyCycle(_)
+			| Error::DuplicateCycle(_) => 409,
+			Error::TooLarge(..) => 413,
+			Error::Http(..) => 502,
+			_ => 500,
+		};
+		Self::new(status, err.to_string())
+	}
+}
+
+impl From<crate::Error> for ApiError {
+	fn from(err: crate::Error) -> Self {
+		match err {
+			crate::Error::Minimap(err) => err.into(),
+			err => Self::new(500, err.to_string()),
+		}
+	}
+}
+
+impl From<ApiError> for Response {
+	fn from(err: ApiError) -> Self {
+		let mut response = Response::json(err.status, &serde_json::json!({ "error": err.message }));
+		if err.status == 401 {
+			response
+				.headers
+				.push(("WWW-Authenticate", "Bearer".to_string()));
+		}
+		response
+	}
+}
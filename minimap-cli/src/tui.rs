@@ -0,0 +1,504 @@
+//! `minimap tui`: an interactive project and ticket browser.
+//!
+//! Only built with the `tui` feature. Everything is read from and written
+//! to the workspace through the same core APIs the other commands use; the
+//! browser only keeps plain copies of what it shows, and reloads them
+//! after every change.
+
+use crate::{open_workspace, timestamp_to_date, Result};
+use crossterm::{
+	event::{self, Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers},
+	execute,
+	terminal::{disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen},
+};
+use minimap_core::{GitRemote, Record, TicketState, Workspace};
+use ratatui::{
+	prelude::*,
+	widgets::{Block, Borders, List, ListItem, ListState, Paragraph, Wrap},
+};
+use std::{io::Stdout, rc::Rc};
+
+const HELP: &str = "q quit  tab switch pane  enter open  / filter  s state  c close  o reopen  m comment  r reload";
+
+pub(crate) fn cmd_tui() -> Result<i32> {
+	let workspace = open_workspace()?;
+	let mut app = App::new(&workspace)?;
+	with_terminal(|terminal| app.run(terminal))?;
+
+	Ok(0)
+}
+
+/// Runs `f` on the alternate screen, with the terminal in raw mode.
+pub(crate) fn with_terminal(
+	f: impl FnOnce(&mut Terminal<CrosstermBackend<Stdout>>) -> Result<()>,
+) -> Result<()> {
+	enable_raw_mode()?;
+	execute!(std::io::stdout(), EnterAlternateScreen)?;
+	let result = Terminal::new(CrosstermBackend::new(std::io::stdout()))
+		.map_err(Into::into)
+		.and_then(|mut terminal| f(&mut terminal));
+	// Always give the terminal back, even if something failed.
+	disable_raw_mode()?;
+	execute!(std::io::stdout(), LeaveAlternateScreen)?;
+
+	result
+}
+
+/// A ticket, as listed by the browser.
+struct TicketRow {
+	slug: String,
+	state: TicketState,
+	title: String,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Pane {
+	Projects,
+	Tickets,
+}
+
+/// Which tickets are listed, by state.
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum StateFilter {
+	Open,
+	Closed,
+	All,
+}
+
+impl StateFilter {
+	fn next(self) -> Self {
+		match self {
+			Self::Open => Self::Closed,
+			Self::Closed => Self::All,
+			Self::All => Self::Open,
+		}
+	}
+
+	fn matches(self, state: TicketState) -> bool {
+		match self {
+			Self::Open => state == TicketState::Open,
+			Self::Closed => state == TicketState::Closed,
+			Self::All => true,
+		}
+	}
+
+	fn name(self) -> &'static str {
+		match self {
+			Self::Open => "open",
+			Self::Closed => "closed",
+			Self::All => "all",
+		}
+	}
+}
+
+/// What the user is typing into the input line.
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Input {
+	Filter,
+	Comment,
+}
+
+struct App<'a> {
+	workspace: &'a Workspace<'a, Rc<GitRemote>>,
+	/// `(slug, name)`
+	projects: Vec<(String, Option<String>)>,
+	project_list: ListState,
+	/// Every ticket of the selected project.
+	tickets: Vec<TicketRow>,
+	/// Indices into `tickets` of those that are listed.
+	visible: Vec<usize>,
+	ticket_list: ListState,
+	pane: Pane,
+	state_filter: StateFilter,
+	filter: String,
+	/// The ticket being viewed, as lines of text, and the scroll offset.
+	detail: Option<(Vec<Line<'static>>, u16)>,
+	input: Option<(Input, String)>,
+	status: String,
+	quit: bool,
+}
+
+impl<'a> App<'a> {
+	fn new(workspace: &'a Workspace<'a, Rc<GitRemote>>) -> Result<Self> {
+		let mut app = Self {
+			workspace,
+			projects: vec![],
+			project_list: ListState::default(),
+			tickets: vec![],
+			visible: vec![],
+			ticket_list: ListState::default(),
+			pane: Pane::Projects,
+			state_filter: StateFilter::Open,
+			filter: String::new(),
+			detail: None,
+			input: None,
+			status: HELP.to_string(),
+			quit: false,
+		};
+		app.load_projects()?;
+		Ok(app)
+	}
+
+	fn run<B: Backend>(&mut self, terminal: &mut Terminal<B>) -> Result<()> {
+		while !self.quit {
+			terminal.draw(|frame| self.draw(frame))?;
+
+			if let Event::Key(key) = event::read()?
+				&& key.kind == KeyEventKind::Press
+			{
+				// Errors (e.g. a denied transition) are shown rather
+				// than ending the session.
+				if let Err(err) = self.handle_key(key) {
+					self.status = format!("error: {}", err);
+				}
+			}
+		}
+
+		Ok(())
+	}
+
+	fn load_projects(&mut self) -> Result<()> {
+		let selected = self.selected_project().map(str::to_string);
+		self.projects = self
+			.workspace
+			.projects_with_meta()?
+			.into_iter()
+			.map(|meta| {
+				(
+					meta.project.slug().to_string(),
+					meta.name.map(|name| name.message()),
+				)
+			})
+			.collect();
+
+		let index = selected
+			.and_then(|selected| self.projects.iter().position(|(slug, _)| *slug == selected))
+			.or((!self.projects.is_empty()).then_some(0));
+		self.project_list.select(index);
+		self.load_tickets()
+	}
+
+	fn load_tickets(&mut self) -> Result<()> {
+		self.tickets.clear();
+		if let Some(slug) = self.selected_project() {
+			for ticket in self.workspace.project(slug)?.tickets()? {
+				self.tickets.push(TicketRow {
+					slug: ticket.slug().to_string(),
+					state: ticket.state()?.0,
+					title: ticket.title()?.map(|r| r.message()).unwrap_or_default(),
+				});
+			}
+		}
+		self.apply_filters();
+		Ok(())
+	}
+
+	/// Recomputes which tickets are listed, keeping the
+	/// selected one selected if it still is.
+	fn apply_filters(&mut self) {
+		let selected = self.selected_ticket().map(str::to_string);
+		let filter = self.filter.to_lowercase();
+		self.visible = (0..self.tickets.len())
+			.filter(|&index| {
+				let ticket = &self.tickets[index];
+				self.state_filter.matches(ticket.state)
+					&& (ticket.slug.to_lowercase().contains(&filter)
+						|| ticket.title.to_lowercase().contains(&filter))
+			})
+			.collect();
+
+		let index = selected
+			.and_then(|selected| {
+				self.visible
+					.iter()
+					.position(|&index| self.tickets[index].slug == selected)
+			})
+			.or((!self.visible.is_empty()).then_some(0));
+		self.ticket_list.select(index);
+	}
+
+	fn selected_project(&self) -> Option<&str> {
+		self.project_list
+			.selected()
+			.and_then(|index| self.projects.get(index))
+			.map(|(slug, _)| slug.as_str())
+	}
+
+	fn selected_ticket(&self) -> Option<&str> {
+		self.ticket_list
+			.selected()
+			.and_then(|index| self.visible.get(index))
+			.map(|&index| self.tickets[index].slug.as_str())
+	}
+
+	fn open_detail(&mut self) -> Result<()> {
+		let Some(slug) = self.selected_ticket() else {
+			return Ok(());
+		};
+		let view = self.workspace.ticket(slug)?.view()?;
+
+		let mut lines = vec![
+			Line::from(vec![
+				Span::styled(format!("{}: ", view.slug), Style::new().bold()),
+				Span::raw(view.title.unwrap_or_default()),
+			]),
+			Line::default(),
+			Line::from(match view.state_reason {
+				Some(reason) => format!("State:      {} ({})", view.state.to_string(), reason),
+				None => format!("State:      {}", view.state.to_string()),
+			}),
+		];
+		if !view.labels.is_empty() {
+			lines.push(Line::from(format!(
+				"Labels:     {}",
+				view.labels.join(", ")
+			)));
+		}
+		if !view.assignees.is_empty() {
+			lines.push(Line::from(format!(
+				"Assignees:  {}",
+				view.assignees.join(", ")
+			)));
+		}
+		if let Some(description) = view.description {
+			lines.push(Line::default());
+			lines.extend(description.lines().map(|line| Line::from(line.to_string())));
+		}
+		if !view.comments.is_empty() {
+			lines.push(Line::default());
+			lines.push(Line::styled("Comments:", Style::new().bold()));
+		}
+		// Oldest first, as they would be read.
+		for comment in view.comments.iter().rev() {
+			lines.push(Line::default());
+			lines.push(Line::styled(
+				format!(
+					"{} <{}> on {}",
+					comment.record.author,
+					comment.record.email,
+					timestamp_to_date(comment.record.timestamp)
+				),
+				Style::new().dim(),
+			));
+			lines.extend(
+				comment
+					.record
+					.message
+					.lines()
+					.map(|line| Line::from(format!("    {}", line))),
+			);
+		}
+
+		self.detail = Some((lines, 0));
+		Ok(())
+	}
+
+	fn set_state(&mut self, state: TicketState) -> Result<()> {
+		let Some(slug) = self.selected_ticket().map(str::to_string) else {
+			return Ok(());
+		};
+		self.workspace.ticket(&slug)?.set_state(state)?;
+		self.status = format!("{} is now {}", slug, state.to_string());
+		self.reload()
+	}
+
+	/// Reloads everything shown from the workspace.
+	fn reload(&mut self) -> Result<()> {
+		self.load_projects()?;
+		if self.detail.is_some() {
+			let scroll = self.detail.as_ref().map_or(0, |(_, scroll)| *scroll);
+			self.open_detail()?;
+			if let Some((_, offset)) = &mut self.detail {
+				*offset = scroll;
+			}
+		}
+		Ok(())
+	}
+
+	fn handle_key(&mut self, key: KeyEvent) -> Result<()> {
+		if key.modifiers.contains(KeyModifiers::CONTROL) && key.code == KeyCode::Char('c') {
+			self.quit = true;
+			return Ok(());
+		}
+
+		if let Some((input, buffer)) = &mut self.input {
+			let input = *input;
+			match key.code {
+				KeyCode::Esc => {
+					if input == Input::Filter {
+						self.filter.clear();
+						self.apply_filters();
+					}
+					self.input = None;
+					self.status = HELP.to_string();
+				}
+				KeyCode::Enter => {
+					let text = std::mem::take(buffer);
+					self.input = None;
+					self.status = HELP.to_string();
+					if input == Input::Comment && !text.trim().is_empty() {
+						if let Some(slug) = self.selected_ticket().map(str::to_string) {
+							self.workspace.ticket(&slug)?.add_comment(text.trim())?;
+							self.status = format!("commented on {}", slug);
+							self.reload()?;
+						}
+					}
+				}
+				KeyCode::Backspace => {
+					buffer.pop();
+				}
+				KeyCode::Char(c) => buffer.push(c),
+				_ => {}
+			}
+
+			if input == Input::Filter
+				&& let Some((_, buffer)) = &self.input
+			{
+				self.filter = buffer.clone();
+				self.apply_filters();
+			}
+			return Ok(());
+		}
+
+		match key.code {
+			KeyCode::Char('q') => self.quit = true,
+			KeyCode::Esc if self.detail.is_some() => self.detail = None,
+			KeyCode::Esc => self.quit = true,
+			KeyCode::Char('r') => {
+				self.reload()?;
+				self.status = "reloaded".to_string();
+			}
+			KeyCode::Char('c') => self.set_state(TicketState::Closed)?,
+			KeyCode::Char('o') => self.set_state(TicketState::Open)?,
+			KeyCode::Char('m') if self.selected_ticket().is_some() => {
+				self.input = Some((Input::Comment, String::new()));
+			}
+			KeyCode::Down | KeyCode::Char('j') => self.move_selection(1)?,
+			KeyCode::Up | KeyCode::Char('k') => self.move_selection(-1)?,
+			_ if self.detail.is_some() => {}
+			KeyCode::Tab | KeyCode::Char('h' | 'l') | KeyCode::Left | KeyCode::Right => {
+				self.pane = match self.pane {
+					Pane::Projects => Pane::Tickets,
+					Pane::Tickets => Pane::Projects,
+				};
+			}
+			KeyCode::Enter if self.pane == Pane::Projects => self.pane = Pane::Tickets,
+			KeyCode::Enter => self.open_detail()?,
+			KeyCode::Char('/') => {
+				self.input = Some((Input::Filter, self.filter.clone()));
+			}
+			KeyCode::Char('s') => {
+				self.state_filter = self.state_filter.next();
+				self.apply_filters();
+			}
+			_ => {}
+		}
+
+		Ok(())
+	}
+
+	fn move_selection(&mut self, by: isize) -> Result<()> {
+		if let Some((_, scroll)) = &mut self.detail {
+			*scroll = scroll.saturating_add_signed(by as i16);
+			return Ok(());
+		}
+
+		let (list, len) = match self.pane {
+			Pane::Projects => (&mut self.project_list, self.projects.len()),
+			Pane::Tickets => (&mut self.ticket_list, self.visible.len()),
+		};
+		if len == 0 {
+			return Ok(());
+		}
+		let index = list.selected().unwrap_or(0) as isize + by;
+		list.select(Some(index.clamp(0, len as isize - 1) as usize));
+
+		if self.pane == Pane::Projects {
+			self.load_tickets()?;
+		}
+		Ok(())
+	}
+
+	fn draw(&mut self, frame: &mut Frame) {
+		let [main, status] = *Layout::default()
+			.direction(Direction::Vertical)
+			.constraints([Constraint::Min(1), Constraint::Length(1)])
+			.split(frame.size())
+		else {
+			return;
+		};
+
+		let status_line = match &self.input {
+			Some((Input::Filter, buffer)) => format!("/{}", buffer),
+			Some((Input::Comment, buffer)) => format!("comment: {}", buffer),
+			None => self.status.clone(),
+		};
+		frame.render_widget(Paragraph::new(status_line), status);
+
+		if let Some((lines, scroll)) = &self.detail {
+			let detail = Paragraph::new(lines.clone())
+				.block(Block::default().borders(Borders::ALL))
+				.wrap(Wrap { trim: false })
+				.scroll((*scroll, 0));
+			frame.render_widget(detail, main);
+			return;
+		}
+
+		let [projects, tickets] = *Layout::default()
+			.direction(Direction::Horizontal)
+			.constraints([Constraint::Percentage(25), Constraint::Percentage(75)])
+			.split(main)
+		else {
+			return;
+		};
+
+		let focused = |pane| match self.pane == pane {
+			true => Style::new().reversed(),
+			false => Style::new().underlined(),
+		};
+
+		let project_items = self
+			.projects
+			.iter()
+			.map(|(slug, name)| match name {
+				Some(name) => ListItem::new(format!("{} ({})", name, slug)),
+				None => ListItem::new(slug.clone()),
+			})
+			.collect::<Vec<_>>();
+		let project_list = List::new(project_items)
+			.block(Block::default().borders(Borders::ALL).title("Projects"))
+			.highlight_style(focused(Pane::Projects));
+		frame.render_stateful_widget(project_list, projects, &mut self.project_list);
+
+		let width = self
+			.visible
+			.iter()
+			.map(|&index| self.tickets[index].slug.len())
+			.max()
+			.unwrap_or(0);
+		let ticket_items = self
+			.visible
+			.iter()
+			.map(|&index| {
+				let ticket = &self.tickets[index];
+				let state = match ticket.state {
+					TicketState::Open => Span::styled("open  ", Style::new().green()),
+					TicketState::Closed => Span::styled("closed", Style::new().dim()),
+				};
+				ListItem::new(Line::from(vec![
+					Span::styled(format!("{:<width$}  ", ticket.slug), Style::new().bold()),
+					state,
+					Span::raw(format!("  {}", ticket.title)),
+				]))
+			})
+			.collect::<Vec<_>>();
+		let mut title = format!("Tickets ({})", self.state_filter.name());
+		if !self.filter.is_empty() {
+			title.push_str(&format!(" matching `{}`", self.filter));
+		}
+		let ticket_list = List::new(ticket_items)
+			.block(Block::default().borders(Borders::ALL).title(title))
+			.highlight_style(focused(Pane::Tickets));
+		frame.render_stateful_widget(ticket_list, tickets, &mut self.ticket_list);
+	}
+}
@@ -17,7 +17,7 @@ use crate::*;
 
 #[test]
 fn test_commit() {
-	let workspace = Workspace::open(create_test_remote!());
+	let workspace = Workspace::open(create_test_remote!()).unwrap();
 
 	let commit = workspace.remote().record_builder("coll").commit("test").unwrap();
 	assert_eq!(Record::message(&commit), "test");
@@ -28,7 +28,7 @@ fn test_commit() {
 
 #[test]
 fn test_walk() {
-	let workspace = Workspace::open(create_test_remote!());
+	let workspace = Workspace::open(create_test_remote!()).unwrap();
 
 	let first = workspace.remote().record_builder("coll").commit("test").unwrap();
 	let second = workspace.remote().record_builder("coll").commit("test2").unwrap();
@@ -43,9 +43,55 @@ fn test_walk() {
 	assert!(iter.next().is_none());
 }
 
+#[test]
+fn test_walk_page() {
+	let workspace = Workspace::open(create_test_remote!()).unwrap();
+
+	let mut ids = Vec::new();
+	for i in 0..5 {
+		ids.push(workspace.remote().record_builder("coll").commit(&format!("test{i}")).unwrap().id());
+	}
+	ids.reverse();
+
+	let page = workspace.remote().walk_page("coll", None, 2).unwrap();
+	assert_eq!(page.records.iter().map(Record::id).collect::<Vec<_>>(), &ids[0..2]);
+	let cursor = page.next.unwrap();
+
+	// cursors must survive being serialized and parsed back
+	let cursor = cursor.to_string().parse::<Cursor>().unwrap();
+	let page = workspace.remote().walk_page("coll", Some(&cursor), 2).unwrap();
+	assert_eq!(page.records.iter().map(Record::id).collect::<Vec<_>>(), &ids[2..4]);
+
+	let page = workspace.remote().walk_page("coll", page.next.as_ref(), 2).unwrap();
+	assert_eq!(page.records.iter().map(Record::id).collect::<Vec<_>>(), &ids[4..5]);
+	assert!(page.next.is_none());
+
+	// an exactly-full last page shouldn't hand out a dangling cursor
+	let page = workspace.remote().walk_page("coll", None, 5).unwrap();
+	assert_eq!(page.records.len(), 5);
+	assert!(page.next.is_none());
+
+	// huge limits are fine; they're only an upper bound
+	let page = workspace.remote().walk_page("coll", None, usize::MAX).unwrap();
+	assert_eq!(page.records.len(), 5);
+	assert!(page.next.is_none());
+
+	// empty pages would claim the list is over
+	assert!(matches!(
+		workspace.remote().walk_page("coll", None, 0),
+		Err(Error::ZeroPageLimit)
+	));
+
+	assert!(matches!(
+		workspace.remote().walk_page("other", Some(&cursor), 2),
+		Err(Error::MalformedCursor(_))
+	));
+	assert!(matches!("nonsense".parse::<Cursor>(), Err(Error::MalformedCursor(_))));
+}
+
 #[test]
 fn test_set_walk() {
-	let workspace = Workspace::open(create_test_remote!());
+	let workspace = Workspace::open(create_test_remote!()).unwrap();
 
 	workspace.remote().set_add_unchecked("coll", "test").unwrap();
 	workspace.remote().set_del_unchecked("coll", "test").unwrap();
@@ -73,7 +119,7 @@ fn test_set_walk() {
 
 #[test]
 fn test_set() {
-	let workspace = Workspace::open(create_test_remote!());
+	let workspace = Workspace::open(create_test_remote!()).unwrap();
 
 	let commit = workspace.remote().set_add_unchecked("coll", "test").unwrap();
 	assert_eq!(Record::message(&commit), "test");
@@ -147,7 +193,7 @@ fn test_set() {
 
 #[test]
 fn test_set_get_all() {
-	let workspace = Workspace::open(create_test_remote!());
+	let workspace = Workspace::open(create_test_remote!()).unwrap();
 
 	let commit = workspace.remote().set_add_unchecked("coll", "test").unwrap();
 	assert_eq!(Record::message(&commit), "test");
@@ -195,7 +241,7 @@ fn test_set_get_all() {
 
 #[test]
 fn test_set_checked() {
-	let workspace = Workspace::open(create_test_remote!());
+	let workspace = Workspace::open(create_test_remote!()).unwrap();
 
 	let commit = workspace.remote().set_add("coll", "test").unwrap().unwrap();
 	assert_eq!(Record::message(&commit.0), "test");
@@ -221,7 +267,7 @@ fn test_set_checked() {
 
 #[test]
 fn test_workspace() {
-	let workspace = Workspace::open(create_test_remote!());
+	let workspace = Workspace::open(create_test_remote!()).unwrap();
 
 	assert_eq!(workspace.name().unwrap(), None);
 	assert_eq!(workspace.description().unwrap(), None);
@@ -233,7 +279,7 @@ fn test_workspace() {
 
 #[test]
 fn test_project() {
-	let workspace = Workspace::open(create_test_remote!());
+	let workspace = Workspace::open(create_test_remote!()).unwrap();
 
 	let project = workspace.create_project("test").unwrap().unwrap();
 	assert_eq!(project.name().unwrap(), None);
@@ -263,11 +309,34 @@ fn test_project() {
 	assert_eq!(collected.len(), 2);
 	assert_eq!(collected[0].message(), "test");
 	assert_eq!(collected[1].message(), "test2");
+
+	let handles = workspace.project_handles().unwrap();
+	assert_eq!(
+		handles.iter().map(Project::slug).collect::<Vec<_>>(),
+		vec!["test", "test2"]
+	);
+
+	workspace
+		.project("test2")
+		.unwrap()
+		.set_name("Second")
+		.unwrap();
+	let with_meta = workspace.projects_with_meta().unwrap();
+	assert_eq!(with_meta.len(), 2);
+	assert_eq!(with_meta[0].project.slug(), "test");
+	assert_eq!(with_meta[0].record, collected[0]);
+	assert_eq!(with_meta[0].name.as_ref().unwrap().message(), "test");
+	assert_eq!(
+		with_meta[0].description.as_ref().unwrap().message(),
+		"test description"
+	);
+	assert_eq!(with_meta[1].name.as_ref().unwrap().message(), "Second");
+	assert!(with_meta[1].description.is_none());
 }
 
 #[test]
 fn test_ticket() {
-	let workspace = Workspace::open(create_test_remote!());
+	let workspace = Workspace::open(create_test_remote!()).unwrap();
 
 	let project = workspace.create_project("test").unwrap().unwrap();
 	let ticket = project.create_ticket().unwrap();
@@ -300,11 +369,39 @@ fn test_ticket() {
 	assert!(project.delete_ticket(1).unwrap().is_err());
 	assert!(matches!(project.ticket(1), Err(Error::NotFound(_, _))));
 	assert!(matches!(workspace.ticket("test-1"), Err(Error::NotFound(_, _))));
+
+	// restoring brings it back, along with its data
+	project.restore_ticket(1).unwrap().unwrap();
+	assert_eq!(
+		workspace.ticket("test-1").unwrap().title().unwrap().unwrap().message(),
+		"test title"
+	);
+	assert!(project.restore_ticket(1).unwrap().unwrap_err().is_some());
+	assert!(project.restore_ticket(99).unwrap().unwrap_err().is_none());
+}
+
+#[test]
+fn test_ticket_description() {
+	let workspace = Workspace::open(create_test_remote!()).unwrap();
+
+	let project = workspace.create_project("test").unwrap().unwrap();
+	let ticket = project.create_ticket().unwrap();
+	let ticket2 = project.create_ticket().unwrap();
+	assert_eq!(ticket.description().unwrap(), None);
+
+	ticket.set_title("title").unwrap();
+	ticket.set_description("# Body\n\nSome *markdown*.").unwrap();
+	assert_eq!(ticket.description().unwrap().unwrap().message(), "# Body\n\nSome *markdown*.");
+	assert_eq!(ticket.title().unwrap().unwrap().message(), "title");
+	assert_eq!(ticket2.description().unwrap(), None);
+
+	ticket.set_description("updated").unwrap();
+	assert_eq!(ticket.description().unwrap().unwrap().message(), "updated");
 }
 
 #[test]
 fn test_ticket_slug() {
-	let workspace = Workspace::open(create_test_remote!());
+	let workspace = Workspace::open(create_test_remote!()).unwrap();
 
 	let project = workspace.create_project("test").unwrap().unwrap();
 	let ticket = project.create_ticket().unwrap();
@@ -330,7 +427,7 @@ fn test_ticket_slug() {
 
 #[test]
 fn test_ticket_comment() {
-	let workspace = Workspace::open(create_test_remote!());
+	let workspace = Workspace::open(create_test_remote!()).unwrap();
 
 	let project = workspace.create_project("test").unwrap().unwrap();
 	let ticket = project.create_ticket().unwrap();
@@ -351,9 +448,73 @@ fn test_ticket_comment() {
 	assert_eq!(comments[1].message(), "test comment");
 }
 
+#[test]
+fn test_ticket_comments_page() {
+	let workspace = Workspace::open(create_test_remote!()).unwrap();
+
+	let project = workspace.create_project("test").unwrap().unwrap();
+	let ticket = project.create_ticket().unwrap();
+
+	for i in 0..3 {
+		ticket.add_comment(&format!("comment {i}")).unwrap();
+	}
+
+	let page = ticket.comments_page(None, 2).unwrap();
+	assert_eq!(page.records.len(), 2);
+	assert_eq!(page.records[0].message(), "comment 2");
+	assert_eq!(page.records[1].message(), "comment 1");
+
+	let page = ticket.comments_page(page.next.as_ref(), 2).unwrap();
+	assert_eq!(page.records.len(), 1);
+	assert_eq!(page.records[0].message(), "comment 0");
+	assert!(page.next.is_none());
+}
+
+#[test]
+fn test_ticket_delete_comment() {
+	let workspace = Workspace::open(create_test_remote!()).unwrap();
+
+	let project = workspace.create_project("test").unwrap().unwrap();
+	let ticket = project.create_ticket().unwrap();
+
+	let secret = ticket.add_comment("password: hunter2").unwrap();
+	ticket.add_comment("oops, ignore that").unwrap();
+
+	let tombstone = ticket.delete_comment(&secret.id()).unwrap().unwrap();
+	assert_eq!(tombstone.message(), secret.id());
+	assert_eq!(
+		ticket.delete_comment(&secret.id()).unwrap().unwrap_err(),
+		Some(tombstone.clone())
+	);
+	assert_eq!(ticket.delete_comment("nonexistent").unwrap().unwrap_err(), None);
+
+	let comments = ticket.comments().unwrap().map(Result::unwrap).collect::<Vec<_>>();
+	assert_eq!(comments.len(), 1);
+	assert_eq!(comments[0].message(), "oops, ignore that");
+
+	let page = ticket.comments_page(None, 10).unwrap();
+	assert_eq!(page.records, comments);
+
+	let view = ticket.comments_view().unwrap();
+	assert_eq!(view.len(), 1);
+	assert_eq!(view[0].record, comments[0]);
+	assert!(!view[0].is_deleted());
+
+	// Auditors can still see the raw log.
+	let audit = ticket.comments_audit().unwrap();
+	assert_eq!(audit.len(), 2);
+	assert!(!audit[0].is_deleted());
+	assert_eq!(audit[1].record, secret);
+	assert_eq!(audit[1].deleted, Some(tombstone));
+
+	let entry = &workspace.audit_log().unwrap()[0];
+	assert_eq!(entry.operation, AuditOperation::Redact);
+	assert_eq!(entry.target, format!("test-1#{}", secret.id()));
+}
+
 #[test]
 fn test_ticket_comment_attachment() {
-	let workspace = Workspace::open(create_test_remote!());
+	let workspace = Workspace::open(create_test_remote!()).unwrap();
 
 	let project = workspace.create_project("test").unwrap().unwrap();
 	let ticket = project.create_ticket().unwrap();
@@ -367,9 +528,97 @@ fn test_ticket_comment_attachment() {
 	assert_eq!(attachment, b"test attachment");
 }
 
+#[test]
+fn test_ticket_attachment_info() {
+	let workspace = Workspace::open(create_test_remote!()).unwrap();
+
+	let project = workspace.create_project("test").unwrap().unwrap();
+	let ticket = project.create_ticket().unwrap();
+	assert!(ticket.attachment_info("shot.png").unwrap().is_none());
+
+	let record = ticket
+		.upsert_attachment("shot.png", b"\x89PNG\r\n\x1a\nnot really")
+		.unwrap();
+	let info = ticket.attachment_info("shot.png").unwrap().unwrap();
+	assert_eq!(info.size, 18);
+	assert_eq!(info.mime, "image/png");
+	assert_eq!(info.author, record.author());
+	assert_eq!(info.email, record.email());
+	assert_eq!(info.timestamp, record.timestamp());
+
+	ticket.upsert_attachment("notes", b"plain text").unwrap();
+	let info = ticket.attachment_info("notes").unwrap().unwrap();
+	assert_eq!(info.size, 10);
+	assert_eq!(info.mime, "text/plain");
+
+	let removed = ticket.remove_attachment("shot.png").unwrap().unwrap();
+	assert!(ticket.attachment_info("shot.png").unwrap().is_none());
+	assert_eq!(
+		ticket.remove_attachment("shot.png").unwrap().unwrap_err(),
+		Some(removed)
+	);
+	assert_eq!(
+		ticket.remove_attachment("missing").unwrap().unwrap_err(),
+		None
+	);
+
+	assert_eq!(
+		guess_mime_type("data.bin", &[0xff, 0xfe, 0x00]),
+		"application/octet-stream"
+	);
+}
+
+#[test]
+fn test_ticket_comment_with_attachments() {
+	let workspace = Workspace::open(create_test_remote!()).unwrap();
+
+	let project = workspace.create_project("test").unwrap().unwrap();
+	let ticket = project.create_ticket().unwrap();
+
+	let plain = ticket.add_comment("no files here").unwrap();
+	let comment = ticket
+		.add_comment_with_attachments(
+			"see screenshots",
+			&[("before.png", b"before"), ("after.png", b"after")],
+		)
+		.unwrap();
+	let later = ticket
+		.add_comment_with_attachments("updated", &[("before.png", b"before 2")])
+		.unwrap();
+
+	assert!(ticket.comment_attachments(&plain).unwrap().is_empty());
+	assert_eq!(
+		ticket.comment_attachments(&comment).unwrap(),
+		vec!["before.png", "after.png"]
+	);
+	assert_eq!(comment.attachment("before.png").unwrap().unwrap(), b"before");
+	assert_eq!(later.attachment("before.png").unwrap().unwrap(), b"before 2");
+
+	// Comment files don't show up as ticket attachments.
+	assert!(ticket.attachment("before.png").unwrap().is_none());
+
+	let comments = ticket.comments_view().unwrap();
+	assert_eq!(comments[0].attachments, vec!["before.png"]);
+	assert_eq!(comments[1].attachments, vec!["before.png", "after.png"]);
+	assert!(comments[2].attachments.is_empty());
+
+	let view = ticket.view().unwrap();
+	assert_eq!(view.comments[1].record.id, comment.id());
+	assert_eq!(view.comments[1].attachments, vec!["before.png", "after.png"]);
+
+	assert!(matches!(
+		ticket.add_comment_with_attachments("dup", &[("a", b"1"), ("a", b"2")]),
+		Err(Error::Malformed(_))
+	));
+	assert!(matches!(
+		ticket.add_comment_with_attachments("bad", &[("", b"1")]),
+		Err(Error::Malformed(_))
+	));
+}
+
 #[test]
 fn test_ticket_state() {
-	let workspace = Workspace::open(create_test_remote!());
+	let workspace = Workspace::open(create_test_remote!()).unwrap();
 
 	let project = workspace.create_project("test").unwrap().unwrap();
 	let ticket = project.create_ticket().unwrap();
@@ -417,9 +666,60 @@ fn test_ticket_state() {
 	assert!(ticket.is_closed().unwrap());
 }
 
+#[test]
+fn test_ticket_state_reason() {
+	let workspace = Workspace::open(create_test_remote!()).unwrap();
+
+	let project = workspace.create_project("test").unwrap().unwrap();
+	let ticket = project.create_ticket().unwrap();
+	assert_eq!(ticket.state_reason().unwrap(), None);
+
+	let record = ticket
+		.set_state_with_reason(TicketState::Closed, "wontfix")
+		.unwrap();
+	let (state, latest) = ticket.state().unwrap();
+	assert_eq!(state, TicketState::Closed);
+	assert_eq!(latest.unwrap(), record);
+	assert_eq!(ticket.state_reason().unwrap().as_deref(), Some("wontfix"));
+	assert_eq!(ticket.view().unwrap().state_reason.as_deref(), Some("wontfix"));
+
+	// a plain state change clears the reason
+	ticket.set_state(TicketState::Open).unwrap();
+	assert_eq!(ticket.state_reason().unwrap(), None);
+
+	ticket
+		.set_state_with_reason(TicketState::Closed, "fixed in 1.2\n\nsee the changelog")
+		.unwrap();
+	assert_eq!(
+		ticket.state_reason().unwrap().as_deref(),
+		Some("fixed in 1.2\n\nsee the changelog")
+	);
+
+	let reasons = ticket
+		.activity()
+		.unwrap()
+		.into_iter()
+		.filter_map(|event| match event {
+			TicketEvent::StateChanged { state, reason, .. } => Some((state, reason)),
+			_ => None,
+		})
+		.collect::<Vec<_>>();
+	assert_eq!(
+		reasons,
+		vec![
+			(TicketState::Closed, Some("wontfix".to_string())),
+			(TicketState::Open, None),
+			(
+				TicketState::Closed,
+				Some("fixed in 1.2\n\nsee the changelog".to_string())
+			),
+		]
+	);
+}
+
 #[test]
 fn test_ticket_dependency() {
-	let workspace = Workspace::open(create_test_remote!());
+	let workspace = Workspace::open(create_test_remote!()).unwrap();
 
 	let project = workspace.create_project("test").unwrap().unwrap();
 	let ticket = project.create_ticket().unwrap();
@@ -457,7 +757,7 @@ fn test_ticket_dependency() {
 
 	// now go through the dependencies and make sure all the ones we didn't remove
 	// are still there.
-	let deps = ticket.dependencies().unwrap().into_iter().map(|(a, b, _)| (a, b)).collect::<Vec<_>>();
+	let deps = ticket.dependencies().unwrap().into_iter().map(|d| (d.origin, d.endpoint)).collect::<Vec<_>>();
 	assert_eq!(deps.len(), 2);
 	assert!(deps.contains(&("_".to_string(), "foo-1".to_string())));
 	assert!(deps.contains(&("ext".to_string(), "foo-1".to_string())));
@@ -465,7 +765,7 @@ fn test_ticket_dependency() {
 
 #[test]
 fn test_self_dependencies() {
-	let workspace = Workspace::open(create_test_remote!());
+	let workspace = Workspace::open(create_test_remote!()).unwrap();
 
 	let project = workspace.create_project("test").unwrap().unwrap();
 	let ticket = project.create_ticket().unwrap();
@@ -475,7 +775,7 @@ fn test_self_dependencies() {
 
 	ticket.add_dependency("_", "test-2").unwrap();
 
-	let deps = ticket.dependencies().unwrap().into_iter().map(|(a, b, _)| (a, b)).collect::<Vec<_>>();
+	let deps = ticket.dependencies().unwrap().into_iter().map(|d| (d.origin, d.endpoint)).collect::<Vec<_>>();
 	assert_eq!(deps.len(), 1);
 	assert!(deps.contains(&("_".to_string(), "test-2".to_string())));
 
@@ -507,7 +807,7 @@ fn test_self_dependencies() {
 
 #[test]
 fn test_subprojects() {
-	let workspace = Workspace::open(create_test_remote!());
+	let workspace = Workspace::open(create_test_remote!()).unwrap();
 
 	let project = workspace.create_project("test").unwrap().unwrap();
 	let subproject = project.create_project("sub").unwrap().unwrap();
@@ -525,3 +825,2568 @@ fn test_subprojects() {
 
 	assert_eq!(workspace.ticket("sub-1").unwrap().slug(), "sub-1");
 }
+
+#[test]
+fn test_project_tree() {
+	let workspace = Workspace::open(create_test_remote!()).unwrap();
+
+	let root = workspace.create_project("root").unwrap().unwrap();
+	let a = root.create_project("a").unwrap().unwrap();
+	root.create_project("b").unwrap().unwrap();
+	a.create_project("a1").unwrap().unwrap();
+	let other = workspace.create_project("other").unwrap().unwrap();
+	let orphan = other.create_project("orphan").unwrap().unwrap();
+	workspace.delete_project("other").unwrap().unwrap();
+
+	let slugs = |projects: Vec<Project<'_, _>>| {
+		projects
+			.iter()
+			.map(|p| p.slug().to_string())
+			.collect::<Vec<_>>()
+	};
+	assert_eq!(slugs(root.children().unwrap()), vec!["a", "b"]);
+	assert_eq!(slugs(a.children().unwrap()), vec!["a1"]);
+	assert!(orphan.children().unwrap().is_empty());
+
+	let tree = workspace.project_tree().unwrap();
+	assert_eq!(tree.len(), 2);
+	assert_eq!(tree[0].project.slug(), "root");
+	assert_eq!(tree[0].children.len(), 2);
+	assert_eq!(tree[0].children[0].project.slug(), "a");
+	assert_eq!(tree[0].children[0].children[0].project.slug(), "a1");
+	assert_eq!(tree[0].children[1].project.slug(), "b");
+	assert!(tree[0].children[1].children.is_empty());
+	assert_eq!(tree[1].project.slug(), "orphan");
+}
+
+#[test]
+fn test_trash() {
+	let workspace = Workspace::open(create_test_remote!()).unwrap();
+
+	let project = workspace.create_project("test").unwrap().unwrap();
+	workspace.create_project("other").unwrap().unwrap();
+	let ticket = project.create_ticket().unwrap();
+	ticket.set_title("secret").unwrap();
+	project.create_ticket().unwrap();
+
+	assert!(workspace.trash().items().unwrap().is_empty());
+
+	workspace.delete_project("other").unwrap().unwrap();
+	project.delete_ticket(1).unwrap().unwrap();
+
+	let items = workspace.trash().items().unwrap();
+	assert_eq!(items.len(), 2);
+	assert!(items.iter().any(|i| i.entity == TrashEntity::Project("other".to_string())));
+	assert!(items.iter().any(|i| i.entity == TrashEntity::Ticket("test".to_string(), 1)));
+
+	// restore the project
+	let item = items.iter().find(|i| i.entity.slug() == "other").unwrap();
+	workspace.trash().restore(item).unwrap();
+	assert!(workspace.project("other").is_ok());
+	assert!(matches!(workspace.trash().restore(item), Err(Error::Exists(_, _))));
+
+	// purge the ticket
+	let item = items.iter().find(|i| i.entity.slug() == "test-1").unwrap();
+	let removed = workspace.trash().purge(item, None).unwrap();
+	assert_eq!(removed, vec!["project/test/ticket/1/title".to_string()]);
+	assert!(workspace.remote().collections("project/test/ticket/1").unwrap().is_empty());
+	assert!(workspace.trash().items().unwrap().is_empty());
+	assert!(matches!(workspace.trash().restore(item), Err(Error::NotFound(_, _))));
+	assert!(matches!(workspace.trash().purge(item, None), Err(Error::NotFound(_, _))));
+
+	// other tickets are left alone
+	assert!(project.ticket(2).is_ok());
+}
+
+#[test]
+fn test_purge_project() {
+	let workspace = Workspace::open(create_test_remote!()).unwrap();
+
+	let project = workspace.create_project("leaky").unwrap().unwrap();
+	project.set_name("Leaky").unwrap();
+	let ticket = project.create_ticket().unwrap();
+	ticket.add_comment("password: hunter2").unwrap();
+	let sub = project.create_project("leaky-sub").unwrap().unwrap();
+	sub.create_ticket().unwrap();
+
+	assert!(matches!(
+		workspace.purge_project("leaky", false, None),
+		Err(Error::ForceRequired(_))
+	));
+	assert!(workspace.project("leaky").is_ok());
+
+	let mut removed = workspace
+		.purge_project("leaky", true, Some("leaked credentials"))
+		.unwrap();
+	removed.sort();
+	assert!(removed.contains(&"meta/project/leaky/name".to_string()));
+	assert!(removed.contains(&"project/leaky/ticket/1/comment".to_string()));
+	assert!(workspace.remote().collections("project/leaky").unwrap().is_empty());
+	assert!(workspace.remote().collections("meta/project/leaky").unwrap().is_empty());
+	assert!(workspace.project("leaky").is_err());
+	assert!(workspace.trash().items().unwrap().is_empty());
+
+	// sub-projects (despite sharing a slug prefix) are left alone
+	assert!(workspace.project("leaky-sub").unwrap().ticket(1).is_ok());
+
+	let entry = &workspace.audit_log().unwrap()[0];
+	assert_eq!(entry.operation, AuditOperation::Purge);
+	assert_eq!(entry.target, "leaky");
+
+	assert!(matches!(
+		workspace.purge_project("leaky", true, None),
+		Err(Error::NotFound(_, _))
+	));
+	assert!(matches!(
+		workspace.purge_project("missing", true, None),
+		Err(Error::NotFound(_, _))
+	));
+}
+
+#[test]
+fn test_archive() {
+	let workspace = Workspace::open(create_test_remote!()).unwrap();
+
+	let project = workspace.create_project("test").unwrap().unwrap();
+	let ticket = project.create_ticket().unwrap();
+	let other = project.create_ticket().unwrap();
+	assert!(!project.is_archived().unwrap());
+	assert!(!ticket.is_archived().unwrap());
+	assert!(ticket.unarchive().unwrap().is_none());
+
+	assert!(ticket.archive().unwrap().is_some());
+	assert!(ticket.archive().unwrap().is_none());
+	assert!(ticket.is_archived().unwrap());
+	assert!(!other.is_archived().unwrap());
+
+	let slugs = |query: TicketQuery| {
+		project
+			.query(&query)
+			.unwrap()
+			.map(|ticket| ticket.unwrap().slug().to_string())
+			.collect::<Vec<_>>()
+	};
+	assert_eq!(slugs(TicketQuery::new().archived(true)), vec!["test-1"]);
+	assert_eq!(slugs(TicketQuery::new().archived(false)), vec!["test-2"]);
+	assert_eq!(slugs(TicketQuery::new()).len(), 2);
+
+	// Archiving a project archives its tickets, until it's unarchived.
+	assert!(project.archive().unwrap().is_some());
+	assert!(project.is_archived().unwrap());
+	assert!(other.is_archived().unwrap());
+	assert!(other.unarchive().unwrap().is_none());
+	assert!(other.is_archived().unwrap());
+	assert_eq!(slugs(TicketQuery::new().archived(true)).len(), 2);
+
+	assert!(project.unarchive().unwrap().is_some());
+	assert!(project.unarchive().unwrap().is_none());
+	assert!(!other.is_archived().unwrap());
+	assert!(ticket.is_archived().unwrap());
+
+	// Archived tickets still resolve, and stay archived when moved.
+	assert!(workspace.ticket("test-1").unwrap().is_archived().unwrap());
+	workspace.create_project("other").unwrap().unwrap();
+	assert!(ticket.move_to("other").unwrap().is_archived().unwrap());
+}
+
+#[test]
+fn test_delete_collection() {
+	let workspace = Workspace::open(create_test_remote!()).unwrap();
+
+	workspace.remote().record_builder("a/b").commit("test").unwrap();
+	workspace.remote().record_builder("a/c/d").commit("test").unwrap();
+	workspace.remote().record_builder("ab").commit("test").unwrap();
+
+	let mut collections = workspace.remote().collections("a").unwrap();
+	collections.sort();
+	assert_eq!(collections, vec!["a/b".to_string(), "a/c/d".to_string()]);
+
+	assert!(workspace.remote().delete_collection("a/b").unwrap());
+	assert!(!workspace.remote().delete_collection("a/b").unwrap());
+	assert!(workspace.remote().latest("a/b").unwrap().is_none());
+	assert_eq!(workspace.remote().collections("a").unwrap(), vec!["a/c/d".to_string()]);
+}
+
+#[test]
+fn test_observed_remote() {
+	let workspace = Workspace::open(ObservedRemote::new(create_test_remote!())).unwrap();
+	let project = workspace.create_project("test").unwrap().unwrap();
+	let ticket = project.create_ticket().unwrap();
+
+	let events = std::sync::Arc::new(std::sync::Mutex::new(Vec::new()));
+	let observed = events.clone();
+	let id = workspace
+		.remote()
+		.subscribe(Box::new(move |event: &RecordEvent| {
+			observed.lock().unwrap().push(event.clone());
+		}));
+
+	let title = ticket.set_title("Observed").unwrap();
+	let label = ticket.add_label("bug").unwrap();
+	// Existing labels aren't added again.
+	ticket.add_label("bug").unwrap();
+	let unlabel = ticket.remove_label("bug").unwrap().unwrap();
+	let png = b"\x89PNG\r\n\x1a\nfake image data";
+	let icon = project.set_icon(png).unwrap();
+
+	assert_eq!(
+		*events.lock().unwrap(),
+		vec![
+			RecordEvent {
+				collection: "project/test/ticket/1/title".to_string(),
+				operation: RecordOperation::Commit,
+				record: title.into(),
+			},
+			RecordEvent {
+				collection: "project/test/ticket/1/labels".to_string(),
+				operation: RecordOperation::SetAdd,
+				record: label.into(),
+			},
+			RecordEvent {
+				collection: "project/test/ticket/1/labels".to_string(),
+				operation: RecordOperation::SetDel,
+				record: unlabel.into(),
+			},
+			RecordEvent {
+				collection: "meta/project/test/icon".to_string(),
+				operation: RecordOperation::Commit,
+				record: icon.into(),
+			},
+		]
+	);
+	// Attachments make it through the observed record builder.
+	assert_eq!(project.icon().unwrap().unwrap().data, png);
+
+	assert!(workspace.remote().unsubscribe(id));
+	assert!(!workspace.remote().unsubscribe(id));
+	ticket.set_title("Unobserved").unwrap();
+	assert_eq!(events.lock().unwrap().len(), 4);
+}
+
+#[test]
+fn test_audit_log() {
+	let workspace = Workspace::open(create_test_remote!()).unwrap();
+
+	assert!(workspace.audit_log().unwrap().is_empty());
+	assert!(workspace.verify_audit_log().unwrap());
+
+	let project = workspace.create_project("test").unwrap().unwrap();
+	project.create_ticket().unwrap();
+	workspace.create_project("other").unwrap().unwrap();
+
+	project.delete_ticket(1).unwrap().unwrap();
+	// no-op deletions aren't logged
+	project.delete_ticket(1).unwrap().unwrap_err();
+	workspace
+		.delete_project_with_reason("other", Some("duplicate of test"))
+		.unwrap()
+		.unwrap();
+
+	let item = workspace
+		.trash()
+		.items()
+		.unwrap()
+		.into_iter()
+		.find(|i| i.entity.slug() == "test-1")
+		.unwrap();
+	workspace.trash().purge(&item, Some("leaked credentials")).unwrap();
+
+	let log = workspace.audit_log().unwrap();
+	assert_eq!(log.len(), 3);
+	assert_eq!(log[0].operation, AuditOperation::Purge);
+	assert_eq!(log[0].target, "test-1");
+	assert_eq!(log[0].reason.as_deref(), Some("leaked credentials"));
+	assert_eq!(log[1].operation, AuditOperation::Delete);
+	assert_eq!(log[1].target, "other");
+	assert_eq!(log[1].reason.as_deref(), Some("duplicate of test"));
+	assert_eq!(log[2].reason, None);
+	assert_eq!(log[2].operation, AuditOperation::Delete);
+	assert_eq!(log[2].target, "test-1");
+	assert_eq!(log[0].record.email(), log[2].record.email());
+	assert!(workspace.verify_audit_log().unwrap());
+
+	// a forged entry without a valid digest breaks the chain
+	workspace
+		.remote()
+		.record_builder("meta/audit")
+		.upsert_attachment("digest", "bogus")
+		.unwrap()
+		.commit("delete nothing")
+		.unwrap();
+	assert!(!workspace.verify_audit_log().unwrap());
+}
+
+#[test]
+fn test_audit_log_key() {
+	let workspace = Workspace::open(create_test_remote!())
+		.unwrap()
+		.with_audit_key(b"secret");
+	workspace.create_project("test").unwrap().unwrap();
+	workspace.delete_project("test").unwrap().unwrap();
+	assert!(workspace.verify_audit_log().unwrap());
+	// HMAC-SHA256("secret", "\ndelete test")
+	assert_eq!(
+		workspace.audit_log().unwrap()[0].digest,
+		"fcc17c859321dfb4138282591763e5c47afef8586e40201b8d5f0adb1a5b7430"
+	);
+
+	// entries chained without the key don't pass
+	let previous = workspace.audit_log().unwrap()[0].digest.clone();
+	let forged = <sha2::Sha256 as sha2::Digest>::digest(format!("{previous}\ndelete forged"));
+	workspace
+		.remote()
+		.record_builder("meta/audit")
+		.upsert_attachment("digest", format!("{:x}", forged))
+		.unwrap()
+		.commit("delete forged")
+		.unwrap();
+	assert!(!workspace.verify_audit_log().unwrap());
+}
+
+#[test]
+fn test_slug_strategy() {
+	struct UppercaseProjects;
+
+	impl SlugStrategy for UppercaseProjects {
+		fn project_slug(&self, requested: &str) -> String {
+			requested.to_uppercase()
+		}
+
+		fn ticket_slug(&self, project: &str, id: u64) -> String {
+			format!("{}_{}", project, id)
+		}
+	}
+
+	let workspace = Workspace::open(create_test_remote!()).unwrap().with_slug_strategy(UppercaseProjects);
+
+	let project = workspace.create_project("test").unwrap().unwrap();
+	assert_eq!(project.slug(), "TEST");
+
+	let ticket = project.create_ticket().unwrap();
+	assert_eq!(ticket.slug(), "TEST_1");
+	assert_eq!(workspace.ticket("TEST_1").unwrap().id(), 1);
+	assert_eq!(project.ticket(1).unwrap().slug(), "TEST_1");
+	assert_eq!(workspace.ticket("TEST-1").unwrap().slug(), "TEST_1");
+}
+
+#[test]
+fn test_ulid_and_haiku_slugs() {
+	let workspace = Workspace::open(create_test_remote!()).unwrap().with_slug_strategy(UlidSlugStrategy);
+	let project = workspace.create_project("test").unwrap().unwrap();
+
+	let ticket = project.create_ticket().unwrap();
+	let ulid = ticket.slug().strip_prefix("test-").unwrap().to_string();
+	assert_eq!(ulid.len(), 26);
+	assert_eq!(workspace.ticket(ticket.slug()).unwrap().id(), 1);
+
+	let workspace = workspace.with_slug_strategy(HaikuSlugStrategy);
+	let project = workspace.project("test").unwrap();
+
+	let ticket = project.create_ticket().unwrap();
+	assert!(ticket.slug().starts_with("test-"));
+	assert!(ticket.slug().ends_with("-2"));
+	assert_eq!(ticket.slug().split('-').count(), 4);
+	assert_eq!(workspace.ticket(ticket.slug()).unwrap().id(), 2);
+
+	// Slugs recorded under the previous strategy still resolve.
+	assert_eq!(
+		workspace.ticket(&format!("test-{}", ulid)).unwrap().id(),
+		1
+	);
+	assert_eq!(project.ticket(1).unwrap().slug(), format!("test-{}", ulid));
+}
+
+#[test]
+fn test_workflow_transitions() {
+	let workflow = Workflow::new()
+		.allow(TicketState::Open, TicketState::Closed)
+		.require(TicketState::Closed, TransitionCondition::NoOpenDependencies);
+	let workspace = Workspace::open(create_test_remote!()).unwrap().with_workflow(workflow);
+
+	let project = workspace.create_project("test").unwrap().unwrap();
+	let ticket = project.create_ticket().unwrap();
+	let blocker = project.create_ticket().unwrap();
+
+	ticket.add_dependency("_", blocker.slug()).unwrap();
+	ticket.add_dependency("github", "foo/bar#1").unwrap();
+
+	match ticket.set_state(TicketState::Closed) {
+		Err(Error::TransitionDenied(slug, from, to, reason)) => {
+			assert_eq!(slug, "test-1");
+			assert_eq!(from, "open");
+			assert_eq!(to, "closed");
+			assert!(reason.contains("test-2"));
+		}
+		other => panic!("expected transition to be denied, got {:?}", other),
+	}
+	assert!(ticket.is_open().unwrap());
+
+	blocker.set_state(TicketState::Closed).unwrap();
+	ticket.set_state(TicketState::Closed).unwrap();
+	assert!(ticket.is_closed().unwrap());
+
+	// Re-opening isn't allowed, but staying closed is.
+	assert!(matches!(
+		ticket.set_state(TicketState::Open),
+		Err(Error::TransitionDenied(_, _, _, _))
+	));
+	ticket.set_state(TicketState::Closed).unwrap();
+}
+
+#[test]
+fn test_workspace_settings() {
+	let workspace = Workspace::open(create_test_remote!()).unwrap();
+	assert_eq!(workspace.settings().unwrap(), WorkspaceSettings::default());
+
+	let project = workspace.create_project("test").unwrap().unwrap();
+	project.define_field("customer", &FieldType::String).unwrap();
+
+	let settings = WorkspaceSettings {
+		default_state: TicketState::Closed,
+		slug_policy: Some(SlugPolicy::Haiku),
+		max_attachment_size: Some(4),
+		required_fields: vec!["title".to_string(), "customer".to_string()],
+		restrict_labels: false,
+		unknown: vec![],
+	};
+	workspace.set_settings(&settings).unwrap();
+	assert_eq!(workspace.settings().unwrap(), settings);
+
+	// settings from newer versions are kept, not rejected
+	workspace
+		.remote()
+		.record_builder("meta/workspace/settings")
+		.commit(&format!("{}\nfrom-the-future 42", settings.to_string()))
+		.unwrap();
+	let read = workspace.settings().unwrap();
+	assert_eq!(read.unknown, vec!["from-the-future 42"]);
+	workspace.set_settings(&read).unwrap();
+	assert_eq!(workspace.settings().unwrap(), read);
+	workspace.set_settings(&settings).unwrap();
+
+	// new tickets follow the default state and slug policy
+	let ticket = project.create_ticket().unwrap();
+	assert!(ticket.is_closed().unwrap());
+	assert_ne!(ticket.slug(), "test-1");
+	assert_eq!(workspace.ticket(ticket.slug()).unwrap().id(), 1);
+
+	// attachments are size-limited
+	ticket.upsert_attachment("small", b"1234").unwrap();
+	assert!(matches!(
+		ticket.upsert_attachment("big", b"12345"),
+		Err(Error::TooLarge(name, 5, 4)) if name == "big"
+	));
+	assert!(matches!(
+		ticket.add_comment_with_attachments("see", &[("big", b"12345")]),
+		Err(Error::TooLarge(_, _, _))
+	));
+
+	// closing requires the required fields to be set
+	ticket.set_state(TicketState::Open).unwrap();
+	assert_eq!(
+		ticket.missing_required_fields().unwrap(),
+		vec!["title", "customer"]
+	);
+	match ticket.set_state(TicketState::Closed) {
+		Err(Error::MissingRequiredFields(_, missing)) => {
+			assert_eq!(missing, vec!["title", "customer"])
+		}
+		other => panic!("expected transition to be denied, got {:?}", other),
+	}
+	ticket.set_title("Fix it").unwrap();
+	ticket.set_field("customer", "ACME").unwrap();
+	ticket.set_state(TicketState::Closed).unwrap();
+
+	assert!(matches!(
+		workspace.set_settings(&WorkspaceSettings {
+			required_fields: vec!["has space".to_string()],
+			..WorkspaceSettings::default()
+		}),
+		Err(Error::Malformed(_))
+	));
+}
+
+#[test]
+fn test_users() {
+	let workspace = Workspace::open(create_test_remote!()).unwrap();
+
+	assert!(workspace.users().unwrap().is_empty());
+	let record = workspace.set_name("test").unwrap();
+	assert_eq!(workspace.display_name(&record).unwrap(), record.author());
+
+	workspace.add_user("Alice", &record.email()).unwrap();
+	workspace.add_user("Bob", "bob@example.com").unwrap();
+	assert!(matches!(
+		workspace.add_user("Bob", "bob@example.com"),
+		Err(Error::Exists(_, _))
+	));
+	assert!(matches!(
+		workspace.add_user("Bob", "bob at example.com"),
+		Err(Error::Malformed(_))
+	));
+	assert!(matches!(
+		workspace.add_user("", "eve@example.com"),
+		Err(Error::Malformed(_))
+	));
+	workspace.add_user("Robert: Bob", "bob@example.com").unwrap();
+
+	assert_eq!(workspace.display_name(&record).unwrap(), "Alice");
+	assert_eq!(
+		workspace.user("bob@example.com").unwrap(),
+		Some(User {
+			email: "bob@example.com".to_string(),
+			name: "Robert: Bob".to_string(),
+		})
+	);
+	assert_eq!(workspace.users().unwrap().len(), 2);
+
+	assert!(workspace.remove_user("bob@example.com").unwrap().is_some());
+	assert!(workspace.remove_user("bob@example.com").unwrap().is_none());
+	assert_eq!(workspace.user("bob@example.com").unwrap(), None);
+}
+
+#[test]
+fn test_project_icon() {
+	let workspace = Workspace::open(create_test_remote!()).unwrap();
+
+	let project = workspace.create_project("test").unwrap().unwrap();
+	assert!(project.icon().unwrap().is_none());
+	assert!(project.remove_icon().unwrap().is_none());
+
+	let png = b"\x89PNG\r\n\x1a\nfake image data";
+	project.set_icon(png).unwrap();
+	assert_eq!(
+		project.icon().unwrap(),
+		Some(Icon {
+			data: png.to_vec(),
+			mime: "image/png".to_string(),
+		})
+	);
+
+	let svg = b"<?xml version=\"1.0\"?>\n<svg xmlns=\"http://www.w3.org/2000/svg\"/>";
+	project.set_icon(svg).unwrap();
+	assert_eq!(project.icon().unwrap().unwrap().mime, "image/svg+xml");
+
+	assert!(matches!(
+		project.set_icon(b"just some text"),
+		Err(Error::Malformed(_))
+	));
+	assert!(matches!(
+		project.set_icon(b"%PDF-1.4"),
+		Err(Error::Malformed(_))
+	));
+	assert_eq!(project.icon().unwrap().unwrap().mime, "image/svg+xml");
+
+	assert!(project.remove_icon().unwrap().is_some());
+	assert!(project.icon().unwrap().is_none());
+}
+
+#[test]
+fn test_project_members() {
+	let workspace = Workspace::open(create_test_remote!()).unwrap();
+
+	let project = workspace.create_project("test").unwrap().unwrap();
+	assert!(project.members().unwrap().is_empty());
+	assert_eq!(project.role("alice@example.com").unwrap(), None);
+
+	project.add_member("alice@example.com", Role::Maintainer).unwrap();
+	project.add_member("bob@example.com", Role::Viewer).unwrap();
+	assert!(matches!(
+		project.add_member("bob@example.com", Role::Viewer),
+		Err(Error::Exists(_, _))
+	));
+	project.add_member("bob@example.com", Role::Contributor).unwrap();
+	assert!(matches!(
+		project.add_member("not an email", Role::Viewer),
+		Err(Error::Malformed(_))
+	));
+
+	let members = project.members().unwrap();
+	assert_eq!(members.len(), 2);
+	assert_eq!(members["alice@example.com"], Role::Maintainer);
+	assert_eq!(members["bob@example.com"], Role::Contributor);
+
+	assert!(project.has_role("bob@example.com", Role::Viewer).unwrap());
+	assert!(project.has_role("bob@example.com", Role::Contributor).unwrap());
+	assert!(!project.has_role("bob@example.com", Role::Maintainer).unwrap());
+	assert!(!project.has_role("eve@example.com", Role::Viewer).unwrap());
+	project.require_role("alice@example.com", Role::Maintainer).unwrap();
+	assert!(matches!(
+		project.require_role("bob@example.com", Role::Maintainer),
+		Err(Error::PermissionDenied(_, _, _))
+	));
+
+	assert!(project.remove_member("bob@example.com").unwrap().is_some());
+	assert!(project.remove_member("bob@example.com").unwrap().is_none());
+	assert_eq!(project.role("bob@example.com").unwrap(), None);
+}
+
+#[test]
+fn test_project_templates() {
+	let workspace = Workspace::open(create_test_remote!()).unwrap();
+
+	let workflow = Workflow::new()
+		.allow(TicketState::Open, TicketState::Closed)
+		.require(TicketState::Closed, TransitionCondition::NoOpenDependencies);
+	let template = ProjectTemplate::new()
+		.description("## Goals\n\n## Non-goals")
+		.label("bug")
+		.label("feature")
+		.field("customer", FieldType::String)
+		.field("priority", FieldType::Enum(vec!["low".into(), "high".into()]))
+		.workflow(workflow.clone());
+
+	workspace.create_template("service", &template).unwrap().unwrap();
+	assert!(workspace
+		.create_template("service", &ProjectTemplate::new())
+		.unwrap()
+		.is_err());
+	assert!(matches!(
+		workspace.create_template("bad name", &template),
+		Err(Error::Malformed(_))
+	));
+	assert_eq!(workspace.templates().unwrap(), vec!["service"]);
+	assert_eq!(workspace.template("service").unwrap(), template);
+	assert_eq!(
+		Workflow::try_from(workflow.to_string().as_str()).unwrap(),
+		workflow
+	);
+
+	let project = workspace
+		.create_project_from_template("billing", "service")
+		.unwrap()
+		.unwrap();
+	assert_eq!(
+		project.description().unwrap().unwrap().message(),
+		"## Goals\n\n## Non-goals"
+	);
+	assert_eq!(
+		project
+			.labels()
+			.unwrap()
+			.into_iter()
+			.map(|label| label.name)
+			.collect::<Vec<_>>(),
+		vec!["bug", "feature"]
+	);
+	assert_eq!(project.fields().unwrap().len(), 2);
+	assert_eq!(project.workflow().unwrap(), Some(workflow));
+
+	// the project's workflow applies to its tickets only
+	let ticket = project.create_ticket().unwrap();
+	ticket.set_state(TicketState::Closed).unwrap();
+	assert!(matches!(
+		ticket.set_state(TicketState::Open),
+		Err(Error::TransitionDenied(_, _, _, _))
+	));
+	let plain = workspace.create_project("plain").unwrap().unwrap();
+	assert_eq!(plain.workflow().unwrap(), None);
+	let ticket = plain.create_ticket().unwrap();
+	ticket.set_state(TicketState::Closed).unwrap();
+	ticket.set_state(TicketState::Open).unwrap();
+
+	assert!(workspace
+		.create_project_from_template("billing", "service")
+		.unwrap()
+		.is_err());
+	assert!(matches!(
+		workspace.create_project_from_template("other", "missing"),
+		Err(Error::NotFound(_, _))
+	));
+	assert!(workspace.project("other").is_err());
+}
+
+#[test]
+fn test_ticket_duplicates() {
+	let workspace = Workspace::open(create_test_remote!()).unwrap();
+
+	let project = workspace.create_project("test").unwrap().unwrap();
+	let canonical = project.create_ticket().unwrap();
+	let dupe1 = project.create_ticket().unwrap();
+	let dupe2 = project.create_ticket().unwrap();
+	let other = project.create_ticket().unwrap();
+
+	assert!(canonical.duplicate_of().unwrap().is_none());
+	assert!(canonical.duplicates().unwrap().is_empty());
+
+	dupe1.mark_duplicate_of("test-1").unwrap();
+	dupe2.mark_duplicate_of("test-4").unwrap();
+	dupe2.mark_duplicate_of("test-1").unwrap();
+	assert!(matches!(
+		dupe2.mark_duplicate_of("test-1"),
+		Err(Error::Exists(_, _))
+	));
+
+	assert!(other.duplicates().unwrap().is_empty());
+	assert_eq!(dupe2.duplicate_of().unwrap().unwrap().slug(), "test-1");
+
+	let mut duplicates = canonical
+		.duplicates()
+		.unwrap()
+		.iter()
+		.map(|t| t.slug().to_string())
+		.collect::<Vec<_>>();
+	duplicates.sort();
+	assert_eq!(duplicates, vec!["test-2", "test-3"]);
+
+	// No cycles, and no self-duplicates.
+	match canonical.mark_duplicate_of("test-2") {
+		Err(Error::DuplicateCycle(path)) => assert_eq!(path, "test-1 -> test-2 -> test-1"),
+		other => panic!("expected a duplicate cycle, got {:?}", other.map(|r| r.id())),
+	}
+	assert!(matches!(
+		canonical.mark_duplicate_of("test-1"),
+		Err(Error::DuplicateCycle(_))
+	));
+
+	dupe1.set_state(TicketState::Closed).unwrap();
+	let records = canonical.close_with_duplicates().unwrap();
+	assert_eq!(records.len(), 2);
+	assert!(canonical.is_closed().unwrap());
+	assert!(dupe2.is_closed().unwrap());
+	assert!(other.is_open().unwrap());
+
+	// Moved tickets are recognized under their old slug.
+	workspace.create_project("moved").unwrap().unwrap();
+	let original = project.create_ticket().unwrap();
+	other.mark_duplicate_of(original.slug()).unwrap();
+	let moved = original.move_to("moved").unwrap();
+	assert!(matches!(
+		moved.mark_duplicate_of(other.slug()),
+		Err(Error::DuplicateCycle(_))
+	));
+
+	// Chains that already loop don't hang the check.
+	for (ticket, canonical) in [(&dupe1, "test-3"), (&dupe2, "test-2")] {
+		workspace
+			.remote()
+			.record_builder(&format!("project/test/ticket/{}/duplicate_of", ticket.id()))
+			.commit(canonical)
+			.unwrap();
+	}
+	other.mark_duplicate_of("test-2").unwrap();
+}
+
+#[test]
+fn test_ticket_move() {
+	let workspace = Workspace::open(create_test_remote!()).unwrap();
+
+	let source = workspace.create_project("source").unwrap().unwrap();
+	let target = workspace.create_project("target").unwrap().unwrap();
+	target.create_ticket().unwrap();
+
+	let ticket = source.create_ticket().unwrap();
+	let other = source.create_ticket().unwrap();
+	ticket.set_title("Moving").unwrap();
+	ticket.add_label("bug").unwrap();
+	ticket.set_state(TicketState::Closed).unwrap();
+	let kept = ticket
+		.add_comment_with_attachments("kept", &[("log.txt", b"hello")])
+		.unwrap();
+	let deleted = ticket.add_comment("deleted").unwrap();
+	ticket.delete_comment(&deleted.id()).unwrap().unwrap();
+	other.add_dependency("_", "source-1").unwrap();
+
+	assert!(matches!(ticket.move_to("source"), Err(Error::Exists(_, _))));
+	assert!(matches!(
+		ticket.move_to("missing"),
+		Err(Error::NotFound(_, _))
+	));
+
+	let moved = ticket.move_to("target").unwrap();
+	assert_eq!(moved.slug(), "target-2");
+	assert_eq!(moved.project().unwrap().slug(), "target");
+	assert_eq!(moved.title().unwrap().unwrap().message(), "Moving");
+	assert_eq!(moved.labels().unwrap(), vec!["bug"]);
+	assert!(moved.is_closed().unwrap());
+
+	let comments = moved.comments_audit().unwrap();
+	assert_eq!(comments.len(), 2);
+	assert!(comments[0].is_deleted());
+	assert!(!comments[1].is_deleted());
+	assert_eq!(comments[1].record.message(), "kept");
+	assert_eq!(comments[1].record.author(), kept.author());
+	assert_eq!(comments[1].record.timestamp(), kept.timestamp());
+	assert_eq!(comments[1].attachments, vec!["log.txt"]);
+	assert_eq!(
+		comments[1].record.attachment("log.txt").unwrap().unwrap(),
+		b"hello"
+	);
+
+	// The old slug redirects to the moved ticket.
+	assert_eq!(source.tickets().unwrap().len(), 1);
+	assert_eq!(
+		workspace.ticket_redirect("source-1").unwrap().as_deref(),
+		Some("target-2")
+	);
+	assert_eq!(workspace.ticket("source-1").unwrap().slug(), "target-2");
+	assert!(workspace.ticket_redirect("source-2").unwrap().is_none());
+	// The original isn't in the trash, and can't be restored.
+	assert!(workspace.trash().items().unwrap().is_empty());
+	assert!(matches!(
+		source.restore_ticket(ticket.id()),
+		Err(Error::NotFound(_, _))
+	));
+	assert_eq!(workspace.ticket("source-1").unwrap().slug(), "target-2");
+	// Dependencies on the old slug follow the redirect.
+	assert_eq!(
+		other.effective_state(&DependencyRegistry::new()).unwrap(),
+		EffectiveState::Open
+	);
+	moved.set_state(TicketState::Open).unwrap();
+	assert_eq!(
+		other.effective_state(&DependencyRegistry::new()).unwrap(),
+		EffectiveState::Blocked
+	);
+	assert_eq!(target.next_ticket_slug().unwrap(), "target-3");
+}
+
+#[test]
+fn test_ticket_subtasks() {
+	let workspace = Workspace::open(create_test_remote!()).unwrap();
+
+	let project = workspace.create_project("test").unwrap().unwrap();
+	let ticket = project.create_ticket().unwrap();
+	assert_eq!(ticket.project().unwrap().slug(), "test");
+	assert!(ticket.parent().unwrap().is_none());
+	assert_eq!(ticket.subtask_progress().unwrap().fraction(), None);
+
+	let subtask1 = ticket.create_subtask().unwrap();
+	let subtask2 = ticket.create_subtask().unwrap();
+	let nested = subtask1.create_subtask().unwrap();
+
+	assert_eq!(subtask1.slug(), "test-2");
+	assert_eq!(subtask1.parent().unwrap().unwrap().slug(), "test-1");
+	assert_eq!(nested.parent().unwrap().unwrap().slug(), "test-2");
+
+	let mut subtasks = ticket
+		.subtasks()
+		.unwrap()
+		.iter()
+		.map(|t| t.slug().to_string())
+		.collect::<Vec<_>>();
+	subtasks.sort();
+	assert_eq!(subtasks, vec!["test-2", "test-3"]);
+
+	subtask2.set_state(TicketState::Closed).unwrap();
+	let progress = ticket.subtask_progress().unwrap();
+	assert_eq!(progress, SubtaskProgress { closed: 1, total: 2 });
+	assert_eq!(progress.fraction(), Some(0.5));
+
+	project.delete_ticket(subtask1.id()).unwrap().unwrap();
+	assert_eq!(ticket.subtask_progress().unwrap().fraction(), Some(1.0));
+	assert!(nested.parent().unwrap().is_none());
+}
+
+#[test]
+fn test_ticket_time_tracking() {
+	let workspace = Workspace::open(create_test_remote!()).unwrap();
+
+	let project = workspace.create_project("test").unwrap().unwrap();
+	let ticket = project.create_ticket().unwrap();
+
+	assert!(ticket.estimate().unwrap().is_none());
+	assert_eq!(ticket.total_time_spent().unwrap(), 0);
+
+	ticket.set_estimate(120).unwrap();
+	ticket.set_estimate(90).unwrap();
+	assert_eq!(ticket.estimate().unwrap().unwrap().0, 90);
+
+	ticket.log_work(30, None).unwrap();
+	ticket.log_work(45, Some("pairing\n\non the parser")).unwrap();
+
+	let worklog = ticket.worklog().unwrap();
+	assert_eq!(worklog.len(), 2);
+	assert_eq!(worklog[0].minutes, 45);
+	assert_eq!(worklog[0].note.as_deref(), Some("pairing\n\non the parser"));
+	assert_eq!(worklog[1].minutes, 30);
+	assert_eq!(worklog[1].note, None);
+
+	assert_eq!(ticket.total_time_spent().unwrap(), 75);
+
+	let by_author = ticket.time_spent_by_author().unwrap();
+	assert_eq!(by_author.len(), 1);
+	assert_eq!(by_author[&worklog[0].record.email()], 75);
+}
+
+#[test]
+fn test_custom_fields() {
+	let workspace = Workspace::open(create_test_remote!()).unwrap();
+
+	let project = workspace.create_project("test").unwrap().unwrap();
+	let ticket = project.create_ticket().unwrap();
+
+	assert!(project.fields().unwrap().is_empty());
+	assert!(matches!(
+		ticket.set_field("customer", "ACME"),
+		Err(Error::NotFound(_, _))
+	));
+
+	let severity = FieldType::Enum(vec!["low".to_string(), "high".to_string()]);
+	project.define_field("customer", &FieldType::String).unwrap();
+	project.define_field("severity", &severity).unwrap();
+	project.define_field("points", &FieldType::Number).unwrap();
+	project.define_field("billable", &FieldType::Bool).unwrap();
+	project.define_field("due", &FieldType::Date).unwrap();
+	assert!(matches!(
+		project.define_field("due", &FieldType::Date),
+		Err(Error::Exists(_, _))
+	));
+	assert!(matches!(
+		project.define_field("bad name", &FieldType::Date),
+		Err(Error::Malformed(_))
+	));
+
+	let fields = project.fields().unwrap();
+	assert_eq!(fields.len(), 5);
+	assert_eq!(fields["severity"], severity);
+
+	assert!(ticket.field("customer").unwrap().is_none());
+	ticket.set_field("customer", "ACME, Inc.").unwrap();
+	ticket.set_field("severity", "high").unwrap();
+	ticket.set_field("points", "2.5").unwrap();
+	ticket.set_field("billable", "true").unwrap();
+	ticket.set_field("due", "2024-02-29").unwrap();
+
+	assert_eq!(
+		ticket.field("customer").unwrap().unwrap().0,
+		FieldValue::String("ACME, Inc.".to_string())
+	);
+	assert_eq!(
+		ticket.field("severity").unwrap().unwrap().0,
+		FieldValue::Enum("high".to_string())
+	);
+	assert_eq!(ticket.field("points").unwrap().unwrap().0, FieldValue::Number(2.5));
+	assert_eq!(ticket.field("billable").unwrap().unwrap().0, FieldValue::Bool(true));
+	assert_eq!(ticket.field("due").unwrap().unwrap().0, FieldValue::Date(2024, 2, 29));
+
+	for (name, value) in [
+		("severity", "critical"),
+		("points", "lots"),
+		("points", "NaN"),
+		("billable", "yes"),
+		("due", "2023-02-29"),
+		("due", "tomorrow"),
+	] {
+		assert!(
+			matches!(ticket.set_field(name, value), Err(Error::Malformed(_))),
+			"{name}={value} should be rejected"
+		);
+	}
+
+	// Changing a field's type replaces it.
+	project.define_field("points", &FieldType::String).unwrap();
+	assert_eq!(project.fields().unwrap()["points"], FieldType::String);
+	assert_eq!(project.fields().unwrap().len(), 5);
+
+	assert!(project.remove_field("customer").unwrap().is_some());
+	assert!(project.remove_field("customer").unwrap().is_none());
+	assert!(matches!(ticket.field("customer"), Err(Error::NotFound(_, _))));
+}
+
+#[test]
+fn test_ticket_activity() {
+	let workspace = Workspace::open(create_test_remote!()).unwrap();
+
+	let project = workspace.create_project("test").unwrap().unwrap();
+	let ticket = project.create_ticket().unwrap();
+
+	ticket.set_title("first").unwrap();
+	let comment = ticket.add_comment("hello").unwrap();
+	ticket.upsert_attachment("log.txt", b"data").unwrap();
+	ticket.add_dependency("_", "test-2").unwrap();
+	ticket.set_state(TicketState::Closed).unwrap();
+	ticket.remove_dependency("_", "test-2").unwrap();
+	ticket.delete_comment(&comment.id()).unwrap().unwrap();
+	ticket.set_title("second").unwrap();
+
+	let events = ticket.activity().unwrap();
+	assert_eq!(events.len(), 9);
+	assert!(matches!(events[0], TicketEvent::Created(_)));
+	assert!(events
+		.windows(2)
+		.all(|w| w[0].timestamp() <= w[1].timestamp()));
+
+	let has = |f: &dyn Fn(&TicketEvent<_>) -> bool| events.iter().any(f);
+	assert!(has(
+		&|e| matches!(e, TicketEvent::TitleChanged { title, .. } if title == "first")
+	));
+	assert!(has(
+		&|e| matches!(e, TicketEvent::TitleChanged { title, .. } if title == "second")
+	));
+	assert!(has(&|e| matches!(e, TicketEvent::CommentAdded(r) if *r == comment)));
+	assert!(has(
+		&|e| matches!(e, TicketEvent::CommentDeleted { comment_id, .. } if *comment_id == comment.id())
+	));
+	assert!(has(
+		&|e| matches!(e, TicketEvent::AttachmentAdded { name, .. } if name == "log.txt")
+	));
+	assert!(has(&|e| matches!(
+		e,
+		TicketEvent::StateChanged {
+			state: TicketState::Closed,
+			..
+		}
+	)));
+	assert!(has(
+		&|e| matches!(e, TicketEvent::DependencyAdded { origin, endpoint, .. } if origin == "_" && endpoint == "test-2")
+	));
+	assert!(has(&|e| matches!(e, TicketEvent::DependencyRemoved { .. })));
+
+	// Events within the same collection stay in order.
+	let titles = events
+		.iter()
+		.filter_map(|e| match e {
+			TicketEvent::TitleChanged { title, .. } => Some(title.as_str()),
+			_ => None,
+		})
+		.collect::<Vec<_>>();
+	assert_eq!(titles, vec!["first", "second"]);
+}
+
+#[test]
+fn test_ticket_view() {
+	let workspace = Workspace::open(create_test_remote!()).unwrap();
+
+	let project = workspace.create_project("test").unwrap().unwrap();
+	project.define_field("customer", &FieldType::String).unwrap();
+	let ticket = project.create_ticket().unwrap();
+	let subtask = ticket.create_subtask().unwrap();
+
+	let view = ticket.view().unwrap();
+	assert_eq!(view.slug, "test-1");
+	assert_eq!(view.title, None);
+	assert_eq!(view.state, TicketState::Open);
+	assert!(view.comments.is_empty());
+	assert!(view.fields.is_empty());
+
+	ticket.set_title("title").unwrap();
+	ticket.set_description("description").unwrap();
+	ticket.set_state(TicketState::Closed).unwrap();
+	let deleted = ticket.add_comment("deleted").unwrap();
+	let kept = ticket.add_comment("kept").unwrap();
+	ticket.delete_comment(&deleted.id()).unwrap().unwrap();
+	ticket.upsert_attachment("a.txt", b"a").unwrap();
+	ticket.upsert_attachment("b.txt", b"b").unwrap();
+	ticket.upsert_attachment("a.txt", b"aa").unwrap();
+	ticket.add_dependency("github", "foo/bar#1").unwrap();
+	ticket.set_estimate(60).unwrap();
+	ticket.log_work(15, None).unwrap();
+	ticket.set_field("customer", "ACME").unwrap();
+
+	let view = ticket.view().unwrap();
+	assert_eq!(view.title.as_deref(), Some("title"));
+	assert_eq!(view.description.as_deref(), Some("description"));
+	assert_eq!(view.state, TicketState::Closed);
+	assert_eq!(
+		view.comments,
+		vec![CommentView {
+			record: RecordView::from(kept),
+			attachments: vec![],
+		}]
+	);
+	assert_eq!(view.attachments, vec!["a.txt", "b.txt"]);
+	assert_eq!(
+		view.dependencies,
+		vec![DependencyView {
+			origin: "github".to_string(),
+			endpoint: "foo/bar#1".to_string(),
+			kind: DependencyKind::Blocking,
+			note: None,
+		}]
+	);
+	assert_eq!(view.subtasks, vec![subtask.slug().to_string()]);
+	assert_eq!(view.parent, None);
+	assert_eq!(view.estimate, Some(60));
+	assert_eq!(view.time_spent, 15);
+	assert_eq!(view.fields["customer"], "ACME");
+
+	assert_eq!(subtask.view().unwrap().parent.as_deref(), Some("test-1"));
+}
+
+#[test]
+fn test_find_tickets() {
+	let workspace = Workspace::open(create_test_remote!()).unwrap();
+
+	let project = workspace.create_project("test").unwrap().unwrap();
+	assert!(project.tickets().unwrap().is_empty());
+
+	let crash = project.create_ticket().unwrap();
+	crash.set_title("App crashes on startup").unwrap();
+	let typo = project.create_ticket().unwrap();
+	typo.set_title("Typo in README").unwrap();
+	typo.set_description("The word 'crash' is misspelled").unwrap();
+	project.create_ticket().unwrap();
+	let deleted = project.create_ticket().unwrap();
+	deleted.set_title("Another crash").unwrap();
+	project.delete_ticket(deleted.id()).unwrap().unwrap();
+
+	assert_eq!(
+		project
+			.tickets()
+			.unwrap()
+			.iter()
+			.map(|t| t.id())
+			.collect::<Vec<_>>(),
+		vec![1, 2, 3]
+	);
+
+	let found = project.find_tickets("CRASH", false).unwrap();
+	assert_eq!(found.len(), 1);
+	assert_eq!(found[0].ticket.slug(), "test-1");
+	assert_eq!(found[0].record.message(), "App crashes on startup");
+
+	let found = project.find_tickets("crash", true).unwrap();
+	assert_eq!(found.len(), 2);
+	assert_eq!(found[1].ticket.slug(), "test-2");
+	assert_eq!(found[1].record.message(), "The word 'crash' is misspelled");
+
+	assert!(project.find_tickets("nothing", true).unwrap().is_empty());
+}
+
+#[test]
+fn test_search_index() {
+	let workspace = Workspace::open(create_test_remote!()).unwrap();
+
+	let project = workspace.create_project("test").unwrap().unwrap();
+	let login = project.create_ticket().unwrap();
+	login.set_title("Login page crashes").unwrap();
+	let typo = project.create_ticket().unwrap();
+	typo.set_title("Typo on settings page").unwrap();
+	typo.add_comment("Also crashes sometimes?").unwrap();
+	project.create_ticket().unwrap();
+
+	let hits = workspace.search("crashes").unwrap();
+	assert_eq!(
+		hits.iter().map(|h| h.slug.as_str()).collect::<Vec<_>>(),
+		vec!["test-1", "test-2"]
+	);
+	assert!(hits[0].score > hits[1].score);
+
+	assert!(workspace.search("nonexistent").unwrap().is_empty());
+
+	// Only changed tickets are re-indexed.
+	let mut index = SearchIndex::new();
+	assert_eq!(index.update(&workspace).unwrap(), 3);
+	assert_eq!(index.update(&workspace).unwrap(), 0);
+	typo.set_description("the settings page").unwrap();
+	project.delete_ticket(login.id()).unwrap().unwrap();
+	assert_eq!(index.update(&workspace).unwrap(), 2);
+	assert_eq!(index.len(), 2);
+
+	let hits = index.search("SETTINGS");
+	assert_eq!(hits.len(), 1);
+	assert_eq!(hits[0].slug, "test-2");
+
+	// Deleted comments are no longer searchable.
+	let comment = typo.comments().unwrap().next().unwrap().unwrap();
+	typo.delete_comment(&comment.id()).unwrap().unwrap();
+	assert_eq!(index.update(&workspace).unwrap(), 1);
+	assert!(index.search("crashes").is_empty());
+
+	let path = std::env::temp_dir().join(format!(
+		"minimap-search-index-{}-{:p}",
+		std::process::id(),
+		&workspace
+	));
+	index.save(&path).unwrap();
+	let loaded = SearchIndex::load(&path).unwrap();
+	assert_eq!(loaded.len(), 2);
+	assert_eq!(loaded.search("settings"), index.search("settings"));
+	std::fs::remove_file(&path).unwrap();
+
+	assert!(SearchIndex::load(&path).unwrap().is_empty());
+}
+
+#[test]
+fn test_ticket_labels_and_assignees() {
+	let workspace = Workspace::open(create_test_remote!()).unwrap();
+
+	let project = workspace.create_project("test").unwrap().unwrap();
+	let ticket = project.create_ticket().unwrap();
+	assert!(ticket.labels().unwrap().is_empty());
+	assert!(ticket.assignees().unwrap().is_empty());
+
+	let record = ticket.add_label("bug").unwrap();
+	assert_eq!(ticket.add_label("bug").unwrap(), record);
+	ticket.add_label("ui").unwrap();
+	ticket.add_assignee("alice@example.com").unwrap();
+
+	let mut labels = ticket.labels().unwrap();
+	labels.sort();
+	assert_eq!(labels, vec!["bug", "ui"]);
+	assert_eq!(ticket.assignees().unwrap(), vec!["alice@example.com"]);
+
+	assert!(ticket.remove_label("bug").unwrap().is_some());
+	assert!(ticket.remove_label("bug").unwrap().is_none());
+	assert!(ticket.remove_assignee("alice@example.com").unwrap().is_some());
+	assert_eq!(ticket.labels().unwrap(), vec!["ui"]);
+	assert!(ticket.assignees().unwrap().is_empty());
+
+	let view = ticket.view().unwrap();
+	assert_eq!(view.labels, vec!["ui"]);
+	assert!(view.assignees.is_empty());
+}
+
+#[test]
+fn test_ticket_votes() {
+	let workspace = Workspace::open(create_test_remote!()).unwrap();
+
+	let project = workspace.create_project("test").unwrap().unwrap();
+	let quiet = project.create_ticket().unwrap();
+	let popular = project.create_ticket().unwrap();
+	let liked = project.create_ticket().unwrap();
+
+	let vote = popular.vote("alice@example.com").unwrap();
+	assert_eq!(popular.vote("alice@example.com").unwrap(), vote);
+	popular.vote("bob@example.com").unwrap();
+	popular.vote("carol@example.com").unwrap();
+	liked.vote("alice@example.com").unwrap();
+	liked.vote("dave@example.com").unwrap();
+	assert!(liked.unvote("dave@example.com").unwrap().is_some());
+	assert!(liked.unvote("dave@example.com").unwrap().is_none());
+
+	assert_eq!(popular.vote_count().unwrap(), 3);
+	assert_eq!(liked.votes().unwrap(), vec!["alice@example.com"]);
+	assert!(quiet.votes().unwrap().is_empty());
+	assert_eq!(popular.view().unwrap().votes.len(), 3);
+
+	let ids = project
+		.query(&TicketQuery::new().sort(TicketSort::Votes))
+		.unwrap()
+		.map(|t| t.unwrap().id())
+		.collect::<Vec<_>>();
+	assert_eq!(ids, vec![popular.id(), liked.id(), quiet.id()]);
+}
+
+#[test]
+fn test_query_sort_by_field() {
+	let workspace = Workspace::open(create_test_remote!()).unwrap();
+
+	let project = workspace.create_project("test").unwrap().unwrap();
+	let other = workspace.create_project("other").unwrap().unwrap();
+	project
+		.define_field(
+			"priority",
+			&FieldType::Enum(vec!["low".into(), "medium".into(), "high".into()]),
+		)
+		.unwrap();
+
+	let medium = project.create_ticket().unwrap();
+	medium.set_field("priority", "medium").unwrap();
+	let unset = project.create_ticket().unwrap();
+	let high = project.create_ticket().unwrap();
+	high.set_field("priority", "high").unwrap();
+	let low = project.create_ticket().unwrap();
+	low.set_field("priority", "low").unwrap();
+	let elsewhere = other.create_ticket().unwrap();
+
+	let slugs = |descending| {
+		workspace
+			.query(&TicketQuery::new().sort(TicketSort::Field {
+				name: "priority".to_string(),
+				descending,
+			}))
+			.unwrap()
+			.map(|t| t.unwrap().slug().to_string())
+			.collect::<Vec<_>>()
+	};
+	assert_eq!(
+		slugs(false),
+		vec![
+			low.slug(),
+			medium.slug(),
+			high.slug(),
+			unset.slug(),
+			elsewhere.slug()
+		]
+	);
+	assert_eq!(
+		slugs(true),
+		vec![
+			high.slug(),
+			medium.slug(),
+			low.slug(),
+			unset.slug(),
+			elsewhere.slug()
+		]
+	);
+}
+
+#[test]
+fn test_ticket_query() {
+	let workspace = Workspace::open(create_test_remote!()).unwrap();
+
+	let project = workspace.create_project("test").unwrap().unwrap();
+	let other = workspace.create_project("other").unwrap().unwrap();
+
+	let bug = project.create_ticket().unwrap();
+	bug.set_title("Crash on save").unwrap();
+	bug.add_label("bug").unwrap();
+	bug.add_assignee("alice").unwrap();
+
+	let closed_bug = project.create_ticket().unwrap();
+	closed_bug.add_label("bug").unwrap();
+	closed_bug.set_state(TicketState::Closed).unwrap();
+
+	let feature = project.create_ticket().unwrap();
+	feature.add_label("feature").unwrap();
+	feature.add_assignee("alice").unwrap();
+
+	let other_bug = other.create_ticket().unwrap();
+	other_bug.add_label("bug").unwrap();
+
+	let slugs = |iter: TicketQueryIterator<'_, _>| {
+		let mut slugs = iter
+			.map(|t| t.unwrap().slug().to_string())
+			.collect::<Vec<_>>();
+		slugs.sort();
+		slugs
+	};
+
+	assert_eq!(
+		slugs(project.query(&TicketQuery::new()).unwrap()),
+		vec!["test-1", "test-2", "test-3"]
+	);
+	assert_eq!(
+		slugs(
+			project
+				.query(&TicketQuery::new().state(TicketState::Open).label("bug"))
+				.unwrap()
+		),
+		vec!["test-1"]
+	);
+	assert_eq!(
+		slugs(project.query(&TicketQuery::new().assignee("alice")).unwrap()),
+		vec!["test-1", "test-3"]
+	);
+	assert_eq!(
+		slugs(
+			project
+				.query(&TicketQuery::new().label("bug").label("feature"))
+				.unwrap()
+		),
+		Vec::<String>::new()
+	);
+	assert_eq!(
+		slugs(project.query(&TicketQuery::new().text("CRASH")).unwrap()),
+		vec!["test-1"]
+	);
+	assert_eq!(
+		slugs(workspace.query(&TicketQuery::new().label("bug")).unwrap()),
+		vec!["other-1", "test-1", "test-2"]
+	);
+
+	// workspace-level queries skip deleted projects
+	workspace.delete_project("other").unwrap().unwrap();
+	assert_eq!(workspace.tickets().unwrap().len(), 3);
+	assert_eq!(
+		slugs(
+			workspace
+				.query(&TicketQuery::new().state(TicketState::Open).assignee("alice"))
+				.unwrap()
+		),
+		vec!["test-1", "test-3"]
+	);
+	workspace.create_project("other").unwrap().unwrap();
+
+	let created = bug.created().unwrap().timestamp();
+	assert_eq!(
+		slugs(
+			workspace
+				.query(&TicketQuery::new().created_after(created - 1))
+				.unwrap()
+		)
+		.len(),
+		4
+	);
+	assert!(slugs(
+		workspace
+			.query(&TicketQuery::new().created_before(created))
+			.unwrap()
+	)
+	.is_empty());
+}
+
+#[test]
+fn test_stats() {
+	let workspace = Workspace::open(create_test_remote!()).unwrap();
+
+	let project = workspace.create_project("test").unwrap().unwrap();
+	let other = workspace.create_project("other").unwrap().unwrap();
+	assert_eq!(project.stats().unwrap(), Stats::default());
+
+	let bug = project.create_ticket().unwrap();
+	bug.add_label("bug").unwrap();
+	bug.add_assignee("alice").unwrap();
+	bug.add_comment("one").unwrap();
+	let deleted = bug.add_comment("two").unwrap();
+	bug.delete_comment(&deleted.id()).unwrap().unwrap();
+
+	let closed = project.create_ticket().unwrap();
+	closed.add_label("bug").unwrap();
+	closed.set_state(TicketState::Closed).unwrap();
+
+	let elsewhere = other.create_ticket().unwrap();
+	elsewhere.add_assignee("alice").unwrap();
+	elsewhere.add_comment("three").unwrap();
+
+	let stats = project.stats().unwrap();
+	assert_eq!(stats.open, 1);
+	assert_eq!(stats.closed, 1);
+	assert_eq!(stats.tickets(), 2);
+	assert_eq!(stats.labels["bug"], 2);
+	assert_eq!(stats.assignees["alice"], 1);
+	assert_eq!(stats.comments, 1);
+	// created + 2 comments + tombstone + created + state change
+	assert_eq!(stats.activity.values().sum::<usize>(), 6);
+	assert!(stats
+		.activity
+		.keys()
+		.all(|bucket| bucket % STATS_BUCKET_SECONDS == 0));
+
+	let stats = workspace.stats().unwrap();
+	assert_eq!(stats.tickets(), 3);
+	assert_eq!(stats.assignees["alice"], 2);
+	assert_eq!(stats.comments, 2);
+}
+
+#[test]
+fn test_project_label_definitions() {
+	let workspace = Workspace::open(create_test_remote!()).unwrap();
+	let project = workspace.create_project("test").unwrap().unwrap();
+	assert!(project.labels().unwrap().is_empty());
+
+	project.define_label("bug", "#D73A4A", "Something isn't working").unwrap();
+	project.define_label("good first issue", "#7057ff", "").unwrap();
+	assert_eq!(
+		project.label("bug").unwrap(),
+		Some(LabelDefinition {
+			name: "bug".to_string(),
+			color: Some("#d73a4a".to_string()),
+			description: Some("Something isn't working".to_string()),
+		})
+	);
+	assert_eq!(
+		project.label("good first issue").unwrap().unwrap().description,
+		None
+	);
+
+	// redefining replaces the color and description
+	assert!(matches!(
+		project.define_label("bug", "#d73a4a", "Something isn't working"),
+		Err(Error::Exists(_, _))
+	));
+	project.define_label("bug", "#ff0000", "Broken").unwrap();
+	let labels = project.labels().unwrap();
+	assert_eq!(labels.len(), 2);
+	assert_eq!(project.label("bug").unwrap().unwrap().color.unwrap(), "#ff0000");
+
+	assert!(matches!(
+		project.define_label("ui", "red", ""),
+		Err(Error::Malformed(_))
+	));
+	assert!(matches!(
+		project.define_label("", "#ffffff", ""),
+		Err(Error::Malformed(_))
+	));
+
+	// labels aren't restricted by default
+	let ticket = project.create_ticket().unwrap();
+	ticket.add_label("ui").unwrap();
+
+	workspace
+		.set_settings(&WorkspaceSettings {
+			restrict_labels: true,
+			..WorkspaceSettings::default()
+		})
+		.unwrap();
+	assert!(workspace.settings().unwrap().restrict_labels);
+	assert!(matches!(
+		ticket.add_label("docs"),
+		Err(Error::NotFound(_, _))
+	));
+	ticket.add_label("bug").unwrap();
+
+	assert!(project.remove_label("bug").unwrap().is_some());
+	assert!(project.remove_label("bug").unwrap().is_none());
+	assert_eq!(project.labels().unwrap().len(), 1);
+	assert!(ticket.labels().unwrap().contains(&"bug".to_string()));
+}
+
+#[test]
+fn test_project_parent_cycles() {
+	let workspace = Workspace::open(create_test_remote!()).unwrap();
+	let a = workspace.create_project("a").unwrap().unwrap();
+	let b = a.create_project("b").unwrap().unwrap();
+	let c = b.create_project("c").unwrap().unwrap();
+
+	let ancestors = c
+		.ancestors()
+		.map(|project| project.unwrap().slug().to_string())
+		.collect::<Vec<_>>();
+	assert_eq!(ancestors, vec!["b", "a"]);
+	assert_eq!(a.ancestors().count(), 0);
+
+	assert!(matches!(a.set_parent(Some("a")), Err(Error::ProjectCycle(_))));
+	assert!(matches!(
+		a.set_parent(Some("c")),
+		Err(Error::ProjectCycle(chain)) if chain == "a -> c -> b -> a"
+	));
+	assert!(matches!(
+		a.set_parent(Some("missing")),
+		Err(Error::NotFound(_, _))
+	));
+
+	// moving a project is fine as long as it doesn't loop
+	c.set_parent(Some("a")).unwrap();
+	assert_eq!(c.parent().unwrap().unwrap().slug(), "a");
+	assert!(b.children().unwrap().is_empty());
+	c.set_parent(None).unwrap();
+	assert!(c.parent().unwrap().is_none());
+	assert_eq!(workspace.project_tree().unwrap().len(), 2);
+
+	// cycles introduced behind set_parent's back (e.g. by merging
+	// concurrent changes) are detected rather than looping forever
+	workspace
+		.remote()
+		.record_builder("meta/project/a/parent")
+		.commit("b")
+		.unwrap();
+	assert!(matches!(
+		b.ancestors().last(),
+		Some(Err(Error::ProjectCycle(_)))
+	));
+	let roots = workspace.project_tree().unwrap();
+	assert_eq!(roots.len(), 3);
+	assert!(roots.iter().all(|root| root.children.is_empty()));
+}
+
+#[test]
+fn test_workspace_export_import() {
+	let source = Workspace::open(create_test_remote!("source")).unwrap();
+	source
+		.remote()
+		.record_builder("meta/workspace/name")
+		.authored("Ada Lovelace", "ada@example.com", 1_000_000)
+		.commit("Engine")
+		.unwrap();
+
+	let project = source.create_project("test").unwrap().unwrap();
+	let ticket = project.create_ticket().unwrap();
+	ticket.set_title("Binary attachments").unwrap();
+	ticket.add_label("bug").unwrap();
+	ticket.remove_label("bug").unwrap();
+	ticket.add_label("ui").unwrap();
+	ticket.upsert_attachment("blob.bin", b"\x00\x01\n\xff").unwrap();
+	ticket.upsert_attachment("notes.txt", b"first").unwrap();
+	ticket.upsert_attachment("notes.txt", b"second").unwrap();
+	ticket.remove_attachment("blob.bin").unwrap().unwrap();
+	ticket
+		.add_comment_with_attachments("see log", &[("log.txt", b"hello")])
+		.unwrap();
+	let redacted = ticket.add_comment("secret").unwrap();
+	ticket.delete_comment(&redacted.id()).unwrap().unwrap();
+
+	let deleted = source.create_project("gone").unwrap().unwrap();
+	deleted.create_ticket().unwrap();
+	source.purge_project("gone", true, None).unwrap();
+
+	let mut bundle = Vec::new();
+	let exported = source.export(&mut bundle).unwrap();
+	assert!(exported > 0);
+
+	let target = Workspace::open(create_test_remote!("target")).unwrap();
+	let other = target.create_project("other").unwrap().unwrap();
+	let report = target.import(bundle.as_slice()).unwrap();
+	assert_eq!(report.records, exported);
+	assert!(report.collisions.is_empty());
+
+	// authorship and timestamps survive
+	let name = target.name().unwrap().unwrap();
+	assert_eq!(name.message(), "Engine");
+	assert_eq!(name.author(), "Ada Lovelace");
+	assert_eq!(name.email(), "ada@example.com");
+	assert_eq!(name.timestamp(), 1_000_000);
+
+	// and so do sets, attachments, and existing projects
+	let imported = target.ticket(ticket.slug()).unwrap();
+	assert_eq!(
+		imported.title().unwrap().unwrap().message(),
+		"Binary attachments"
+	);
+	assert_eq!(imported.labels().unwrap(), vec!["ui"]);
+	assert_eq!(imported.attachment("notes.txt").unwrap().unwrap(), b"second");
+	assert!(imported.attachment_info("blob.bin").unwrap().is_none());
+	// comments keep their files (and deletions), even though their IDs
+	// changed
+	let comments = imported.comments_view().unwrap();
+	assert_eq!(comments.len(), 1);
+	assert_eq!(comments[0].record.message(), "see log");
+	assert_eq!(comments[0].attachments, vec!["log.txt"]);
+	assert_eq!(
+		comments[0].record.attachment("log.txt").unwrap().unwrap(),
+		b"hello"
+	);
+	assert_eq!(imported.comments_audit().unwrap().len(), 2);
+	assert_eq!(other.tickets().unwrap().len(), 0);
+	let mut projects = target
+		.projects()
+		.unwrap()
+		.into_iter()
+		.map(|r| r.message())
+		.collect::<Vec<_>>();
+	projects.sort();
+	assert_eq!(projects, vec!["other", "test"]);
+
+	// purged projects stay purged, even though record IDs changed
+	assert!(target.trash().items().unwrap().is_empty());
+
+	// importing again collides with everything that's already there
+	let report = target.import(bundle.as_slice()).unwrap();
+	assert_eq!(report.collisions, vec!["test"]);
+	assert_eq!(target.project("test").unwrap().tickets().unwrap().len(), 1);
+
+	assert!(matches!(
+		target.import(&b"minimap-bundle 1\nrecord"[..]),
+		Err(Error::Malformed(_))
+	));
+}
+
+#[test]
+fn test_workspace_format() {
+	// workspaces from before the format was stamped can still be opened
+	let remote = create_test_remote!();
+	remote
+		.record_builder("meta/workspace/name")
+		.commit("legacy")
+		.unwrap();
+	let workspace = Workspace::open(remote).unwrap();
+	assert_eq!(workspace.format_version().unwrap(), 0);
+
+	// migrating stamps the current version, once
+	let remote = create_test_remote!("migrated");
+	assert_eq!(Workspace::migrate(&remote).unwrap(), vec![FORMAT_VERSION]);
+	assert!(Workspace::migrate(&remote).unwrap().is_empty());
+	let workspace = Workspace::open(remote).unwrap();
+	assert_eq!(workspace.format_version().unwrap(), FORMAT_VERSION);
+
+	// new workspaces are stamped with the current version, once
+	let remote = create_test_remote!("init");
+	let workspace = Workspace::init(remote).unwrap();
+	assert_eq!(workspace.format_version().unwrap(), FORMAT_VERSION);
+	let remote = create_test_remote!("init-existing");
+	remote
+		.record_builder("meta/workspace/name")
+		.commit("existing")
+		.unwrap();
+	assert!(matches!(Workspace::init(remote), Err(Error::Exists(..))));
+
+	// newer formats are refused
+	let remote = create_test_remote!("newer");
+	remote
+		.record_builder("meta/workspace/format")
+		.commit(&(FORMAT_VERSION + 1).to_string())
+		.unwrap();
+	assert!(matches!(
+		Workspace::open(remote),
+		Err(Error::UnsupportedFormat(found, FORMAT_VERSION)) if found == FORMAT_VERSION + 1
+	));
+}
+
+#[test]
+fn test_workspace_namespaces() {
+	let games = Workspace::open_namespaced(create_test_remote!(), "games").unwrap();
+	assert_eq!(games.remote().namespace(), "games");
+
+	games.set_name("Games").unwrap();
+	let project = games.create_project("shared").unwrap().unwrap();
+	project.create_ticket().unwrap().set_title("Sprites").unwrap();
+	assert_eq!(games.name().unwrap().unwrap().message(), "Games");
+	assert_eq!(
+		games.ticket("shared-1").unwrap().title().unwrap().unwrap().message(),
+		"Sprites"
+	);
+	assert!(games
+		.remote()
+		.collections("meta")
+		.unwrap()
+		.iter()
+		.all(|collection| collection.starts_with("meta/")));
+
+	// nothing leaks into the workspace outside of the namespace
+	let inner = games.remote().inner();
+	assert!(inner.collections("meta").unwrap().is_empty());
+	assert!(inner.latest("ns/games/meta/workspace/name").unwrap().is_some());
+	assert_eq!(Workspace::namespaces(inner).unwrap(), vec!["games"]);
+
+	assert!(matches!(
+		Workspace::open_namespaced(create_test_remote!("invalid"), "a/b"),
+		Err(Error::Malformed(_))
+	));
+}
+
+#[cfg(feature = "http")]
+#[test]
+fn test_http_dependency_origin() {
+	use crate::deps::fixtures::FakeClient;
+
+	const CI: FakeClient = FakeClient(&[
+		(
+			"http://ci/passing",
+			200,
+			r#"{"build": {"state": "passed", "green": true}}"#,
+		),
+		(
+			"http://ci/failing",
+			200,
+			r#"{"build": {"state": "failed", "green": false}}"#,
+		),
+	]);
+
+	let mut registry = DependencyRegistry::new();
+	registry
+		.register(Box::new(
+			HttpDependencyOrigin::new("http").with_client(CI),
+		))
+		.unwrap();
+	registry
+		.register(Box::new(
+			HttpDependencyOrigin::new("ci")
+				.with_client(CI)
+				.with_rule(HttpStatusRule::json_pointer("/build/state", &["passed"])),
+		))
+		.unwrap();
+	registry
+		.register(Box::new(
+			HttpDependencyOrigin::new("green")
+				.with_client(CI)
+				.with_rule(HttpStatusRule::regex(r#""green": (\w+)"#, &["true"]).unwrap()),
+		))
+		.unwrap();
+
+	assert_eq!(
+		registry.status("http", "http://ci/passing").unwrap(),
+		DependencyStatus::Complete
+	);
+	assert_eq!(
+		registry.status("http", "http://ci/missing").unwrap(),
+		DependencyStatus::Pending
+	);
+	assert_eq!(
+		registry.status("ci", "http://ci/passing").unwrap(),
+		DependencyStatus::Complete
+	);
+	assert_eq!(
+		registry.status("ci", "http://ci/failing").unwrap(),
+		DependencyStatus::Pending
+	);
+	assert!(registry.status("ci", "http://ci/missing").is_err());
+	assert_eq!(
+		registry.status("green", "http://ci/failing").unwrap(),
+		DependencyStatus::Pending
+	);
+
+	let workspace = Workspace::open(create_test_remote!()).unwrap();
+	let project = workspace.create_project("test").unwrap().unwrap();
+	let ticket = project.create_ticket().unwrap();
+	ticket.add_dependency("green", "http://ci/passing").unwrap();
+	let resolved = ticket
+		.resolve_dependencies(&registry)
+		.unwrap()
+		.map(|dependency| dependency.unwrap().2)
+		.collect::<Vec<_>>();
+	assert_eq!(resolved, vec![DependencyStatus::Complete]);
+
+	assert!(matches!(
+		HttpStatusRule::regex("(unclosed", &[]),
+		Err(Error::Malformed(_))
+	));
+}
+
+#[test]
+fn test_date_dependency_origin() {
+	// 2024-03-01T09:00:00Z
+	let embargo = 1709283600;
+
+	let before = DateDependencyOrigin::new().with_clock(FixedClock(embargo - 1));
+	let after = DateDependencyOrigin::new().with_clock(FixedClock(embargo));
+
+	for endpoint in [
+		"2024-03-01T09:00:00Z",
+		"2024-03-01t09:00:00.999z",
+		"2024-03-01 10:00:00+01:00",
+		"2024-03-01T04:30:00-04:30",
+	] {
+		assert_eq!(
+			before.status(endpoint).unwrap(),
+			DependencyStatus::Pending,
+			"{}",
+			endpoint
+		);
+		assert_eq!(
+			after.status(endpoint).unwrap(),
+			DependencyStatus::Complete,
+			"{}",
+			endpoint
+		);
+	}
+
+	assert_eq!(
+		after.status("2024-03-01").unwrap(),
+		DependencyStatus::Complete
+	);
+	assert_eq!(
+		after.status("2024-03-02").unwrap(),
+		DependencyStatus::Pending
+	);
+	assert_eq!(
+		after.status("2024-02-29T23:59:60Z").unwrap(),
+		DependencyStatus::Complete
+	);
+
+	for endpoint in [
+		"",
+		"tomorrow",
+		"2024-3-1",
+		"2023-02-29",
+		"2024-13-01",
+		"2024-03-01T09:00Z",
+		"2024-03-01T09:00:00",
+		"2024-03-01T09:00:00+0100",
+		"2024-03-01T25:00:00Z",
+	] {
+		assert!(after.status(endpoint).is_err(), "{}", endpoint);
+	}
+
+	let mut registry = DependencyRegistry::new();
+	registry.register(Box::new(after)).unwrap();
+
+	let workspace = Workspace::open(create_test_remote!()).unwrap();
+	let project = workspace.create_project("test").unwrap().unwrap();
+	let ticket = project.create_ticket().unwrap();
+	ticket
+		.add_dependency("date", "2024-03-01T09:00:00Z")
+		.unwrap();
+	ticket.add_dependency("date", "2025-01-01").unwrap();
+	let resolved = ticket
+		.resolve_dependencies(&registry)
+		.unwrap()
+		.map(|dependency| dependency.unwrap().2)
+		.collect::<Vec<_>>();
+	assert_eq!(
+		resolved,
+		vec![DependencyStatus::Pending, DependencyStatus::Complete]
+	);
+
+	assert!(SystemClock.now() > embargo);
+}
+
+#[test]
+fn test_async_dependency_resolution() {
+	use std::{
+		future::Future,
+		sync::Arc,
+		task::{Context, Poll, Wake, Waker},
+	};
+
+	/// Polls a future to completion on the current thread, giving up
+	/// (rather than spinning forever) if it takes too many polls.
+	fn block_on<F: Future>(future: F) -> F::Output {
+		struct NoopWaker;
+
+		impl Wake for NoopWaker {
+			fn wake(self: Arc<Self>) {}
+		}
+
+		let waker = Waker::from(Arc::new(NoopWaker));
+		let mut context = Context::from_waker(&waker);
+		let mut future = std::pin::pin!(future);
+		for _ in 0..1000 {
+			if let Poll::Ready(output) = future.as_mut().poll(&mut context) {
+				return output;
+			}
+		}
+		panic!("future didn't resolve");
+	}
+
+	/// Yields to the executor once before resolving.
+	struct YieldingOrigin;
+
+	impl AsyncDependencyOrigin for YieldingOrigin {
+		fn slug(&self) -> &str {
+			"yielding"
+		}
+
+		fn status_async<'a>(
+			&'a self,
+			endpoint: &'a str,
+		) -> BoxFuture<'a, std::result::Result<DependencyStatus, Box<dyn std::error::Error>>> {
+			let mut yielded = false;
+			Box::pin(async move {
+				std::future::poll_fn(|context| {
+					if yielded {
+						Poll::Ready(())
+					} else {
+						yielded = true;
+						context.waker().wake_by_ref();
+						Poll::Pending
+					}
+				})
+				.await;
+
+				match endpoint {
+					"done" => Ok(DependencyStatus::Complete),
+					"todo" => Ok(DependencyStatus::Pending),
+					_ => Err(Error::MalformedEndpoint(endpoint.to_string()).into()),
+				}
+			})
+		}
+	}
+
+	let mut registry = AsyncDependencyRegistry::new();
+	registry.register(Box::new(YieldingOrigin)).unwrap();
+	registry
+		.register_sync(Box::new(
+			DateDependencyOrigin::new().with_clock(FixedClock(1000)),
+		))
+		.unwrap();
+
+	assert_eq!(
+		block_on(registry.status_async("yielding", "done"
+		))
+		.unwrap(),
+		DependencyStatus::Complete
+	);
+	assert!(matches!(
+		block_on(registry.status_async("yielding", "broken"
+		)),
+		Err(Error::Origin(_))
+	));
+	assert!(matches!(
+		block_on(registry.status_async("nope", "done")),
+		Err(Error::UnknownOrigin(_))
+	));
+	assert!(matches!(
+		block_on(registry.status_async("_", "done")),
+		Err(Error::MalformedOrigin(_))
+	));
+
+	// The futures can be spawned on multi-threaded runtimes.
+	fn assert_send<T: Send>(_: &T) {}
+	assert_send(&registry.status_async("yielding", "done"));
+
+	let workspace = Workspace::open(create_test_remote!()).unwrap();
+	let project = workspace.create_project("test").unwrap().unwrap();
+	let other = project.create_ticket().unwrap();
+	other.set_state(TicketState::Closed).unwrap();
+	let ticket = project.create_ticket().unwrap();
+	ticket.add_dependency("yielding", "todo").unwrap();
+	ticket.add_dependency("date", "1970-01-01").unwrap();
+	ticket.add_dependency("_", other.slug()).unwrap();
+
+	let resolved = block_on(ticket.resolve_dependencies_async(&registry)).unwrap();
+	assert_eq!(
+		resolved
+			.iter()
+			.map(|(origin, _, status)| (origin.as_str(), *status))
+			.collect::<Vec<_>>(),
+		vec![
+			("_", DependencyStatus::Complete),
+			("date", DependencyStatus::Complete),
+			("yielding", DependencyStatus::Pending),
+		]
+	);
+
+	// Thread-safe synchronous resolvers can be used as asynchronous ones.
+	struct DateResolver(DateDependencyOrigin);
+
+	impl DependencyResolver for DateResolver {
+		fn status(&self, slug: &str, endpoint: &str) -> Result<DependencyStatus> {
+			match slug {
+				"date" => self.0.status(endpoint).map_err(Error::Origin),
+				_ => Err(Error::UnknownOrigin(slug.to_string())),
+			}
+		}
+	}
+
+	let sync_resolver = DateResolver(DateDependencyOrigin::new().with_clock(FixedClock(0)));
+	let other_ticket = project.create_ticket().unwrap();
+	other_ticket.add_dependency("date", "1970-01-02").unwrap();
+	assert_eq!(
+		block_on(other_ticket.resolve_dependencies_async(&sync_resolver)).unwrap(),
+		vec![(
+			"date".to_string(),
+			"1970-01-02".to_string(),
+			DependencyStatus::Pending
+		)]
+	);
+
+	// Dependencies are resolved concurrently: these only complete once
+	// both of them have been polled.
+	struct BarrierOrigin(std::sync::atomic::AtomicUsize);
+
+	impl AsyncDependencyOrigin for BarrierOrigin {
+		fn slug(&self) -> &str {
+			"barrier"
+		}
+
+		fn status_async<'a>(
+			&'a self,
+			_endpoint: &'a str,
+		) -> BoxFuture<'a, std::result::Result<DependencyStatus, Box<dyn std::error::Error>>> {
+			use std::sync::atomic::Ordering;
+
+			let mut arrived = false;
+			Box::pin(std::future::poll_fn(move |context| {
+				if !arrived {
+					arrived = true;
+					self.0.fetch_add(1, Ordering::SeqCst);
+				}
+				if self.0.load(Ordering::SeqCst) >= 2 {
+					Poll::Ready(Ok(DependencyStatus::Complete))
+				} else {
+					context.waker().wake_by_ref();
+					Poll::Pending
+				}
+			}))
+		}
+	}
+
+	registry
+		.register(Box::new(BarrierOrigin(Default::default())))
+		.unwrap();
+	let concurrent = project.create_ticket().unwrap();
+	concurrent.add_dependency("barrier", "a").unwrap();
+	concurrent.add_dependency("barrier", "b").unwrap();
+	assert_eq!(
+		block_on(concurrent.resolve_dependencies_async(&registry))
+			.unwrap()
+			.len(),
+		2
+	);
+
+	ticket.add_dependency("yielding", "broken").unwrap();
+	assert!(block_on(ticket.resolve_dependencies_async(&registry)).is_err());
+}
+
+#[test]
+fn test_dependency_graph() {
+	let workspace = Workspace::open(create_test_remote!()).unwrap();
+	let project = workspace.create_project("rel").unwrap().unwrap();
+	let design = project.create_ticket().unwrap();
+	let build = project.create_ticket().unwrap();
+	let docs = project.create_ticket().unwrap();
+	let release = project.create_ticket().unwrap();
+	project.create_ticket().unwrap();
+
+	build.add_dependency("_", design.slug()).unwrap();
+	docs.add_dependency("_", design.slug()).unwrap();
+	release.add_dependency("_", build.slug()).unwrap();
+	release.add_dependency("_", docs.slug()).unwrap();
+	release.add_dependency("_", "gone-1").unwrap();
+	release
+		.add_dependency("minimap", "https://example.com/upstream.git@up-1")
+		.unwrap();
+	release.add_dependency("date", "2024-01-01").unwrap();
+
+	let graph = workspace.dependency_graph(false).unwrap();
+	assert_eq!(
+		graph.nodes().collect::<Vec<_>>(),
+		vec!["rel-1", "rel-2", "rel-3", "gone-1", "rel-4"]
+	);
+	assert_eq!(graph.edges().count(), 5);
+	assert_eq!(
+		graph.blockers("rel-4"),
+		vec!["gone-1", "rel-3", "rel-2", "rel-1"]
+	);
+	assert_eq!(graph.blockers("rel-1"), Vec::<&str>::new());
+	assert_eq!(graph.blocked_by("rel-1"), vec!["rel-2", "rel-3", "rel-4"]);
+	assert_eq!(graph.blocked_by("rel-5"), Vec::<&str>::new());
+	assert_eq!(
+		graph.topological_order().unwrap(),
+		vec!["rel-1", "rel-2", "rel-3", "gone-1", "rel-4"]
+	);
+	assert_eq!(
+		graph.to_dot(),
+		"digraph dependencies {\n\
+		\t\"rel-1\";\n\t\"rel-2\";\n\t\"rel-3\";\n\t\"gone-1\";\n\t\"rel-4\";\n\
+		\t\"rel-1\" -> \"rel-2\";\n\
+		\t\"rel-1\" -> \"rel-3\";\n\
+		\t\"gone-1\" -> \"rel-4\";\n\
+		\t\"rel-3\" -> \"rel-4\";\n\
+		\t\"rel-2\" -> \"rel-4\";\n\
+		}\n"
+	);
+
+	let graph = workspace.dependency_graph(true).unwrap();
+	assert!(graph
+		.blockers("rel-4")
+		.contains(&"minimap@https://example.com/upstream.git@up-1"));
+	assert_eq!(graph.edges().count(), 6);
+
+	// Cycles can only appear through merges.
+	workspace
+		.remote()
+		.set_add_unchecked("project/rel/ticket/1/dependencies", "_@rel-4")
+		.unwrap();
+	let graph = workspace.dependency_graph(false).unwrap();
+	assert!(graph.topological_order().is_none());
+	assert!(graph.blockers("rel-1").contains(&"rel-1"));
+}
+
+#[test]
+fn test_dependency_cycle() {
+	let workspace = Workspace::open(create_test_remote!()).unwrap();
+	let project = workspace.create_project("test").unwrap().unwrap();
+	let a = project.create_ticket().unwrap();
+	let b = project.create_ticket().unwrap();
+	let c = project.create_ticket().unwrap();
+
+	a.add_dependency("_", b.slug()).unwrap();
+	b.add_dependency("_", c.slug()).unwrap();
+	// Diamonds aren't cycles.
+	a.add_dependency("_", c.slug()).unwrap();
+
+	match c.add_dependency("_", a.slug()) {
+		Err(Error::DependencyCycle(path)) => {
+			assert!(
+				path == "test-3 -> test-1 -> test-2 -> test-3"
+					|| path == "test-3 -> test-1 -> test-3",
+				"{}",
+				path
+			)
+		}
+		other => panic!("expected a cycle, got {:?}", other.map(|r| r.id())),
+	}
+	match b.add_dependency("_", a.slug()) {
+		Err(Error::DependencyCycle(path)) => assert_eq!(path, "test-2 -> test-1 -> test-2"),
+		other => panic!("expected a cycle, got {:?}", other.map(|r| r.id())),
+	}
+	match a.add_dependency("_", a.slug()) {
+		Err(Error::DependencyCycle(path)) => assert_eq!(path, "test-1 -> test-1"),
+		other => panic!("expected a cycle, got {:?}", other.map(|r| r.id())),
+	}
+	assert_eq!(c.dependencies().unwrap().len(), 0);
+	assert_eq!(b.dependencies().unwrap().len(), 1);
+
+	// Other origins aren't checked, even if they point at the same ticket.
+	c.add_dependency("ext", a.slug()).unwrap();
+	c.add_dependency("_", "missing-1").unwrap();
+}
+
+#[test]
+fn test_effective_state() {
+	let workspace = Workspace::open(create_test_remote!()).unwrap();
+	let project = workspace.create_project("test").unwrap().unwrap();
+	let blocker = project.create_ticket().unwrap();
+	let ticket = project.create_ticket().unwrap();
+	let external = project.create_ticket().unwrap();
+	let closed = project.create_ticket().unwrap();
+	closed.set_state(TicketState::Closed).unwrap();
+
+	let mut registry = DependencyRegistry::new();
+	registry
+		.register(Box::new(
+			DateDependencyOrigin::new().with_clock(FixedClock(0)),
+		))
+		.unwrap();
+
+	assert_eq!(
+		ticket.effective_state(&registry).unwrap(),
+		EffectiveState::Open
+	);
+
+	ticket.add_dependency("_", blocker.slug()).unwrap();
+	assert_eq!(
+		ticket.effective_state(&registry).unwrap(),
+		EffectiveState::Blocked
+	);
+	blocker.set_state(TicketState::Closed).unwrap();
+	assert_eq!(
+		ticket.effective_state(&registry).unwrap(),
+		EffectiveState::Open
+	);
+	assert_eq!(
+		blocker.effective_state(&registry).unwrap(),
+		EffectiveState::Closed
+	);
+
+	external.add_dependency("date", "1970-01-01").unwrap();
+	assert_eq!(
+		external.effective_state(&registry).unwrap(),
+		EffectiveState::Open
+	);
+	external.add_dependency("date", "2000-01-01").unwrap();
+	assert_eq!(
+		external.effective_state(&registry).unwrap(),
+		EffectiveState::Blocked
+	);
+
+	closed.add_dependency("date", "2000-01-01").unwrap();
+	assert_eq!(
+		closed.effective_state(&registry).unwrap(),
+		EffectiveState::Closed
+	);
+
+	let slugs = |query: &TicketQuery| {
+		workspace
+			.query_with(query, &registry)
+			.unwrap()
+			.map(|ticket| ticket.unwrap().slug().to_string())
+			.collect::<Vec<_>>()
+	};
+	assert_eq!(
+		slugs(&TicketQuery::new().effective_state(EffectiveState::Open)),
+		vec!["test-2"]
+	);
+	assert_eq!(
+		slugs(&TicketQuery::new().effective_state(EffectiveState::Blocked)),
+		vec!["test-3"]
+	);
+	assert_eq!(
+		slugs(&TicketQuery::new().effective_state(EffectiveState::Closed)),
+		vec!["test-1", "test-4"]
+	);
+
+	// Without a resolver, only `_` dependencies can be resolved,
+	// and the others block their tickets.
+	let query = TicketQuery::new().effective_state(EffectiveState::Open);
+	assert!(query.matches(&ticket).unwrap());
+	assert!(!query.matches(&external).unwrap());
+	assert_eq!(
+		workspace
+			.query(&TicketQuery::new().effective_state(EffectiveState::Blocked))
+			.unwrap()
+			.count(),
+		1
+	);
+
+	// Neither do dependencies on deleted tickets, nor do they stop the query.
+	let deleted = project.create_ticket().unwrap();
+	ticket.add_dependency("_", deleted.slug()).unwrap();
+	project.delete_ticket(deleted.id()).unwrap().unwrap();
+	assert_eq!(
+		ticket.effective_state(&registry).unwrap(),
+		EffectiveState::Blocked
+	);
+	assert_eq!(
+		slugs(&TicketQuery::new().effective_state(EffectiveState::Blocked)),
+		vec!["test-2", "test-3"]
+	);
+
+	assert_eq!(
+		EffectiveState::try_from("blocked").unwrap(),
+		EffectiveState::Blocked
+	);
+	assert_eq!(EffectiveState::Blocked.to_string(), "blocked");
+}
+
+#[test]
+fn test_dependency_notes() {
+	let workspace = Workspace::open(create_test_remote!()).unwrap();
+	let project = workspace.create_project("test").unwrap().unwrap();
+	let ticket = project.create_ticket().unwrap();
+
+	let plain = ticket.add_dependency("ext", "plain").unwrap();
+	let noted = ticket
+		.add_dependency_with_note("ext", "noted", "Waiting on the vendor\tfix")
+		.unwrap();
+
+	let dependencies = ticket.dependencies().unwrap();
+	assert_eq!(
+		dependencies,
+		vec![
+			Dependency {
+				origin: "ext".to_string(),
+				endpoint: "noted".to_string(),
+				kind: DependencyKind::Blocking,
+				note: Some("Waiting on the vendor\tfix".to_string()),
+				added_by: noted.email(),
+				added_at: noted.timestamp(),
+			},
+			Dependency {
+				origin: "ext".to_string(),
+				endpoint: "plain".to_string(),
+				kind: DependencyKind::Blocking,
+				note: None,
+				added_by: plain.email(),
+				added_at: plain.timestamp(),
+			},
+		]
+	);
+
+	// Re-adding keeps the dependency and replaces the note.
+	let again = ticket
+		.add_dependency_with_note("ext", "noted", "Vendor promised a fix")
+		.unwrap();
+	assert_eq!(again.id(), noted.id());
+	ticket
+		.add_dependency_with_note("ext", "plain", "Now with a note")
+		.unwrap();
+	let notes = |ticket: &Ticket<_>| {
+		ticket
+			.dependencies()
+			.unwrap()
+			.into_iter()
+			.map(|dependency| (dependency.endpoint, dependency.note))
+			.collect::<Vec<_>>()
+	};
+	assert_eq!(
+		notes(&ticket),
+		vec![
+			(
+				"noted".to_string(),
+				Some("Vendor promised a fix".to_string())
+			),
+			("plain".to_string(), Some("Now with a note".to_string())),
+		]
+	);
+
+	// Empty notes remove the note.
+	ticket.add_dependency_with_note("ext", "plain", "").unwrap();
+	assert_eq!(notes(&ticket)[1], ("plain".to_string(), None));
+
+	// Notes don't outlive their dependency.
+	ticket.remove_dependency("ext", "noted").unwrap();
+	ticket.add_dependency("ext", "noted").unwrap();
+	assert_eq!(notes(&ticket)[0], ("noted".to_string(), None));
+
+	ticket
+		.add_dependency_with_note("ext", "noted", "Shown in views")
+		.unwrap();
+	assert_eq!(
+		ticket.view().unwrap().dependencies[0].note.as_deref(),
+		Some("Shown in views")
+	);
+}
+
+#[test]
+fn test_dependency_kinds() {
+	let workflow =
+		Workflow::new().require(TicketState::Closed, TransitionCondition::NoOpenDependencies);
+	let workspace = Workspace::open(create_test_remote!()).unwrap().with_workflow(workflow);
+	let project = workspace.create_project("test").unwrap().unwrap();
+	let ticket = project.create_ticket().unwrap();
+	let related = project.create_ticket().unwrap();
+	let registry = DependencyRegistry::new();
+
+	let informational = ticket
+		.add_dependency_with_kind("_", related.slug(), DependencyKind::Informational)
+		.unwrap();
+	assert_eq!(
+		ticket.dependencies().unwrap()[0].kind,
+		DependencyKind::Informational
+	);
+	assert_eq!(
+		ticket.view().unwrap().dependencies[0].kind,
+		DependencyKind::Informational
+	);
+
+	// Informational dependencies neither block the ticket nor form cycles.
+	assert_eq!(
+		ticket.effective_state(&registry).unwrap(),
+		EffectiveState::Open
+	);
+	assert!(workspace.dependency_graph(false).unwrap().edges().next().is_none());
+	related.add_dependency("_", ticket.slug()).unwrap();
+
+	// Re-adding keeps the kind, changing it keeps the dependency.
+	assert_eq!(
+		ticket.add_dependency("_", related.slug()).unwrap().id(),
+		informational.id()
+	);
+	assert_eq!(
+		ticket.dependencies().unwrap()[0].kind,
+		DependencyKind::Informational
+	);
+	assert!(matches!(
+		ticket.add_dependency_with_kind("_", related.slug(), DependencyKind::Blocking),
+		Err(Error::DependencyCycle(_))
+	));
+	related.remove_dependency("_", ticket.slug()).unwrap();
+	ticket
+		.add_dependency_with_kind("_", related.slug(), DependencyKind::Blocking)
+		.unwrap();
+	assert_eq!(ticket.dependencies().unwrap().len(), 1);
+	assert_eq!(
+		ticket.effective_state(&registry).unwrap(),
+		EffectiveState::Blocked
+	);
+	assert!(matches!(
+		ticket.set_state(TicketState::Closed),
+		Err(Error::TransitionDenied(_, _, _, _))
+	));
+
+	ticket
+		.add_dependency_with_kind("_", related.slug(), DependencyKind::Informational)
+		.unwrap();
+	ticket.set_state(TicketState::Closed).unwrap();
+
+	// Kinds don't outlive their dependency.
+	ticket.remove_dependency("_", related.slug()).unwrap();
+	ticket.add_dependency("_", related.slug()).unwrap();
+	assert_eq!(
+		ticket.dependencies().unwrap()[0].kind,
+		DependencyKind::Blocking
+	);
+
+	assert_eq!(
+		DependencyKind::try_from("informational").unwrap(),
+		DependencyKind::Informational
+	);
+	assert_eq!(DependencyKind::Blocking.to_string(), "blocking");
+}
+
+#[test]
+fn test_dependency_monitor() {
+	let workspace = Workspace::open(create_test_remote!()).unwrap();
+	let project = workspace.create_project("test").unwrap().unwrap();
+	let ticket = project.create_ticket().unwrap();
+	let blocker = project.create_ticket().unwrap();
+	let done = project.create_ticket().unwrap();
+	let related = project.create_ticket().unwrap();
+	done.set_state(TicketState::Closed).unwrap();
+
+	ticket.add_dependency("_", blocker.slug()).unwrap();
+	ticket.add_dependency("_", done.slug()).unwrap();
+	ticket
+		.add_dependency_with_kind("_", related.slug(), DependencyKind::Informational)
+		.unwrap();
+	ticket.add_dependency("flaky", "endpoint").unwrap();
+
+	// Fails to resolve until given a status.
+	struct FlakyResolver(std::cell::Cell<Option<DependencyStatus>>);
+
+	impl DependencyResolver for FlakyResolver {
+		fn status(&self, slug: &str, _endpoint: &str) -> Result<DependencyStatus> {
+			self.0
+				.get()
+				.ok_or_else(|| Error::UnknownOrigin(slug.to_string()))
+		}
+	}
+
+	let resolver = FlakyResolver(std::cell::Cell::new(None));
+	let received = std::cell::RefCell::new(Vec::new());
+	let mut monitor = DependencyMonitor::new(&resolver).with_interval(std::time::Duration::ZERO);
+	monitor.subscribe(|event| received.borrow_mut().push(event.clone()));
+
+	// The first poll only records what's pending; failures are unknown,
+	// and informational dependencies aren't watched.
+	assert_eq!(monitor.poll(&workspace).unwrap(), vec![]);
+	assert_eq!(monitor.pending(), vec![("test-1", "_", "test-2")]);
+
+	// Unknown dependencies that resolve as complete aren't reported...
+	resolver.0.set(Some(DependencyStatus::Complete));
+	related.set_state(TicketState::Closed).unwrap();
+	assert_eq!(monitor.poll(&workspace).unwrap(), vec![]);
+	assert_eq!(monitor.pending(), vec![("test-1", "_", "test-2")]);
+
+	// ...and neither are pending ones while they fail to resolve.
+	ticket.add_dependency("flaky", "other").unwrap();
+	resolver.0.set(Some(DependencyStatus::Pending));
+	monitor.poll(&workspace).unwrap();
+	resolver.0.set(None);
+	assert_eq!(
+		monitor.pending(),
+		vec![("test-1", "_", "test-2"), ("test-1", "flaky", "other")]
+	);
+
+	assert_eq!(monitor.poll(&workspace).unwrap(), vec![]);
+	blocker.set_state(TicketState::Closed).unwrap();
+	let completed = DependencyEvent::Completed {
+		ticket: "test-1".to_string(),
+		origin: "_".to_string(),
+		endpoint: "test-2".to_string(),
+	};
+	assert_eq!(monitor.poll(&workspace).unwrap(), vec![completed.clone()]);
+	assert_eq!(*received.borrow(), vec![completed.clone()]);
+	assert_eq!(monitor.pending(), vec![("test-1", "flaky", "other")]);
+
+	// Completed dependencies aren't reported twice.
+	let mut polls = 0;
+	monitor
+		.run(&workspace, || {
+			polls += 1;
+			polls <= 2
+		})
+		.unwrap();
+	assert_eq!(polls, 3);
+	assert_eq!(received.borrow().len(), 1);
+
+	// Closed tickets aren't watched.
+	blocker.set_state(TicketState::Open).unwrap();
+	monitor.poll(&workspace).unwrap();
+	ticket.set_state(TicketState::Closed).unwrap();
+	monitor.poll(&workspace).unwrap();
+	assert!(monitor.pending().is_empty());
+	blocker.set_state(TicketState::Closed).unwrap();
+	assert_eq!(monitor.poll(&workspace).unwrap(), vec![]);
+	drop(monitor);
+	assert_eq!(received.into_inner(), vec![completed]);
+}
+
+#[test]
+fn test_resolve_all_dependencies() {
+	use crate::deps::fixtures::CountingOrigin;
+	use std::{cell::Cell, rc::Rc};
+
+	let workspace = Workspace::open(create_test_remote!()).unwrap();
+	let project = workspace.create_project("test").unwrap().unwrap();
+	let first = project.create_ticket().unwrap();
+	let second = project.create_ticket().unwrap();
+	let third = project.create_ticket().unwrap();
+
+	let calls = Rc::new(Cell::new(0));
+	let mut registry = DependencyRegistry::new();
+	registry
+		.register(Box::new(CountingOrigin(calls.clone())))
+		.unwrap();
+
+	assert!(workspace
+		.resolve_all_dependencies(&registry)
+		.unwrap()
+		.is_empty());
+
+	for ticket in [&first, &second, &third] {
+		ticket.add_dependency("counting", "done").unwrap();
+	}
+	first.add_dependency("counting", "todo").unwrap();
+	second.add_dependency("counting", "todo").unwrap();
+	second.add_dependency("counting", "broken").unwrap();
+	third.add_dependency("_", first.slug()).unwrap();
+	third.add_dependency("unknown", "endpoint").unwrap();
+
+	let statuses = workspace.resolve_all_dependencies(&registry).unwrap();
+	assert_eq!(calls.get(), 3);
+	assert_eq!(
+		statuses.keys().cloned().collect::<Vec<_>>(),
+		vec![
+			("counting".to_string(), "todo".to_string()),
+			("counting".to_string(), "done".to_string()),
+			("counting".to_string(), "broken".to_string()),
+			("unknown".to_string(), "endpoint".to_string()),
+			("_".to_string(), "test-1".to_string()),
+		]
+	);
+	let status = |origin: &str, endpoint: &str| {
+		statuses
+			.get(&(origin.to_string(), endpoint.to_string()))
+			.unwrap()
+	};
+	assert!(matches!(
+		status("counting", "todo"),
+		Ok(DependencyStatus::Pending)
+	));
+	assert!(matches!(
+		status("counting", "done"),
+		Ok(DependencyStatus::Complete)
+	));
+	assert!(matches!(status("counting", "broken"), Err(Error::Origin(_))));
+	assert!(matches!(
+		status("unknown", "endpoint"),
+		Err(Error::UnknownOrigin(_))
+	));
+	assert!(matches!(status("_", "test-1"), Ok(DependencyStatus::Pending)));
+}
+
@@ -0,0 +1,233 @@
+//! Unified activity timeline for tickets.
+//!
+//! A ticket's history is spread across several collections (title,
+//! state, comments, attachments, dependencies, ...). [`Ticket::activity`]
+//! walks all of them and merges them into a single, chronologically
+//! ordered list of typed [`TicketEvent`]s.
+
+use crate::{
+	parse_state_message, Error, Record, Remote, Result, SetOperation, Ticket, TicketState,
+};
+
+/// A single event in a ticket's activity timeline.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TicketEvent<T: Record> {
+	/// The ticket was created (or re-created after being deleted).
+	Created(T),
+	/// The ticket was deleted.
+	Deleted(T),
+	/// The ticket's title was changed.
+	TitleChanged {
+		/// The new title.
+		title: String,
+		/// The underlying record.
+		record: T,
+	},
+	/// The ticket's description was changed.
+	DescriptionChanged(T),
+	/// The ticket's state was changed.
+	StateChanged {
+		/// The new state.
+		state: TicketState,
+		/// The reason given for the change, if any.
+		reason: Option<String>,
+		/// The underlying record.
+		record: T,
+	},
+	/// A comment was added to the ticket.
+	CommentAdded(T),
+	/// A comment was deleted from the ticket.
+	CommentDeleted {
+		/// The record ID of the deleted comment.
+		comment_id: String,
+		/// The tombstone record.
+		record: T,
+	},
+	/// An attachment was added to (or updated on) the ticket.
+	AttachmentAdded {
+		/// The name of the attachment.
+		name: String,
+		/// The underlying record.
+		record: T,
+	},
+	/// An attachment was removed from the ticket.
+	AttachmentRemoved {
+		/// The name of the attachment.
+		name: String,
+		/// The underlying record.
+		record: T,
+	},
+	/// A dependency was added to the ticket.
+	DependencyAdded {
+		/// The dependency's origin.
+		origin: String,
+		/// The dependency's endpoint.
+		endpoint: String,
+		/// The underlying record.
+		record: T,
+	},
+	/// A dependency was removed from the ticket.
+	DependencyRemoved {
+		/// The dependency's origin.
+		origin: String,
+		/// The dependency's endpoint.
+		endpoint: String,
+		/// The underlying record.
+		record: T,
+	},
+}
+
+impl<T: Record> TicketEvent<T> {
+	/// Gets the record underlying the event.
+	pub fn record(&self) -> &T {
+		match self {
+			Self::Created(record)
+			| Self::Deleted(record)
+			| Self::DescriptionChanged(record)
+			| Self::CommentAdded(record)
+			| Self::TitleChanged { record, .. }
+			| Self::StateChanged { record, .. }
+			| Self::CommentDeleted { record, .. }
+			| Self::AttachmentAdded { record, .. }
+			| Self::AttachmentRemoved { record, .. }
+			| Self::DependencyAdded { record, .. }
+			| Self::DependencyRemoved { record, .. } => record,
+		}
+	}
+
+	/// Gets the unix timestamp (in seconds) of the event.
+	#[inline]
+	pub fn timestamp(&self) -> i64 {
+		self.record().timestamp()
+	}
+}
+
+impl<'a, R: Remote<'a>> Ticket<'a, R> {
+	/// Gets every event in the ticket's history, from oldest to latest.
+	///
+	/// Events are ordered by timestamp; events with the same timestamp
+	/// keep their order within their collection.
+	pub fn activity(&self) -> Result<Vec<TicketEvent<R::Record>>> {
+		let remote = &self.workspace.remote;
+		let mut events = Vec::new();
+
+		// Every collection walks from latest to oldest, so each one
+		// is reversed before being merged in.
+		let tickets_path = self
+			.path
+			.rsplit_once("/ticket/")
+			.map(|(project, _)| format!("{}/tickets", project))
+			.ok_or_else(|| Error::Malformed(self.path.clone()))?;
+		let id = self.id.to_string();
+		for result in remote
+			.walk_set(&tickets_path)?
+			.collect::<Vec<_>>()
+			.into_iter()
+			.rev()
+		{
+			let (record, op) = result?;
+			if record.message() == id {
+				events.push(match op {
+					SetOperation::Add => TicketEvent::Created(record),
+					SetOperation::Del => TicketEvent::Deleted(record),
+				});
+			}
+		}
+
+		for record in walk_oldest_first(remote, &format!("{}/title", self.path))? {
+			events.push(TicketEvent::TitleChanged {
+				title: record.message(),
+				record,
+			});
+		}
+
+		for record in walk_oldest_first(remote, &format!("{}/description", self.path))? {
+			events.push(TicketEvent::DescriptionChanged(record));
+		}
+
+		let state_path = format!("{}/state", self.path);
+		for record in walk_oldest_first(remote, &state_path)? {
+			let (state, reason) = parse_state_message(&record.message())
+				.ok_or_else(|| Error::Malformed(state_path.clone()))?;
+			events.push(TicketEvent::StateChanged {
+				state,
+				reason,
+				record,
+			});
+		}
+
+		for record in walk_oldest_first(remote, &format!("{}/comment", self.path))? {
+			events.push(TicketEvent::CommentAdded(record));
+		}
+
+		let tombstones_path = format!("{}/comment_tombstones", self.path);
+		for result in remote
+			.walk_set(&tombstones_path)?
+			.collect::<Vec<_>>()
+			.into_iter()
+			.rev()
+		{
+			let (record, op) = result?;
+			if op == SetOperation::Add {
+				events.push(TicketEvent::CommentDeleted {
+					comment_id: record.message(),
+					record,
+				});
+			}
+		}
+
+		let attachment_path = format!("{}/attachment", self.path);
+		for record in walk_oldest_first(remote, &attachment_path)? {
+			let message = record.message();
+			events.push(if let Some(name) = message.strip_prefix('+') {
+				TicketEvent::AttachmentAdded {
+					name: name.to_string(),
+					record,
+				}
+			} else if let Some(name) = message.strip_prefix('-') {
+				TicketEvent::AttachmentRemoved {
+					name: name.to_string(),
+					record,
+				}
+			} else {
+				return Err(Error::Malformed(attachment_path));
+			});
+		}
+
+		let dependencies_path = format!("{}/dependencies", self.path);
+		for result in remote
+			.walk_set(&dependencies_path)?
+			.collect::<Vec<_>>()
+			.into_iter()
+			.rev()
+		{
+			let (record, op) = result?;
+			let message = record.message();
+			let (origin, endpoint) = message
+				.split_once('@')
+				.ok_or_else(|| Error::Malformed(dependencies_path.clone()))?;
+			let (origin, endpoint) = (origin.to_string(), endpoint.to_string());
+			events.push(match op {
+				SetOperation::Add => TicketEvent::DependencyAdded {
+					origin,
+					endpoint,
+					record,
+				},
+				SetOperation::Del => TicketEvent::DependencyRemoved {
+					origin,
+					endpoint,
+					record,
+				},
+			});
+		}
+
+		events.sort_by_key(TicketEvent::timestamp);
+		Ok(events)
+	}
+}
+
+fn walk_oldest_first<'a, R: Remote<'a>>(remote: &'a R, collection: &str) -> Result<Vec<R::Record>> {
+	let mut records = remote.walk(collection)?.collect::<Result<Vec<_>>>()?;
+	records.reverse();
+	Ok(records)
+}
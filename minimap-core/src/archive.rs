@@ -0,0 +1,84 @@
+//! Archiving projects and tickets.
+//!
+//! Archived projects and tickets are kept as they are, but are meant to be
+//! left out of listings until they're unarchived (see
+//! [`TicketQuery::archived`](crate::TicketQuery::archived)). Unlike
+//! deleted ones, they still resolve by slug, and dependencies on them keep
+//! their status.
+//!
+//! Whether an entity is archived is the message of the latest record of
+//! its `archived` collection: `archived`, or empty once unarchived. A
+//! ticket is also archived while its project is.
+
+use crate::{Project, Record, RecordBuilder, Remote, Result, Ticket};
+
+/// The message of records that archive an entity.
+const ARCHIVED: &str = "archived";
+
+impl<'a, R: Remote<'a>> Project<'a, R> {
+	/// Checks whether the project is archived.
+	pub fn is_archived(&self) -> Result<bool> {
+		is_archived(&self.workspace.remote, &self.archived_path())
+	}
+
+	/// Archives the project, along with its tickets. Returns the record
+	/// that archived it, or `None` if it was already archived.
+	pub fn archive(&self) -> Result<Option<R::Record>> {
+		set_archived(&self.workspace.remote, &self.archived_path(), true)
+	}
+
+	/// Unarchives the project. Returns the record that unarchived it, or
+	/// `None` if it wasn't archived.
+	pub fn unarchive(&self) -> Result<Option<R::Record>> {
+		set_archived(&self.workspace.remote, &self.archived_path(), false)
+	}
+
+	fn archived_path(&self) -> String {
+		format!("{}/archived", self.meta_path)
+	}
+}
+
+impl<'a, R: Remote<'a>> Ticket<'a, R> {
+	/// Checks whether the ticket is archived, either by itself or along
+	/// with its project.
+	pub fn is_archived(&self) -> Result<bool> {
+		Ok(is_archived(&self.workspace.remote, &self.archived_path())?
+			|| self.project()?.is_archived()?)
+	}
+
+	/// Archives the ticket. Returns the record that archived it, or `None`
+	/// if it was already archived by itself.
+	pub fn archive(&self) -> Result<Option<R::Record>> {
+		set_archived(&self.workspace.remote, &self.archived_path(), true)
+	}
+
+	/// Unarchives the ticket. Returns the record that unarchived it, or
+	/// `None` if it wasn't archived by itself; a ticket archived along with
+	/// its project stays archived until the project is unarchived.
+	pub fn unarchive(&self) -> Result<Option<R::Record>> {
+		set_archived(&self.workspace.remote, &self.archived_path(), false)
+	}
+
+	fn archived_path(&self) -> String {
+		format!("{}/archived", self.path)
+	}
+}
+
+fn is_archived<'a, R: Remote<'a>>(remote: &'a R, collection: &str) -> Result<bool> {
+	Ok(remote
+		.latest(collection)?
+		.map_or(false, |record| record.message() == ARCHIVED))
+}
+
+fn set_archived<'a, R: Remote<'a>>(
+	remote: &'a R,
+	collection: &str,
+	archived: bool,
+) -> Result<Option<R::Record>> {
+	if is_archived(remote, collection)? == archived {
+		return Ok(None);
+	}
+
+	let message = if archived { ARCHIVED } else { "" };
+	Ok(Some(remote.record_builder(collection).commit(message)?))
+}
@@ -0,0 +1,162 @@
+//! Attachment metadata.
+//!
+//! Whenever an attachment is upserted, its size and MIME type are
+//! recorded in the ticket's `attachment_info/<name>` collection, so
+//! that clients can show them without fetching the attachment itself.
+//! Attachments uploaded before metadata was recorded have it derived
+//! from the attachment data instead.
+
+use crate::{Error, Record, RecordBuilder, Remote, Result, Ticket};
+use serde::Serialize;
+
+/// Metadata about a ticket attachment. See [`Ticket::attachment_info`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct AttachmentInfo {
+	/// The size of the attachment, in bytes.
+	pub size: u64,
+	/// The MIME type of the attachment.
+	pub mime: String,
+	/// The name of the user that last uploaded the attachment.
+	pub author: String,
+	/// The email address of the user that last uploaded the attachment.
+	pub email: String,
+	/// The unix timestamp of the last upload, in seconds.
+	pub timestamp: i64,
+}
+
+/// Guesses the MIME type of an attachment, first by the extension of
+/// its name and then by sniffing its data. Falls back to
+/// `application/octet-stream`.
+pub fn guess_mime_type(name: &str, data: &[u8]) -> &'static str {
+	let extension = name
+		.rsplit_once('.')
+		.map(|(_, ext)| ext.to_ascii_lowercase());
+
+	let by_extension = match extension.as_deref() {
+		Some("txt" | "log") => Some("text/plain"),
+		Some("md") => Some("text/markdown"),
+		Some("csv") => Some("text/csv"),
+		Some("html" | "htm") => Some("text/html"),
+		Some("json") => Some("application/json"),
+		Some("pdf") => Some("application/pdf"),
+		Some("zip") => Some("application/zip"),
+		Some("gz") => Some("application/gzip"),
+		Some("png") => Some("image/png"),
+		Some("jpg" | "jpeg") => Some("image/jpeg"),
+		Some("gif") => Some("image/gif"),
+		Some("webp") => Some("image/webp"),
+		Some("svg") => Some("image/svg+xml"),
+		_ => None,
+	};
+
+	if let Some(mime) = by_extension {
+		return mime;
+	}
+
+	if let Some(mime) = sniff_mime_type(data) {
+		mime
+	} else if std::str::from_utf8(data).is_ok() {
+		"text/plain"
+	} else {
+		"application/octet-stream"
+	}
+}
+
+/// Detects the MIME type of well-known binary formats
+/// (and SVG images) from their data alone.
+pub(crate) fn sniff_mime_type(data: &[u8]) -> Option<&'static str> {
+	let is_svg = std::str::from_utf8(data).map_or(false, |text| {
+		let text = text.trim_start();
+		text.starts_with("<svg") || (text.starts_with("<?xml") && text.contains("<svg"))
+	});
+
+	if data.starts_with(b"\x89PNG\r\n\x1a\n") {
+		Some("image/png")
+	} else if data.starts_with(b"\xff\xd8\xff") {
+		Some("image/jpeg")
+	} else if data.starts_with(b"GIF87a") || data.starts_with(b"GIF89a") {
+		Some("image/gif")
+	} else if data.len() >= 12 && data.starts_with(b"RIFF") && &data[8..12] == b"WEBP" {
+		Some("image/webp")
+	} else if data.starts_with(b"%PDF-") {
+		Some("application/pdf")
+	} else if is_svg {
+		Some("image/svg+xml")
+	} else {
+		None
+	}
+}
+
+impl<'a, R: Remote<'a>> Ticket<'a, R> {
+	/// Gets the metadata of an attachment on the ticket,
+	/// or `None` if the attachment doesn't exist.
+	pub fn attachment_info(&self, name: &str) -> Result<Option<AttachmentInfo>> {
+		let record = match self.attachment_record(name)? {
+			Some((true, record)) => record,
+			_ => return Ok(None),
+		};
+
+		let info_path = format!("{}/attachment_info/{}", self.path, name);
+		let recorded = self
+			.workspace
+			.remote
+			.latest(&info_path)?
+			// Only trust the metadata if it was written for the latest upload.
+			.filter(|info| info.timestamp() >= record.timestamp())
+			.map(|info| {
+				let message = info.message();
+				let (size, mime) = message
+					.split_once(' ')
+					.ok_or_else(|| Error::Malformed(info_path.clone()))?;
+				let size = size
+					.parse::<u64>()
+					.map_err(|_| Error::Malformed(info_path.clone()))?;
+				Ok::<_, Error>((size, mime.to_string()))
+			})
+			.transpose()?;
+
+		let (size, mime) = match recorded {
+			Some(recorded) => recorded,
+			None => match record.attachment(name)? {
+				Some(data) => (data.len() as u64, guess_mime_type(name, &data).to_string()),
+				None => return Ok(None),
+			},
+		};
+
+		Ok(Some(AttachmentInfo {
+			size,
+			mime,
+			author: record.author(),
+			email: record.email(),
+			timestamp: record.timestamp(),
+		}))
+	}
+
+	/// Records the metadata of a freshly upserted attachment.
+	pub(crate) fn record_attachment_info(&self, name: &str, data: &[u8]) -> Result<R::Record> {
+		self.workspace
+			.remote
+			.record_builder(&format!("{}/attachment_info/{}", self.path, name))
+			.commit(&format!("{} {}", data.len(), guess_mime_type(name, data)))
+	}
+
+	/// Finds the latest upsert (`true`) or removal (`false`) record
+	/// of an attachment, or `None` if it was never uploaded.
+	pub(crate) fn attachment_record(&self, name: &str) -> Result<Option<(bool, R::Record)>> {
+		for record in self
+			.workspace
+			.remote
+			.walk(&format!("{}/attachment", self.path))?
+		{
+			let record = record?;
+			let message = record.message();
+			if message.strip_prefix('+') == Some(name) {
+				return Ok(Some((true, record)));
+			} else if message.strip_prefix('-') == Some(name) {
+				return Ok(Some((false, record)));
+			}
+		}
+
+		Ok(None)
+	}
+}
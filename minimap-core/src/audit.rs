@@ -0,0 +1,210 @@
+//! Audit log of destructive operations.
+//!
+//! Every operation that removes data (deletions, purges and redactions)
+//! appends an entry to the `meta/audit` collection, along with the
+//! reason given for it, if any. The actor of an entry is the author of
+//! its record.
+//!
+//! Entries are chained together: each record carries a `digest`
+//! attachment holding the HMAC-SHA256 of the previous entry's digest and
+//! the entry's own message, keyed with the workspace's audit key (see
+//! [`Workspace::with_audit_key`]). Rewriting or dropping an entry
+//! without recomputing every later digest is detected by
+//! [`Workspace::verify_audit_log`], and only holders of the key can
+//! recompute them. Without a key, digests are plain SHA-256s, which
+//! anyone with push access to the remote can recompute.
+
+use crate::{Error, Record, RecordBuilder, Remote, Result, Workspace};
+use sha2::{Digest, Sha256};
+
+const AUDIT_COLLECTION: &str = "meta/audit";
+const DIGEST_ATTACHMENT: &str = "digest";
+
+/// The block size of SHA-256, for HMACs.
+const BLOCK_SIZE: usize = 64;
+
+/// A destructive operation recorded in the audit log.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum AuditOperation {
+	/// An entity was soft-deleted.
+	Delete,
+	/// An entity was permanently deleted.
+	Purge,
+	/// Content was hidden or removed from an entity (e.g. a comment).
+	Redact,
+}
+
+impl AuditOperation {
+	fn as_str(&self) -> &'static str {
+		match self {
+			Self::Delete => "delete",
+			Self::Purge => "purge",
+			Self::Redact => "redact",
+		}
+	}
+}
+
+impl TryFrom<&str> for AuditOperation {
+	type Error = Error;
+
+	fn try_from(value: &str) -> Result<Self> {
+		match value {
+			"delete" => Ok(Self::Delete),
+			"purge" => Ok(Self::Purge),
+			"redact" => Ok(Self::Redact),
+			_ => Err(Error::Malformed(value.to_string())),
+		}
+	}
+}
+
+impl ToString for AuditOperation {
+	#[inline]
+	fn to_string(&self) -> String {
+		self.as_str().to_string()
+	}
+}
+
+/// A single entry in the audit log.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AuditEntry<T: Record> {
+	/// The operation that was performed.
+	pub operation: AuditOperation,
+	/// The entity the operation was performed on (e.g. a
+	/// project or ticket slug).
+	pub target: String,
+	/// The reason given for the operation, if any.
+	pub reason: Option<String>,
+	/// The chained digest of the entry.
+	pub digest: String,
+	/// The underlying record. Its author is the actor
+	/// who performed the operation.
+	pub record: T,
+}
+
+impl<'a, R: Remote<'a>> Workspace<'a, R> {
+	/// Keys the audit log's digests with the given secret, which must
+	/// be kept out of the workspace (e.g. in the environment) and be the
+	/// same for everyone writing to or verifying the log.
+	pub fn with_audit_key(mut self, key: &[u8]) -> Self {
+		self.audit_key = Some(key.to_vec());
+		self
+	}
+
+	/// Appends an entry to the audit log.
+	///
+	/// Minimap's own destructive operations call this automatically;
+	/// it's exposed so that higher layers can log destructive operations
+	/// they perform on the remote themselves.
+	pub fn audit(
+		&'a self,
+		operation: AuditOperation,
+		target: &str,
+		reason: Option<&str>,
+	) -> Result<R::Record> {
+		let previous = match self.remote().latest(AUDIT_COLLECTION)? {
+			Some(record) => Some(digest_of(&record)?),
+			None => None,
+		};
+
+		let mut message = format!("{} {}", operation.as_str(), target);
+		if let Some(reason) = reason {
+			message.push_str("\n\n");
+			message.push_str(reason);
+		}
+
+		let digest = chain_digest(self.audit_key.as_deref(), previous.as_deref(), &message);
+
+		self.remote()
+			.record_builder(AUDIT_COLLECTION)
+			.upsert_attachment(DIGEST_ATTACHMENT, digest)?
+			.commit(&message)
+	}
+
+	/// Gets all entries in the audit log, from latest to oldest.
+	pub fn audit_log(&'a self) -> Result<Vec<AuditEntry<R::Record>>> {
+		self.remote()
+			.walk(AUDIT_COLLECTION)?
+			.map(|record| parse_entry(record?))
+			.collect()
+	}
+
+	/// Checks that the audit log's digest chain is intact, i.e. that
+	/// no entry has been altered or removed. Returns `false` if the
+	/// chain is broken, or was written with another audit key.
+	pub fn verify_audit_log(&'a self) -> Result<bool> {
+		let mut entries = self.audit_log()?;
+		entries.reverse();
+
+		let mut previous: Option<String> = None;
+		for entry in entries {
+			let digest = chain_digest(
+				self.audit_key.as_deref(),
+				previous.as_deref(),
+				&entry.record.message(),
+			);
+			if digest != entry.digest {
+				return Ok(false);
+			}
+			previous = Some(entry.digest);
+		}
+
+		Ok(true)
+	}
+}
+
+/// Digests an entry's message along with the previous entry's digest:
+/// their HMAC-SHA256 (RFC 2104) if there's a key, or else their SHA-256.
+fn chain_digest(key: Option<&[u8]>, previous: Option<&str>, message: &str) -> String {
+	let data = [
+		previous.unwrap_or_default().as_bytes(),
+		b"\n",
+		message.as_bytes(),
+	];
+	let Some(key) = key else {
+		return format!("{:x}", Sha256::digest(data.concat()));
+	};
+
+	let mut block = [0u8; BLOCK_SIZE];
+	if key.len() > BLOCK_SIZE {
+		block[..32].copy_from_slice(&Sha256::digest(key));
+	} else {
+		block[..key.len()].copy_from_slice(key);
+	}
+
+	let mut inner = Sha256::new();
+	inner.update(block.map(|b| b ^ 0x36));
+	for part in data {
+		inner.update(part);
+	}
+	let mut outer = Sha256::new();
+	outer.update(block.map(|b| b ^ 0x5c));
+	outer.update(inner.finalize());
+	format!("{:x}", outer.finalize())
+}
+
+fn digest_of<T: Record>(record: &T) -> Result<String> {
+	record
+		.attachment(DIGEST_ATTACHMENT)?
+		.and_then(|d| String::from_utf8(d).ok())
+		.ok_or_else(|| Error::Malformed(format!("{}/{}", AUDIT_COLLECTION, record.id())))
+}
+
+fn parse_entry<T: Record>(record: T) -> Result<AuditEntry<T>> {
+	let malformed = || Error::Malformed(format!("{}/{}", AUDIT_COLLECTION, record.id()));
+
+	let message = record.message();
+	let (header, reason) = match message.split_once("\n\n") {
+		Some((header, reason)) => (header, Some(reason.to_string())),
+		None => (message.as_str(), None),
+	};
+	let (operation, target) = header.split_once(' ').ok_or_else(malformed)?;
+	let operation = AuditOperation::try_from(operation).map_err(|_| malformed())?;
+
+	Ok(AuditEntry {
+		operation,
+		target: target.to_string(),
+		reason,
+		digest: digest_of(&record)?,
+		record,
+	})
+}
@@ -0,0 +1,101 @@
+//! Computed ticket states.
+//!
+//! A ticket's stored state is only ever open or closed. Whether an open
+//! ticket can actually be worked on depends on its blocking dependencies
+//! (see [`DependencyKind`]), so the [`EffectiveState`] is computed on
+//! demand with a [`DependencyResolver`].
+
+use crate::{
+	DependencyKind, DependencyResolver, DependencyStatus, Error, Remote, Result, Ticket,
+	TicketState,
+};
+
+/// The state of a ticket, taking its dependencies into account.
+/// See [`Ticket::effective_state`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, serde::Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum EffectiveState {
+	/// The ticket is open, and all of its dependencies are complete.
+	Open,
+	/// The ticket is open, but at least one dependency is pending.
+	Blocked,
+	/// The ticket is closed.
+	Closed,
+}
+
+impl TryFrom<&str> for EffectiveState {
+	type Error = Error;
+
+	fn try_from(value: &str) -> Result<Self> {
+		match value {
+			"open" => Ok(Self::Open),
+			"blocked" => Ok(Self::Blocked),
+			"closed" => Ok(Self::Closed),
+			_ => Err(Error::Malformed(value.to_string())),
+		}
+	}
+}
+
+impl ToString for EffectiveState {
+	fn to_string(&self) -> String {
+		match self {
+			Self::Open => "open",
+			Self::Blocked => "blocked",
+			Self::Closed => "closed",
+		}
+		.to_string()
+	}
+}
+
+/// Resolves nothing; used when only `_` dependencies can be resolved.
+pub(crate) struct LocalResolver;
+
+impl DependencyResolver for LocalResolver {
+	fn status(&self, slug: &str, _endpoint: &str) -> Result<DependencyStatus> {
+		Err(Error::UnknownOrigin(slug.to_string()))
+	}
+}
+
+impl<'a, R: Remote<'a>> Ticket<'a, R> {
+	/// Gets the state of the ticket, taking its dependencies into account:
+	/// open tickets with at least one pending blocking dependency are
+	/// blocked. Informational dependencies aren't resolved.
+	///
+	/// Dependencies on other tickets in the workspace (`_`) are resolved
+	/// directly, and all others with the given resolver. As they can't be
+	/// known to be complete, dependencies on tickets that no longer exist
+	/// and on origins the resolver doesn't know block the ticket. Returns
+	/// the first other error encountered while resolving a dependency.
+	pub fn effective_state<D: DependencyResolver + ?Sized>(
+		&self,
+		resolver: &D,
+	) -> Result<EffectiveState> {
+		if self.state()?.0 == TicketState::Closed {
+			return Ok(EffectiveState::Closed);
+		}
+
+		for dependency in self.dependencies()? {
+			if dependency.kind != DependencyKind::Blocking {
+				continue;
+			}
+
+			let status = if dependency.origin == "_" {
+				self.workspace
+					.ticket(&dependency.endpoint)
+					.and_then(|ticket| Ok(ticket.state()?.0.into()))
+			} else {
+				resolver.status(&dependency.origin, &dependency.endpoint)
+			};
+
+			match status {
+				Ok(DependencyStatus::Complete) => {}
+				Ok(DependencyStatus::Pending)
+				| Err(Error::NotFound(..))
+				| Err(Error::UnknownOrigin(_)) => return Ok(EffectiveState::Blocked),
+				Err(err) => return Err(err),
+			}
+		}
+
+		Ok(EffectiveState::Open)
+	}
+}
@@ -0,0 +1,480 @@
+//! Portable workspace bundles.
+//!
+//! [`Workspace::export`] writes every collection of a workspace, record by
+//! record, into a single backend-agnostic bundle; [`Workspace::import`]
+//! replays a bundle into any [`Remote`]. Together they allow migrating a
+//! workspace from one backend to another.
+//!
+//! A bundle starts with a `minimap-bundle <version>` line, followed by:
+//!
+//! - `collection <name>` lines, starting a collection;
+//! - `record <id> <kind> <timestamp> <author-len> <email-len> <message-len>`
+//!   lines, followed by the raw author, email and message (and a newline),
+//!   appending a record to the current collection. `kind` is either
+//!   `plain`, `add` or `del` (for set operations);
+//! - `upsert <name-len> <data-len>` and `remove <name-len>` lines, followed
+//!   by the raw attachment name (and data), changing an attachment on the
+//!   preceding record.
+//!
+//! Records are listed oldest first, and only carry the attachments that
+//! changed since the previous record in their collection.
+
+use crate::{
+	is_collection_under, Error, Record, RecordBuilder, Remote, Result, SetOperation, Workspace,
+};
+use std::{
+	collections::{HashMap, HashSet},
+	io::{Read, Write},
+};
+
+const BUNDLE_HEADER: &str = "minimap-bundle";
+const BUNDLE_VERSION: u32 = 1;
+
+/// The outcome of a [`Workspace::import`].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ImportReport {
+	/// The number of collections that were imported.
+	pub collections: usize,
+	/// The number of records that were imported.
+	pub records: usize,
+	/// The project and ticket slugs from the bundle that already existed
+	/// in the workspace. Their data was skipped.
+	pub collisions: Vec<String>,
+}
+
+/// A record of a collection, as stored in a bundle.
+pub(crate) struct BundleRecord {
+	id: String,
+	op: Option<SetOperation>,
+	timestamp: i64,
+	author: String,
+	email: String,
+	message: String,
+	/// The attachments that changed since the previous record.
+	upserts: Vec<(String, Vec<u8>)>,
+	/// The attachments removed since the previous record.
+	removals: Vec<String>,
+}
+
+/// A collection, as stored in a bundle.
+pub(crate) struct BundleCollection {
+	pub(crate) name: String,
+	records: Vec<BundleRecord>,
+}
+
+impl<'a, R: Remote<'a>> Workspace<'a, R> {
+	/// Writes every collection of the workspace into a bundle (see the
+	/// [module documentation](self)), returning the number of records
+	/// written.
+	pub fn export<W: Write>(&'a self, mut writer: W) -> Result<usize> {
+		let mut collections = self.remote.collections("meta")?;
+		collections.extend(self.remote.collections("project")?);
+		collections.sort();
+
+		writeln!(writer, "{} {}", BUNDLE_HEADER, BUNDLE_VERSION)?;
+
+		let mut count = 0;
+		for collection in collections {
+			writeln!(writer, "collection {}", collection)?;
+
+			for record in self.bundle_collection(&collection)?.records {
+				let kind = match record.op {
+					None => "plain",
+					Some(SetOperation::Add) => "add",
+					Some(SetOperation::Del) => "del",
+				};
+				writeln!(
+					writer,
+					"record {} {} {} {} {} {}",
+					record.id,
+					kind,
+					record.timestamp,
+					record.author.len(),
+					record.email.len(),
+					record.message.len()
+				)?;
+				writer.write_all(record.author.as_bytes())?;
+				writer.write_all(record.email.as_bytes())?;
+				writer.write_all(record.message.as_bytes())?;
+				writeln!(writer)?;
+
+				for (name, data) in &record.upserts {
+					writeln!(writer, "upsert {} {}", name.len(), data.len())?;
+					writer.write_all(name.as_bytes())?;
+					writer.write_all(data)?;
+					writeln!(writer)?;
+				}
+				for name in &record.removals {
+					writeln!(writer, "remove {}", name.len())?;
+					writer.write_all(name.as_bytes())?;
+					writeln!(writer)?;
+				}
+
+				count += 1;
+			}
+		}
+
+		writer.flush()?;
+		Ok(count)
+	}
+
+	/// Replays a bundle (see the [module documentation](self)) into the
+	/// workspace. Timestamps and authorship are preserved if the remote
+	/// supports it (see [`RecordBuilder::authored`]).
+	///
+	/// Projects (and ticket slugs) that already exist in the workspace
+	/// are reported as collisions and skipped, along with all of their
+	/// data; everything else is appended to the workspace's collections.
+	/// Returns [`Error::Malformed`] if the bundle can't be parsed, in
+	/// which case nothing is imported.
+	pub fn import<Rd: Read>(&'a self, mut reader: Rd) -> Result<ImportReport> {
+		let mut data = Vec::new();
+		reader.read_to_end(&mut data)?;
+		let collections = parse_bundle(&data)?;
+
+		let mut report = ImportReport::default();
+
+		let mut projects = HashSet::new();
+		for collection in collections.iter().filter(|c| c.name == "meta/projects") {
+			projects.extend(collection.records.iter().map(|r| r.message.clone()));
+		}
+		let mut skipped_projects = HashSet::new();
+		for slug in projects {
+			let exists = !self
+				.remote
+				.collections(&format!("meta/project/{}", slug))?
+				.is_empty() || !self
+				.remote
+				.collections(&format!("project/{}", slug))?
+				.is_empty();
+			if exists {
+				skipped_projects.insert(slug);
+			}
+		}
+		report.collisions.extend(skipped_projects.iter().cloned());
+
+		let belongs_to_skipped = |collection: &str| {
+			skipped_projects.iter().any(|slug| {
+				is_collection_under(collection, &format!("meta/project/{}", slug))
+					|| is_collection_under(collection, &format!("project/{}", slug))
+			})
+		};
+		let points_to_skipped = |collection: &str, message: &str| match collection {
+			"meta/projects" => skipped_projects.contains(message),
+			_ if collection.starts_with("meta/ticket_slug/") => message
+				.split_once('/')
+				.map_or(false, |(project, _)| skipped_projects.contains(project)),
+			_ => false,
+		};
+
+		let (collections, records) = self.replay_collections(
+			&collections,
+			|collection| {
+				if belongs_to_skipped(&collection.name) {
+					return Ok(None);
+				}
+
+				if let Some(slug) = collection.name.strip_prefix("meta/ticket_slug/") {
+					if !points_to_skipped(&collection.name, &last_message(collection))
+						&& self.remote.latest(&collection.name)?.is_some()
+					{
+						report.collisions.push(slug.to_string());
+						return Ok(None);
+					}
+				}
+
+				Ok(Some(collection.name.clone()))
+			},
+			|collection, record| !points_to_skipped(collection, &record.message),
+		)?;
+		report.collections = collections;
+		report.records = records;
+
+		report.collisions.sort();
+		Ok(report)
+	}
+
+	/// Replays collections (e.g. a bundle's) into the workspace, as
+	/// [`Workspace::import`] and [`Ticket::move_to`](crate::Ticket::move_to)
+	/// do. `target` gives the name of the collection to replay each one
+	/// into, or `None` to skip it, and records that `keep` rejects (given
+	/// the collection's original name) are skipped. Returns the number of
+	/// collections and records that were replayed.
+	///
+	/// Some collections refer to records by ID, in their names (e.g. a
+	/// comment's attachments) or in their records (e.g. tombstones, or the
+	/// trash's purge list). They're replayed last, so that the IDs can be
+	/// translated to those of the replayed records.
+	pub(crate) fn replay_collections(
+		&'a self,
+		collections: &[BundleCollection],
+		mut target: impl FnMut(&BundleCollection) -> Result<Option<String>>,
+		keep: impl Fn(&str, &BundleRecord) -> bool,
+	) -> Result<(usize, usize)> {
+		let old_ids = collections
+			.iter()
+			.flat_map(|c| c.records.iter().map(|r| r.id.as_str()))
+			.collect::<HashSet<_>>();
+		let (referencing, plain): (Vec<_>, Vec<_>) = collections.iter().partition(|c| {
+			c.name.split('/').any(|segment| old_ids.contains(segment))
+				|| c.records
+					.iter()
+					.any(|r| old_ids.contains(r.message.as_str()))
+		});
+
+		let (mut replayed_collections, mut replayed_records) = (0, 0);
+		let mut ids = HashMap::new();
+		for collection in plain.into_iter().chain(referencing) {
+			let Some(name) = target(collection)? else {
+				continue;
+			};
+			let name = name
+				.split('/')
+				.map(|segment| ids.get(segment).map_or(segment, String::as_str))
+				.collect::<Vec<_>>()
+				.join("/");
+
+			let mut replayed = false;
+			for record in &collection.records {
+				if !keep(&collection.name, record) {
+					continue;
+				}
+
+				let message = ids.get(&record.message).unwrap_or(&record.message);
+
+				let mut builder = match record.op {
+					Some(op) => self.remote.set_record_builder(&name, op),
+					None => self.remote.record_builder(&name),
+				}
+				.authored(&record.author, &record.email, record.timestamp);
+				for (name, data) in &record.upserts {
+					builder = builder.upsert_attachment(name, data)?;
+				}
+				for name in &record.removals {
+					builder = builder.remove_attachment(name)?;
+				}
+				let commit = builder.commit(message)?;
+
+				ids.insert(record.id.clone(), commit.id());
+				replayed_records += 1;
+				replayed = true;
+			}
+
+			if replayed {
+				replayed_collections += 1;
+			}
+		}
+
+		Ok((replayed_collections, replayed_records))
+	}
+
+	/// Reads a collection for a bundle, oldest record first, along with
+	/// the changes each record made to its attachments.
+	pub(crate) fn bundle_collection(&'a self, collection: &str) -> Result<BundleCollection> {
+		let mut records = Vec::new();
+		let mut previous = HashMap::<String, Vec<u8>>::new();
+		for (record, op) in self.bundle_records(collection)? {
+			let mut upserts = Vec::new();
+			let mut current = HashMap::new();
+			let mut names = record.attachment_names()?;
+			names.sort();
+			for name in names {
+				let data = record.attachment(&name)?.unwrap_or_default();
+				if previous.get(&name) != Some(&data) {
+					upserts.push((name.clone(), data.clone()));
+				}
+				current.insert(name, data);
+			}
+
+			let mut removals = previous
+				.keys()
+				.filter(|name| !current.contains_key(*name))
+				.cloned()
+				.collect::<Vec<_>>();
+			removals.sort();
+			previous = current;
+
+			records.push(BundleRecord {
+				id: record.id(),
+				op,
+				timestamp: record.timestamp(),
+				author: record.author(),
+				email: record.email(),
+				message: record.message(),
+				upserts,
+				removals,
+			});
+		}
+
+		Ok(BundleCollection {
+			name: collection.to_string(),
+			records,
+		})
+	}
+
+	/// Lists the records of a collection for a bundle, oldest first,
+	/// along with their set operation if the collection is a set.
+	fn bundle_records(
+		&'a self,
+		collection: &str,
+	) -> Result<Vec<(R::Record, Option<SetOperation>)>> {
+		// Whether a collection is a set isn't stored anywhere; walking a
+		// collection that isn't as a set fails on its first record.
+		let mut records = match self
+			.remote
+			.walk_set(collection)?
+			.collect::<Result<Vec<_>>>()
+		{
+			Ok(records) => records
+				.into_iter()
+				.map(|(record, op)| (record, Some(op)))
+				.collect::<Vec<_>>(),
+			Err(Error::Malformed(_)) => self
+				.remote
+				.walk(collection)?
+				.map(|record| Ok((record?, None)))
+				.collect::<Result<Vec<_>>>()?,
+			Err(err) => return Err(err),
+		};
+
+		records.reverse();
+		Ok(records)
+	}
+}
+
+fn last_message(collection: &BundleCollection) -> String {
+	collection
+		.records
+		.last()
+		.map(|record| record.message.clone())
+		.unwrap_or_default()
+}
+
+/// A cursor over the raw bundle data.
+struct BundleReader<'d> {
+	data: &'d [u8],
+	pos: usize,
+}
+
+impl<'d> BundleReader<'d> {
+	fn malformed(&self) -> Error {
+		Error::Malformed(format!("bundle (at byte {})", self.pos))
+	}
+
+	fn is_empty(&self) -> bool {
+		self.pos >= self.data.len()
+	}
+
+	fn line(&mut self) -> Result<&'d str> {
+		let rest = &self.data[self.pos..];
+		let end = rest
+			.iter()
+			.position(|&b| b == b'\n')
+			.ok_or_else(|| self.malformed())?;
+		let line = std::str::from_utf8(&rest[..end]).map_err(|_| self.malformed())?;
+		self.pos += end + 1;
+		Ok(line)
+	}
+
+	fn bytes(&mut self, len: usize) -> Result<&'d [u8]> {
+		let bytes = self
+			.data
+			.get(self.pos..self.pos + len)
+			.ok_or_else(|| self.malformed())?;
+		self.pos += len;
+		Ok(bytes)
+	}
+
+	fn string(&mut self, len: usize) -> Result<String> {
+		let bytes = self.bytes(len)?;
+		String::from_utf8(bytes.to_vec()).map_err(|_| self.malformed())
+	}
+
+	fn newline(&mut self) -> Result<()> {
+		match self.bytes(1)? {
+			b"\n" => Ok(()),
+			_ => Err(self.malformed()),
+		}
+	}
+}
+
+fn parse_bundle(data: &[u8]) -> Result<Vec<BundleCollection>> {
+	let mut reader = BundleReader { data, pos: 0 };
+
+	if reader.line()? != format!("{} {}", BUNDLE_HEADER, BUNDLE_VERSION) {
+		return Err(Error::Malformed("bundle (unsupported header)".to_string()));
+	}
+
+	let mut collections = Vec::<BundleCollection>::new();
+	while !reader.is_empty() {
+		let line = reader.line()?;
+		let mut parts = line.split(' ');
+		let keyword = parts.next().unwrap_or_default();
+		let mut numbers = parts.clone().map(|part| part.parse::<usize>());
+
+		match keyword {
+			"collection" => collections.push(BundleCollection {
+				name: parts.next().ok_or_else(|| reader.malformed())?.to_string(),
+				records: Vec::new(),
+			}),
+			"record" => {
+				let collection = collections.last_mut().ok_or_else(|| reader.malformed())?;
+				let fields = parts.collect::<Vec<_>>();
+				let [id, kind, timestamp, author_len, email_len, message_len] = fields[..] else {
+					return Err(reader.malformed());
+				};
+
+				let op = match kind {
+					"plain" => None,
+					"add" => Some(SetOperation::Add),
+					"del" => Some(SetOperation::Del),
+					_ => return Err(reader.malformed()),
+				};
+				let timestamp = timestamp.parse().map_err(|_| reader.malformed())?;
+				let lens = [author_len, email_len, message_len]
+					.iter()
+					.map(|len| len.parse::<usize>().map_err(|_| reader.malformed()))
+					.collect::<Result<Vec<_>>>()?;
+
+				let record = BundleRecord {
+					id: id.to_string(),
+					op,
+					timestamp,
+					author: reader.string(lens[0])?,
+					email: reader.string(lens[1])?,
+					message: reader.string(lens[2])?,
+					upserts: Vec::new(),
+					removals: Vec::new(),
+				};
+				reader.newline()?;
+				collection.records.push(record);
+			}
+			"upsert" | "remove" => {
+				let record = collections
+					.last_mut()
+					.and_then(|collection| collection.records.last_mut())
+					.ok_or_else(|| reader.malformed())?;
+				let name_len = numbers
+					.next()
+					.and_then(|len| len.ok())
+					.ok_or_else(|| reader.malformed())?;
+
+				if keyword == "upsert" {
+					let data_len = numbers
+						.next()
+						.and_then(|len| len.ok())
+						.ok_or_else(|| reader.malformed())?;
+					let name = reader.string(name_len)?;
+					let data = reader.bytes(data_len)?.to_vec();
+					record.upserts.push((name, data));
+				} else {
+					record.removals.push(reader.string(name_len)?);
+				}
+				reader.newline()?;
+			}
+			_ => return Err(reader.malformed()),
+		}
+	}
+
+	Ok(collections)
+}
@@ -0,0 +1,35 @@
+//! Time sources.
+//!
+//! Anything that depends on the current time takes a [`Clock`], so that
+//! it can be tested (and simulated) deterministically.
+
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// A source of the current time.
+pub trait Clock: Send + Sync {
+	/// Gets the current time, in seconds since the Unix epoch.
+	fn now(&self) -> i64;
+}
+
+/// The system's wall clock.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+	fn now(&self) -> i64 {
+		SystemTime::now()
+			.duration_since(UNIX_EPOCH)
+			.map(|duration| duration.as_secs() as i64)
+			.unwrap_or_default()
+	}
+}
+
+/// A clock that's stopped at a given time, in seconds since the Unix epoch.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FixedClock(pub i64);
+
+impl Clock for FixedClock {
+	fn now(&self) -> i64 {
+		self.0
+	}
+}
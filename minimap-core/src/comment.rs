@@ -0,0 +1,216 @@
+//! Comment deletion via tombstones.
+//!
+//! Comment records can't be removed from a ticket's comment log without
+//! rewriting history, so deleting a comment instead adds its record ID to
+//! the ticket's `comment_tombstones` set. [`Ticket::comments`] and
+//! [`Ticket::comments_view`] skip tombstoned comments, while
+//! [`Ticket::comments_audit`] still yields the full log (along with each
+//! comment's tombstone, if any) for auditing.
+//!
+//! Comments may also carry files (see
+//! [`Ticket::add_comment_with_attachments`]), which are stored in the
+//! comment's own record. The names of each comment's files are kept in
+//! the ticket's `comment_attachments/<comment id>` collection.
+
+use crate::{AuditOperation, Error, Record, RecordBuilder, Remote, Result, Ticket};
+use std::collections::HashMap;
+
+/// A comment on a ticket, along with its files.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Comment<T: Record> {
+	/// The comment's record.
+	pub record: T,
+	/// The tombstone record, if the comment has been deleted
+	/// (only ever set by [`Ticket::comments_audit`]).
+	pub deleted: Option<T>,
+	/// The names of the files attached to the comment.
+	/// The data can be fetched with [`Record::attachment`].
+	pub attachments: Vec<String>,
+}
+
+impl<T: Record> Comment<T> {
+	/// Returns if the comment has been deleted.
+	#[inline]
+	pub fn is_deleted(&self) -> bool {
+		self.deleted.is_some()
+	}
+}
+
+impl<'a, R: Remote<'a>> Ticket<'a, R> {
+	/// Creates a new comment on the ticket, attaching the given files
+	/// to the comment's record.
+	///
+	/// Attachment names must be unique, and cannot be empty or
+	/// contain newlines. Returns [`Error::Malformed`] otherwise.
+	/// Returns [`Error::TooLarge`] if any attachment exceeds the
+	/// workspace's size limit.
+	pub fn add_comment_with_attachments(
+		&self,
+		comment: &str,
+		attachments: &[(&str, &[u8])],
+	) -> Result<R::Record> {
+		for (i, (name, _)) in attachments.iter().enumerate() {
+			if name.is_empty()
+				|| name.contains('\n')
+				|| attachments[..i].iter().any(|(other, _)| other == name)
+			{
+				return Err(Error::Malformed(name.to_string()));
+			}
+		}
+		for (name, data) in attachments {
+			self.workspace.check_attachment_size(name, data)?;
+		}
+
+		let mut builder = self
+			.workspace
+			.remote
+			.record_builder(&format!("{}/comment", self.path));
+		for (name, data) in attachments {
+			builder = builder.upsert_attachment(name, data)?;
+		}
+		let record = builder.commit(comment)?;
+
+		if !attachments.is_empty() {
+			let names = attachments
+				.iter()
+				.map(|(name, _)| *name)
+				.collect::<Vec<_>>()
+				.join("\n");
+			self.workspace
+				.remote
+				.record_builder(&self.comment_attachments_path(&record.id()))
+				.commit(&names)?;
+		}
+
+		Ok(record)
+	}
+
+	/// Gets the names of the files attached to a comment, in the order
+	/// they were attached. The data can be fetched from the comment
+	/// record with [`Record::attachment`].
+	pub fn comment_attachments(&self, comment: &R::Record) -> Result<Vec<String>> {
+		Ok(self
+			.workspace
+			.remote
+			.latest(&self.comment_attachments_path(&comment.id()))?
+			.map(|record| record.message().lines().map(str::to_string).collect())
+			.unwrap_or_default())
+	}
+
+	/// **Soft-deletes** a comment on the ticket, given its record ID.
+	/// The comment is hidden from [`Ticket::comments`], but remains in
+	/// the ticket's history. The deletion is recorded in the audit log.
+	///
+	/// Returns `Ok(record)` with the newly created tombstone record,
+	/// `Err(Some(record))` with the existing tombstone if the comment was
+	/// already deleted, or `Err(None)` if the comment never existed.
+	pub fn delete_comment(
+		&self,
+		comment_id: &str,
+	) -> Result<std::result::Result<R::Record, Option<R::Record>>> {
+		let mut found = false;
+		for record in self.comments_log()? {
+			if record?.id() == comment_id {
+				found = true;
+				break;
+			}
+		}
+
+		if !found {
+			return Ok(Err(None));
+		}
+
+		match self
+			.workspace
+			.remote
+			.set_add(&self.tombstones_path(), comment_id)?
+		{
+			Ok((tombstone, _)) => {
+				self.workspace.audit(
+					AuditOperation::Redact,
+					&format!("{}#{}", self.slug, comment_id),
+					None,
+				)?;
+				Ok(Ok(tombstone))
+			}
+			Err(tombstone) => Ok(Err(Some(tombstone))),
+		}
+	}
+
+	/// Gets the comments on the ticket along with their files, in reverse
+	/// order from latest to oldest. Deleted comments are skipped; see
+	/// [`Ticket::comments_audit`] to include them.
+	pub fn comments_view(&self) -> Result<Vec<Comment<R::Record>>> {
+		Ok(self
+			.comments_audit()?
+			.into_iter()
+			.filter(|comment| !comment.is_deleted())
+			.collect())
+	}
+
+	/// Gets all comments on the ticket, **including** deleted ones,
+	/// in reverse order from latest to oldest. Each comment carries
+	/// its tombstone record if it has been deleted.
+	///
+	/// Meant for auditing; deleted comments may hold data (e.g. leaked
+	/// secrets) that shouldn't be shown otherwise.
+	pub fn comments_audit(&self) -> Result<Vec<Comment<R::Record>>> {
+		let mut tombstones = self.tombstones()?;
+
+		self.comments_log()?
+			.map(|record| {
+				let record = record?;
+				Ok(Comment {
+					deleted: tombstones.remove(&record.id()),
+					attachments: self.comment_attachments(&record)?,
+					record,
+				})
+			})
+			.collect()
+	}
+
+	/// Walks the raw comment log, without skipping deleted comments.
+	pub(crate) fn comments_log(&self) -> Result<R::Iterator> {
+		self.workspace
+			.remote
+			.walk(&format!("{}/comment", self.path))
+	}
+
+	/// Gets the tombstone records of all deleted comments, by comment ID.
+	pub(crate) fn tombstones(&self) -> Result<HashMap<String, R::Record>> {
+		Ok(self
+			.workspace
+			.remote
+			.set_get_all(&self.tombstones_path())?
+			.into_iter()
+			.map(|record| (record.message(), record))
+			.collect())
+	}
+
+	fn tombstones_path(&self) -> String {
+		format!("{}/comment_tombstones", self.path)
+	}
+
+	fn comment_attachments_path(&self, comment_id: &str) -> String {
+		format!("{}/comment_attachments/{}", self.path, comment_id)
+	}
+}
+
+/// An iterator over a ticket's comments that skips deleted comments.
+pub struct CommentIterator<'a, R: Remote<'a>> {
+	pub(crate) inner: R::Iterator,
+	pub(crate) tombstones: HashMap<String, R::Record>,
+}
+
+impl<'a, R: Remote<'a>> Iterator for CommentIterator<'a, R> {
+	type Item = Result<R::Record>;
+
+	fn next(&mut self) -> Option<Self::Item> {
+		loop {
+			match self.inner.next()? {
+				Ok(record) if self.tombstones.contains_key(&record.id()) => continue,
+				result => return Some(result),
+			}
+		}
+	}
+}
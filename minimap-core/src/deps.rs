@@ -21,12 +21,39 @@
 //! within which the ticket resides (and thus the `_` origin's endpoints
 //! are ticket slugs, i.e. `project-123`).
 
-use crate::{DependencyResolver, DependencyStatus, Error, Result};
-use std::collections::HashMap;
+use crate::{
+	Clock, Dependency, DependencyResolver, DependencyStatus, Error, Remote, Result, SystemClock,
+	Workspace,
+};
+use indexmap::IndexMap;
+use std::{cell::RefCell, collections::HashMap, time::Duration};
 
+pub(crate) mod asynchronous;
+#[cfg(feature = "crates-io")]
+pub(crate) mod crates_io;
+pub(crate) mod date;
+#[cfg(test)]
+pub(crate) mod fixtures;
+#[cfg(feature = "http")]
+pub(crate) mod http;
 pub(crate) mod minimap;
+pub(crate) mod monitor;
+// Loading dynamic libraries is inherently unsafe; this is the only
+// module allowed to use `unsafe`, and only with the opt-in feature.
+#[cfg(feature = "plugins")]
+#[allow(unsafe_code)]
+pub(crate) mod plugin;
 
+pub use self::asynchronous::*;
+#[cfg(feature = "crates-io")]
+pub use self::crates_io::*;
+pub use self::date::*;
+#[cfg(feature = "http")]
+pub use self::http::*;
 pub use self::minimap::*;
+pub use self::monitor::*;
+#[cfg(feature = "plugins")]
+pub use self::plugin::*;
 
 /// Dependency origins are sources from which dependency statuses
 /// can be queried. The "handle" to a dependency is referred to as
@@ -45,10 +72,31 @@ pub trait DependencyOrigin {
 	) -> std::result::Result<DependencyStatus, Box<dyn std::error::Error>>;
 }
 
+impl<O: DependencyOrigin + ?Sized> DependencyOrigin for Box<O> {
+	fn slug(&self) -> &str {
+		(**self).slug()
+	}
+
+	fn status(
+		&self,
+		endpoint: &str,
+	) -> std::result::Result<DependencyStatus, Box<dyn std::error::Error>> {
+		(**self).status(endpoint)
+	}
+}
+
 /// A registry of dependency origins that can be queried for
 /// dependency statuses.
+///
+/// Statuses can be cached per origin and endpoint for a while (see
+/// [`DependencyRegistry::with_cache_ttl`]), so that e.g. rendering a
+/// list of tickets doesn't query the same origins over and over.
+/// Errors are never cached.
 pub struct DependencyRegistry {
 	origins: HashMap<String, Box<dyn DependencyOrigin>>,
+	ttl: Duration,
+	clock: Box<dyn Clock>,
+	cache: RefCell<HashMap<(String, String), (i64, DependencyStatus)>>,
 }
 
 fn validate_origin_slug(slug: &str) -> Result<()> {
@@ -71,18 +119,61 @@ impl DependencyRegistry {
 	pub fn new() -> Self {
 		let mut origins = HashMap::<String, Box<dyn DependencyOrigin>>::new();
 
-		origins.insert("minimap".to_string(), Box::new(MinimapDependencyOrigin));
+		origins.insert(
+			"minimap".to_string(),
+			Box::new(MinimapDependencyOrigin::new()),
+		);
+
+		Self {
+			origins,
+			ttl: Duration::ZERO,
+			clock: Box::new(SystemClock),
+			cache: RefCell::default(),
+		}
+	}
+
+	/// Sets how long resolved statuses are cached for. By default,
+	/// the TTL is zero, i.e. statuses aren't cached. The TTL is
+	/// truncated to whole seconds.
+	pub fn with_cache_ttl(mut self, ttl: Duration) -> Self {
+		self.ttl = ttl;
+		self
+	}
 
-		Self { origins }
+	/// Sets the clock used to expire cached statuses.
+	pub fn with_clock<C: Clock + 'static>(mut self, clock: C) -> Self {
+		self.clock = Box::new(clock);
+		self
 	}
 
-	/// Register an origin with this registry.
+	/// Register an origin with this registry. Replacing an origin
+	/// invalidates the statuses cached for it.
 	pub fn register(&mut self, origin: Box<dyn DependencyOrigin>) -> Result<()> {
 		validate_origin_slug(origin.slug())?;
 
+		self.invalidate_origin(origin.slug());
 		self.origins.insert(origin.slug().to_string(), origin);
 		Ok(())
 	}
+
+	/// Removes the cached status of an endpoint, if any.
+	pub fn invalidate(&self, slug: &str, endpoint: &str) {
+		self.cache
+			.borrow_mut()
+			.remove(&(slug.to_string(), endpoint.to_string()));
+	}
+
+	/// Removes the cached statuses of all of an origin's endpoints.
+	pub fn invalidate_origin(&self, slug: &str) {
+		self.cache
+			.borrow_mut()
+			.retain(|(origin, _), _| origin != slug);
+	}
+
+	/// Removes all cached statuses.
+	pub fn clear_cache(&self) {
+		self.cache.borrow_mut().clear();
+	}
 }
 
 impl DependencyResolver for DependencyRegistry {
@@ -91,9 +182,164 @@ impl DependencyResolver for DependencyRegistry {
 			return Err(Error::MalformedOrigin(slug.to_string()));
 		}
 
-		match self.origins.get(slug) {
-			Some(origin) => origin.status(endpoint).map_err(Error::Origin),
-			None => Err(Error::UnknownOrigin(slug.to_string())),
+		let origin = self
+			.origins
+			.get(slug)
+			.ok_or_else(|| Error::UnknownOrigin(slug.to_string()))?;
+
+		if self.ttl.is_zero() {
+			return origin.status(endpoint).map_err(Error::Origin);
+		}
+
+		let key = (slug.to_string(), endpoint.to_string());
+		let now = self.clock.now();
+		if let Some((expires, status)) = self.cache.borrow().get(&key) {
+			if now < *expires {
+				return Ok(*status);
+			}
 		}
+
+		let status = origin.status(endpoint).map_err(Error::Origin)?;
+		let ttl = i64::try_from(self.ttl.as_secs()).unwrap_or(i64::MAX);
+		self.cache
+			.borrow_mut()
+			.insert(key, (now.saturating_add(ttl), status));
+		Ok(status)
+	}
+}
+
+impl<'a, R: Remote<'a>> Workspace<'a, R> {
+	/// Resolves the dependencies of every ticket in the workspace,
+	/// resolving each distinct origin and endpoint only once, no matter
+	/// how many tickets depend on it.
+	///
+	/// Returns the status (or the error encountered while resolving it)
+	/// of every distinct dependency, keyed by `(origin, endpoint)`, in the
+	/// order they were first found. Dependencies on other tickets in the
+	/// workspace (`_`) are resolved directly, and all others with the
+	/// given resolver.
+	pub fn resolve_all_dependencies<D: DependencyResolver + ?Sized>(
+		&'a self,
+		resolver: &D,
+	) -> Result<IndexMap<(String, String), Result<DependencyStatus>>> {
+		let mut statuses = IndexMap::new();
+		for ticket in self.tickets()? {
+			for Dependency {
+				origin, endpoint, ..
+			} in ticket.dependencies()?
+			{
+				let key = (origin, endpoint);
+				if statuses.contains_key(&key) {
+					continue;
+				}
+
+				let (origin, endpoint) = &key;
+				let status = if origin == "_" {
+					self.ticket(endpoint)
+						.and_then(|ticket| ticket.state())
+						.map(|(state, _)| state.into())
+				} else {
+					resolver.status(origin, endpoint)
+				};
+				statuses.insert(key, status);
+			}
+		}
+
+		Ok(statuses)
+	}
+}
+
+#[cfg(test)]
+mod test {
+	use super::*;
+	use crate::deps::fixtures::CountingOrigin;
+
+	#[test]
+	fn test_dependency_cache() {
+		use std::{
+			cell::Cell,
+			rc::Rc,
+			sync::{
+				atomic::{AtomicI64, Ordering},
+				Arc,
+			},
+		};
+
+		struct TestClock(Arc<AtomicI64>);
+
+		impl Clock for TestClock {
+			fn now(&self) -> i64 {
+				self.0.load(Ordering::Relaxed)
+			}
+		}
+
+		let calls = Rc::new(Cell::new(0));
+		let now = Arc::new(AtomicI64::new(1000));
+
+		// Without a TTL, every query hits the origin.
+		let mut registry = DependencyRegistry::new();
+		registry
+			.register(Box::new(CountingOrigin(calls.clone())))
+			.unwrap();
+		registry.status("counting", "done").unwrap();
+		registry.status("counting", "done").unwrap();
+		assert_eq!(calls.get(), 2);
+
+		calls.set(0);
+		let mut registry = DependencyRegistry::new()
+			.with_cache_ttl(std::time::Duration::from_secs(60))
+			.with_clock(TestClock(now.clone()));
+		registry
+			.register(Box::new(CountingOrigin(calls.clone())))
+			.unwrap();
+
+		assert_eq!(
+			registry.status("counting", "done").unwrap(),
+			DependencyStatus::Complete
+		);
+		assert_eq!(
+			registry.status("counting", "done").unwrap(),
+			DependencyStatus::Complete
+		);
+		assert_eq!(
+			registry.status("counting", "todo").unwrap(),
+			DependencyStatus::Pending
+		);
+		assert_eq!(calls.get(), 2);
+
+		// Errors aren't cached.
+		assert!(registry.status("counting", "broken").is_err());
+		assert!(registry.status("counting", "broken").is_err());
+		assert_eq!(calls.get(), 4);
+
+		// Entries expire after the TTL.
+		now.store(1059, Ordering::Relaxed);
+		registry.status("counting", "done").unwrap();
+		assert_eq!(calls.get(), 4);
+		now.store(1060, Ordering::Relaxed);
+		registry.status("counting", "done").unwrap();
+		registry.status("counting", "todo").unwrap();
+		assert_eq!(calls.get(), 6);
+
+		registry.invalidate("counting", "done");
+		registry.status("counting", "done").unwrap();
+		registry.status("counting", "todo").unwrap();
+		assert_eq!(calls.get(), 7);
+
+		registry.invalidate_origin("counting");
+		registry.status("counting", "done").unwrap();
+		registry.status("counting", "todo").unwrap();
+		assert_eq!(calls.get(), 9);
+
+		registry.clear_cache();
+		registry.status("counting", "done").unwrap();
+		assert_eq!(calls.get(), 10);
+
+		// Re-registering an origin drops its cached statuses.
+		registry
+			.register(Box::new(CountingOrigin(calls.clone())))
+			.unwrap();
+		registry.status("counting", "done").unwrap();
+		assert_eq!(calls.get(), 11);
 	}
 }
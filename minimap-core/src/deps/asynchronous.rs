@@ -0,0 +1,216 @@
+//! Asynchronous dependency resolution.
+//!
+//! [`AsyncDependencyOrigin`] and [`AsyncDependencyResolver`] mirror their
+//! synchronous counterparts, so that origins backed by network services
+//! can be implemented on top of an async runtime without blocking it.
+//! Minimap doesn't depend on any particular runtime; methods return
+//! boxed futures so that the traits can be used as trait objects. The
+//! futures are [`Send`], so that they can be spawned on multi-threaded
+//! runtimes, which is why origins and resolvers must be thread-safe.
+//!
+//! Synchronous origins can be used where async ones are expected by
+//! wrapping them in a [`SyncOrigin`], and every thread-safe
+//! [`DependencyResolver`] is also an [`AsyncDependencyResolver`].
+
+use crate::{
+	deps::validate_origin_slug, Dependency, DependencyOrigin, DependencyResolver, DependencyStatus,
+	Error, MinimapDependencyOrigin, Remote, Result, Ticket,
+};
+use std::{
+	collections::HashMap,
+	future::Future,
+	pin::Pin,
+	task::{Context, Poll},
+};
+
+/// A boxed future, as returned by the async dependency traits.
+pub type BoxFuture<'a, T> = Pin<Box<dyn Future<Output = T> + Send + 'a>>;
+
+/// The asynchronous counterpart to [`DependencyOrigin`].
+pub trait AsyncDependencyOrigin: Send + Sync {
+	/// The unique identifier ("slug") of the origin.
+	///
+	/// Origin slugs cannot be `_` and cannot contain the `@` character.
+	fn slug(&self) -> &str;
+
+	/// Query the origin for the status of the given endpoint.
+	fn status_async<'a>(
+		&'a self,
+		endpoint: &'a str,
+	) -> BoxFuture<'a, std::result::Result<DependencyStatus, Box<dyn std::error::Error>>>;
+}
+
+/// The asynchronous counterpart to [`DependencyResolver`].
+///
+/// A general purpose registry is implemented in [`AsyncDependencyRegistry`].
+pub trait AsyncDependencyResolver: Send + Sync {
+	/// Resolves the status of a dependency given its origin and endpoint.
+	fn status_async<'a>(
+		&'a self,
+		slug: &'a str,
+		endpoint: &'a str,
+	) -> BoxFuture<'a, Result<DependencyStatus>>;
+}
+
+impl<D: DependencyResolver + Send + Sync> AsyncDependencyResolver for D {
+	fn status_async<'a>(
+		&'a self,
+		slug: &'a str,
+		endpoint: &'a str,
+	) -> BoxFuture<'a, Result<DependencyStatus>> {
+		Box::pin(async move { DependencyResolver::status(self, slug, endpoint) })
+	}
+}
+
+/// Adapts a synchronous [`DependencyOrigin`] into an
+/// [`AsyncDependencyOrigin`]. The origin is queried in place when the
+/// future is polled, so slow origins will block the async runtime.
+pub struct SyncOrigin<O>(pub O);
+
+impl<O: DependencyOrigin + Send + Sync> AsyncDependencyOrigin for SyncOrigin<O> {
+	fn slug(&self) -> &str {
+		self.0.slug()
+	}
+
+	fn status_async<'a>(
+		&'a self,
+		endpoint: &'a str,
+	) -> BoxFuture<'a, std::result::Result<DependencyStatus, Box<dyn std::error::Error>>> {
+		Box::pin(async move { self.0.status(endpoint) })
+	}
+}
+
+/// A registry of asynchronous dependency origins; the asynchronous
+/// counterpart to [`DependencyRegistry`](crate::DependencyRegistry).
+pub struct AsyncDependencyRegistry {
+	origins: HashMap<String, Box<dyn AsyncDependencyOrigin>>,
+}
+
+impl Default for AsyncDependencyRegistry {
+	fn default() -> Self {
+		Self::new()
+	}
+}
+
+impl AsyncDependencyRegistry {
+	/// Create a new registry. By default, the `minimap` origin is
+	/// registered, which is the origin for Minimap workspaces.
+	pub fn new() -> Self {
+		let mut origins = HashMap::<String, Box<dyn AsyncDependencyOrigin>>::new();
+
+		origins.insert(
+			"minimap".to_string(),
+			Box::new(SyncOrigin(MinimapDependencyOrigin::new())),
+		);
+
+		Self { origins }
+	}
+
+	/// Register an asynchronous origin with this registry.
+	pub fn register(&mut self, origin: Box<dyn AsyncDependencyOrigin>) -> Result<()> {
+		validate_origin_slug(origin.slug())?;
+
+		self.origins.insert(origin.slug().to_string(), origin);
+		Ok(())
+	}
+
+	/// Register a synchronous origin with this registry
+	/// (see [`SyncOrigin`]).
+	pub fn register_sync(&mut self, origin: Box<dyn DependencyOrigin + Send + Sync>) -> Result<()> {
+		self.register(Box::new(SyncOrigin(origin)))
+	}
+}
+
+impl AsyncDependencyResolver for AsyncDependencyRegistry {
+	fn status_async<'a>(
+		&'a self,
+		slug: &'a str,
+		endpoint: &'a str,
+	) -> BoxFuture<'a, Result<DependencyStatus>> {
+		Box::pin(async move {
+			if slug == "_" {
+				return Err(Error::MalformedOrigin(slug.to_string()));
+			}
+
+			match self.origins.get(slug) {
+				Some(origin) => origin.status_async(endpoint).await.map_err(Error::Origin),
+				None => Err(Error::UnknownOrigin(slug.to_string())),
+			}
+		})
+	}
+}
+
+impl<'a, R: Remote<'a>> Ticket<'a, R> {
+	/// Resolves the status of each of the ticket's dependencies using an
+	/// asynchronous resolver, all at once, returning them in the same order
+	/// as [`Ticket::resolve_dependencies`]. Returns the first error in that
+	/// order, if any.
+	pub async fn resolve_dependencies_async<D: AsyncDependencyResolver + ?Sized>(
+		&self,
+		resolver: &D,
+	) -> Result<Vec<(String, String, DependencyStatus)>> {
+		let dependencies = self.dependencies()?;
+
+		// `_` dependencies are resolved right away; the others are left
+		// to the resolver, and filled in once they've all resolved.
+		let mut local = Vec::with_capacity(dependencies.len());
+		let mut remote = Vec::new();
+		for Dependency {
+			origin, endpoint, ..
+		} in &dependencies
+		{
+			if origin == "_" {
+				local.push(Some(self.workspace.ticket(endpoint)?.state()?.0.into()));
+			} else {
+				local.push(None);
+				remote.push(resolver.status_async(origin, endpoint));
+			}
+		}
+		let mut remote = join_all(remote).await.into_iter();
+
+		dependencies
+			.into_iter()
+			.zip(local)
+			.map(|(dependency, status)| {
+				let status = match status {
+					Some(status) => status,
+					None => remote.next().unwrap()?,
+				};
+				Ok((dependency.origin, dependency.endpoint, status))
+			})
+			.collect()
+	}
+}
+
+/// Polls all of the futures concurrently, resolving to their outputs
+/// in the same order.
+async fn join_all<T>(futures: Vec<BoxFuture<'_, T>>) -> Vec<T> {
+	let mut futures = futures
+		.into_iter()
+		.map(|future| (future, None))
+		.collect::<Vec<_>>();
+
+	std::future::poll_fn(move |context: &mut Context<'_>| {
+		let mut done = true;
+		for (future, output) in &mut futures {
+			if output.is_none() {
+				match future.as_mut().poll(context) {
+					Poll::Ready(value) => *output = Some(value),
+					Poll::Pending => done = false,
+				}
+			}
+		}
+
+		if done {
+			Poll::Ready(
+				futures
+					.iter_mut()
+					.map(|(_, output)| output.take().unwrap())
+					.collect(),
+			)
+		} else {
+			Poll::Pending
+		}
+	})
+	.await
+}
@@ -0,0 +1,140 @@
+use crate::{DependencyOrigin, DependencyStatus, Error, HttpClient};
+use semver::{Version, VersionReq};
+
+const CRATES_IO_API: &str = "https://crates.io/api/v1/crates";
+
+/// A dependency origin that waits for crate releases. Endpoints are
+/// `crate@requirement` (e.g. `serde@>=1.0.200`), where the requirement
+/// uses Cargo's syntax. The dependency is complete once a non-yanked
+/// version matching the requirement has been published.
+///
+/// crates.io is only served over HTTPS, so the client must support TLS
+/// (which [`PlainHttpClient`](crate::PlainHttpClient) doesn't), unless
+/// the API URL points to a plain HTTP mirror.
+pub struct CratesIoDependencyOrigin {
+	api: String,
+	client: Box<dyn HttpClient>,
+}
+
+impl CratesIoDependencyOrigin {
+	/// Creates an origin that queries crates.io with the given client.
+	pub fn new<C: HttpClient + 'static>(client: C) -> Self {
+		Self {
+			api: CRATES_IO_API.to_string(),
+			client: Box::new(client),
+		}
+	}
+
+	/// Sets the base URL of the crates API (by default,
+	/// `https://crates.io/api/v1/crates`), e.g. for a mirror.
+	pub fn with_api_url(mut self, url: &str) -> Self {
+		self.api = url.trim_end_matches('/').to_string();
+		self
+	}
+}
+
+#[derive(serde::Deserialize)]
+struct CrateVersions {
+	versions: Vec<CrateVersion>,
+}
+
+#[derive(serde::Deserialize)]
+struct CrateVersion {
+	num: String,
+	#[serde(default)]
+	yanked: bool,
+}
+
+impl DependencyOrigin for CratesIoDependencyOrigin {
+	fn slug(&self) -> &str {
+		"crates-io"
+	}
+
+	fn status(
+		&self,
+		endpoint: &str,
+	) -> std::result::Result<DependencyStatus, Box<dyn std::error::Error>> {
+		let malformed = || Error::MalformedEndpoint(endpoint.to_string());
+
+		let (name, requirement) = endpoint.split_once('@').ok_or_else(malformed)?;
+		if name.is_empty()
+			|| !name
+				.chars()
+				.all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+		{
+			return Err(malformed().into());
+		}
+		let requirement = VersionReq::parse(requirement).map_err(|_| malformed())?;
+
+		let url = format!("{}/{}/versions", self.api, name);
+		let response = self.client.get(&url)?;
+		// Crates that don't exist (yet) can't have a matching release.
+		if response.status == 404 {
+			return Ok(DependencyStatus::Pending);
+		}
+		if !(200..300).contains(&response.status) {
+			return Err(Error::Http(url, format!("status {}", response.status)).into());
+		}
+
+		let versions = serde_json::from_str::<CrateVersions>(&response.body)
+			.map_err(|_| Error::Malformed(format!("HTTP response from {}", url)))?;
+		let released = versions.versions.iter().any(|version| {
+			!version.yanked
+				&& Version::parse(&version.num)
+					.map(|version| requirement.matches(&version))
+					.unwrap_or(false)
+		});
+
+		Ok(if released {
+			DependencyStatus::Complete
+		} else {
+			DependencyStatus::Pending
+		})
+	}
+}
+
+#[cfg(test)]
+mod test {
+	use super::*;
+	use crate::{deps::fixtures::FakeClient, DependencyRegistry, DependencyResolver};
+
+	#[test]
+	fn test_crates_io_dependency_origin() {
+		const CRATES: FakeClient = FakeClient(&[(
+			"http://crates/minimap/versions",
+			200,
+			r#"{"versions": [
+				{"num": "2.0.0-beta.1", "yanked": false},
+				{"num": "1.4.0", "yanked": true},
+				{"num": "1.3.2", "yanked": false}
+			]}"#,
+		)]);
+
+		let mut registry = DependencyRegistry::new();
+		registry
+			.register(Box::new(
+				CratesIoDependencyOrigin::new(CRATES).with_api_url("http://crates/"),
+			))
+			.unwrap();
+
+		let status = |endpoint| registry.status("crates-io", endpoint);
+		assert_eq!(status("minimap@>=1.3").unwrap(), DependencyStatus::Complete);
+		assert_eq!(
+			status("minimap@^1.3.2").unwrap(),
+			DependencyStatus::Complete
+		);
+		// Yanked releases don't count.
+		assert_eq!(status("minimap@>=1.4").unwrap(), DependencyStatus::Pending);
+		// Pre-releases only count when explicitly asked for.
+		assert_eq!(status("minimap@>=2").unwrap(), DependencyStatus::Pending);
+		assert_eq!(
+			status("minimap@>=2.0.0-beta").unwrap(),
+			DependencyStatus::Complete
+		);
+		assert_eq!(status("unreleased@*").unwrap(), DependencyStatus::Pending);
+
+		assert!(status("minimap").is_err());
+		assert!(status("minimap@not a version").is_err());
+		assert!(status("mini/map@1").is_err());
+	}
+}
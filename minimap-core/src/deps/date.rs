@@ -0,0 +1,141 @@
+use crate::{Clock, DependencyOrigin, DependencyStatus, Error, SystemClock};
+
+/// A dependency origin whose endpoints are points in time, completing
+/// once they've passed (e.g. "don't start before the embargo lifts").
+///
+/// Endpoints are RFC 3339 timestamps (e.g. `2024-03-01T09:00:00+01:00`),
+/// or dates (e.g. `2024-03-01`), which are taken as midnight UTC.
+pub struct DateDependencyOrigin {
+	clock: Box<dyn Clock>,
+}
+
+impl Default for DateDependencyOrigin {
+	fn default() -> Self {
+		Self::new()
+	}
+}
+
+impl DateDependencyOrigin {
+	/// Creates an origin that uses the [`SystemClock`].
+	pub fn new() -> Self {
+		Self {
+			clock: Box::new(SystemClock),
+		}
+	}
+
+	/// Sets the clock used to tell whether endpoints have passed.
+	pub fn with_clock<C: Clock + 'static>(mut self, clock: C) -> Self {
+		self.clock = Box::new(clock);
+		self
+	}
+}
+
+impl DependencyOrigin for DateDependencyOrigin {
+	fn slug(&self) -> &str {
+		"date"
+	}
+
+	fn status(
+		&self,
+		endpoint: &str,
+	) -> std::result::Result<DependencyStatus, Box<dyn std::error::Error>> {
+		let timestamp = parse_rfc3339(endpoint)
+			.ok_or_else(|| Error::MalformedEndpoint(endpoint.to_string()))?;
+
+		Ok(if self.clock.now() >= timestamp {
+			DependencyStatus::Complete
+		} else {
+			DependencyStatus::Pending
+		})
+	}
+}
+
+/// Parses an RFC 3339 timestamp or full date into seconds since the
+/// Unix epoch. Fractional seconds are truncated.
+fn parse_rfc3339(s: &str) -> Option<i64> {
+	fn number(s: &str) -> Option<i64> {
+		if s.is_empty() || !s.bytes().all(|b| b.is_ascii_digit()) {
+			return None;
+		}
+		s.parse().ok()
+	}
+
+	let (date, time) = match s.find(['T', 't', ' ']) {
+		Some(index) => (&s[..index], Some(&s[index + 1..])),
+		None => (s, None),
+	};
+
+	let mut parts = date.split('-');
+	let (year, month, day) = match (parts.next(), parts.next(), parts.next(), parts.next()) {
+		(Some(y), Some(m), Some(d), None) if y.len() == 4 && m.len() == 2 && d.len() == 2 => {
+			(number(y)?, number(m)?, number(d)?)
+		}
+		_ => return None,
+	};
+	if !(1..=12).contains(&month) || day < 1 || day > days_in_month(year, month) {
+		return None;
+	}
+	let days = days_from_civil(year, month, day);
+
+	let Some(time) = time else {
+		return Some(days * 86400);
+	};
+
+	let (time, offset) = if let Some(time) = time.strip_suffix(['Z', 'z']) {
+		(time, 0)
+	} else {
+		let index = time.rfind(['+', '-'])?;
+		let (time, offset) = time.split_at(index);
+		let sign = if offset.starts_with('-') { -1 } else { 1 };
+		let (hours, minutes) = offset[1..].split_once(':')?;
+		if hours.len() != 2 || minutes.len() != 2 {
+			return None;
+		}
+		let (hours, minutes) = (number(hours)?, number(minutes)?);
+		if hours > 23 || minutes > 59 {
+			return None;
+		}
+		(time, sign * (hours * 3600 + minutes * 60))
+	};
+
+	let time = match time.split_once('.') {
+		Some((time, fraction)) => {
+			number(fraction)?;
+			time
+		}
+		None => time,
+	};
+	let mut parts = time.split(':');
+	let (hour, minute, second) = match (parts.next(), parts.next(), parts.next(), parts.next()) {
+		(Some(h), Some(m), Some(s), None) if h.len() == 2 && m.len() == 2 && s.len() == 2 => {
+			(number(h)?, number(m)?, number(s)?)
+		}
+		_ => return None,
+	};
+	// Leap seconds (`:60`) are allowed.
+	if hour > 23 || minute > 59 || second > 60 {
+		return None;
+	}
+
+	Some(days * 86400 + hour * 3600 + minute * 60 + second - offset)
+}
+
+fn days_in_month(year: i64, month: i64) -> i64 {
+	match month {
+		2 if year % 4 == 0 && (year % 100 != 0 || year % 400 == 0) => 29,
+		2 => 28,
+		4 | 6 | 9 | 11 => 30,
+		_ => 31,
+	}
+}
+
+/// Gets the number of days since the Unix epoch of a proleptic Gregorian
+/// date. See <http://howardhinnant.github.io/date_algorithms.html>.
+fn days_from_civil(year: i64, month: i64, day: i64) -> i64 {
+	let year = if month <= 2 { year - 1 } else { year };
+	let era = year.div_euclid(400);
+	let year_of_era = year - era * 400;
+	let day_of_year = (153 * ((month + 9) % 12) + 2) / 5 + day - 1;
+	let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+	era * 146097 + day_of_era - 719468
+}
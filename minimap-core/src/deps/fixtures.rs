@@ -0,0 +1,48 @@
+//! Fixtures shared by the dependency tests.
+
+use crate::{DependencyOrigin, DependencyStatus, Error};
+#[cfg(feature = "http")]
+use crate::{HttpClient, HttpResponse};
+use std::{cell::Cell, rc::Rc};
+
+/// The `counting` origin, which counts how often it's queried. Its `done`
+/// endpoints are complete, `todo` ones pending, and all others fail.
+pub(crate) struct CountingOrigin(pub(crate) Rc<Cell<usize>>);
+
+impl DependencyOrigin for CountingOrigin {
+	fn slug(&self) -> &str {
+		"counting"
+	}
+
+	fn status(
+		&self,
+		endpoint: &str,
+	) -> std::result::Result<DependencyStatus, Box<dyn std::error::Error>> {
+		self.0.set(self.0.get() + 1);
+		match endpoint {
+			"done" => Ok(DependencyStatus::Complete),
+			"todo" => Ok(DependencyStatus::Pending),
+			_ => Err(Error::MalformedEndpoint(endpoint.to_string()).into()),
+		}
+	}
+}
+
+/// Answers with canned `(url, status, body)` responses, and with a 404
+/// for any other URL.
+#[cfg(feature = "http")]
+pub(crate) struct FakeClient(pub(crate) &'static [(&'static str, u16, &'static str)]);
+
+#[cfg(feature = "http")]
+impl HttpClient for FakeClient {
+	fn get(&self, url: &str) -> std::result::Result<HttpResponse, Box<dyn std::error::Error>> {
+		let (status, body) = self
+			.0
+			.iter()
+			.find(|(known, _, _)| *known == url)
+			.map_or((404, "not found"), |&(_, status, body)| (status, body));
+		Ok(HttpResponse {
+			status,
+			body: body.to_string(),
+		})
+	}
+}
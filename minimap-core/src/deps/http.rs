@@ -0,0 +1,402 @@
+use crate::{DependencyOrigin, DependencyStatus, Error, Result};
+use regex::Regex;
+use std::{
+	io::{Read, Write},
+	net::{TcpStream, ToSocketAddrs},
+	time::Duration,
+};
+
+/// A response to an HTTP `GET` request. See [`HttpClient`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HttpResponse {
+	/// The response's status code.
+	pub status: u16,
+	/// The response's body.
+	pub body: String,
+}
+
+/// Performs HTTP requests for an [`HttpDependencyOrigin`].
+///
+/// Minimap only ships with [`PlainHttpClient`]; applications that need
+/// TLS, proxies, authentication, etc. can plug in their own client.
+pub trait HttpClient {
+	/// Performs a `GET` request to the given URL.
+	fn get(&self, url: &str) -> std::result::Result<HttpResponse, Box<dyn std::error::Error>>;
+}
+
+/// A minimal HTTP/1.0 client that only supports plain `http://` URLs.
+///
+/// Connecting, and each read or write, give up after 30 seconds, and
+/// responses larger than 8 MiB are rejected.
+pub struct PlainHttpClient;
+
+const PLAIN_HTTP_TIMEOUT: Duration = Duration::from_secs(30);
+const PLAIN_HTTP_MAX_RESPONSE: u64 = 8 * 1024 * 1024;
+
+impl PlainHttpClient {
+	/// Splits a URL into its host, port and path, returning
+	/// [`Error::MalformedEndpoint`] if it's not a plain HTTP URL.
+	fn parse_url(url: &str) -> Result<(&str, u16, &str)> {
+		let malformed = || Error::MalformedEndpoint(url.to_string());
+
+		let rest = url.strip_prefix("http://").ok_or_else(malformed)?;
+		let (authority, path) = match rest.find('/') {
+			Some(index) => rest.split_at(index),
+			None => (rest, "/"),
+		};
+		// IPv6 addresses are bracketed, as they contain colons themselves.
+		let (host, port) = match authority.strip_prefix('[') {
+			Some(rest) => {
+				let (host, port) = rest.split_once(']').ok_or_else(malformed)?;
+				match port {
+					"" => (host, None),
+					port => (host, Some(port.strip_prefix(':').ok_or_else(malformed)?)),
+				}
+			}
+			None => match authority.rsplit_once(':') {
+				Some((host, port)) => (host, Some(port)),
+				None => (authority, None),
+			},
+		};
+		let port = match port {
+			Some(port) => port.parse::<u16>().map_err(|_| malformed())?,
+			None => 80,
+		};
+		if host.is_empty() {
+			return Err(malformed());
+		}
+
+		Ok((host, port, path))
+	}
+
+	fn connect(host: &str, port: u16) -> std::io::Result<TcpStream> {
+		let mut last_error = None;
+		for address in (host, port).to_socket_addrs()? {
+			match TcpStream::connect_timeout(&address, PLAIN_HTTP_TIMEOUT) {
+				Ok(stream) => return Ok(stream),
+				Err(e) => last_error = Some(e),
+			}
+		}
+		Err(last_error.unwrap_or_else(|| {
+			std::io::Error::new(std::io::ErrorKind::NotFound, "host has no addresses")
+		}))
+	}
+}
+
+impl HttpClient for PlainHttpClient {
+	fn get(&self, url: &str) -> std::result::Result<HttpResponse, Box<dyn std::error::Error>> {
+		let (host, port, path) = Self::parse_url(url)?;
+		let failed = |reason: String| Error::Http(url.to_string(), reason);
+
+		let response = (|| -> std::io::Result<Vec<u8>> {
+			let mut stream = Self::connect(host, port)?;
+			stream.set_read_timeout(Some(PLAIN_HTTP_TIMEOUT))?;
+			stream.set_write_timeout(Some(PLAIN_HTTP_TIMEOUT))?;
+			// HTTP/1.0 keeps servers from using chunked transfer encoding,
+			// so the body is simply everything after the headers.
+			let authority = url["http://".len()..].split('/').next().unwrap_or_default();
+			write!(
+				stream,
+				"GET {} HTTP/1.0\r\nHost: {}\r\nUser-Agent: minimap\r\nConnection: close\r\n\r\n",
+				path, authority
+			)?;
+
+			let mut response = Vec::new();
+			stream
+				.take(PLAIN_HTTP_MAX_RESPONSE + 1)
+				.read_to_end(&mut response)?;
+			Ok(response)
+		})()
+		.map_err(|e| failed(e.to_string()))?;
+		if response.len() as u64 > PLAIN_HTTP_MAX_RESPONSE {
+			return Err(failed(format!(
+				"response larger than {} bytes",
+				PLAIN_HTTP_MAX_RESPONSE
+			))
+			.into());
+		}
+
+		let bad_response = || failed("malformed response".to_string());
+		let split = response
+			.windows(4)
+			.position(|window| window == b"\r\n\r\n")
+			.ok_or_else(bad_response)?;
+		let head = String::from_utf8_lossy(&response[..split]);
+		let status = head
+			.lines()
+			.next()
+			.and_then(|line| line.split(' ').nth(1))
+			.and_then(|status| status.parse::<u16>().ok())
+			.ok_or_else(bad_response)?;
+
+		Ok(HttpResponse {
+			status,
+			body: String::from_utf8_lossy(&response[split + 4..]).to_string(),
+		})
+	}
+}
+
+enum Extraction {
+	None,
+	JsonPointer(String),
+	Regex(Regex),
+}
+
+/// How an [`HttpDependencyOrigin`] derives a dependency's status
+/// from the response to its endpoint URL.
+pub struct HttpStatusRule {
+	extraction: Extraction,
+	complete: Vec<String>,
+}
+
+impl Default for HttpStatusRule {
+	fn default() -> Self {
+		Self::success()
+	}
+}
+
+impl HttpStatusRule {
+	/// Complete for any `2xx` response, and pending otherwise.
+	pub fn success() -> Self {
+		Self {
+			extraction: Extraction::None,
+			complete: Vec::new(),
+		}
+	}
+
+	/// Complete if the value at the given JSON pointer (e.g. `/state`)
+	/// of the response body is one of the given values. Non-string
+	/// values are compared in their JSON form (e.g. `true`).
+	pub fn json_pointer(pointer: &str, complete: &[&str]) -> Self {
+		Self {
+			extraction: Extraction::JsonPointer(pointer.to_string()),
+			complete: complete.iter().map(|value| value.to_string()).collect(),
+		}
+	}
+
+	/// Complete if the given regular expression matches the response body
+	/// and its first capture group (or the whole match, if it has none)
+	/// is one of the given values. If no values are given, any match is
+	/// enough. Returns [`Error::Malformed`] if the pattern is invalid.
+	pub fn regex(pattern: &str, complete: &[&str]) -> Result<Self> {
+		Ok(Self {
+			extraction: Extraction::Regex(
+				Regex::new(pattern).map_err(|_| Error::Malformed(pattern.to_string()))?,
+			),
+			complete: complete.iter().map(|value| value.to_string()).collect(),
+		})
+	}
+
+	fn status(&self, url: &str, response: &HttpResponse) -> Result<DependencyStatus> {
+		let success = (200..300).contains(&response.status);
+
+		let value = match &self.extraction {
+			Extraction::None => {
+				return Ok(if success {
+					DependencyStatus::Complete
+				} else {
+					DependencyStatus::Pending
+				});
+			}
+			// Without a successful response, there's nothing to extract from.
+			_ if !success => {
+				return Err(Error::Http(
+					url.to_string(),
+					format!("status {}", response.status),
+				));
+			}
+			Extraction::JsonPointer(pointer) => {
+				let json = serde_json::from_str::<serde_json::Value>(&response.body)
+					.map_err(|_| Error::Malformed(format!("HTTP response from {}", url)))?;
+				json.pointer(pointer).map(|value| match value {
+					serde_json::Value::String(value) => value.clone(),
+					value => value.to_string(),
+				})
+			}
+			Extraction::Regex(regex) => regex.captures(&response.body).and_then(|captures| {
+				captures
+					.get(1)
+					.or_else(|| captures.get(0))
+					.map(|value| value.as_str().to_string())
+			}),
+		};
+
+		let complete = match value {
+			Some(_) if self.complete.is_empty() => true,
+			Some(value) => self.complete.contains(&value),
+			None => false,
+		};
+
+		Ok(if complete {
+			DependencyStatus::Complete
+		} else {
+			DependencyStatus::Pending
+		})
+	}
+}
+
+/// A dependency origin whose endpoints are URLs, with the status
+/// derived from the response (see [`HttpStatusRule`]). It's meant as
+/// an escape hatch for systems that don't have a dedicated origin.
+pub struct HttpDependencyOrigin {
+	slug: String,
+	client: Box<dyn HttpClient>,
+	rule: HttpStatusRule,
+}
+
+impl HttpDependencyOrigin {
+	/// Creates an origin with the given slug, which uses
+	/// [`PlainHttpClient`] and [`HttpStatusRule::success`].
+	pub fn new(slug: &str) -> Self {
+		Self {
+			slug: slug.to_string(),
+			client: Box::new(PlainHttpClient),
+			rule: HttpStatusRule::success(),
+		}
+	}
+
+	/// Sets the client used to perform requests.
+	pub fn with_client<C: HttpClient + 'static>(mut self, client: C) -> Self {
+		self.client = Box::new(client);
+		self
+	}
+
+	/// Sets the rule used to derive statuses from responses.
+	pub fn with_rule(mut self, rule: HttpStatusRule) -> Self {
+		self.rule = rule;
+		self
+	}
+}
+
+impl DependencyOrigin for HttpDependencyOrigin {
+	fn slug(&self) -> &str {
+		&self.slug
+	}
+
+	fn status(
+		&self,
+		endpoint: &str,
+	) -> std::result::Result<DependencyStatus, Box<dyn std::error::Error>> {
+		let response = self.client.get(endpoint)?;
+		Ok(self.rule.status(endpoint, &response)?)
+	}
+}
+
+#[cfg(test)]
+mod test {
+	use super::*;
+	use std::{net::TcpListener, thread};
+
+	/// Serves a single request with the given response, returning the
+	/// server's address and a handle to the request it got.
+	fn serve(response: Vec<u8>) -> (String, thread::JoinHandle<String>) {
+		let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+		let address = listener.local_addr().unwrap().to_string();
+		let handle = thread::spawn(move || {
+			let (mut stream, _) = listener.accept().unwrap();
+			let mut request = Vec::new();
+			let mut buffer = [0; 1024];
+			while !request.ends_with(b"\r\n\r\n") {
+				let read = stream.read(&mut buffer).unwrap();
+				request.extend_from_slice(&buffer[..read]);
+			}
+			// The client may give up before reading everything.
+			let _ = stream.write_all(&response);
+			String::from_utf8(request).unwrap()
+		});
+		(address, handle)
+	}
+
+	#[test]
+	fn test_plain_http_client() {
+		let (address, server) = serve(b"HTTP/1.0 404 Not Found\r\nX: y\r\n\r\nnope".to_vec());
+		let response = PlainHttpClient
+			.get(&format!("http://{}/some/path?q", address))
+			.unwrap();
+		assert_eq!(
+			response,
+			HttpResponse {
+				status: 404,
+				body: "nope".to_string()
+			}
+		);
+		let request = server.join().unwrap();
+		assert!(request.starts_with("GET /some/path?q HTTP/1.0\r\n"));
+		assert!(request.contains(&format!("\r\nHost: {}\r\n", address)));
+
+		let mut huge = b"HTTP/1.0 200 OK\r\n\r\n".to_vec();
+		huge.resize(PLAIN_HTTP_MAX_RESPONSE as usize + 64, b'x');
+		let (address, server) = serve(huge);
+		let error = PlainHttpClient
+			.get(&format!("http://{}/", address))
+			.unwrap_err();
+		assert!(matches!(
+			error.downcast_ref::<Error>(),
+			Some(Error::Http(_, reason)) if reason.contains("larger")
+		));
+		server.join().unwrap();
+
+		let (address, server) = serve(b"garbage".to_vec());
+		let error = PlainHttpClient
+			.get(&format!("http://{}/", address))
+			.unwrap_err();
+		assert!(matches!(
+			error.downcast_ref::<Error>(),
+			Some(Error::Http(..))
+		));
+		server.join().unwrap();
+	}
+
+	#[test]
+	fn test_plain_http_client_urls() {
+		assert_eq!(
+			PlainHttpClient::parse_url("http://example.com").unwrap(),
+			("example.com", 80, "/")
+		);
+		assert_eq!(
+			PlainHttpClient::parse_url("http://example.com:8080/a/b").unwrap(),
+			("example.com", 8080, "/a/b")
+		);
+		assert_eq!(
+			PlainHttpClient::parse_url("http://[::1]:8080/a").unwrap(),
+			("::1", 8080, "/a")
+		);
+		assert_eq!(
+			PlainHttpClient::parse_url("http://[::1]").unwrap(),
+			("::1", 80, "/")
+		);
+		for url in [
+			"https://example.com",
+			"http://",
+			"http://:80",
+			"http://example.com:http",
+			"http://[::1",
+			"http://[::1]8080",
+		] {
+			assert!(
+				matches!(
+					PlainHttpClient::parse_url(url),
+					Err(Error::MalformedEndpoint(_))
+				),
+				"{}",
+				url
+			);
+		}
+	}
+
+	#[test]
+	fn test_plain_http_client_unreachable() {
+		// Nothing listens on a port that was just freed.
+		let address = TcpListener::bind("127.0.0.1:0")
+			.unwrap()
+			.local_addr()
+			.unwrap();
+		let error = PlainHttpClient
+			.get(&format!("http://{}/", address))
+			.unwrap_err();
+		assert!(matches!(
+			error.downcast_ref::<Error>(),
+			Some(Error::Http(..))
+		));
+	}
+}
@@ -1,8 +1,87 @@
 use crate::{DependencyOrigin, DependencyStatus, Error, GitRemote, Workspace};
+use std::{collections::VecDeque, rc::Rc, sync::Mutex};
+
+/// The number of remotes a [`MinimapDependencyOrigin`] keeps open by default.
+pub const DEFAULT_CACHED_REMOTES: usize = 8;
 
 /// A dependency origin that queries remote Minimap workspaces
 /// for dependency statuses over Git.
-pub struct MinimapDependencyOrigin;
+///
+/// Opened remotes are kept around, so that resolving several
+/// dependencies against the same workspace only opens it once. When
+/// more remotes are needed than the cache holds, the least recently
+/// used one is closed. Remotes are taken out of the cache while they're
+/// queried, so concurrent queries against the same workspace each open
+/// their own.
+pub struct MinimapDependencyOrigin {
+	capacity: usize,
+	/// Most recently used first.
+	remotes: Mutex<VecDeque<(String, GitRemote)>>,
+}
+
+impl Default for MinimapDependencyOrigin {
+	fn default() -> Self {
+		Self::new()
+	}
+}
+
+impl MinimapDependencyOrigin {
+	/// Creates an origin that keeps up to [`DEFAULT_CACHED_REMOTES`]
+	/// remotes open.
+	pub fn new() -> Self {
+		Self::with_cache_size(DEFAULT_CACHED_REMOTES)
+	}
+
+	/// Creates an origin that keeps up to the given number of remotes
+	/// open. With a size of zero, remotes are opened for every query.
+	pub fn with_cache_size(capacity: usize) -> Self {
+		Self {
+			capacity,
+			remotes: Mutex::default(),
+		}
+	}
+
+	/// Lists the URLs of the remotes that are currently open,
+	/// most recently used first.
+	pub fn cached_remotes(&self) -> Vec<String> {
+		self.remotes
+			.lock()
+			.unwrap()
+			.iter()
+			.map(|(url, _)| url.clone())
+			.collect()
+	}
+
+	/// Closes all open remotes.
+	pub fn clear_cache(&self) {
+		self.remotes.lock().unwrap().clear();
+	}
+
+	/// Takes a remote out of the cache, opening it if it isn't cached.
+	fn take_remote(&self, url: &str) -> crate::Result<GitRemote> {
+		let cached = {
+			let mut remotes = self.remotes.lock().unwrap();
+			remotes
+				.iter()
+				.position(|(cached, _)| cached == url)
+				.and_then(|index| remotes.remove(index))
+		};
+
+		match cached {
+			Some((_, remote)) => Ok(remote),
+			None => GitRemote::open(url),
+		}
+	}
+
+	/// Puts a remote back into the cache, as the most recently used one.
+	fn put_remote(&self, url: &str, remote: GitRemote) {
+		if self.capacity > 0 {
+			let mut remotes = self.remotes.lock().unwrap();
+			remotes.truncate(self.capacity - 1);
+			remotes.push_front((url.to_string(), remote));
+		}
+	}
+}
 
 impl DependencyOrigin for MinimapDependencyOrigin {
 	fn slug(&self) -> &str {
@@ -22,9 +101,16 @@ impl DependencyOrigin for MinimapDependencyOrigin {
 			.next()
 			.ok_or(Error::MalformedEndpoint(endpoint.to_string()))?;
 
-		let remote = GitRemote::open(remote)?;
-		let workspace = Workspace::open(remote);
+		let url = remote;
+		let remote = Rc::new(self.take_remote(url)?);
+		let state = Workspace::open(remote.clone()).and_then(|workspace| {
+			let state = workspace.ticket(ticket_slug)?.state()?.0;
+			Ok(state)
+		});
+		if let Ok(remote) = Rc::try_unwrap(remote) {
+			self.put_remote(url, remote);
+		}
 
-		Ok(workspace.ticket(ticket_slug)?.state().map(|s| s.0)?.into())
+		Ok(state?.into())
 	}
 }
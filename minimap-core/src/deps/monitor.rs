@@ -0,0 +1,181 @@
+//! Dependency completion notifications.
+//!
+//! A [`DependencyMonitor`] keeps track of the pending dependencies of a
+//! workspace's open tickets, re-resolving them on every poll, and
+//! notifies its observers when one of them completes.
+
+use crate::{
+	Dependency, DependencyKind, DependencyResolver, DependencyStatus, Remote, Result, TicketState,
+	Workspace,
+};
+use std::{collections::HashMap, time::Duration};
+
+/// The time a [`DependencyMonitor`] waits between polls by default.
+pub const DEFAULT_MONITOR_INTERVAL: Duration = Duration::from_secs(60);
+
+/// An event emitted by a [`DependencyMonitor`].
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize)]
+#[serde(tag = "event", rename_all = "snake_case")]
+pub enum DependencyEvent {
+	/// A dependency that was pending is now complete.
+	Completed {
+		/// The slug of the ticket with the dependency.
+		ticket: String,
+		/// The dependency's origin.
+		origin: String,
+		/// The dependency's endpoint.
+		endpoint: String,
+	},
+}
+
+type Observer<'r> = Box<dyn FnMut(&DependencyEvent) + 'r>;
+
+/// Polls a workspace's dependencies and emits a [`DependencyEvent`]
+/// whenever a pending dependency completes.
+///
+/// The first poll only records which dependencies are pending.
+/// Dependencies that are already complete when they're first seen
+/// don't emit events, and neither do the dependencies of closed tickets
+/// nor informational dependencies (see [`DependencyKind`]).
+///
+/// Dependencies that have never resolved are of unknown status, and
+/// aren't reported when they first resolve as complete either.
+pub struct DependencyMonitor<'r, D: DependencyResolver + ?Sized> {
+	resolver: &'r D,
+	interval: Duration,
+	observers: Vec<Observer<'r>>,
+	/// `(ticket, origin, endpoint)` to the last known status,
+	/// if the dependency ever resolved.
+	statuses: HashMap<(String, String, String), Option<DependencyStatus>>,
+}
+
+impl<'r, D: DependencyResolver + ?Sized> DependencyMonitor<'r, D> {
+	/// Creates a monitor that resolves non-`_` dependencies with the
+	/// given resolver, polling every [`DEFAULT_MONITOR_INTERVAL`].
+	pub fn new(resolver: &'r D) -> Self {
+		Self {
+			resolver,
+			interval: DEFAULT_MONITOR_INTERVAL,
+			observers: Vec::new(),
+			statuses: HashMap::new(),
+		}
+	}
+
+	/// Sets the time to wait between polls in [`DependencyMonitor::run`].
+	pub fn with_interval(mut self, interval: Duration) -> Self {
+		self.interval = interval;
+		self
+	}
+
+	/// Registers an observer, which is called with every event
+	/// in the order they're emitted.
+	pub fn subscribe(&mut self, observer: impl FnMut(&DependencyEvent) + 'r) {
+		self.observers.push(Box::new(observer));
+	}
+
+	/// Lists the dependencies currently known to be pending,
+	/// as `(ticket, origin, endpoint)`.
+	pub fn pending(&self) -> Vec<(&str, &str, &str)> {
+		let mut pending = self
+			.statuses
+			.iter()
+			.filter(|(_, &status)| status == Some(DependencyStatus::Pending))
+			.map(|((ticket, origin, endpoint), _)| {
+				(ticket.as_str(), origin.as_str(), endpoint.as_str())
+			})
+			.collect::<Vec<_>>();
+		pending.sort_unstable();
+		pending
+	}
+
+	/// Resolves every pending or newly added dependency once, notifying
+	/// the observers of (and returning) the dependencies that completed.
+	///
+	/// Dependencies that fail to resolve keep their previous status, so
+	/// that an unreachable origin doesn't stop the monitor; they're
+	/// retried on the next poll.
+	pub fn poll<'a, R: Remote<'a>>(
+		&mut self,
+		workspace: &'a Workspace<'a, R>,
+	) -> Result<Vec<DependencyEvent>> {
+		let mut statuses = HashMap::new();
+		let mut events = Vec::new();
+
+		for ticket in workspace.tickets()? {
+			if ticket.state()?.0 == TicketState::Closed {
+				continue;
+			}
+
+			for Dependency {
+				origin,
+				endpoint,
+				kind,
+				..
+			} in ticket.dependencies()?
+			{
+				if kind == DependencyKind::Informational {
+					continue;
+				}
+
+				let key = (ticket.slug().to_string(), origin, endpoint);
+				let previous = self.statuses.get(&key).copied().flatten();
+				if previous == Some(DependencyStatus::Complete) {
+					statuses.insert(key, previous);
+					continue;
+				}
+
+				let (_, origin, endpoint) = &key;
+				let status = if origin == "_" {
+					workspace
+						.ticket(endpoint)
+						.and_then(|blocker| blocker.state())
+						.map(|(state, _)| state.into())
+				} else {
+					self.resolver.status(origin, endpoint)
+				};
+				let status = status.ok().or(previous);
+
+				if previous == Some(DependencyStatus::Pending)
+					&& status == Some(DependencyStatus::Complete)
+				{
+					events.push(DependencyEvent::Completed {
+						ticket: key.0.clone(),
+						origin: key.1.clone(),
+						endpoint: key.2.clone(),
+					});
+				}
+				statuses.insert(key, status);
+			}
+		}
+
+		// Dependencies that were removed (or whose tickets were closed)
+		// are forgotten.
+		self.statuses = statuses;
+
+		for event in &events {
+			for observer in &mut self.observers {
+				observer(event);
+			}
+		}
+
+		Ok(events)
+	}
+
+	/// Polls the workspace repeatedly, waiting for the monitor's interval
+	/// between polls, for as long as `keep_running` returns `true`.
+	/// `keep_running` is checked before every poll.
+	///
+	/// Stops at the first error reading the workspace.
+	pub fn run<'a, R: Remote<'a>>(
+		&mut self,
+		workspace: &'a Workspace<'a, R>,
+		mut keep_running: impl FnMut() -> bool,
+	) -> Result<()> {
+		while keep_running() {
+			self.poll(workspace)?;
+			std::thread::sleep(self.interval);
+		}
+
+		Ok(())
+	}
+}
@@ -0,0 +1,272 @@
+//! Dependency origins loaded from dynamic libraries.
+//!
+//! A plugin is a dynamic library (`.so`, `.dylib` or `.dll`) exporting
+//! the following C functions:
+//!
+//! ```c
+//! // Must return MINIMAP_PLUGIN_ABI_VERSION (currently 1).
+//! uint32_t minimap_plugin_abi_version(void);
+//! // The origin's slug, as a static, NUL-terminated UTF-8 string.
+//! const char *minimap_origin_slug(void);
+//! // Returns 1 if the endpoint is complete, 0 if it's pending,
+//! // and any negative value if its status couldn't be determined.
+//! int32_t minimap_origin_status(const char *endpoint);
+//! ```
+//!
+//! Plugins are dropped into a directory and loaded with
+//! [`DependencyRegistry::load_plugins`], so that third-party origins
+//! can be used without recompiling Minimap.
+
+use crate::{DependencyOrigin, DependencyRegistry, DependencyStatus, Error, Result};
+use libloading::{Library, Symbol};
+use std::{
+	ffi::{c_char, CStr, CString},
+	path::Path,
+};
+
+/// The plugin ABI version supported by this version of Minimap.
+pub const PLUGIN_ABI_VERSION: u32 = 1;
+
+type AbiVersionFn = unsafe extern "C" fn() -> u32;
+type SlugFn = unsafe extern "C" fn() -> *const c_char;
+type StatusFn = unsafe extern "C" fn(*const c_char) -> i32;
+
+/// A dependency origin implemented by a plugin. See the
+/// [module documentation](self) for the plugin interface.
+pub struct PluginDependencyOrigin {
+	path: String,
+	slug: String,
+	status: StatusFn,
+	// Must outlive `status`.
+	_library: Library,
+}
+
+impl PluginDependencyOrigin {
+	/// Loads a plugin from a dynamic library.
+	///
+	/// Returns [`Error::Plugin`] if the library can't be loaded, doesn't
+	/// export the plugin interface, or was built for another ABI version.
+	///
+	/// # Safety
+	///
+	/// Loading a library runs its initialization code, and the plugin's
+	/// functions are trusted to follow the interface. Only load plugins
+	/// from trusted sources.
+	pub unsafe fn load(path: &Path) -> Result<Self> {
+		let error = |reason: String| Error::Plugin(path.display().to_string(), reason);
+
+		let library = Library::new(path).map_err(|err| error(err.to_string()))?;
+
+		let abi_version: Symbol<AbiVersionFn> = library
+			.get(b"minimap_plugin_abi_version\0")
+			.map_err(|err| error(err.to_string()))?;
+		let abi_version = abi_version();
+		if abi_version != PLUGIN_ABI_VERSION {
+			return Err(error(format!(
+				"unsupported ABI version {} (expected {})",
+				abi_version, PLUGIN_ABI_VERSION
+			)));
+		}
+
+		let slug: Symbol<SlugFn> = library
+			.get(b"minimap_origin_slug\0")
+			.map_err(|err| error(err.to_string()))?;
+		let slug = slug();
+		if slug.is_null() {
+			return Err(error("missing origin slug".to_string()));
+		}
+		let slug = CStr::from_ptr(slug)
+			.to_str()
+			.map_err(|_| error("origin slug isn't valid UTF-8".to_string()))?
+			.to_string();
+
+		let status: Symbol<StatusFn> = library
+			.get(b"minimap_origin_status\0")
+			.map_err(|err| error(err.to_string()))?;
+		let status = *status;
+
+		Ok(Self {
+			path: path.display().to_string(),
+			slug,
+			status,
+			_library: library,
+		})
+	}
+}
+
+impl DependencyOrigin for PluginDependencyOrigin {
+	fn slug(&self) -> &str {
+		&self.slug
+	}
+
+	fn status(
+		&self,
+		endpoint: &str,
+	) -> std::result::Result<DependencyStatus, Box<dyn std::error::Error>> {
+		let endpoint_c =
+			CString::new(endpoint).map_err(|_| Error::MalformedEndpoint(endpoint.to_string()))?;
+
+		// SAFETY: the library is kept loaded for as long as `self` lives,
+		// and the endpoint is a valid, NUL-terminated string.
+		match unsafe { (self.status)(endpoint_c.as_ptr()) } {
+			0 => Ok(DependencyStatus::Pending),
+			1 => Ok(DependencyStatus::Complete),
+			code => Err(Error::Plugin(
+				self.path.clone(),
+				format!("status of {} failed with code {}", endpoint, code),
+			)
+			.into()),
+		}
+	}
+}
+
+impl DependencyRegistry {
+	/// Loads every plugin (i.e. every file with the platform's dynamic
+	/// library extension) in a directory and registers its origin,
+	/// returning the registered slugs in the order they were loaded.
+	/// Plugins are loaded in file name order; later plugins replace
+	/// earlier ones with the same slug.
+	///
+	/// Stops at the first plugin that fails to load or register.
+	///
+	/// # Safety
+	///
+	/// See [`PluginDependencyOrigin::load`].
+	pub unsafe fn load_plugins(&mut self, dir: &Path) -> Result<Vec<String>> {
+		let mut paths = std::fs::read_dir(dir)?
+			.map(|entry| Ok(entry?.path()))
+			.collect::<Result<Vec<_>>>()?;
+		paths.retain(|path| {
+			path.is_file()
+				&& path.extension().and_then(|ext| ext.to_str())
+					== Some(std::env::consts::DLL_EXTENSION)
+		});
+		paths.sort();
+
+		let mut slugs = Vec::new();
+		for path in paths {
+			let origin = PluginDependencyOrigin::load(&path)?;
+			slugs.push(origin.slug().to_string());
+			self.register(Box::new(origin))?;
+		}
+
+		Ok(slugs)
+	}
+}
+
+#[cfg(test)]
+mod test {
+	use super::*;
+	use crate::DependencyResolver;
+
+	#[test]
+	fn test_dependency_plugins() {
+		let mut registry = DependencyRegistry::new();
+		let dir = std::env::temp_dir().join(format!(
+			"minimap-plugins-{}-{:p}",
+			std::process::id(),
+			&registry
+		));
+		std::fs::create_dir_all(&dir).unwrap();
+
+		// Only dynamic libraries are considered.
+		std::fs::write(dir.join("README.txt"), "not a plugin").unwrap();
+		assert_eq!(
+			unsafe { registry.load_plugins(&dir) }.unwrap(),
+			Vec::<String>::new()
+		);
+
+		let bogus = dir.join(format!("bogus.{}", std::env::consts::DLL_EXTENSION));
+		std::fs::write(&bogus, "not a library either").unwrap();
+		assert!(matches!(
+			unsafe { registry.load_plugins(&dir) },
+			Err(Error::Plugin(path, _)) if path == bogus.display().to_string()
+		));
+		assert!(matches!(
+			unsafe { PluginDependencyOrigin::load(&bogus) },
+			Err(Error::Plugin(_, _))
+		));
+
+		std::fs::remove_file(&bogus).unwrap();
+
+		let fixture = dir.join(format!("fixture.{}", std::env::consts::DLL_EXTENSION));
+		build_fixture(&fixture, 1);
+		assert_eq!(
+			unsafe { registry.load_plugins(&dir) }.unwrap(),
+			vec!["fixture"]
+		);
+		assert_eq!(
+			registry.status("fixture", "done").unwrap(),
+			DependencyStatus::Complete
+		);
+		assert_eq!(
+			registry.status("fixture", "todo").unwrap(),
+			DependencyStatus::Pending
+		);
+		let origin = unsafe { PluginDependencyOrigin::load(&fixture) }.unwrap();
+		let error = origin.status("broken").unwrap_err();
+		assert!(matches!(
+			error.downcast_ref::<Error>(),
+			Some(Error::Plugin(path, _)) if *path == fixture.display().to_string()
+		));
+		assert!(origin.status("nul\0").is_err());
+
+		let outdated = dir.join(format!("outdated.{}", std::env::consts::DLL_EXTENSION));
+		build_fixture(&outdated, 0);
+		assert!(matches!(
+			unsafe { PluginDependencyOrigin::load(&outdated) },
+			Err(Error::Plugin(path, reason))
+				if path == outdated.display().to_string() && reason.contains("ABI version 0")
+		));
+
+		std::fs::remove_dir_all(&dir).unwrap();
+		assert!(matches!(
+			unsafe { registry.load_plugins(&dir) },
+			Err(Error::Io(_))
+		));
+	}
+
+	/// Builds a plugin for the `fixture` origin, whose `done` endpoints are
+	/// complete, `todo` ones pending, and all others fail.
+	fn build_fixture(path: &Path, abi_version: u32) {
+		let source = path.with_extension("rs");
+		std::fs::write(
+			&source,
+			format!(
+				r#"
+				use std::ffi::{{c_char, CStr}};
+
+				#[no_mangle]
+				pub extern "C" fn minimap_plugin_abi_version() -> u32 {{
+					{}
+				}}
+
+				#[no_mangle]
+				pub extern "C" fn minimap_origin_slug() -> *const c_char {{
+					b"fixture\0".as_ptr().cast()
+				}}
+
+				#[no_mangle]
+				pub unsafe extern "C" fn minimap_origin_status(endpoint: *const c_char) -> i32 {{
+					match CStr::from_ptr(endpoint).to_bytes() {{
+						b"done" => 1,
+						b"todo" => 0,
+						_ => -1,
+					}}
+				}}
+				"#,
+				abi_version
+			),
+		)
+		.unwrap();
+
+		let status =
+			std::process::Command::new(std::env::var_os("RUSTC").unwrap_or_else(|| "rustc".into()))
+				.args(["--crate-type", "cdylib", "--edition", "2021", "-o"])
+				.arg(path)
+				.arg(&source)
+				.status()
+				.unwrap();
+		assert!(status.success(), "cannot build plugin fixture");
+	}
+}
@@ -0,0 +1,126 @@
+//! Duplicate-of relationships between tickets.
+//!
+//! Marking a ticket as a duplicate records the canonical ticket's slug
+//! in the duplicate's `duplicate_of` collection, and adds the duplicate's
+//! slug to the canonical ticket's `duplicates` set. Unlike dependencies,
+//! duplicates don't block anything; they exist so that reporting can
+//! tell them apart from real work.
+
+use crate::{Error, Record, RecordBuilder, Remote, Result, Ticket, TicketState};
+use std::collections::HashSet;
+
+impl<'a, R: Remote<'a>> Ticket<'a, R> {
+	/// Marks the ticket as a duplicate of the ticket with the given slug,
+	/// returning the newly created record. If the ticket was already marked
+	/// as a duplicate of another ticket, it's moved over.
+	///
+	/// Returns [`Error::NotFound`] if the canonical ticket doesn't exist,
+	/// and [`Error::DuplicateCycle`] if the canonical ticket is this
+	/// ticket, or is (transitively) a duplicate of it.
+	pub fn mark_duplicate_of(&self, slug: &str) -> Result<R::Record> {
+		let canonical = self.workspace.ticket(slug)?;
+
+		// Walk the canonical ticket's own chain to avoid cycles. Slugs are
+		// compared once resolved (e.g. for moved tickets), and chains that
+		// already loop (without this ticket) are cut short.
+		let mut chain = vec![self.slug.clone()];
+		let mut visited = HashSet::new();
+		let mut current = Some(canonical.slug.clone());
+		while let Some(slug) = current {
+			chain.push(slug.clone());
+			if slug == self.slug {
+				return Err(Error::DuplicateCycle(chain.join(" -> ")));
+			}
+			if !visited.insert(slug.clone()) {
+				break;
+			}
+			current = match self.workspace.ticket(&slug) {
+				Ok(ticket) => ticket.duplicate_of()?.map(|ticket| ticket.slug),
+				Err(Error::NotFound(_, _)) => None,
+				Err(err) => return Err(err),
+			};
+		}
+
+		if let Some(previous) = self.duplicate_of()? {
+			if previous.slug == canonical.slug {
+				return Err(Error::Exists(
+					format!("{}/duplicates", canonical.path),
+					self.slug.clone(),
+				));
+			}
+
+			self.workspace
+				.remote
+				.set_del_unchecked(&format!("{}/duplicates", previous.path), &self.slug)?;
+		}
+
+		let record = self
+			.workspace
+			.remote
+			.record_builder(&format!("{}/duplicate_of", self.path))
+			.commit(&canonical.slug)?;
+
+		self.workspace
+			.remote
+			.set_add_unchecked(&format!("{}/duplicates", canonical.path), &self.slug)?;
+
+		Ok(record)
+	}
+
+	/// Gets the canonical ticket this ticket is a duplicate of, if any.
+	/// Returns `None` if the canonical ticket has since been deleted.
+	pub fn duplicate_of(&self) -> Result<Option<Ticket<'a, R>>> {
+		match self.duplicate_of_slug()? {
+			Some(slug) => match self.workspace.ticket(&slug) {
+				Ok(ticket) => Ok(Some(ticket)),
+				Err(Error::NotFound(_, _)) => Ok(None),
+				Err(err) => Err(err),
+			},
+			None => Ok(None),
+		}
+	}
+
+	/// Gets all (existing) tickets that are marked as duplicates of this ticket.
+	pub fn duplicates(&self) -> Result<Vec<Ticket<'a, R>>> {
+		let mut duplicates = Vec::new();
+
+		for record in self
+			.workspace
+			.remote
+			.set_get_all(&format!("{}/duplicates", self.path))?
+		{
+			match self.workspace.ticket(&record.message()) {
+				Ok(ticket) => duplicates.push(ticket),
+				Err(Error::NotFound(_, _)) => {}
+				Err(err) => return Err(err),
+			}
+		}
+
+		Ok(duplicates)
+	}
+
+	/// Closes the ticket along with all of its open duplicates,
+	/// returning the state records that were created (this ticket's first).
+	///
+	/// Each transition is checked against the workspace's workflow; the
+	/// first denied transition aborts the operation.
+	pub fn close_with_duplicates(&self) -> Result<Vec<R::Record>> {
+		let mut records = vec![self.set_state(TicketState::Closed)?];
+
+		for duplicate in self.duplicates()? {
+			if duplicate.is_open()? {
+				records.push(duplicate.set_state(TicketState::Closed)?);
+			}
+		}
+
+		Ok(records)
+	}
+
+	fn duplicate_of_slug(&self) -> Result<Option<String>> {
+		Ok(self
+			.workspace
+			.remote
+			.latest(&format!("{}/duplicate_of", self.path))?
+			.map(|record| record.message()))
+	}
+}
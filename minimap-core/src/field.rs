@@ -0,0 +1,254 @@
+//! Typed custom fields on tickets.
+//!
+//! Each project has a schema of custom fields, stored as the
+//! `meta/project/<slug>/fields` set with items of the form `name:type`.
+//! Tickets store field values in their `field/<name>` collections,
+//! which are validated against the schema when set.
+
+use crate::{Error, Project, Record, RecordBuilder, Remote, Result, Ticket};
+use indexmap::IndexMap;
+
+/// The type of a custom field.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum FieldType {
+	/// Free-form text.
+	String,
+	/// A (finite) number.
+	Number,
+	/// `true` or `false`.
+	Bool,
+	/// One of a fixed set of values.
+	Enum(Vec<String>),
+	/// A calendar date, formatted as `YYYY-MM-DD`.
+	Date,
+}
+
+impl TryFrom<&str> for FieldType {
+	type Error = Error;
+
+	fn try_from(value: &str) -> Result<Self> {
+		match value {
+			"string" => Ok(Self::String),
+			"number" => Ok(Self::Number),
+			"bool" => Ok(Self::Bool),
+			"date" => Ok(Self::Date),
+			_ => match value.strip_prefix("enum:") {
+				Some(variants) if !variants.is_empty() => Ok(Self::Enum(
+					variants.split(',').map(str::to_string).collect(),
+				)),
+				_ => Err(Error::Malformed(value.to_string())),
+			},
+		}
+	}
+}
+
+impl ToString for FieldType {
+	fn to_string(&self) -> String {
+		match self {
+			Self::String => "string".to_string(),
+			Self::Number => "number".to_string(),
+			Self::Bool => "bool".to_string(),
+			Self::Enum(variants) => format!("enum:{}", variants.join(",")),
+			Self::Date => "date".to_string(),
+		}
+	}
+}
+
+/// The value of a custom field on a ticket.
+#[derive(Debug, Clone, PartialEq)]
+pub enum FieldValue {
+	/// A [`FieldType::String`] value.
+	String(String),
+	/// A [`FieldType::Number`] value.
+	Number(f64),
+	/// A [`FieldType::Bool`] value.
+	Bool(bool),
+	/// A [`FieldType::Enum`] value.
+	Enum(String),
+	/// A [`FieldType::Date`] value, as `(year, month, day)`.
+	Date(i32, u8, u8),
+}
+
+impl FieldType {
+	/// Parses and validates a value of this type.
+	/// Returns [`Error::Malformed`] if the value is invalid.
+	pub fn parse(&self, value: &str) -> Result<FieldValue> {
+		let malformed = || Error::Malformed(format!("{} ({})", value, self.to_string()));
+
+		match self {
+			Self::String => Ok(FieldValue::String(value.to_string())),
+			Self::Number => value
+				.parse::<f64>()
+				.ok()
+				.filter(|n| n.is_finite())
+				.map(FieldValue::Number)
+				.ok_or_else(malformed),
+			Self::Bool => match value {
+				"true" => Ok(FieldValue::Bool(true)),
+				"false" => Ok(FieldValue::Bool(false)),
+				_ => Err(malformed()),
+			},
+			Self::Enum(variants) => {
+				if variants.iter().any(|v| v == value) {
+					Ok(FieldValue::Enum(value.to_string()))
+				} else {
+					Err(malformed())
+				}
+			}
+			Self::Date => parse_date(value).ok_or_else(malformed),
+		}
+	}
+}
+
+impl ToString for FieldValue {
+	fn to_string(&self) -> String {
+		match self {
+			Self::String(s) | Self::Enum(s) => s.clone(),
+			Self::Number(n) => n.to_string(),
+			Self::Bool(b) => b.to_string(),
+			Self::Date(y, m, d) => format!("{:04}-{:02}-{:02}", y, m, d),
+		}
+	}
+}
+
+fn parse_date(value: &str) -> Option<FieldValue> {
+	let mut parts = value.splitn(3, '-');
+	let (year, month, day) = (parts.next()?, parts.next()?, parts.next()?);
+	if year.len() != 4 || month.len() != 2 || day.len() != 2 {
+		return None;
+	}
+
+	let year = year.parse::<i32>().ok()?;
+	let month = month.parse::<u8>().ok()?;
+	let day = day.parse::<u8>().ok()?;
+
+	let leap = (year % 4 == 0 && year % 100 != 0) || year % 400 == 0;
+	let days = match month {
+		1 | 3 | 5 | 7 | 8 | 10 | 12 => 31,
+		4 | 6 | 9 | 11 => 30,
+		2 if leap => 29,
+		2 => 28,
+		_ => return None,
+	};
+
+	(1..=days)
+		.contains(&day)
+		.then_some(FieldValue::Date(year, month, day))
+}
+
+pub(crate) fn validate_field(name: &str, ty: &FieldType) -> Result<()> {
+	// Field names end up in collection names and set items.
+	if name.is_empty()
+		|| name.contains('/')
+		|| name.contains(':')
+		|| name.contains(char::is_whitespace)
+	{
+		return Err(Error::Malformed(name.to_string()));
+	}
+
+	if let FieldType::Enum(variants) = ty {
+		if variants.is_empty() || variants.iter().any(|v| v.is_empty() || v.contains(',')) {
+			return Err(Error::Malformed(ty.to_string()));
+		}
+	}
+
+	Ok(())
+}
+
+impl<'a, R: Remote<'a>> Project<'a, R> {
+	/// Gets the project's custom field schema, by field name.
+	pub fn fields(&self) -> Result<IndexMap<String, FieldType>> {
+		let path = format!("{}/fields", self.meta_path);
+
+		self.workspace
+			.remote
+			.set_get_all(&path)?
+			.into_iter()
+			.map(|record| {
+				let message = record.message();
+				let (name, ty) = message
+					.split_once(':')
+					.ok_or_else(|| Error::Malformed(path.clone()))?;
+				Ok((name.to_string(), FieldType::try_from(ty)?))
+			})
+			.collect()
+	}
+
+	/// Defines a custom field on the project, replacing the type of
+	/// the field if it already exists. Values already set on tickets
+	/// aren't migrated, and may fail to parse after a type change.
+	///
+	/// Field names cannot be empty or contain `/`, `:`, or whitespace
+	/// characters. Returns [`Error::Exists`] if the field is already
+	/// defined with the same type.
+	pub fn define_field(&self, name: &str, ty: &FieldType) -> Result<R::Record> {
+		validate_field(name, ty)?;
+
+		let path = format!("{}/fields", self.meta_path);
+		let item = format!("{}:{}", name, ty.to_string());
+
+		if let Some(existing) = self.fields()?.get(name) {
+			if existing == ty {
+				return Err(Error::Exists(path, item));
+			}
+
+			self.workspace
+				.remote
+				.set_del_unchecked(&path, &format!("{}:{}", name, existing.to_string()))?;
+		}
+
+		self.workspace.remote.set_add_unchecked(&path, &item)
+	}
+
+	/// Removes a custom field from the project's schema, returning the
+	/// removal record, or `None` if the field wasn't defined. Values set
+	/// on tickets are kept, but are no longer accessible.
+	pub fn remove_field(&self, name: &str) -> Result<Option<R::Record>> {
+		match self.fields()?.get(name) {
+			Some(ty) => Ok(Some(self.workspace.remote.set_del_unchecked(
+				&format!("{}/fields", self.meta_path),
+				&format!("{}:{}", name, ty.to_string()),
+			)?)),
+			None => Ok(None),
+		}
+	}
+}
+
+impl<'a, R: Remote<'a>> Ticket<'a, R> {
+	/// Gets the value of a custom field on the ticket, along with the
+	/// record that set it, or `None` if the field has never been set.
+	///
+	/// Returns [`Error::NotFound`] if the field isn't defined in the
+	/// project's schema.
+	pub fn field(&self, name: &str) -> Result<Option<(FieldValue, R::Record)>> {
+		let ty = self.field_type(name)?;
+
+		self.workspace
+			.remote
+			.latest(&format!("{}/field/{}", self.path, name))?
+			.map(|record| Ok((ty.parse(&record.message())?, record)))
+			.transpose()
+	}
+
+	/// Sets the value of a custom field on the ticket.
+	///
+	/// Returns [`Error::NotFound`] if the field isn't defined in the
+	/// project's schema, and [`Error::Malformed`] if the value isn't
+	/// valid for the field's type.
+	pub fn set_field(&self, name: &str, value: &str) -> Result<R::Record> {
+		let value = self.field_type(name)?.parse(value)?;
+
+		self.workspace
+			.remote
+			.record_builder(&format!("{}/field/{}", self.path, name))
+			.commit(&value.to_string())
+	}
+
+	fn field_type(&self, name: &str) -> Result<FieldType> {
+		let project = self.project()?;
+
+		project.fields()?.swap_remove(name).ok_or_else(|| {
+			Error::NotFound(format!("{}/fields", project.meta_path), name.to_string())
+		})
+	}
+}
@@ -0,0 +1,102 @@
+//! On-disk format versioning.
+//!
+//! The version of the storage layout a workspace uses is stamped in the
+//! `meta/workspace/format` collection. Workspaces that predate the stamp
+//! are version `0`. [`Workspace::init`] stamps new workspaces,
+//! [`Workspace::open`] refuses to open workspaces it can't safely work
+//! with, and [`Workspace::migrate`] upgrades older layouts step by step,
+//! re-stamping the version after each step.
+//!
+//! Migrations operate on the [`Remote`] directly, as the higher-level
+//! APIs always assume the current layout.
+
+use crate::{Error, Record, RecordBuilder, Remote, Result, Workspace};
+
+const FORMAT_COLLECTION: &str = "meta/workspace/format";
+
+/// The format version written by this version of Minimap.
+pub const FORMAT_VERSION: u32 = 1;
+
+/// Gets the format version stamped in a remote.
+pub(crate) fn format_version<R: for<'b> Remote<'b>>(remote: &R) -> Result<u32> {
+	match remote.latest(FORMAT_COLLECTION)? {
+		Some(record) => record
+			.message()
+			.trim()
+			.parse()
+			.map_err(|_| Error::Malformed(FORMAT_COLLECTION.to_string())),
+		None => Ok(0),
+	}
+}
+
+/// Checks that a remote's format version can be opened. Every older
+/// version can be, as no migration has changed the layout yet.
+pub(crate) fn check_format<R: for<'b> Remote<'b>>(remote: &R) -> Result<()> {
+	match format_version(remote)? {
+		version if version > FORMAT_VERSION => {
+			Err(Error::UnsupportedFormat(version, FORMAT_VERSION))
+		}
+		_ => Ok(()),
+	}
+}
+
+/// Upgrades a remote from the given format version to the next one.
+fn migrate_from<R: for<'b> Remote<'b>>(_remote: &R, version: u32) -> Result<()> {
+	match version {
+		// Version 1 introduced the format stamp itself; the layout is unchanged.
+		0 => Ok(()),
+		_ => Err(Error::UnsupportedFormat(version, FORMAT_VERSION)),
+	}
+}
+
+impl<'a, R> Workspace<'a, R>
+where
+	R: for<'b> Remote<'b>,
+{
+	/// Gets the format version of the workspace.
+	pub fn format_version(&self) -> Result<u32> {
+		format_version(&self.remote)
+	}
+
+	/// Initializes a new workspace on an empty remote, stamping it with
+	/// the current [`FORMAT_VERSION`], and opens it.
+	///
+	/// Returns [`Error::Exists`] if the remote already holds a workspace,
+	/// which [`Workspace::open`] opens as is.
+	pub fn init(remote: R) -> Result<Self> {
+		if !remote.collections("meta")?.is_empty() || !remote.collections("project")?.is_empty() {
+			return Err(Error::Exists("meta".to_string(), "workspace".to_string()));
+		}
+
+		remote
+			.record_builder(FORMAT_COLLECTION)
+			.commit(&FORMAT_VERSION.to_string())?;
+		Self::open(remote)
+	}
+
+	/// Migrates a remote to the current [`FORMAT_VERSION`], one version
+	/// at a time, before it's opened with [`Workspace::open`]. Returns
+	/// the versions that were migrated to, which is empty if the remote
+	/// was already up to date.
+	///
+	/// Returns [`Error::UnsupportedFormat`] if the remote uses a newer
+	/// format than this version of Minimap knows about.
+	pub fn migrate(remote: &R) -> Result<Vec<u32>> {
+		let mut version = format_version(remote)?;
+		if version > FORMAT_VERSION {
+			return Err(Error::UnsupportedFormat(version, FORMAT_VERSION));
+		}
+
+		let mut migrated = Vec::new();
+		while version < FORMAT_VERSION {
+			migrate_from(remote, version)?;
+			version += 1;
+			remote
+				.record_builder(FORMAT_COLLECTION)
+				.commit(&version.to_string())?;
+			migrated.push(version);
+		}
+
+		Ok(migrated)
+	}
+}
@@ -0,0 +1,209 @@
+//! Workspace-wide dependency graphs.
+//!
+//! Dependencies are stored on the dependent ticket, so finding out what a
+//! ticket blocks means looking at every other ticket.
+//! [`Workspace::dependency_graph`] reads every ticket's dependencies once
+//! and assembles them into a [`DependencyGraph`].
+//!
+//! [`Ticket::add_dependency`] refuses to create cycles between tickets.
+//! Cycles can still appear when concurrent changes are merged, which
+//! [`DependencyGraph::topological_order`] reports.
+
+use crate::{Dependency, DependencyKind, Error, Remote, Result, Ticket, Workspace};
+use indexmap::IndexSet;
+use std::collections::{HashSet, VecDeque};
+
+/// A directed graph of dependencies between tickets. See
+/// [`Workspace::dependency_graph`].
+///
+/// Nodes are identified by ticket slug for tickets in the workspace
+/// (i.e. `_` dependencies), and by `minimap@<remote>@<slug>` for tickets
+/// in other workspaces. Edges point from a blocker to the ticket it blocks.
+#[derive(Debug, Clone, Default)]
+pub struct DependencyGraph {
+	nodes: IndexSet<String>,
+	/// `(blocker, blocked)` node indices.
+	edges: IndexSet<(usize, usize)>,
+}
+
+impl DependencyGraph {
+	fn add_edge(&mut self, blocker: String, blocked: String) {
+		let (blocker, _) = self.nodes.insert_full(blocker);
+		let (blocked, _) = self.nodes.insert_full(blocked);
+		self.edges.insert((blocker, blocked));
+	}
+
+	fn traverse(&self, node: &str, forward: bool) -> Vec<&str> {
+		let Some(start) = self.nodes.get_index_of(node) else {
+			return Vec::new();
+		};
+
+		let mut seen = HashSet::new();
+		let mut queue = VecDeque::from([start]);
+		let mut found = Vec::new();
+		while let Some(current) = queue.pop_front() {
+			for &(blocker, blocked) in &self.edges {
+				let next = match forward {
+					true if blocker == current => blocked,
+					false if blocked == current => blocker,
+					_ => continue,
+				};
+				if seen.insert(next) {
+					found.push(self.nodes[next].as_str());
+					queue.push_back(next);
+				}
+			}
+		}
+
+		found
+	}
+
+	/// Lists the nodes in the graph, i.e. every ticket that blocks
+	/// or is blocked by another, in the order they were found.
+	pub fn nodes(&self) -> impl Iterator<Item = &str> {
+		self.nodes.iter().map(String::as_str)
+	}
+
+	/// Lists the edges in the graph, as `(blocker, blocked)` pairs.
+	pub fn edges(&self) -> impl Iterator<Item = (&str, &str)> {
+		self.edges
+			.iter()
+			.map(|&(blocker, blocked)| (self.nodes[blocker].as_str(), self.nodes[blocked].as_str()))
+	}
+
+	/// Lists everything that blocks the given node, directly or
+	/// transitively, nearest first. Nodes that are part of a cycle
+	/// are listed as blocking themselves.
+	pub fn blockers(&self, node: &str) -> Vec<&str> {
+		self.traverse(node, false)
+	}
+
+	/// Lists everything that's blocked by the given node, directly
+	/// or transitively, nearest first. Nodes that are part of a cycle
+	/// are listed as blocked by themselves.
+	pub fn blocked_by(&self, node: &str) -> Vec<&str> {
+		self.traverse(node, true)
+	}
+
+	/// Orders the nodes such that every node comes after all of its
+	/// blockers, keeping the order in which nodes were found where
+	/// possible. Returns `None` if the graph contains a cycle.
+	pub fn topological_order(&self) -> Option<Vec<&str>> {
+		let mut remaining = vec![0; self.nodes.len()];
+		for &(_, blocked) in &self.edges {
+			remaining[blocked] += 1;
+		}
+
+		let mut ordered = Vec::with_capacity(self.nodes.len());
+		let mut done = vec![false; self.nodes.len()];
+		while ordered.len() < self.nodes.len() {
+			let next = (0..self.nodes.len()).find(|&node| !done[node] && remaining[node] == 0)?;
+			done[next] = true;
+			ordered.push(self.nodes[next].as_str());
+			for &(blocker, blocked) in &self.edges {
+				if blocker == next {
+					remaining[blocked] -= 1;
+				}
+			}
+		}
+
+		Some(ordered)
+	}
+
+	/// Renders the graph in Graphviz's DOT language.
+	pub fn to_dot(&self) -> String {
+		let quote = |node: &str| format!("\"{}\"", node.replace('\\', "\\\\").replace('"', "\\\""));
+
+		let mut dot = String::from("digraph dependencies {\n");
+		for node in self.nodes() {
+			dot.push_str(&format!("\t{};\n", quote(node)));
+		}
+		for (blocker, blocked) in self.edges() {
+			dot.push_str(&format!("\t{} -> {};\n", quote(blocker), quote(blocked)));
+		}
+		dot.push_str("}\n");
+
+		dot
+	}
+}
+
+impl<'a, R: Remote<'a>> Workspace<'a, R> {
+	/// Builds the graph of blocking dependencies between the workspace's
+	/// tickets (i.e. `_` dependencies). If `include_minimap` is set, dependencies
+	/// on tickets in other workspaces (`minimap` dependencies) are
+	/// included as well, although their own dependencies are not.
+	///
+	/// Dependencies on tickets that no longer exist are kept as-is.
+	pub fn dependency_graph(&'a self, include_minimap: bool) -> Result<DependencyGraph> {
+		let mut graph = DependencyGraph::default();
+		for ticket in self.tickets()? {
+			for Dependency {
+				origin,
+				endpoint,
+				kind,
+				..
+			} in ticket.dependencies()?
+			{
+				if kind != DependencyKind::Blocking {
+					continue;
+				}
+
+				let blocker = match origin.as_str() {
+					"_" => match self.ticket(&endpoint) {
+						Ok(blocker) => blocker.slug().to_string(),
+						Err(Error::NotFound(..)) => endpoint,
+						Err(err) => return Err(err),
+					},
+					"minimap" if include_minimap => format!("minimap@{}", endpoint),
+					_ => continue,
+				};
+				graph.add_edge(blocker, ticket.slug().to_string());
+			}
+		}
+
+		Ok(graph)
+	}
+}
+
+impl<'a, R: Remote<'a>> Ticket<'a, R> {
+	/// Returns [`Error::DependencyCycle`] if blocking on the given ticket
+	/// would create a cycle.
+	pub(crate) fn check_dependency_cycle(&self, endpoint: &str) -> Result<()> {
+		match self.dependency_cycle(endpoint)? {
+			Some(cycle) => Err(Error::DependencyCycle(cycle.join(" -> "))),
+			None => Ok(()),
+		}
+	}
+
+	/// Checks whether blocking on the given ticket would create a cycle,
+	/// returning the chain of ticket slugs (starting and ending with this
+	/// ticket) if so. Informational dependencies are ignored.
+	fn dependency_cycle(&self, endpoint: &str) -> Result<Option<Vec<String>>> {
+		let Ok(target) = self.workspace.ticket(endpoint) else {
+			return Ok(None);
+		};
+
+		let mut seen = HashSet::new();
+		let mut stack = vec![(target, vec![self.slug().to_string()])];
+		while let Some((ticket, mut path)) = stack.pop() {
+			path.push(ticket.slug().to_string());
+			if ticket.slug() == self.slug() {
+				return Ok(Some(path));
+			}
+			if !seen.insert(ticket.slug().to_string()) {
+				continue;
+			}
+
+			for dependency in ticket.dependencies()? {
+				if dependency.origin != "_" || dependency.kind != DependencyKind::Blocking {
+					continue;
+				}
+				if let Ok(next) = self.workspace.ticket(&dependency.endpoint) {
+					stack.push((next, path.clone()));
+				}
+			}
+		}
+
+		Ok(None)
+	}
+}
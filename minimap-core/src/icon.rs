@@ -0,0 +1,82 @@
+//! Project icons.
+//!
+//! A project's icon is stored as the `icon` attachment on the project's
+//! `meta/project/<slug>/icon` collection, with the icon's MIME type as
+//! the record message. Only common image formats are accepted, detected
+//! from the icon's data rather than trusted from the caller.
+
+use crate::{sniff_mime_type, Error, Project, Record, RecordBuilder, Remote, Result};
+
+/// The MIME types accepted for project icons.
+pub const ICON_MIME_TYPES: &[&str] = &[
+	"image/png",
+	"image/jpeg",
+	"image/gif",
+	"image/webp",
+	"image/svg+xml",
+];
+
+/// A project's icon.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Icon {
+	/// The icon's image data.
+	pub data: Vec<u8>,
+	/// The icon's MIME type; one of [`ICON_MIME_TYPES`].
+	pub mime: String,
+}
+
+impl<'a, R: Remote<'a>> Project<'a, R> {
+	/// Gets the project's icon, or `None` if it has none.
+	pub fn icon(&self) -> Result<Option<Icon>> {
+		let record = match self.workspace.remote.latest(&self.icon_path())? {
+			Some(record) => record,
+			None => return Ok(None),
+		};
+
+		// An empty message marks a removed icon.
+		let mime = record.message();
+		if mime.is_empty() {
+			return Ok(None);
+		}
+
+		Ok(record.attachment("icon")?.map(|data| Icon { data, mime }))
+	}
+
+	/// Sets the project's icon.
+	///
+	/// Returns [`Error::Malformed`] if the data isn't one of the accepted
+	/// image formats (see [`ICON_MIME_TYPES`]), and [`Error::TooLarge`]
+	/// if it exceeds the workspace's attachment size limit.
+	pub fn set_icon(&self, data: &[u8]) -> Result<R::Record> {
+		let mime = sniff_mime_type(data)
+			.filter(|mime| ICON_MIME_TYPES.contains(mime))
+			.ok_or_else(|| Error::Malformed(format!("{} (icon)", self.icon_path())))?;
+		self.workspace.check_attachment_size("icon", data)?;
+
+		self.workspace
+			.remote
+			.record_builder(&self.icon_path())
+			.upsert_attachment("icon", data)?
+			.commit(mime)
+	}
+
+	/// Removes the project's icon, returning the removal
+	/// record, or `None` if the project had no icon.
+	pub fn remove_icon(&self) -> Result<Option<R::Record>> {
+		if self.icon()?.is_none() {
+			return Ok(None);
+		}
+
+		Ok(Some(
+			self.workspace
+				.remote
+				.record_builder(&self.icon_path())
+				.remove_attachment("icon")?
+				.commit("")?,
+		))
+	}
+
+	fn icon_path(&self) -> String {
+		format!("{}/icon", self.meta_path)
+	}
+}
@@ -0,0 +1,142 @@
+//! Project-scoped label definitions.
+//!
+//! Each project defines its labels in the `meta/project/<slug>/labels`
+//! set, with items of the form `name`, `name<TAB>color` or
+//! `name<TAB>color<TAB>description`. Tickets may use labels that aren't
+//! defined, unless [`WorkspaceSettings::restrict_labels`] is set.
+
+use crate::{Error, Project, Record, Remote, Result, WorkspaceSettings};
+
+/// A label defined on a project.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, serde::Serialize)]
+pub struct LabelDefinition {
+	/// The label's name.
+	pub name: String,
+	/// The label's color, as `#rrggbb`.
+	pub color: Option<String>,
+	/// A description of what the label means.
+	pub description: Option<String>,
+}
+
+impl LabelDefinition {
+	fn parse(item: &str) -> Self {
+		let mut parts = item.splitn(3, '\t');
+		let mut next = || {
+			parts
+				.next()
+				.filter(|part| !part.is_empty())
+				.map(str::to_string)
+		};
+
+		Self {
+			name: next().unwrap_or_default(),
+			color: next(),
+			description: next(),
+		}
+	}
+}
+
+impl ToString for LabelDefinition {
+	fn to_string(&self) -> String {
+		match (&self.color, &self.description) {
+			(_, Some(description)) => format!(
+				"{}\t{}\t{}",
+				self.name,
+				self.color.as_deref().unwrap_or_default(),
+				description
+			),
+			(Some(color), None) => format!("{}\t{}", self.name, color),
+			(None, None) => self.name.clone(),
+		}
+	}
+}
+
+fn is_color(color: &str) -> bool {
+	color.len() == 7 && color.starts_with('#') && color[1..].chars().all(|c| c.is_ascii_hexdigit())
+}
+
+impl<'a, R: Remote<'a>> Project<'a, R> {
+	/// Lists the labels defined on the project, in order of definition.
+	pub fn labels(&self) -> Result<Vec<LabelDefinition>> {
+		Ok(self
+			.workspace
+			.remote
+			.set_get_all(&self.labels_path())?
+			.into_iter()
+			.map(|record| LabelDefinition::parse(&record.message()))
+			.collect())
+	}
+
+	/// Gets a label defined on the project by name.
+	pub fn label(&self, name: &str) -> Result<Option<LabelDefinition>> {
+		Ok(self.labels()?.into_iter().find(|label| label.name == name))
+	}
+
+	/// Defines a label on the project, replacing its color and description
+	/// if it's already defined. An empty description is the same as none.
+	///
+	/// Label names cannot be empty or contain tabs or newlines, colors must
+	/// be of the form `#rrggbb`, and descriptions cannot contain tabs or
+	/// newlines. Returns [`Error::Malformed`] otherwise, and
+	/// [`Error::Exists`] if the label is already defined the same way.
+	pub fn define_label(&self, name: &str, color: &str, description: &str) -> Result<R::Record> {
+		if name.trim().is_empty() || name.contains(['\t', '\n']) {
+			return Err(Error::Malformed(name.to_string()));
+		}
+		if !is_color(color) {
+			return Err(Error::Malformed(color.to_string()));
+		}
+		if description.contains(['\t', '\n']) {
+			return Err(Error::Malformed(description.to_string()));
+		}
+
+		let label = LabelDefinition {
+			name: name.to_string(),
+			color: Some(color.to_ascii_lowercase()),
+			description: Some(description.to_string()).filter(|d| !d.is_empty()),
+		};
+
+		let path = self.labels_path();
+		if let Some(existing) = self.label(name)? {
+			if existing == label {
+				return Err(Error::Exists(path, label.to_string()));
+			}
+
+			self.workspace
+				.remote
+				.set_del_unchecked(&path, &existing.to_string())?;
+		}
+
+		self.workspace
+			.remote
+			.set_add_unchecked(&path, &label.to_string())
+	}
+
+	/// Removes a label definition from the project, returning the removal
+	/// record, or `None` if the label wasn't defined. Tickets keep the label.
+	pub fn remove_label(&self, name: &str) -> Result<Option<R::Record>> {
+		match self.label(name)? {
+			Some(label) => Ok(Some(
+				self.workspace
+					.remote
+					.set_del_unchecked(&self.labels_path(), &label.to_string())?,
+			)),
+			None => Ok(None),
+		}
+	}
+
+	/// Checks that a label may be added to the project's tickets,
+	/// returning [`Error::NotFound`] if labels are restricted
+	/// (see [`WorkspaceSettings::restrict_labels`]) and it isn't defined.
+	pub(crate) fn check_label(&self, settings: &WorkspaceSettings, name: &str) -> Result<()> {
+		if settings.restrict_labels && self.label(name)?.is_none() {
+			return Err(Error::NotFound(self.labels_path(), name.to_string()));
+		}
+
+		Ok(())
+	}
+
+	fn labels_path(&self) -> String {
+		format!("{}/labels", self.meta_path)
+	}
+}
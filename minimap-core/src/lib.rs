@@ -7,21 +7,93 @@
 //! struct.
 #![deny(missing_docs, unsafe_code)]
 
+pub(crate) mod activity;
+pub(crate) mod archive;
+pub(crate) mod attachment;
+pub(crate) mod audit;
+pub(crate) mod blocked;
+pub(crate) mod bundle;
+pub(crate) mod clock;
+pub(crate) mod comment;
 pub(crate) mod deps;
+pub(crate) mod duplicate;
+pub(crate) mod field;
+pub(crate) mod format;
+pub(crate) mod graph;
+pub(crate) mod icon;
+pub(crate) mod label;
+pub(crate) mod link;
+pub(crate) mod member;
+pub(crate) mod page;
+pub(crate) mod query;
+pub(crate) mod reference;
 pub(crate) mod remote;
+pub(crate) mod search;
+pub(crate) mod settings;
+pub(crate) mod slug;
+pub(crate) mod stats;
+pub(crate) mod subtask;
+pub(crate) mod template;
+pub(crate) mod transfer;
+pub(crate) mod trash;
+pub(crate) mod tree;
+pub(crate) mod user;
+pub(crate) mod view;
+pub(crate) mod vote;
+pub(crate) mod workflow;
+pub(crate) mod worklog;
 
 /// Re-exports the version of git2 used by Minimap
 pub mod git2 {
 	pub use git2::*;
 }
 
+pub use activity::*;
+pub use attachment::*;
+pub use audit::*;
+pub use blocked::*;
+pub use bundle::*;
+pub use clock::*;
+pub use comment::*;
 pub use deps::*;
+pub use field::*;
+pub use format::*;
+pub use graph::*;
+pub use icon::*;
+pub use label::*;
+pub use link::*;
+pub use member::*;
+pub use page::*;
+pub use query::*;
+pub use reference::*;
+#[cfg(feature = "git")]
+pub use remote::credentials::CredentialProvider;
 #[cfg(feature = "git")]
 pub use remote::git::*;
 pub use remote::memory::*;
+pub use remote::namespaced::*;
+pub use remote::observed::*;
+pub use search::*;
+pub use settings::*;
+pub use slug::*;
+pub use stats::*;
+pub use subtask::*;
+pub use template::*;
+pub use trash::*;
+pub use tree::*;
+pub use user::*;
+pub use view::*;
+pub use workflow::*;
+pub use worklog::*;
 
 use indexmap::{IndexMap, IndexSet};
-use std::{collections::HashSet, hash::Hash, marker::PhantomData};
+use std::{
+	collections::{HashMap, HashSet},
+	hash::Hash,
+	marker::PhantomData,
+	path::PathBuf,
+	sync::Mutex,
+};
 
 /// The error type for all Minimap operations.
 #[derive(Debug, thiserror::Error)]
@@ -74,6 +146,67 @@ pub enum Error {
 	/// The project slug is malformed
 	#[error("malformed project slug: {0}")]
 	MalformedProjectSlug(String),
+	/// The ticket slug is malformed
+	#[error("malformed ticket slug: {0}")]
+	MalformedTicketSlug(String),
+	/// A pagination cursor could not be parsed, or was used
+	/// with a collection other than the one it was created for.
+	#[error("malformed cursor: {0}")]
+	MalformedCursor(String),
+	/// A page of records was requested with a limit of zero, which would
+	/// never get past the start of the collection.
+	#[error("page limit must be at least 1")]
+	ZeroPageLimit,
+	/// An HTTP request (e.g. of an [`HttpDependencyOrigin`]) failed, or
+	/// was answered with an error; holds the URL and the reason.
+	#[error("HTTP request to {0} failed: {1}")]
+	Http(String, String),
+	/// A ticket link could not be parsed (see [`TicketLink`]).
+	#[error("malformed ticket link: {0}")]
+	MalformedLink(String),
+	/// A ticket's state change was rejected by the workspace's
+	/// [`Workflow`]; holds the ticket slug, the current and
+	/// requested states, and the reason.
+	#[error("cannot transition ticket {0} from {1} to {2}: {3}")]
+	TransitionDenied(String, String, String, String),
+	/// A ticket was closed without the fields the workspace requires (see
+	/// [`WorkspaceSettings::required_fields`]); holds the ticket slug and
+	/// the missing fields.
+	#[error("cannot close ticket {0} without its required fields: {}", .1.join(", "))]
+	MissingRequiredFields(String, Vec<String>),
+	/// A destructive operation was attempted without being forced;
+	/// holds a description of the operation.
+	#[error("refusing to {0} without force")]
+	ForceRequired(String),
+	/// A user lacks the role required for an operation; holds
+	/// the user's email, the project slug, and the required role.
+	#[error("{0} must be at least a {2} of project {1}")]
+	PermissionDenied(String, String, String),
+	/// An attachment exceeds the workspace's size limit; holds the
+	/// attachment name, its size, and the limit (in bytes).
+	#[error("attachment {0} is {1} bytes, exceeding the limit of {2} bytes")]
+	TooLarge(String, u64, u64),
+	/// A project's chain of parents loops back on itself, or is deeper
+	/// than [`MAX_PROJECT_DEPTH`]; holds the chain of project slugs.
+	#[error("project hierarchy cycle: {0}")]
+	ProjectCycle(String),
+	/// Adding a dependency on another ticket in the workspace would make
+	/// the ticket (transitively) depend on itself; holds the chain of
+	/// ticket slugs.
+	#[error("dependency cycle: {0}")]
+	DependencyCycle(String),
+	/// Marking a ticket as a duplicate would make it (transitively) a
+	/// duplicate of itself; holds the chain of ticket slugs.
+	#[error("duplicate cycle: {0}")]
+	DuplicateCycle(String),
+	/// The workspace uses a newer on-disk format than this version of
+	/// Minimap supports; holds the workspace's and the supported version.
+	#[error("workspace format version {0} is newer than the supported version {1}")]
+	UnsupportedFormat(u32, u32),
+	/// A dependency origin plugin couldn't be loaded, or failed to
+	/// resolve a status; holds the plugin's path and the reason.
+	#[error("plugin {0} failed: {1}")]
+	Plugin(String, String),
 }
 
 /// The result type for all Minimap operations.
@@ -115,12 +248,55 @@ where
 		self.walk(collection)?.next().transpose()
 	}
 
+	/// Returns a single page of at most `limit` records from the collection,
+	/// in the same order as [`Remote::walk`]. If `after` is given, the page
+	/// starts directly after the record the cursor points to.
+	///
+	/// Returns [`Error::NotFound`] if the cursor's record is no longer
+	/// part of the collection, and [`Error::ZeroPageLimit`] if `limit`
+	/// is zero.
+	fn walk_page(
+		&'a self,
+		collection: &str,
+		after: Option<&Cursor>,
+		limit: usize,
+	) -> Result<Page<Self::Record>> {
+		page::walk_page(self, collection, after, limit)
+	}
+
 	/// Adds an item to a set. Does not check if the item already exists.
 	fn set_add_unchecked(&'a self, collection: &str, message: &str) -> Result<Self::Record>;
 
 	/// Removes an item from a set. Does not check if the item already exists.
 	fn set_del_unchecked(&'a self, collection: &str, message: &str) -> Result<Self::Record>;
 
+	/// Creates a new record builder for a set operation, as used by
+	/// [`Remote::set_add_unchecked`] and [`Remote::set_del_unchecked`].
+	/// Useful when the set record needs attachments or authorship of its own.
+	fn set_record_builder(&'a self, collection: &str, op: SetOperation) -> Self::RecordBuilder;
+
+	/// Lists the names of all collections that either are `prefix` itself
+	/// or are nested beneath it (i.e. start with `prefix/`).
+	fn collections(&'a self, prefix: &str) -> Result<Vec<String>>;
+
+	/// **Permanently** deletes a collection along with all of its records.
+	/// Returns `false` if the collection didn't exist.
+	///
+	/// This is not a set operation and leaves no trace in the history;
+	/// it's meant for cases where soft deletion isn't acceptable (e.g.
+	/// secrets having been committed). Note that for some remotes (e.g. Git)
+	/// the underlying data may linger until it's garbage collected.
+	fn delete_collection(&'a self, collection: &str) -> Result<bool>;
+
+	/// Brings the local copy of the remote up to date with changes
+	/// made elsewhere, returning the collections that changed (by
+	/// name, in order) and how.
+	///
+	/// Remotes that don't keep a local copy have nothing to fetch.
+	fn fetch(&'a self) -> Result<Vec<(String, FetchChange)>> {
+		Ok(Vec::new())
+	}
+
 	/// Get an iterator over a set of records in a collection, in order of creation.
 	/// The iterator returns both the record itself and the operation that was performed on it.
 	fn walk_set(&'a self, collection: &str) -> Result<Self::SetIterator>;
@@ -278,6 +454,14 @@ impl<'a, R: Remote<'a>> Iterator for SetWalkIterator<'a, R> {
 	}
 }
 
+/// Returns whether `collection` is `prefix` itself or nested beneath it.
+pub(crate) fn is_collection_under(collection: &str, prefix: &str) -> bool {
+	collection
+		.strip_prefix(prefix)
+		.map(|rest| rest.is_empty() || rest.starts_with('/'))
+		.unwrap_or(false)
+}
+
 /// A Minimap workspace holds all project tickets, assets, and other data.
 /// It is routinely synchronized with a local clone that Minimap manages
 /// itself - thus, it is not necessary nor recommended to manually clone
@@ -290,19 +474,72 @@ where
 	Self: 'a,
 {
 	remote: R,
+	slug_strategy: Box<dyn SlugStrategy>,
+	workflow: Workflow,
+	search_index: Mutex<SearchIndex>,
+	search_index_path: Option<PathBuf>,
+	/// See [`Workspace::with_audit_key`].
+	audit_key: Option<Vec<u8>>,
 	_phantom: PhantomData<&'a ()>,
 }
 
-impl<'a, R: Remote<'a>> Workspace<'a, R>
+// Opening a workspace reads from the remote before the workspace (and
+// with it, the `'a` lifetime) exists, so it needs to work for any lifetime.
+impl<'a, R> Workspace<'a, R>
 where
+	R: for<'b> Remote<'b>,
 	Self: 'a,
 {
 	/// Opens a workspace given the remote.
-	pub fn open(remote: R) -> Self {
-		Self {
+	///
+	/// New tickets are named sequentially (see [`SequentialSlugStrategy`]);
+	/// use [`Workspace::with_slug_strategy`] to change this.
+	///
+	/// Returns [`Error::UnsupportedFormat`] if the workspace was written
+	/// by a newer version of Minimap.
+	pub fn open(remote: R) -> Result<Self> {
+		format::check_format(&remote)?;
+
+		Ok(Self {
 			remote,
+			slug_strategy: Box::new(SequentialSlugStrategy),
+			workflow: Workflow::new(),
+			search_index: Mutex::new(SearchIndex::new()),
+			search_index_path: None,
+			audit_key: None,
 			_phantom: PhantomData,
-		}
+		})
+	}
+}
+
+impl<'a, R: Remote<'a>> Workspace<'a, R>
+where
+	Self: 'a,
+{
+	/// Sets the strategy used to generate slugs for
+	/// new projects and tickets in this workspace.
+	pub fn with_slug_strategy<S: SlugStrategy + 'static>(mut self, strategy: S) -> Self {
+		self.slug_strategy = Box::new(strategy);
+		self
+	}
+
+	/// Returns the strategy used to generate slugs.
+	#[inline]
+	pub fn slug_strategy(&self) -> &dyn SlugStrategy {
+		&*self.slug_strategy
+	}
+
+	/// Sets the workflow that guards ticket state transitions
+	/// in this workspace.
+	pub fn with_workflow(mut self, workflow: Workflow) -> Self {
+		self.workflow = workflow;
+		self
+	}
+
+	/// Returns the workflow that guards ticket state transitions.
+	#[inline]
+	pub fn workflow(&self) -> &Workflow {
+		&self.workflow
 	}
 
 	/// Returns a reference to the remote.
@@ -341,12 +578,7 @@ where
 			.set_find("meta/projects", slug)?
 			.map_err(|_| Error::NotFound("meta/projects".to_string(), slug.to_string()))?;
 
-		Ok(Project {
-			workspace: self,
-			slug: slug.to_string(),
-			meta_path: format!("meta/project/{}", slug),
-			path: format!("project/{}", slug),
-		})
+		Ok(self.project_handle(slug.to_string()))
 	}
 
 	/// Lists the project slugs that exist in the workspace
@@ -354,26 +586,60 @@ where
 		self.remote.set_get_all("meta/projects")
 	}
 
+	/// Lists the projects that exist in the workspace, as handles.
+	pub fn project_handles(&'a self) -> Result<Vec<Project<'a, R>>> {
+		Ok(self
+			.projects()?
+			.into_iter()
+			.map(|record| self.project_handle(record.message()))
+			.collect())
+	}
+
+	/// Lists the projects that exist in the workspace along with their
+	/// names and descriptions, in a single pass over the project set.
+	pub fn projects_with_meta(&'a self) -> Result<Vec<ProjectMeta<'a, R>>> {
+		self.projects()?
+			.into_iter()
+			.map(|record| {
+				let project = self.project_handle(record.message());
+				Ok(ProjectMeta {
+					name: project.name()?,
+					description: project.description()?,
+					record,
+					project,
+				})
+			})
+			.collect()
+	}
+
+	/// Builds a project handle without checking that the project exists.
+	fn project_handle(&'a self, slug: String) -> Project<'a, R> {
+		Project {
+			workspace: self,
+			meta_path: format!("meta/project/{}", slug),
+			path: format!("project/{}", slug),
+			slug,
+		}
+	}
+
 	/// Creates a project with the given slug.
 	/// If the project already exists, returns `Ok(Err(record))` with the
 	/// set record of the existing project.
 	///
+	/// The slug is passed through the workspace's [`SlugStrategy`] first,
+	/// so the created project's slug may differ from the requested one.
 	/// Project slugs cannot have `/` or whitespace characters.
 	pub fn create_project(
 		&'a self,
 		slug: &str,
 	) -> Result<::std::result::Result<Project<'a, R>, R::Record>> {
-		validate_project_slug(slug)?;
+		let slug = self.effective_slug_strategy()?.project_slug(slug);
+		validate_project_slug(&slug)?;
 
 		self.remote
-			.set_add("meta/projects", slug)
+			.set_add("meta/projects", &slug)
 			.map(|result| match result {
-				Ok(_) => Ok(Project {
-					workspace: self,
-					slug: slug.to_string(),
-					meta_path: format!("meta/project/{}", slug),
-					path: format!("project/{}", slug),
-				}),
+				Ok(_) => Ok(self.project_handle(slug)),
 				Err(record) => Err(record),
 			})
 	}
@@ -381,6 +647,33 @@ where
 	/// Gets a ticket by its slug.
 	/// Returns [`Error::NotFound`] if either the project or ticket do not exist.
 	pub fn ticket(&'a self, slug: &str) -> Result<Ticket<'a, R>> {
+		// Tickets that were moved to another project
+		// (see `Ticket::move_to`) are found at their new slug.
+		match self.ticket_unredirected(slug) {
+			Err(Error::NotFound(collection, item)) => match self.ticket_redirect(slug)? {
+				Some(redirect) => self.ticket(&redirect),
+				None => Err(Error::NotFound(collection, item)),
+			},
+			result => result,
+		}
+	}
+
+	fn ticket_unredirected(&'a self, slug: &str) -> Result<Ticket<'a, R>> {
+		// Tickets created with a non-default slug strategy are looked up
+		// by their recorded slug; everything else is `project-123`.
+		if validate_ticket_slug(slug).is_ok() {
+			let alias_path = format!("meta/ticket_slug/{}", slug);
+			if let Some(record) = self.remote.latest(&alias_path)? {
+				let message = record.message();
+				let (project_slug, ticket_id) = message
+					.split_once('/')
+					.and_then(|(project, id)| Some((project, id.parse::<u64>().ok()?)))
+					.ok_or_else(|| Error::Malformed(alias_path.clone()))?;
+
+				return self.project(project_slug)?.ticket(ticket_id);
+			}
+		}
+
 		let (project_slug, ticket_id) = slug
 			.rsplit_once('-')
 			.ok_or_else(|| Error::Malformed(slug.to_string()))?;
@@ -394,6 +687,17 @@ where
 		project.ticket(ticket_id)
 	}
 
+	/// Lists all (existing) tickets across all (existing) projects
+	/// in the workspace, ordered by project and then by ID.
+	pub fn tickets(&'a self) -> Result<Vec<Ticket<'a, R>>> {
+		let mut tickets = Vec::new();
+		for project in self.project_handles()? {
+			tickets.extend(project.tickets()?);
+		}
+
+		Ok(tickets)
+	}
+
 	/// **Soft-deletes** a project given its slug.
 	///
 	/// **NOTE:** Re-creating a project with the same slug will
@@ -407,12 +711,23 @@ where
 		&'a self,
 		slug: &str,
 	) -> Result<std::result::Result<R::Record, Option<R::Record>>> {
-		self.remote
-			.set_del("meta/projects", slug)
-			.map(|result| match result {
-				Ok((removed, _)) => Ok(removed),
-				Err(record) => Err(record),
-			})
+		self.delete_project_with_reason(slug, None)
+	}
+
+	/// **Soft-deletes** a project like [`Workspace::delete_project`],
+	/// recording the given reason in the audit log.
+	pub fn delete_project_with_reason(
+		&'a self,
+		slug: &str,
+		reason: Option<&str>,
+	) -> Result<std::result::Result<R::Record, Option<R::Record>>> {
+		match self.remote.set_del("meta/projects", slug)? {
+			Ok((removed, _)) => {
+				self.audit(AuditOperation::Delete, slug, reason)?;
+				Ok(Ok(removed))
+			}
+			Err(record) => Ok(Err(record)),
+		}
 	}
 }
 
@@ -431,6 +746,8 @@ pub trait Record: Clone + Sized + Hash + PartialEq + Eq + std::fmt::Debug {
 	fn timestamp(&self) -> i64;
 	/// Gets an attachment by its name.
 	fn attachment(&self, name: &str) -> Result<Option<Vec<u8>>>;
+	/// Lists the names of all attachments present on the record.
+	fn attachment_names(&self) -> Result<Vec<String>>;
 }
 
 /// Builds a record (with attachments) in order to submit a
@@ -451,6 +768,14 @@ where
 	/// Removes an attachment from the collection entirely upon record.
 	/// Future records will not contain this attachment.
 	fn remove_attachment(self, name: &str) -> Result<Self>;
+
+	/// Sets the author and (unix) timestamp of the record, rather than
+	/// using the remote's own user and the current time. Used to replay
+	/// records from elsewhere (see [`Workspace::import`]); remotes that
+	/// cannot store arbitrary authorship ignore it.
+	fn authored(self, _author: &str, _email: &str, _timestamp: i64) -> Self {
+		self
+	}
 }
 
 /// The type of operation performed on a record in a set.
@@ -462,6 +787,17 @@ pub enum SetOperation {
 	Del,
 }
 
+/// How a collection changed elsewhere. See [`Remote::fetch`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FetchChange {
+	/// The collection was created, with the given number of records.
+	Created(usize),
+	/// The given number of records were added to the collection.
+	Updated(usize),
+	/// The collection was deleted.
+	Deleted,
+}
+
 /// A Minimap project. Projects are a collection of tickets,
 /// which are a collection of comments, attachments, and other
 /// such resources.
@@ -472,6 +808,19 @@ pub struct Project<'a, R: Remote<'a>> {
 	path: String,
 }
 
+/// A project along with its metadata, as returned by
+/// [`Workspace::projects_with_meta`].
+pub struct ProjectMeta<'a, R: Remote<'a>> {
+	/// The project itself.
+	pub project: Project<'a, R>,
+	/// The set record that created the project.
+	pub record: R::Record,
+	/// The latest name record of the project, if any.
+	pub name: Option<R::Record>,
+	/// The latest description record of the project, if any.
+	pub description: Option<R::Record>,
+}
+
 fn validate_project_slug(slug: &str) -> Result<()> {
 	// Slugs cannot have `/` or whitespace characters.
 	if slug.contains('/') || slug.contains(char::is_whitespace) {
@@ -481,6 +830,15 @@ fn validate_project_slug(slug: &str) -> Result<()> {
 	Ok(())
 }
 
+fn validate_ticket_slug(slug: &str) -> Result<()> {
+	// Same rules as project slugs.
+	if slug.is_empty() || slug.contains('/') || slug.contains(char::is_whitespace) {
+		return Err(Error::MalformedTicketSlug(slug.to_string()));
+	}
+
+	Ok(())
+}
+
 impl<'a, R: Remote<'a>> Project<'a, R> {
 	/// Gets the slug of the project.
 	#[inline]
@@ -532,27 +890,24 @@ impl<'a, R: Remote<'a>> Project<'a, R> {
 
 	/// Creates a ticket in the project.
 	pub fn create_ticket(&self) -> Result<Ticket<'a, R>> {
-		// First, get a new ticket ID by incrementing the ticket counter.
-		// The ticket counter is stored in the meta/project/<slug>/ticket_counter
-		// collection, and is the head record with a single integer value.
-		// If the collection doesn't exist, the counter starts at 1.
-		// The ticket counter is not a set, it's just a running count.
 		let ticket_counter_path = format!("{}/ticket_counter", self.meta_path);
-		let ticket_counter = self
-			.workspace
-			.remote
-			.latest(&ticket_counter_path)?
-			.map(|record| {
-				record
-					.message()
-					.parse::<u64>()
-					.map_err(|_| Error::Malformed(ticket_counter_path.clone()))
-			})
-			.transpose()?
-			.unwrap_or(0);
-
-		let ticket_id = ticket_counter + 1;
-		let ticket_slug = format!("{}-{}", self.slug, ticket_id);
+		let ticket_id = self.next_ticket_id()?;
+		let settings = self.workspace.settings()?;
+		let ticket_slug = settings
+			.slug_policy
+			.map_or(&*self.workspace.slug_strategy, |policy| policy.strategy())
+			.ticket_slug(&self.slug, ticket_id);
+		validate_ticket_slug(&ticket_slug)?;
+
+		// Non-default slugs are recorded so that the ticket can be found by
+		// its slug, and so that the slug can be found given the ticket.
+		let alias_path = format!("meta/ticket_slug/{}", ticket_slug);
+		let is_alias = ticket_slug != format!("{}-{}", self.slug, ticket_id);
+		if is_alias {
+			if let Some(record) = self.workspace.remote.latest(&alias_path)? {
+				return Err(Error::Exists(alias_path, record.message()));
+			}
+		}
 
 		// First, we try to increment the ID. The worst case here is that we have a skipped ticket
 		// count if the tickets set add fails, which is fine - because in the inverse cass (where
@@ -569,14 +924,72 @@ impl<'a, R: Remote<'a>> Project<'a, R> {
 			.set_add(&format!("{}/tickets", self.path), &ticket_id.to_string())?
 			.map_err(|_| Error::Malformed(format!("{}/tickets", self.path)))?;
 
+		let path = format!("{}/ticket/{}", self.path, ticket_id);
+
+		if is_alias {
+			self.workspace
+				.remote
+				.record_builder(&format!("{}/slug", path))
+				.commit(&ticket_slug)?;
+			self.workspace
+				.remote
+				.record_builder(&alias_path)
+				.commit(&format!("{}/{}", self.slug, ticket_id))?;
+		}
+
+		if settings.default_state != TicketState::Open {
+			self.workspace
+				.remote
+				.record_builder(&format!("{}/state", path))
+				.commit(&settings.default_state.to_string())?;
+		}
+
 		Ok(Ticket {
 			workspace: self.workspace,
 			slug: ticket_slug,
 			id: ticket_id,
-			path: format!("{}/ticket/{}", self.path, ticket_id),
+			path,
 		})
 	}
 
+	/// Gets the slug that the next ticket created in the
+	/// project will have, without creating anything.
+	pub fn next_ticket_slug(&self) -> Result<String> {
+		let ticket_id = self.next_ticket_id()?;
+		let ticket_slug = self
+			.workspace
+			.settings()?
+			.slug_policy
+			.map_or(&*self.workspace.slug_strategy, |policy| policy.strategy())
+			.ticket_slug(&self.slug, ticket_id);
+		validate_ticket_slug(&ticket_slug)?;
+
+		Ok(ticket_slug)
+	}
+
+	/// Gets the ID of the next ticket by incrementing the ticket counter.
+	/// The ticket counter is stored in the meta/project/<slug>/ticket_counter
+	/// collection, and is the head record with a single integer value.
+	/// If the collection doesn't exist, the counter starts at 1.
+	/// The ticket counter is not a set, it's just a running count.
+	fn next_ticket_id(&self) -> Result<u64> {
+		let ticket_counter_path = format!("{}/ticket_counter", self.meta_path);
+		let ticket_counter = self
+			.workspace
+			.remote
+			.latest(&ticket_counter_path)?
+			.map(|record| {
+				record
+					.message()
+					.parse::<u64>()
+					.map_err(|_| Error::Malformed(ticket_counter_path.clone()))
+			})
+			.transpose()?
+			.unwrap_or(0);
+
+		Ok(ticket_counter + 1)
+	}
+
 	/// Gets a ticket by its ID.
 	pub fn ticket(&self, id: u64) -> Result<Ticket<'a, R>> {
 		// First, check if the ticket exists.
@@ -587,12 +1000,44 @@ impl<'a, R: Remote<'a>> Project<'a, R> {
 
 		Ok(Ticket {
 			workspace: self.workspace,
-			slug: format!("{}-{}", self.slug, id),
+			slug: self.ticket_slug(id)?,
 			id,
 			path: format!("{}/ticket/{}", self.path, id),
 		})
 	}
 
+	/// Lists all (existing) tickets in the project, ordered by ID.
+	pub fn tickets(&self) -> Result<Vec<Ticket<'a, R>>> {
+		let path = format!("{}/tickets", self.path);
+
+		let mut ids = self
+			.workspace
+			.remote
+			.set_get_all(&path)?
+			.into_iter()
+			.map(|record| {
+				record
+					.message()
+					.parse::<u64>()
+					.map_err(|_| Error::Malformed(path.clone()))
+			})
+			.collect::<Result<Vec<_>>>()?;
+		ids.sort_unstable();
+
+		ids.into_iter().map(|id| self.ticket(id)).collect()
+	}
+
+	/// Gets the slug of a ticket given its ID, which is either the
+	/// slug recorded at creation time or the default `project-123`.
+	fn ticket_slug(&self, id: u64) -> Result<String> {
+		Ok(self
+			.workspace
+			.remote
+			.latest(&format!("{}/ticket/{}/slug", self.path, id))?
+			.map(|record| record.message())
+			.unwrap_or_else(|| format!("{}-{}", self.slug, id)))
+	}
+
 	/// Creates a (sub)-project with the given slug.
 	/// If the project already exists, returns `Ok(Err(record))` with the
 	/// set record of the existing project.
@@ -619,17 +1064,13 @@ impl<'a, R: Remote<'a>> Project<'a, R> {
 
 	/// Gets the parent project of this project, or `None`
 	/// if the project is a root project.
+	///
+	/// To walk all the way up the hierarchy, use [`Project::ancestors`],
+	/// which guards against cycles.
 	pub fn parent(&self) -> Result<Option<Project<'a, R>>> {
-		self.workspace
-			.remote
-			.latest(&format!("{}/parent", self.meta_path))?
-			.map_or_else(
-				|| Ok(None),
-				|record| {
-					let slug = record.message();
-					Ok(Some(self.workspace.project(&slug)?))
-				},
-			)
+		self.parent_slug()?
+			.map(|slug| self.workspace.project(&slug))
+			.transpose()
 	}
 
 	/// **Soft-deletes** a ticket.
@@ -645,13 +1086,57 @@ impl<'a, R: Remote<'a>> Project<'a, R> {
 		&self,
 		id: u64,
 	) -> Result<std::result::Result<R::Record, Option<R::Record>>> {
-		self.workspace
+		self.delete_ticket_with_reason(id, None)
+	}
+
+	/// **Soft-deletes** a ticket like [`Project::delete_ticket`],
+	/// recording the given reason in the audit log.
+	pub fn delete_ticket_with_reason(
+		&self,
+		id: u64,
+		reason: Option<&str>,
+	) -> Result<std::result::Result<R::Record, Option<R::Record>>> {
+		match self
+			.workspace
 			.remote
-			.set_del(&format!("{}/tickets", self.path), &id.to_string())
-			.map(|result| match result {
-				Ok((removed, _)) => Ok(removed),
-				Err(record) => Err(record),
-			})
+			.set_del(&format!("{}/tickets", self.path), &id.to_string())?
+		{
+			Ok((removed, _)) => {
+				self.workspace
+					.audit(AuditOperation::Delete, &self.ticket_slug(id)?, reason)?;
+				Ok(Ok(removed))
+			}
+			Err(record) => Ok(Err(record)),
+		}
+	}
+
+	/// Restores a **soft-deleted** ticket given its ID.
+	///
+	/// After unwrapping the outer error, returns `Ok(record)` with the
+	/// newly created set add record, `Err(Some(record))` with the creation
+	/// record if the ticket isn't deleted, or `Err(None)` if the ticket
+	/// never existed. Returns [`Error::NotFound`] if the ticket has been
+	/// purged from the [`Trash`], or moved to another project.
+	pub fn restore_ticket(
+		&self,
+		id: u64,
+	) -> Result<std::result::Result<R::Record, Option<R::Record>>> {
+		let path = format!("{}/tickets", self.path);
+
+		match self.workspace.remote.set_find(&path, &id.to_string())? {
+			Ok(record) => Ok(Err(Some(record))),
+			Err(None) => Ok(Err(None)),
+			Err(Some(deletion)) => {
+				if self.workspace.trash().purged()?.contains(&deletion.id()) {
+					return Err(Error::NotFound(path, id.to_string()));
+				}
+
+				Ok(Ok(self
+					.workspace
+					.remote
+					.set_add_unchecked(&path, &id.to_string())?))
+			}
+		}
 	}
 }
 
@@ -676,6 +1161,18 @@ impl<'a, R: Remote<'a>> Ticket<'a, R> {
 		self.id
 	}
 
+	/// Gets the project the ticket belongs to.
+	pub fn project(&self) -> Result<Project<'a, R>> {
+		// Ticket paths are always `project/<slug>/ticket/<id>`.
+		let project_slug = self
+			.path
+			.split('/')
+			.nth(1)
+			.ok_or_else(|| Error::Malformed(self.path.clone()))?;
+
+		self.workspace.project(project_slug)
+	}
+
 	/// Gets this ticket's record
 	pub fn record(&self) -> Result<Option<R::Record>> {
 		self.workspace.remote.latest(&self.path)
@@ -696,12 +1193,49 @@ impl<'a, R: Remote<'a>> Ticket<'a, R> {
 			.commit(name)
 	}
 
-	/// Gets an iterator over all comments on the ticket,
-	/// in reverse order from latest to oldest.
-	pub fn comments(&self) -> Result<R::Iterator> {
+	/// Gets the description (body) of the ticket. Descriptions are
+	/// free-form markdown text.
+	pub fn description(&self) -> Result<Option<R::Record>> {
+		self.workspace
+			.remote
+			.latest(&format!("{}/description", self.path))
+	}
+
+	/// Sets the description (body) of the ticket.
+	pub fn set_description(&self, description: &str) -> Result<R::Record> {
 		self.workspace
 			.remote
-			.walk(&format!("{}/comment", self.path))
+			.record_builder(&format!("{}/description", self.path))
+			.commit(description)
+	}
+
+	/// Gets an iterator over all comments on the ticket,
+	/// in reverse order from latest to oldest. Deleted comments
+	/// are skipped; see [`Ticket::comments_audit`] to include them.
+	pub fn comments(&self) -> Result<CommentIterator<'a, R>> {
+		Ok(CommentIterator {
+			inner: self.comments_log()?,
+			tombstones: self.tombstones()?,
+		})
+	}
+
+	/// Gets a single page of comments on the ticket, in reverse
+	/// order from latest to oldest. Pass the returned [`Page::next`]
+	/// cursor back in to fetch the following page.
+	///
+	/// Deleted comments are removed from the page after it's fetched,
+	/// so a page may hold fewer than `limit` comments even if more follow.
+	pub fn comments_page(&self, after: Option<&Cursor>, limit: usize) -> Result<Page<R::Record>> {
+		let mut page =
+			self.workspace
+				.remote
+				.walk_page(&format!("{}/comment", self.path), after, limit)?;
+
+		let tombstones = self.tombstones()?;
+		page.records
+			.retain(|record| !tombstones.contains_key(&record.id()));
+
+		Ok(page)
 	}
 
 	/// Creates a new comment on the ticket.
@@ -712,13 +1246,24 @@ impl<'a, R: Remote<'a>> Ticket<'a, R> {
 			.commit(comment)
 	}
 
-	/// Creates a new attachment on the ticket.
+	/// Creates a new attachment on the ticket, recording its metadata
+	/// (see [`Ticket::attachment_info`]).
+	///
+	/// Returns [`Error::TooLarge`] if the attachment exceeds the
+	/// workspace's [`WorkspaceSettings::max_attachment_size`].
 	pub fn upsert_attachment(&self, name: &str, data: &[u8]) -> Result<R::Record> {
-		self.workspace
+		self.workspace.check_attachment_size(name, data)?;
+
+		let record = self
+			.workspace
 			.remote
 			.record_builder(&format!("{}/attachment", self.path))
 			.upsert_attachment(name, data)?
-			.commit(&format!("+{}", name))
+			.commit(&format!("+{}", name))?;
+
+		self.record_attachment_info(name, data)?;
+
+		Ok(record)
 	}
 
 	/// Removes an attachment from the ticket.
@@ -729,17 +1274,16 @@ impl<'a, R: Remote<'a>> Ticket<'a, R> {
 		&self,
 		name: &str,
 	) -> Result<std::result::Result<R::Record, Option<R::Record>>> {
-		let attachment_path = format!("{}/attachment", self.path);
-
-		match self.workspace.remote.set_find(&attachment_path, name)? {
-			Ok(_) => {}
-			Err(record) => return Ok(Err(record)),
+		match self.attachment_record(name)? {
+			Some((true, _)) => {}
+			Some((false, record)) => return Ok(Err(Some(record))),
+			None => return Ok(Err(None)),
 		}
 
 		Ok(Ok(self
 			.workspace
 			.remote
-			.record_builder(&attachment_path)
+			.record_builder(&format!("{}/attachment", self.path))
 			.remove_attachment(name)?
 			.commit(&format!("-{}", name))?))
 	}
@@ -768,25 +1312,65 @@ impl<'a, R: Remote<'a>> Ticket<'a, R> {
 			.map_or_else(
 				|| Ok((TicketState::Open, None)),
 				|record| {
-					let state = match record.message().as_str() {
-						"open" => TicketState::Open,
-						"closed" => TicketState::Closed,
-						_ => return Err(Error::Malformed(format!("{}/state", self.path))),
-					};
+					let (state, _) = parse_state_message(&record.message())
+						.ok_or_else(|| Error::Malformed(format!("{}/state", self.path)))?;
 					Ok((state, Some(record)))
 				},
 			)
 	}
 
+	/// Gets the reason given for the ticket's latest state change,
+	/// or `None` if there was none (see [`Ticket::set_state_with_reason`]).
+	pub fn state_reason(&self) -> Result<Option<String>> {
+		match self.state()?.1 {
+			Some(record) => {
+				Ok(parse_state_message(&record.message()).and_then(|(_, reason)| reason))
+			}
+			None => Ok(None),
+		}
+	}
+
 	/// Sets the state of a ticket.
+	///
+	/// Returns [`Error::TransitionDenied`] if the project's (or
+	/// workspace's) [`Workflow`] doesn't allow the transition.
 	pub fn set_state(&self, state: TicketState) -> Result<R::Record> {
+		self.set_state_with_reason(state, "")
+	}
+
+	/// Sets the state of a ticket, recording the reason for the change
+	/// (e.g. `wontfix`) alongside it. An empty reason is the same as
+	/// calling [`Ticket::set_state`].
+	///
+	/// Returns [`Error::TransitionDenied`] if the project's (or
+	/// workspace's) [`Workflow`] doesn't allow the transition, and
+	/// [`Error::MissingRequiredFields`] if the ticket is being closed
+	/// while missing required fields (see
+	/// [`WorkspaceSettings::required_fields`]).
+	pub fn set_state_with_reason(&self, state: TicketState, reason: &str) -> Result<R::Record> {
+		match self.project()?.workflow()? {
+			Some(workflow) => workflow.check(self, state)?,
+			None => self.workspace.workflow.check(self, state)?,
+		}
+
+		if state == TicketState::Closed && !self.is_closed()? {
+			let missing = self.missing_required_fields()?;
+			if !missing.is_empty() {
+				return Err(Error::MissingRequiredFields(self.slug.clone(), missing));
+			}
+		}
+
+		let reason = reason.trim();
+		let message = if reason.is_empty() {
+			state.to_string()
+		} else {
+			format!("{}\n\n{}", state.to_string(), reason)
+		};
+
 		self.workspace
 			.remote
 			.record_builder(&format!("{}/state", self.path))
-			.commit(match state {
-				TicketState::Open => "open",
-				TicketState::Closed => "closed",
-			})
+			.commit(&message)
 	}
 
 	/// Returns if the ticket is open.
@@ -801,6 +1385,89 @@ impl<'a, R: Remote<'a>> Ticket<'a, R> {
 		Ok(self.state()?.0 == TicketState::Closed)
 	}
 
+	/// Adds a label to the ticket.
+	///
+	/// Returns the record of the label addition if created,
+	/// or the record of the existing label if it already exists.
+	/// If the workspace restricts labels to those defined on the project
+	/// (see [`WorkspaceSettings::restrict_labels`]), returns
+	/// [`Error::NotFound`] for undefined labels.
+	pub fn add_label(&self, label: &str) -> Result<R::Record> {
+		self.project()?
+			.check_label(&self.workspace.settings()?, label)?;
+
+		self.workspace
+			.remote
+			.set_add(&format!("{}/labels", self.path), label)?
+			.map_or_else(Ok, |(r, _)| Ok(r))
+	}
+
+	/// Removes a label from the ticket.
+	///
+	/// Returns the record of the label removal if created,
+	/// or None if the ticket didn't have the label.
+	pub fn remove_label(&self, label: &str) -> Result<Option<R::Record>> {
+		self.workspace
+			.remote
+			.set_del(&format!("{}/labels", self.path), label)?
+			.map_or_else(|_| Ok(None), |(r, _)| Ok(Some(r)))
+	}
+
+	/// Lists all labels on the ticket.
+	pub fn labels(&self) -> Result<Vec<String>> {
+		self.workspace
+			.remote
+			.walk_set_present(&format!("{}/labels", self.path))?
+			.map(|r| Ok(r?.message()))
+			.collect()
+	}
+
+	/// Assigns a user to the ticket. Users are identified by
+	/// free-form strings (typically email addresses).
+	///
+	/// Returns the record of the assignment if created,
+	/// or the record of the existing assignment if it already exists.
+	pub fn add_assignee(&self, user: &str) -> Result<R::Record> {
+		self.workspace
+			.remote
+			.set_add(&format!("{}/assignees", self.path), user)?
+			.map_or_else(Ok, |(r, _)| Ok(r))
+	}
+
+	/// Unassigns a user from the ticket.
+	///
+	/// Returns the record of the unassignment if created,
+	/// or None if the user wasn't assigned.
+	pub fn remove_assignee(&self, user: &str) -> Result<Option<R::Record>> {
+		self.workspace
+			.remote
+			.set_del(&format!("{}/assignees", self.path), user)?
+			.map_or_else(|_| Ok(None), |(r, _)| Ok(Some(r)))
+	}
+
+	/// Lists all users assigned to the ticket.
+	pub fn assignees(&self) -> Result<Vec<String>> {
+		self.workspace
+			.remote
+			.walk_set_present(&format!("{}/assignees", self.path))?
+			.map(|r| Ok(r?.message()))
+			.collect()
+	}
+
+	/// Gets the set record that created the ticket.
+	pub fn created(&self) -> Result<R::Record> {
+		let tickets_path = self
+			.path
+			.rsplit_once("/ticket/")
+			.map(|(project, _)| format!("{}/tickets", project))
+			.ok_or_else(|| Error::Malformed(self.path.clone()))?;
+
+		self.workspace
+			.remote
+			.set_find(&tickets_path, &self.id.to_string())?
+			.map_err(|_| Error::NotFound(tickets_path, self.id.to_string()))
+	}
+
 	/// Adds a dependency for the ticket.
 	///
 	/// Dependencies are tuples of `(origin, endpoint)`,
@@ -813,11 +1480,26 @@ impl<'a, R: Remote<'a>> Ticket<'a, R> {
 	/// and can be used with a `project-#` (ticket slug) endpoint to
 	/// create dependencies on other tickets in the same workspace.
 	///
+	/// New dependencies are [`DependencyKind::Blocking`]; re-adding an
+	/// existing dependency keeps its kind (see
+	/// [`Ticket::add_dependency_with_kind`]).
+	///
+	/// Blocking dependencies on other tickets may not form cycles. Returns
+	/// [`Error::DependencyCycle`] if the other ticket already depends
+	/// on this one, directly or transitively.
+	///
 	/// Returns the record of the dependency addition if created,
 	/// or the record of the existing dependency if it already exists.
 	pub fn add_dependency(&self, origin: &str, endpoint: &str) -> Result<R::Record> {
 		validate_origin(origin)?;
+		if origin == "_" && self.dependency_kind(origin, endpoint)? == DependencyKind::Blocking {
+			self.check_dependency_cycle(endpoint)?;
+		}
+
+		self.insert_dependency(origin, endpoint)
+	}
 
+	pub(crate) fn insert_dependency(&self, origin: &str, endpoint: &str) -> Result<R::Record> {
 		self.workspace
 			.remote
 			.set_add(
@@ -836,6 +1518,9 @@ impl<'a, R: Remote<'a>> Ticket<'a, R> {
 	pub fn remove_dependency(&self, origin: &str, endpoint: &str) -> Result<Option<R::Record>> {
 		validate_origin(origin)?;
 
+		self.set_dependency_note(origin, endpoint, "")?;
+		self.set_dependency_kind(origin, endpoint, DependencyKind::Blocking)?;
+
 		self.workspace
 			.remote
 			.set_del(
@@ -845,10 +1530,136 @@ impl<'a, R: Remote<'a>> Ticket<'a, R> {
 			.map_or_else(|_| Ok(None), |(r, _)| Ok(Some(r)))
 	}
 
-	/// Lists all dependencies for the ticket.
+	/// Adds a dependency for the ticket along with a human-readable
+	/// note explaining it (e.g. why the ticket is blocked). If the
+	/// dependency already exists, its note is replaced. An empty
+	/// note removes the note.
+	///
+	/// See [`Ticket::add_dependency`] for more information on dependencies.
+	pub fn add_dependency_with_note(
+		&self,
+		origin: &str,
+		endpoint: &str,
+		note: &str,
+	) -> Result<R::Record> {
+		let record = self.add_dependency(origin, endpoint)?;
+		self.set_dependency_note(origin, endpoint, note)?;
+		Ok(record)
+	}
+
+	/// Adds a dependency of the given kind for the ticket, changing the
+	/// kind of the dependency if it already exists.
 	///
 	/// See [`Ticket::add_dependency`] for more information on dependencies.
-	pub fn dependencies(&self) -> Result<Vec<(String, String, R::Record)>> {
+	pub fn add_dependency_with_kind(
+		&self,
+		origin: &str,
+		endpoint: &str,
+		kind: DependencyKind,
+	) -> Result<R::Record> {
+		validate_origin(origin)?;
+		if kind == DependencyKind::Blocking && origin == "_" {
+			self.check_dependency_cycle(endpoint)?;
+		}
+
+		let record = self.insert_dependency(origin, endpoint)?;
+		self.set_dependency_kind(origin, endpoint, kind)?;
+		Ok(record)
+	}
+
+	/// Notes are stored in a separate set, as `origin@endpoint<TAB>note`,
+	/// so that the dependency set itself keeps its plain items.
+	fn set_dependency_note(&self, origin: &str, endpoint: &str, note: &str) -> Result<()> {
+		let path = format!("{}/dependency_notes", self.path);
+		let prefix = format!("{}@{}\t", origin, endpoint);
+		let item = format!("{}{}", prefix, note);
+
+		let existing = self
+			.workspace
+			.remote
+			.set_get_all(&path)?
+			.into_iter()
+			.map(|record| record.message())
+			.filter(|message| message.starts_with(&prefix))
+			.collect::<Vec<_>>();
+		if !note.is_empty() && existing == [item.clone()] {
+			return Ok(());
+		}
+
+		for message in existing {
+			self.workspace.remote.set_del_unchecked(&path, &message)?;
+		}
+		if !note.is_empty() {
+			self.workspace.remote.set_add_unchecked(&path, &item)?;
+		}
+
+		Ok(())
+	}
+
+	/// Gets the kind of a dependency, which is blocking
+	/// for dependencies that don't exist.
+	fn dependency_kind(&self, origin: &str, endpoint: &str) -> Result<DependencyKind> {
+		Ok(
+			if self
+				.informational_dependencies()?
+				.contains(&format!("{}@{}", origin, endpoint))
+			{
+				DependencyKind::Informational
+			} else {
+				DependencyKind::Blocking
+			},
+		)
+	}
+
+	/// Informational dependencies are listed in a separate set, as
+	/// `origin@endpoint`; blocking ones, the default, aren't listed.
+	fn set_dependency_kind(
+		&self,
+		origin: &str,
+		endpoint: &str,
+		kind: DependencyKind,
+	) -> Result<()> {
+		let path = format!("{}/informational_dependencies", self.path);
+		let item = format!("{}@{}", origin, endpoint);
+		match kind {
+			DependencyKind::Blocking => {
+				self.workspace.remote.set_del(&path, &item)?.ok();
+			}
+			DependencyKind::Informational => {
+				self.workspace.remote.set_add(&path, &item)?.ok();
+			}
+		}
+
+		Ok(())
+	}
+
+	fn informational_dependencies(&self) -> Result<HashSet<String>> {
+		Ok(self
+			.workspace
+			.remote
+			.set_get_all(&format!("{}/informational_dependencies", self.path))?
+			.into_iter()
+			.map(|record| record.message())
+			.collect())
+	}
+
+	/// Lists all dependencies for the ticket, most recently added first.
+	///
+	/// See [`Ticket::add_dependency`] for more information on dependencies.
+	pub fn dependencies(&self) -> Result<Vec<Dependency>> {
+		let mut notes = HashMap::new();
+		for record in self
+			.workspace
+			.remote
+			.set_get_all(&format!("{}/dependency_notes", self.path))?
+		{
+			if let Some((key, note)) = record.message().split_once('\t') {
+				notes.insert(key.to_string(), note.to_string());
+			}
+		}
+
+		let informational = self.informational_dependencies()?;
+
 		self.workspace
 			.remote
 			.walk_set_present(&format!("{}/dependencies", self.path))?
@@ -858,7 +1669,18 @@ impl<'a, R: Remote<'a>> Ticket<'a, R> {
 				let (origin, endpoint) = message
 					.split_once('@')
 					.ok_or_else(|| Error::Malformed(format!("{}/dependencies", self.path)))?;
-				Ok((origin.to_string(), endpoint.to_string(), r))
+				Ok(Dependency {
+					origin: origin.to_string(),
+					endpoint: endpoint.to_string(),
+					kind: if informational.contains(&message) {
+						DependencyKind::Informational
+					} else {
+						DependencyKind::Blocking
+					},
+					note: notes.remove(&message),
+					added_by: r.email(),
+					added_at: r.timestamp(),
+				})
 			})
 			.collect()
 	}
@@ -880,7 +1702,7 @@ impl<'a, R: Remote<'a>> Ticket<'a, R> {
 	}
 }
 
-fn validate_origin(origin: &str) -> Result<()> {
+pub(crate) fn validate_origin(origin: &str) -> Result<()> {
 	if origin.contains('@') {
 		return Err(Error::MalformedOrigin(origin.to_string()));
 	}
@@ -888,6 +1710,59 @@ fn validate_origin(origin: &str) -> Result<()> {
 	Ok(())
 }
 
+/// A dependency of a ticket. See [`Ticket::dependencies`].
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize)]
+pub struct Dependency {
+	/// The dependency's origin.
+	pub origin: String,
+	/// The dependency's endpoint.
+	pub endpoint: String,
+	/// Whether the dependency blocks the ticket.
+	pub kind: DependencyKind,
+	/// A human-readable note explaining the dependency, if any.
+	/// See [`Ticket::add_dependency_with_note`].
+	pub note: Option<String>,
+	/// The email address of the user who added the dependency.
+	pub added_by: String,
+	/// When the dependency was added, as a unix timestamp (in seconds).
+	pub added_at: i64,
+}
+
+/// Whether a dependency gates its ticket. See
+/// [`Ticket::add_dependency_with_kind`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, serde::Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum DependencyKind {
+	/// The ticket can't be worked on (or, with the right workflow,
+	/// closed) until the dependency is complete.
+	#[default]
+	Blocking,
+	/// The dependency is only linked for reference.
+	Informational,
+}
+
+impl TryFrom<&str> for DependencyKind {
+	type Error = Error;
+
+	fn try_from(value: &str) -> Result<Self> {
+		match value {
+			"blocking" => Ok(Self::Blocking),
+			"informational" => Ok(Self::Informational),
+			_ => Err(Error::Malformed(value.to_string())),
+		}
+	}
+}
+
+impl ToString for DependencyKind {
+	fn to_string(&self) -> String {
+		match self {
+			Self::Blocking => "blocking",
+			Self::Informational => "informational",
+		}
+		.to_string()
+	}
+}
+
 /// An iterator over a ticket's dependencies that resolves
 /// the status of each dependency.
 pub struct TicketDependencyIterator<'a, R: Remote<'a>, D: DependencyResolver> {
@@ -920,7 +1795,8 @@ impl<'a, R: Remote<'a>, D: DependencyResolver> Iterator for TicketDependencyIter
 }
 
 /// The status of a ticket.
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, serde::Serialize)]
+#[serde(rename_all = "lowercase")]
 pub enum TicketState {
 	/// The ticket is open.
 	Open,
@@ -959,6 +1835,17 @@ impl ToString for TicketState {
 	}
 }
 
+/// Parses the message of a state record, which is the state optionally
+/// followed by a blank line and the reason for the change.
+pub(crate) fn parse_state_message(message: &str) -> Option<(TicketState, Option<String>)> {
+	match message.split_once("\n\n") {
+		Some((state, reason)) => {
+			Some((TicketState::try_from(state).ok()?, Some(reason.to_string())))
+		}
+		None => Some((TicketState::try_from(message).ok()?, None)),
+	}
+}
+
 /// Dependency resolvers take an origin slug and endpoint string
 /// and resolve the current status of the dependency.
 ///
@@ -969,7 +1856,8 @@ pub trait DependencyResolver {
 }
 
 /// The state of a dependency.
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize)]
+#[serde(rename_all = "lowercase")]
 pub enum DependencyStatus {
 	/// The dependency is still pending
 	Pending,
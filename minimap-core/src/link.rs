@@ -0,0 +1,134 @@
+//! Links to tickets.
+//!
+//! Tickets can be linked to from chat or commit messages with
+//! `minimap://workspace/<remote>/ticket/<slug>` URIs, which the app opens.
+//! The remote is usually a URL itself, so it's percent-encoded to keep the
+//! link a single, unambiguous token.
+
+use crate::{Error, Result};
+use std::{fmt, str::FromStr};
+
+/// The URI scheme of ticket links.
+pub const LINK_SCHEME: &str = "minimap";
+
+/// A link to a ticket of the workspace on a remote.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct TicketLink {
+	/// The remote of the ticket's workspace, as given to e.g.
+	/// [`GitRemote::open`](crate::GitRemote::open).
+	pub remote: String,
+	/// The ticket's slug.
+	pub ticket: String,
+}
+
+impl TicketLink {
+	/// Creates a link to a ticket of the workspace on a remote.
+	pub fn new(remote: &str, ticket: &str) -> Self {
+		Self {
+			remote: remote.to_string(),
+			ticket: ticket.to_string(),
+		}
+	}
+}
+
+impl fmt::Display for TicketLink {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		write!(
+			f,
+			"{}://workspace/{}/ticket/{}",
+			LINK_SCHEME,
+			percent_encode(&self.remote),
+			percent_encode(&self.ticket)
+		)
+	}
+}
+
+impl FromStr for TicketLink {
+	type Err = Error;
+
+	fn from_str(s: &str) -> Result<Self> {
+		let malformed = || Error::MalformedLink(s.to_string());
+
+		let path = s
+			.strip_prefix(LINK_SCHEME)
+			.and_then(|rest| rest.strip_prefix("://workspace/"))
+			.ok_or_else(malformed)?;
+		// Links pasted in messages may pick up a trailing slash.
+		let path = path.strip_suffix('/').unwrap_or(path);
+		let (remote, ticket) = path.split_once("/ticket/").ok_or_else(malformed)?;
+
+		let remote = percent_decode(remote).ok_or_else(malformed)?;
+		let ticket = percent_decode(ticket).ok_or_else(malformed)?;
+		if remote.is_empty() || ticket.is_empty() || ticket.contains('/') {
+			return Err(malformed());
+		}
+
+		Ok(Self { remote, ticket })
+	}
+}
+
+/// Percent-encodes everything but unreserved characters (RFC 3986).
+fn percent_encode(s: &str) -> String {
+	let mut encoded = String::with_capacity(s.len());
+	for b in s.bytes() {
+		match b {
+			b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' => {
+				encoded.push(b as char)
+			}
+			_ => encoded.push_str(&format!("%{:02X}", b)),
+		}
+	}
+	encoded
+}
+
+fn percent_decode(s: &str) -> Option<String> {
+	let bytes = s.as_bytes();
+	let mut decoded = Vec::with_capacity(bytes.len());
+	let mut i = 0;
+	while i < bytes.len() {
+		if bytes[i] == b'%' {
+			let hex = s.get(i + 1..i + 3)?;
+			// `from_str_radix` would take a sign, e.g. `%+1`.
+			if !hex.bytes().all(|b| b.is_ascii_hexdigit()) {
+				return None;
+			}
+			decoded.push(u8::from_str_radix(hex, 16).ok()?);
+			i += 3;
+		} else {
+			decoded.push(bytes[i]);
+			i += 1;
+		}
+	}
+	String::from_utf8(decoded).ok()
+}
+
+#[cfg(test)]
+mod test {
+	use super::*;
+
+	#[test]
+	fn test_ticket_link() {
+		let link = TicketLink::new("git@example.com:team/tracker.git", "ABC-12");
+		assert_eq!(
+			link.to_string(),
+			"minimap://workspace/git%40example.com%3Ateam%2Ftracker.git/ticket/ABC-12"
+		);
+		assert_eq!(link.to_string().parse::<TicketLink>().unwrap(), link);
+		assert_eq!(format!("{}/", link).parse::<TicketLink>().unwrap(), link);
+
+		for malformed in [
+			"https://workspace/remote/ticket/ABC-12",
+			"minimap://workspace/remote",
+			"minimap://workspace//ticket/ABC-12",
+			"minimap://workspace/remote/ticket/",
+			"minimap://workspace/remote/ticket/ABC/12",
+			"minimap://workspace/re%2/ticket/ABC-12",
+			"minimap://workspace/re%+1/ticket/ABC-12",
+		] {
+			assert!(matches!(
+				malformed.parse::<TicketLink>(),
+				Err(Error::MalformedLink(_))
+			));
+		}
+	}
+}
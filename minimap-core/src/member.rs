@@ -0,0 +1,131 @@
+//! Project members and roles.
+//!
+//! Members are stored in the project's `members` set, with items of the
+//! form `email:role`. Minimap itself doesn't enforce roles; instead,
+//! [`Project::require_role`] gives higher layers (e.g. a server or the
+//! app) a single place to check permissions.
+
+use crate::{Error, Project, Record, Remote, Result};
+use indexmap::IndexMap;
+
+/// A member's role in a project. Roles are ordered, with
+/// each role including the permissions of the ones before it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, serde::Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Role {
+	/// Can view the project.
+	Viewer,
+	/// Can create and edit tickets.
+	Contributor,
+	/// Can manage the project itself, including its members.
+	Maintainer,
+}
+
+impl TryFrom<&str> for Role {
+	type Error = Error;
+
+	fn try_from(value: &str) -> Result<Self> {
+		match value {
+			"viewer" => Ok(Self::Viewer),
+			"contributor" => Ok(Self::Contributor),
+			"maintainer" => Ok(Self::Maintainer),
+			_ => Err(Error::Malformed(value.to_string())),
+		}
+	}
+}
+
+impl ToString for Role {
+	fn to_string(&self) -> String {
+		match self {
+			Self::Viewer => "viewer".to_string(),
+			Self::Contributor => "contributor".to_string(),
+			Self::Maintainer => "maintainer".to_string(),
+		}
+	}
+}
+
+impl<'a, R: Remote<'a>> Project<'a, R> {
+	/// Gets the project's members and their roles, by email address.
+	pub fn members(&self) -> Result<IndexMap<String, Role>> {
+		let path = self.members_path();
+
+		self.workspace
+			.remote
+			.set_get_all(&path)?
+			.into_iter()
+			.map(|record| {
+				let message = record.message();
+				let (email, role) = message
+					.rsplit_once(':')
+					.ok_or_else(|| Error::Malformed(path.clone()))?;
+				Ok((email.to_string(), Role::try_from(role)?))
+			})
+			.collect()
+	}
+
+	/// Adds a member to the project, replacing their role if they're
+	/// already a member. Returns [`Error::Exists`] if the user is already
+	/// a member with the same role.
+	///
+	/// Email addresses cannot be empty or contain whitespace characters.
+	pub fn add_member(&self, email: &str, role: Role) -> Result<R::Record> {
+		if email.is_empty() || email.contains(char::is_whitespace) {
+			return Err(Error::Malformed(email.to_string()));
+		}
+
+		let path = self.members_path();
+		let item = format!("{}:{}", email, role.to_string());
+
+		if let Some(existing) = self.members()?.get(email) {
+			if *existing == role {
+				return Err(Error::Exists(path, item));
+			}
+
+			self.workspace
+				.remote
+				.set_del_unchecked(&path, &format!("{}:{}", email, existing.to_string()))?;
+		}
+
+		self.workspace.remote.set_add_unchecked(&path, &item)
+	}
+
+	/// Removes a member from the project, returning the removal
+	/// record, or `None` if the user wasn't a member.
+	pub fn remove_member(&self, email: &str) -> Result<Option<R::Record>> {
+		match self.members()?.get(email) {
+			Some(role) => Ok(Some(self.workspace.remote.set_del_unchecked(
+				&self.members_path(),
+				&format!("{}:{}", email, role.to_string()),
+			)?)),
+			None => Ok(None),
+		}
+	}
+
+	/// Gets a user's role in the project, or `None` if they aren't a member.
+	pub fn role(&self, email: &str) -> Result<Option<Role>> {
+		Ok(self.members()?.get(email).copied())
+	}
+
+	/// Returns if the user has at least the given role in the project.
+	pub fn has_role(&self, email: &str, role: Role) -> Result<bool> {
+		Ok(self.role(email)?.map_or(false, |actual| actual >= role))
+	}
+
+	/// Checks that the user has at least the given role in the project,
+	/// returning [`Error::PermissionDenied`] if not.
+	pub fn require_role(&self, email: &str, role: Role) -> Result<()> {
+		if self.has_role(email, role)? {
+			Ok(())
+		} else {
+			Err(Error::PermissionDenied(
+				email.to_string(),
+				self.slug.clone(),
+				role.to_string(),
+			))
+		}
+	}
+
+	fn members_path(&self) -> String {
+		format!("{}/members", self.meta_path)
+	}
+}
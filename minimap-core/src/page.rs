@@ -0,0 +1,154 @@
+//! Paged access to collections.
+//!
+//! Collections can grow arbitrarily large (e.g. tickets with thousands
+//! of comments), so consumers such as the app or an HTTP server need a
+//! way to list them a chunk at a time. Since records are immutable and
+//! their IDs are stable across clones and restarts, a cursor is simply
+//! the collection name plus the ID of the last record that was returned.
+
+use crate::{Error, Record, Remote, Result};
+use std::{fmt, str::FromStr};
+
+/// An opaque, stable position within a collection.
+///
+/// Cursors are returned by paged APIs and can be passed back in
+/// to resume listing directly after the last returned record.
+/// They can be serialized to a string (via [`ToString`]) and parsed
+/// back (via [`FromStr`]), and remain valid for as long as the record
+/// they point to exists - including across workspace re-opens.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Cursor {
+	collection: String,
+	record: String,
+}
+
+impl Cursor {
+	/// Creates a cursor pointing directly after the given record
+	/// in the given collection.
+	pub fn new(collection: &str, record: &str) -> Self {
+		Self {
+			collection: collection.to_string(),
+			record: record.to_string(),
+		}
+	}
+
+	/// Gets the collection the cursor points into.
+	#[inline]
+	pub fn collection(&self) -> &str {
+		&self.collection
+	}
+
+	/// Gets the ID of the last record returned before this cursor.
+	#[inline]
+	pub fn record(&self) -> &str {
+		&self.record
+	}
+}
+
+impl fmt::Display for Cursor {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		// Collection names contain `/` and could in theory contain
+		// the separator, so they're hex-encoded to keep the cursor
+		// a single, unambiguous token.
+		for b in self.collection.as_bytes() {
+			write!(f, "{:02x}", b)?;
+		}
+		write!(f, ".{}", self.record)
+	}
+}
+
+impl FromStr for Cursor {
+	type Err = Error;
+
+	fn from_str(s: &str) -> Result<Self> {
+		let malformed = || Error::MalformedCursor(s.to_string());
+
+		let (collection, record) = s.split_once('.').ok_or_else(malformed)?;
+		if collection.len() % 2 != 0 || record.is_empty() {
+			return Err(malformed());
+		}
+
+		let collection = (0..collection.len())
+			.step_by(2)
+			.map(|i| {
+				collection
+					.get(i..i + 2)
+					.and_then(|b| u8::from_str_radix(b, 16).ok())
+			})
+			.collect::<Option<Vec<u8>>>()
+			.and_then(|b| String::from_utf8(b).ok())
+			.ok_or_else(malformed)?;
+
+		Ok(Self {
+			collection,
+			record: record.to_string(),
+		})
+	}
+}
+
+/// A single page of records from a collection.
+#[derive(Debug, Clone)]
+pub struct Page<R: Record> {
+	/// The records in the page, in the same order as [`Remote::walk`]
+	/// (latest to oldest).
+	pub records: Vec<R>,
+	/// The cursor to pass to the next call in order to fetch the
+	/// following page, or `None` if this was the last page.
+	pub next: Option<Cursor>,
+}
+
+/// Walks a single page of a collection. See [`Remote::walk_page`].
+pub(crate) fn walk_page<'a, R: Remote<'a>>(
+	remote: &'a R,
+	collection: &str,
+	after: Option<&Cursor>,
+	limit: usize,
+) -> Result<Page<R::Record>> {
+	// An empty page has no last record to continue from, so it couldn't
+	// tell callers whether the list is over.
+	if limit == 0 {
+		return Err(Error::ZeroPageLimit);
+	}
+
+	let mut iter = remote.walk(collection)?;
+
+	if let Some(cursor) = after {
+		if cursor.collection != collection {
+			return Err(Error::MalformedCursor(cursor.to_string()));
+		}
+
+		loop {
+			match iter.next() {
+				Some(record) => {
+					if record?.id() == cursor.record {
+						break;
+					}
+				}
+				None => {
+					return Err(Error::NotFound(
+						collection.to_string(),
+						cursor.record.clone(),
+					));
+				}
+			}
+		}
+	}
+
+	// The limit comes from callers (e.g. the frontend), so it isn't trusted
+	// to size the allocation.
+	let mut records = Vec::new();
+	for record in iter.by_ref().take(limit) {
+		records.push(record?);
+	}
+
+	// Only hand out a cursor if there's actually something after it,
+	// so that callers can use `next.is_none()` as an end-of-list check.
+	let next = match records.last() {
+		Some(last) if records.len() == limit && iter.next().transpose()?.is_some() => {
+			Some(Cursor::new(collection, &last.id()))
+		}
+		_ => None,
+	};
+
+	Ok(Page { records, next })
+}
@@ -0,0 +1,319 @@
+//! Filtering tickets.
+//!
+//! A [`TicketQuery`] describes a set of conditions on tickets; running it
+//! against a [`Project`] or the whole [`Workspace`] yields the tickets
+//! that match all of them, in the order given by its [`TicketSort`].
+
+use crate::{
+	blocked::LocalResolver, DependencyResolver, EffectiveState, Error, FieldType, FieldValue,
+	Project, Record, Remote, Result, Ticket, TicketState, Workspace,
+};
+use std::cmp::Ordering;
+
+/// The order in which a [`TicketQuery`] yields tickets.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub enum TicketSort {
+	/// By project, and then by ticket ID.
+	#[default]
+	Id,
+	/// By vote count, from most to least voted. Tickets with
+	/// the same number of votes keep their [`TicketSort::Id`] order.
+	Votes,
+	/// By the value of a custom field (see [`Ticket::field`]). Enum
+	/// values are ordered as they're declared in the project's schema.
+	/// Tickets without a value, or in projects that don't define the
+	/// field, come last; ties keep their [`TicketSort::Id`] order.
+	Field {
+		/// The name of the field.
+		name: String,
+		/// Whether to order from the greatest to the least value.
+		descending: bool,
+	},
+}
+
+/// A filter over tickets. All conditions must hold for a ticket to match.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct TicketQuery {
+	state: Option<TicketState>,
+	archived: Option<bool>,
+	effective_state: Option<EffectiveState>,
+	labels: Vec<String>,
+	assignee: Option<String>,
+	created_after: Option<i64>,
+	created_before: Option<i64>,
+	text: Option<String>,
+	sort: TicketSort,
+}
+
+impl TicketQuery {
+	/// Creates a query that matches every ticket.
+	#[inline]
+	pub fn new() -> Self {
+		Self::default()
+	}
+
+	/// Only matches tickets in the given state.
+	pub fn state(mut self, state: TicketState) -> Self {
+		self.state = Some(state);
+		self
+	}
+
+	/// Only matches archived tickets, or only unarchived ones if
+	/// `archived` is false (see [`Ticket::is_archived`]).
+	pub fn archived(mut self, archived: bool) -> Self {
+		self.archived = Some(archived);
+		self
+	}
+
+	/// Only matches tickets in the given effective state (see
+	/// [`Ticket::effective_state`]).
+	///
+	/// Unless the query is run with a resolver (e.g.
+	/// [`Workspace::query_with`]), only dependencies on other tickets in
+	/// the workspace can be resolved, and tickets with any other
+	/// blocking dependencies are considered blocked.
+	pub fn effective_state(mut self, state: EffectiveState) -> Self {
+		self.effective_state = Some(state);
+		self
+	}
+
+	/// Only matches tickets with the given label. May be given
+	/// multiple times, in which case all labels must be present.
+	pub fn label(mut self, label: &str) -> Self {
+		self.labels.push(label.to_string());
+		self
+	}
+
+	/// Only matches tickets assigned to the given user.
+	pub fn assignee(mut self, user: &str) -> Self {
+		self.assignee = Some(user.to_string());
+		self
+	}
+
+	/// Only matches tickets created after the given unix timestamp (in seconds).
+	pub fn created_after(mut self, timestamp: i64) -> Self {
+		self.created_after = Some(timestamp);
+		self
+	}
+
+	/// Only matches tickets created before the given unix timestamp (in seconds).
+	pub fn created_before(mut self, timestamp: i64) -> Self {
+		self.created_before = Some(timestamp);
+		self
+	}
+
+	/// Only matches tickets whose title contains the given text, case-insensitively.
+	pub fn text(mut self, text: &str) -> Self {
+		self.text = Some(text.to_lowercase());
+		self
+	}
+
+	/// Sets the order in which matching tickets are yielded.
+	pub fn sort(mut self, sort: TicketSort) -> Self {
+		self.sort = sort;
+		self
+	}
+
+	/// Checks whether a single ticket matches the query.
+	pub fn matches<'a, R: Remote<'a>>(&self, ticket: &Ticket<'a, R>) -> Result<bool> {
+		self.matches_with(ticket, &LocalResolver)
+	}
+
+	/// Checks whether a single ticket matches the query, resolving
+	/// dependencies with the given resolver.
+	pub fn matches_with<'a, R: Remote<'a>, D: DependencyResolver + ?Sized>(
+		&self,
+		ticket: &Ticket<'a, R>,
+		resolver: &D,
+	) -> Result<bool> {
+		if let Some(state) = self.state {
+			if ticket.state()?.0 != state {
+				return Ok(false);
+			}
+		}
+
+		if let Some(archived) = self.archived {
+			if ticket.is_archived()? != archived {
+				return Ok(false);
+			}
+		}
+
+		if !self.labels.is_empty() {
+			let labels = ticket.labels()?;
+			if !self.labels.iter().all(|label| labels.contains(label)) {
+				return Ok(false);
+			}
+		}
+
+		if let Some(assignee) = &self.assignee {
+			if !ticket.assignees()?.contains(assignee) {
+				return Ok(false);
+			}
+		}
+
+		if self.created_after.is_some() || self.created_before.is_some() {
+			let created = ticket.created()?.timestamp();
+			if self.created_after.map_or(false, |after| created <= after)
+				|| self
+					.created_before
+					.map_or(false, |before| created >= before)
+			{
+				return Ok(false);
+			}
+		}
+
+		if let Some(state) = self.effective_state {
+			if ticket.effective_state(resolver)? != state {
+				return Ok(false);
+			}
+		}
+
+		if let Some(text) = &self.text {
+			let title = ticket
+				.title()?
+				.map(|record| record.message().to_lowercase());
+			if !title.map_or(false, |title| title.contains(text)) {
+				return Ok(false);
+			}
+		}
+
+		Ok(true)
+	}
+}
+
+/// An iterator over the tickets matching a [`TicketQuery`].
+/// Tickets are checked lazily, as the iterator is advanced.
+pub struct TicketQueryIterator<'a, R: Remote<'a>> {
+	query: TicketQuery,
+	tickets: std::vec::IntoIter<Ticket<'a, R>>,
+	resolver: &'a dyn DependencyResolver,
+}
+
+impl<'a, R: Remote<'a>> TicketQueryIterator<'a, R> {
+	fn new(
+		query: &TicketQuery,
+		mut tickets: Vec<Ticket<'a, R>>,
+		resolver: &'a dyn DependencyResolver,
+	) -> Result<Self> {
+		match &query.sort {
+			TicketSort::Id => {}
+			TicketSort::Votes => {
+				let mut counted = tickets
+					.into_iter()
+					.map(|ticket| Ok((ticket.vote_count()?, ticket)))
+					.collect::<Result<Vec<_>>>()?;
+				counted.sort_by_key(|(votes, _)| ::std::cmp::Reverse(*votes));
+				tickets = counted.into_iter().map(|(_, ticket)| ticket).collect();
+			}
+			TicketSort::Field { name, descending } => {
+				let mut keyed = tickets
+					.into_iter()
+					.map(|ticket| Ok((field_sort_key(&ticket, name)?, ticket)))
+					.collect::<Result<Vec<_>>>()?;
+				keyed.sort_by(|(a, _), (b, _)| match (a, b) {
+					(Some(a), Some(b)) if *descending => {
+						b.partial_cmp(a).unwrap_or(Ordering::Equal)
+					}
+					(Some(a), Some(b)) => a.partial_cmp(b).unwrap_or(Ordering::Equal),
+					(Some(_), None) => Ordering::Less,
+					(None, Some(_)) => Ordering::Greater,
+					(None, None) => Ordering::Equal,
+				});
+				tickets = keyed.into_iter().map(|(_, ticket)| ticket).collect();
+			}
+		}
+
+		Ok(Self {
+			query: query.clone(),
+			tickets: tickets.into_iter(),
+			resolver,
+		})
+	}
+}
+
+/// A comparable stand-in for a field value; see [`TicketSort::Field`].
+/// Strings sort after every other kind of value, which only matters
+/// when projects disagree on the field's type.
+#[derive(PartialEq, PartialOrd)]
+enum FieldSortKey {
+	Ordinal(f64),
+	Text(String),
+}
+
+fn field_sort_key<'a, R: Remote<'a>>(
+	ticket: &Ticket<'a, R>,
+	name: &str,
+) -> Result<Option<FieldSortKey>> {
+	let value = match ticket.field(name) {
+		Ok(Some((value, _))) => value,
+		Ok(None) | Err(Error::NotFound(_, _)) => return Ok(None),
+		Err(err) => return Err(err),
+	};
+
+	Ok(Some(match value {
+		FieldValue::String(text) => FieldSortKey::Text(text),
+		FieldValue::Number(number) => FieldSortKey::Ordinal(number),
+		FieldValue::Bool(value) => FieldSortKey::Ordinal(f64::from(u8::from(value))),
+		FieldValue::Enum(value) => {
+			let index = match ticket.project()?.fields()?.get(name) {
+				Some(FieldType::Enum(variants)) => variants.iter().position(|v| *v == value),
+				_ => None,
+			};
+			FieldSortKey::Ordinal(index.unwrap_or(usize::MAX) as f64)
+		}
+		FieldValue::Date(year, month, day) => FieldSortKey::Ordinal(f64::from(
+			year * 10000 + i32::from(month) * 100 + i32::from(day),
+		)),
+	}))
+}
+
+impl<'a, R: Remote<'a>> Iterator for TicketQueryIterator<'a, R> {
+	type Item = Result<Ticket<'a, R>>;
+
+	fn next(&mut self) -> Option<Self::Item> {
+		for ticket in self.tickets.by_ref() {
+			match self.query.matches_with(&ticket, self.resolver) {
+				Ok(true) => return Some(Ok(ticket)),
+				Ok(false) => continue,
+				Err(err) => return Some(Err(err)),
+			}
+		}
+
+		None
+	}
+}
+
+impl<'a, R: Remote<'a>> Project<'a, R> {
+	/// Runs a query against the project's tickets.
+	pub fn query(&self, query: &TicketQuery) -> Result<TicketQueryIterator<'a, R>> {
+		TicketQueryIterator::new(query, self.tickets()?, &LocalResolver)
+	}
+
+	/// Runs a query against the project's tickets, resolving
+	/// dependencies with the given resolver.
+	pub fn query_with(
+		&self,
+		query: &TicketQuery,
+		resolver: &'a dyn DependencyResolver,
+	) -> Result<TicketQueryIterator<'a, R>> {
+		TicketQueryIterator::new(query, self.tickets()?, resolver)
+	}
+}
+
+impl<'a, R: Remote<'a>> Workspace<'a, R> {
+	/// Runs a query against all tickets in the workspace
+	/// (see [`Workspace::tickets`]).
+	pub fn query(&'a self, query: &TicketQuery) -> Result<TicketQueryIterator<'a, R>> {
+		TicketQueryIterator::new(query, self.tickets()?, &LocalResolver)
+	}
+
+	/// Runs a query against all tickets in the workspace,
+	/// resolving dependencies with the given resolver.
+	pub fn query_with(
+		&'a self,
+		query: &TicketQuery,
+		resolver: &'a dyn DependencyResolver,
+	) -> Result<TicketQueryIterator<'a, R>> {
+		TicketQueryIterator::new(query, self.tickets()?, resolver)
+	}
+}
@@ -0,0 +1,120 @@
+//! Ticket references in commit messages.
+//!
+//! Commits in source repositories refer to tickets with trailer-style
+//! lines, like `Refs: ABC-12, ABC-13` or `Closes: ABC-14`. Keys are
+//! case-insensitive; see [`REFERENCE_KEYS`] for the recognized ones.
+
+/// How a commit refers to a ticket. See [`ticket_references`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ReferenceKind {
+	/// The commit relates to the ticket, e.g. `Refs: ABC-12`.
+	Mentions,
+	/// The commit resolves the ticket, e.g. `Closes: ABC-12`.
+	Closes,
+}
+
+/// The keys of reference lines, and the kind of reference they make.
+pub const REFERENCE_KEYS: &[(&str, ReferenceKind)] = &[
+	("Refs", ReferenceKind::Mentions),
+	("Ref", ReferenceKind::Mentions),
+	("References", ReferenceKind::Mentions),
+	("Closes", ReferenceKind::Closes),
+	("Fixes", ReferenceKind::Closes),
+	("Resolves", ReferenceKind::Closes),
+];
+
+/// A ticket referenced by a commit message.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TicketReference {
+	/// The referenced ticket's slug, as written.
+	pub slug: String,
+	/// How the ticket is referenced.
+	pub kind: ReferenceKind,
+}
+
+/// Finds the tickets referenced by a commit message, in order of first
+/// reference, each once; a ticket that's both mentioned and closed is
+/// closed. A line may reference several tickets, separated by commas or
+/// spaces.
+///
+/// Like Git (by default), lines starting with `#` are ignored, along
+/// with everything after a scissors line (`# --- >8 ---`).
+pub fn ticket_references(message: &str) -> Vec<TicketReference> {
+	let mut references = Vec::<TicketReference>::new();
+
+	for line in message.lines() {
+		if line.starts_with('#') {
+			if line.contains(" >8 ") {
+				break;
+			}
+			continue;
+		}
+
+		let Some((key, value)) = line.split_once(':') else {
+			continue;
+		};
+		let Some(&(_, kind)) = REFERENCE_KEYS
+			.iter()
+			.find(|(known, _)| known.eq_ignore_ascii_case(key.trim()))
+		else {
+			continue;
+		};
+
+		for slug in value
+			.split(|c: char| c == ',' || c.is_whitespace())
+			.filter(|slug| !slug.is_empty())
+		{
+			match references.iter_mut().find(|known| known.slug == slug) {
+				Some(known) if kind == ReferenceKind::Closes => known.kind = kind,
+				Some(_) => {}
+				None => references.push(TicketReference {
+					slug: slug.to_string(),
+					kind,
+				}),
+			}
+		}
+	}
+
+	references
+}
+
+#[cfg(test)]
+mod test {
+	use super::*;
+
+	#[test]
+	fn test_ticket_references() {
+		let message = concat!(
+			"Fix the frobnicator\n",
+			"\n",
+			"Mentioning ABC-9 in prose isn't a reference.\n",
+			"\n",
+			"Refs: ABC-1, ABC-2\n",
+			"fixes: ABC-2 ABC-3\n",
+			"Co-authored-by: Someone <someone@example.com>\n",
+			"# Closes: ABC-4\n",
+			"# ------------------------ >8 ------------------------\n",
+			"Refs: ABC-5\n",
+		);
+
+		let references = ticket_references(message);
+		assert_eq!(
+			references,
+			vec![
+				TicketReference {
+					slug: "ABC-1".to_string(),
+					kind: ReferenceKind::Mentions,
+				},
+				TicketReference {
+					slug: "ABC-2".to_string(),
+					kind: ReferenceKind::Closes,
+				},
+				TicketReference {
+					slug: "ABC-3".to_string(),
+					kind: ReferenceKind::Closes,
+				},
+			]
+		);
+		assert!(ticket_references("Refs:\n").is_empty());
+	}
+}
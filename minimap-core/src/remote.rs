@@ -1,3 +1,8 @@
 #[cfg(feature = "git")]
+pub(crate) mod credentials;
+#[cfg(feature = "git")]
 pub(crate) mod git;
 pub(crate) mod memory;
+pub(crate) mod namespaced;
+pub(crate) mod observed;
+pub(crate) mod shared;
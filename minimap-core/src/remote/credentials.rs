@@ -0,0 +1,183 @@
+//! Credentials for [`GitRemote`](crate::GitRemote)s.
+//!
+//! Remotes authenticate over SSH with `~/.ssh/id_rsa`, and ask their
+//! [`CredentialProvider`] (if they were opened with one, see
+//! [`GitRemote::open_with_credentials`](crate::GitRemote::open_with_credentials))
+//! for anything else: the key's passphrase if it turns out to need one,
+//! a username and password (or token) for HTTPS remotes, and whether to
+//! trust hosts whose keys aren't in `~/.ssh/known_hosts`. Without a
+//! provider, those operations fail as they would have.
+
+use crate::Result;
+use git2::{CertificateCheckStatus, Cred, CredentialType, ErrorCode, RemoteCallbacks};
+use std::{
+	cell::{Cell, RefCell},
+	collections::HashSet,
+	path::{Path, PathBuf},
+	rc::Rc,
+	sync::{Arc, Mutex},
+};
+
+/// How many times a passphrase is asked for before giving up.
+const MAX_PASSPHRASE_ATTEMPTS: u32 = 3;
+
+/// Provides the credentials a [`GitRemote`](crate::GitRemote) can't find
+/// by itself, typically by asking the user. Each method may block until
+/// they answer.
+pub trait CredentialProvider: Send + Sync {
+	/// Gets the passphrase of an SSH key, or `None` to give up.
+	fn passphrase(&self, url: &str, key: &Path) -> Option<String>;
+
+	/// Gets a username and password (or token), or `None` to give up.
+	fn user_pass(&self, url: &str, username: Option<&str>) -> Option<(String, String)>;
+
+	/// Asks whether to trust a host whose key isn't known, given the key's
+	/// SHA-256 fingerprint (as `SHA256:<base64>`, like OpenSSH shows it).
+	fn confirm_host_key(&self, host: &str, fingerprint: &str) -> bool;
+}
+
+/// The credentials of a remote.
+#[derive(Default)]
+pub(crate) struct Credentials {
+	provider: Option<Arc<dyn CredentialProvider>>,
+	/// The fingerprints of the host keys confirmed so far.
+	trusted: Mutex<HashSet<String>>,
+}
+
+impl Credentials {
+	pub(crate) fn new(provider: Option<Arc<dyn CredentialProvider>>) -> Self {
+		Self {
+			provider,
+			trusted: Mutex::default(),
+		}
+	}
+
+	/// Runs a network operation with callbacks providing credentials.
+	/// If the host's key is rejected and the provider confirms it, the
+	/// operation is run again.
+	pub(crate) fn run<'c, T>(
+		&'c self,
+		mut operation: impl FnMut(RemoteCallbacks<'c>) -> std::result::Result<T, git2::Error>,
+	) -> Result<T> {
+		loop {
+			let rejected = Rc::new(RefCell::new(None));
+
+			let mut callbacks = RemoteCallbacks::new();
+			let attempts = Cell::new(0);
+			callbacks.credentials(move |url, username, allowed| {
+				attempts.set(attempts.get() + 1);
+				self.credential(url, username, allowed, attempts.get())
+			});
+			let host_key = rejected.clone();
+			callbacks.certificate_check(move |cert, host| {
+				let Some(hash) = cert.as_hostkey().and_then(|key| key.hash_sha256()) else {
+					return Ok(CertificateCheckStatus::CertificatePassthrough);
+				};
+
+				let fingerprint = fingerprint(hash);
+				if self.trusted.lock().unwrap().contains(&fingerprint) {
+					return Ok(CertificateCheckStatus::CertificateOk);
+				}
+
+				// Known hosts pass libgit2's own check; the others are
+				// confirmed once it's failed.
+				*host_key.borrow_mut() = Some((host.to_string(), fingerprint));
+				Ok(CertificateCheckStatus::CertificatePassthrough)
+			});
+
+			match operation(callbacks) {
+				Err(e) if e.code() == ErrorCode::Certificate => {
+					let (Some(provider), Some((host, fingerprint))) =
+						(&self.provider, rejected.take())
+					else {
+						return Err(e.into());
+					};
+
+					if !provider.confirm_host_key(&host, &fingerprint) {
+						return Err(e.into());
+					}
+					self.trusted.lock().unwrap().insert(fingerprint);
+				}
+				result => return Ok(result?),
+			}
+		}
+	}
+
+	fn credential(
+		&self,
+		url: &str,
+		username: Option<&str>,
+		allowed: CredentialType,
+		attempt: u32,
+	) -> std::result::Result<Cred, git2::Error> {
+		let give_up = || git2::Error::from_str(&format!("no credentials for {}", url));
+
+		if allowed.contains(CredentialType::SSH_KEY) {
+			let key = ssh_key().ok_or_else(give_up)?;
+			// The key is tried as is first, and with a passphrase once
+			// that's failed.
+			let passphrase = match attempt {
+				1 => None,
+				_ if attempt > MAX_PASSPHRASE_ATTEMPTS + 1 => return Err(give_up()),
+				_ => Some(
+					self.provider
+						.as_ref()
+						.and_then(|provider| provider.passphrase(url, &key))
+						.ok_or_else(give_up)?,
+				),
+			};
+			return Cred::ssh_key(username.unwrap_or("git"), None, &key, passphrase.as_deref());
+		}
+
+		if allowed.contains(CredentialType::USER_PASS_PLAINTEXT) {
+			let (username, password) = self
+				.provider
+				.as_ref()
+				.and_then(|provider| provider.user_pass(url, username))
+				.ok_or_else(give_up)?;
+			return Cred::userpass_plaintext(&username, &password);
+		}
+
+		if allowed.contains(CredentialType::USERNAME) {
+			return Cred::username(username.unwrap_or("git"));
+		}
+
+		Err(give_up())
+	}
+}
+
+fn ssh_key() -> Option<PathBuf> {
+	let home = std::env::var_os("HOME")?;
+	Some(Path::new(&home).join(".ssh").join("id_rsa"))
+}
+
+/// Formats a SHA-256 hash as OpenSSH does: unpadded base64.
+fn fingerprint(hash: &[u8; 32]) -> String {
+	const ALPHABET: &[u8] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+	let mut encoded = String::from("SHA256:");
+	for chunk in hash.chunks(3) {
+		let bits = chunk.iter().enumerate().fold(0u32, |bits, (i, &byte)| {
+			bits | (byte as u32) << (16 - 8 * i)
+		});
+		for i in 0..=chunk.len() {
+			encoded.push(ALPHABET[(bits >> (18 - 6 * i) & 0x3f) as usize] as char);
+		}
+	}
+	encoded
+}
+
+#[cfg(test)]
+mod test {
+	use super::*;
+	use sha2::{Digest, Sha256};
+
+	#[test]
+	fn test_fingerprint() {
+		let hash = Sha256::digest(b"minimap");
+		assert_eq!(
+			fingerprint(&hash.into()),
+			"SHA256:FiAr5lkw+CSrBmrZuBYsODjF8WQLAPEafjaGKN3cMZc"
+		);
+	}
+}
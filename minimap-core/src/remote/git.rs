@@ -2,18 +2,25 @@
 //!
 //! This allows Minimap to be used with a remote git repository
 //! as a backend. Reads hit the local repository, and writes
-//! are immediately pushed to the workspace.
+//! are immediately pushed to the workspace (unless pushes are
+//! deferred, or queued while offline; see [`GitRemote::defer_pushes`]
+//! and [`GitRemote::with_offline_queue`]).
 
-use crate::{Error, Record, RecordBuilder, Remote, Result, SetOperation};
+use crate::{
+	is_collection_under, remote::credentials::Credentials, CredentialProvider, Error, FetchChange,
+	Record, RecordBuilder, Remote, Result, SetOperation,
+};
 use git2::{
 	build::{RepoBuilder, TreeUpdateBuilder},
-	AutotagOption, Commit, Cred, FetchOptions, FetchPrune, ObjectType, Oid, PushOptions,
-	RemoteCallbacks, Repository, Revwalk,
+	AutotagOption, Commit, FetchOptions, FetchPrune, ObjectType, Oid, PushOptions, Repository,
+	Revwalk, Signature, Time, TreeWalkMode, TreeWalkResult,
 };
 use std::{
 	cell::RefCell,
+	collections::{BTreeMap, HashMap},
 	hash::{Hash, Hasher},
 	path::{Path, PathBuf},
+	sync::Arc,
 };
 
 /// An iterator over the commits in a [`GitRemote`].
@@ -24,12 +31,33 @@ pub struct GitRemote {
 	repo: Repository,
 	set_add_oid: Oid,
 	set_del_oid: Oid,
+	/// Overrides Git's configured identity; see [`GitRemote::with_identity`].
+	identity: Option<(String, String)>,
+	/// While pushes are deferred, the collections written to since,
+	/// along with their heads from before; see [`GitRemote::defer_pushes`].
+	deferred: RefCell<Option<BTreeMap<String, Option<Oid>>>>,
+	/// See [`GitRemote::with_offline_queue`].
+	offline_queue: bool,
+	credentials: Credentials,
 }
 
 impl GitRemote {
 	/// Opens a remote repository. If the repository hasn't been cloned yet,
 	/// Minimap will attempt to clone it from the remote prior to returning.
 	pub fn open(remote: &str) -> Result<Self> {
+		Self::open_with(remote, Credentials::default())
+	}
+
+	/// Opens a remote repository like [`GitRemote::open`], asking the given
+	/// provider for the credentials it needs (see [`CredentialProvider`]).
+	pub fn open_with_credentials(
+		remote: &str,
+		provider: Arc<dyn CredentialProvider>,
+	) -> Result<Self> {
+		Self::open_with(remote, Credentials::new(Some(provider)))
+	}
+
+	fn open_with(remote: &str, credentials: Credentials) -> Result<Self> {
 		let local_dir = generate_tmp_dir(remote)?;
 
 		// Try to open it as a local repository first,
@@ -37,29 +65,18 @@ impl GitRemote {
 		let repo = if let Ok(repo) = Repository::open(&local_dir) {
 			repo
 		} else {
-			let mut callbacks = RemoteCallbacks::new();
-			callbacks.credentials(|_url, username_from_url, _allowed_types| {
-				Cred::ssh_key(
-					username_from_url.unwrap(),
-					None,
-					Path::new(&format!(
-						"{}/.ssh/id_rsa",
-						std::env::var("HOME").expect("HOME environment variable not set")
-					)),
-					None,
-				)
-			});
-
-			let mut fetch_opts = FetchOptions::new();
-			fetch_opts.update_fetchhead(false);
-			fetch_opts.download_tags(AutotagOption::All);
-			fetch_opts.prune(FetchPrune::On);
-			fetch_opts.remote_callbacks(callbacks);
-
-			RepoBuilder::new()
-				.bare(true)
-				.fetch_options(fetch_opts)
-				.clone(remote, &local_dir)?
+			credentials.run(|callbacks| {
+				let mut fetch_opts = FetchOptions::new();
+				fetch_opts.update_fetchhead(false);
+				fetch_opts.download_tags(AutotagOption::All);
+				fetch_opts.prune(FetchPrune::On);
+				fetch_opts.remote_callbacks(callbacks);
+
+				RepoBuilder::new()
+					.bare(true)
+					.fetch_options(fetch_opts)
+					.clone(remote, &local_dir)
+			})?
 		};
 
 		// The set_add_oid/ set_del_oid are the OIDs of two
@@ -86,30 +103,65 @@ impl GitRemote {
 
 		if needs_push {
 			let mut remote = repo.find_remote("origin")?;
-			let mut callbacks = RemoteCallbacks::new();
-
-			callbacks.credentials(|_url, username_from_url, _allowed_types| {
-				Cred::ssh_key(
-					username_from_url.unwrap(),
-					None,
-					Path::new(&format!(
-						"{}/.ssh/id_rsa",
-						std::env::var("HOME").expect("HOME environment variable not set")
-					)),
-					None,
+			credentials.run(|callbacks| {
+				remote.push(
+					&["refs/tags/meta/+", "refs/tags/meta/-"],
+					Some(PushOptions::new().remote_callbacks(callbacks)),
 				)
-			});
-
-			remote.push(
-				&["refs/tags/meta/+", "refs/tags/meta/-"],
-				Some(PushOptions::new().remote_callbacks(callbacks)),
-			)?;
+			})?;
 		}
 
 		Ok(Self {
 			repo,
 			set_add_oid,
 			set_del_oid,
+			identity: None,
+			deferred: RefCell::new(None),
+			offline_queue: false,
+			credentials,
+		})
+	}
+
+	/// Authors new records with the given name and email address,
+	/// rather than the identity configured in Git.
+	pub fn with_identity(mut self, name: &str, email: &str) -> Self {
+		self.identity = Some((name.to_string(), email.to_string()));
+		self
+	}
+
+	/// Queues writes while the remote can't be reached, rather than
+	/// failing them: the first write whose push fails with a network error
+	/// defers pushes (see [`GitRemote::defer_pushes`]), so that it and
+	/// the following writes are kept local until [`GitRemote::push_deferred`]
+	/// succeeds. Until then, failing to push them again keeps them queued,
+	/// rather than rolling them back.
+	///
+	/// The queue is kept in the local repository, so that it survives
+	/// closing the remote; it's picked up again here.
+	pub fn with_offline_queue(mut self) -> Result<Self> {
+		self.offline_queue = true;
+		let queue = self.load_queue()?;
+		if !queue.is_empty() {
+			*self.deferred.borrow_mut() = Some(queue);
+		}
+		Ok(self)
+	}
+
+	/// Gets the name and email address that new records are authored
+	/// with: the identity given to [`GitRemote::with_identity`], or else
+	/// the one configured in Git (`user.name` and `user.email`).
+	pub fn identity(&self) -> Result<(String, String)> {
+		let signature = self.signature()?;
+		Ok((
+			String::from_utf8_lossy(signature.name_bytes()).into_owned(),
+			String::from_utf8_lossy(signature.email_bytes()).into_owned(),
+		))
+	}
+
+	fn signature(&self) -> Result<Signature<'static>> {
+		Ok(match &self.identity {
+			Some((name, email)) => Signature::now(name, email)?,
+			None => self.repo.signature()?,
 		})
 	}
 
@@ -135,6 +187,457 @@ impl GitRemote {
 		repo.tag_lightweight(name, &commit, false)?;
 		Ok((commit_oid, true))
 	}
+
+	/// Gets the head of every collection.
+	fn heads(&self) -> Result<HashMap<String, Oid>> {
+		let mut heads = HashMap::new();
+		for reference in self.repo.references_glob("refs/heads/*")? {
+			let reference = reference?;
+			if let (Some(name), Some(oid)) = (reference.name(), reference.target()) {
+				heads.insert(name["refs/heads/".len()..].to_string(), oid);
+			}
+		}
+		Ok(heads)
+	}
+
+	/// Keeps writes local until [`GitRemote::push_deferred`] pushes
+	/// them all at once (or [`GitRemote::discard_deferred`] drops them),
+	/// rather than pushing every record as it's written. Useful to make
+	/// many changes without a round trip to the remote for each.
+	///
+	/// Does nothing if pushes are already deferred.
+	pub fn defer_pushes(&self) {
+		self.deferred.borrow_mut().get_or_insert_with(BTreeMap::new);
+	}
+
+	/// Pushes every collection written to since [`GitRemote::defer_pushes`]
+	/// in a single push, and stops deferring pushes. Returns the number of
+	/// collections pushed.
+	///
+	/// Collections that the remote doesn't accept (e.g. because they were
+	/// changed elsewhere in the meantime) are rolled back to what they
+	/// were before, and the first of them is returned as
+	/// [`Error::PushFailed`].
+	///
+	/// Writes queued while offline (see [`GitRemote::with_offline_queue`])
+	/// aren't rolled back, but replayed onto the remote's heads instead,
+	/// and pushed again. The replayed records get new IDs, and their
+	/// attachments win over the remote's. They stay queued if that fails.
+	pub fn push_deferred(&self) -> Result<usize> {
+		let pushed = self.push_queued(true);
+		self.save_queue()?;
+		pushed
+	}
+
+	/// Pushes the deferred collections for [`GitRemote::push_deferred`],
+	/// replaying rejected queued writes (and pushing them again) if
+	/// `replay` is set.
+	fn push_queued(&self, replay: bool) -> Result<usize> {
+		let Some(deferred) = self.deferred.take() else {
+			return Ok(0);
+		};
+		if deferred.is_empty() {
+			return Ok(0);
+		}
+
+		let refspecs = deferred
+			.keys()
+			.map(|collection| {
+				let ref_head = format!("refs/heads/{collection}");
+				match self.repo.find_reference(&ref_head) {
+					Ok(_) => format!("{ref_head}:{ref_head}"),
+					// Deleted with `delete_collection`.
+					Err(_) => format!(":{ref_head}"),
+				}
+			})
+			.collect::<Vec<_>>();
+		let (statuses, mut failure) = match self.push_refs(&refspecs) {
+			Ok(statuses) => (statuses, None),
+			// Some transports reject the whole push rather than the collections
+			// changed elsewhere; they're then all replayed.
+			Err(Error::Git(e))
+				if self.offline_queue && e.code() == git2::ErrorCode::NotFastForward =>
+			{
+				(HashMap::new(), Some(Error::Git(e)))
+			}
+			Err(e) if self.offline_queue => {
+				*self.deferred.borrow_mut() = Some(deferred);
+				return Err(e);
+			}
+			Err(e) => {
+				self.restore_heads(&deferred)?;
+				return Err(e);
+			}
+		};
+
+		let mut rejected = BTreeMap::new();
+		for (collection, head) in deferred {
+			let status = statuses.get(&format!("refs/heads/{collection}"));
+			if let Some(None) = status {
+				continue;
+			}
+
+			failure.get_or_insert_with(|| match status {
+				Some(Some(status)) => Error::PushFailed(collection.clone(), status.clone()),
+				_ => Error::NotPushed(collection.clone()),
+			});
+			rejected.insert(collection, head);
+		}
+		let Some(failure) = failure else {
+			return Ok(refspecs.len());
+		};
+		if !self.offline_queue {
+			self.restore_heads(&rejected)?;
+			return Err(failure);
+		}
+
+		// Queued first, so that nothing is lost if replaying fails.
+		*self.deferred.borrow_mut() = Some(rejected.clone());
+		if !replay {
+			return Err(failure);
+		}
+
+		let upstream = self.fetch_upstream()?;
+		for (collection, previous) in rejected {
+			let base = upstream.get(&collection).copied();
+			self.replay_queued(&collection, previous, base)?;
+			if let Some(deferred) = self.deferred.borrow_mut().as_mut() {
+				deferred.insert(collection, base);
+			}
+		}
+		self.push_queued(false).map(|_| refspecs.len())
+	}
+
+	/// Gets the remote's head of every collection, leaving the local
+	/// ones as they are.
+	fn fetch_upstream(&self) -> Result<HashMap<String, Oid>> {
+		let mut remote = self.repo.find_remote("origin")?;
+		self.credentials.run(|callbacks| {
+			let mut fetch_opts = FetchOptions::new();
+			fetch_opts.update_fetchhead(false);
+			fetch_opts.download_tags(AutotagOption::None);
+			fetch_opts.remote_callbacks(callbacks);
+
+			remote.fetch(
+				&["+refs/heads/*:refs/minimap/upstream/*"],
+				Some(&mut fetch_opts),
+				None,
+			)
+		})?;
+
+		let mut heads = HashMap::new();
+		for reference in self.repo.references_glob("refs/minimap/upstream/*")? {
+			let mut reference = reference?;
+			if let (Some(name), Some(oid)) = (reference.name(), reference.target()) {
+				heads.insert(name["refs/minimap/upstream/".len()..].to_string(), oid);
+			}
+			reference.delete()?;
+		}
+		Ok(heads)
+	}
+
+	/// Replays the records written to a collection since `previous` onto
+	/// `base`, keeping their messages, authorship, and set operations, and
+	/// points the collection's local head at the result. The changes each
+	/// record made to its attachments are applied on top of `base`'s.
+	fn replay_queued(
+		&self,
+		collection: &str,
+		previous: Option<Oid>,
+		base: Option<Oid>,
+	) -> Result<()> {
+		let ref_head = format!("refs/heads/{collection}");
+		let Ok(head) = self.repo.refname_to_id(&ref_head) else {
+			// Deleted, so there's nothing to replay.
+			return Ok(());
+		};
+
+		let mut walk = self.repo.revwalk()?;
+		walk.set_sorting(git2::Sort::TOPOLOGICAL | git2::Sort::REVERSE)?;
+		walk.push(head)?;
+		for hidden in previous
+			.into_iter()
+			.chain([self.set_add_oid, self.set_del_oid])
+		{
+			walk.hide(hidden)?;
+		}
+
+		let empty_tree = self.repo.find_tree(self.repo.treebuilder(None)?.write()?)?;
+		let mut new_head = base;
+		for oid in walk {
+			let record = self.repo.find_commit(oid?)?;
+			let (operators, parents): (Vec<_>, Vec<_>) = record
+				.parents()
+				.partition(|parent| [self.set_add_oid, self.set_del_oid].contains(&parent.id()));
+			let old_tree = match parents.first() {
+				Some(parent) => parent.tree()?,
+				None => empty_tree.clone(),
+			};
+
+			let mut update = TreeUpdateBuilder::new();
+			let diff = self
+				.repo
+				.diff_tree_to_tree(Some(&old_tree), Some(&record.tree()?), None)?;
+			for delta in diff.deltas() {
+				match (
+					delta.status(),
+					delta.old_file().path(),
+					delta.new_file().path(),
+				) {
+					(git2::Delta::Deleted, Some(path), _) => {
+						update.remove(path);
+					}
+					(_, _, Some(path)) => {
+						update.upsert(path, delta.new_file().id(), delta.new_file().mode());
+					}
+					_ => {}
+				}
+			}
+
+			let base_commit = new_head.map(|oid| self.repo.find_commit(oid)).transpose()?;
+			let base_tree = match &base_commit {
+				Some(commit) => commit.tree()?,
+				None => empty_tree.clone(),
+			};
+			let tree = self
+				.repo
+				.find_tree(update.create_updated(&self.repo, &base_tree)?)?;
+
+			let parents = base_commit.into_iter().chain(operators).collect::<Vec<_>>();
+			new_head = Some(self.repo.commit(
+				None,
+				&record.author(),
+				&record.committer(),
+				&String::from_utf8_lossy(record.message_raw_bytes()),
+				&tree,
+				&parents.iter().collect::<Vec<_>>(),
+			)?);
+		}
+
+		self.restore_heads(&BTreeMap::from([(collection.to_string(), new_head)]))
+	}
+
+	/// Rolls back every collection written to since [`GitRemote::defer_pushes`],
+	/// and stops deferring pushes.
+	pub fn discard_deferred(&self) -> Result<()> {
+		if let Some(deferred) = self.deferred.take() {
+			self.restore_heads(&deferred)?;
+		}
+		self.save_queue()
+	}
+
+	/// Counts the records written since [`GitRemote::defer_pushes`] (or
+	/// queued while offline), and the collections deleted.
+	pub fn deferred_changes(&self) -> Result<usize> {
+		Ok(self
+			.deferred_records()?
+			.iter()
+			.map(|(_, records)| records.as_ref().map_or(1, Vec::len))
+			.sum())
+	}
+
+	/// Lists the collections written to since [`GitRemote::defer_pushes`],
+	/// in alphabetical order, along with the records written to each
+	/// (oldest first, with their operation for sets), or `None` for
+	/// collections that were deleted.
+	#[allow(clippy::type_complexity)]
+	pub fn deferred_records(
+		&self,
+	) -> Result<Vec<(String, Option<Vec<(GitRecord<'_>, Option<SetOperation>)>>)>> {
+		let deferred = self.deferred.borrow().clone().unwrap_or_default();
+
+		let mut collections = Vec::new();
+		for (collection, previous) in deferred {
+			let Ok(head) = self.repo.refname_to_id(&format!("refs/heads/{collection}")) else {
+				collections.push((collection, None));
+				continue;
+			};
+
+			let mut walk = self.repo.revwalk()?;
+			walk.push(head)?;
+			for hidden in previous
+				.into_iter()
+				.chain([self.set_add_oid, self.set_del_oid])
+			{
+				walk.hide(hidden)?;
+			}
+
+			let mut records = GitIterator(self, walk)
+				.map(|record| {
+					let record = record?;
+					let op = record.1.parents().find_map(|parent| match parent.id() {
+						id if id == self.set_add_oid => Some(SetOperation::Add),
+						id if id == self.set_del_oid => Some(SetOperation::Del),
+						_ => None,
+					});
+					Ok((record, op))
+				})
+				.collect::<Result<Vec<_>>>()?;
+			records.reverse();
+			collections.push((collection, Some(records)));
+		}
+
+		Ok(collections)
+	}
+
+	/// Where the offline queue is kept in the local repository: a line per
+	/// queued collection, with its head from before (or `-` if it didn't
+	/// exist) and its name.
+	fn queue_path(&self) -> PathBuf {
+		self.repo.path().join("minimap-queue")
+	}
+
+	/// Reads the offline queue saved by [`GitRemote::save_queue`].
+	fn load_queue(&self) -> Result<BTreeMap<String, Option<Oid>>> {
+		let queue = match ::std::fs::read_to_string(self.queue_path()) {
+			Ok(queue) => queue,
+			Err(e) if e.kind() == ::std::io::ErrorKind::NotFound => return Ok(BTreeMap::new()),
+			Err(e) => return Err(e.into()),
+		};
+
+		queue
+			.lines()
+			.map(|line| {
+				let (previous, collection) = line
+					.split_once(' ')
+					.ok_or_else(|| Error::Malformed(format!("offline queue entry {line:?}")))?;
+				let previous = match previous {
+					"-" => None,
+					oid => Some(Oid::from_str(oid)?),
+				};
+				Ok((collection.to_string(), previous))
+			})
+			.collect()
+	}
+
+	/// Saves the offline queue (if writes are queued while offline),
+	/// removing it once it's empty.
+	fn save_queue(&self) -> Result<()> {
+		if !self.offline_queue {
+			return Ok(());
+		}
+
+		let queue = self
+			.deferred
+			.borrow()
+			.iter()
+			.flatten()
+			.map(|(collection, previous)| match previous {
+				Some(previous) => format!("{previous} {collection}\n"),
+				None => format!("- {collection}\n"),
+			})
+			.collect::<String>();
+		if !queue.is_empty() {
+			::std::fs::write(self.queue_path(), queue)?;
+			return Ok(());
+		}
+
+		match ::std::fs::remove_file(self.queue_path()) {
+			Err(e) if e.kind() != ::std::io::ErrorKind::NotFound => Err(e.into()),
+			_ => Ok(()),
+		}
+	}
+
+	/// Points collections back at the given heads (deleting
+	/// those that didn't exist).
+	fn restore_heads(&self, heads: &BTreeMap<String, Option<Oid>>) -> Result<()> {
+		for (collection, head) in heads {
+			let ref_head = format!("refs/heads/{collection}");
+			match head {
+				Some(head) => {
+					self.repo
+						.reference(&ref_head, *head, true, "minimap: discard deferred")?;
+				}
+				None => {
+					if let Ok(mut reference) = self.repo.find_reference(&ref_head) {
+						reference.delete()?;
+					}
+				}
+			}
+		}
+
+		Ok(())
+	}
+
+	/// Updates a collection's local head, rather than pushing it, if pushes are
+	/// deferred. Returns `false` if they aren't.
+	fn defer_head(&self, collection: &str, head: Option<Oid>) -> Result<bool> {
+		let ref_head = format!("refs/heads/{collection}");
+		{
+			let mut deferred = self.deferred.borrow_mut();
+			let Some(deferred) = deferred.as_mut() else {
+				return Ok(false);
+			};
+
+			let previous = self.repo.refname_to_id(&ref_head).ok();
+			deferred.entry(collection.to_string()).or_insert(previous);
+		}
+		self.save_queue()?;
+		match head {
+			Some(head) => {
+				self.repo
+					.reference(&ref_head, head, true, &format!("commit: {head}"))?;
+			}
+			None => {
+				if let Ok(mut reference) = self.repo.find_reference(&ref_head) {
+					reference.delete()?;
+				}
+			}
+		}
+
+		Ok(true)
+	}
+
+	/// Pushes a collection's new head (or its deletion, for `None`) like
+	/// [`GitRemote::push_ref`], but queues it instead if the remote can't be
+	/// reached and offline writes are queued. Returns `false` if queued; the
+	/// local ref is then up to date already.
+	fn push_or_queue(&self, refspec: &str, collection: &str, head: Option<Oid>) -> Result<bool> {
+		let ref_head = format!("refs/heads/{collection}");
+		match self.push_ref(refspec, &ref_head, collection) {
+			Ok(()) => Ok(true),
+			Err(e) if self.offline_queue && is_network_error(&e) => {
+				self.defer_pushes();
+				self.defer_head(collection, head)?;
+				Ok(false)
+			}
+			Err(e) => Err(e),
+		}
+	}
+
+	/// Pushes a single refspec to the remote, checking that `ref_name`
+	/// was actually updated. `collection` is only used for error reporting.
+	fn push_ref(&self, refspec: &str, ref_name: &str, collection: &str) -> Result<()> {
+		match self.push_refs(&[refspec.to_string()])?.remove(ref_name) {
+			None => Err(Error::NotPushed(collection.to_string())),
+			Some(Some(status)) => Err(Error::PushFailed(collection.to_string(), status)),
+			Some(None) => Ok(()),
+		}
+	}
+
+	/// Pushes refspecs to the remote in one go, returning the status of
+	/// each ref that the remote reported on: `None` if it was updated, or
+	/// the reason it wasn't.
+	fn push_refs(&self, refspecs: &[String]) -> Result<HashMap<String, Option<String>>> {
+		let mut remote = self.repo.find_remote("origin")?;
+		let pushed_statuses = RefCell::new(HashMap::new());
+
+		self.credentials.run(|mut callbacks| {
+			callbacks.push_update_reference(|refname, status| {
+				pushed_statuses
+					.borrow_mut()
+					.insert(refname.to_string(), status.map(|s| s.to_string()));
+				Ok(())
+			});
+
+			remote.push(
+				refspecs,
+				Some(PushOptions::new().remote_callbacks(callbacks)),
+			)
+		})?;
+
+		Ok(pushed_statuses.into_inner())
+	}
 }
 
 /// A singular git record (a wrapper around a [`git2::Commit`]).
@@ -206,20 +709,129 @@ impl<'a> Remote<'a> for GitRemote {
 	}
 
 	fn set_add_unchecked(&'a self, collection: &str, message: &str) -> Result<Self::Record> {
-		let mut b = self.record_builder(collection);
-		b.add_parent(self.set_add_oid);
-		b.commit(message)
+		self.set_record_builder(collection, SetOperation::Add)
+			.commit(message)
 	}
 
 	fn set_del_unchecked(&'a self, collection: &str, message: &str) -> Result<Self::Record> {
+		self.set_record_builder(collection, SetOperation::Del)
+			.commit(message)
+	}
+
+	fn set_record_builder(&'a self, collection: &str, op: SetOperation) -> Self::RecordBuilder {
 		let mut b = self.record_builder(collection);
-		b.add_parent(self.set_del_oid);
-		b.commit(message)
+		b.add_parent(match op {
+			SetOperation::Add => self.set_add_oid,
+			SetOperation::Del => self.set_del_oid,
+		});
+		b
 	}
 
 	fn walk_set(&'a self, collection: &str) -> Result<Self::SetIterator> {
 		Ok(GitSetIterator(self.walk(collection)?))
 	}
+
+	fn collections(&'a self, prefix: &str) -> Result<Vec<String>> {
+		let mut collections = Vec::new();
+
+		for reference in self.repo.references()? {
+			let reference = reference?;
+			let name = match reference.name().and_then(|n| n.strip_prefix("refs/heads/")) {
+				Some(name) => name,
+				None => continue,
+			};
+
+			if is_collection_under(name, prefix) {
+				collections.push(name.to_string());
+			}
+		}
+
+		Ok(collections)
+	}
+
+	fn delete_collection(&'a self, collection: &str) -> Result<bool> {
+		let ref_head = format!("refs/heads/{collection}");
+
+		let mut reference = match self.repo.find_reference(&ref_head) {
+			Ok(reference) => reference,
+			Err(e) if e.code() == git2::ErrorCode::NotFound => return Ok(false),
+			Err(e) => return Err(e.into()),
+		};
+
+		if self.defer_head(collection, None)? {
+			return Ok(true);
+		}
+
+		// Same as with commits; only touch the local ref once
+		// the remote has accepted the deletion.
+		if self.push_or_queue(&format!(":{ref_head}"), collection, None)? {
+			reference.delete()?;
+		}
+
+		Ok(true)
+	}
+
+	fn fetch(&'a self) -> Result<Vec<(String, FetchChange)>> {
+		let before = self.heads()?;
+		// Deferred writes are kept, to be pushed later.
+		let deferred = self
+			.deferred
+			.borrow()
+			.iter()
+			.flat_map(BTreeMap::keys)
+			.map(|collection| {
+				let head = before.get(collection).copied();
+				(collection.clone(), head)
+			})
+			.collect::<BTreeMap<_, _>>();
+
+		let mut remote = self.repo.find_remote("origin")?;
+		self.credentials.run(|callbacks| {
+			let mut fetch_opts = FetchOptions::new();
+			fetch_opts.update_fetchhead(false);
+			fetch_opts.download_tags(AutotagOption::All);
+			fetch_opts.prune(FetchPrune::On);
+			fetch_opts.remote_callbacks(callbacks);
+
+			// Writes only touch local refs once they've been pushed, so
+			// local refs never have anything the remote doesn't, and can
+			// simply be overwritten (but for deferred ones, put back below).
+			remote.fetch(&["+refs/heads/*:refs/heads/*"], Some(&mut fetch_opts), None)
+		})?;
+		self.restore_heads(&deferred)?;
+
+		let after = self.heads()?;
+		let mut changes = Vec::new();
+		for (name, &oid) in &after {
+			let old = before.get(name).copied();
+			if old == Some(oid) {
+				continue;
+			}
+
+			let mut walk = self.repo.revwalk()?;
+			walk.push(oid)?;
+			for hidden in old.into_iter().chain([self.set_add_oid, self.set_del_oid]) {
+				walk.hide(hidden)?;
+			}
+			let records = walk.count();
+
+			changes.push((
+				name.clone(),
+				match old {
+					Some(_) => FetchChange::Updated(records),
+					None => FetchChange::Created(records),
+				},
+			));
+		}
+		for name in before.keys() {
+			if !after.contains_key(name) {
+				changes.push((name.clone(), FetchChange::Deleted));
+			}
+		}
+		changes.sort_by(|(a, _), (b, _)| a.cmp(b));
+
+		Ok(changes)
+	}
 }
 
 /// An iterator over a set of records in a collection. The iterator returns
@@ -327,6 +939,18 @@ impl<'b> Record for GitRecord<'b> {
 		let blob = self.0.repo.find_blob(entry.id())?;
 		Ok(Some(blob.content().to_vec()))
 	}
+	fn attachment_names(&self) -> Result<Vec<String>> {
+		let mut names = Vec::new();
+		self.1.tree()?.walk(TreeWalkMode::PreOrder, |root, entry| {
+			if entry.kind() == Some(ObjectType::Blob) {
+				if let Some(name) = entry.name() {
+					names.push(format!("{root}{name}"));
+				}
+			}
+			TreeWalkResult::Ok
+		})?;
+		Ok(names)
+	}
 }
 
 /// Builds a commit (with attachments) in order to submit it to a [`GitRemote`].
@@ -335,6 +959,7 @@ pub struct GitRecordBuilder<'a> {
 	branch: String,
 	update: TreeUpdateBuilder,
 	additional_parents: Vec<Oid>,
+	signature: Option<Signature<'static>>,
 }
 
 impl<'a> GitRecordBuilder<'a> {
@@ -345,6 +970,7 @@ impl<'a> GitRecordBuilder<'a> {
 			branch: branch.to_string(),
 			update: TreeUpdateBuilder::new(),
 			additional_parents: Vec::new(),
+			signature: None,
 		}
 	}
 
@@ -371,6 +997,15 @@ impl<'a> RecordBuilder<'a> for GitRecordBuilder<'a> {
 		Ok(self)
 	}
 
+	fn authored(self, author: &str, email: &str, timestamp: i64) -> Self {
+		Self {
+			// Fall back to the repository's own signature if the
+			// authorship can't be represented (e.g. an empty name).
+			signature: Signature::new(author, email, &Time::new(timestamp, 0)).ok(),
+			..self
+		}
+	}
+
 	fn commit(self, message: &str) -> Result<Self::Record> {
 		let ref_head = format!("refs/heads/{}", self.branch);
 
@@ -392,7 +1027,10 @@ impl<'a> RecordBuilder<'a> for GitRecordBuilder<'a> {
 		let tree_oid = update.create_updated(&self.workspace.repo, &base_tree)?;
 		let tree = self.workspace.repo.find_tree(tree_oid)?;
 
-		let sig = self.workspace.repo.signature()?;
+		let sig = match self.signature {
+			Some(sig) => sig,
+			None => self.workspace.signature()?,
+		};
 
 		let mut parents = head.map(|h| vec![h]).unwrap_or_default();
 		for additional_parent in self.additional_parents {
@@ -407,61 +1045,49 @@ impl<'a> RecordBuilder<'a> for GitRecordBuilder<'a> {
 			.repo
 			.commit(None, &sig, &sig, message, &tree, &parent_refs)?;
 
+		if self.workspace.defer_head(&self.branch, Some(commit))? {
+			let commit = self.workspace.repo.find_commit(commit)?;
+			return Ok(GitRecord(self.workspace, commit));
+		}
+
 		// Now push the commit to the remote. We don't update the local ref
 		// yet until the push succeeds. Yes, this creates a bit of a race condition,
 		// but the more error-prone operation is the push, whereas the local ref update
 		// is trivial and only fails if there's some sort of disk I/O failure, or if something
 		// else is modifies the repository at the same time.
-		let mut remote = self.workspace.repo.find_remote("origin")?;
-		let pushed_status = RefCell::new(None);
-		let mut callbacks = RemoteCallbacks::new();
-
-		callbacks.credentials(|_url, username_from_url, _allowed_types| {
-			Cred::ssh_key(
-				username_from_url.unwrap(),
-				None,
-				Path::new(&format!(
-					"{}/.ssh/id_rsa",
-					std::env::var("HOME").expect("HOME environment variable not set")
-				)),
-				None,
-			)
-		});
-
-		callbacks.push_update_reference(|refname, status| {
-			if refname == ref_head {
-				pushed_status
-					.borrow_mut()
-					.replace(status.map(|s| s.to_string()));
-			}
-			Ok(())
-		});
-
-		remote.push(
-			&[format!("{commit}:{ref_head}")],
-			Some(PushOptions::new().remote_callbacks(callbacks)),
+		let pushed = self.workspace.push_or_queue(
+			&format!("{commit}:{ref_head}"),
+			&self.branch,
+			Some(commit),
 		)?;
 
-		match pushed_status.take() {
-			None => Err(Error::NotPushed(self.branch)),
-			Some(Some(status)) => Err(Error::PushFailed(self.branch, status)),
-			Some(None) => {
-				// Finally update the branch's ref to the newly created commit
-				// in our local repository.
-				self.workspace.repo.reference(
-					&ref_head,
-					commit,
-					true,
-					&format!("commit: {commit}"),
-				)?;
-
-				let commit = self.workspace.repo.find_commit(commit)?;
-				Ok(GitRecord(self.workspace, commit))
-			}
+		// Finally update the branch's ref to the newly created commit
+		// in our local repository (unless queued, which did already).
+		if pushed {
+			self.workspace
+				.repo
+				.reference(&ref_head, commit, true, &format!("commit: {commit}"))?;
 		}
+
+		let commit = self.workspace.repo.find_commit(commit)?;
+		Ok(GitRecord(self.workspace, commit))
 	}
 }
 
+/// Whether an error means the remote couldn't be reached, as opposed to
+/// e.g. it rejecting a push. Failing to connect at all is reported as an OS
+/// error, which is what pushes fail with for local remotes that are gone
+/// (e.g. on an unmounted drive) too.
+fn is_network_error(e: &Error) -> bool {
+	matches!(
+		e,
+		Error::Git(e) if matches!(
+			e.class(),
+			git2::ErrorClass::Net | git2::ErrorClass::Os | git2::ErrorClass::Http | git2::ErrorClass::Ssl
+		)
+	)
+}
+
 /// Generates the temporary directory for a given remote
 /// by first hashing the remote and using that as a subfolder
 /// in the standard temporary directory joined with the
@@ -567,11 +1193,288 @@ mod test {
 
 	include!("../acceptance-tests.inc.rs");
 
+	#[test]
+	fn test_remote_identity() {
+		let remote = create_test_remote!();
+		assert_eq!(
+			remote.identity().unwrap(),
+			("Test User".to_string(), "test@example.com".to_string())
+		);
+
+		let remote = remote.with_identity("Someone Else", "else@example.com");
+		assert_eq!(
+			remote.identity().unwrap(),
+			("Someone Else".to_string(), "else@example.com".to_string())
+		);
+
+		let record = remote.record_builder("identity").commit("hello").unwrap();
+		assert_eq!(record.author(), "Someone Else");
+		assert_eq!(record.email(), "else@example.com");
+	}
+
+	#[test]
+	fn test_remote_fetch() {
+		let (path, uri) = get_remote_uri(function!().to_string());
+		let ours = init_test_remote(&path, &uri);
+		ours.record_builder("kept").commit("one").unwrap();
+		ours.record_builder("deleted").commit("one").unwrap();
+		assert_eq!(ours.fetch().unwrap(), vec![]);
+
+		// A different URL for the same repository gets its own local copy.
+		let their_uri = format!("{}/", uri);
+		let their_dir = generate_tmp_dir(&their_uri).unwrap();
+		::std::fs::remove_dir_all(their_dir).unwrap();
+		let theirs = GitRemote::open(&their_uri)
+			.unwrap()
+			.with_identity("Someone Else", "else@example.com");
+		assert_eq!(
+			theirs.fetch().unwrap(),
+			vec![
+				("deleted".to_string(), FetchChange::Created(1)),
+				("kept".to_string(), FetchChange::Created(1)),
+			]
+		);
+		theirs.record_builder("kept").commit("two").unwrap();
+		theirs.record_builder("kept").commit("three").unwrap();
+		theirs.set_add_unchecked("created", "item").unwrap();
+		assert!(theirs.delete_collection("deleted").unwrap());
+
+		assert_eq!(ours.latest("kept").unwrap().unwrap().message(), "one");
+		assert_eq!(
+			ours.fetch().unwrap(),
+			vec![
+				("created".to_string(), FetchChange::Created(1)),
+				("deleted".to_string(), FetchChange::Deleted),
+				("kept".to_string(), FetchChange::Updated(2)),
+			]
+		);
+		assert_eq!(ours.latest("kept").unwrap().unwrap().message(), "three");
+		assert!(ours.set_find("created", "item").unwrap().is_ok());
+		assert!(ours.walk("deleted").unwrap().next().is_none());
+		assert_eq!(ours.fetch().unwrap(), vec![]);
+	}
+
+	#[test]
+	fn test_remote_deferred_pushes() {
+		let (path, uri) = get_remote_uri(function!().to_string());
+		let ours = init_test_remote(&path, &uri);
+		ours.record_builder("deleted").commit("one").unwrap();
+		let upstream = Repository::open_bare(&path).unwrap();
+		let upstream_head = |collection: &str| {
+			upstream
+				.refname_to_id(&format!("refs/heads/{collection}"))
+				.ok()
+		};
+
+		ours.defer_pushes();
+		ours.record_builder("updated").commit("one").unwrap();
+		let latest = ours.record_builder("updated").commit("two").unwrap();
+		ours.set_add_unchecked("set", "item").unwrap();
+		assert!(ours.delete_collection("deleted").unwrap());
+		assert_eq!(ours.latest("updated").unwrap().unwrap().message(), "two");
+		assert!(upstream_head("updated").is_none());
+		assert!(upstream_head("deleted").is_some());
+
+		let deferred = ours
+			.deferred_records()
+			.unwrap()
+			.into_iter()
+			.map(|(collection, records)| {
+				let records = records.map(|records| {
+					records
+						.into_iter()
+						.map(|(record, op)| (record.message(), op))
+						.collect::<Vec<_>>()
+				});
+				(collection, records)
+			})
+			.collect::<Vec<_>>();
+		assert_eq!(
+			deferred,
+			vec![
+				("deleted".to_string(), None),
+				(
+					"set".to_string(),
+					Some(vec![("item".to_string(), Some(SetOperation::Add))])
+				),
+				(
+					"updated".to_string(),
+					Some(vec![("one".to_string(), None), ("two".to_string(), None)])
+				),
+			]
+		);
+
+		assert_eq!(ours.push_deferred().unwrap(), 3);
+		assert_eq!(upstream_head("updated").unwrap().to_string(), latest.id());
+		assert!(upstream_head("set").is_some());
+		assert!(upstream_head("deleted").is_none());
+		assert_eq!(ours.push_deferred().unwrap(), 0);
+
+		// Pushes aren't deferred anymore.
+		let latest = ours.record_builder("updated").commit("three").unwrap();
+		assert_eq!(upstream_head("updated").unwrap().to_string(), latest.id());
+
+		ours.defer_pushes();
+		ours.record_builder("updated").commit("discarded").unwrap();
+		ours.record_builder("created").commit("discarded").unwrap();
+		ours.discard_deferred().unwrap();
+		assert_eq!(ours.latest("updated").unwrap().unwrap().id(), latest.id());
+		assert!(ours.latest("created").unwrap().is_none());
+
+		// Collections changed elsewhere in the meantime are rolled back.
+		let their_uri = format!("{}/", uri);
+		::std::fs::remove_dir_all(generate_tmp_dir(&their_uri).unwrap()).unwrap();
+		let theirs = GitRemote::open(&their_uri)
+			.unwrap()
+			.with_identity("Someone Else", "else@example.com");
+		theirs.fetch().unwrap();
+
+		ours.defer_pushes();
+		ours.record_builder("updated").commit("ours").unwrap();
+		let their_latest = theirs.record_builder("updated").commit("theirs").unwrap();
+		assert!(ours.push_deferred().is_err());
+		assert_eq!(ours.latest("updated").unwrap().unwrap().id(), latest.id());
+		assert_eq!(
+			upstream_head("updated").unwrap().to_string(),
+			their_latest.id()
+		);
+	}
+
+	#[test]
+	fn test_remote_offline_queue() {
+		let (path, uri) = get_remote_uri(function!().to_string());
+		let ours = init_test_remote(&path, &uri).with_offline_queue().unwrap();
+		ours.record_builder("deleted").commit("one").unwrap();
+		let upstream = Repository::open_bare(&path).unwrap();
+		let upstream_head = |collection: &str| {
+			upstream
+				.refname_to_id(&format!("refs/heads/{collection}"))
+				.ok()
+		};
+
+		// Nothing listens there, so the remote can't be reached.
+		ours.repo
+			.remote_set_url("origin", "http://127.0.0.1:9/unreachable.git")
+			.unwrap();
+		let latest = ours.record_builder("updated").commit("one").unwrap();
+		ours.set_add_unchecked("set", "item").unwrap();
+		assert!(ours.delete_collection("deleted").unwrap());
+		assert_eq!(ours.latest("updated").unwrap().unwrap().id(), latest.id());
+		assert!(ours.latest("deleted").unwrap().is_none());
+		assert_eq!(ours.deferred_changes().unwrap(), 3);
+
+		// Still offline; nothing is lost.
+		assert!(ours.fetch().is_err());
+		assert!(ours.push_deferred().is_err());
+		assert_eq!(ours.deferred_changes().unwrap(), 3);
+		assert_eq!(ours.latest("updated").unwrap().unwrap().id(), latest.id());
+
+		ours.repo.remote_set_url("origin", &uri).unwrap();
+		// Fetching keeps the queued writes.
+		ours.fetch().unwrap();
+		assert_eq!(ours.latest("updated").unwrap().unwrap().id(), latest.id());
+		assert!(ours.latest("deleted").unwrap().is_none());
+
+		assert_eq!(ours.push_deferred().unwrap(), 3);
+		assert_eq!(ours.deferred_changes().unwrap(), 0);
+		assert_eq!(upstream_head("updated").unwrap().to_string(), latest.id());
+		assert!(upstream_head("set").is_some());
+		assert!(upstream_head("deleted").is_none());
+
+		// Rejected pushes aren't queued.
+		let their_uri = format!("{}/", uri);
+		::std::fs::remove_dir_all(generate_tmp_dir(&their_uri).unwrap()).unwrap();
+		let theirs = GitRemote::open(&their_uri)
+			.unwrap()
+			.with_identity("Someone Else", "else@example.com");
+		theirs.fetch().unwrap();
+		let their_latest = theirs.record_builder("updated").commit("theirs").unwrap();
+		assert!(ours.record_builder("updated").commit("ours").is_err());
+		assert_eq!(ours.deferred_changes().unwrap(), 0);
+		assert_eq!(ours.latest("updated").unwrap().unwrap().id(), latest.id());
+		assert_eq!(
+			upstream_head("updated").unwrap().to_string(),
+			their_latest.id()
+		);
+	}
+
+	#[test]
+	fn test_remote_offline_queue_conflicts() {
+		let (path, uri) = get_remote_uri(function!().to_string());
+		let ours = init_test_remote(&path, &uri).with_offline_queue().unwrap();
+		ours.record_builder("updated")
+			.upsert_attachment("shared", "base")
+			.unwrap()
+			.commit("base")
+			.unwrap();
+
+		let their_uri = format!("{}/", uri);
+		::std::fs::remove_dir_all(generate_tmp_dir(&their_uri).unwrap()).unwrap();
+		let theirs = GitRemote::open(&their_uri)
+			.unwrap()
+			.with_identity("Someone Else", "else@example.com");
+		theirs.fetch().unwrap();
+
+		ours.repo
+			.remote_set_url("origin", "http://127.0.0.1:9/unreachable.git")
+			.unwrap();
+		ours.record_builder("updated")
+			.upsert_attachment("shared", "ours")
+			.unwrap()
+			.upsert_attachment("ours", "ours")
+			.unwrap()
+			.commit("ours")
+			.unwrap();
+		ours.set_add_unchecked("set", "ours").unwrap();
+		assert_eq!(ours.deferred_changes().unwrap(), 2);
+
+		theirs
+			.record_builder("updated")
+			.upsert_attachment("shared", "theirs")
+			.unwrap()
+			.upsert_attachment("theirs", "theirs")
+			.unwrap()
+			.commit("theirs")
+			.unwrap();
+		theirs.set_add_unchecked("set", "theirs").unwrap();
+
+		// The queue survives reopening the remote.
+		drop(ours);
+		let ours = GitRemote::open(&uri).unwrap().with_offline_queue().unwrap();
+		assert_eq!(ours.deferred_changes().unwrap(), 2);
+
+		// Queued records are replayed onto their changes, rather than lost.
+		ours.repo.remote_set_url("origin", &uri).unwrap();
+		assert_eq!(ours.push_deferred().unwrap(), 2);
+		assert_eq!(ours.deferred_changes().unwrap(), 0);
+		ours.fetch().unwrap();
+
+		let messages = ours
+			.walk("updated")
+			.unwrap()
+			.map(|record| record.unwrap().message())
+			.collect::<Vec<_>>();
+		assert_eq!(messages, vec!["ours", "theirs", "base"]);
+		let latest = ours.latest("updated").unwrap().unwrap();
+		assert_ne!(latest.author(), "Someone Else");
+		for (name, data) in [("shared", "ours"), ("ours", "ours"), ("theirs", "theirs")] {
+			assert_eq!(latest.attachment(name).unwrap().unwrap(), data.as_bytes());
+		}
+		for item in ["ours", "theirs"] {
+			assert!(ours.set_find("set", item).unwrap().is_ok());
+		}
+
+		// Nothing is left queued for next time.
+		let reopened = GitRemote::open(&uri).unwrap().with_offline_queue().unwrap();
+		assert_eq!(reopened.deferred_changes().unwrap(), 0);
+	}
+
 	#[test]
 	fn test_remote_minimap_dependencies() {
-		let our_workspace = Workspace::open(create_test_remote!());
+		let our_workspace = Workspace::open(create_test_remote!()).unwrap();
 		let (their_path, their_remote_uri) = get_remote_uri(format!("{}-other", function!()));
-		let their_workspace = Workspace::open(init_test_remote(&their_path, &their_remote_uri));
+		let their_workspace =
+			Workspace::open(init_test_remote(&their_path, &their_remote_uri)).unwrap();
 
 		let our_project = our_workspace.create_project("test").unwrap().unwrap();
 		let their_project = their_workspace.create_project("other").unwrap().unwrap();
@@ -631,4 +1534,48 @@ mod test {
 
 		assert!(found)
 	}
+
+	#[test]
+	fn test_remote_minimap_dependency_cache() {
+		let remotes = ["a", "b"].map(|suffix| {
+			let (path, uri) = get_remote_uri(format!("{}-{}", function!(), suffix));
+			let workspace = Workspace::open(init_test_remote(&path, &uri)).unwrap();
+			let project = workspace.create_project("other").unwrap().unwrap();
+			project.create_ticket().unwrap();
+			project.create_ticket().unwrap();
+			uri
+		});
+
+		let origin = MinimapDependencyOrigin::with_cache_size(1);
+		for slug in ["other-1", "other-2"] {
+			assert_eq!(
+				origin.status(&format!("{}@{}", remotes[0], slug)).unwrap(),
+				DependencyStatus::Pending
+			);
+		}
+		assert_eq!(origin.cached_remotes(), vec![remotes[0].clone()]);
+
+		// The least recently used remote is evicted.
+		origin.status(&format!("{}@other-1", remotes[1])).unwrap();
+		assert_eq!(origin.cached_remotes(), vec![remotes[1].clone()]);
+
+		// Changes made after a remote was cached are still seen.
+		let workspace = Workspace::open(GitRemote::open(&remotes[1]).unwrap()).unwrap();
+		workspace
+			.ticket("other-1")
+			.unwrap()
+			.set_state(TicketState::Closed)
+			.unwrap();
+		assert_eq!(
+			origin.status(&format!("{}@other-1", remotes[1])).unwrap(),
+			DependencyStatus::Complete
+		);
+
+		origin.clear_cache();
+		assert!(origin.cached_remotes().is_empty());
+
+		let uncached = MinimapDependencyOrigin::with_cache_size(0);
+		uncached.status(&format!("{}@other-1", remotes[0])).unwrap();
+		assert!(uncached.cached_remotes().is_empty());
+	}
 }
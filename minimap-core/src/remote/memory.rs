@@ -1,6 +1,6 @@
 //! An in-memory Minimap workspace, useful for testing.
 
-use crate::{Error, Record, RecordBuilder, Remote, Result, SetOperation};
+use crate::{is_collection_under, Error, Record, RecordBuilder, Remote, Result, SetOperation};
 use sha2::{Digest, Sha256};
 use std::{
 	collections::HashMap,
@@ -148,17 +148,19 @@ impl<'a> Remote<'a> for MemoryRemote {
 	}
 
 	fn set_add_unchecked(&'a self, collection: &str, message: &str) -> Result<Self::Record> {
-		self.record_builder(collection)
-			.op(SetOperation::Add)
+		self.set_record_builder(collection, SetOperation::Add)
 			.commit(message)
 	}
 
 	fn set_del_unchecked(&'a self, collection: &str, message: &str) -> Result<Self::Record> {
-		self.record_builder(collection)
-			.op(SetOperation::Del)
+		self.set_record_builder(collection, SetOperation::Del)
 			.commit(message)
 	}
 
+	fn set_record_builder(&'a self, collection: &str, op: SetOperation) -> Self::RecordBuilder {
+		self.record_builder(collection).op(op)
+	}
+
 	fn walk_set(&'a self, collection: &str) -> Result<Self::SetIterator> {
 		self.walk(collection).map(MemorySetIterator)
 	}
@@ -171,6 +173,34 @@ impl<'a> Remote<'a> for MemoryRemote {
 			.cloned()
 			.map(|record| MemoryRecordRef(self.state.clone(), record)))
 	}
+
+	fn collections(&'a self, prefix: &str) -> Result<Vec<String>> {
+		let state = self.state.lock().unwrap();
+		Ok(state
+			.heads
+			.keys()
+			.filter(|name| is_collection_under(name, prefix))
+			.cloned()
+			.collect())
+	}
+
+	fn delete_collection(&'a self, collection: &str) -> Result<bool> {
+		let mut state = self.state.lock().unwrap();
+
+		let mut next = match state.heads.remove(collection) {
+			Some(head) => Some(head),
+			None => return Ok(false),
+		};
+
+		// Records only ever belong to a single collection's chain,
+		// so they can all be dropped. Attachment data is content-addressed
+		// and might be shared, so it's left in the pool.
+		while let Some(id) = next {
+			next = state.records.remove(&id).and_then(|record| record.parent);
+		}
+
+		Ok(true)
+	}
 }
 
 impl Record for MemoryRecordRef {
@@ -208,6 +238,9 @@ impl Record for MemoryRecordRef {
 		let state = self.0.lock().unwrap();
 		Ok(state.attachment_pool.get(id).cloned())
 	}
+	fn attachment_names(&self) -> Result<Vec<String>> {
+		Ok(self.1.attachments.keys().cloned().collect())
+	}
 }
 
 /// The iterator type for [`MemoryRemote`].
@@ -265,6 +298,7 @@ pub struct MemoryRecordBuilder<'a> {
 	collection: String,
 	attachments: HashMap<String, Option<String>>,
 	op: Option<SetOperation>,
+	authored: Option<(String, String, i64)>,
 }
 
 impl<'a> MemoryRecordBuilder<'a> {
@@ -274,6 +308,7 @@ impl<'a> MemoryRecordBuilder<'a> {
 			collection,
 			attachments: HashMap::new(),
 			op: None,
+			authored: None,
 		}
 	}
 
@@ -300,12 +335,25 @@ impl<'a> RecordBuilder<'a> for MemoryRecordBuilder<'a> {
 		Ok(self)
 	}
 
+	fn authored(self, author: &str, email: &str, timestamp: i64) -> Self {
+		Self {
+			authored: Some((author.to_string(), email.to_string(), timestamp)),
+			..self
+		}
+	}
+
 	fn commit(self, message: &str) -> Result<Self::Record> {
 		let mut state = self.workspace.state.lock().unwrap();
-		let timestamp = SystemTime::now()
-			.duration_since(UNIX_EPOCH)
-			.unwrap()
-			.as_secs() as i64;
+		let (author, email, timestamp) = self.authored.unwrap_or_else(|| {
+			(
+				self.workspace.author.clone(),
+				self.workspace.email.clone(),
+				SystemTime::now()
+					.duration_since(UNIX_EPOCH)
+					.unwrap()
+					.as_secs() as i64,
+			)
+		});
 		let id = state.next_id();
 
 		// get the latest record, clone its attachments, and then
@@ -327,8 +375,8 @@ impl<'a> RecordBuilder<'a> for MemoryRecordBuilder<'a> {
 		let record = MemoryRecord {
 			id: id.clone(),
 			message: message.to_string(),
-			author: self.workspace.author.clone(),
-			email: self.workspace.email.clone(),
+			author,
+			email,
 			timestamp,
 			op: self.op,
 			attachments,
@@ -354,4 +402,26 @@ mod tests {
 	}
 
 	include!("../acceptance-tests.inc.rs");
+
+	#[test]
+	fn test_memory_shared_namespaces() {
+		let remote = create_test_remote!();
+		let default = Workspace::open(remote.clone()).unwrap();
+		let games = Workspace::open_namespaced(remote.clone(), "games").unwrap();
+		let tools = Workspace::open_namespaced(remote.clone(), "tools").unwrap();
+
+		// the same slugs can be used independently in each namespace
+		default.create_project("shared").unwrap().unwrap();
+		games.create_project("shared").unwrap().unwrap();
+		tools.create_project("shared").unwrap().unwrap();
+		tools.create_project("cli").unwrap().unwrap();
+
+		assert_eq!(default.projects().unwrap().len(), 1);
+		assert_eq!(games.projects().unwrap().len(), 1);
+		assert_eq!(tools.projects().unwrap().len(), 2);
+		assert_eq!(
+			Workspace::namespaces(&remote).unwrap(),
+			vec!["games", "tools"]
+		);
+	}
 }
@@ -0,0 +1,155 @@
+//! Namespaced workspaces.
+//!
+//! A [`NamespacedRemote`] wraps another remote and moves every collection
+//! under `ns/<namespace>/`, so that several independent workspaces can
+//! share a single remote (e.g. one git repository per team rather than
+//! one per product line). Records themselves are untouched, so record
+//! IDs stay valid across namespaces.
+
+use crate::{Error, FetchChange, Remote, Result, SetOperation, Workspace};
+
+const NAMESPACE_ROOT: &str = "ns";
+
+/// A remote whose collections all live under a namespace.
+/// See [`Workspace::open_namespaced`].
+pub struct NamespacedRemote<R> {
+	inner: R,
+	namespace: String,
+	prefix: String,
+}
+
+impl<R> NamespacedRemote<R> {
+	/// Wraps a remote, placing all collections under the given namespace.
+	///
+	/// Namespaces cannot be empty or contain `/` or whitespace
+	/// characters. Returns [`Error::Malformed`] otherwise.
+	pub fn new(inner: R, namespace: &str) -> Result<Self> {
+		if namespace.is_empty()
+			|| namespace.contains('/')
+			|| namespace.contains(char::is_whitespace)
+		{
+			return Err(Error::Malformed(namespace.to_string()));
+		}
+
+		Ok(Self {
+			inner,
+			namespace: namespace.to_string(),
+			prefix: format!("{}/{}/", NAMESPACE_ROOT, namespace),
+		})
+	}
+
+	/// Gets the namespace.
+	#[inline]
+	pub fn namespace(&self) -> &str {
+		&self.namespace
+	}
+
+	/// Gets the wrapped remote.
+	#[inline]
+	pub fn inner(&self) -> &R {
+		&self.inner
+	}
+
+	fn collection(&self, collection: &str) -> String {
+		format!("{}{}", self.prefix, collection)
+	}
+}
+
+impl<'a, R: Remote<'a>> Remote<'a> for NamespacedRemote<R> {
+	type Record = R::Record;
+	type RecordBuilder = R::RecordBuilder;
+	type Iterator = R::Iterator;
+	type SetIterator = R::SetIterator;
+
+	fn walk(&'a self, collection: &str) -> Result<Self::Iterator> {
+		self.inner.walk(&self.collection(collection))
+	}
+
+	fn record_builder(&'a self, collection: &str) -> Self::RecordBuilder {
+		self.inner.record_builder(&self.collection(collection))
+	}
+
+	fn get_record(&'a self, id: &str) -> Result<Option<Self::Record>> {
+		self.inner.get_record(id)
+	}
+
+	fn set_add_unchecked(&'a self, collection: &str, message: &str) -> Result<Self::Record> {
+		self.inner
+			.set_add_unchecked(&self.collection(collection), message)
+	}
+
+	fn set_del_unchecked(&'a self, collection: &str, message: &str) -> Result<Self::Record> {
+		self.inner
+			.set_del_unchecked(&self.collection(collection), message)
+	}
+
+	fn set_record_builder(&'a self, collection: &str, op: SetOperation) -> Self::RecordBuilder {
+		self.inner
+			.set_record_builder(&self.collection(collection), op)
+	}
+
+	fn collections(&'a self, prefix: &str) -> Result<Vec<String>> {
+		Ok(self
+			.inner
+			.collections(&self.collection(prefix))?
+			.into_iter()
+			.filter_map(|name| name.strip_prefix(&self.prefix).map(str::to_string))
+			.collect())
+	}
+
+	fn delete_collection(&'a self, collection: &str) -> Result<bool> {
+		self.inner.delete_collection(&self.collection(collection))
+	}
+
+	fn fetch(&'a self) -> Result<Vec<(String, FetchChange)>> {
+		// Changes to other namespaces are fetched too, but not reported.
+		Ok(self
+			.inner
+			.fetch()?
+			.into_iter()
+			.filter_map(|(name, change)| {
+				name.strip_prefix(&self.prefix)
+					.map(|name| (name.to_string(), change))
+			})
+			.collect())
+	}
+
+	fn walk_set(&'a self, collection: &str) -> Result<Self::SetIterator> {
+		self.inner.walk_set(&self.collection(collection))
+	}
+}
+
+impl<'a, R> Workspace<'a, NamespacedRemote<R>>
+where
+	R: for<'b> Remote<'b>,
+{
+	/// Opens the workspace living under a namespace of the remote
+	/// (see [`NamespacedRemote`]). Namespaces are created implicitly,
+	/// the first time something is written to them.
+	pub fn open_namespaced(remote: R, namespace: &str) -> Result<Self> {
+		Workspace::open(NamespacedRemote::new(remote, namespace)?)
+	}
+}
+
+impl<'a, R> Workspace<'a, R>
+where
+	R: for<'b> Remote<'b>,
+{
+	/// Lists the namespaces that exist in a remote, in alphabetical order.
+	/// The workspace outside of any namespace isn't listed.
+	pub fn namespaces(remote: &R) -> Result<Vec<String>> {
+		let mut namespaces = remote
+			.collections(NAMESPACE_ROOT)?
+			.into_iter()
+			.filter_map(|name| {
+				let rest = name.strip_prefix(NAMESPACE_ROOT)?.strip_prefix('/')?;
+				let (namespace, _) = rest.split_once('/')?;
+				Some(namespace.to_string())
+			})
+			.collect::<Vec<_>>();
+		namespaces.sort();
+		namespaces.dedup();
+
+		Ok(namespaces)
+	}
+}
@@ -0,0 +1,235 @@
+//! Observed remotes.
+//!
+//! An [`ObservedRemote`] wraps another remote and tells its observers
+//! about every record committed through it, as a [`RecordEvent`], so that
+//! frontends can refresh, notify or index as changes are made rather than
+//! polling for them. Changes made elsewhere aren't observed; they're
+//! reported by [`Remote::fetch`] instead.
+
+use crate::{FetchChange, RecordBuilder, RecordView, Remote, Result, SetOperation};
+use serde::Serialize;
+use std::sync::{
+	atomic::{AtomicU64, Ordering},
+	Arc, Mutex,
+};
+
+/// How a record changed its collection. See [`RecordEvent`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RecordOperation {
+	/// The record was committed to the collection.
+	Commit,
+	/// The record added an item to the set.
+	SetAdd,
+	/// The record removed an item from the set.
+	SetDel,
+}
+
+impl From<SetOperation> for RecordOperation {
+	fn from(op: SetOperation) -> Self {
+		match op {
+			SetOperation::Add => Self::SetAdd,
+			SetOperation::Del => Self::SetDel,
+		}
+	}
+}
+
+/// A record committed through an [`ObservedRemote`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct RecordEvent {
+	/// The collection the record was committed to.
+	pub collection: String,
+	/// How the record changed the collection.
+	pub operation: RecordOperation,
+	/// The record itself.
+	pub record: RecordView,
+}
+
+/// Observes the records committed through an [`ObservedRemote`].
+///
+/// Implemented for closures taking a [`RecordEvent`].
+pub trait RecordObserver: Send + Sync {
+	/// Called after a record is committed.
+	fn record_committed(&self, event: &RecordEvent);
+}
+
+impl<F: Fn(&RecordEvent) + Send + Sync> RecordObserver for F {
+	fn record_committed(&self, event: &RecordEvent) {
+		self(event)
+	}
+}
+
+/// Identifies an observer of an [`ObservedRemote`], to unsubscribe it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct ObserverId(u64);
+
+/// A remote that tells observers about the records committed through it.
+/// See the [module documentation](self) for details.
+pub struct ObservedRemote<R> {
+	inner: R,
+	observers: Mutex<Vec<(ObserverId, Arc<dyn RecordObserver>)>>,
+	next_id: AtomicU64,
+}
+
+impl<R> ObservedRemote<R> {
+	/// Wraps a remote, with no observers yet.
+	pub fn new(inner: R) -> Self {
+		Self {
+			inner,
+			observers: Mutex::new(Vec::new()),
+			next_id: AtomicU64::new(0),
+		}
+	}
+
+	/// Gets the wrapped remote.
+	#[inline]
+	pub fn inner(&self) -> &R {
+		&self.inner
+	}
+
+	/// Subscribes an observer to the records committed from now on.
+	/// Observers are called in order of subscription, once the record
+	/// has been committed; they may commit records of their own.
+	pub fn subscribe(&self, observer: Box<dyn RecordObserver>) -> ObserverId {
+		let id = ObserverId(self.next_id.fetch_add(1, Ordering::Relaxed));
+		self.observers
+			.lock()
+			.unwrap()
+			.push((id, Arc::from(observer)));
+		id
+	}
+
+	/// Unsubscribes an observer. Returns `false` if it wasn't subscribed.
+	pub fn unsubscribe(&self, id: ObserverId) -> bool {
+		let mut observers = self.observers.lock().unwrap();
+		let count = observers.len();
+		observers.retain(|(subscribed, _)| *subscribed != id);
+		observers.len() != count
+	}
+
+	fn notify(&self, collection: &str, operation: RecordOperation, record: RecordView) {
+		// Observers are called without holding the lock, so that they can
+		// commit (and subscribe) themselves.
+		let observers = self
+			.observers
+			.lock()
+			.unwrap()
+			.iter()
+			.map(|(_, observer)| observer.clone())
+			.collect::<Vec<_>>();
+		if observers.is_empty() {
+			return;
+		}
+
+		let event = RecordEvent {
+			collection: collection.to_string(),
+			operation,
+			record,
+		};
+		for observer in observers {
+			observer.record_committed(&event);
+		}
+	}
+}
+
+impl<'a, R: Remote<'a>> Remote<'a> for ObservedRemote<R> {
+	type Record = R::Record;
+	type RecordBuilder = ObservedRecordBuilder<'a, R>;
+	type Iterator = R::Iterator;
+	type SetIterator = R::SetIterator;
+
+	fn walk(&'a self, collection: &str) -> Result<Self::Iterator> {
+		self.inner.walk(collection)
+	}
+
+	fn record_builder(&'a self, collection: &str) -> Self::RecordBuilder {
+		ObservedRecordBuilder {
+			remote: self,
+			inner: self.inner.record_builder(collection),
+			collection: collection.to_string(),
+			operation: RecordOperation::Commit,
+		}
+	}
+
+	fn get_record(&'a self, id: &str) -> Result<Option<Self::Record>> {
+		self.inner.get_record(id)
+	}
+
+	fn set_add_unchecked(&'a self, collection: &str, message: &str) -> Result<Self::Record> {
+		let record = self.inner.set_add_unchecked(collection, message)?;
+		self.notify(collection, RecordOperation::SetAdd, record.clone().into());
+		Ok(record)
+	}
+
+	fn set_del_unchecked(&'a self, collection: &str, message: &str) -> Result<Self::Record> {
+		let record = self.inner.set_del_unchecked(collection, message)?;
+		self.notify(collection, RecordOperation::SetDel, record.clone().into());
+		Ok(record)
+	}
+
+	fn set_record_builder(&'a self, collection: &str, op: SetOperation) -> Self::RecordBuilder {
+		ObservedRecordBuilder {
+			remote: self,
+			inner: self.inner.set_record_builder(collection, op),
+			collection: collection.to_string(),
+			operation: op.into(),
+		}
+	}
+
+	fn collections(&'a self, prefix: &str) -> Result<Vec<String>> {
+		self.inner.collections(prefix)
+	}
+
+	fn delete_collection(&'a self, collection: &str) -> Result<bool> {
+		self.inner.delete_collection(collection)
+	}
+
+	fn fetch(&'a self) -> Result<Vec<(String, FetchChange)>> {
+		self.inner.fetch()
+	}
+
+	fn walk_set(&'a self, collection: &str) -> Result<Self::SetIterator> {
+		self.inner.walk_set(collection)
+	}
+}
+
+/// Builds a record for an [`ObservedRemote`], telling its observers once
+/// it's committed.
+pub struct ObservedRecordBuilder<'a, R: Remote<'a>> {
+	remote: &'a ObservedRemote<R>,
+	inner: R::RecordBuilder,
+	collection: String,
+	operation: RecordOperation,
+}
+
+impl<'a, R: Remote<'a>> RecordBuilder<'a> for ObservedRecordBuilder<'a, R> {
+	type Record = R::Record;
+
+	fn commit(self, message: &str) -> Result<Self::Record> {
+		let record = self.inner.commit(message)?;
+		self.remote
+			.notify(&self.collection, self.operation, record.clone().into());
+		Ok(record)
+	}
+
+	fn upsert_attachment<D: AsRef<[u8]>>(self, name: &str, data: D) -> Result<Self> {
+		Ok(Self {
+			inner: self.inner.upsert_attachment(name, data)?,
+			..self
+		})
+	}
+
+	fn remove_attachment(self, name: &str) -> Result<Self> {
+		Ok(Self {
+			inner: self.inner.remove_attachment(name)?,
+			..self
+		})
+	}
+
+	fn authored(self, author: &str, email: &str, timestamp: i64) -> Self {
+		Self {
+			inner: self.inner.authored(author, email, timestamp),
+			..self
+		}
+	}
+}
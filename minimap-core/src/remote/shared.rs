@@ -0,0 +1,55 @@
+//! Shared remotes.
+//!
+//! Workspaces take ownership of their remote. Wrapping a remote in an
+//! [`Rc`] lets it outlive the workspace, e.g. so that it can be cached
+//! and reused by later workspaces without opening it again.
+
+use crate::{FetchChange, Remote, Result, SetOperation};
+use std::rc::Rc;
+
+impl<'a, R: Remote<'a>> Remote<'a> for Rc<R> {
+	type Record = R::Record;
+	type RecordBuilder = R::RecordBuilder;
+	type Iterator = R::Iterator;
+	type SetIterator = R::SetIterator;
+
+	fn walk(&'a self, collection: &str) -> Result<Self::Iterator> {
+		(**self).walk(collection)
+	}
+
+	fn record_builder(&'a self, collection: &str) -> Self::RecordBuilder {
+		(**self).record_builder(collection)
+	}
+
+	fn get_record(&'a self, id: &str) -> Result<Option<Self::Record>> {
+		(**self).get_record(id)
+	}
+
+	fn set_add_unchecked(&'a self, collection: &str, message: &str) -> Result<Self::Record> {
+		(**self).set_add_unchecked(collection, message)
+	}
+
+	fn set_del_unchecked(&'a self, collection: &str, message: &str) -> Result<Self::Record> {
+		(**self).set_del_unchecked(collection, message)
+	}
+
+	fn set_record_builder(&'a self, collection: &str, op: SetOperation) -> Self::RecordBuilder {
+		(**self).set_record_builder(collection, op)
+	}
+
+	fn collections(&'a self, prefix: &str) -> Result<Vec<String>> {
+		(**self).collections(prefix)
+	}
+
+	fn delete_collection(&'a self, collection: &str) -> Result<bool> {
+		(**self).delete_collection(collection)
+	}
+
+	fn fetch(&'a self) -> Result<Vec<(String, FetchChange)>> {
+		(**self).fetch()
+	}
+
+	fn walk_set(&'a self, collection: &str) -> Result<Self::SetIterator> {
+		(**self).walk_set(collection)
+	}
+}
@@ -0,0 +1,329 @@
+//! Searching for tickets.
+//!
+//! There are two ways of searching: [`Project::find_tickets`] is a
+//! simple linear scan over a project's ticket titles and descriptions,
+//! while [`Workspace::search`] performs a ranked full-text search over
+//! the whole workspace using an incrementally updated [`SearchIndex`].
+
+use crate::{Error, Project, Record, Remote, Result, Ticket, Workspace};
+use std::{
+	collections::{HashMap, HashSet},
+	fs, io,
+	path::{Path, PathBuf},
+	sync::Mutex,
+};
+
+/// A ticket that matched a search, along with the record
+/// (title or description) that matched.
+pub struct TicketMatch<'a, R: Remote<'a>> {
+	/// The matching ticket.
+	pub ticket: Ticket<'a, R>,
+	/// The record whose message matched the query.
+	pub record: R::Record,
+}
+
+impl<'a, R: Remote<'a>> Project<'a, R> {
+	/// Finds all tickets in the project whose title (and, if
+	/// `descriptions` is set, description) contains the query,
+	/// case-insensitively. Tickets are ordered by ID; if both the
+	/// title and description match, the title is returned.
+	///
+	/// This is a linear scan over the project's tickets.
+	pub fn find_tickets(&self, query: &str, descriptions: bool) -> Result<Vec<TicketMatch<'a, R>>> {
+		let query = query.to_lowercase();
+		let matches = |record: &R::Record| record.message().to_lowercase().contains(&query);
+
+		let mut found = Vec::new();
+		for ticket in self.tickets()? {
+			let mut record = ticket.title()?.filter(matches);
+			if record.is_none() && descriptions {
+				record = ticket.description()?.filter(matches);
+			}
+
+			if let Some(record) = record {
+				found.push(TicketMatch { ticket, record });
+			}
+		}
+
+		Ok(found)
+	}
+}
+
+/// How much a term occurrence in each part of a ticket counts towards its score.
+const TITLE_WEIGHT: u32 = 3;
+const DESCRIPTION_WEIGHT: u32 = 2;
+const COMMENT_WEIGHT: u32 = 1;
+
+/// A ranked full-text search hit.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SearchHit {
+	/// The slug of the matching ticket.
+	pub slug: String,
+	/// The relevance score of the hit; higher is better.
+	pub score: f64,
+}
+
+/// An indexed ticket: a fingerprint of the records it was indexed
+/// from, and its (weighted) term frequencies.
+#[derive(Debug, Clone, Default, PartialEq)]
+struct IndexedTicket {
+	fingerprint: String,
+	terms: HashMap<String, u32>,
+}
+
+/// An inverted full-text index over ticket titles, descriptions and
+/// (non-deleted) comments.
+///
+/// The index is updated incrementally: only tickets whose title,
+/// description or comments changed since the last update are
+/// re-indexed. It can be persisted to a local file with
+/// [`SearchIndex::save`] and read back with [`SearchIndex::load`].
+#[derive(Debug, Clone, Default)]
+pub struct SearchIndex {
+	tickets: HashMap<String, IndexedTicket>,
+	postings: HashMap<String, HashMap<String, u32>>,
+}
+
+impl SearchIndex {
+	/// Creates an empty index.
+	#[inline]
+	pub fn new() -> Self {
+		Self::default()
+	}
+
+	/// Loads an index from a file previously written by [`SearchIndex::save`].
+	/// If the file doesn't exist, an empty index is returned.
+	pub fn load<P: AsRef<Path>>(path: P) -> Result<Self> {
+		let contents = match fs::read_to_string(path.as_ref()) {
+			Ok(contents) => contents,
+			Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(Self::new()),
+			Err(err) => return Err(err.into()),
+		};
+
+		let malformed = || Error::Malformed(path.as_ref().display().to_string());
+
+		let mut index = Self::new();
+		for line in contents.lines() {
+			let mut parts = line.split('\t');
+			let slug = parts.next().ok_or_else(malformed)?;
+			let fingerprint = parts.next().ok_or_else(malformed)?;
+
+			let mut terms = HashMap::new();
+			for term in parts {
+				let (term, count) = term.rsplit_once(':').ok_or_else(malformed)?;
+				terms.insert(
+					term.to_string(),
+					count.parse::<u32>().map_err(|_| malformed())?,
+				);
+			}
+
+			index.insert(
+				slug.to_string(),
+				IndexedTicket {
+					fingerprint: fingerprint.to_string(),
+					terms,
+				},
+			);
+		}
+
+		Ok(index)
+	}
+
+	/// Writes the index to a file.
+	pub fn save<P: AsRef<Path>>(&self, path: P) -> Result<()> {
+		let mut contents = String::new();
+		for (slug, ticket) in &self.tickets {
+			contents.push_str(slug);
+			contents.push('\t');
+			contents.push_str(&ticket.fingerprint);
+			for (term, count) in &ticket.terms {
+				contents.push_str(&format!("\t{}:{}", term, count));
+			}
+			contents.push('\n');
+		}
+
+		Ok(fs::write(path, contents)?)
+	}
+
+	/// Returns the number of indexed tickets.
+	#[inline]
+	pub fn len(&self) -> usize {
+		self.tickets.len()
+	}
+
+	/// Returns if the index is empty.
+	#[inline]
+	pub fn is_empty(&self) -> bool {
+		self.tickets.is_empty()
+	}
+
+	/// Brings the index up to date with the workspace, returning the
+	/// number of tickets that were (re-)indexed or removed.
+	pub fn update<'a, R: Remote<'a>>(&mut self, workspace: &'a Workspace<'a, R>) -> Result<usize> {
+		let mut seen = HashSet::new();
+		let mut changed = 0;
+
+		for project in workspace.project_handles()? {
+			for ticket in project.tickets()? {
+				seen.insert(ticket.slug().to_string());
+
+				let fingerprint = fingerprint(&ticket)?;
+				if self
+					.tickets
+					.get(ticket.slug())
+					.map_or(false, |indexed| indexed.fingerprint == fingerprint)
+				{
+					continue;
+				}
+
+				let mut terms = HashMap::new();
+				if let Some(title) = ticket.title()? {
+					count_terms(&mut terms, &title.message(), TITLE_WEIGHT);
+				}
+				if let Some(description) = ticket.description()? {
+					count_terms(&mut terms, &description.message(), DESCRIPTION_WEIGHT);
+				}
+				for comment in ticket.comments()? {
+					count_terms(&mut terms, &comment?.message(), COMMENT_WEIGHT);
+				}
+
+				self.remove(ticket.slug());
+				self.insert(
+					ticket.slug().to_string(),
+					IndexedTicket { fingerprint, terms },
+				);
+				changed += 1;
+			}
+		}
+
+		let stale = self
+			.tickets
+			.keys()
+			.filter(|slug| !seen.contains(*slug))
+			.cloned()
+			.collect::<Vec<_>>();
+		for slug in stale {
+			self.remove(&slug);
+			changed += 1;
+		}
+
+		Ok(changed)
+	}
+
+	/// Searches the index, returning hits ranked from most to least
+	/// relevant (using TF-IDF). A ticket matches if it contains any
+	/// of the query's terms.
+	pub fn search(&self, query: &str) -> Vec<SearchHit> {
+		let mut query_terms = HashMap::new();
+		count_terms(&mut query_terms, query, 1);
+
+		let total = self.tickets.len() as f64;
+		let mut scores = HashMap::<&str, f64>::new();
+
+		for term in query_terms.keys() {
+			let Some(postings) = self.postings.get(term) else {
+				continue;
+			};
+
+			let idf = (1.0 + total / postings.len() as f64).ln();
+			for (slug, count) in postings {
+				*scores.entry(slug).or_default() += *count as f64 * idf;
+			}
+		}
+
+		let mut hits = scores
+			.into_iter()
+			.map(|(slug, score)| SearchHit {
+				slug: slug.to_string(),
+				score,
+			})
+			.collect::<Vec<_>>();
+		hits.sort_by(|a, b| {
+			b.score
+				.total_cmp(&a.score)
+				.then_with(|| a.slug.cmp(&b.slug))
+		});
+		hits
+	}
+
+	fn insert(&mut self, slug: String, ticket: IndexedTicket) {
+		for (term, count) in &ticket.terms {
+			self.postings
+				.entry(term.clone())
+				.or_default()
+				.insert(slug.clone(), *count);
+		}
+		self.tickets.insert(slug, ticket);
+	}
+
+	fn remove(&mut self, slug: &str) {
+		let Some(ticket) = self.tickets.remove(slug) else {
+			return;
+		};
+
+		for term in ticket.terms.keys() {
+			if let Some(postings) = self.postings.get_mut(term) {
+				postings.remove(slug);
+				if postings.is_empty() {
+					self.postings.remove(term);
+				}
+			}
+		}
+	}
+}
+
+impl<'a, R: Remote<'a>> Workspace<'a, R> {
+	/// Performs a full-text search over all tickets' titles, descriptions
+	/// and comments, returning hits ranked from most to least relevant.
+	///
+	/// The workspace's [`SearchIndex`] is brought up to date first (and
+	/// saved, if the workspace was given an index path with
+	/// [`Workspace::with_search_index`]).
+	pub fn search(&'a self, query: &str) -> Result<Vec<SearchHit>> {
+		let mut index = self.search_index.lock().unwrap();
+
+		if index.update(self)? > 0 {
+			if let Some(path) = &self.search_index_path {
+				index.save(path)?;
+			}
+		}
+
+		Ok(index.search(query))
+	}
+
+	/// Loads the workspace's search index from (and persists it to)
+	/// the given local file, so that subsequent searches only need to
+	/// re-index tickets that changed in the meantime.
+	pub fn with_search_index<P: Into<PathBuf>>(mut self, path: P) -> Result<Self> {
+		let path = path.into();
+		self.search_index = Mutex::new(SearchIndex::load(&path)?);
+		self.search_index_path = Some(path);
+		Ok(self)
+	}
+}
+
+/// Identifies the state of everything that's indexed for a ticket.
+fn fingerprint<'a, R: Remote<'a>>(ticket: &Ticket<'a, R>) -> Result<String> {
+	let remote = &ticket.workspace.remote;
+	let mut fingerprint = String::new();
+
+	for name in ["title", "description", "comment", "comment_tombstones"] {
+		if let Some(record) = remote.latest(&format!("{}/{}", ticket.path, name))? {
+			fingerprint.push_str(&record.id());
+		}
+		fingerprint.push(',');
+	}
+
+	Ok(fingerprint)
+}
+
+/// Splits text into lowercase alphanumeric terms, adding
+/// `weight` to each term's count for every occurrence.
+fn count_terms(terms: &mut HashMap<String, u32>, text: &str, weight: u32) {
+	for term in text
+		.split(|c: char| !c.is_alphanumeric())
+		.filter(|term| !term.is_empty())
+	{
+		*terms.entry(term.to_lowercase()).or_default() += weight;
+	}
+}
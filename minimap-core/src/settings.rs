@@ -0,0 +1,222 @@
+//! Workspace-level settings.
+//!
+//! Unlike the options set up in code when opening a workspace (e.g.
+//! [`Workspace::with_slug_strategy`]), [`WorkspaceSettings`] are stored
+//! in the workspace itself (the `meta/workspace/settings` collection),
+//! so they apply to everyone using it. They're stored as one setting
+//! per line, in the form `<key> <value>`. Settings added by newer
+//! versions of Minimap are kept as they are (see
+//! [`WorkspaceSettings::unknown`]), rather than rejected.
+
+use crate::{
+	Error, HaikuSlugStrategy, Record, RecordBuilder, Remote, Result, SequentialSlugStrategy,
+	SlugStrategy, Ticket, TicketState, UlidSlugStrategy, Workspace,
+};
+
+const SETTINGS_COLLECTION: &str = "meta/workspace/settings";
+
+/// One of the built-in [`SlugStrategy`] implementations.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum SlugPolicy {
+	/// See [`SequentialSlugStrategy`].
+	Sequential,
+	/// See [`UlidSlugStrategy`].
+	Ulid,
+	/// See [`HaikuSlugStrategy`].
+	Haiku,
+}
+
+impl SlugPolicy {
+	/// Gets the slug strategy implementing the policy.
+	pub fn strategy(&self) -> &'static dyn SlugStrategy {
+		match self {
+			Self::Sequential => &SequentialSlugStrategy,
+			Self::Ulid => &UlidSlugStrategy,
+			Self::Haiku => &HaikuSlugStrategy,
+		}
+	}
+}
+
+impl TryFrom<&str> for SlugPolicy {
+	type Error = Error;
+
+	fn try_from(value: &str) -> Result<Self> {
+		match value {
+			"sequential" => Ok(Self::Sequential),
+			"ulid" => Ok(Self::Ulid),
+			"haiku" => Ok(Self::Haiku),
+			_ => Err(Error::Malformed(value.to_string())),
+		}
+	}
+}
+
+impl ToString for SlugPolicy {
+	fn to_string(&self) -> String {
+		match self {
+			Self::Sequential => "sequential".to_string(),
+			Self::Ulid => "ulid".to_string(),
+			Self::Haiku => "haiku".to_string(),
+		}
+	}
+}
+
+/// Policies that apply to the whole workspace.
+/// See the [module documentation](self) for details.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WorkspaceSettings {
+	/// The state new tickets are created in.
+	pub default_state: TicketState,
+	/// The slug policy for new projects and tickets. If set, it takes
+	/// precedence over the workspace's configured [`SlugStrategy`].
+	pub slug_policy: Option<SlugPolicy>,
+	/// The maximum size of a single attachment (on tickets
+	/// or comments), in bytes.
+	pub max_attachment_size: Option<u64>,
+	/// The fields that must be set on a ticket before it can be closed;
+	/// either `title`, `description`, or the name of a custom field.
+	pub required_fields: Vec<String>,
+	/// Whether tickets may only be labeled with
+	/// labels defined on their project.
+	pub restrict_labels: bool,
+	/// The settings this version doesn't know about, as `<key> <value>`
+	/// lines, so that writing the settings back keeps them.
+	pub unknown: Vec<String>,
+}
+
+impl Default for WorkspaceSettings {
+	fn default() -> Self {
+		Self {
+			default_state: TicketState::Open,
+			slug_policy: None,
+			max_attachment_size: None,
+			required_fields: Vec::new(),
+			restrict_labels: false,
+			unknown: Vec::new(),
+		}
+	}
+}
+
+impl TryFrom<&str> for WorkspaceSettings {
+	type Error = Error;
+
+	fn try_from(value: &str) -> Result<Self> {
+		let mut settings = Self::default();
+
+		for line in value.lines().filter(|line| !line.trim().is_empty()) {
+			let malformed = || Error::Malformed(line.to_string());
+			let (key, value) = line.split_once(' ').ok_or_else(malformed)?;
+			match key {
+				"default-state" => {
+					settings.default_state =
+						TicketState::try_from(value).map_err(|_| malformed())?
+				}
+				"slug-policy" => {
+					settings.slug_policy =
+						Some(SlugPolicy::try_from(value).map_err(|_| malformed())?)
+				}
+				"max-attachment-size" => {
+					settings.max_attachment_size = Some(value.parse().map_err(|_| malformed())?)
+				}
+				"required-field" => settings.required_fields.push(value.to_string()),
+				"restrict-labels" => {
+					settings.restrict_labels = value.parse().map_err(|_| malformed())?
+				}
+				_ => settings.unknown.push(line.to_string()),
+			}
+		}
+
+		Ok(settings)
+	}
+}
+
+impl ToString for WorkspaceSettings {
+	fn to_string(&self) -> String {
+		let mut lines = vec![format!("default-state {}", self.default_state.to_string())];
+		if let Some(policy) = &self.slug_policy {
+			lines.push(format!("slug-policy {}", policy.to_string()));
+		}
+		if let Some(size) = self.max_attachment_size {
+			lines.push(format!("max-attachment-size {}", size));
+		}
+		for field in &self.required_fields {
+			lines.push(format!("required-field {}", field));
+		}
+		if self.restrict_labels {
+			lines.push("restrict-labels true".to_string());
+		}
+		lines.extend(self.unknown.iter().cloned());
+		lines.join("\n")
+	}
+}
+
+impl<'a, R: Remote<'a>> Workspace<'a, R> {
+	/// Gets the workspace's settings, or the defaults if they've never been set.
+	pub fn settings(&'a self) -> Result<WorkspaceSettings> {
+		self.remote.latest(SETTINGS_COLLECTION)?.map_or_else(
+			|| Ok(WorkspaceSettings::default()),
+			|record| WorkspaceSettings::try_from(record.message().as_str()),
+		)
+	}
+
+	/// Replaces the workspace's settings.
+	///
+	/// Required field names cannot be empty or contain whitespace
+	/// characters. Returns [`Error::Malformed`] otherwise.
+	pub fn set_settings(&'a self, settings: &WorkspaceSettings) -> Result<R::Record> {
+		for field in &settings.required_fields {
+			if field.is_empty() || field.contains(char::is_whitespace) {
+				return Err(Error::Malformed(field.clone()));
+			}
+		}
+
+		self.remote
+			.record_builder(SETTINGS_COLLECTION)
+			.commit(&settings.to_string())
+	}
+
+	/// Gets the slug strategy to use for new projects and tickets,
+	/// honoring the configured [`SlugPolicy`].
+	pub(crate) fn effective_slug_strategy(&'a self) -> Result<&'a dyn SlugStrategy> {
+		Ok(match self.settings()?.slug_policy {
+			Some(policy) => policy.strategy(),
+			None => &*self.slug_strategy,
+		})
+	}
+
+	/// Checks an attachment against the configured maximum size,
+	/// returning [`Error::TooLarge`] if it exceeds it.
+	pub(crate) fn check_attachment_size(&'a self, name: &str, data: &[u8]) -> Result<()> {
+		match self.settings()?.max_attachment_size {
+			Some(limit) if data.len() as u64 > limit => {
+				Err(Error::TooLarge(name.to_string(), data.len() as u64, limit))
+			}
+			_ => Ok(()),
+		}
+	}
+}
+
+impl<'a, R: Remote<'a>> Ticket<'a, R> {
+	/// Gets the required fields (see [`WorkspaceSettings::required_fields`])
+	/// that haven't been set on the ticket.
+	pub fn missing_required_fields(&self) -> Result<Vec<String>> {
+		let mut missing = Vec::new();
+
+		for field in self.workspace.settings()?.required_fields {
+			let collection = match field.as_str() {
+				"title" | "description" => format!("{}/{}", self.path, field),
+				_ => format!("{}/field/{}", self.path, field),
+			};
+
+			let set = self
+				.workspace
+				.remote
+				.latest(&collection)?
+				.map_or(false, |record| !record.message().trim().is_empty());
+			if !set {
+				missing.push(field);
+			}
+		}
+
+		Ok(missing)
+	}
+}
@@ -0,0 +1,178 @@
+//! Slug generation strategies.
+//!
+//! Internally, tickets are always numbered sequentially within their
+//! project; a [`SlugStrategy`] decides what the public slug of a new
+//! ticket looks like (and may normalize requested project slugs).
+//! Strategies are configured per workspace via
+//! [`Workspace::with_slug_strategy`](crate::Workspace::with_slug_strategy).
+//!
+//! Slugs that differ from the default `project-123` form are recorded
+//! when the ticket is created, so looking tickets up by slug keeps
+//! working regardless of which strategy is configured later on.
+
+use std::{
+	collections::hash_map::RandomState,
+	hash::{BuildHasher, Hasher},
+	sync::atomic::{AtomicU64, Ordering},
+	time::{SystemTime, UNIX_EPOCH},
+};
+
+/// Generates slugs for newly created projects and tickets.
+pub trait SlugStrategy: Send + Sync {
+	/// Returns the slug to use for a new project, given the requested slug.
+	/// By default, the requested slug is used as-is.
+	fn project_slug(&self, requested: &str) -> String {
+		requested.to_string()
+	}
+
+	/// Returns the slug for a new ticket, given the project slug and the
+	/// ticket's sequential ID. Ticket slugs must be unique within the
+	/// workspace and cannot have `/` or whitespace characters.
+	fn ticket_slug(&self, project: &str, id: u64) -> String;
+}
+
+/// The default strategy; tickets are named `project-123`.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct SequentialSlugStrategy;
+
+impl SlugStrategy for SequentialSlugStrategy {
+	fn ticket_slug(&self, project: &str, id: u64) -> String {
+		format!("{}-{}", project, id)
+	}
+}
+
+/// Names tickets `project-<ULID>`, e.g. `project-01HH3X2Q4M6W8E9T0V1B2C3D4F`.
+/// Useful when slugs shouldn't reveal how many tickets a project has.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct UlidSlugStrategy;
+
+impl SlugStrategy for UlidSlugStrategy {
+	fn ticket_slug(&self, project: &str, _id: u64) -> String {
+		format!("{}-{}", project, ulid())
+	}
+}
+
+/// Names tickets with memorable, haiku-style names,
+/// e.g. `project-misty-river-12`. The sequential ID is kept
+/// as a suffix to guarantee uniqueness.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct HaikuSlugStrategy;
+
+const ADJECTIVES: [&str; 32] = [
+	"autumn",
+	"hidden",
+	"bitter",
+	"misty",
+	"silent",
+	"empty",
+	"dry",
+	"dark",
+	"summer",
+	"icy",
+	"delicate",
+	"quiet",
+	"white",
+	"cool",
+	"spring",
+	"winter",
+	"patient",
+	"twilight",
+	"crimson",
+	"wispy",
+	"weathered",
+	"blue",
+	"billowing",
+	"broken",
+	"cold",
+	"damp",
+	"falling",
+	"frosty",
+	"green",
+	"long",
+	"late",
+	"ancient",
+];
+
+const NOUNS: [&str; 32] = [
+	"waterfall",
+	"river",
+	"breeze",
+	"moon",
+	"rain",
+	"wind",
+	"sea",
+	"morning",
+	"snow",
+	"lake",
+	"sunset",
+	"pine",
+	"shadow",
+	"leaf",
+	"dawn",
+	"glitter",
+	"forest",
+	"hill",
+	"cloud",
+	"meadow",
+	"sun",
+	"glade",
+	"bird",
+	"brook",
+	"butterfly",
+	"bush",
+	"dew",
+	"dust",
+	"field",
+	"fire",
+	"flower",
+	"firefly",
+];
+
+impl SlugStrategy for HaikuSlugStrategy {
+	fn ticket_slug(&self, project: &str, id: u64) -> String {
+		let r = random_u64();
+		format!(
+			"{}-{}-{}-{}",
+			project,
+			ADJECTIVES[(r % 32) as usize],
+			NOUNS[((r >> 5) % 32) as usize],
+			id
+		)
+	}
+}
+
+/// Returns a pseudo-random number. This doesn't need to be
+/// cryptographically secure, just unpredictable enough to
+/// avoid collisions.
+fn random_u64() -> u64 {
+	static COUNTER: AtomicU64 = AtomicU64::new(0);
+
+	let mut hasher = RandomState::new().build_hasher();
+	hasher.write_u64(COUNTER.fetch_add(1, Ordering::Relaxed));
+	hasher.write_u128(
+		SystemTime::now()
+			.duration_since(UNIX_EPOCH)
+			.unwrap()
+			.as_nanos(),
+	);
+	hasher.finish()
+}
+
+/// Generates a [ULID](https://github.com/ulid/spec): a 48-bit millisecond
+/// timestamp followed by 80 random bits, in Crockford's base32.
+fn ulid() -> String {
+	const ALPHABET: &[u8; 32] = b"0123456789ABCDEFGHJKMNPQRSTVWXYZ";
+
+	let millis = SystemTime::now()
+		.duration_since(UNIX_EPOCH)
+		.unwrap()
+		.as_millis()
+		& ((1 << 48) - 1);
+	let random = ((random_u64() as u128) << 16) | (random_u64() as u128 & 0xffff);
+	let value = (millis << 80) | random;
+
+	(0..26)
+		.rev()
+		.map(|i| ALPHABET[((value >> (i * 5)) & 31) as usize] as char)
+		.collect()
+}
@@ -0,0 +1,88 @@
+//! Project and workspace statistics.
+
+use crate::{Project, Record, Remote, Result, Ticket, TicketEvent, TicketState, Workspace};
+use serde::Serialize;
+use std::collections::BTreeMap;
+
+/// The width of each activity bucket, in seconds (one day).
+pub const STATS_BUCKET_SECONDS: i64 = 24 * 60 * 60;
+
+/// Aggregate statistics over a set of tickets.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct Stats {
+	/// The number of open tickets.
+	pub open: usize,
+	/// The number of closed tickets.
+	pub closed: usize,
+	/// The number of tickets per label.
+	pub labels: BTreeMap<String, usize>,
+	/// The number of tickets per assignee.
+	pub assignees: BTreeMap<String, usize>,
+	/// The number of (non-deleted) comments across all tickets.
+	pub comments: usize,
+	/// The number of ticket events (see [`Ticket::activity`]) per
+	/// [`STATS_BUCKET_SECONDS`]-wide bucket, keyed by the unix
+	/// timestamp at which each bucket starts.
+	pub activity: BTreeMap<i64, usize>,
+}
+
+impl Stats {
+	/// Gets the total number of tickets.
+	#[inline]
+	pub fn tickets(&self) -> usize {
+		self.open + self.closed
+	}
+
+	fn add_ticket<'a, R: Remote<'a>>(&mut self, ticket: &Ticket<'a, R>) -> Result<()> {
+		match ticket.state()?.0 {
+			TicketState::Open => self.open += 1,
+			TicketState::Closed => self.closed += 1,
+		}
+
+		for label in ticket.labels()? {
+			*self.labels.entry(label).or_default() += 1;
+		}
+
+		for assignee in ticket.assignees()? {
+			*self.assignees.entry(assignee).or_default() += 1;
+		}
+
+		for event in ticket.activity()? {
+			match event {
+				TicketEvent::CommentAdded(_) => self.comments += 1,
+				TicketEvent::CommentDeleted { .. } => {
+					self.comments = self.comments.saturating_sub(1)
+				}
+				_ => {}
+			}
+
+			let timestamp = event.record().timestamp();
+			let bucket = timestamp - timestamp.rem_euclid(STATS_BUCKET_SECONDS);
+			*self.activity.entry(bucket).or_default() += 1;
+		}
+
+		Ok(())
+	}
+}
+
+impl<'a, R: Remote<'a>> Project<'a, R> {
+	/// Computes statistics over the project's tickets.
+	pub fn stats(&self) -> Result<Stats> {
+		let mut stats = Stats::default();
+		for ticket in self.tickets()? {
+			stats.add_ticket(&ticket)?;
+		}
+		Ok(stats)
+	}
+}
+
+impl<'a, R: Remote<'a>> Workspace<'a, R> {
+	/// Computes statistics over all tickets in the workspace.
+	pub fn stats(&'a self) -> Result<Stats> {
+		let mut stats = Stats::default();
+		for ticket in self.tickets()? {
+			stats.add_ticket(&ticket)?;
+		}
+		Ok(stats)
+	}
+}
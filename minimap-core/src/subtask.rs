@@ -0,0 +1,97 @@
+//! Parent/child relationships between tickets.
+//!
+//! Subtasks are regular tickets in the same project as their parent.
+//! Each subtask records its parent's slug in its `parent` collection,
+//! and the parent tracks its subtasks' slugs in its `subtasks` set.
+
+use crate::{Error, Record, RecordBuilder, Remote, Result, Ticket};
+
+/// How many of a ticket's subtasks are closed.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct SubtaskProgress {
+	/// The number of closed subtasks.
+	pub closed: usize,
+	/// The total number of subtasks.
+	pub total: usize,
+}
+
+impl SubtaskProgress {
+	/// Gets the fraction (from `0.0` to `1.0`) of subtasks that are
+	/// closed, or `None` if there are no subtasks.
+	pub fn fraction(&self) -> Option<f64> {
+		if self.total == 0 {
+			None
+		} else {
+			Some(self.closed as f64 / self.total as f64)
+		}
+	}
+}
+
+impl<'a, R: Remote<'a>> Ticket<'a, R> {
+	/// Creates a new ticket in the same project, as a subtask of this ticket.
+	pub fn create_subtask(&self) -> Result<Ticket<'a, R>> {
+		let subtask = self.project()?.create_ticket()?;
+
+		self.workspace
+			.remote
+			.record_builder(&format!("{}/parent", subtask.path))
+			.commit(&self.slug)?;
+
+		self.workspace
+			.remote
+			.set_add(&format!("{}/subtasks", self.path), &subtask.slug)?
+			.map_err(|_| Error::Malformed(format!("{}/subtasks", self.path)))?;
+
+		Ok(subtask)
+	}
+
+	/// Gets all (existing) subtasks of the ticket.
+	pub fn subtasks(&self) -> Result<Vec<Ticket<'a, R>>> {
+		let mut subtasks = Vec::new();
+
+		for record in self
+			.workspace
+			.remote
+			.set_get_all(&format!("{}/subtasks", self.path))?
+		{
+			match self.workspace.ticket(&record.message()) {
+				Ok(ticket) => subtasks.push(ticket),
+				Err(Error::NotFound(_, _)) => {}
+				Err(err) => return Err(err),
+			}
+		}
+
+		Ok(subtasks)
+	}
+
+	/// Gets the parent ticket of this ticket, or `None` if the
+	/// ticket isn't a subtask (or its parent has been deleted).
+	pub fn parent(&self) -> Result<Option<Ticket<'a, R>>> {
+		match self
+			.workspace
+			.remote
+			.latest(&format!("{}/parent", self.path))?
+		{
+			Some(record) => match self.workspace.ticket(&record.message()) {
+				Ok(ticket) => Ok(Some(ticket)),
+				Err(Error::NotFound(_, _)) => Ok(None),
+				Err(err) => Err(err),
+			},
+			None => Ok(None),
+		}
+	}
+
+	/// Counts how many of the ticket's (direct) subtasks are closed.
+	pub fn subtask_progress(&self) -> Result<SubtaskProgress> {
+		let mut progress = SubtaskProgress::default();
+
+		for subtask in self.subtasks()? {
+			progress.total += 1;
+			if subtask.is_closed()? {
+				progress.closed += 1;
+			}
+		}
+
+		Ok(progress)
+	}
+}
@@ -0,0 +1,193 @@
+//! Project templates.
+//!
+//! Templates are stored in the workspace (the `meta/templates` set, with
+//! each template's settings under `meta/template/<name>`), so that
+//! everyone sharing the workspace sets up new projects the same way.
+//! A template's settings are copied into a project when it's created
+//! with [`Workspace::create_project_from_template`]; later changes to
+//! the project don't affect the template, and vice versa.
+
+use crate::{
+	validate_field, Error, FieldType, Project, Record, RecordBuilder, Remote, Result, Workflow,
+	Workspace,
+};
+
+/// The settings a new project is created with.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ProjectTemplate {
+	/// The project's initial description.
+	pub description: Option<String>,
+	/// The labels defined on the project.
+	pub labels: Vec<String>,
+	/// The project's custom fields, by name.
+	pub fields: Vec<(String, FieldType)>,
+	/// The project's workflow, or `None` to use the workspace's.
+	pub workflow: Option<Workflow>,
+}
+
+impl ProjectTemplate {
+	/// Creates an empty template.
+	#[inline]
+	pub fn new() -> Self {
+		Self::default()
+	}
+
+	/// Sets the initial description of projects.
+	pub fn description(mut self, description: &str) -> Self {
+		self.description = Some(description.to_string());
+		self
+	}
+
+	/// Adds a label to define on projects.
+	pub fn label(mut self, label: &str) -> Self {
+		self.labels.push(label.to_string());
+		self
+	}
+
+	/// Adds a custom field to define on projects.
+	pub fn field(mut self, name: &str, ty: FieldType) -> Self {
+		self.fields.push((name.to_string(), ty));
+		self
+	}
+
+	/// Sets the workflow of projects.
+	pub fn workflow(mut self, workflow: Workflow) -> Self {
+		self.workflow = Some(workflow);
+		self
+	}
+}
+
+impl<'a, R: Remote<'a>> Workspace<'a, R> {
+	/// Creates a project template with the given name. If the template
+	/// already exists, returns `Ok(Err(record))` with the set record of
+	/// the existing template. Templates can't be changed once created.
+	///
+	/// Template names follow the same rules as project slugs. Returns
+	/// [`Error::Malformed`] if the name or any of the fields is invalid.
+	pub fn create_template(
+		&'a self,
+		name: &str,
+		template: &ProjectTemplate,
+	) -> Result<std::result::Result<R::Record, R::Record>> {
+		if name.is_empty() || name.contains('/') || name.contains(char::is_whitespace) {
+			return Err(Error::Malformed(name.to_string()));
+		}
+		for (field, ty) in &template.fields {
+			validate_field(field, ty)?;
+		}
+
+		let record = match self.remote.set_add("meta/templates", name)? {
+			Ok((record, _)) => record,
+			Err(existing) => return Ok(Err(existing)),
+		};
+
+		let path = format!("meta/template/{}", name);
+		if let Some(description) = &template.description {
+			self.remote
+				.record_builder(&format!("{}/description", path))
+				.commit(description)?;
+		}
+		for label in &template.labels {
+			self.remote
+				.set_add_unchecked(&format!("{}/labels", path), label)?;
+		}
+		for (field, ty) in &template.fields {
+			self.remote.set_add_unchecked(
+				&format!("{}/fields", path),
+				&format!("{}:{}", field, ty.to_string()),
+			)?;
+		}
+		if let Some(workflow) = &template.workflow {
+			self.remote
+				.record_builder(&format!("{}/workflow", path))
+				.commit(&workflow.to_string())?;
+		}
+
+		Ok(Ok(record))
+	}
+
+	/// Lists the names of the workspace's project templates.
+	pub fn templates(&'a self) -> Result<Vec<String>> {
+		Ok(self
+			.remote
+			.set_get_all("meta/templates")?
+			.into_iter()
+			.map(|record| record.message())
+			.collect())
+	}
+
+	/// Gets a project template by name.
+	pub fn template(&'a self, name: &str) -> Result<ProjectTemplate> {
+		self.remote
+			.set_find("meta/templates", name)?
+			.map_err(|_| Error::NotFound("meta/templates".to_string(), name.to_string()))?;
+
+		let path = format!("meta/template/{}", name);
+		let fields_path = format!("{}/fields", path);
+
+		Ok(ProjectTemplate {
+			description: self
+				.remote
+				.latest(&format!("{}/description", path))?
+				.map(|record| record.message()),
+			labels: self
+				.remote
+				.set_get_all(&format!("{}/labels", path))?
+				.into_iter()
+				.map(|record| record.message())
+				.collect(),
+			fields: self
+				.remote
+				.set_get_all(&fields_path)?
+				.into_iter()
+				.map(|record| {
+					let message = record.message();
+					let (name, ty) = message
+						.split_once(':')
+						.ok_or_else(|| Error::Malformed(fields_path.clone()))?;
+					Ok((name.to_string(), FieldType::try_from(ty)?))
+				})
+				.collect::<Result<_>>()?,
+			workflow: self
+				.remote
+				.latest(&format!("{}/workflow", path))?
+				.map(|record| Workflow::try_from(record.message().as_str()))
+				.transpose()?,
+		})
+	}
+
+	/// Creates a project with the given slug, set up according to the
+	/// named template. If the project already exists, returns
+	/// `Ok(Err(record))` with the set record of the existing project,
+	/// and leaves the project untouched.
+	///
+	/// Returns [`Error::NotFound`] if the template doesn't exist.
+	pub fn create_project_from_template(
+		&'a self,
+		slug: &str,
+		template: &str,
+	) -> Result<std::result::Result<Project<'a, R>, R::Record>> {
+		let template = self.template(template)?;
+
+		let project = match self.create_project(slug)? {
+			Ok(project) => project,
+			Err(record) => return Ok(Err(record)),
+		};
+
+		if let Some(description) = &template.description {
+			project.set_description(description)?;
+		}
+		for label in &template.labels {
+			self.remote
+				.set_add_unchecked(&format!("{}/labels", project.meta_path), label)?;
+		}
+		for (name, ty) in &template.fields {
+			project.define_field(name, ty)?;
+		}
+		if let Some(workflow) = &template.workflow {
+			project.set_workflow(workflow)?;
+		}
+
+		Ok(Ok(project))
+	}
+}
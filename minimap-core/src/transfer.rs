@@ -0,0 +1,86 @@
+//! Moving tickets between projects.
+//!
+//! Collections can't be renamed, so moving a ticket creates a new ticket
+//! in the target project and replays every record of the original into
+//! it, preserving timestamps and authorship where the remote supports it
+//! (see [`RecordBuilder::authored`]). The original ticket is then removed
+//! from its project (and kept out of the [`Trash`](crate::Trash)), and a
+//! redirect is recorded in the `meta/ticket_redirect/<slug>` collection,
+//! so that its old slug (and everything referring to it, like
+//! dependencies) keeps resolving to the moved ticket through
+//! [`Workspace::ticket`].
+
+use crate::{
+	is_collection_under, trash::MOVED_COLLECTION, Error, Record, RecordBuilder, Remote, Result,
+	Ticket, Workspace,
+};
+
+impl<'a, R: Remote<'a>> Ticket<'a, R> {
+	/// Moves the ticket to another project, returning the moved ticket.
+	/// The ticket keeps its history, but gets a new ID (and slug) in the
+	/// target project; its old slug redirects to the new one.
+	///
+	/// Returns [`Error::NotFound`] if the target project doesn't exist,
+	/// and [`Error::Exists`] if the ticket already belongs to it.
+	pub fn move_to(&self, project: &str) -> Result<Ticket<'a, R>> {
+		let source = self.project()?;
+		let target = self.workspace.project(project)?;
+		if source.slug() == target.slug() {
+			return Err(Error::Exists(
+				format!("project/{}/tickets", target.slug()),
+				self.id.to_string(),
+			));
+		}
+
+		let remote = self.workspace.remote();
+		let collections = remote
+			.collections(&self.path)?
+			.into_iter()
+			// The slug alias is only valid for the original ticket;
+			// the moved ticket gets its own.
+			.filter(|collection| {
+				is_collection_under(collection, &self.path)
+					&& *collection != format!("{}/slug", self.path)
+			})
+			.map(|collection| self.workspace.bundle_collection(&collection))
+			.collect::<Result<Vec<_>>>()?;
+
+		let moved = target.create_ticket()?;
+		self.workspace.replay_collections(
+			&collections,
+			|collection| {
+				Ok(Some(format!(
+					"{}{}",
+					moved.path,
+					&collection.name[self.path.len()..]
+				)))
+			},
+			|_, _| true,
+		)?;
+
+		let (deletion, _) = remote
+			.set_del(&format!("{}/tickets", source.path), &self.id.to_string())?
+			.map_err(|_| {
+				Error::NotFound(format!("{}/tickets", source.path), self.id.to_string())
+			})?;
+		// Otherwise it'd look soft-deleted, and could be restored.
+		remote.set_add_unchecked(MOVED_COLLECTION, &deletion.id())?;
+		remote
+			.record_builder(&format!("meta/ticket_redirect/{}", self.slug))
+			.commit(&moved.slug)?;
+
+		Ok(moved)
+	}
+}
+
+impl<'a, R: Remote<'a>> Workspace<'a, R> {
+	/// Gets the slug that a ticket moved to another project was moved to
+	/// (see [`Ticket::move_to`]), or `None` if it was never moved. The
+	/// moved ticket may itself have been moved since.
+	pub fn ticket_redirect(&'a self, slug: &str) -> Result<Option<String>> {
+		Ok(self
+			.remote
+			.latest(&format!("meta/ticket_redirect/{}", slug))?
+			.map(|record| record.message()))
+	}
+}
@@ -0,0 +1,266 @@
+//! Workspace-wide view over soft-deleted entities.
+//!
+//! Projects and tickets are only ever **soft-deleted** (removed from
+//! their respective sets), meaning all of their data is still around
+//! and can be brought back. The [`Trash`] collects everything that is
+//! currently soft-deleted in one place, and allows either restoring
+//! it or purging it (permanently deleting its collections).
+
+use crate::{AuditOperation, Error, Record, Remote, Result, SetOperation, Workspace};
+use std::collections::HashSet;
+
+/// The set of deletion record IDs whose entities have been purged.
+/// Purged entities stay soft-deleted in their sets, so this is how
+/// they're kept from showing up in the trash again.
+const PURGED_COLLECTION: &str = "meta/trash/purged";
+
+/// The set of deletion record IDs of tickets moved to another project
+/// (see [`Ticket::move_to`](crate::Ticket::move_to)). They live on in
+/// their new project, so they're kept out of the trash just like purged
+/// entities.
+pub(crate) const MOVED_COLLECTION: &str = "meta/trash/moved";
+
+/// An entity that can be in the [`Trash`].
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum TrashEntity {
+	/// A project, by slug.
+	Project(String),
+	/// A ticket, by project slug and ticket ID.
+	Ticket(String, u64),
+}
+
+impl TrashEntity {
+	/// Gets the slug of the entity (the project slug,
+	/// or the ticket slug, i.e. `project-123`).
+	pub fn slug(&self) -> String {
+		match self {
+			Self::Project(slug) => slug.clone(),
+			Self::Ticket(project, id) => format!("{}-{}", project, id),
+		}
+	}
+}
+
+/// A soft-deleted entity along with the record of its deletion.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TrashItem<T: Record> {
+	/// The entity that was deleted.
+	pub entity: TrashEntity,
+	/// The set record that deleted the entity.
+	pub record: T,
+}
+
+impl<T: Record> TrashItem<T> {
+	/// Gets the unix timestamp (in seconds) at which the entity was deleted.
+	#[inline]
+	pub fn timestamp(&self) -> i64 {
+		self.record.timestamp()
+	}
+}
+
+/// The workspace trash. See the [module documentation](self) for details.
+pub struct Trash<'a, R: Remote<'a>> {
+	workspace: &'a Workspace<'a, R>,
+}
+
+impl<'a, R: Remote<'a>> Workspace<'a, R> {
+	/// Gets the workspace's trash, holding all soft-deleted
+	/// projects and tickets.
+	#[inline]
+	pub fn trash(&'a self) -> Trash<'a, R> {
+		Trash { workspace: self }
+	}
+
+	/// **Permanently** deletes a project and all of its tickets, returning
+	/// the names of the collections that were removed. The project is
+	/// soft-deleted first if needed, and the purge is recorded in the audit
+	/// log along with the given reason. Sub-projects are left untouched.
+	///
+	/// As this can't be undone, `force` must be set, otherwise
+	/// [`Error::ForceRequired`] is returned. Returns [`Error::NotFound`]
+	/// if the project never existed or has already been purged.
+	pub fn purge_project(
+		&'a self,
+		slug: &str,
+		force: bool,
+		reason: Option<&str>,
+	) -> Result<Vec<String>> {
+		if !force {
+			return Err(Error::ForceRequired(format!("purge project {}", slug)));
+		}
+
+		let record = match self.remote().set_find("meta/projects", slug)? {
+			Ok(_) => self
+				.delete_project(slug)?
+				.map_err(|_| Error::NotFound("meta/projects".to_string(), slug.to_string()))?,
+			Err(Some(record)) => record,
+			Err(None) => {
+				return Err(Error::NotFound(
+					"meta/projects".to_string(),
+					slug.to_string(),
+				));
+			}
+		};
+
+		self.trash().purge(
+			&TrashItem {
+				entity: TrashEntity::Project(slug.to_string()),
+				record,
+			},
+			reason,
+		)
+	}
+}
+
+impl<'a, R: Remote<'a>> Trash<'a, R> {
+	/// Lists everything that is currently in the trash, from most
+	/// to least recently deleted.
+	///
+	/// Tickets belonging to a deleted project are not listed separately;
+	/// they're restored or purged along with the project itself. Tickets
+	/// moved to another project aren't in the trash at all.
+	pub fn items(&self) -> Result<Vec<TrashItem<R::Record>>> {
+		let remote = self.workspace.remote();
+		let purged = self.purged()?;
+
+		let mut items = deleted_set_items(remote, "meta/projects")?
+			.into_iter()
+			.filter(|record| !purged.contains(&record.id()))
+			.map(|record| TrashItem {
+				entity: TrashEntity::Project(record.message()),
+				record,
+			})
+			.collect::<Vec<_>>();
+
+		for project in self.workspace.projects()? {
+			let project = project.message();
+			let tickets_path = format!("project/{}/tickets", project);
+
+			for record in deleted_set_items(remote, &tickets_path)? {
+				if purged.contains(&record.id()) {
+					continue;
+				}
+
+				let id = record
+					.message()
+					.parse::<u64>()
+					.map_err(|_| Error::Malformed(tickets_path.clone()))?;
+
+				items.push(TrashItem {
+					entity: TrashEntity::Ticket(project.clone(), id),
+					record,
+				});
+			}
+		}
+
+		items.sort_by_key(|item| ::std::cmp::Reverse(item.timestamp()));
+		Ok(items)
+	}
+
+	/// Restores an item from the trash, returning the set record
+	/// that re-added it.
+	///
+	/// Returns [`Error::NotFound`] if the item has since been purged or
+	/// moved (or its project no longer exists), and [`Error::Exists`] if it
+	/// has already been restored or re-created.
+	pub fn restore(&self, item: &TrashItem<R::Record>) -> Result<R::Record> {
+		let (set, message) = set_of(&item.entity);
+
+		if self.purged()?.contains(&item.record.id()) {
+			return Err(Error::NotFound(
+				PURGED_COLLECTION.to_string(),
+				item.entity.slug(),
+			));
+		}
+
+		if let TrashEntity::Ticket(project, _) = &item.entity {
+			self.workspace.project(project)?;
+		}
+
+		match self.workspace.remote().set_add(&set, &message)? {
+			Ok((record, _)) => Ok(record),
+			Err(_) => Err(Error::Exists(set, message)),
+		}
+	}
+
+	/// **Permanently** deletes all of an item's collections, returning
+	/// the names of the collections that were removed. The purge is
+	/// recorded in the audit log along with the given reason.
+	///
+	/// The item must still be in the trash (i.e. not have been restored,
+	/// re-created or purged since), otherwise [`Error::NotFound`] is returned.
+	pub fn purge(&self, item: &TrashItem<R::Record>, reason: Option<&str>) -> Result<Vec<String>> {
+		let remote = self.workspace.remote();
+		let (set, message) = set_of(&item.entity);
+
+		// Make sure the entity is still deleted by the very same record;
+		// otherwise we'd be purging something that has been brought back.
+		match remote.set_find(&set, &message)? {
+			Err(Some(record))
+				if record.id() == item.record.id() && !self.purged()?.contains(&record.id()) => {}
+			_ => return Err(Error::NotFound(set, message)),
+		}
+
+		let prefixes = match &item.entity {
+			TrashEntity::Project(slug) => {
+				vec![
+					format!("project/{}", slug),
+					format!("meta/project/{}", slug),
+				]
+			}
+			TrashEntity::Ticket(project, id) => vec![format!("project/{}/ticket/{}", project, id)],
+		};
+
+		let mut removed = Vec::new();
+		for prefix in prefixes {
+			for collection in remote.collections(&prefix)? {
+				if remote.delete_collection(&collection)? {
+					removed.push(collection);
+				}
+			}
+		}
+
+		remote.set_add_unchecked(PURGED_COLLECTION, &item.record.id())?;
+		self.workspace
+			.audit(AuditOperation::Purge, &item.entity.slug(), reason)?;
+
+		Ok(removed)
+	}
+
+	/// Gets the IDs of the deletion records whose entities are gone from
+	/// the trash for good: those purged, and tickets moved elsewhere.
+	pub(crate) fn purged(&self) -> Result<HashSet<String>> {
+		let remote = self.workspace.remote();
+		Ok(remote
+			.set_get_all(PURGED_COLLECTION)?
+			.into_iter()
+			.chain(remote.set_get_all(MOVED_COLLECTION)?)
+			.map(|record| record.message())
+			.collect())
+	}
+}
+
+/// Returns the set (and set item) that tracks the existence of an entity.
+fn set_of(entity: &TrashEntity) -> (String, String) {
+	match entity {
+		TrashEntity::Project(slug) => ("meta/projects".to_string(), slug.clone()),
+		TrashEntity::Ticket(project, id) => {
+			(format!("project/{}/tickets", project), id.to_string())
+		}
+	}
+}
+
+/// Gets the deletion records of all items in a set that are currently
+/// deleted, from latest to oldest.
+fn deleted_set_items<'a, R: Remote<'a>>(remote: &'a R, collection: &str) -> Result<Vec<R::Record>> {
+	let mut seen = HashSet::new();
+	let mut deleted = Vec::new();
+
+	for result in remote.walk_set(collection)? {
+		let (record, op) = result?;
+		if seen.insert(record.message()) && op == SetOperation::Del {
+			deleted.push(record);
+		}
+	}
+
+	Ok(deleted)
+}
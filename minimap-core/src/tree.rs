@@ -0,0 +1,195 @@
+//! Project hierarchy.
+//!
+//! Sub-projects only store a pointer to their parent (see
+//! [`Project::create_project`]). [`Workspace::project_tree`] reads every
+//! parent pointer once and assembles the full tree from the top down.
+//!
+//! Parent pointers can be changed with [`Project::set_parent`], which
+//! refuses to create cycles. Cycles can still appear when concurrent
+//! changes are merged, so anything walking up the hierarchy goes through
+//! [`Project::ancestors`], which detects them.
+
+use crate::{Error, Project, Record, RecordBuilder, Remote, Result, Workspace};
+use std::collections::{HashMap, HashSet};
+
+/// The maximum number of ancestors a project may have.
+pub const MAX_PROJECT_DEPTH: usize = 64;
+
+/// A project along with its sub-projects. See [`Workspace::project_tree`].
+pub struct ProjectTree<'a, R: Remote<'a>> {
+	/// The project itself.
+	pub project: Project<'a, R>,
+	/// The project's direct sub-projects, in workspace order.
+	pub children: Vec<ProjectTree<'a, R>>,
+}
+
+impl<'a, R: Remote<'a>> Workspace<'a, R> {
+	/// Gets the full project hierarchy of the workspace, as a list of
+	/// root projects with their sub-projects nested below them.
+	///
+	/// Projects whose parent no longer exists, as well as projects that
+	/// are part of a parent cycle, are treated as root projects.
+	pub fn project_tree(&'a self) -> Result<Vec<ProjectTree<'a, R>>> {
+		let projects = self.project_handles()?;
+		let slugs = projects
+			.iter()
+			.map(|project| project.slug().to_string())
+			.collect::<HashSet<_>>();
+
+		let mut parents = HashMap::new();
+		for project in &projects {
+			if let Some(parent) = project.parent_slug()? {
+				parents.insert(project.slug().to_string(), parent);
+			}
+		}
+
+		let mut children = HashMap::<String, Vec<Project<'a, R>>>::new();
+		let mut roots = Vec::new();
+		for project in projects {
+			match parents.get(project.slug()) {
+				Some(parent) if slugs.contains(parent) && !in_cycle(project.slug(), &parents) => {
+					children.entry(parent.clone()).or_default().push(project)
+				}
+				_ => roots.push(project),
+			}
+		}
+
+		Ok(roots
+			.into_iter()
+			.map(|project| build_tree(project, &mut children))
+			.collect())
+	}
+}
+
+fn in_cycle(slug: &str, parents: &HashMap<String, String>) -> bool {
+	let mut current = slug;
+	for _ in 0..parents.len() {
+		match parents.get(current) {
+			Some(parent) if parent == slug => return true,
+			Some(parent) => current = parent,
+			None => return false,
+		}
+	}
+	false
+}
+
+fn build_tree<'a, R: Remote<'a>>(
+	project: Project<'a, R>,
+	children: &mut HashMap<String, Vec<Project<'a, R>>>,
+) -> ProjectTree<'a, R> {
+	let own = children.remove(project.slug()).unwrap_or_default();
+
+	ProjectTree {
+		children: own
+			.into_iter()
+			.map(|child| build_tree(child, children))
+			.collect(),
+		project,
+	}
+}
+
+impl<'a, R: Remote<'a>> Project<'a, R> {
+	/// Gets the project's direct sub-projects, in workspace order.
+	pub fn children(&self) -> Result<Vec<Project<'a, R>>> {
+		let mut children = Vec::new();
+		for project in self.workspace.project_handles()? {
+			if project.parent_slug()?.as_deref() == Some(self.slug()) {
+				children.push(project);
+			}
+		}
+
+		Ok(children)
+	}
+
+	/// Moves the project under a new parent, or to the root
+	/// of the hierarchy if `parent` is `None`.
+	///
+	/// Returns [`Error::NotFound`] if the parent doesn't exist, and
+	/// [`Error::ProjectCycle`] if the project is the parent itself
+	/// or one of its ancestors.
+	pub fn set_parent(&self, parent: Option<&str>) -> Result<R::Record> {
+		if let Some(parent) = parent {
+			let parent = self.workspace.project(parent)?;
+
+			let mut chain = vec![self.slug().to_string(), parent.slug().to_string()];
+			if parent.slug() != self.slug() {
+				for ancestor in parent.ancestors() {
+					let ancestor = ancestor?;
+					chain.push(ancestor.slug().to_string());
+					if ancestor.slug() == self.slug() {
+						break;
+					}
+				}
+			}
+
+			if chain.last().map(String::as_str) == Some(self.slug()) {
+				return Err(Error::ProjectCycle(chain.join(" -> ")));
+			}
+		}
+
+		self.workspace
+			.remote
+			.record_builder(&format!("{}/parent", self.meta_path))
+			.commit(parent.unwrap_or_default())
+	}
+
+	/// Iterates over the project's ancestors, from its parent up to the
+	/// root of the hierarchy. Stops at projects whose parent no longer
+	/// exists.
+	///
+	/// Yields [`Error::ProjectCycle`] (and then stops) if the chain of
+	/// parents loops, or is deeper than [`MAX_PROJECT_DEPTH`].
+	pub fn ancestors(&self) -> Ancestors<'a, R> {
+		Ancestors {
+			current: Some(self.workspace.project_handle(self.slug().to_string())),
+			seen: vec![self.slug().to_string()],
+		}
+	}
+
+	/// Gets the slug of the project's parent, without checking
+	/// that the parent still exists.
+	pub(crate) fn parent_slug(&self) -> Result<Option<String>> {
+		Ok(self
+			.workspace
+			.remote
+			.latest(&format!("{}/parent", self.meta_path))?
+			.map(|record| record.message())
+			// An empty message marks a project moved to the root.
+			.filter(|slug| !slug.is_empty()))
+	}
+}
+
+/// An iterator over a project's ancestors. See [`Project::ancestors`].
+pub struct Ancestors<'a, R: Remote<'a>> {
+	current: Option<Project<'a, R>>,
+	seen: Vec<String>,
+}
+
+impl<'a, R: Remote<'a>> Iterator for Ancestors<'a, R> {
+	type Item = Result<Project<'a, R>>;
+
+	fn next(&mut self) -> Option<Self::Item> {
+		let current = self.current.take()?;
+
+		let parent = match current.parent_slug() {
+			Ok(Some(parent)) => parent,
+			Ok(None) => return None,
+			Err(err) => return Some(Err(err)),
+		};
+
+		let parent = match current.workspace.project(&parent) {
+			Ok(parent) => parent,
+			Err(Error::NotFound(_, _)) => return None,
+			Err(err) => return Some(Err(err)),
+		};
+
+		let looped = self.seen.iter().any(|slug| slug == parent.slug());
+		self.seen.push(parent.slug().to_string());
+		if looped || self.seen.len() > MAX_PROJECT_DEPTH + 1 {
+			return Some(Err(Error::ProjectCycle(self.seen.join(" -> "))));
+		}
+
+		self.current = Some(current.workspace.project_handle(parent.slug().to_string()));
+		Some(Ok(parent))
+	}
+}
@@ -0,0 +1,95 @@
+//! Workspace user directory.
+//!
+//! Users are stored in the `meta/users` set, with items of the form
+//! `email:name`. The email address is the canonical identity of a user
+//! (it's what records carry as their author email); the name is what
+//! should be displayed for them.
+
+use crate::{Error, Record, Remote, Result, Workspace};
+
+const USERS_COLLECTION: &str = "meta/users";
+
+/// A user registered in the workspace.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, serde::Serialize)]
+pub struct User {
+	/// The user's email address.
+	pub email: String,
+	/// The user's display name.
+	pub name: String,
+}
+
+impl<'a, R: Remote<'a>> Workspace<'a, R> {
+	/// Lists the users registered in the workspace, in order of registration.
+	pub fn users(&'a self) -> Result<Vec<User>> {
+		self.remote
+			.set_get_all(USERS_COLLECTION)?
+			.into_iter()
+			.map(|record| {
+				let message = record.message();
+				let (email, name) = message
+					.split_once(':')
+					.ok_or_else(|| Error::Malformed(USERS_COLLECTION.to_string()))?;
+				Ok(User {
+					email: email.to_string(),
+					name: name.to_string(),
+				})
+			})
+			.collect()
+	}
+
+	/// Gets a user by email address, or `None` if they aren't registered.
+	pub fn user(&'a self, email: &str) -> Result<Option<User>> {
+		Ok(self.users()?.into_iter().find(|user| user.email == email))
+	}
+
+	/// Registers a user, updating their name if they're already
+	/// registered. Returns [`Error::Exists`] if the user is already
+	/// registered with the same name.
+	///
+	/// Email addresses cannot be empty or contain `:` or whitespace
+	/// characters, and names cannot be empty or contain newlines.
+	pub fn add_user(&'a self, name: &str, email: &str) -> Result<R::Record> {
+		if email.is_empty() || email.contains(':') || email.contains(char::is_whitespace) {
+			return Err(Error::Malformed(email.to_string()));
+		}
+		if name.trim().is_empty() || name.contains('\n') {
+			return Err(Error::Malformed(name.to_string()));
+		}
+
+		let item = format!("{}:{}", email, name);
+
+		if let Some(existing) = self.user(email)? {
+			if existing.name == name {
+				return Err(Error::Exists(USERS_COLLECTION.to_string(), item));
+			}
+
+			self.remote.set_del_unchecked(
+				USERS_COLLECTION,
+				&format!("{}:{}", existing.email, existing.name),
+			)?;
+		}
+
+		self.remote.set_add_unchecked(USERS_COLLECTION, &item)
+	}
+
+	/// Removes a user from the directory, returning the removal
+	/// record, or `None` if the user wasn't registered.
+	pub fn remove_user(&'a self, email: &str) -> Result<Option<R::Record>> {
+		match self.user(email)? {
+			Some(user) => Ok(Some(self.remote.set_del_unchecked(
+				USERS_COLLECTION,
+				&format!("{}:{}", user.email, user.name),
+			)?)),
+			None => Ok(None),
+		}
+	}
+
+	/// Gets the name to display for the author of a record: the name
+	/// registered for the author's email address, falling back to the
+	/// author name stored in the record itself.
+	pub fn display_name(&'a self, record: &R::Record) -> Result<String> {
+		Ok(self
+			.user(&record.email())?
+			.map_or_else(|| record.author(), |user| user.name))
+	}
+}
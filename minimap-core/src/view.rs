@@ -0,0 +1,195 @@
+//! Materialized ticket snapshots.
+//!
+//! Rendering a ticket otherwise takes a separate walk per piece of
+//! data (title, state, comments, ...). [`Ticket::view`] walks each of
+//! the ticket's collections once and returns a plain, serializable
+//! [`TicketView`] holding everything a client needs to display it.
+
+use crate::{DependencyKind, Error, Record, Remote, Result, Ticket, TicketState};
+use serde::Serialize;
+use std::collections::BTreeMap;
+
+/// A plain, serializable copy of a record.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct RecordView {
+	/// The record's ID.
+	pub id: String,
+	/// The name of the record's author.
+	pub author: String,
+	/// The email address of the record's author.
+	pub email: String,
+	/// The record's message.
+	pub message: String,
+	/// The unix timestamp of the record, in seconds.
+	pub timestamp: i64,
+}
+
+impl<R: Record> From<R> for RecordView {
+	fn from(record: R) -> Self {
+		Self {
+			id: record.id(),
+			author: record.author(),
+			email: record.email(),
+			message: record.message(),
+			timestamp: record.timestamp(),
+		}
+	}
+}
+
+/// A comment on a ticket.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct CommentView {
+	/// The comment's record.
+	#[serde(flatten)]
+	pub record: RecordView,
+	/// The names of the files attached to the comment.
+	/// See [`Ticket::add_comment_with_attachments`].
+	pub attachments: Vec<String>,
+}
+
+/// A dependency of a ticket. See [`Ticket::add_dependency`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct DependencyView {
+	/// The dependency's origin.
+	pub origin: String,
+	/// The dependency's endpoint.
+	pub endpoint: String,
+	/// Whether the dependency blocks the ticket.
+	pub kind: DependencyKind,
+	/// The dependency's note, if any.
+	pub note: Option<String>,
+}
+
+/// A snapshot of a ticket. See the [module documentation](self) for details.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct TicketView {
+	/// The ticket's slug.
+	pub slug: String,
+	/// The ticket's ID.
+	pub id: u64,
+	/// The ticket's title, if set.
+	pub title: Option<String>,
+	/// The ticket's description, if set.
+	pub description: Option<String>,
+	/// The ticket's state.
+	pub state: TicketState,
+	/// The reason given for the ticket's latest state change, if any.
+	pub state_reason: Option<String>,
+	/// The ticket's labels.
+	pub labels: Vec<String>,
+	/// The users assigned to the ticket.
+	pub assignees: Vec<String>,
+	/// The users that voted for the ticket.
+	pub votes: Vec<String>,
+	/// The comments on the ticket (excluding deleted ones),
+	/// from latest to oldest.
+	pub comments: Vec<CommentView>,
+	/// The names of the ticket's current attachments.
+	pub attachments: Vec<String>,
+	/// The ticket's dependencies.
+	pub dependencies: Vec<DependencyView>,
+	/// The slug of the ticket's parent, if it's a subtask.
+	pub parent: Option<String>,
+	/// The slugs of the ticket's subtasks.
+	pub subtasks: Vec<String>,
+	/// The slug of the ticket this ticket is a duplicate of, if any.
+	pub duplicate_of: Option<String>,
+	/// The ticket's estimate in minutes, if set.
+	pub estimate: Option<u64>,
+	/// The total number of minutes logged on the ticket.
+	pub time_spent: u64,
+	/// The (raw) values of the ticket's custom fields, by field name.
+	pub fields: BTreeMap<String, String>,
+}
+
+impl<'a, R: Remote<'a>> Ticket<'a, R> {
+	/// Builds a snapshot of the ticket.
+	pub fn view(&self) -> Result<TicketView> {
+		let remote = &self.workspace.remote;
+		let latest_message = |name: &str| -> Result<Option<String>> {
+			Ok(remote
+				.latest(&format!("{}/{}", self.path, name))?
+				.map(|record| record.message()))
+		};
+
+		let tombstones = self.tombstones()?;
+		let comments = self
+			.comments_log()?
+			.filter(|record| {
+				record
+					.as_ref()
+					.map_or(true, |record| !tombstones.contains_key(&record.id()))
+			})
+			.map(|record| {
+				let record = record?;
+				Ok(CommentView {
+					attachments: self.comment_attachments(&record)?,
+					record: record.into(),
+				})
+			})
+			.collect::<Result<Vec<_>>>()?;
+
+		// Replay the attachment log from oldest to latest.
+		let attachment_path = format!("{}/attachment", self.path);
+		let mut attachments = Vec::<String>::new();
+		let mut log = remote.walk(&attachment_path)?.collect::<Result<Vec<_>>>()?;
+		log.reverse();
+		for record in log {
+			let message = record.message();
+			if let Some(name) = message.strip_prefix('+') {
+				if !attachments.iter().any(|a| a == name) {
+					attachments.push(name.to_string());
+				}
+			} else if let Some(name) = message.strip_prefix('-') {
+				attachments.retain(|a| a != name);
+			} else {
+				return Err(Error::Malformed(attachment_path));
+			}
+		}
+
+		let dependencies = self
+			.dependencies()?
+			.into_iter()
+			.map(|dependency| DependencyView {
+				origin: dependency.origin,
+				endpoint: dependency.endpoint,
+				kind: dependency.kind,
+				note: dependency.note,
+			})
+			.collect();
+
+		let subtasks = remote
+			.set_get_all(&format!("{}/subtasks", self.path))?
+			.into_iter()
+			.map(|record| record.message())
+			.collect();
+
+		let mut fields = BTreeMap::new();
+		for name in self.project()?.fields()?.keys() {
+			if let Some(value) = latest_message(&format!("field/{}", name))? {
+				fields.insert(name.clone(), value);
+			}
+		}
+
+		Ok(TicketView {
+			slug: self.slug.clone(),
+			id: self.id,
+			title: latest_message("title")?,
+			description: latest_message("description")?,
+			state: self.state()?.0,
+			state_reason: self.state_reason()?,
+			labels: self.labels()?,
+			assignees: self.assignees()?,
+			votes: self.votes()?,
+			comments,
+			attachments,
+			dependencies,
+			parent: latest_message("parent")?,
+			subtasks,
+			duplicate_of: latest_message("duplicate_of")?,
+			estimate: self.estimate()?.map(|(minutes, _)| minutes),
+			time_spent: self.total_time_spent()?,
+			fields,
+		})
+	}
+}
@@ -0,0 +1,51 @@
+//! Ticket voting.
+//!
+//! Votes are stored in each ticket's `votes` set, keyed by the voter's
+//! email address, so that every user can vote for a ticket at most once.
+//! Queries can be sorted by vote count with
+//! [`TicketQuery::sort`](crate::TicketQuery::sort).
+
+use crate::{Record, Remote, Result, Ticket};
+
+impl<'a, R: Remote<'a>> Ticket<'a, R> {
+	/// Votes for the ticket on behalf of the given user.
+	///
+	/// Returns the record of the vote if created,
+	/// or the record of the existing vote if the user already voted.
+	pub fn vote(&self, email: &str) -> Result<R::Record> {
+		self.workspace
+			.remote
+			.set_add(&self.votes_path(), email)?
+			.map_or_else(Ok, |(r, _)| Ok(r))
+	}
+
+	/// Withdraws a user's vote for the ticket.
+	///
+	/// Returns the record of the withdrawal if created,
+	/// or None if the user hadn't voted.
+	pub fn unvote(&self, email: &str) -> Result<Option<R::Record>> {
+		self.workspace
+			.remote
+			.set_del(&self.votes_path(), email)?
+			.map_or_else(|_| Ok(None), |(r, _)| Ok(Some(r)))
+	}
+
+	/// Lists the email addresses of all users that voted for the ticket.
+	pub fn votes(&self) -> Result<Vec<String>> {
+		self.workspace
+			.remote
+			.walk_set_present(&self.votes_path())?
+			.map(|r| Ok(r?.message()))
+			.collect()
+	}
+
+	/// Gets the number of votes for the ticket.
+	#[inline]
+	pub fn vote_count(&self) -> Result<usize> {
+		Ok(self.votes()?.len())
+	}
+
+	fn votes_path(&self) -> String {
+		format!("{}/votes", self.path)
+	}
+}
@@ -0,0 +1,228 @@
+//! Ticket state transition guards.
+//!
+//! A [`Workflow`] declares which state transitions are allowed and which
+//! conditions must hold for a ticket to enter a given state. It's
+//! configured per workspace via
+//! [`Workspace::with_workflow`](crate::Workspace::with_workflow), and
+//! can be overridden per project with [`Project::set_workflow`].
+//! It's enforced by [`Ticket::set_state`]; violating it results in an
+//! [`Error::TransitionDenied`].
+//!
+//! The default workflow allows every transition unconditionally.
+
+use crate::{
+	Dependency, DependencyKind, Error, Project, Record, RecordBuilder, Remote, Result, Ticket,
+	TicketState,
+};
+use std::collections::HashSet;
+
+/// A condition that must hold for a ticket to transition into a state.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum TransitionCondition {
+	/// None of the ticket's blocking dependencies on other tickets in
+	/// the workspace (i.e. the `_` origin) may be open. Informational
+	/// dependencies, and dependencies on tickets that no longer exist,
+	/// don't count.
+	NoOpenDependencies,
+}
+
+impl TryFrom<&str> for TransitionCondition {
+	type Error = Error;
+
+	fn try_from(value: &str) -> Result<Self> {
+		match value {
+			"no-open-dependencies" => Ok(Self::NoOpenDependencies),
+			_ => Err(Error::Malformed(value.to_string())),
+		}
+	}
+}
+
+impl ToString for TransitionCondition {
+	fn to_string(&self) -> String {
+		match self {
+			Self::NoOpenDependencies => "no-open-dependencies".to_string(),
+		}
+	}
+}
+
+/// The set of allowed state transitions and their conditions.
+/// See the [module documentation](self) for details.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Workflow {
+	allowed: Option<HashSet<(TicketState, TicketState)>>,
+	conditions: Vec<(TicketState, TransitionCondition)>,
+}
+
+impl Workflow {
+	/// Creates a workflow that allows every transition unconditionally.
+	#[inline]
+	pub fn new() -> Self {
+		Self::default()
+	}
+
+	/// Allows transitioning from one state to another. Once any
+	/// transition has been allowed explicitly, all transitions that
+	/// haven't been are denied.
+	///
+	/// Transitioning a ticket to the state it's already in is always allowed.
+	pub fn allow(mut self, from: TicketState, to: TicketState) -> Self {
+		self.allowed
+			.get_or_insert_with(HashSet::new)
+			.insert((from, to));
+		self
+	}
+
+	/// Requires a condition to hold for a ticket to transition into the given state.
+	pub fn require(mut self, to: TicketState, condition: TransitionCondition) -> Self {
+		self.conditions.push((to, condition));
+		self
+	}
+
+	/// Returns if the workflow allows transitioning from one state to
+	/// another, without taking conditions into account.
+	pub fn allows(&self, from: TicketState, to: TicketState) -> bool {
+		from == to
+			|| self
+				.allowed
+				.as_ref()
+				.map_or(true, |allowed| allowed.contains(&(from, to)))
+	}
+
+	/// Checks whether the given ticket may transition into the given state,
+	/// returning [`Error::TransitionDenied`] describing why if not.
+	pub fn check<'a, R: Remote<'a>>(&self, ticket: &Ticket<'a, R>, to: TicketState) -> Result<()> {
+		let from = ticket.state()?.0;
+		if from == to {
+			return Ok(());
+		}
+
+		let denied = |reason: String| {
+			Err(Error::TransitionDenied(
+				ticket.slug().to_string(),
+				from.to_string(),
+				to.to_string(),
+				reason,
+			))
+		};
+
+		if !self.allows(from, to) {
+			return denied("transition is not allowed by the workflow".to_string());
+		}
+
+		for (state, condition) in &self.conditions {
+			if *state != to {
+				continue;
+			}
+
+			match condition {
+				TransitionCondition::NoOpenDependencies => {
+					let open = open_dependencies(ticket)?;
+					if !open.is_empty() {
+						return denied(format!("open dependencies: {}", open.join(", ")));
+					}
+				}
+			}
+		}
+
+		Ok(())
+	}
+}
+
+/// Workflows are stored as one rule per line, either
+/// `allow <from> <to>` or `require <to> <condition>`.
+impl TryFrom<&str> for Workflow {
+	type Error = Error;
+
+	fn try_from(value: &str) -> Result<Self> {
+		let mut workflow = Self::new();
+
+		for line in value.lines().filter(|line| !line.trim().is_empty()) {
+			let malformed = || Error::Malformed(line.to_string());
+			let parts = line.split_whitespace().collect::<Vec<_>>();
+			workflow = match parts.as_slice() {
+				["allow", from, to] => workflow.allow(
+					TicketState::try_from(*from).map_err(|_| malformed())?,
+					TicketState::try_from(*to).map_err(|_| malformed())?,
+				),
+				["require", to, condition] => workflow.require(
+					TicketState::try_from(*to).map_err(|_| malformed())?,
+					TransitionCondition::try_from(*condition).map_err(|_| malformed())?,
+				),
+				_ => return Err(malformed()),
+			};
+		}
+
+		Ok(workflow)
+	}
+}
+
+impl ToString for Workflow {
+	fn to_string(&self) -> String {
+		let mut allowed = self
+			.allowed
+			.iter()
+			.flatten()
+			.map(|(from, to)| format!("allow {} {}", from.to_string(), to.to_string()))
+			.collect::<Vec<_>>();
+		// Keep the output stable regardless of hash order.
+		allowed.sort();
+
+		allowed
+			.into_iter()
+			.chain(self.conditions.iter().map(|(to, condition)| {
+				format!("require {} {}", to.to_string(), condition.to_string())
+			}))
+			.collect::<Vec<_>>()
+			.join("\n")
+	}
+}
+
+impl<'a, R: Remote<'a>> Project<'a, R> {
+	/// Gets the project's own workflow, or `None` if the project
+	/// uses the workspace's workflow.
+	pub fn workflow(&self) -> Result<Option<Workflow>> {
+		self.workspace
+			.remote
+			.latest(&format!("{}/workflow", self.meta_path))?
+			.map(|record| Workflow::try_from(record.message().as_str()))
+			.transpose()
+	}
+
+	/// Sets the workflow for the project's tickets,
+	/// overriding the workspace's workflow.
+	pub fn set_workflow(&self, workflow: &Workflow) -> Result<R::Record> {
+		self.workspace
+			.remote
+			.record_builder(&format!("{}/workflow", self.meta_path))
+			.commit(&workflow.to_string())
+	}
+}
+
+/// Gets the slugs of all open tickets that block a ticket.
+fn open_dependencies<'a, R: Remote<'a>>(ticket: &Ticket<'a, R>) -> Result<Vec<String>> {
+	let mut open = Vec::new();
+
+	for Dependency {
+		origin,
+		endpoint,
+		kind,
+		..
+	} in ticket.dependencies()?
+	{
+		if origin != "_" || kind != DependencyKind::Blocking {
+			continue;
+		}
+
+		match ticket.workspace.ticket(&endpoint) {
+			Ok(dependency) => {
+				if dependency.is_open()? {
+					open.push(endpoint);
+				}
+			}
+			Err(Error::NotFound(_, _)) => {}
+			Err(err) => return Err(err),
+		}
+	}
+
+	Ok(open)
+}
@@ -0,0 +1,108 @@
+//! Time tracking on tickets.
+//!
+//! A ticket's estimate is the latest record in its `estimate` collection,
+//! holding a number of minutes. Work is logged to the ticket's `worklog`
+//! collection; each record's message holds the number of minutes spent,
+//! optionally followed by a blank line and a free-form note. The author
+//! of a worklog record is who performed the work.
+
+use crate::{Error, Record, RecordBuilder, Remote, Result, Ticket};
+use indexmap::IndexMap;
+
+/// A single worklog entry on a ticket.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WorklogEntry<T: Record> {
+	/// The number of minutes spent.
+	pub minutes: u64,
+	/// A note describing the work, if any.
+	pub note: Option<String>,
+	/// The underlying record.
+	pub record: T,
+}
+
+impl<'a, R: Remote<'a>> Ticket<'a, R> {
+	/// Gets the ticket's estimate in minutes, along with the record that
+	/// set it, or `None` if the ticket has never been estimated.
+	pub fn estimate(&self) -> Result<Option<(u64, R::Record)>> {
+		let path = format!("{}/estimate", self.path);
+
+		self.workspace
+			.remote
+			.latest(&path)?
+			.map(|record| {
+				let minutes = record
+					.message()
+					.parse::<u64>()
+					.map_err(|_| Error::Malformed(path.clone()))?;
+				Ok((minutes, record))
+			})
+			.transpose()
+	}
+
+	/// Sets the ticket's estimate, in minutes.
+	pub fn set_estimate(&self, minutes: u64) -> Result<R::Record> {
+		self.workspace
+			.remote
+			.record_builder(&format!("{}/estimate", self.path))
+			.commit(&minutes.to_string())
+	}
+
+	/// Logs time spent working on the ticket, with an optional note.
+	pub fn log_work(&self, minutes: u64, note: Option<&str>) -> Result<R::Record> {
+		let mut message = minutes.to_string();
+		if let Some(note) = note {
+			message.push_str("\n\n");
+			message.push_str(note);
+		}
+
+		self.workspace
+			.remote
+			.record_builder(&format!("{}/worklog", self.path))
+			.commit(&message)
+	}
+
+	/// Gets all worklog entries on the ticket, from latest to oldest.
+	pub fn worklog(&self) -> Result<Vec<WorklogEntry<R::Record>>> {
+		let path = format!("{}/worklog", self.path);
+
+		self.workspace
+			.remote
+			.walk(&path)?
+			.map(|record| {
+				let record = record?;
+				let message = record.message();
+				let (minutes, note) = match message.split_once("\n\n") {
+					Some((minutes, note)) => (minutes, Some(note.to_string())),
+					None => (message.as_str(), None),
+				};
+				let minutes = minutes
+					.parse::<u64>()
+					.map_err(|_| Error::Malformed(path.clone()))?;
+
+				Ok(WorklogEntry {
+					minutes,
+					note,
+					record,
+				})
+			})
+			.collect()
+	}
+
+	/// Gets the total number of minutes logged on the ticket.
+	pub fn total_time_spent(&self) -> Result<u64> {
+		Ok(self.worklog()?.iter().map(|entry| entry.minutes).sum())
+	}
+
+	/// Gets the total number of minutes logged on the ticket per author,
+	/// keyed by the authors' email addresses, in order of each author's
+	/// first worklog entry.
+	pub fn time_spent_by_author(&self) -> Result<IndexMap<String, u64>> {
+		let mut totals = IndexMap::new();
+
+		for entry in self.worklog()?.into_iter().rev() {
+			*totals.entry(entry.record.email()).or_insert(0) += entry.minutes;
+		}
+
+		Ok(totals)
+	}
+}